@@ -0,0 +1,166 @@
+/*! Aliases of sequence names.
+
+Annotations and genomes from different sources often name the same sequences differently, for
+example `1` and `MT` in Ensembl and `chr1` and `chrM` in UCSC. An alias table maps such names to
+the names used by the rest of an analysis. It can be set on the GTF, GFF, and refFlat readers to
+rename sequences as records are read, or applied to a whole annotation set with
+`AnnotationSet::rename_seqs`.
+
+Writers do not rename sequences. To write an annotation set with other sequence names, for
+example back into the names of its source, call `rename_seqs` with the appropriate table (e.g.
+one created with `inverse`) before writing it.
+
+Alias tables are read from tab-separated files whose first column contains an alias and whose
+second column contains the name it maps to. Further columns are ignored, so the `chromAlias.txt`
+files of UCSC, which also map GenBank and RefSeq accessions, can be used as they are.
+*/
+use std::error::Error;
+use std::fs;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+use linked_hash_map::LinkedHashMap;
+
+use AnnotationSet;
+
+
+quick_error! {
+    /// Errors that occur when reading sequence name aliases.
+    #[derive(Debug)]
+    pub enum AliasError {
+        /// Occurs when a line does not contain both an alias and a sequence name.
+        InvalidRecord(line_num: usize) {
+            description("invalid sequence name alias record")
+            display(self_) -> ("{}, line: {}", self_.description(), line_num)
+        }
+        /// Errors propagated from reading the underlying file.
+        Io(err: io::Error) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// Table mapping sequence name aliases to sequence names.
+#[derive(Debug, Clone, Default)]
+pub struct SeqNameAliases {
+    names: LinkedHashMap<String, String>,
+}
+
+impl SeqNameAliases {
+
+    /// Creates an empty alias table.
+    pub fn new() -> Self {
+        SeqNameAliases { names: LinkedHashMap::new() }
+    }
+
+    /// Creates an alias table mapping the Ensembl names of the human nuclear chromosomes and
+    /// the mitochondrial genome to their UCSC names.
+    ///
+    /// `1` to `22`, `X`, and `Y` are mapped to `chr1` to `chr22`, `chrX`, and `chrY`, while
+    /// `MT` is mapped to `chrM`. Use `inverse` for the opposite direction.
+    pub fn ensembl_to_ucsc() -> Self {
+        let mut aliases = SeqNameAliases::new();
+        for num in 1..23 {
+            aliases.add(num.to_string(), format!("chr{}", num));
+        }
+        aliases.add("X", "chrX");
+        aliases.add("Y", "chrY");
+        aliases.add("MT", "chrM");
+        aliases
+    }
+
+    /// Creates an alias table from the given tab-separated reader.
+    ///
+    /// Empty lines and lines starting with `#` are ignored. When an alias is defined more than
+    /// once, its last definition is used.
+    pub fn from_reader<R: io::Read>(in_reader: R) -> ::Result<SeqNameAliases> {
+        let mut aliases = SeqNameAliases::new();
+        for (idx, line) in BufReader::new(in_reader).lines().enumerate() {
+            let line = line.map_err(|e| ::Error::from(AliasError::from(e)))?;
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split('\t').map(|field| field.trim());
+            match (fields.next(), fields.next()) {
+                (Some(alias), Some(name)) if !alias.is_empty() && !name.is_empty() =>
+                    aliases.add(alias, name),
+                _ => return Err(::Error::from(AliasError::InvalidRecord(idx + 1))),
+            }
+        }
+        Ok(aliases)
+    }
+
+    /// Creates an alias table from the tab-separated file in the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> ::Result<SeqNameAliases> {
+        let file = fs::File::open(path)
+            .map_err(|e| ::Error::from(AliasError::from(e)))?;
+        SeqNameAliases::from_reader(file)
+    }
+
+    /// Maps the given alias to the given sequence name, replacing any previous mapping of the
+    /// alias.
+    pub fn add<T, U>(&mut self, alias: T, seq_name: U)
+        where T: Into<String>, U: Into<String>
+    {
+        let _ = self.names.insert(alias.into(), seq_name.into());
+    }
+
+    /// Returns the sequence name the given alias maps to, if any.
+    pub fn get(&self, alias: &str) -> Option<&str> {
+        self.names.get(alias).map(|name| name.as_str())
+    }
+
+    /// Returns the sequence name the given name maps to, or the given name if it is not an
+    /// alias.
+    pub fn resolve<'a>(&'a self, seq_name: &'a str) -> &'a str {
+        self.get(seq_name).unwrap_or(seq_name)
+    }
+
+    /// Creates the alias table of the opposite direction.
+    ///
+    /// When more than one alias maps to the same sequence name, the sequence name is mapped
+    /// to the first of these aliases that was added.
+    pub fn inverse(&self) -> SeqNameAliases {
+        let mut inverse = SeqNameAliases::new();
+        for (alias, name) in self.names.iter() {
+            if !inverse.names.contains_key(name) {
+                inverse.add(name.as_str(), alias.as_str());
+            }
+        }
+        inverse
+    }
+
+    /// Returns the number of aliases in the table.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Returns whether the table contains no aliases.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Renames the given sequence name in place if it is an alias.
+    pub(crate) fn update_seq_name(&self, seq_name: &mut String) {
+        if let Some(name) = self.names.get(seq_name.as_str()) {
+            seq_name.clone_from(name);
+        }
+    }
+}
+
+impl AnnotationSet {
+
+    /// Renames the sequences of all genes, transcripts, and exons whose sequence names are
+    /// aliases in the given table.
+    pub fn rename_seqs(&mut self, aliases: &SeqNameAliases) {
+        for gene in self.genes_mut() {
+            let name = aliases.get(gene.seq_name()).map(|name| name.to_owned());
+            if let Some(name) = name {
+                gene.rename_seq(name);
+            }
+        }
+    }
+}
@@ -0,0 +1,731 @@
+/*! Collections of gene models.
+
+*/
+use std::cmp::{max, Ordering};
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::iter::{FromIterator, Peekable};
+use std::slice;
+
+use linked_hash_map;
+
+use {expand_window, AnnotationStats, Coord, ExonFeatureKind, Gene, Strand, Transcript};
+use sort::ChromOrder;
+
+
+/// Prefix of generated locus identifiers.
+const LOCUS_ID_PREFIX: &'static str = "XLOC_";
+
+quick_error! {
+    /// Errors that occur when working with collections of gene models.
+    #[derive(Debug)]
+    pub enum AnnotationError {
+        /// Occurs when genes expected to be sorted by sequence name and start coordinate are
+        /// not.
+        UnsortedGenes(seq_name: String, start: u64) {
+            description("genes are not sorted by sequence name and start coordinate")
+            display(self_) -> ("{}, gene at: {}:{}", self_.description(), seq_name, start)
+        }
+    }
+}
+
+
+/// Default size of the promoter and transcription termination site windows, in bases.
+const DEF_REGION_WINDOW: u64 = 3000;
+
+/// Label of a genomic region relative to the transcripts of an annotation set.
+///
+/// The labels are ordered by their priority, as commonly used for annotating ChIP-seq and
+/// ATAC-seq peaks: a region overlapping both a promoter and an intron is usually reported as a
+/// promoter region.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum RegionLabel {
+    /// Region around a transcription start site.
+    Promoter,
+    /// 5'UTR of a coding transcript.
+    UTR5,
+    /// 3'UTR of a coding transcript.
+    UTR3,
+    /// Coding part of an exon, including start and stop codons.
+    CodingExon,
+    /// Exon of a noncoding transcript, or a UTR whose side can not be determined.
+    OtherExon,
+    /// Intron of a transcript.
+    Intron,
+    /// Region downstream of a transcription termination site.
+    TTS,
+    /// Region that does not have any of the other labels.
+    DistalIntergenic,
+}
+
+/// Window sizes used for labeling regions around transcription start and termination sites.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegionWindows {
+    promoter_upstream: u64,
+    promoter_downstream: u64,
+    tts_downstream: u64,
+}
+
+impl Default for RegionWindows {
+    fn default() -> Self {
+        RegionWindows::new(DEF_REGION_WINDOW, DEF_REGION_WINDOW, DEF_REGION_WINDOW)
+    }
+}
+
+impl RegionWindows {
+
+    /// Creates windows with the given number of bases upstream and downstream of transcription
+    /// start sites, and downstream of transcription termination sites.
+    pub fn new(promoter_upstream: u64, promoter_downstream: u64, tts_downstream: u64) -> Self {
+        RegionWindows {
+            promoter_upstream: promoter_upstream,
+            promoter_downstream: promoter_downstream,
+            tts_downstream: tts_downstream,
+        }
+    }
+
+    /// Returns the number of bases upstream of transcription start sites labeled as promoters.
+    pub fn promoter_upstream(&self) -> u64 {
+        self.promoter_upstream
+    }
+
+    /// Returns the number of bases downstream of transcription start sites labeled as
+    /// promoters.
+    pub fn promoter_downstream(&self) -> u64 {
+        self.promoter_downstream
+    }
+
+    /// Returns the number of bases downstream of transcription termination sites labeled as
+    /// such.
+    pub fn tts_downstream(&self) -> u64 {
+        self.tts_downstream
+    }
+}
+
+/// Collection of genes from a single annotation source.
+///
+/// The genes are stored in the order they are added.
+#[derive(Debug, Clone, Default)]
+pub struct AnnotationSet {
+    genes: Vec<Gene>,
+}
+
+impl AnnotationSet {
+
+    /// Creates an empty annotation set.
+    pub fn new() -> Self {
+        AnnotationSet { genes: Vec::new() }
+    }
+
+    /// Creates an annotation set from the given genes.
+    pub fn from_genes<I>(genes: I) -> Self
+        where I: IntoIterator<Item=Gene>
+    {
+        AnnotationSet { genes: genes.into_iter().collect() }
+    }
+
+    /// Creates an annotation set from the given gene results, returning the first error found.
+    pub fn try_from_results<I>(results: I) -> ::Result<Self>
+        where I: IntoIterator<Item=::Result<Gene>>
+    {
+        let mut genes = Vec::new();
+        for result in results {
+            genes.push(result?);
+        }
+        Ok(AnnotationSet::from_genes(genes))
+    }
+
+    /// Adds a gene to the annotation set.
+    pub fn add_gene(&mut self, gene: Gene) {
+        self.genes.push(gene);
+    }
+
+    /// Returns a slice of the genes in the annotation set.
+    pub fn genes(&self) -> &[Gene] {
+        self.genes.as_slice()
+    }
+
+    /// Returns a mutable slice of the genes in the annotation set.
+    pub fn genes_mut(&mut self) -> &mut [Gene] {
+        self.genes.as_mut_slice()
+    }
+
+    /// Consumes the annotation set and returns its genes.
+    pub fn take_genes(self) -> Vec<Gene> {
+        self.genes
+    }
+
+    /// Returns an iterator over all transcripts of all genes in the annotation set.
+    pub fn transcripts(&self) -> AnnotationTranscripts {
+        AnnotationTranscripts {
+            genes: self.genes.iter(),
+            transcripts: None,
+        }
+    }
+
+    /// Returns the number of genes in the annotation set.
+    pub fn len(&self) -> usize {
+        self.genes.len()
+    }
+
+    /// Returns whether the annotation set contains no genes.
+    pub fn is_empty(&self) -> bool {
+        self.genes.is_empty()
+    }
+
+    /// Returns the number of genes, transcripts, exons, and exon features in the set, along
+    /// with their approximate size in bytes.
+    ///
+    /// This visits every feature in the set, so it is meant for diagnostics rather than for
+    /// repeated use.
+    pub fn stats(&self) -> AnnotationStats {
+        AnnotationStats::from_genes(self.genes.iter())
+    }
+
+    /// Returns the exonic regions of the annotation set, per sequence name.
+    ///
+    /// Exons of all transcripts are merged regardless of their strands, so that each base
+    /// covered by any exon is contained in exactly one region. The regions of each sequence are
+    /// sorted by their coordinates. Adjacent exons are merged as well.
+    pub fn collapsed_exons(&self) -> HashMap<String, Vec<Coord<u64>>> {
+        let mut regions: HashMap<String, Vec<Coord<u64>>> = HashMap::new();
+        for trx in self.transcripts() {
+            let seq_regions = regions.entry(trx.seq_name().to_owned()).or_insert_with(Vec::new);
+            seq_regions.extend(trx.exons().iter().map(|exon| (exon.start(), exon.end())));
+        }
+        for seq_regions in regions.values_mut() {
+            seq_regions.sort();
+            let mut merged: Vec<Coord<u64>> = Vec::with_capacity(seq_regions.len());
+            for &(start, end) in seq_regions.iter() {
+                if let Some(last) = merged.last_mut() {
+                    if start <= last.1 {
+                        last.1 = max(last.1, end);
+                        continue;
+                    }
+                }
+                merged.push((start, end));
+            }
+            *seq_regions = merged;
+        }
+        regions
+    }
+
+    /// Returns the labels of the given zero-based, half-open region, using the default windows
+    /// of 3000 bases.
+    ///
+    /// See `classify_region_with` for details.
+    pub fn classify_region(&self, seq_name: &str, start: u64, end: u64) -> Vec<RegionLabel> {
+        self.classify_region_with(seq_name, start, end, &RegionWindows::default())
+    }
+
+    /// Returns the labels of the given zero-based, half-open region, using the given windows.
+    ///
+    /// All labels of the transcripts overlapping the region are returned, sorted by their
+    /// priority and without duplicates, so that the first label is the one usually reported.
+    /// Promoter and transcription termination site windows take the transcript strands into
+    /// account, with unknown strands treated as forward strands. `DistalIntergenic` is returned
+    /// only when no other label applies.
+    pub fn classify_region_with(&self, seq_name: &str, start: u64, end: u64,
+                                windows: &RegionWindows) -> Vec<RegionLabel>
+    {
+        let mut labels = Vec::new();
+        for trx in self.transcripts().filter(|trx| trx.seq_name() == seq_name) {
+            classify_transcript_region(trx, start, end, windows, &mut labels);
+        }
+        labels.sort();
+        labels.dedup();
+        if labels.is_empty() {
+            labels.push(RegionLabel::DistalIntergenic);
+        }
+        labels
+    }
+
+    /// Groups overlapping or nearby genes into loci.
+    ///
+    /// Genes on the same sequence are placed in the same locus when they overlap, or when the
+    /// gap between them is at most `max_gap` bases. If `stranded` is true, only genes on the
+    /// same strand are grouped together.
+    ///
+    /// The loci are sorted by their sequence names and coordinates, and are given identifiers
+    /// in that order (`XLOC_000001`, `XLOC_000002`, ...), so that the same annotation always
+    /// results in the same identifiers regardless of the order of its genes.
+    pub fn cluster_loci(&self, max_gap: u64, stranded: bool) -> Vec<Locus> {
+        let strand_key = |gene: &Gene| if stranded { strand_ord(gene.strand()) } else { 0 };
+        let mut idxs = (0..self.genes.len()).collect::<Vec<usize>>();
+        idxs.sort_by(|&a, &b| {
+            let (ga, gb) = (&self.genes[a], &self.genes[b]);
+            (ga.seq_name(), strand_key(ga), ga.start(), ga.end())
+                .cmp(&(gb.seq_name(), strand_key(gb), gb.start(), gb.end()))
+        });
+
+        let mut loci: Vec<Locus> = Vec::new();
+        for idx in idxs {
+            let gene = &self.genes[idx];
+            if let Some(locus) = loci.last_mut() {
+                let same_group = locus.seq_name == gene.seq_name()
+                    && (!stranded || &locus.strand == gene.strand());
+                if same_group && gene.start() <= locus.end.saturating_add(max_gap) {
+                    locus.end = max(locus.end, gene.end());
+                    if &locus.strand != gene.strand() {
+                        locus.strand = Strand::Unknown;
+                    }
+                    locus.gene_indices.push(idx);
+                    continue;
+                }
+            }
+            loci.push(Locus {
+                id: String::new(),
+                seq_name: gene.seq_name().to_owned(),
+                start: gene.start(),
+                end: gene.end(),
+                strand: *gene.strand(),
+                gene_indices: vec![idx],
+            });
+        }
+
+        loci.sort_by(|a, b| {
+            (a.seq_name.as_str(), a.start, a.end, strand_ord(&a.strand))
+                .cmp(&(b.seq_name.as_str(), b.start, b.end, strand_ord(&b.strand)))
+        });
+        for (num, locus) in loci.iter_mut().enumerate() {
+            locus.id = format!("{}{:06}", LOCUS_ID_PREFIX, num + 1);
+            locus.gene_indices.sort();
+        }
+        loci
+    }
+}
+
+/// Group of overlapping or nearby genes of an annotation set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Locus {
+    id: String,
+    seq_name: String,
+    start: u64,
+    end: u64,
+    strand: Strand,
+    gene_indices: Vec<usize>,
+}
+
+impl Locus {
+
+    /// Returns the locus identifier.
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    /// Returns the sequence name.
+    pub fn seq_name(&self) -> &str {
+        self.seq_name.as_str()
+    }
+
+    /// Returns the 5'-most coordinate of the genes in the locus.
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// Returns the 3'-most coordinate of the genes in the locus.
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// Returns the strand of the locus.
+    ///
+    /// This is `Strand::Unknown` when the locus contains genes on different strands.
+    pub fn strand(&self) -> &Strand {
+        &self.strand
+    }
+
+    /// Returns the positions of the genes of the locus in the annotation set, in ascending order.
+    pub fn gene_indices(&self) -> &[usize] {
+        self.gene_indices.as_slice()
+    }
+
+    /// Returns the genes of the locus from the annotation set it was created from.
+    pub fn genes<'a>(&self, annotation: &'a AnnotationSet) -> Vec<&'a Gene> {
+        self.gene_indices.iter()
+            .filter_map(|&idx| annotation.genes.get(idx))
+            .collect()
+    }
+}
+
+impl FromIterator<Gene> for AnnotationSet {
+    fn from_iter<I: IntoIterator<Item=Gene>>(iter: I) -> Self {
+        AnnotationSet::from_genes(iter)
+    }
+}
+
+/// Annotation of a genome indexed for overlap queries.
+///
+/// Genes and transcripts are indexed per sequence, sorted by their start coordinates along
+/// with the running maximum of their end coordinates. This allows overlap queries to only visit
+/// the features that start before the queried region ends and stop as soon as no earlier feature
+/// can reach the region.
+#[derive(Debug, Clone, Default)]
+pub struct GenomeAnnotation {
+    genes: Vec<Gene>,
+    gene_index: HashMap<String, IntervalIndex<usize>>,
+    transcript_index: HashMap<String, IntervalIndex<(usize, String)>>,
+}
+
+impl GenomeAnnotation {
+
+    /// Creates an indexed annotation from the given genes.
+    pub fn from_genes<I>(genes: I) -> Self
+        where I: IntoIterator<Item=Gene>
+    {
+        let genes = genes.into_iter().collect::<Vec<Gene>>();
+        let mut gene_index: HashMap<String, IntervalIndex<usize>> = HashMap::new();
+        let mut transcript_index: HashMap<String, IntervalIndex<(usize, String)>> =
+            HashMap::new();
+        for (idx, gene) in genes.iter().enumerate() {
+            gene_index.entry(gene.seq_name().to_owned()).or_insert_with(IntervalIndex::default)
+                .entries.push((gene.start(), gene.end(), idx));
+            for (tid, trx) in gene.transcripts() {
+                transcript_index.entry(trx.seq_name().to_owned())
+                    .or_insert_with(IntervalIndex::default)
+                    .entries.push((trx.start(), trx.end(), (idx, tid.clone())));
+            }
+        }
+        for index in gene_index.values_mut() {
+            index.finalize();
+        }
+        for index in transcript_index.values_mut() {
+            index.finalize();
+        }
+        GenomeAnnotation {
+            genes: genes,
+            gene_index: gene_index,
+            transcript_index: transcript_index,
+        }
+    }
+
+    /// Returns a slice of the indexed genes, in the order they were given.
+    pub fn genes(&self) -> &[Gene] {
+        self.genes.as_slice()
+    }
+
+    /// Returns the number of indexed genes.
+    pub fn len(&self) -> usize {
+        self.genes.len()
+    }
+
+    /// Returns whether no genes are indexed.
+    pub fn is_empty(&self) -> bool {
+        self.genes.is_empty()
+    }
+
+    /// Returns the genes overlapping the given zero-based, half-open region, sorted by their
+    /// start coordinates.
+    pub fn find_overlapping(&self, seq_name: &str, start: u64, end: u64) -> Vec<&Gene> {
+        match self.gene_index.get(seq_name) {
+            Some(index) => index.overlapping(start, end).into_iter()
+                .map(|&idx| &self.genes[idx])
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns the transcripts overlapping the given zero-based, half-open region, sorted by
+    /// their start coordinates.
+    pub fn find_overlapping_transcripts(&self, seq_name: &str, start: u64, end: u64)
+        -> Vec<&Transcript>
+    {
+        match self.transcript_index.get(seq_name) {
+            Some(index) => index.overlapping(start, end).into_iter()
+                .filter_map(|&(idx, ref tid)| self.genes[idx].transcripts().get(tid))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+impl From<AnnotationSet> for GenomeAnnotation {
+    fn from(annotation: AnnotationSet) -> GenomeAnnotation {
+        GenomeAnnotation::from_genes(annotation.take_genes())
+    }
+}
+
+impl FromIterator<Gene> for GenomeAnnotation {
+    fn from_iter<I: IntoIterator<Item=Gene>>(iter: I) -> Self {
+        GenomeAnnotation::from_genes(iter)
+    }
+}
+
+/// Intervals of a single sequence, sorted for overlap queries.
+#[derive(Debug, Clone)]
+struct IntervalIndex<T> {
+    entries: Vec<(u64, u64, T)>,
+    // running maximum of entry end coordinates, used to bound overlap queries
+    max_ends: Vec<u64>,
+}
+
+impl<T> Default for IntervalIndex<T> {
+    fn default() -> Self {
+        IntervalIndex { entries: Vec::new(), max_ends: Vec::new() }
+    }
+}
+
+impl<T> IntervalIndex<T> {
+
+    /// Sorts the entries and computes their running maximum end coordinates.
+    fn finalize(&mut self) {
+        self.entries.sort_by_key(|entry| (entry.0, entry.1));
+        let mut cur_max = 0;
+        self.max_ends = self.entries.iter()
+            .map(|entry| { cur_max = max(cur_max, entry.1); cur_max })
+            .collect();
+    }
+
+    /// Returns the values of all entries overlapping the given interval, sorted by their start
+    /// coordinates.
+    fn overlapping(&self, start: u64, end: u64) -> Vec<&T> {
+        let (mut lo, mut hi) = (0, self.entries.len());
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if self.entries[mid].0 < end {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        let mut res = Vec::new();
+        let mut idx = lo;
+        while idx > 0 {
+            idx -= 1;
+            if self.max_ends[idx] <= start {
+                break;
+            }
+            let entry = &self.entries[idx];
+            if entry.1 > start {
+                res.push(&entry.2);
+            }
+        }
+        res.reverse();
+        res
+    }
+}
+
+/// Adds the labels of the given region relative to the given transcript.
+pub(crate) fn classify_transcript_region(trx: &Transcript, start: u64, end: u64,
+                                         windows: &RegionWindows, labels: &mut Vec<RegionLabel>)
+{
+    let overlaps = |coord: Coord<u64>| coord.0 < end && start < coord.1;
+    let (tss, tts) = match trx.strand() {
+        &Strand::Reverse => (trx.end(), trx.start()),
+        _ => (trx.start(), trx.end()),
+    };
+    let promoter = expand_window(trx.seq_name(), (tss, tss), windows.promoter_upstream,
+                                 windows.promoter_downstream, trx.strand(), None);
+    let tts = expand_window(trx.seq_name(), (tts, tts), 0, windows.tts_downstream, trx.strand(),
+                            None);
+    if overlaps(promoter) {
+        labels.push(RegionLabel::Promoter);
+    }
+    if overlaps(tts) {
+        labels.push(RegionLabel::TTS);
+    }
+    if !overlaps((trx.start(), trx.end())) {
+        return;
+    }
+
+    for exon in trx.exons().iter().filter(|exon| overlaps((exon.start(), exon.end()))) {
+        if exon.features().is_empty() {
+            labels.push(RegionLabel::OtherExon);
+        }
+        for feature in exon.features().iter() {
+            if !overlaps((feature.start(), feature.end())) {
+                continue;
+            }
+            labels.push(match feature.kind() {
+                &ExonFeatureKind::UTR5 => RegionLabel::UTR5,
+                &ExonFeatureKind::UTR3 => RegionLabel::UTR3,
+                &ExonFeatureKind::UTR => RegionLabel::OtherExon,
+                _ => RegionLabel::CodingExon,
+            });
+        }
+    }
+    if trx.intron_coords().into_iter().any(|intron| overlaps(intron)) {
+        labels.push(RegionLabel::Intron);
+    }
+}
+
+/// Helper function to order strands.
+pub(crate) fn strand_ord(strand: &Strand) -> u8 {
+    match strand {
+        &Strand::Forward => 0,
+        &Strand::Reverse => 1,
+        &Strand::Unknown => 2,
+    }
+}
+
+/// Iterator over the transcripts of an annotation set.
+pub struct AnnotationTranscripts<'a> {
+    genes: slice::Iter<'a, Gene>,
+    transcripts: Option<linked_hash_map::Values<'a, String, Transcript>>,
+}
+
+impl<'a> Iterator for AnnotationTranscripts<'a> {
+
+    type Item = &'a Transcript;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(trx) = self.transcripts.as_mut().and_then(|trxs| trxs.next()) {
+                return Some(trx);
+            }
+            match self.genes.next() {
+                Some(gene) => self.transcripts = Some(gene.transcripts().values()),
+                None => return None,
+            }
+        }
+    }
+}
+
+/// Creates an iterator over the pairs of overlapping genes from two sources.
+///
+/// Both sources must yield genes sorted by sequence name, in the default `ChromOrder`, and then
+/// start coordinate, e.g. as sorted by `sort::by_position` or written by a coordinate-sorting
+/// writer; use `overlaps_with` for other orders. The sources are then swept together, keeping
+/// only the genes that may still overlap upcoming genes in memory, so that two whole annotation
+/// releases can be compared without indexing either one of them.
+///
+/// Genes overlap when their zero-based, half-open intervals share at least one base, regardless
+/// of their strands. Each pair is yielded once, as `(gene_from_a, gene_from_b)`, ordered by the
+/// start coordinate of the later of the two genes. Since a gene may overlap more than one gene,
+/// the yielded genes are clones.
+///
+/// An error is yielded, and iteration stops, when either source is found to be unsorted.
+pub fn overlaps<A, B>(a: A, b: B) -> Overlaps<A::IntoIter, B::IntoIter>
+    where A: IntoIterator<Item=Gene>, B: IntoIterator<Item=Gene>
+{
+    overlaps_with(a, b, ChromOrder::default())
+}
+
+/// Creates an iterator over the pairs of overlapping genes from two sources whose sequence
+/// names are sorted in the given order.
+///
+/// This is otherwise the same as [`overlaps`].
+///
+/// [`overlaps`]: fn.overlaps.html
+pub fn overlaps_with<A, B>(a: A, b: B, order: ChromOrder) -> Overlaps<A::IntoIter, B::IntoIter>
+    where A: IntoIterator<Item=Gene>, B: IntoIterator<Item=Gene>
+{
+    Overlaps {
+        a: OverlapSide::new(a.into_iter()),
+        b: OverlapSide::new(b.into_iter()),
+        pairs: VecDeque::new(),
+        order: order,
+        done: false,
+    }
+}
+
+/// Iterator over the pairs of overlapping genes from two sorted sources.
+///
+/// This is created using the [`overlaps`] function.
+///
+/// [`overlaps`]: fn.overlaps.html
+pub struct Overlaps<A, B>
+    where A: Iterator<Item=Gene>, B: Iterator<Item=Gene>
+{
+    a: OverlapSide<A>,
+    b: OverlapSide<B>,
+    pairs: VecDeque<(Gene, Gene)>,
+    order: ChromOrder,
+    done: bool,
+}
+
+impl<A, B> Iterator for Overlaps<A, B>
+    where A: Iterator<Item=Gene>, B: Iterator<Item=Gene>
+{
+    type Item = ::Result<(Gene, Gene)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pairs.is_empty() {
+            if self.done {
+                return None;
+            }
+            let take_a = match (self.a.genes.peek(), self.b.genes.peek()) {
+                (None, None) => {
+                    self.done = true;
+                    return None;
+                },
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (Some(ga), Some(gb)) =>
+                    cmp_starts((ga.seq_name(), ga.start()), (gb.seq_name(), gb.start()),
+                               self.order) != Ordering::Greater,
+            };
+            let order = self.order;
+            let res = if take_a {
+                self.a.advance(&mut self.b, order).map(|(gene, hits)| {
+                    self.pairs.extend(hits.into_iter().map(|hit| (gene.clone(), hit)));
+                })
+            } else {
+                self.b.advance(&mut self.a, order).map(|(gene, hits)| {
+                    self.pairs.extend(hits.into_iter().map(|hit| (hit, gene.clone())));
+                })
+            };
+            if let Err(e) = res {
+                self.done = true;
+                return Some(Err(e));
+            }
+        }
+        self.pairs.pop_front().map(Ok)
+    }
+}
+
+/// One of the two sources of an overlap sweep.
+struct OverlapSide<I: Iterator<Item=Gene>> {
+    genes: Peekable<I>,
+    // genes from this source that may still overlap genes from the other source
+    active: Vec<Gene>,
+    last: Option<(String, u64)>,
+}
+
+impl<I: Iterator<Item=Gene>> OverlapSide<I> {
+
+    fn new(genes: I) -> Self {
+        OverlapSide { genes: genes.peekable(), active: Vec::new(), last: None }
+    }
+
+    /// Takes the next gene of this source, returning it along with the overlapping active genes
+    /// of the other source.
+    ///
+    /// The gene must be the next gene of the sweep, i.e. not start after the next gene of the
+    /// other source.
+    fn advance<J>(&mut self, other: &mut OverlapSide<J>, order: ChromOrder)
+        -> ::Result<(Gene, Vec<Gene>)>
+        where J: Iterator<Item=Gene>
+    {
+        let gene = self.genes.next().expect("a gene is peeked before advancing");
+        let unsorted = match self.last {
+            Some((ref seq_name, start)) =>
+                cmp_starts((seq_name, start), (gene.seq_name(), gene.start()), order)
+                    == Ordering::Greater,
+            None => false,
+        };
+        if unsorted {
+            return Err(::Error::from(
+                AnnotationError::UnsortedGenes(gene.seq_name().to_owned(), gene.start())));
+        }
+        self.last = Some((gene.seq_name().to_owned(), gene.start()));
+
+        // Active genes that end before this gene can not overlap any later gene either, since
+        // those start at or after this gene.
+        prune_active(&mut self.active, &gene);
+        prune_active(&mut other.active, &gene);
+        let hits = other.active.clone();
+        self.active.push(gene.clone());
+        Ok((gene, hits))
+    }
+}
+
+/// Compares two pairs of sequence names and start coordinates.
+fn cmp_starts(a: (&str, u64), b: (&str, u64), order: ChromOrder) -> Ordering {
+    order.cmp_seq_names(a.0, b.0).then_with(|| a.1.cmp(&b.1))
+}
+
+/// Removes the genes that can not overlap the given gene or any gene after it.
+fn prune_active(active: &mut Vec<Gene>, gene: &Gene) {
+    active.retain(|prev| prev.seq_name() == gene.seq_name() && prev.end() > gene.start());
+}
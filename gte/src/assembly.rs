@@ -0,0 +1,298 @@
+/*! Sequence names and lengths of a genome assembly.
+
+Assemblies are usually read from `chrom.sizes` files, which are tab-separated files where each
+line contains a sequence name and its length. These files can be obtained from
+[UCSC](https://hgdownload.soe.ucsc.edu/downloads.html) or created from a FASTA index using
+`cut -f1,2 genome.fa.fai`.
+
+Assemblies can also be used to check that gene models fit in their sequences, which catches
+annotations made for a different assembly or with off-by-one coordinates.
+*/
+use std::cmp::min;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+use std::str::FromStr;
+
+use linked_hash_map::{self, LinkedHashMap};
+
+use {Coord, Gene, Strand, DEF_ID};
+use utils::OptionDeref;
+
+
+quick_error! {
+    /// Errors that occur when reading assembly sequence lengths.
+    #[derive(Debug)]
+    pub enum AssemblyError {
+        /// Occurs when a line can not be parsed into a sequence name and length.
+        InvalidRecord(line_num: usize) {
+            description("invalid sequence length record")
+            display(self_) -> ("{}, line: {}", self_.description(), line_num)
+        }
+        /// Occurs when a sequence name is defined more than once.
+        DuplicateSeqName(line_num: usize) {
+            description("duplicate sequence name")
+            display(self_) -> ("{}, line: {}", self_.description(), line_num)
+        }
+        /// Errors propagated from reading the underlying file.
+        Io(err: io::Error) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// Names and lengths of the sequences of an assembly.
+///
+/// The sequences are stored in the order they are added.
+#[derive(Debug, Clone, Default)]
+pub struct Assembly {
+    seq_lens: LinkedHashMap<String, u64>,
+}
+
+impl Assembly {
+
+    /// Creates an empty assembly.
+    pub fn new() -> Self {
+        Assembly { seq_lens: LinkedHashMap::new() }
+    }
+
+    /// Creates an assembly from the given `chrom.sizes` reader.
+    ///
+    /// Empty lines and lines starting with `#` are ignored. Columns after the second one are
+    /// ignored as well.
+    pub fn from_reader<R: io::Read>(in_reader: R) -> ::Result<Assembly> {
+        let mut assembly = Assembly::new();
+        for (idx, line) in BufReader::new(in_reader).lines().enumerate() {
+            let line_num = idx + 1;
+            let line = line.map_err(|e| ::Error::from(AssemblyError::from(e)))?;
+            let fields = line.split_whitespace().collect::<Vec<&str>>();
+            if fields.is_empty() || fields[0].starts_with('#') {
+                continue;
+            }
+            let seq_len = fields.get(1)
+                .and_then(|raw| u64::from_str(raw).ok())
+                .ok_or(::Error::from(AssemblyError::InvalidRecord(line_num)))?;
+            if assembly.seq_lens.contains_key(fields[0]) {
+                return Err(::Error::from(AssemblyError::DuplicateSeqName(line_num)));
+            }
+            assembly.add_seq(fields[0], seq_len);
+        }
+        Ok(assembly)
+    }
+
+    /// Creates an assembly from the `chrom.sizes` file in the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> ::Result<Assembly> {
+        let file = fs::File::open(path)
+            .map_err(|e| ::Error::from(AssemblyError::from(e)))?;
+        Assembly::from_reader(file)
+    }
+
+    /// Adds a sequence to the assembly, replacing the length of any sequence with the same name.
+    pub fn add_seq<T>(&mut self, seq_name: T, seq_len: u64)
+        where T: Into<String>
+    {
+        let _ = self.seq_lens.insert(seq_name.into(), seq_len);
+    }
+
+    /// Returns the length of the given sequence, if it is present in the assembly.
+    pub fn seq_len(&self, seq_name: &str) -> Option<u64> {
+        self.seq_lens.get(seq_name).cloned()
+    }
+
+    /// Returns whether the given sequence is present in the assembly.
+    pub fn contains_seq(&self, seq_name: &str) -> bool {
+        self.seq_lens.contains_key(seq_name)
+    }
+
+    /// Returns an iterator over the sequence names and lengths, in the order they were added.
+    pub fn iter(&self) -> linked_hash_map::Iter<String, u64> {
+        self.seq_lens.iter()
+    }
+
+    /// Returns the number of sequences in the assembly.
+    pub fn len(&self) -> usize {
+        self.seq_lens.len()
+    }
+
+    /// Returns whether the assembly contains no sequences.
+    pub fn is_empty(&self) -> bool {
+        self.seq_lens.is_empty()
+    }
+
+    /// Checks that the given genes, their transcripts, and their exons are within the bounds of
+    /// the sequences of the assembly.
+    ///
+    /// All violations are returned, in the order of the genes. Genes on sequences that are not
+    /// in the assembly are reported once, without their transcripts and exons. Otherwise, every
+    /// gene, transcript, and exon that ends after the end of its sequence is reported.
+    pub fn check_bounds<'a, I>(&self, genes: I) -> Vec<BoundsViolation>
+        where I: IntoIterator<Item=&'a Gene>
+    {
+        let mut violations = Vec::new();
+        for gene in genes {
+            let gene_id = gene.id().map(|id| id.to_owned());
+            let seq_len = match self.seq_len(gene.seq_name()) {
+                Some(seq_len) => seq_len,
+                None => {
+                    violations.push(BoundsViolation {
+                        kind: BoundsViolationKind::UnknownSeqName,
+                        seq_name: gene.seq_name().to_owned(),
+                        start: gene.start(),
+                        end: gene.end(),
+                        gene_id: gene_id,
+                        transcript_id: None,
+                        exon_num: None,
+                    });
+                    continue;
+                },
+            };
+            let kind = BoundsViolationKind::ExceedsSeqLen(seq_len);
+            let violation = |start, end, transcript_id: Option<&str>, exon_num| BoundsViolation {
+                kind: kind,
+                seq_name: gene.seq_name().to_owned(),
+                start: start,
+                end: end,
+                gene_id: gene_id.clone(),
+                transcript_id: transcript_id.map(|id| id.to_owned()),
+                exon_num: exon_num,
+            };
+            if gene.end() > seq_len {
+                violations.push(violation(gene.start(), gene.end(), None, None));
+            }
+            for (tid, transcript) in gene.transcripts().iter() {
+                if transcript.end() > seq_len {
+                    violations.push(violation(transcript.start(), transcript.end(),
+                                              Some(tid.as_str()), None));
+                }
+                for (idx, exon) in transcript.exons().iter().enumerate() {
+                    if exon.end() > seq_len {
+                        violations.push(violation(exon.start(), exon.end(),
+                                                  Some(tid.as_str()), Some(idx + 1)));
+                    }
+                }
+            }
+        }
+        violations
+    }
+}
+
+/// Kinds of problems found when checking features against an assembly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoundsViolationKind {
+    /// The sequence of the feature is not in the assembly.
+    UnknownSeqName,
+    /// The feature ends after its sequence, whose length is contained.
+    ExceedsSeqLen(u64),
+}
+
+/// Feature that does not fit in the sequences of an assembly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoundsViolation {
+    kind: BoundsViolationKind,
+    seq_name: String,
+    start: u64,
+    end: u64,
+    gene_id: Option<String>,
+    transcript_id: Option<String>,
+    exon_num: Option<usize>,
+}
+
+impl BoundsViolation {
+
+    /// Returns the kind of the violation.
+    pub fn kind(&self) -> &BoundsViolationKind {
+        &self.kind
+    }
+
+    /// Returns the sequence name of the feature.
+    pub fn seq_name(&self) -> &str {
+        self.seq_name.as_str()
+    }
+
+    /// Returns the zero-based start coordinate of the feature.
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// Returns the end coordinate of the feature.
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// Returns the identifier of the gene of the feature.
+    pub fn gene_id(&self) -> Option<&str> {
+        self.gene_id.as_deref()
+    }
+
+    /// Returns the identifier of the transcript of the feature, if the feature is a transcript
+    /// or an exon.
+    pub fn transcript_id(&self) -> Option<&str> {
+        self.transcript_id.as_deref()
+    }
+
+    /// Returns the one-based position of the exon in its transcript, in coordinate order, if
+    /// the feature is an exon.
+    pub fn exon_num(&self) -> Option<usize> {
+        self.exon_num
+    }
+}
+
+/// Writes the violation as a single line.
+impl fmt::Display for BoundsViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "gene {}", self.gene_id().unwrap_or(DEF_ID))?;
+        if let Some(tid) = self.transcript_id() {
+            write!(f, " transcript {}", tid)?;
+        }
+        if let Some(exon_num) = self.exon_num {
+            write!(f, " exon {}", exon_num)?;
+        }
+        write!(f, " {}:{}-{}", self.seq_name, self.start, self.end)?;
+        match self.kind {
+            BoundsViolationKind::UnknownSeqName => write!(f, ": unknown sequence"),
+            BoundsViolationKind::ExceedsSeqLen(seq_len) =>
+                write!(f, ": exceeds sequence length {}", seq_len),
+        }
+    }
+}
+
+/// Clamps the given zero-based, half-open coordinates to the bounds of their sequence.
+///
+/// The end coordinate is lowered to the sequence length, and the start coordinate is lowered
+/// to the end coordinate, so the returned region may be empty. Coordinates on sequences that
+/// are not in the assembly are only checked for their order.
+pub fn clamp_to_contig(seq_name: &str, coord: Coord<u64>, assembly: &Assembly) -> Coord<u64> {
+    let end = match assembly.seq_len(seq_name) {
+        Some(seq_len) => min(coord.1, seq_len),
+        None => coord.1,
+    };
+    (min(coord.0, end), end)
+}
+
+/// Expands the given zero-based, half-open coordinates by a number of bases on each side.
+///
+/// `upstream` and `downstream` are relative to the given strand, so upstream bases are added
+/// after the end coordinate on the reverse strand. Unknown strands are treated as the forward
+/// strand. The start coordinate never goes below zero and, if an assembly is given, the result
+/// is clamped as in `clamp_to_contig`.
+///
+/// This is how promoter and termination windows, flanks, and padded regions are computed
+/// throughout the crate.
+pub fn expand_window(seq_name: &str, coord: Coord<u64>, upstream: u64, downstream: u64,
+                     strand: &Strand, assembly: Option<&Assembly>) -> Coord<u64>
+{
+    let (before, after) = match strand {
+        &Strand::Reverse => (downstream, upstream),
+        _ => (upstream, downstream),
+    };
+    let expanded = (coord.0.saturating_sub(before), coord.1.saturating_add(after));
+    match assembly {
+        Some(asm) => clamp_to_contig(seq_name, expanded, asm),
+        None => expanded,
+    }
+}
@@ -0,0 +1,181 @@
+/*! Ordered attributes of genes, transcripts, and exons.
+
+Annotation formats such as GTF list the attributes of each record in a meaningful order, for
+example with `gene_name` before `level` and tags. `Attributes` keeps the keys in the order they
+were first inserted, along with the values of each key in their insertion order, so that
+writers can output attributes in the same order as they were read:
+
+```
+use gte::Attributes;
+
+let mut attribs = Attributes::new();
+attribs.insert("gene_type".to_owned(), "protein_coding".to_owned());
+attribs.insert("tag".to_owned(), "basic".to_owned());
+attribs.insert("level".to_owned(), "2".to_owned());
+attribs.insert("tag".to_owned(), "CCDS".to_owned());
+
+assert_eq!(attribs.keys().collect::<Vec<_>>(), vec!["gene_type", "tag", "level"]);
+assert_eq!(attribs.get_vec("tag"), Some(&vec!["basic".to_owned(), "CCDS".to_owned()]));
+```
+
+Attributes compare equal when they have the same values for the same keys, regardless of the
+order of their keys.
+*/
+use std::borrow::Borrow;
+use std::hash::Hash;
+use std::iter::FromIterator;
+use std::mem;
+
+use linked_hash_map::{self, LinkedHashMap};
+use multimap::MultiMap;
+
+
+/// Attributes whose keys may have multiple values, kept in insertion order.
+#[derive(Debug, Clone)]
+pub struct Attributes {
+    inner: LinkedHashMap<String, Vec<String>>,
+}
+
+impl Default for Attributes {
+    fn default() -> Self {
+        Attributes::new()
+    }
+}
+
+impl Attributes {
+
+    /// Creates empty attributes.
+    pub fn new() -> Self {
+        Attributes { inner: LinkedHashMap::new() }
+    }
+
+    /// Adds a value to the given key.
+    ///
+    /// Keys not yet present are added after all existing keys.
+    pub fn insert(&mut self, key: String, value: String) {
+        self.inner.entry(key).or_insert_with(Vec::new).push(value);
+    }
+
+    /// Sets the values of the given key, returning its previous values.
+    ///
+    /// Keys already present keep their position, while other keys are added after all existing
+    /// keys.
+    pub fn replace_vec(&mut self, key: String, values: Vec<String>) -> Option<Vec<String>> {
+        match self.inner.get_mut(&key) {
+            Some(existing) => return Some(mem::replace(existing, values)),
+            None => {},
+        }
+        let _ = self.inner.insert(key, values);
+        None
+    }
+
+    /// Returns the first value of the given key.
+    pub fn get<Q: ?Sized>(&self, key: &Q) -> Option<&String>
+        where String: Borrow<Q>, Q: Hash + Eq
+    {
+        self.inner.get(key).and_then(|values| values.first())
+    }
+
+    /// Returns all values of the given key.
+    pub fn get_vec<Q: ?Sized>(&self, key: &Q) -> Option<&Vec<String>>
+        where String: Borrow<Q>, Q: Hash + Eq
+    {
+        self.inner.get(key)
+    }
+
+    /// Returns a mutable reference of all values of the given key.
+    pub fn get_vec_mut<Q: ?Sized>(&mut self, key: &Q) -> Option<&mut Vec<String>>
+        where String: Borrow<Q>, Q: Hash + Eq
+    {
+        self.inner.get_mut(key)
+    }
+
+    /// Returns whether the given key is present.
+    pub fn contains_key<Q: ?Sized>(&self, key: &Q) -> bool
+        where String: Borrow<Q>, Q: Hash + Eq
+    {
+        self.inner.contains_key(key)
+    }
+
+    /// Removes the given key, returning its values.
+    ///
+    /// The other keys keep their order.
+    pub fn remove<Q: ?Sized>(&mut self, key: &Q) -> Option<Vec<String>>
+        where String: Borrow<Q>, Q: Hash + Eq
+    {
+        self.inner.remove(key)
+    }
+
+    /// Returns the number of keys.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns whether there are no keys.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Removes all keys and values.
+    pub fn clear(&mut self) {
+        self.inner.clear()
+    }
+
+    /// Returns an iterator over the keys, in insertion order.
+    pub fn keys(&self) -> linked_hash_map::Keys<String, Vec<String>> {
+        self.inner.keys()
+    }
+
+    /// Returns an iterator over the keys and all of their values, in insertion order.
+    pub fn iter_all(&self) -> linked_hash_map::Iter<String, Vec<String>> {
+        self.inner.iter()
+    }
+
+    /// Transforms the attributes into a `MultiMap`, which does not keep the order of its keys.
+    pub fn into_multimap(self) -> MultiMap<String, String> {
+        let mut converted = MultiMap::new();
+        for (key, values) in self.inner {
+            for value in values {
+                converted.insert(key.clone(), value);
+            }
+        }
+        converted
+    }
+}
+
+impl PartialEq for Attributes {
+    fn eq(&self, other: &Attributes) -> bool {
+        self.len() == other.len()
+            && self.iter_all().all(|(key, values)| other.get_vec(key) == Some(values))
+    }
+}
+
+impl From<MultiMap<String, String>> for Attributes {
+    fn from(attributes: MultiMap<String, String>) -> Attributes {
+        let mut converted = Attributes::new();
+        for (key, values) in attributes.iter_all() {
+            let _ = converted.replace_vec(key.clone(), values.clone());
+        }
+        converted
+    }
+}
+
+impl FromIterator<(String, String)> for Attributes {
+    fn from_iter<I>(iter: I) -> Attributes
+        where I: IntoIterator<Item=(String, String)>
+    {
+        let mut attributes = Attributes::new();
+        attributes.extend(iter);
+        attributes
+    }
+}
+
+impl Extend<(String, String)> for Attributes {
+    fn extend<I>(&mut self, iter: I)
+        where I: IntoIterator<Item=(String, String)>
+    {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
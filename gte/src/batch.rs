@@ -0,0 +1,232 @@
+/*! Length-prefixed record batches for streaming gene models between processes.
+
+This module is only available with the `stream` feature enabled. It lets a single process parse
+an annotation and send the resulting models to consumers through any byte stream, such as a
+socket or a pipe, so that the consumers do not have to parse the annotation again.
+
+A stream starts with the 4-byte header `GTEB` and a version byte, followed by batches. Each
+batch is prefixed with its length in bytes, as a little-endian `u64`, and contains the number of
+records in the batch followed by the records. The records use the encoding of
+`AnnotationCache`, so decoding errors are reported as `CacheError`s. The stream ends with a
+batch of length zero.
+
+Batches are written once they reach a maximum number of records or a maximum size, so that
+consumers receive data in bounded chunks. Writes to the underlying stream block when the
+consumer does not keep up, which provides backpressure to the producer.
+*/
+use std::io::{Read, Write};
+
+use {CacheError, Gene, Transcript};
+use cache::{self, cache_io_error, invalid_data, read_gene, read_transcript, read_u64, read_u8,
+            write_gene, write_transcript, write_u64, write_u8};
+
+
+/// Bytes at the start of every stream.
+const MAGIC: &'static [u8; 4] = b"GTEB";
+
+/// Marker preceding each gene record.
+const GENE_MARKER: u8 = 1;
+
+/// Marker preceding each transcript record.
+const TRANSCRIPT_MARKER: u8 = 2;
+
+/// Default maximum number of records in a batch.
+const DEFAULT_MAX_RECORDS: usize = 1024;
+
+/// Default maximum size of a batch in bytes.
+///
+/// Batches may exceed this size by at most one record.
+const DEFAULT_MAX_BYTES: usize = 1024 * 1024;
+
+/// Record in a batch.
+#[derive(Debug, Clone)]
+pub enum BatchRecord {
+    /// Gene, along with its transcripts.
+    Gene(Gene),
+    /// Transcript that is not part of any gene record.
+    Transcript(Transcript),
+}
+
+/// Writer of record batches.
+///
+/// Records are buffered in memory until their batch is full. The `finish` method must be called
+/// once all records have been written, to write the remaining records and mark the end of the
+/// stream.
+pub struct BatchWriter<W: Write> {
+    inner: W,
+    buffer: Vec<u8>,
+    num_buffered: usize,
+    max_records: usize,
+    max_bytes: usize,
+    header_written: bool,
+}
+
+impl<W: Write> BatchWriter<W> {
+
+    /// Creates a batch writer from another writer.
+    pub fn new(inner: W) -> BatchWriter<W> {
+        BatchWriter {
+            inner: inner,
+            buffer: Vec::new(),
+            num_buffered: 0,
+            max_records: DEFAULT_MAX_RECORDS,
+            max_bytes: DEFAULT_MAX_BYTES,
+            header_written: false,
+        }
+    }
+
+    /// Sets the maximum number of records in a batch.
+    ///
+    /// The default is 1024 records. Values smaller than 1 are treated as 1.
+    pub fn max_records(mut self, max_records: usize) -> Self {
+        self.max_records = max_records;
+        self
+    }
+
+    /// Sets the maximum size of a batch in bytes.
+    ///
+    /// The default is 1 MiB. A batch is written as soon as it reaches this size, so batches
+    /// may exceed it by at most one record.
+    pub fn max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Adds the given gene to the current batch.
+    pub fn write_gene(&mut self, gene: &Gene) -> ::Result<()> {
+        write_u8(&mut self.buffer, GENE_MARKER)?;
+        write_gene(&mut self.buffer, gene)?;
+        self.record_written()
+    }
+
+    /// Adds the given transcript to the current batch.
+    pub fn write_transcript(&mut self, transcript: &Transcript) -> ::Result<()> {
+        write_u8(&mut self.buffer, TRANSCRIPT_MARKER)?;
+        write_transcript(&mut self.buffer, transcript)?;
+        self.record_written()
+    }
+
+    /// Writes the current batch, if it is not empty, and flushes the underlying writer.
+    pub fn flush_batch(&mut self) -> ::Result<()> {
+        if self.num_buffered == 0 {
+            return Ok(());
+        }
+        self.write_header()?;
+        let mut count = Vec::with_capacity(8);
+        write_u64(&mut count, self.num_buffered as u64)?;
+        write_u64(&mut self.inner, (count.len() + self.buffer.len()) as u64)?;
+        self.inner.write_all(&count).map_err(cache_io_error)?;
+        self.inner.write_all(&self.buffer).map_err(cache_io_error)?;
+        self.inner.flush().map_err(cache_io_error)?;
+        self.buffer.clear();
+        self.num_buffered = 0;
+        Ok(())
+    }
+
+    /// Writes the remaining records and the end of the stream, returning the underlying writer.
+    pub fn finish(mut self) -> ::Result<W> {
+        self.flush_batch()?;
+        self.write_header()?;
+        write_u64(&mut self.inner, 0)?;
+        self.inner.flush().map_err(cache_io_error)?;
+        Ok(self.inner)
+    }
+
+    fn record_written(&mut self) -> ::Result<()> {
+        self.num_buffered += 1;
+        if self.num_buffered >= self.max_records || self.buffer.len() >= self.max_bytes {
+            self.flush_batch()
+        } else {
+            Ok(())
+        }
+    }
+
+    fn write_header(&mut self) -> ::Result<()> {
+        if self.header_written {
+            return Ok(());
+        }
+        self.header_written = true;
+        self.inner.write_all(MAGIC).map_err(cache_io_error)?;
+        write_u8(&mut self.inner, cache::VERSION)
+    }
+}
+
+/// Reader of record batches.
+pub struct BatchReader<R: Read> {
+    inner: R,
+    header_read: bool,
+    finished: bool,
+}
+
+impl<R: Read> BatchReader<R> {
+
+    /// Creates a batch reader from another reader.
+    pub fn new(inner: R) -> BatchReader<R> {
+        BatchReader {
+            inner: inner,
+            header_read: false,
+            finished: false,
+        }
+    }
+
+    /// Reads the next batch, returning `None` at the end of the stream.
+    pub fn next_batch(&mut self) -> ::Result<Option<Vec<BatchRecord>>> {
+        if self.finished {
+            return Ok(None);
+        }
+        self.read_header()?;
+        let len = read_u64(&mut self.inner)?;
+        if len == 0 {
+            self.finished = true;
+            return Ok(None);
+        }
+        let mut batch = (&mut self.inner).take(len);
+        let num_records = read_u64(&mut batch)?;
+        let mut records = Vec::new();
+        for _ in 0..num_records {
+            let record = match read_u8(&mut batch)? {
+                GENE_MARKER => BatchRecord::Gene(read_gene(&mut batch)?),
+                TRANSCRIPT_MARKER => BatchRecord::Transcript(read_transcript(&mut batch)?),
+                _ => return Err(invalid_data("unknown record marker")),
+            };
+            records.push(record);
+        }
+        if batch.limit() != 0 {
+            return Err(invalid_data("batch length does not match its records"));
+        }
+        Ok(Some(records))
+    }
+
+    fn read_header(&mut self) -> ::Result<()> {
+        if self.header_read {
+            return Ok(());
+        }
+        let mut magic = [0u8; 4];
+        self.inner.read_exact(&mut magic).map_err(cache_io_error)?;
+        if &magic != MAGIC {
+            return Err(::Error::from(CacheError::InvalidHeader));
+        }
+        let version = read_u8(&mut self.inner)?;
+        if version != cache::VERSION {
+            return Err(::Error::from(CacheError::UnsupportedVersion(version)));
+        }
+        self.header_read = true;
+        Ok(())
+    }
+}
+
+impl<R: Read> Iterator for BatchReader<R> {
+
+    type Item = ::Result<Vec<BatchRecord>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_batch() {
+            Ok(Some(records)) => Some(Ok(records)),
+            Ok(None) => None,
+            Err(e) => {
+                self.finished = true;
+                Some(Err(e))
+            },
+        }
+    }
+}
@@ -0,0 +1,160 @@
+/*! Biotypes of genes and transcripts.
+
+Biotypes classify genes and transcripts by their function, such as `protein_coding` or
+`lncRNA`. The GTF reader stores the biotypes found in the `gene_biotype` and
+`transcript_biotype` attributes used by Ensembl, or in the `gene_type` and `transcript_type`
+attributes used by GENCODE, while the GFF reader stores transcript biotypes. The attributes
+themselves are kept, so writers output them unchanged.
+
+Common biotypes have their own variants, while all others are kept as they are:
+
+```
+use gte::Biotype;
+
+assert_eq!(Biotype::from("protein_coding"), Biotype::ProteinCoding);
+assert_eq!(Biotype::from("TEC"), Biotype::Other("TEC".to_owned()));
+assert_eq!(Biotype::from("lncRNA").as_str(), "lncRNA");
+assert!(Biotype::from("transcribed_unitary_pseudogene").is_pseudogene());
+```
+
+With the `serde` feature enabled, biotypes are serialized as their names.
+*/
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use Attributes;
+
+
+/// Attribute keys of gene biotypes, in order of preference.
+pub(crate) const GENE_BIOTYPE_KEYS: [&str; 2] = ["gene_biotype", "gene_type"];
+
+/// Attribute keys of transcript biotypes, in order of preference.
+pub(crate) const TRANSCRIPT_BIOTYPE_KEYS: [&str; 2] = ["transcript_biotype", "transcript_type"];
+
+/// Biotype of a gene or a transcript.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Biotype {
+    /// `protein_coding`
+    ProteinCoding,
+    /// `lncRNA`
+    LncRNA,
+    /// `miRNA`
+    MiRNA,
+    /// `misc_RNA`
+    MiscRNA,
+    /// `snRNA`
+    SnRNA,
+    /// `snoRNA`
+    SnoRNA,
+    /// `rRNA`
+    RRNA,
+    /// `nonsense_mediated_decay`
+    NonsenseMediatedDecay,
+    /// `retained_intron`
+    RetainedIntron,
+    /// `processed_transcript`
+    ProcessedTranscript,
+    /// `processed_pseudogene`
+    ProcessedPseudogene,
+    /// `unprocessed_pseudogene`
+    UnprocessedPseudogene,
+    /// Any other biotype.
+    Other(String),
+}
+
+impl Biotype {
+
+    /// Returns the name of the biotype, as written in annotation files.
+    pub fn as_str(&self) -> &str {
+        match *self {
+            Biotype::ProteinCoding => "protein_coding",
+            Biotype::LncRNA => "lncRNA",
+            Biotype::MiRNA => "miRNA",
+            Biotype::MiscRNA => "misc_RNA",
+            Biotype::SnRNA => "snRNA",
+            Biotype::SnoRNA => "snoRNA",
+            Biotype::RRNA => "rRNA",
+            Biotype::NonsenseMediatedDecay => "nonsense_mediated_decay",
+            Biotype::RetainedIntron => "retained_intron",
+            Biotype::ProcessedTranscript => "processed_transcript",
+            Biotype::ProcessedPseudogene => "processed_pseudogene",
+            Biotype::UnprocessedPseudogene => "unprocessed_pseudogene",
+            Biotype::Other(ref name) => name.as_str(),
+        }
+    }
+
+    /// Returns whether the biotype is `protein_coding`.
+    pub fn is_protein_coding(&self) -> bool {
+        *self == Biotype::ProteinCoding
+    }
+
+    /// Returns whether the biotype is any kind of pseudogene.
+    pub fn is_pseudogene(&self) -> bool {
+        self.as_str().ends_with("pseudogene")
+    }
+}
+
+impl<'a> From<&'a str> for Biotype {
+    fn from(name: &'a str) -> Biotype {
+        match name {
+            "protein_coding" => Biotype::ProteinCoding,
+            "lncRNA" => Biotype::LncRNA,
+            "miRNA" => Biotype::MiRNA,
+            "misc_RNA" => Biotype::MiscRNA,
+            "snRNA" => Biotype::SnRNA,
+            "snoRNA" => Biotype::SnoRNA,
+            "rRNA" => Biotype::RRNA,
+            "nonsense_mediated_decay" => Biotype::NonsenseMediatedDecay,
+            "retained_intron" => Biotype::RetainedIntron,
+            "processed_transcript" => Biotype::ProcessedTranscript,
+            "processed_pseudogene" => Biotype::ProcessedPseudogene,
+            "unprocessed_pseudogene" => Biotype::UnprocessedPseudogene,
+            other => Biotype::Other(other.to_owned()),
+        }
+    }
+}
+
+impl From<String> for Biotype {
+    fn from(name: String) -> Biotype {
+        match Biotype::from(name.as_str()) {
+            Biotype::Other(_) => Biotype::Other(name),
+            known => known,
+        }
+    }
+}
+
+impl fmt::Display for Biotype {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Biotype {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Biotype {
+    fn deserialize<D>(deserializer: D) -> Result<Biotype, D::Error>
+        where D: Deserializer<'de>
+    {
+        String::deserialize(deserializer).map(Biotype::from)
+    }
+}
+
+/// Helper function to get the biotype from the first of the given attribute keys present.
+pub(crate) fn biotype_from_attributes(attributes: &Attributes, keys: &[&str])
+    -> Option<Biotype>
+{
+    keys.iter()
+        .filter_map(|key| attributes.get(*key))
+        .next()
+        .map(|name| Biotype::from(name.as_str()))
+}
@@ -0,0 +1,404 @@
+/*! Binary cache of gene models.
+
+The cache stores genes in a compact binary encoding, so that an annotation parsed from a slower
+text format such as GTF or refFlat can be reloaded quickly. The encoding is specific to this
+crate and may change between versions; caches written by a different version are rejected when
+read, and should then be recreated from the original annotation.
+
+Cached genes are rebuilt using the model builders, but their exon features are restored as
+written instead of being inferred again.
+*/
+use std::error::Error;
+use std::fs;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use bio::utils::Interval;
+use linked_hash_map::LinkedHashMap;
+use {Attributes, EBuilder, Exon, ExonFeature, ExonFeatureKind, Feature, GBuilder, Gene, Strand,
+     StrandExt, TBuilder, Transcript};
+use ExonFeatureKind::*;
+
+
+quick_error! {
+    /// Errors that occur when reading or writing annotation caches.
+    #[derive(Debug)]
+    pub enum CacheError {
+        /// Occurs when the input does not start with the cache header.
+        InvalidHeader {
+            description("input is not an annotation cache")
+        }
+        /// Occurs when the cache was written by an incompatible version of this crate.
+        UnsupportedVersion(version: u8) {
+            description("unsupported annotation cache version")
+            display(self_) -> ("{}: {}", self_.description(), version)
+        }
+        /// Occurs when the cache contains invalid values.
+        InvalidData(reason: &'static str) {
+            description("invalid annotation cache data")
+            display(self_) -> ("{}: {}", self_.description(), reason)
+        }
+        /// Errors propagated from reading or writing the underlying files.
+        Io(err: io::Error) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// Bytes at the start of every cache.
+const MAGIC: &'static [u8; 4] = b"GTEC";
+
+/// Version of the cache encoding.
+pub(crate) const VERSION: u8 = 4;
+
+/// Marker preceding each gene record.
+const GENE_MARKER: u8 = 1;
+
+/// Marker of the end of the cache.
+const END_MARKER: u8 = 0;
+
+/// Encoded value of unknown frames.
+const NO_FRAME: u8 = u8::max_value();
+
+/// Reader and writer of binary annotation caches.
+#[derive(Debug, Clone, Copy)]
+pub struct AnnotationCache;
+
+impl AnnotationCache {
+
+    /// Writes the given genes to a cache at the given path, replacing any existing file.
+    pub fn write<'a, P, I>(path: P, genes: I) -> ::Result<()>
+        where P: AsRef<Path>, I: IntoIterator<Item=&'a Gene>
+    {
+        let file = fs::File::create(path).map_err(cache_io_error)?;
+        let mut writer = BufWriter::new(file);
+        AnnotationCache::write_to(&mut writer, genes)?;
+        writer.flush().map_err(cache_io_error)
+    }
+
+    /// Reads all genes from the cache at the given path.
+    pub fn read<P: AsRef<Path>>(path: P) -> ::Result<Vec<Gene>> {
+        let file = fs::File::open(path).map_err(cache_io_error)?;
+        AnnotationCache::read_from(BufReader::new(file))
+    }
+
+    /// Writes the given genes as a cache to the given writer.
+    pub fn write_to<'a, W, I>(mut writer: W, genes: I) -> ::Result<()>
+        where W: Write, I: IntoIterator<Item=&'a Gene>
+    {
+        writer.write_all(MAGIC).map_err(cache_io_error)?;
+        write_u8(&mut writer, VERSION)?;
+        for gene in genes {
+            write_u8(&mut writer, GENE_MARKER)?;
+            write_gene(&mut writer, gene)?;
+        }
+        write_u8(&mut writer, END_MARKER)
+    }
+
+    /// Reads all genes from the cache read by the given reader.
+    pub fn read_from<R: Read>(mut reader: R) -> ::Result<Vec<Gene>> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic).map_err(cache_io_error)?;
+        if &magic != MAGIC {
+            return Err(::Error::from(CacheError::InvalidHeader));
+        }
+        let version = read_u8(&mut reader)?;
+        if version != VERSION {
+            return Err(::Error::from(CacheError::UnsupportedVersion(version)));
+        }
+        let mut genes = Vec::new();
+        loop {
+            match read_u8(&mut reader)? {
+                GENE_MARKER => genes.push(read_gene(&mut reader)?),
+                END_MARKER => return Ok(genes),
+                _ => return Err(invalid_data("unknown record marker")),
+            }
+        }
+    }
+}
+
+pub(crate) fn write_gene<W: Write>(writer: &mut W, gene: &Gene) -> ::Result<()> {
+    write_str(writer, gene.seq_name())?;
+    write_u64(writer, gene.start())?;
+    write_u64(writer, gene.end())?;
+    write_u8(writer, gene.strand().to_char() as u8)?;
+    write_opt_str(writer, gene.id())?;
+    write_opt_str(writer, gene.biotype().map(|b| b.as_str()))?;
+    write_attributes(writer, gene.attributes())?;
+    write_u64(writer, gene.transcripts().len() as u64)?;
+    for (tid, transcript) in gene.transcripts().iter() {
+        write_str(writer, tid)?;
+        write_transcript(writer, transcript)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn read_gene<R: Read>(reader: &mut R) -> ::Result<Gene> {
+    let seq_name = read_string(reader)?;
+    let (start, end) = (read_u64(reader)?, read_u64(reader)?);
+    let strand = read_strand(reader)?;
+    let id = read_opt_string(reader)?;
+    let biotype = read_opt_string(reader)?;
+    let attributes = read_attributes(reader)?;
+    let mut transcripts = LinkedHashMap::new();
+    for _ in 0..read_u64(reader)? {
+        let tid = read_string(reader)?;
+        let transcript = read_transcript(reader)?;
+        let _ = transcripts.insert(tid, transcript);
+    }
+
+    let mut builder = GBuilder::new(seq_name, start, end)
+        .strand(strand)
+        .attributes(attributes)
+        .transcripts(transcripts);
+    if let Some(id) = id {
+        builder = builder.id(id);
+    }
+    if let Some(biotype) = biotype {
+        builder = builder.biotype(biotype);
+    }
+    builder.build()
+}
+
+pub(crate) fn write_transcript<W: Write>(writer: &mut W, transcript: &Transcript) -> ::Result<()> {
+    // transcripts may not share the sequence name or strand of their gene, e.g. when their gene
+    // has an unknown strand
+    write_str(writer, transcript.seq_name())?;
+    write_u8(writer, transcript.strand().to_char() as u8)?;
+    write_u64(writer, transcript.start())?;
+    write_u64(writer, transcript.end())?;
+    write_opt_str(writer, transcript.id())?;
+    write_opt_str(writer, transcript.gene_id())?;
+    write_opt_str(writer, transcript.biotype().map(|b| b.as_str()))?;
+    write_u8(writer, transcript.cds_5_incomplete() as u8)?;
+    write_u8(writer, transcript.cds_3_incomplete() as u8)?;
+    write_attributes(writer, transcript.attributes())?;
+    write_u64(writer, transcript.exons().len() as u64)?;
+    for exon in transcript.exons() {
+        write_exon(writer, exon)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn read_transcript<R: Read>(reader: &mut R) -> ::Result<Transcript> {
+    let seq_name = read_string(reader)?;
+    let strand = read_strand(reader)?;
+    let (start, end) = (read_u64(reader)?, read_u64(reader)?);
+    let id = read_opt_string(reader)?;
+    let gene_id = read_opt_string(reader)?;
+    let biotype = read_opt_string(reader)?;
+    let cds_5_incomplete = read_bool(reader)?;
+    let cds_3_incomplete = read_bool(reader)?;
+    let attributes = read_attributes(reader)?;
+    let mut exons = Vec::new();
+    for _ in 0..read_u64(reader)? {
+        exons.push(read_exon(reader, &seq_name, strand)?);
+    }
+
+    let mut builder = TBuilder::new(seq_name, start, end)
+        .strand(strand)
+        .attributes(attributes)
+        .exons(exons)
+        .cds_5_incomplete(cds_5_incomplete)
+        .cds_3_incomplete(cds_3_incomplete);
+    if let Some(id) = id {
+        builder = builder.id(id);
+    }
+    if let Some(gene_id) = gene_id {
+        builder = builder.gene_id(gene_id);
+    }
+    if let Some(biotype) = biotype {
+        builder = builder.biotype(biotype);
+    }
+    builder.build()
+}
+
+fn write_exon<W: Write>(writer: &mut W, exon: &Exon) -> ::Result<()> {
+    write_u64(writer, exon.start())?;
+    write_u64(writer, exon.end())?;
+    write_opt_str(writer, exon.id())?;
+    write_opt_str(writer, exon.transcript_id())?;
+    write_opt_str(writer, exon.gene_id())?;
+    write_attributes(writer, exon.attributes())?;
+    write_u64(writer, exon.features().len() as u64)?;
+    for feature in exon.features() {
+        write_feature(writer, feature)?;
+    }
+    Ok(())
+}
+
+fn read_exon<R: Read>(reader: &mut R, seq_name: &str, strand: Strand) -> ::Result<Exon> {
+    let (start, end) = (read_u64(reader)?, read_u64(reader)?);
+    let id = read_opt_string(reader)?;
+    let transcript_id = read_opt_string(reader)?;
+    let gene_id = read_opt_string(reader)?;
+    let attributes = read_attributes(reader)?;
+    let mut features = Vec::new();
+    for _ in 0..read_u64(reader)? {
+        features.push(read_feature(reader)?);
+    }
+
+    let mut builder = EBuilder::new(seq_name, start, end)
+        .strand(strand)
+        .attributes(attributes)
+        .features(features);
+    if let Some(id) = id {
+        builder = builder.id(id);
+    }
+    if let Some(transcript_id) = transcript_id {
+        builder = builder.transcript_id(transcript_id);
+    }
+    if let Some(gene_id) = gene_id {
+        builder = builder.gene_id(gene_id);
+    }
+    builder.build()
+}
+
+fn write_feature<W: Write>(writer: &mut W, feature: &ExonFeature) -> ::Result<()> {
+    write_u64(writer, feature.start())?;
+    write_u64(writer, feature.end())?;
+    let (tag, frame) = match feature.kind() {
+        &UTR => (0, None),
+        &UTR5 => (1, None),
+        &UTR3 => (2, None),
+        &CDS { frame } => (3, frame),
+        &StartCodon { frame } => (4, frame),
+        &StopCodon { frame } => (5, frame),
+        &Any(_) => (6, None),
+    };
+    write_u8(writer, tag)?;
+    match feature.kind() {
+        &Any(ref name) => write_str(writer, name),
+        _ if tag >= 3 => write_u8(writer, frame.unwrap_or(NO_FRAME)),
+        _ => Ok(()),
+    }
+}
+
+fn read_feature<R: Read>(reader: &mut R) -> ::Result<ExonFeature> {
+    let (start, end) = (read_u64(reader)?, read_u64(reader)?);
+    let interval = Interval::new(start..end)
+        .map_err(|_| invalid_data("feature start larger than its end"))?;
+    let kind = match read_u8(reader)? {
+        0 => UTR,
+        1 => UTR5,
+        2 => UTR3,
+        3 => CDS { frame: read_frame(reader)? },
+        4 => StartCodon { frame: read_frame(reader)? },
+        5 => StopCodon { frame: read_frame(reader)? },
+        6 => Any(read_string(reader)?),
+        _ => return Err(invalid_data("unknown exon feature kind")),
+    };
+    Ok(Feature::new(interval, kind))
+}
+
+fn read_frame<R: Read>(reader: &mut R) -> ::Result<Option<u8>> {
+    read_u8(reader).map(|frame| if frame == NO_FRAME { None } else { Some(frame) })
+}
+
+fn write_attributes<W: Write>(writer: &mut W, attributes: &Attributes) -> ::Result<()> {
+    // in their stored order, so that reading them back keeps the order
+    write_u64(writer, attributes.len() as u64)?;
+    for (key, values) in attributes.iter_all() {
+        write_str(writer, key)?;
+        write_u64(writer, values.len() as u64)?;
+        for value in values.iter() {
+            write_str(writer, value)?;
+        }
+    }
+    Ok(())
+}
+
+fn read_attributes<R: Read>(reader: &mut R) -> ::Result<Attributes> {
+    let mut attributes = Attributes::new();
+    for _ in 0..read_u64(reader)? {
+        let key = read_string(reader)?;
+        for _ in 0..read_u64(reader)? {
+            attributes.insert(key.clone(), read_string(reader)?);
+        }
+    }
+    Ok(attributes)
+}
+
+pub(crate) fn read_strand<R: Read>(reader: &mut R) -> ::Result<Strand> {
+    Strand::from_char(&(read_u8(reader)? as char))
+        .map_err(|_| invalid_data("invalid strand character"))
+}
+
+pub(crate) fn write_u8<W: Write>(writer: &mut W, value: u8) -> ::Result<()> {
+    writer.write_all(&[value]).map_err(cache_io_error)
+}
+
+pub(crate) fn read_u8<R: Read>(reader: &mut R) -> ::Result<u8> {
+    let mut buf = [0u8; 1];
+    reader.read_exact(&mut buf).map_err(cache_io_error)?;
+    Ok(buf[0])
+}
+
+fn read_bool<R: Read>(reader: &mut R) -> ::Result<bool> {
+    match read_u8(reader)? {
+        0 => Ok(false),
+        1 => Ok(true),
+        _ => Err(invalid_data("invalid boolean value")),
+    }
+}
+
+pub(crate) fn write_u64<W: Write>(writer: &mut W, value: u64) -> ::Result<()> {
+    let mut buf = [0u8; 8];
+    for (idx, byte) in buf.iter_mut().enumerate() {
+        *byte = (value >> (8 * idx)) as u8;
+    }
+    writer.write_all(&buf).map_err(cache_io_error)
+}
+
+pub(crate) fn read_u64<R: Read>(reader: &mut R) -> ::Result<u64> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf).map_err(cache_io_error)?;
+    Ok(buf.iter().rev().fold(0, |acc, &byte| (acc << 8) | u64::from(byte)))
+}
+
+pub(crate) fn write_str<W: Write>(writer: &mut W, value: &str) -> ::Result<()> {
+    write_u64(writer, value.len() as u64)?;
+    writer.write_all(value.as_bytes()).map_err(cache_io_error)
+}
+
+pub(crate) fn read_string<R: Read>(reader: &mut R) -> ::Result<String> {
+    let len = read_u64(reader)?;
+    let mut buf = Vec::new();
+    let _ = reader.take(len).read_to_end(&mut buf).map_err(cache_io_error)?;
+    if buf.len() as u64 != len {
+        return Err(cache_io_error(io::Error::new(io::ErrorKind::UnexpectedEof,
+                                                 "truncated annotation cache")));
+    }
+    String::from_utf8(buf).map_err(|_| invalid_data("invalid UTF-8 string"))
+}
+
+fn write_opt_str<W: Write>(writer: &mut W, value: Option<&str>) -> ::Result<()> {
+    match value {
+        Some(value) => {
+            write_u8(writer, 1)?;
+            write_str(writer, value)
+        },
+        None => write_u8(writer, 0),
+    }
+}
+
+fn read_opt_string<R: Read>(reader: &mut R) -> ::Result<Option<String>> {
+    match read_u8(reader)? {
+        0 => Ok(None),
+        1 => read_string(reader).map(Some),
+        _ => Err(invalid_data("invalid optional string marker")),
+    }
+}
+
+/// Helper function for creating invalid data errors.
+pub(crate) fn invalid_data(reason: &'static str) -> ::Error {
+    ::Error::from(CacheError::InvalidData(reason))
+}
+
+/// Helper function for converting I/O errors.
+pub(crate) fn cache_io_error(err: io::Error) -> ::Error {
+    ::Error::from(CacheError::from(err))
+}
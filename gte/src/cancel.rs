@@ -0,0 +1,54 @@
+/*! Cooperative cancellation of long-running operations.
+
+A `CancelToken` is handed to a reader, which checks it before reading each record. Once the
+token is cancelled, either explicitly or because its deadline has passed, the reader's streams
+yield a single `Error::Cancelled` and then stop. Operations built on top of the streams, such as
+loading an annotation set, return that error as well, so an embedding application can abort the
+reading of a large or malformed file from another thread.
+
+Tokens are accepted by the GTF, GFF, refFlat, and knownGene readers, and by indexed refFlat
+readers for their queries. Operations on genes that have already been read, such as sorting,
+are not cancellable.
+*/
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+
+/// Shared flag for cancelling operations.
+///
+/// Clones of a token share its state, so a token may be given to a reader while a clone is
+/// kept for cancelling it later, possibly from another thread.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl CancelToken {
+
+    /// Creates a token that is only cancelled through `cancel`.
+    pub fn new() -> Self {
+        CancelToken::default()
+    }
+
+    /// Creates a token that is cancelled once the given duration has passed, or earlier
+    /// through `cancel`.
+    pub fn with_timeout(timeout: Duration) -> Self {
+        CancelToken {
+            cancelled: Arc::new(AtomicBool::new(false)),
+            deadline: Some(Instant::now() + timeout),
+        }
+    }
+
+    /// Cancels the token and all of its clones.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns whether the token has been cancelled or its deadline has passed.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+            || self.deadline.map(|deadline| Instant::now() >= deadline).unwrap_or(false)
+    }
+}
@@ -0,0 +1,483 @@
+/*! Compact, read-only storage of transcript intervals.
+
+`CompactAnnotation` keeps only the coordinates, strands, identifiers, and coding regions of
+transcripts, in a columnar layout meant for very large collections such as the assembled
+transcripts of many samples. Per sequence, transcripts are sorted by their coordinates and
+encoded as variable-length integers: start coordinates as deltas from the previous start, and
+end, exon, and coding coordinates relative to their own transcript. Identifiers are stored in
+shared string arenas, with gene identifiers stored once per gene.
+
+To keep overlap queries fast, entries are grouped in blocks of a fixed number of transcripts.
+Each block records its offset, its first start coordinate, and the running maximum of the end
+coordinates up to the block, so that queries only decode the blocks that may overlap the
+queried region.
+
+Queries return lightweight `CompactTranscript` views, which can be converted back into
+transcripts. Attributes and exon features other than the coding region are not stored.
+*/
+use std::cmp::{max, min};
+use std::collections::HashMap;
+use std::mem;
+
+use {AnnotationSet, Coord, Gene, Strand, TBuilder, Transcript};
+
+
+/// Number of transcripts in each encoded block.
+const BLOCK_LEN: usize = 64;
+
+/// Flag set for entries with a gene identifier.
+const HAS_GENE_ID: u8 = 1;
+
+/// Flag set for entries with a coding region.
+const HAS_CODING: u8 = 1 << 1;
+
+/// Read-only, compactly encoded collection of transcript intervals.
+#[derive(Debug, Clone, Default)]
+pub struct CompactAnnotation {
+    seqs: Vec<CompactSeq>,
+    seq_index: HashMap<String, usize>,
+    transcript_ids: StringArena,
+    gene_ids: StringArena,
+    num_transcripts: usize,
+}
+
+impl CompactAnnotation {
+
+    /// Creates a compact annotation from the transcripts of the given genes.
+    ///
+    /// The genes are consumed one at a time, so they may be streamed from a reader without
+    /// keeping all of them in memory. Transcripts whose exons are unsorted, overlapping, or
+    /// outside of the transcript, or whose coding regions are outside of the transcript, can
+    /// not be encoded and are skipped.
+    pub fn from_genes<I>(genes: I) -> Self
+        where I: IntoIterator<Item=Gene>
+    {
+        let mut builder = Builder::default();
+        for gene in genes {
+            builder.add_gene(&gene);
+        }
+        builder.build()
+    }
+
+    /// Returns the number of stored transcripts.
+    pub fn len(&self) -> usize {
+        self.num_transcripts
+    }
+
+    /// Returns whether no transcripts are stored.
+    pub fn is_empty(&self) -> bool {
+        self.num_transcripts == 0
+    }
+
+    /// Returns the names of the sequences with stored transcripts, sorted.
+    pub fn seq_names(&self) -> Vec<&str> {
+        self.seqs.iter().map(|seq| seq.name.as_str()).collect()
+    }
+
+    /// Returns the approximate size of the stored data in bytes.
+    pub fn approx_bytes(&self) -> usize {
+        let seqs = self.seqs.iter()
+            .map(|seq| {
+                mem::size_of::<CompactSeq>() + seq.name.capacity() + seq.data.capacity()
+                    + seq.blocks.capacity() * mem::size_of::<Block>()
+            })
+            .fold(0, |acc, size| acc + size);
+        mem::size_of::<Self>() + seqs + self.transcript_ids.approx_bytes()
+            + self.gene_ids.approx_bytes()
+            + self.seq_index.capacity() * (mem::size_of::<String>() + mem::size_of::<usize>())
+    }
+
+    /// Returns all transcripts of the given sequence, sorted by their coordinates.
+    pub fn transcripts(&self, seq_name: &str) -> Vec<CompactTranscript> {
+        match self.seq_index.get(seq_name) {
+            Some(&idx) => {
+                let seq = &self.seqs[idx];
+                (0..seq.blocks.len())
+                    .flat_map(|bidx| self.decode_block(seq, bidx).into_iter())
+                    .collect()
+            },
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns the transcripts overlapping the given zero-based, half-open region, sorted by
+    /// their coordinates.
+    pub fn find_overlapping_transcripts(&self, seq_name: &str, start: u64, end: u64)
+        -> Vec<CompactTranscript>
+    {
+        let seq = match self.seq_index.get(seq_name) {
+            Some(&idx) => &self.seqs[idx],
+            None => return Vec::new(),
+        };
+        // number of blocks starting before the end of the region
+        let (mut lo, mut hi) = (0, seq.blocks.len());
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if seq.blocks[mid].first_start < end {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        let mut blocks = Vec::new();
+        let mut bidx = lo;
+        while bidx > 0 {
+            bidx -= 1;
+            if seq.blocks[bidx].max_end <= start {
+                break;
+            }
+            blocks.push(bidx);
+        }
+        blocks.into_iter().rev()
+            .flat_map(|bidx| self.decode_block(seq, bidx).into_iter())
+            .filter(|trx| trx.start < end && trx.end > start)
+            .collect()
+    }
+
+    /// Decodes all entries of the given block.
+    fn decode_block<'a>(&'a self, seq: &'a CompactSeq, bidx: usize)
+        -> Vec<CompactTranscript<'a>>
+    {
+        let block = &seq.blocks[bidx];
+        let num_entries = min(seq.len - bidx * BLOCK_LEN, BLOCK_LEN);
+        let mut pos = block.offset;
+        let mut prev_start = block.first_start;
+        let mut entries = Vec::with_capacity(num_entries);
+        for _ in 0..num_entries {
+            let start = prev_start + read_varint(&seq.data, &mut pos);
+            prev_start = start;
+            let end = start + read_varint(&seq.data, &mut pos);
+            let strand = match seq.data[pos] {
+                0 => Strand::Forward,
+                1 => Strand::Reverse,
+                _ => Strand::Unknown,
+            };
+            let flags = seq.data[pos + 1];
+            pos += 2;
+            let id = self.transcript_ids.get(read_varint(&seq.data, &mut pos) as usize);
+            let gene_id =
+                if flags & HAS_GENE_ID != 0 {
+                    Some(self.gene_ids.get(read_varint(&seq.data, &mut pos) as usize))
+                } else {
+                    None
+                };
+            let num_exons = read_varint(&seq.data, &mut pos);
+            let mut exons = Vec::with_capacity(num_exons as usize);
+            let mut prev_end = start;
+            for _ in 0..num_exons {
+                let exon_start = prev_end + read_varint(&seq.data, &mut pos);
+                prev_end = exon_start + read_varint(&seq.data, &mut pos);
+                exons.push((exon_start, prev_end));
+            }
+            let coding =
+                if flags & HAS_CODING != 0 {
+                    let coding_start = start + read_varint(&seq.data, &mut pos);
+                    Some((coding_start, coding_start + read_varint(&seq.data, &mut pos)))
+                } else {
+                    None
+                };
+            entries.push(CompactTranscript {
+                seq_name: seq.name.as_str(),
+                start: start,
+                end: end,
+                strand: strand,
+                id: id,
+                gene_id: gene_id,
+                exons: exons,
+                coding: coding,
+            });
+        }
+        entries
+    }
+}
+
+impl AnnotationSet {
+
+    /// Creates a compact copy of the transcript intervals of the set.
+    pub fn to_compact(&self) -> CompactAnnotation {
+        let mut builder = Builder::default();
+        for gene in self.genes() {
+            builder.add_gene(gene);
+        }
+        builder.build()
+    }
+}
+
+/// Transcript decoded from a compact annotation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompactTranscript<'a> {
+    seq_name: &'a str,
+    start: u64,
+    end: u64,
+    strand: Strand,
+    id: &'a str,
+    gene_id: Option<&'a str>,
+    exons: Vec<Coord<u64>>,
+    coding: Option<Coord<u64>>,
+}
+
+impl<'a> CompactTranscript<'a> {
+
+    /// Returns the sequence name.
+    pub fn seq_name(&self) -> &'a str {
+        self.seq_name
+    }
+
+    /// Returns the 5'-most coordinate.
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// Returns the 3'-most coordinate.
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// Returns a reference of the strand.
+    pub fn strand(&self) -> &Strand {
+        &self.strand
+    }
+
+    /// Returns the transcript identifier.
+    pub fn id(&self) -> &'a str {
+        self.id
+    }
+
+    /// Returns the gene identifier.
+    pub fn gene_id(&self) -> Option<&'a str> {
+        self.gene_id
+    }
+
+    /// Returns the exon coordinates.
+    pub fn exon_coords(&self) -> &[Coord<u64>] {
+        self.exons.as_slice()
+    }
+
+    /// Returns the coding region, including the stop codon.
+    pub fn coding_coord(&self) -> Option<Coord<u64>> {
+        self.coding
+    }
+
+    /// Builds a transcript from the stored values.
+    ///
+    /// Exon features are inferred from the coding region, and attributes are left empty.
+    pub fn to_transcript(&self) -> ::Result<Transcript> {
+        let mut builder = TBuilder::new(self.seq_name, self.start, self.end)
+            .strand(self.strand)
+            .id(self.id)
+            .coords(self.exons.clone(), self.coding)
+            .coding_incl_stop(true);
+        if let Some(gene_id) = self.gene_id {
+            builder = builder.gene_id(gene_id);
+        }
+        builder.build()
+    }
+}
+
+/// Encoded transcripts of a single sequence.
+#[derive(Debug, Clone)]
+struct CompactSeq {
+    name: String,
+    data: Vec<u8>,
+    blocks: Vec<Block>,
+    len: usize,
+}
+
+/// Position and bounds of a block of encoded transcripts.
+#[derive(Debug, Clone)]
+struct Block {
+    offset: usize,
+    first_start: u64,
+    // running maximum of the end coordinates of all entries up to this block
+    max_end: u64,
+}
+
+/// Concatenated strings, addressed by their insertion index.
+#[derive(Debug, Clone, Default)]
+struct StringArena {
+    data: String,
+    ends: Vec<u32>,
+}
+
+impl StringArena {
+
+    fn push(&mut self, value: &str) -> usize {
+        self.data.push_str(value);
+        self.ends.push(self.data.len() as u32);
+        self.ends.len() - 1
+    }
+
+    fn get(&self, idx: usize) -> &str {
+        let start = if idx == 0 { 0 } else { self.ends[idx - 1] as usize };
+        &self.data[start..self.ends[idx] as usize]
+    }
+
+    fn approx_bytes(&self) -> usize {
+        self.data.capacity() + self.ends.capacity() * mem::size_of::<u32>()
+    }
+}
+
+/// Transcript values collected before encoding.
+struct RawEntry {
+    start: u64,
+    end: u64,
+    strand: u8,
+    id_idx: usize,
+    gene_idx: Option<usize>,
+    exons: Vec<Coord<u64>>,
+    coding: Option<Coord<u64>>,
+}
+
+/// Helper struct for collecting and encoding transcripts.
+#[derive(Default)]
+struct Builder {
+    entries: HashMap<String, Vec<RawEntry>>,
+    transcript_ids: StringArena,
+    gene_ids: StringArena,
+}
+
+impl Builder {
+
+    fn add_gene(&mut self, gene: &Gene) {
+        let gene_idx = gene.id().map(|gid| self.gene_ids.push(gid));
+        for (tid, trx) in gene.transcripts() {
+            let exons = trx.exons().iter()
+                .map(|exon| (exon.start(), exon.end()))
+                .collect::<Vec<Coord<u64>>>();
+            let coding = trx.coding_coord(true);
+            if !is_encodable((trx.start(), trx.end()), &exons, coding) {
+                continue;
+            }
+            let entry = RawEntry {
+                start: trx.start(),
+                end: trx.end(),
+                strand: match trx.strand() {
+                    &Strand::Forward => 0,
+                    &Strand::Reverse => 1,
+                    &Strand::Unknown => 2,
+                },
+                id_idx: self.transcript_ids.push(tid),
+                gene_idx: gene_idx,
+                exons: exons,
+                coding: coding,
+            };
+            self.entries.entry(trx.seq_name().to_owned()).or_insert_with(Vec::new).push(entry);
+        }
+    }
+
+    fn build(self) -> CompactAnnotation {
+        let mut seq_entries = self.entries.into_iter().collect::<Vec<(String, Vec<RawEntry>)>>();
+        seq_entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut seqs = Vec::with_capacity(seq_entries.len());
+        let mut seq_index = HashMap::new();
+        let mut num_transcripts = 0;
+        for (name, mut entries) in seq_entries {
+            entries.sort_by_key(|entry| (entry.start, entry.end));
+            num_transcripts += entries.len();
+            let _ = seq_index.insert(name.clone(), seqs.len());
+            seqs.push(encode_seq(name, &entries));
+        }
+
+        let mut transcript_ids = self.transcript_ids;
+        let mut gene_ids = self.gene_ids;
+        transcript_ids.data.shrink_to_fit();
+        transcript_ids.ends.shrink_to_fit();
+        gene_ids.data.shrink_to_fit();
+        gene_ids.ends.shrink_to_fit();
+        CompactAnnotation {
+            seqs: seqs,
+            seq_index: seq_index,
+            transcript_ids: transcript_ids,
+            gene_ids: gene_ids,
+            num_transcripts: num_transcripts,
+        }
+    }
+}
+
+/// Helper function for checking that all coordinates of a transcript can be encoded as
+/// offsets from the preceding coordinates.
+fn is_encodable(coord: Coord<u64>, exons: &[Coord<u64>], coding: Option<Coord<u64>>) -> bool {
+    let mut prev_end = coord.0;
+    for &(exon_start, exon_end) in exons {
+        if exon_start < prev_end || exon_end < exon_start {
+            return false;
+        }
+        prev_end = exon_end;
+    }
+    let coding_ok = coding
+        .map(|(start, end)| coord.0 <= start && start <= end)
+        .unwrap_or(true);
+    coord.0 <= coord.1 && prev_end <= coord.1 && coding_ok
+}
+
+/// Helper function for encoding the sorted transcripts of a sequence.
+///
+/// The coordinates of the transcripts must have been checked with `is_encodable`.
+fn encode_seq(name: String, entries: &[RawEntry]) -> CompactSeq {
+    let mut data = Vec::new();
+    let mut blocks = Vec::with_capacity((entries.len() + BLOCK_LEN - 1) / BLOCK_LEN);
+    let mut max_end = 0;
+    for chunk in entries.chunks(BLOCK_LEN) {
+        let offset = data.len();
+        let first_start = chunk[0].start;
+        let mut prev_start = first_start;
+        for entry in chunk {
+            write_varint(&mut data, entry.start - prev_start);
+            prev_start = entry.start;
+            write_varint(&mut data, entry.end - entry.start);
+            let mut flags = 0;
+            if entry.gene_idx.is_some() {
+                flags |= HAS_GENE_ID;
+            }
+            if entry.coding.is_some() {
+                flags |= HAS_CODING;
+            }
+            data.push(entry.strand);
+            data.push(flags);
+            write_varint(&mut data, entry.id_idx as u64);
+            if let Some(gene_idx) = entry.gene_idx {
+                write_varint(&mut data, gene_idx as u64);
+            }
+            write_varint(&mut data, entry.exons.len() as u64);
+            let mut prev_end = entry.start;
+            for &(exon_start, exon_end) in entry.exons.iter() {
+                write_varint(&mut data, exon_start - prev_end);
+                write_varint(&mut data, exon_end - exon_start);
+                prev_end = exon_end;
+            }
+            if let Some((coding_start, coding_end)) = entry.coding {
+                write_varint(&mut data, coding_start - entry.start);
+                write_varint(&mut data, coding_end - coding_start);
+            }
+            max_end = max(max_end, entry.end);
+        }
+        blocks.push(Block { offset: offset, first_start: first_start, max_end: max_end });
+    }
+    data.shrink_to_fit();
+    CompactSeq { name: name, data: data, blocks: blocks, len: entries.len() }
+}
+
+/// Helper function for writing LEB128 variable-length integers.
+fn write_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+/// Helper function for reading LEB128 variable-length integers, advancing the position.
+fn read_varint(buf: &[u8], pos: &mut usize) -> u64 {
+    let mut value = 0;
+    let mut shift = 0;
+    loop {
+        let byte = buf[*pos];
+        *pos += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return value;
+        }
+        shift += 7;
+    }
+}
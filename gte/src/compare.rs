@@ -0,0 +1,221 @@
+/*! Classification of transcripts against a reference annotation.
+
+Assembled transcripts are compared with the reference transcripts they overlap and are given
+the class codes used by gffcompare. Each query transcript is classified against every
+overlapping reference transcript, and the best match is reported, preferring the class codes in
+the order listed in `ClassCode`. Ties are broken in favor of the reference transcript sharing
+more introns with the query, then more exonic bases, then the one with the earliest start.
+
+Transcripts on the same strand, or where either strand is unknown, are compared by their intron
+chains, that is, the gaps between their exons. Single-exon transcripts have no intron chain and
+match each other when their overlap covers at least 80% of the longer transcript.
+*/
+use std::cmp::{max, min, Reverse};
+
+use {Coord, GenomeAnnotation, Strand, Transcript};
+use utils::OptionDeref;
+
+
+/// Minimum fraction of the longer transcript covered by the overlap of two single-exon
+/// transcripts for them to match, as a ratio of numerator and denominator.
+const SINGLE_EXON_MATCH_RATIO: (u64, u64) = (4, 5);
+
+/// Relationships between a query transcript and a reference transcript.
+///
+/// The variants are ordered from the best to the worst match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ClassCode {
+    /// `=`: the intron chains are identical, or both single-exon transcripts match.
+    Exact,
+    /// `c`: the query is contained in the reference, with its intron chain being part of the
+    /// reference's intron chain.
+    Contained,
+    /// `k`: the reference is contained in the query, with its intron chain being part of the
+    /// query's intron chain.
+    Containment,
+    /// `j`: the transcripts share at least one intron.
+    JunctionMatch,
+    /// `o`: the transcripts share exonic bases but no introns.
+    ExonicOverlap,
+    /// `x`: the transcripts share exonic bases on opposite strands.
+    OppositeStrand,
+    /// `i`: the query lies entirely within an intron of the reference.
+    Intronic,
+    /// `u`: the query does not match any reference transcript.
+    Unknown,
+}
+
+impl ClassCode {
+
+    /// Returns the character used by gffcompare for the class code.
+    pub fn code(&self) -> char {
+        match *self {
+            ClassCode::Exact => '=',
+            ClassCode::Contained => 'c',
+            ClassCode::Containment => 'k',
+            ClassCode::JunctionMatch => 'j',
+            ClassCode::ExonicOverlap => 'o',
+            ClassCode::OppositeStrand => 'x',
+            ClassCode::Intronic => 'i',
+            ClassCode::Unknown => 'u',
+        }
+    }
+}
+
+/// Best match of a query transcript among the reference transcripts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranscriptMatch {
+    query_id: Option<String>,
+    class_code: ClassCode,
+    ref_gene_id: Option<String>,
+    ref_transcript_id: Option<String>,
+    shared_introns: usize,
+    overlap: u64,
+}
+
+impl TranscriptMatch {
+
+    /// Returns the identifier of the query transcript.
+    pub fn query_id(&self) -> Option<&str> {
+        self.query_id.as_deref()
+    }
+
+    /// Returns the class code of the match.
+    pub fn class_code(&self) -> ClassCode {
+        self.class_code
+    }
+
+    /// Returns the gene identifier of the matching reference transcript.
+    pub fn ref_gene_id(&self) -> Option<&str> {
+        self.ref_gene_id.as_deref()
+    }
+
+    /// Returns the identifier of the matching reference transcript.
+    ///
+    /// The returned value is `None` for `ClassCode::Unknown` matches.
+    pub fn ref_transcript_id(&self) -> Option<&str> {
+        self.ref_transcript_id.as_deref()
+    }
+
+    /// Returns the number of introns shared by the query and the reference transcript.
+    pub fn shared_introns(&self) -> usize {
+        self.shared_introns
+    }
+
+    /// Returns the number of exonic bases shared by the query and the reference transcript.
+    pub fn overlap(&self) -> u64 {
+        self.overlap
+    }
+}
+
+impl GenomeAnnotation {
+
+    /// Classifies the given transcript against the overlapping transcripts of the annotation.
+    pub fn classify_transcript(&self, query: &Transcript) -> TranscriptMatch {
+        let best = self.find_overlapping_transcripts(query.seq_name(), query.start(), query.end())
+            .into_iter()
+            .filter_map(|reference| {
+                classify(query, reference).map(|(code, shared, overlap)| {
+                    (code, Reverse(shared), Reverse(overlap), reference)
+                })
+            })
+            .min_by_key(|&(code, shared, overlap, reference)| {
+                (code, shared, overlap, reference.start())
+            });
+        match best {
+            Some((code, Reverse(shared), Reverse(overlap), reference)) => TranscriptMatch {
+                query_id: query.id().map(|id| id.to_owned()),
+                class_code: code,
+                ref_gene_id: reference.gene_id().map(|id| id.to_owned()),
+                ref_transcript_id: reference.id().map(|id| id.to_owned()),
+                shared_introns: shared,
+                overlap: overlap,
+            },
+            None => TranscriptMatch {
+                query_id: query.id().map(|id| id.to_owned()),
+                class_code: ClassCode::Unknown,
+                ref_gene_id: None,
+                ref_transcript_id: None,
+                shared_introns: 0,
+                overlap: 0,
+            },
+        }
+    }
+
+    /// Classifies each of the given transcripts, returning the matches in the same order.
+    pub fn classify_transcripts<'a, I>(&self, queries: I) -> Vec<TranscriptMatch>
+        where I: IntoIterator<Item=&'a Transcript>
+    {
+        queries.into_iter().map(|query| self.classify_transcript(query)).collect()
+    }
+}
+
+/// Helper function for classifying a query transcript against a single reference transcript.
+///
+/// Returns the class code, the number of shared introns, and the number of shared exonic
+/// bases, or `None` if the transcripts do not share exonic bases and the query is not intronic.
+fn classify(query: &Transcript, reference: &Transcript) -> Option<(ClassCode, usize, u64)> {
+    let query_exons = exon_coords(query);
+    let ref_exons = exon_coords(reference);
+    let same_strand = query.strand() == reference.strand()
+        || query.strand() == &Strand::Unknown || reference.strand() == &Strand::Unknown;
+
+    let overlap = exonic_overlap(&query_exons, &ref_exons);
+    if overlap == 0 {
+        let within_intron = reference.intron_coords().into_iter()
+            .any(|(start, end)| start <= query.start() && query.end() <= end);
+        return if same_strand && within_intron {
+            Some((ClassCode::Intronic, 0, 0))
+        } else {
+            None
+        };
+    }
+    if !same_strand {
+        return Some((ClassCode::OppositeStrand, 0, overlap));
+    }
+
+    let query_introns = query.intron_coords();
+    let ref_introns = reference.intron_coords();
+    let shared = query_introns.iter().filter(|intron| ref_introns.contains(intron)).count();
+    let single_exon_match = query_introns.is_empty() && ref_introns.is_empty() && {
+        let longer = max(query.end() - query.start(), reference.end() - reference.start());
+        overlap * SINGLE_EXON_MATCH_RATIO.1 >= longer * SINGLE_EXON_MATCH_RATIO.0
+    };
+
+    let code = if (!query_introns.is_empty() && query_introns == ref_introns)
+        || single_exon_match
+    {
+        ClassCode::Exact
+    } else if reference.is_compatible_with(&query_exons) {
+        ClassCode::Contained
+    } else if query.is_compatible_with(&ref_exons) {
+        ClassCode::Containment
+    } else if shared > 0 {
+        ClassCode::JunctionMatch
+    } else {
+        ClassCode::ExonicOverlap
+    };
+    Some((code, shared, overlap))
+}
+
+/// Helper function for getting the exon coordinates of a transcript.
+fn exon_coords(transcript: &Transcript) -> Vec<Coord<u64>> {
+    transcript.exons().iter().map(|exon| (exon.start(), exon.end())).collect()
+}
+
+/// Helper function for counting the bases shared by two sorted lists of exon coordinates.
+fn exonic_overlap(a: &[Coord<u64>], b: &[Coord<u64>]) -> u64 {
+    let (mut i, mut j, mut total) = (0, 0, 0);
+    while i < a.len() && j < b.len() {
+        let (start, end) = (max(a[i].0, b[j].0), min(a[i].1, b[j].1));
+        if start < end {
+            total += end - start;
+        }
+        if a[i].1 <= b[j].1 {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    total
+}
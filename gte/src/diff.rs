@@ -0,0 +1,293 @@
+/*! Differences between two versions of a gene.
+
+The differences are computed per transcript, for transcripts present in both versions of the
+gene. The exons of each transcript are swept in coordinate order, where overlapping exons are
+considered to be the same exon. These exons are reported as shifted when their boundaries
+differ, while exons without an overlapping counterpart are reported as gained or lost. Changes
+in the coding region, excluding the stop codon, are reported along with the exons.
+
+The differences can be written as text, for human-readable changelogs, or as JSON. All
+coordinates are zero-based, half-open.
+*/
+use std::fmt;
+
+use {Coord, Gene, Transcript, DEF_ID};
+use utils::{json_str, OptionDeref};
+
+
+/// Change of a single exon.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExonChange {
+    /// Exon present only in the new transcript.
+    Gained(Coord<u64>),
+    /// Exon present only in the old transcript.
+    Lost(Coord<u64>),
+    /// Exon whose boundaries differ between the old and new transcripts.
+    Shifted {
+        /// Exon coordinate in the old transcript.
+        old: Coord<u64>,
+        /// Exon coordinate in the new transcript.
+        new: Coord<u64>,
+    },
+}
+
+/// Changes of a transcript present in both versions of a gene.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranscriptDiff {
+    transcript_id: String,
+    exon_changes: Vec<ExonChange>,
+    coding_change: Option<(Option<Coord<u64>>, Option<Coord<u64>>)>,
+}
+
+impl TranscriptDiff {
+
+    /// Compares two versions of a transcript with the given identifier.
+    pub fn between(transcript_id: &str, old: &Transcript, new: &Transcript) -> Self {
+        let old_coding = old.coding_coord(false);
+        let new_coding = new.coding_coord(false);
+        TranscriptDiff {
+            transcript_id: transcript_id.to_owned(),
+            exon_changes: exon_changes(&exon_coords(old), &exon_coords(new)),
+            coding_change: if old_coding != new_coding {
+                Some((old_coding, new_coding))
+            } else {
+                None
+            },
+        }
+    }
+
+    /// Returns the transcript identifier.
+    pub fn transcript_id(&self) -> &str {
+        self.transcript_id.as_str()
+    }
+
+    /// Returns the exon changes, in coordinate order.
+    pub fn exon_changes(&self) -> &[ExonChange] {
+        self.exon_changes.as_slice()
+    }
+
+    /// Returns the old and new coding regions, if they differ.
+    ///
+    /// A `None` coding region denotes a noncoding transcript.
+    pub fn coding_change(&self) -> Option<(Option<Coord<u64>>, Option<Coord<u64>>)> {
+        self.coding_change
+    }
+
+    /// Returns whether the two versions of the transcript are the same.
+    pub fn is_empty(&self) -> bool {
+        self.exon_changes.is_empty() && self.coding_change.is_none()
+    }
+}
+
+/// Changes between two versions of a gene.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneDiff {
+    gene_id: Option<String>,
+    old_region: (String, u64, u64),
+    new_region: (String, u64, u64),
+    gained_transcripts: Vec<String>,
+    lost_transcripts: Vec<String>,
+    transcript_diffs: Vec<TranscriptDiff>,
+}
+
+impl GeneDiff {
+
+    /// Compares two versions of a gene.
+    ///
+    /// Transcripts are matched by their identifiers. The gene identifier is taken from the new
+    /// gene, or from the old gene if the new one has none.
+    pub fn between(old: &Gene, new: &Gene) -> Self {
+        let lost_transcripts = old.transcripts().keys()
+            .filter(|tid| !new.transcripts().contains_key(*tid))
+            .cloned()
+            .collect();
+        let mut gained_transcripts = Vec::new();
+        let mut transcript_diffs = Vec::new();
+        for (tid, new_trx) in new.transcripts().iter() {
+            match old.transcripts().get(tid) {
+                Some(old_trx) => {
+                    let trx_diff = TranscriptDiff::between(tid, old_trx, new_trx);
+                    if !trx_diff.is_empty() {
+                        transcript_diffs.push(trx_diff);
+                    }
+                },
+                None => gained_transcripts.push(tid.clone()),
+            }
+        }
+        GeneDiff {
+            gene_id: new.id().or(old.id()).map(|id| id.to_owned()),
+            old_region: (old.seq_name().to_owned(), old.start(), old.end()),
+            new_region: (new.seq_name().to_owned(), new.start(), new.end()),
+            gained_transcripts: gained_transcripts,
+            lost_transcripts: lost_transcripts,
+            transcript_diffs: transcript_diffs,
+        }
+    }
+
+    /// Returns the gene identifier.
+    pub fn gene_id(&self) -> Option<&str> {
+        self.gene_id.as_deref()
+    }
+
+    /// Returns the sequence name, start, and end coordinates of the old gene.
+    pub fn old_region(&self) -> (&str, u64, u64) {
+        (self.old_region.0.as_str(), self.old_region.1, self.old_region.2)
+    }
+
+    /// Returns the sequence name, start, and end coordinates of the new gene.
+    pub fn new_region(&self) -> (&str, u64, u64) {
+        (self.new_region.0.as_str(), self.new_region.1, self.new_region.2)
+    }
+
+    /// Returns the identifiers of transcripts present only in the new gene.
+    pub fn gained_transcripts(&self) -> &[String] {
+        self.gained_transcripts.as_slice()
+    }
+
+    /// Returns the identifiers of transcripts present only in the old gene.
+    pub fn lost_transcripts(&self) -> &[String] {
+        self.lost_transcripts.as_slice()
+    }
+
+    /// Returns the changes of transcripts present in both genes, for changed transcripts only.
+    pub fn transcript_diffs(&self) -> &[TranscriptDiff] {
+        self.transcript_diffs.as_slice()
+    }
+
+    /// Returns whether the two versions of the gene are the same.
+    pub fn is_empty(&self) -> bool {
+        self.old_region == self.new_region && self.gained_transcripts.is_empty()
+            && self.lost_transcripts.is_empty() && self.transcript_diffs.is_empty()
+    }
+
+    /// Returns the changes as a JSON object.
+    pub fn to_json(&self) -> String {
+        let region_json = |region: &(String, u64, u64)| {
+            format!("{{\"seq_name\":{},\"start\":{},\"end\":{}}}",
+                    json_str(&region.0), region.1, region.2)
+        };
+        let ids_json = |ids: &[String]| {
+            format!("[{}]", ids.iter().map(|id| json_str(id)).collect::<Vec<String>>().join(","))
+        };
+        let trxs_json = self.transcript_diffs.iter()
+            .map(|trx_diff| {
+                let exons_json = trx_diff.exon_changes.iter()
+                    .map(|change| match change {
+                        &ExonChange::Gained(coord) =>
+                            format!("{{\"change\":\"gained\",\"new\":{}}}", json_coord(coord)),
+                        &ExonChange::Lost(coord) =>
+                            format!("{{\"change\":\"lost\",\"old\":{}}}", json_coord(coord)),
+                        &ExonChange::Shifted { old, new } =>
+                            format!("{{\"change\":\"shifted\",\"old\":{},\"new\":{}}}",
+                                    json_coord(old), json_coord(new)),
+                    })
+                    .collect::<Vec<String>>()
+                    .join(",");
+                let coding_json = match trx_diff.coding_change {
+                    Some((old, new)) => format!("{{\"old\":{},\"new\":{}}}",
+                                                json_opt_coord(old), json_opt_coord(new)),
+                    None => "null".to_owned(),
+                };
+                format!("{{\"transcript_id\":{},\"exons\":[{}],\"coding\":{}}}",
+                        json_str(&trx_diff.transcript_id), exons_json, coding_json)
+            })
+            .collect::<Vec<String>>()
+            .join(",");
+
+        format!("{{\"gene_id\":{},\"old\":{},\"new\":{},\"gained_transcripts\":{},\
+                 \"lost_transcripts\":{},\"transcripts\":[{}]}}",
+                self.gene_id.as_ref().map(|id| json_str(id)).unwrap_or("null".to_owned()),
+                region_json(&self.old_region), region_json(&self.new_region),
+                ids_json(&self.gained_transcripts), ids_json(&self.lost_transcripts),
+                trxs_json)
+    }
+}
+
+/// Writes the changes as text, one change per line.
+impl fmt::Display for GeneDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "gene {} {}:{}-{}", self.gene_id().unwrap_or(DEF_ID),
+               self.old_region.0, self.old_region.1, self.old_region.2)?;
+        if self.old_region != self.new_region {
+            write!(f, " -> {}:{}-{}", self.new_region.0, self.new_region.1, self.new_region.2)?;
+        }
+        for tid in self.gained_transcripts.iter() {
+            write!(f, "\n+ transcript {}", tid)?;
+        }
+        for tid in self.lost_transcripts.iter() {
+            write!(f, "\n- transcript {}", tid)?;
+        }
+        for trx_diff in self.transcript_diffs.iter() {
+            write!(f, "\n~ transcript {}", trx_diff.transcript_id)?;
+            for change in trx_diff.exon_changes.iter() {
+                match change {
+                    &ExonChange::Gained((start, end)) =>
+                        write!(f, "\n  + exon {}-{}", start, end)?,
+                    &ExonChange::Lost((start, end)) =>
+                        write!(f, "\n  - exon {}-{}", start, end)?,
+                    &ExonChange::Shifted { old, new } =>
+                        write!(f, "\n  ~ exon {}-{} -> {}-{}", old.0, old.1, new.0, new.1)?,
+                }
+            }
+            if let Some((old, new)) = trx_diff.coding_change {
+                write!(f, "\n  ~ CDS {} -> {}", text_opt_coord(old), text_opt_coord(new))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the sorted exon coordinates of the given transcript.
+fn exon_coords(transcript: &Transcript) -> Vec<Coord<u64>> {
+    let mut coords = transcript.exons().iter()
+        .map(|exon| (exon.start(), exon.end()))
+        .collect::<Vec<Coord<u64>>>();
+    coords.sort();
+    coords
+}
+
+/// Computes the changes between two sorted lists of exon coordinates.
+fn exon_changes(old: &[Coord<u64>], new: &[Coord<u64>]) -> Vec<ExonChange> {
+    let mut changes = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i == old.len() {
+            changes.push(ExonChange::Gained(new[j]));
+            j += 1;
+        } else if j == new.len() {
+            changes.push(ExonChange::Lost(old[i]));
+            i += 1;
+        } else if old[i].0 < new[j].1 && new[j].0 < old[i].1 {
+            if old[i] != new[j] {
+                changes.push(ExonChange::Shifted { old: old[i], new: new[j] });
+            }
+            i += 1;
+            j += 1;
+        } else if old[i].1 <= new[j].0 {
+            changes.push(ExonChange::Lost(old[i]));
+            i += 1;
+        } else {
+            changes.push(ExonChange::Gained(new[j]));
+            j += 1;
+        }
+    }
+    changes
+}
+
+/// Helper function for writing optional coordinates as text.
+fn text_opt_coord(coord: Option<Coord<u64>>) -> String {
+    match coord {
+        Some((start, end)) => format!("{}-{}", start, end),
+        None => "none".to_owned(),
+    }
+}
+
+/// Helper function for writing coordinates as JSON arrays.
+fn json_coord(coord: Coord<u64>) -> String {
+    format!("[{},{}]", coord.0, coord.1)
+}
+
+/// Helper function for writing optional coordinates as JSON arrays or nulls.
+fn json_opt_coord(coord: Option<Coord<u64>>) -> String {
+    coord.map(json_coord).unwrap_or("null".to_owned())
+}
@@ -0,0 +1,291 @@
+/*! Protein domain tables and their mapping onto the genome.
+
+The domain tables are tab-separated files with one domain per line, in these columns:
+
+1. protein identifier, which must be the identifier of the encoding transcript
+2. domain identifier, e.g. a Pfam accession such as `PF00069`
+3. first residue of the domain (one-based)
+4. last residue of the domain (one-based)
+5. domain name (optional)
+
+Empty lines and lines starting with `#` are ignored. Residue coordinates are converted into
+zero-based, half-open ranges, the convention used by `Transcript::protein_to_genomic`.
+
+Mapped domains may be written as BED12 lines using `BedWriter::write_domain`, or as GFF3 records
+attached to their transcripts using `Gff3Writer::write_domain`.
+*/
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::io::{self, BufRead, BufReader};
+use std::ops::Range;
+use std::path::Path;
+use std::str::FromStr;
+
+use {AnnotationSet, Coord, Strand, Transcript};
+use utils::OptionDeref;
+
+
+quick_error! {
+    /// Errors that occur when reading protein domain tables.
+    #[derive(Debug)]
+    pub enum DomainError {
+        /// Occurs when a domain line can not be parsed.
+        InvalidRecord(line_num: usize) {
+            description("invalid protein domain record")
+            display(self_) -> ("{}, line: {}", self_.description(), line_num)
+        }
+        /// Errors propagated from reading the underlying file.
+        Io(err: io::Error) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// Protein domain in residue coordinates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DomainRecord {
+    protein_id: String,
+    domain_id: String,
+    domain_name: Option<String>,
+    residues: Range<u64>,
+}
+
+impl DomainRecord {
+
+    /// Creates a new domain spanning the given zero-based, half-open residue range.
+    pub fn new<T, U>(protein_id: T, domain_id: U, residues: Range<u64>) -> Self
+        where T: Into<String>, U: Into<String>
+    {
+        DomainRecord {
+            protein_id: protein_id.into(),
+            domain_id: domain_id.into(),
+            domain_name: None,
+            residues: residues,
+        }
+    }
+
+    /// Returns the protein identifier.
+    pub fn protein_id(&self) -> &str {
+        self.protein_id.as_str()
+    }
+
+    /// Returns the domain identifier.
+    pub fn domain_id(&self) -> &str {
+        self.domain_id.as_str()
+    }
+
+    /// Returns the domain name.
+    pub fn domain_name(&self) -> Option<&str> {
+        self.domain_name.as_deref()
+    }
+
+    /// Sets the domain name.
+    pub fn set_domain_name<T>(&mut self, domain_name: Option<T>)
+        where T: Into<String>
+    {
+        self.domain_name = domain_name.map(|v| v.into());
+    }
+
+    /// Returns the zero-based, half-open residue range of the domain.
+    pub fn residues(&self) -> Range<u64> {
+        self.residues.clone()
+    }
+
+    /// Parses a single domain line.
+    fn try_from_line(line: &str, line_num: usize) -> Result<Self, DomainError> {
+        let fields = line.split('\t').collect::<Vec<&str>>();
+        if fields.len() < 4 || fields[0].is_empty() || fields[1].is_empty() {
+            return Err(DomainError::InvalidRecord(line_num));
+        }
+        let parse_num = |idx: usize| {
+            u64::from_str(fields[idx]).map_err(|_| DomainError::InvalidRecord(line_num))
+        };
+        let first = parse_num(2)?;
+        let last = parse_num(3)?;
+        if first == 0 || first > last {
+            return Err(DomainError::InvalidRecord(line_num));
+        }
+        let mut record = DomainRecord::new(fields[0], fields[1], (first - 1)..last);
+        if let Some(name) = fields.get(4) {
+            if !name.is_empty() {
+                record.domain_name = Some((*name).to_owned());
+            }
+        }
+        Ok(record)
+    }
+}
+
+/// Protein domain table reader.
+pub struct Reader<R: io::Read> {
+    inner: BufReader<R>,
+    line_num: usize,
+}
+
+impl<R: io::Read> Reader<R> {
+
+    /// Creates a domain table reader from another reader.
+    pub fn from_reader(in_reader: R) -> Reader<R> {
+        Reader {
+            inner: BufReader::new(in_reader),
+            line_num: 0,
+        }
+    }
+
+    /// Creates an iterator of domain records.
+    pub fn records_stream(&mut self) -> DomainRecordsStream<R> {
+        DomainRecordsStream { reader: self }
+    }
+}
+
+impl Reader<fs::File> {
+
+    /// Creates a domain table reader that reads from the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        fs::File::open(path).map(Reader::from_reader)
+    }
+}
+
+/// Iterator over domain records.
+pub struct DomainRecordsStream<'a, R: 'a> where R: io::Read {
+    reader: &'a mut Reader<R>,
+}
+
+impl<'a, R> Iterator for DomainRecordsStream<'a, R> where R: io::Read {
+
+    type Item = ::Result<DomainRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            match self.reader.inner.read_line(&mut line) {
+                Ok(0) => return None,
+                Ok(_) => {},
+                Err(e) => return Some(Err(::Error::from(DomainError::from(e)))),
+            }
+            self.reader.line_num += 1;
+            let trimmed = line.trim_end_matches(&['\n', '\r'][..]);
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            return Some(DomainRecord::try_from_line(trimmed, self.reader.line_num)
+                        .map_err(::Error::from));
+        }
+    }
+}
+
+/// Protein domain mapped onto the genome.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProteinDomain {
+    seq_name: String,
+    strand: Strand,
+    transcript_id: String,
+    gene_id: Option<String>,
+    record: DomainRecord,
+    blocks: Vec<Coord<u64>>,
+}
+
+impl ProteinDomain {
+
+    /// Maps the given domain onto the genome using the coding region of the given transcript.
+    ///
+    /// An error is returned if the transcript has no coding region or if the domain extends
+    /// past its last residue.
+    pub fn from_transcript(record: DomainRecord, transcript: &Transcript) -> ::Result<Self> {
+        let blocks = transcript.protein_to_genomic(record.residues())?;
+        let transcript_id = transcript.id().unwrap_or(record.protein_id()).to_owned();
+        Ok(ProteinDomain::with_blocks(record, transcript, transcript_id, blocks))
+    }
+
+    fn with_blocks(record: DomainRecord, transcript: &Transcript, transcript_id: String,
+                   blocks: Vec<Coord<u64>>) -> Self
+    {
+        ProteinDomain {
+            seq_name: transcript.seq_name().to_owned(),
+            strand: *transcript.strand(),
+            transcript_id: transcript_id,
+            gene_id: transcript.gene_id().map(|gid| gid.to_owned()),
+            record: record,
+            blocks: blocks,
+        }
+    }
+
+    /// Returns the sequence name.
+    pub fn seq_name(&self) -> &str {
+        self.seq_name.as_str()
+    }
+
+    /// Returns a reference of the strand.
+    pub fn strand(&self) -> &Strand {
+        &self.strand
+    }
+
+    /// Returns the identifier of the transcript encoding the domain.
+    pub fn transcript_id(&self) -> &str {
+        self.transcript_id.as_str()
+    }
+
+    /// Returns the identifier of the gene of the transcript.
+    pub fn gene_id(&self) -> Option<&str> {
+        self.gene_id.as_deref()
+    }
+
+    /// Returns the domain record.
+    pub fn record(&self) -> &DomainRecord {
+        &self.record
+    }
+
+    /// Returns the genome-wise intervals encoding the domain, sorted and never spanning introns.
+    pub fn blocks(&self) -> &[Coord<u64>] {
+        self.blocks.as_slice()
+    }
+
+    /// Returns the genome-wise 5'-most coordinate of the domain.
+    pub fn start(&self) -> u64 {
+        self.blocks[0].0
+    }
+
+    /// Returns the genome-wise 3'-most coordinate of the domain.
+    pub fn end(&self) -> u64 {
+        self.blocks[self.blocks.len() - 1].1
+    }
+}
+
+impl AnnotationSet {
+
+    /// Maps the given domains onto the genome, using the transcripts whose identifiers match the
+    /// domain protein identifiers.
+    ///
+    /// Domains are returned in their input order. Domains whose transcripts are not present, are
+    /// noncoding, or are too short for the domain are returned separately as unmapped records,
+    /// since they usually come from a different annotation version.
+    pub fn map_domains<I>(&self, records: I) -> ::Result<(Vec<ProteinDomain>, Vec<DomainRecord>)>
+        where I: IntoIterator<Item=::Result<DomainRecord>>
+    {
+        let transcripts = self.genes().iter()
+            .flat_map(|gene| gene.transcripts().iter())
+            .map(|(tid, trx)| (tid.as_str(), trx))
+            .collect::<HashMap<&str, &Transcript>>();
+        let (mut mapped, mut unmapped) = (Vec::new(), Vec::new());
+        for result in records {
+            let record = result?;
+            let (tid, transcript) = match transcripts.get(record.protein_id()) {
+                Some(trx) => (record.protein_id().to_owned(), *trx),
+                None => {
+                    unmapped.push(record);
+                    continue;
+                },
+            };
+            match transcript.protein_to_genomic(record.residues()) {
+                Ok(blocks) =>
+                    mapped.push(ProteinDomain::with_blocks(record, transcript, tid, blocks)),
+                Err(_) => unmapped.push(record),
+            }
+        }
+        Ok((mapped, unmapped))
+    }
+}
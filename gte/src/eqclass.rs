@@ -0,0 +1,121 @@
+/*! Equivalence classes of transcripts.
+
+An equivalence class is the set of transcripts that are compatible with an aligned read, i.e.
+whose exon structures are consistent with the read's aligned blocks. Reads with the same
+equivalence class are indistinguishable for quantification purposes, so counting reads per
+equivalence class is the usual input for expectation-maximization quantifiers.
+
+Compatibility is checked against a per-gene index, which maps every intron to the transcripts
+that contain it. Spliced reads are then only checked against transcripts containing their first
+intron.
+*/
+use std::collections::HashMap;
+
+use {Coord, Gene, Transcript};
+
+
+/// Set of transcripts compatible with the same aligned blocks.
+///
+/// The transcript identifiers are sorted, so that equal classes compare and hash equally
+/// regardless of how they were created.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct EquivalenceClass {
+    transcript_ids: Vec<String>,
+}
+
+impl EquivalenceClass {
+
+    /// Creates an equivalence class from the given transcript identifiers.
+    pub fn new<I, T>(transcript_ids: I) -> Self
+        where I: IntoIterator<Item=T>, T: Into<String>
+    {
+        let mut ids = transcript_ids.into_iter().map(|id| id.into()).collect::<Vec<String>>();
+        ids.sort();
+        ids.dedup();
+        EquivalenceClass { transcript_ids: ids }
+    }
+
+    /// Returns the sorted identifiers of the transcripts in the class.
+    pub fn transcript_ids(&self) -> &[String] {
+        self.transcript_ids.as_slice()
+    }
+
+    /// Returns whether the class contains the given transcript.
+    pub fn contains(&self, transcript_id: &str) -> bool {
+        self.transcript_ids.iter().any(|id| id == transcript_id)
+    }
+
+    /// Returns the number of transcripts in the class.
+    pub fn len(&self) -> usize {
+        self.transcript_ids.len()
+    }
+
+    /// Returns whether the class contains no transcripts.
+    pub fn is_empty(&self) -> bool {
+        self.transcript_ids.is_empty()
+    }
+}
+
+/// Precomputed index for finding the transcripts of a gene compatible with aligned blocks.
+#[derive(Debug, Clone)]
+pub struct CompatibilityIndex<'a> {
+    gene: &'a Gene,
+    transcripts: Vec<(&'a str, &'a Transcript)>,
+    introns: HashMap<Coord<u64>, Vec<usize>>,
+}
+
+impl<'a> CompatibilityIndex<'a> {
+
+    /// Creates an index of the transcripts of the given gene.
+    pub fn new(gene: &'a Gene) -> Self {
+        let transcripts = gene.transcripts().iter()
+            .map(|(tid, trx)| (tid.as_str(), trx))
+            .collect::<Vec<_>>();
+        let mut introns: HashMap<Coord<u64>, Vec<usize>> = HashMap::new();
+        for (idx, &(_, trx)) in transcripts.iter().enumerate() {
+            for intron in trx.intron_coords() {
+                introns.entry(intron).or_insert_with(Vec::new).push(idx);
+            }
+        }
+        CompatibilityIndex {
+            gene: gene,
+            transcripts: transcripts,
+            introns: introns,
+        }
+    }
+
+    /// Returns the indexed gene.
+    pub fn gene(&self) -> &'a Gene {
+        self.gene
+    }
+
+    /// Returns the equivalence class of the given aligned blocks.
+    ///
+    /// The blocks must be sorted and non-overlapping. Compatibility is determined using
+    /// [`Transcript::is_compatible_with_tolerance`], with the given end tolerance.
+    ///
+    /// [`Transcript::is_compatible_with_tolerance`]: struct.Transcript.html#method.is_compatible_with_tolerance
+    pub fn equivalence_class(&self, blocks: &[Coord<u64>], end_tolerance: u64)
+        -> EquivalenceClass
+    {
+        let first_intron = blocks.iter().zip(blocks.iter().skip(1))
+            .find(|&(prev, next)| prev.1 < next.0)
+            .map(|(prev, next)| (prev.1, next.0));
+        let all_idxs;
+        let candidates = match first_intron {
+            Some(intron) => match self.introns.get(&intron) {
+                Some(idxs) => idxs.as_slice(),
+                None => return EquivalenceClass::default(),
+            },
+            None => {
+                all_idxs = (0..self.transcripts.len()).collect::<Vec<usize>>();
+                all_idxs.as_slice()
+            },
+        };
+
+        EquivalenceClass::new(candidates.iter()
+            .map(|&idx| self.transcripts[idx])
+            .filter(|&(_, trx)| trx.is_compatible_with_tolerance(blocks, end_tolerance))
+            .map(|(tid, _)| tid))
+    }
+}
@@ -0,0 +1,78 @@
+/*! Flattening of gene models into disjoint exon bins.
+
+Counting reads per exon is ambiguous when isoforms share exons only partially. Tools such as
+DEXSeq resolve this by flattening the gene: the exons of all its transcripts are cut at every
+exon boundary, so that each resulting bin is contained by exactly the same set of transcripts
+along its whole length. The bins do not overlap each other and their union is the union of the
+gene's exons.
+*/
+use Gene;
+
+
+/// Disjoint exonic region of a flattened gene.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExonBin {
+    start: u64,
+    end: u64,
+    transcript_ids: Vec<String>,
+}
+
+impl ExonBin {
+
+    /// Returns the zero-based start coordinate of the bin.
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// Returns the zero-based, exclusive end coordinate of the bin.
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// Returns the number of bases in the bin.
+    pub fn span(&self) -> u64 {
+        self.end - self.start
+    }
+
+    /// Returns the identifiers of the transcripts with an exon containing the bin, in the order
+    /// of the transcripts in the gene.
+    pub fn transcript_ids(&self) -> &[String] {
+        self.transcript_ids.as_slice()
+    }
+}
+
+impl Gene {
+
+    /// Returns the exon bins of the gene, sorted by their start coordinates.
+    ///
+    /// Bins are created by cutting the exons of all transcripts at every exon start and end
+    /// coordinate. Adjacent bins are not merged even if they are contained by the same
+    /// transcripts, so that bin boundaries always correspond to exon boundaries.
+    pub fn flattened_exons(&self) -> Vec<ExonBin> {
+        let mut bounds = self.transcripts().values()
+            .flat_map(|trx| trx.exons().iter())
+            .flat_map(|exon| vec![exon.start(), exon.end()])
+            .collect::<Vec<u64>>();
+        bounds.sort();
+        bounds.dedup();
+
+        let mut bins = Vec::new();
+        for window in bounds.windows(2) {
+            let (start, end) = (window[0], window[1]);
+            let transcript_ids = self.transcripts().iter()
+                .filter(|&(_, trx)| {
+                    trx.exons().iter().any(|exon| exon.start() <= start && exon.end() >= end)
+                })
+                .map(|(tid, _)| tid.clone())
+                .collect::<Vec<String>>();
+            if !transcript_ids.is_empty() {
+                bins.push(ExonBin {
+                    start: start,
+                    end: end,
+                    transcript_ids: transcript_ids,
+                });
+            }
+        }
+        bins
+    }
+}
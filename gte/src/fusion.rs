@@ -0,0 +1,188 @@
+/*! Chimeric transcripts for fusion-aware references.
+
+A fusion transcript joins the 5' part of one transcript to the 3' part of another. It is placed
+on its own sequence, named after the fusion, which consists of the genomic region of the 5'
+partner from its 5' end up to the breakpoint, followed by the genomic region of the 3' partner
+from its breakpoint up to its 3' end. Both regions are read from their transcript strands, so
+the fusion transcript is always on the forward strand and its introns keep their original
+lengths.
+*/
+use std::cmp::{max, min};
+
+use {Coord, ModelError, Strand, TBuilder, Transcript};
+
+
+impl Transcript {
+
+    /// Creates a fusion transcript from the 5' part of this transcript and the 3' part of the
+    /// given transcript.
+    ///
+    /// `breakpoint` is the genome-wise coordinate of the last base retained from this transcript
+    /// and `three_prime_breakpoint` is the genome-wise coordinate of the first base retained
+    /// from `three_prime`. Both must lie in exons. The exons containing the breakpoints are
+    /// joined into a single exon.
+    ///
+    /// The fusion transcript and its sequence are named `{5' ID}--{3' ID}`, and its gene
+    /// identifier is composed the same way if any of the partners has one. Its coding region is
+    /// set, with frames recomputed from the new start codon, when:
+    ///
+    /// * The 5' breakpoint lies past the stop codon of this transcript, in which case only the
+    ///   coding region of this transcript is kept, or
+    /// * The 5' breakpoint lies in the coding region of this transcript, the 3' breakpoint lies
+    ///   in the coding region of `three_prime`, and the junction keeps the reading frame, in
+    ///   which case the coding region ends at the stop codon of `three_prime`.
+    ///
+    /// Otherwise, the fusion transcript is noncoding. An error is returned if any of the
+    /// transcripts has an unknown strand or if any of the breakpoints is not exonic.
+    pub fn fuse(&self, breakpoint: u64, three_prime: &Transcript, three_prime_breakpoint: u64)
+        -> ::Result<Transcript>
+    {
+        let five = Part::new(self, breakpoint, true)?;
+        let three = Part::new(three_prime, three_prime_breakpoint, false)?;
+        let offset = five.len();
+
+        let mut exons = five.exon_coords();
+        for (start, end) in three.exon_coords() {
+            let (start, end) = (start + offset, end + offset);
+            // the exons at the junction always abut, since both breakpoints are exonic
+            let abutting = exons.last().map(|&(_, last_end)| last_end == start).unwrap_or(false);
+            if abutting {
+                let idx = exons.len() - 1;
+                exons[idx].1 = end;
+            } else {
+                exons.push((start, end));
+            }
+        }
+
+        let id = format!("{}--{}", self.id_or_default(), three_prime.id_or_default());
+        let mut builder = TBuilder::new(id.as_str(), 0, offset + three.len())
+            .strand(Strand::Forward)
+            .id(id.as_str())
+            .coords(exons, fusion_coding(&five, &three))
+            .coding_incl_stop(true);
+        if self.gene_id().is_some() || three_prime.gene_id().is_some() {
+            builder = builder.gene_id(format!("{}--{}", self.gene_id_or_default(),
+                                              three_prime.gene_id_or_default()));
+        }
+        builder.build()
+    }
+}
+
+/// Genomic region of a transcript retained in a fusion.
+struct Part<'a> {
+    transcript: &'a Transcript,
+    /// Zero-based, half-open genome-wise coordinates of the region.
+    region: Coord<u64>,
+    /// Offset of the breakpoint along the spliced transcript.
+    breakpoint_offset: u64,
+}
+
+impl<'a> Part<'a> {
+
+    fn new(transcript: &'a Transcript, breakpoint: u64, is_five_prime: bool)
+        -> Result<Part<'a>, ModelError>
+    {
+        let is_forward = match transcript.strand() {
+            &Strand::Forward => true,
+            &Strand::Reverse => false,
+            &Strand::Unknown => return Err(ModelError::UnspecifiedStrand),
+        };
+        let breakpoint_offset = match transcript.genomic_to_transcript(breakpoint) {
+            Some(offset) => offset,
+            None => {
+                let tid = transcript.id().map(|id| id.to_owned());
+                return Err(ModelError::InvalidBreakpoint(tid));
+            },
+        };
+        // the retained region lies genome-wise before the breakpoint for 5' partners on the
+        // forward strand and 3' partners on the reverse strand
+        let region =
+            if is_five_prime == is_forward {
+                (transcript.start(), breakpoint + 1)
+            } else {
+                (breakpoint, transcript.end())
+            };
+        Ok(Part {
+            transcript: transcript,
+            region: region,
+            breakpoint_offset: breakpoint_offset,
+        })
+    }
+
+    /// Returns the length of the region.
+    fn len(&self) -> u64 {
+        self.region.1 - self.region.0
+    }
+
+    /// Returns the offset of the given genome-wise coordinate from the 5' end of the region.
+    fn to_local(&self, pos: u64) -> u64 {
+        match self.transcript.strand() {
+            &Strand::Reverse => self.region.1 - 1 - pos,
+            _ => pos - self.region.0,
+        }
+    }
+
+    /// Returns the retained exon parts, relative to the 5' end of the region.
+    fn exon_coords(&self) -> Vec<Coord<u64>> {
+        let (lo, hi) = self.region;
+        let mut coords = self.transcript.exons().iter()
+            .filter(|exon| exon.start() < hi && exon.end() > lo)
+            .map(|exon| {
+                let (start, end) = (max(exon.start(), lo), min(exon.end(), hi));
+                match self.transcript.strand() {
+                    &Strand::Reverse => (hi - end, hi - start),
+                    _ => (start - lo, end - lo),
+                }
+            })
+            .collect::<Vec<Coord<u64>>>();
+        coords.sort();
+        coords
+    }
+
+    /// Returns the genome-wise coordinates of the first base of the start codon and of the last
+    /// base of the stop codon, along with their offsets along the spliced transcript.
+    fn coding_bases(&self) -> Option<(Coord<u64>, Coord<u64>)> {
+        let (cstart, cend) = match self.transcript.coding_coord(true) {
+            Some(coord) => coord,
+            None => return None,
+        };
+        let (first, last) = match self.transcript.strand() {
+            &Strand::Reverse => (cend - 1, cstart),
+            _ => (cstart, cend - 1),
+        };
+        match (self.transcript.genomic_to_transcript(first),
+               self.transcript.genomic_to_transcript(last)) {
+            (Some(first_offset), Some(last_offset)) =>
+                Some(((first, last), (first_offset, last_offset))),
+            _ => None,
+        }
+    }
+}
+
+/// Helper function for computing the coding region of a fusion transcript.
+fn fusion_coding(five: &Part, three: &Part) -> Option<Coord<u64>> {
+    let ((first5, last5), (first5_offset, last5_offset)) = match five.coding_bases() {
+        Some(bases) => bases,
+        None => return None,
+    };
+    if five.breakpoint_offset < first5_offset {
+        return None;
+    }
+    let start = five.to_local(first5);
+    if five.breakpoint_offset >= last5_offset {
+        return Some((start, five.to_local(last5) + 1));
+    }
+    let ((_, last3), (first3_offset, last3_offset)) = match three.coding_bases() {
+        Some(bases) => bases,
+        None => return None,
+    };
+    let in_frame = three.breakpoint_offset >= first3_offset
+        && three.breakpoint_offset <= last3_offset
+        && (five.breakpoint_offset + 1 - first5_offset) % 3
+            == (three.breakpoint_offset - first3_offset) % 3;
+    if in_frame {
+        Some((start, five.len() + three.to_local(last3) + 1))
+    } else {
+        None
+    }
+}
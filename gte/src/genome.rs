@@ -0,0 +1,316 @@
+/*! In-memory genome sequences.
+
+Genomes are read from FASTA or UCSC 2bit files, keeping every sequence in memory, and are meant
+for extracting many short subsequences such as the windows around splice sites. The sequence name
+of each FASTA record is the first whitespace-separated word of its header line.
+
+2bit files store four bases per byte along with the positions of `N` runs and of soft-masked
+regions. Both are restored when reading, so that a genome read from a 2bit file has the same
+sequences as one read from the FASTA file it was created from, except that unknown bases are
+always `N` and masked bases are lowercase. The specification of the format is available at
+https://genome.ucsc.edu/FAQ/FAQformat.html#format7.
+*/
+use std::error::Error;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+
+use linked_hash_map::LinkedHashMap;
+
+use Strand;
+
+
+quick_error! {
+    /// Errors that occur when reading genome sequences.
+    #[derive(Debug)]
+    pub enum GenomeError {
+        /// Occurs when sequence lines appear before any FASTA header line.
+        MissingHeader(line_num: usize) {
+            description("sequence found before FASTA header")
+            display(self_) -> ("{}, line: {}", self_.description(), line_num)
+        }
+        /// Occurs when a sequence name is defined more than once.
+        DuplicateSeqName(line_num: usize) {
+            description("duplicate sequence name")
+            display(self_) -> ("{}, line: {}", self_.description(), line_num)
+        }
+        /// Occurs when a 2bit file is malformed.
+        InvalidTwoBit(reason: &'static str) {
+            description("invalid 2bit file")
+            display(self_) -> ("{}: {}", self_.description(), reason)
+        }
+        /// Errors propagated from reading the underlying file.
+        Io(err: io::Error) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// Sequences of a genome, stored in the order they are added.
+#[derive(Debug, Clone, Default)]
+pub struct Genome {
+    seqs: LinkedHashMap<String, Vec<u8>>,
+}
+
+impl Genome {
+
+    /// Creates an empty genome.
+    pub fn new() -> Self {
+        Genome { seqs: LinkedHashMap::new() }
+    }
+
+    /// Creates a genome from the given FASTA reader.
+    ///
+    /// Empty lines are ignored.
+    pub fn from_reader<R: io::Read>(in_reader: R) -> ::Result<Genome> {
+        let mut genome = Genome::new();
+        let mut current: Option<(String, Vec<u8>)> = None;
+        for (idx, line) in BufReader::new(in_reader).lines().enumerate() {
+            let line_num = idx + 1;
+            let line = line.map_err(|e| ::Error::from(GenomeError::from(e)))?;
+            let line = line.trim_end();
+            if line.starts_with('>') {
+                if let Some((name, seq)) = current.take() {
+                    genome.add_seq(name, seq);
+                }
+                let name = line[1..].split_whitespace().next().unwrap_or("").to_owned();
+                if genome.seqs.contains_key(&name) {
+                    return Err(::Error::from(GenomeError::DuplicateSeqName(line_num)));
+                }
+                current = Some((name, Vec::new()));
+            } else if !line.is_empty() {
+                match current.as_mut() {
+                    Some(&mut (_, ref mut seq)) => seq.extend_from_slice(line.as_bytes()),
+                    None => return Err(::Error::from(GenomeError::MissingHeader(line_num))),
+                }
+            }
+        }
+        if let Some((name, seq)) = current {
+            genome.add_seq(name, seq);
+        }
+        Ok(genome)
+    }
+
+    /// Creates a genome from the FASTA file in the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> ::Result<Genome> {
+        let file = fs::File::open(path)
+            .map_err(|e| ::Error::from(GenomeError::from(e)))?;
+        Genome::from_reader(file)
+    }
+
+    /// Creates a genome from the given UCSC 2bit reader.
+    ///
+    /// Both little- and big-endian files are supported, with either 32-bit (version 0) or
+    /// 64-bit (version 1) sequence offsets.
+    pub fn from_2bit_reader<R: io::Read>(mut in_reader: R) -> ::Result<Genome> {
+        let mut buf = Vec::new();
+        let _ = in_reader.read_to_end(&mut buf)
+            .map_err(|e| ::Error::from(GenomeError::from(e)))?;
+        TwoBitParser::new(&buf).parse().map_err(::Error::from)
+    }
+
+    /// Creates a genome from the UCSC 2bit file in the given path.
+    pub fn from_2bit_file<P: AsRef<Path>>(path: P) -> ::Result<Genome> {
+        let file = fs::File::open(path)
+            .map_err(|e| ::Error::from(GenomeError::from(e)))?;
+        Genome::from_2bit_reader(file)
+    }
+
+    /// Adds a sequence to the genome, replacing any sequence with the same name.
+    pub fn add_seq<T>(&mut self, seq_name: T, seq: Vec<u8>)
+        where T: Into<String>
+    {
+        let _ = self.seqs.insert(seq_name.into(), seq);
+    }
+
+    /// Returns the whole sequence with the given name.
+    pub fn seq(&self, seq_name: &str) -> Option<&[u8]> {
+        self.seqs.get(seq_name).map(|seq| seq.as_slice())
+    }
+
+    /// Returns the genome-wise subsequence between the given zero-based, half-open coordinates.
+    ///
+    /// `None` is returned if the sequence is absent or shorter than the given end coordinate.
+    pub fn subseq(&self, seq_name: &str, start: u64, end: u64) -> Option<&[u8]> {
+        self.seq(seq_name)
+            .and_then(|seq| if start <= end && end <= seq.len() as u64 {
+                Some(&seq[start as usize..end as usize])
+            } else {
+                None
+            })
+    }
+
+    /// Returns the subsequence between the given coordinates as read from the given strand.
+    ///
+    /// Subsequences on the reverse strand are reverse complemented.
+    pub fn stranded_subseq(&self, seq_name: &str, start: u64, end: u64, strand: &Strand)
+        -> Option<Vec<u8>>
+    {
+        self.subseq(seq_name, start, end)
+            .map(|seq| match strand {
+                &Strand::Reverse => revcomp(seq),
+                _ => seq.to_vec(),
+            })
+    }
+
+    /// Returns the number of sequences in the genome.
+    pub fn len(&self) -> usize {
+        self.seqs.len()
+    }
+
+    /// Returns whether the genome contains no sequences.
+    pub fn is_empty(&self) -> bool {
+        self.seqs.is_empty()
+    }
+}
+
+/// Signature at the start of every 2bit file, as read in the file's own byte order.
+const TWOBIT_SIGNATURE: u32 = 0x1A41_2743;
+
+/// Bases encoded by each two-bit value of a 2bit file.
+const TWOBIT_BASES: [u8; 4] = [b'T', b'C', b'A', b'G'];
+
+/// Parser of 2bit files held in memory.
+struct TwoBitParser<'a> {
+    buf: &'a [u8],
+    pos: usize,
+    big_endian: bool,
+}
+
+impl<'a> TwoBitParser<'a> {
+
+    fn new(buf: &'a [u8]) -> TwoBitParser<'a> {
+        TwoBitParser { buf: buf, pos: 0, big_endian: false }
+    }
+
+    fn parse(mut self) -> Result<Genome, GenomeError> {
+        let signature = self.read_u32()?;
+        if signature != TWOBIT_SIGNATURE {
+            if signature.swap_bytes() != TWOBIT_SIGNATURE {
+                return Err(GenomeError::InvalidTwoBit("unknown signature"));
+            }
+            self.big_endian = true;
+        }
+        let long_offsets = match self.read_u32()? {
+            0 => false,
+            1 => true,
+            _ => return Err(GenomeError::InvalidTwoBit("unsupported version")),
+        };
+        let num_seqs = self.read_u32()?;
+        let _reserved = self.read_u32()?;
+
+        let mut index = Vec::new();
+        for _ in 0..num_seqs {
+            let name_len = self.read_bytes(1)?[0] as usize;
+            let name = String::from_utf8(self.read_bytes(name_len)?.to_vec())
+                .map_err(|_| GenomeError::InvalidTwoBit("sequence name is not valid UTF-8"))?;
+            let offset = if long_offsets {
+                let low = self.read_u32()? as u64;
+                let high = self.read_u32()? as u64;
+                if self.big_endian { (low << 32) | high } else { (high << 32) | low }
+            } else {
+                self.read_u32()? as u64
+            };
+            index.push((name, offset as usize));
+        }
+
+        let mut genome = Genome::new();
+        for (name, offset) in index {
+            if genome.seqs.contains_key(&name) {
+                return Err(GenomeError::InvalidTwoBit("duplicate sequence name"));
+            }
+            self.pos = offset;
+            let seq = self.read_seq()?;
+            genome.add_seq(name, seq);
+        }
+        Ok(genome)
+    }
+
+    fn read_seq(&mut self) -> Result<Vec<u8>, GenomeError> {
+        let seq_len = self.read_u32()? as usize;
+        let n_blocks = self.read_blocks()?;
+        let mask_blocks = self.read_blocks()?;
+        let _reserved = self.read_u32()?;
+        let packed = self.read_bytes((seq_len + 3) / 4)?;
+
+        let mut seq = Vec::with_capacity(seq_len);
+        for idx in 0..seq_len {
+            let code = (packed[idx / 4] >> (6 - 2 * (idx % 4))) & 0b11;
+            seq.push(TWOBIT_BASES[code as usize]);
+        }
+        for (start, end) in n_blocks {
+            if end > seq_len {
+                return Err(GenomeError::InvalidTwoBit("N block exceeds sequence length"));
+            }
+            for base in seq[start..end].iter_mut() {
+                *base = b'N';
+            }
+        }
+        for (start, end) in mask_blocks {
+            if end > seq_len {
+                return Err(GenomeError::InvalidTwoBit("mask block exceeds sequence length"));
+            }
+            for base in seq[start..end].iter_mut() {
+                *base = base.to_ascii_lowercase();
+            }
+        }
+        Ok(seq)
+    }
+
+    fn read_blocks(&mut self) -> Result<Vec<(usize, usize)>, GenomeError> {
+        let num_blocks = self.read_u32()? as usize;
+        let mut starts = Vec::with_capacity(num_blocks);
+        for _ in 0..num_blocks {
+            starts.push(self.read_u32()? as usize);
+        }
+        let mut blocks = Vec::with_capacity(num_blocks);
+        for start in starts {
+            let size = self.read_u32()? as usize;
+            blocks.push((start, start + size));
+        }
+        Ok(blocks)
+    }
+
+    fn read_u32(&mut self) -> Result<u32, GenomeError> {
+        let big_endian = self.big_endian;
+        let bytes = self.read_bytes(4)?;
+        let value = if big_endian {
+            bytes.iter().fold(0, |acc, &byte| (acc << 8) | u32::from(byte))
+        } else {
+            bytes.iter().rev().fold(0, |acc, &byte| (acc << 8) | u32::from(byte))
+        };
+        Ok(value)
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], GenomeError> {
+        let end = self.pos.checked_add(len)
+            .ok_or(GenomeError::InvalidTwoBit("unexpected end of file"))?;
+        if end > self.buf.len() {
+            return Err(GenomeError::InvalidTwoBit("unexpected end of file"));
+        }
+        let buf = self.buf;
+        let bytes = &buf[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+}
+
+/// Returns the reverse complement of the given nucleotide sequence, preserving letter case.
+///
+/// Characters other than the IUPAC nucleotide codes are kept as they are.
+pub(crate) fn revcomp(seq: &[u8]) -> Vec<u8> {
+    seq.iter().rev()
+        .map(|&base| match base {
+            b'A' => b'T', b'C' => b'G', b'G' => b'C', b'T' => b'A', b'U' => b'A',
+            b'a' => b't', b'c' => b'g', b'g' => b'c', b't' => b'a', b'u' => b'a',
+            b'R' => b'Y', b'Y' => b'R', b'K' => b'M', b'M' => b'K',
+            b'B' => b'V', b'V' => b'B', b'D' => b'H', b'H' => b'D',
+            b'r' => b'y', b'y' => b'r', b'k' => b'm', b'm' => b'k',
+            b'b' => b'v', b'v' => b'b', b'd' => b'h', b'h' => b'd',
+            other => other,
+        })
+        .collect()
+}
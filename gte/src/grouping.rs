@@ -0,0 +1,198 @@
+/*! Grouping of transcripts into genes.
+
+Transcript assemblers such as StringTie and de novo assemblies often report transcripts without
+gene identifiers, or with identifiers that do not reflect shared loci. This module groups such
+transcripts into genes: two transcripts belong to the same gene when they are on the same
+sequence and strand and at least one of their exons overlap each other. Grouping is transitive,
+so that a gene may contain transcripts that do not overlap each other directly but are linked by
+other transcripts.
+
+```
+use gte::{GeneAssembler, Strand, TBuilder};
+
+let trx = |id: &str, exons: Vec<(u64, u64)>| {
+    let (start, end) = (exons[0].0, exons[exons.len() - 1].1);
+    TBuilder::new("chr1", start, end)
+        .strand(Strand::Forward)
+        .id(id)
+        .coords(exons, None)
+        .build()
+        .unwrap()
+};
+let genes = GeneAssembler::new()
+    .assemble(vec![trx("t1", vec![(100, 200), (300, 400)]),
+                   trx("t2", vec![(350, 500)]),
+                   trx("t3", vec![(200, 300)])])
+    .unwrap();
+assert_eq!(genes.len(), 2);
+assert_eq!(genes[0].id(), Some("GENE_000001"));
+assert_eq!(genes[0].transcripts().len(), 2);
+```
+*/
+use std::cmp::{max, min};
+use std::collections::HashMap;
+
+use {Coord, Gene, GBuilder, Transcript, INIT_COORD};
+use annotation::strand_ord;
+
+
+/// Prefix of gene identifiers used when none is set.
+const DEF_ID_PREFIX: &'static str = "GENE_";
+
+/// Groups transcripts into genes by exonic overlap.
+#[derive(Debug, Clone)]
+pub struct GeneAssembler {
+    id_prefix: String,
+}
+
+impl Default for GeneAssembler {
+    fn default() -> Self {
+        GeneAssembler::new()
+    }
+}
+
+impl GeneAssembler {
+
+    /// Creates an assembler with the default settings.
+    ///
+    /// By default, genes are given identifiers prefixed with `GENE_`.
+    pub fn new() -> Self {
+        GeneAssembler { id_prefix: DEF_ID_PREFIX.to_owned() }
+    }
+
+    /// Sets the prefix of the gene identifiers.
+    pub fn id_prefix<T>(&mut self, prefix: T) -> &mut Self
+        where T: Into<String>
+    {
+        self.id_prefix = prefix.into();
+        self
+    }
+
+    /// Groups the given transcripts into genes.
+    ///
+    /// The genes are sorted by their sequence names, coordinates, and strands, and are given
+    /// identifiers in that order (`GENE_000001`, `GENE_000002`, ...). Each gene spans its
+    /// transcripts, which keep their input order and have their gene identifiers replaced by
+    /// the identifier of their gene.
+    ///
+    /// An error is returned if any of the transcripts has no identifier, or if transcripts of
+    /// the same gene share an identifier.
+    pub fn assemble<I>(&self, transcripts: I) -> ::Result<Vec<Gene>>
+        where I: IntoIterator<Item=Transcript>
+    {
+        let transcripts = transcripts.into_iter().collect::<Vec<Transcript>>();
+
+        let mut idxs = (0..transcripts.len()).collect::<Vec<usize>>();
+        idxs.sort_by(|&a, &b| {
+            let (ta, tb) = (&transcripts[a], &transcripts[b]);
+            (ta.seq_name(), strand_ord(ta.strand()), ta.start())
+                .cmp(&(tb.seq_name(), strand_ord(tb.strand()), tb.start()))
+        });
+
+        let mut parents = (0..transcripts.len()).collect::<Vec<usize>>();
+        let mut active: Vec<usize> = Vec::new();
+        for &idx in idxs.iter() {
+            let trx = &transcripts[idx];
+            active.retain(|&other| {
+                let prev = &transcripts[other];
+                prev.seq_name() == trx.seq_name() && prev.strand() == trx.strand()
+                    && prev.end() > trx.start()
+            });
+            for &other in active.iter() {
+                if exons_overlap(&transcripts[other], trx) {
+                    union(&mut parents, other, idx);
+                }
+            }
+            active.push(idx);
+        }
+
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        let mut group_idxs: HashMap<usize, usize> = HashMap::new();
+        for idx in 0..transcripts.len() {
+            let root = find(&mut parents, idx);
+            let next_idx = groups.len();
+            let group_idx = *group_idxs.entry(root).or_insert(next_idx);
+            if group_idx == next_idx {
+                groups.push(Vec::new());
+            }
+            groups[group_idx].push(idx);
+        }
+
+        {
+            let key = |group: &Vec<usize>| {
+                let first = &transcripts[group[0]];
+                let span = transcripts_span(group.iter().map(|&idx| &transcripts[idx]));
+                (first.seq_name().to_owned(), span, strand_ord(first.strand()))
+            };
+            groups.sort_by_key(key);
+        }
+
+        let mut slots = transcripts.into_iter().map(Some).collect::<Vec<Option<Transcript>>>();
+        let mut genes = Vec::with_capacity(groups.len());
+        for (num, group) in groups.into_iter().enumerate() {
+            let members = group.into_iter()
+                .filter_map(|idx| slots[idx].take())
+                .collect::<Vec<Transcript>>();
+            let (start, end) = transcripts_span(members.iter());
+            let seq_name = members[0].seq_name().to_owned();
+            let strand = *members[0].strand();
+            let gene = GBuilder::new(seq_name, start, end)
+                .strand(strand)
+                .id(format!("{}{:06}", self.id_prefix, num + 1))
+                .add_transcripts(members)
+                .build()?;
+            genes.push(gene);
+        }
+        Ok(genes)
+    }
+}
+
+/// Helper function for computing the region spanned by a nonempty group of transcripts.
+fn transcripts_span<'a, I>(transcripts: I) -> Coord<u64>
+    where I: Iterator<Item=&'a Transcript>
+{
+    transcripts.fold(INIT_COORD, |(start, end), trx| {
+        (min(start, trx.start()), max(end, trx.end()))
+    })
+}
+
+/// Helper function for checking whether any exons of two transcripts overlap.
+fn exons_overlap(a: &Transcript, b: &Transcript) -> bool {
+    let (exons_a, exons_b) = (a.exons(), b.exons());
+    let (mut i, mut j) = (0, 0);
+    while i < exons_a.len() && j < exons_b.len() {
+        let (ea, eb) = (&exons_a[i], &exons_b[j]);
+        if ea.start() < eb.end() && eb.start() < ea.end() {
+            return true;
+        }
+        if ea.end() <= eb.end() {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    false
+}
+
+/// Helper function for finding the representative of a disjoint set.
+fn find(parents: &mut Vec<usize>, idx: usize) -> usize {
+    let mut root = idx;
+    while parents[root] != root {
+        root = parents[root];
+    }
+    let mut cur = idx;
+    while parents[cur] != root {
+        let next = parents[cur];
+        parents[cur] = root;
+        cur = next;
+    }
+    root
+}
+
+/// Helper function for merging the disjoint sets of two elements.
+fn union(parents: &mut Vec<usize>, a: usize, b: usize) {
+    let (root_a, root_b) = (find(parents, a), find(parents, b));
+    if root_a != root_b {
+        parents[root_b] = root_a;
+    }
+}
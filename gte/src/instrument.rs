@@ -0,0 +1,176 @@
+/*! Instrumentation of loaded annotations.
+
+Instrumentation is opt-in: sizes are only computed when `AnnotationSet::stats` is called, and
+loading is only timed when using the `load_annotation` methods of the readers. These are meant
+for diagnosing why loading a particular annotation is slow or memory-heavy.
+
+The byte counts are approximations. They include the sizes of the model structs, their strings,
+and their attributes, but not allocator overhead or unused capacity.
+*/
+use std::mem::size_of;
+use std::time::{Duration, Instant};
+
+use {Attributes, Exon, ExonFeature, Gene, Transcript};
+
+
+/// Counts and approximate memory usage of a collection of genes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AnnotationStats {
+    num_genes: usize,
+    num_transcripts: usize,
+    num_exons: usize,
+    num_features: usize,
+    approx_bytes: usize,
+}
+
+impl AnnotationStats {
+
+    /// Computes the statistics of the given genes.
+    pub fn from_genes<'a, I>(genes: I) -> Self
+        where I: IntoIterator<Item=&'a Gene>
+    {
+        let mut stats = AnnotationStats::default();
+        for gene in genes {
+            stats.add_gene(gene);
+        }
+        stats
+    }
+
+    /// Returns the number of genes.
+    pub fn num_genes(&self) -> usize {
+        self.num_genes
+    }
+
+    /// Returns the number of transcripts.
+    pub fn num_transcripts(&self) -> usize {
+        self.num_transcripts
+    }
+
+    /// Returns the number of exons.
+    pub fn num_exons(&self) -> usize {
+        self.num_exons
+    }
+
+    /// Returns the number of exon features, such as UTRs, CDSs, and codons.
+    pub fn num_features(&self) -> usize {
+        self.num_features
+    }
+
+    /// Returns the approximate number of bytes used by the genes.
+    pub fn approx_bytes(&self) -> usize {
+        self.approx_bytes
+    }
+
+    fn add_gene(&mut self, gene: &Gene) {
+        self.num_genes += 1;
+        self.approx_bytes += size_of::<Gene>() + gene.seq_name().len()
+            + gene.id().map(|id| id.len()).unwrap_or(0)
+            + attributes_bytes(gene.attributes());
+        for (tid, transcript) in gene.transcripts().iter() {
+            self.approx_bytes += tid.len();
+            self.add_transcript(transcript);
+        }
+    }
+
+    fn add_transcript(&mut self, transcript: &Transcript) {
+        self.num_transcripts += 1;
+        self.approx_bytes += size_of::<Transcript>() + transcript.seq_name().len()
+            + transcript.id().map(|id| id.len()).unwrap_or(0)
+            + transcript.gene_id().map(|id| id.len()).unwrap_or(0)
+            + attributes_bytes(transcript.attributes());
+        for exon in transcript.exons() {
+            self.add_exon(exon);
+        }
+    }
+
+    fn add_exon(&mut self, exon: &Exon) {
+        self.num_exons += 1;
+        self.num_features += exon.features().len();
+        self.approx_bytes += size_of::<Exon>() + exon.seq_name().len()
+            + exon.id().map(|id| id.len()).unwrap_or(0)
+            + exon.transcript_id().map(|id| id.len()).unwrap_or(0)
+            + exon.gene_id().map(|id| id.len()).unwrap_or(0)
+            + attributes_bytes(exon.attributes())
+            + exon.features().len() * size_of::<ExonFeature>();
+    }
+}
+
+/// Report of loading an annotation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoadReport {
+    parse_time: Duration,
+    assembly_time: Duration,
+    stats: AnnotationStats,
+}
+
+impl LoadReport {
+
+    /// Returns the time spent reading and parsing the input records.
+    pub fn parse_time(&self) -> Duration {
+        self.parse_time
+    }
+
+    /// Returns the time spent assembling the parsed records into genes.
+    pub fn assembly_time(&self) -> Duration {
+        self.assembly_time
+    }
+
+    /// Returns the total load time.
+    pub fn total_time(&self) -> Duration {
+        self.parse_time + self.assembly_time
+    }
+
+    /// Returns the statistics of the loaded genes.
+    pub fn stats(&self) -> &AnnotationStats {
+        &self.stats
+    }
+}
+
+/// Helper struct for accumulating parse and assembly times while loading.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct LoadTimer {
+    parse_time: Duration,
+    assembly_time: Duration,
+}
+
+impl LoadTimer {
+
+    pub(crate) fn new() -> Self {
+        LoadTimer { parse_time: Duration::new(0, 0), assembly_time: Duration::new(0, 0) }
+    }
+
+    /// Runs the given parsing step, adding its duration to the parse time.
+    pub(crate) fn parse<T, F: FnOnce() -> T>(&mut self, f: F) -> T {
+        let start = Instant::now();
+        let res = f();
+        self.parse_time += start.elapsed();
+        res
+    }
+
+    /// Runs the given assembly step, adding its duration to the assembly time.
+    pub(crate) fn assemble<T, F: FnOnce() -> T>(&mut self, f: F) -> T {
+        let start = Instant::now();
+        let res = f();
+        self.assembly_time += start.elapsed();
+        res
+    }
+
+    /// Creates the report of the given loaded genes.
+    pub(crate) fn finish(self, genes: &[Gene]) -> LoadReport {
+        LoadReport {
+            parse_time: self.parse_time,
+            assembly_time: self.assembly_time,
+            stats: AnnotationStats::from_genes(genes),
+        }
+    }
+}
+
+/// Helper function for approximating the bytes used by attributes.
+fn attributes_bytes(attributes: &Attributes) -> usize {
+    attributes.iter_all()
+        .map(|(key, values)| {
+            key.len() + values.iter().map(|value| value.len() + size_of::<String>()).sum::<usize>()
+                + size_of::<String>() + size_of::<Vec<String>>()
+        })
+        .sum()
+}
@@ -0,0 +1,395 @@
+/*! Writer for the BED format.
+
+Transcripts are written as [BED12](https://genome.ucsc.edu/FAQ/FAQformat.html#format1) lines,
+with the transcript identifier as the name and the coding region as the thick region. The writer
+may also be set to write the [BED detail](https://genome.ucsc.edu/FAQ/FAQformat.html#format1.7)
+variant, which adds an identifier and a description column built from selected attributes.
+
+Exon features may also be written individually as BED6 lines, with the feature kind in the name
+column. Protein domains mapped onto the genome are written as BED12 lines, with their blocks
+being the genomic intervals encoding the domain.
+
+The thick region and blocks of a transcript are also available without a writer through
+`Transcript::to_bed12_blocks`, for embedding transcripts in other BED-like outputs.
+
+`AnnotationSet::export_tracks` writes a fixed set of BED6 files from a whole annotation set in a
+single pass, one for each kind of region (genes, exons, introns, and so on).
+*/
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use itertools::Itertools;
+
+use {expand_window, AnnotationSet, Assembly, ExonFeatureKind as EFK, Gene, ProteinDomain, Strand,
+     Transcript, DEF_ID};
+use io_gff::{strand_to_char, CDS_STR, START_CODON_STR, STOP_CODON_STR, UTR_STR, UTR3_STR,
+             UTR5_STR};
+use utils::{describe_attributes, OptionDeref};
+
+
+quick_error! {
+    /// Errors that occur when writing BED files.
+    #[derive(Debug)]
+    pub enum BedError {
+        /// Errors propagated from writing the underlying output.
+        Io(err: io::Error) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Occurs when an exon starts before its transcript, so that its block start can not
+        /// be computed.
+        ExonBeforeTranscript(start: u64, tid: Option<String>) {
+            description("exon starts before its transcript")
+            display(self_) -> ("{}, exon start: {}, transcript ID: {}", self_.description(),
+                               start, tid.as_deref().unwrap_or(DEF_ID))
+        }
+    }
+}
+
+/// BED writer.
+pub struct Writer<W: io::Write> {
+    inner: W,
+    detail_attributes: Option<Vec<String>>,
+}
+
+impl<W: io::Write> Writer<W> {
+
+    /// Creates a BED writer from another writer.
+    pub fn from_writer(in_writer: W) -> Writer<W> {
+        Writer {
+            inner: in_writer,
+            detail_attributes: None,
+        }
+    }
+
+    /// Sets the writer to write BED detail lines with a description built from the given
+    /// attribute keys.
+    ///
+    /// The identifier column contains the gene identifier of the transcript. The description
+    /// column contains the `key=value` pairs of the given attributes present in the transcript,
+    /// in the given order.
+    pub fn detail_attributes<I, T>(&mut self, keys: I) -> &mut Self
+        where I: IntoIterator<Item=T>, T: Into<String>
+    {
+        self.detail_attributes = Some(keys.into_iter().map(|k| k.into()).collect());
+        self
+    }
+
+    /// Writes the given transcript as a single line.
+    pub fn write_transcript(&mut self, transcript: &Transcript) -> ::Result<()> {
+        let (thick_start, thick_end, block_sizes, block_starts) = transcript.to_bed12_blocks()?;
+        let block_sizes = block_sizes.iter().join(",");
+        let block_starts = block_starts.iter().join(",");
+
+        let mut line = format!("{}\t{}\t{}\t{}\t0\t{}\t{}\t{}\t0\t{}\t{},\t{},",
+                               transcript.seq_name(), transcript.start(), transcript.end(),
+                               transcript.id().unwrap_or("."),
+                               strand_to_char(transcript.strand()),
+                               thick_start, thick_end, transcript.exons().len(),
+                               block_sizes, block_starts);
+        if let Some(ref keys) = self.detail_attributes {
+            line.push_str(&format!("\t{}\t{}",
+                                   transcript.gene_id().unwrap_or("."),
+                                   describe_attributes(transcript.attributes(), keys)));
+        }
+        writeln!(self.inner, "{}", line).map_err(|e| ::Error::from(BedError::from(e)))
+    }
+
+    /// Writes the features of the given transcript's exons as BED6 lines, one for each feature.
+    ///
+    /// The name column contains the transcript identifier and the feature kind, separated by a
+    /// colon (e.g. `trx01:CDS`), or only the feature kind when the transcript has no identifier.
+    /// Features are written in the order of their coordinates.
+    pub fn write_exon_features(&mut self, transcript: &Transcript) -> ::Result<()> {
+        let strand = strand_to_char(transcript.strand());
+        for exon in transcript.exons() {
+            let mut features = exon.features().iter().collect::<Vec<_>>();
+            features.sort_by_key(|fx| (fx.start(), fx.end()));
+            for fx in features {
+                let kind = match fx.kind() {
+                    &EFK::UTR => UTR_STR,
+                    &EFK::UTR5 => UTR5_STR,
+                    &EFK::UTR3 => UTR3_STR,
+                    &EFK::CDS { .. } => CDS_STR,
+                    &EFK::StartCodon { .. } => START_CODON_STR,
+                    &EFK::StopCodon { .. } => STOP_CODON_STR,
+                    &EFK::Any(ref s) => s.as_str(),
+                };
+                let name = match transcript.id() {
+                    Some(tid) => format!("{}:{}", tid, kind),
+                    None => kind.to_owned(),
+                };
+                writeln!(self.inner, "{}\t{}\t{}\t{}\t0\t{}",
+                         exon.seq_name(), fx.start(), fx.end(), name, strand)
+                    .map_err(|e| ::Error::from(BedError::from(e)))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the given protein domain as a single BED12 line.
+    ///
+    /// The name column contains the transcript identifier and the domain identifier, separated
+    /// by a colon (e.g. `trx01:PF00069`), and the whole domain is written as the thick region.
+    pub fn write_domain(&mut self, domain: &ProteinDomain) -> ::Result<()> {
+        let (start, end) = (domain.start(), domain.end());
+        let block_sizes = domain.blocks().iter()
+            .map(|&(bstart, bend)| bend - bstart)
+            .join(",");
+        let block_starts = domain.blocks().iter()
+            .map(|&(bstart, _)| bstart - start)
+            .join(",");
+        writeln!(self.inner, "{}\t{}\t{}\t{}:{}\t0\t{}\t{}\t{}\t0\t{}\t{},\t{},",
+                 domain.seq_name(), start, end,
+                 domain.transcript_id(), domain.record().domain_id(),
+                 strand_to_char(domain.strand()), start, end, domain.blocks().len(),
+                 block_sizes, block_starts)
+            .map_err(|e| ::Error::from(BedError::from(e)))
+    }
+
+    /// Writes the given gene as multiple lines, one for each transcript.
+    pub fn write_gene(&mut self, gene: &Gene) -> ::Result<()> {
+        for transcript in gene.transcripts().values() {
+            self.write_transcript(transcript)?;
+        }
+        Ok(())
+    }
+}
+
+impl Writer<fs::File> {
+
+    /// Creates a BED writer that writes to the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let f = fs::File::create(path)?;
+        Ok(Writer::from_writer(f))
+    }
+}
+
+impl Writer<Vec<u8>> {
+
+    /// Creates a BED writer that writes to an in-memory buffer.
+    ///
+    /// The initial capacity of the buffer is 64 KiB.
+    pub fn from_memory() -> Writer<Vec<u8>> {
+        Writer::from_writer(Vec::with_capacity(1024 * 64))
+    }
+
+    /// Returns the values of the in-memory buffer as a string.
+    pub fn as_string(&mut self) -> &str {
+        // the buffer only ever contains values written from `String`s
+        ::std::str::from_utf8(self.inner.as_slice()).expect("valid UTF-8 output")
+    }
+}
+
+/// Options for exporting BED tracks with `AnnotationSet::export_tracks`.
+#[derive(Debug, Clone, Default)]
+pub struct TrackOptions {
+    tss_flank: u64,
+    unique: bool,
+    assembly: Option<Assembly>,
+}
+
+impl TrackOptions {
+
+    /// Creates the default options.
+    ///
+    /// By default, transcription start sites are written as single bases, identical
+    /// intervals of different transcripts are all written, and flanks are not clipped.
+    pub fn new() -> Self {
+        TrackOptions::default()
+    }
+
+    /// Sets the number of bases on each side of transcription start sites to include in the
+    /// TSS track.
+    pub fn tss_flank(mut self, tss_flank: u64) -> Self {
+        self.tss_flank = tss_flank;
+        self
+    }
+
+    /// Sets whether intervals with the same coordinates and strand are written only once per
+    /// track, named after the first transcript or gene containing them.
+    pub fn unique(mut self, unique: bool) -> Self {
+        self.unique = unique;
+        self
+    }
+
+    /// Sets the assembly used to clip transcription start site flanks to the sequence ends.
+    pub fn assembly(mut self, assembly: Assembly) -> Self {
+        self.assembly = Some(assembly);
+        self
+    }
+}
+
+impl Transcript {
+
+    /// Returns the thick start, thick end, block sizes, and block starts BED12 columns of the
+    /// transcript.
+    ///
+    /// The thick region is the coding region including the stop codon, or an empty region at
+    /// the transcript end for noncoding transcripts. There is one block for each exon, in
+    /// genome-wise order, with starts relative to the transcript start. An error is returned
+    /// if an exon starts before the transcript, which is only possible for transcripts built
+    /// with `ValidationMode::Ignore`.
+    pub fn to_bed12_blocks(&self) -> ::Result<(u64, u64, Vec<u64>, Vec<u64>)> {
+        let (thick_start, thick_end) = self.coding_coord(true)
+            .unwrap_or((self.end(), self.end()));
+        let block_sizes = self.exons().iter()
+            .map(|exon| exon.span())
+            .collect();
+        let block_starts = self.exons().iter()
+            .map(|exon| {
+                exon.start().checked_sub(self.start())
+                    .ok_or_else(|| {
+                        let tid = self.id().map(|id| id.to_owned());
+                        ::Error::from(BedError::ExonBeforeTranscript(exon.start(), tid))
+                    })
+            })
+            .collect::<::Result<Vec<u64>>>()?;
+        Ok((thick_start, thick_end, block_sizes, block_starts))
+    }
+}
+
+impl AnnotationSet {
+
+    /// Writes the regions of the set as BED6 tracks in the given directory.
+    ///
+    /// The directory is created if it does not exist, and the following files in it are
+    /// overwritten:
+    ///
+    /// * `genes.bed`: gene spans, named with the gene identifiers.
+    /// * `exons.bed`: exons of each transcript.
+    /// * `cds.bed`: coding regions of each exon, excluding stop codons.
+    /// * `utr5.bed` and `utr3.bed`: 5' and 3' untranslated regions of each exon.
+    /// * `introns.bed`: gaps between consecutive exons of each transcript.
+    /// * `tss.bed`: transcription start sites, with the flanks set in the options and clipped
+    ///   to the sequence ends if the options have an assembly.
+    ///
+    /// Apart from genes, all regions are named with their transcript identifiers. Regions of
+    /// transcripts with unknown strands are written to all files but `utr5.bed`, `utr3.bed`,
+    /// and `tss.bed`, since their orientation can not be determined. Regions are written in
+    /// the order of the genes and transcripts in the set.
+    pub fn export_tracks<P: AsRef<Path>>(&self, dir: P, options: &TrackOptions) -> ::Result<()> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir).map_err(|e| ::Error::from(BedError::from(e)))?;
+        let mut tracks = Tracks {
+            genes: FileTrack::create(dir.join("genes.bed"), options.unique)?,
+            exons: FileTrack::create(dir.join("exons.bed"), options.unique)?,
+            cds: FileTrack::create(dir.join("cds.bed"), options.unique)?,
+            utr5: FileTrack::create(dir.join("utr5.bed"), options.unique)?,
+            utr3: FileTrack::create(dir.join("utr3.bed"), options.unique)?,
+            introns: FileTrack::create(dir.join("introns.bed"), options.unique)?,
+            tss: FileTrack::create(dir.join("tss.bed"), options.unique)?,
+        };
+        for gene in self.genes() {
+            tracks.genes.write(gene.seq_name(), gene.start(), gene.end(),
+                               gene.id().unwrap_or("."), gene.strand())?;
+            for transcript in gene.transcripts().values() {
+                tracks.write_transcript(transcript, options)?;
+            }
+        }
+        tracks.flush()
+    }
+}
+
+/// Output files of `AnnotationSet::export_tracks`.
+struct Tracks {
+    genes: FileTrack,
+    exons: FileTrack,
+    cds: FileTrack,
+    utr5: FileTrack,
+    utr3: FileTrack,
+    introns: FileTrack,
+    tss: FileTrack,
+}
+
+impl Tracks {
+
+    fn write_transcript(&mut self, transcript: &Transcript, options: &TrackOptions)
+                        -> ::Result<()>
+    {
+        let (seq_name, strand) = (transcript.seq_name(), transcript.strand());
+        let name = transcript.id().unwrap_or(".");
+        let exons = transcript.exons();
+        for exon in exons {
+            self.exons.write(seq_name, exon.start(), exon.end(), name, strand)?;
+            for fx in exon.features() {
+                let track = match fx.kind() {
+                    &EFK::CDS { .. } => &mut self.cds,
+                    &EFK::UTR5 => &mut self.utr5,
+                    &EFK::UTR3 => &mut self.utr3,
+                    _ => continue,
+                };
+                track.write(seq_name, fx.start(), fx.end(), name, strand)?;
+            }
+        }
+        for (prev, next) in exons.iter().zip(exons.iter().skip(1)) {
+            if prev.end() < next.start() {
+                self.introns.write(seq_name, prev.end(), next.start(), name, strand)?;
+            }
+        }
+        let tss = match strand {
+            &Strand::Forward => Some(transcript.start()),
+            &Strand::Reverse => Some(transcript.end() - 1),
+            &Strand::Unknown => None,
+        };
+        if let Some(tss) = tss {
+            let (start, end) = expand_window(seq_name, (tss, tss + 1), options.tss_flank,
+                                             options.tss_flank, strand,
+                                             options.assembly.as_ref());
+            self.tss.write(seq_name, start, end, name, strand)?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> ::Result<()> {
+        for track in [&mut self.genes, &mut self.exons, &mut self.cds, &mut self.utr5,
+                      &mut self.utr3, &mut self.introns, &mut self.tss].iter_mut() {
+            track.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// Single BED6 output, optionally skipping intervals that were already written.
+struct Track<W: io::Write> {
+    inner: W,
+    written: Option<HashSet<(String, u64, u64, char)>>,
+}
+
+type FileTrack = Track<io::BufWriter<fs::File>>;
+
+impl FileTrack {
+
+    fn create<P: AsRef<Path>>(path: P, unique: bool) -> ::Result<FileTrack> {
+        let file = fs::File::create(path).map_err(|e| ::Error::from(BedError::from(e)))?;
+        Ok(Track {
+            inner: io::BufWriter::new(file),
+            written: if unique { Some(HashSet::new()) } else { None },
+        })
+    }
+}
+
+impl<W: io::Write> Track<W> {
+
+    fn write(&mut self, seq_name: &str, start: u64, end: u64, name: &str, strand: &Strand)
+        -> ::Result<()>
+    {
+        let strand = strand_to_char(strand);
+        if let Some(ref mut written) = self.written {
+            if !written.insert((seq_name.to_owned(), start, end, strand)) {
+                return Ok(());
+            }
+        }
+        writeln!(self.inner, "{}\t{}\t{}\t{}\t0\t{}", seq_name, start, end, name, strand)
+            .map_err(|e| ::Error::from(BedError::from(e)))
+    }
+
+    fn flush(&mut self) -> ::Result<()> {
+        self.inner.flush().map_err(|e| ::Error::from(BedError::from(e)))
+    }
+}
@@ -13,57 +13,58 @@ use std::convert::AsRef;
 use std::error::Error;
 use std::io;
 use std::fs;
+use std::mem;
 use std::path::Path;
 use std::vec;
 
 use bio::io::gff::{self, GffType};
 use itertools::{GroupBy, Group, Itertools};
-use multimap::MultiMap;
 use regex::{Error as RegexError, Regex};
 
-use {Coord, Exon, ExonFeatureKind as EFK, Gene, Strand, TBuilder, Transcript,
-     RawTrxCoords, INIT_START, INIT_END, INIT_COORD, DEF_ID};
+use {Attributes, CancelToken, Coord, Exon, ExonFeatureKind as EFK, Gene, SeqNameAliases, Strand,
+     StrandExt, TBuilder, Transcript, TranscriptCoords, INIT_START, INIT_END, INIT_COORD, DEF_ID};
+use biotype::TRANSCRIPT_BIOTYPE_KEYS;
 use utils::{OptionDeref, update_seq_name};
 
 
 /// Name for gene features.
-const GENE_STR: &'static str = "gene";
+pub(crate) const GENE_STR: &'static str = "gene";
 
 /// Name for transcript features.
-const TRANSCRIPT_STR: &'static str = "transcript";
+pub(crate) const TRANSCRIPT_STR: &'static str = "transcript";
 
 /// Name for exon features.
-const EXON_STR: &'static str = "exon";
+pub(crate) const EXON_STR: &'static str = "exon";
 
 /// Name for generic UTR features.
-const UTR_STR: &'static str = "UTR";
+pub(crate) const UTR_STR: &'static str = "UTR";
 
 /// Name for 5'UTR features.
-const UTR5_STR: &'static str = "UTR5";
+pub(crate) const UTR5_STR: &'static str = "UTR5";
 
 /// Name for 3'UTR features.
-const UTR3_STR: &'static str = "UTR3";
+pub(crate) const UTR3_STR: &'static str = "UTR3";
 
 /// Name for CDS features.
-const CDS_STR: &'static str = "CDS";
+pub(crate) const CDS_STR: &'static str = "CDS";
 
 /// Name for start codon features.
-const START_CODON_STR: &'static str = "start_codon";
+pub(crate) const START_CODON_STR: &'static str = "start_codon";
 
 /// Name for stop codon features.
-const STOP_CODON_STR: &'static str = "stop_codon";
+pub(crate) const STOP_CODON_STR: &'static str = "stop_codon";
 
 /// Name for attribute key of gene identifiers.
-const GENE_ID_STR: &'static str = "gene_id";
+pub(crate) const GENE_ID_STR: &'static str = "gene_id";
 
 /// Name for attribute key of transcript identifiers.
-const TRANSCRIPT_ID_STR: &'static str = "transcript_id";
+pub(crate) const TRANSCRIPT_ID_STR: &'static str = "transcript_id";
 
 /// Value for columns that are undefined, as a string.
-const UNK_STR: &'static str = ".";
+pub(crate) const UNK_STR: &'static str = ".";
 
 /// Value for columns that are undefined, as a char.
-const UNK_CHAR: char = '.';
+pub(crate) const UNK_CHAR: char = '.';
 
 quick_error! {
     /// Errors that occur when reading GFF file variants.
@@ -150,6 +151,9 @@ pub struct Reader<R: io::Read> {
     seq_name_prefix: Option<String>,
     seq_name_lstrip: Option<String>,
     loose_codons: bool,
+    validate_frames: bool,
+    seq_name_aliases: Option<SeqNameAliases>,
+    cancel_token: Option<CancelToken>,
     pub(crate) gff_type: GffType,
 }
 
@@ -164,6 +168,9 @@ impl<R: io::Read> Reader<R> {
             seq_name_prefix: None,
             seq_name_lstrip: None,
             loose_codons: false,
+            validate_frames: false,
+            seq_name_aliases: None,
+            cancel_token: None,
             gff_type: gff_type.clone(),
         }
     }
@@ -201,6 +208,14 @@ impl<R: io::Read> Reader<R> {
         self
     }
 
+    /// Sets the reader to rename sequences using the given alias table.
+    ///
+    /// Aliases are resolved after any prefixing or left-stripping of sequence names.
+    pub fn seq_name_aliases(&mut self, aliases: SeqNameAliases) -> &mut Self {
+        self.seq_name_aliases = Some(aliases);
+        self
+    }
+
     /// Sets the reader to use CDS coordinates when start and/or stop codons for transcripts
     /// can not be found.
     pub fn loose_codons(&mut self, loose_codons: bool) -> &mut Self {
@@ -208,6 +223,25 @@ impl<R: io::Read> Reader<R> {
         self
     }
 
+    /// Sets the reader to check the declared frames of CDS records.
+    ///
+    /// Declared frames are always stored in the CDS features of the transcripts. When this is
+    /// set, a transcript whose declared frames differ from the frames computed from its coding
+    /// region is returned as a `ModelError::FrameMismatch` error for its first differing CDS.
+    pub fn validate_frames(&mut self, validate_frames: bool) -> &mut Self {
+        self.validate_frames = validate_frames;
+        self
+    }
+
+    /// Sets a token for cancelling the reading of records.
+    ///
+    /// The token is checked before each record is read. Once it is cancelled, `transcripts`
+    /// returns `Error::Cancelled`.
+    pub fn cancel_token(&mut self, token: CancelToken) -> &mut Self {
+        self.cancel_token = Some(token);
+        self
+    }
+
     /// Creates an iterator of transcripts.
     ///
     /// This iterator reads all GFF records into memory first, before sorting and grouping them
@@ -217,17 +251,23 @@ impl<R: io::Read> Reader<R> {
 
         let gid_regex = make_gff_id_regex(self.gene_id_attr.as_str(), self.gff_type)?;
         let tid_regex = make_gff_id_regex(self.transcript_id_attr.as_str(), self.gff_type)?;
+        let biotype_regex = make_gff_id_regex(
+            &format!("(?:{})", TRANSCRIPT_BIOTYPE_KEYS.join("|")), self.gff_type)?;
         let prefix = self.seq_name_prefix.clone();
         let lstrip = self.seq_name_lstrip.clone();
+        let aliases = self.seq_name_aliases.clone();
 
         let mut parts = Vec::new();
         for result in self.raw_rows_stream() {
             let mut row = result.map_err(::Error::from)?;
             update_seq_name(&mut row.0, prefix.as_deref(),
                             lstrip.as_deref().map(|v| (v, v.len())));
+            if let Some(ref aliases) = aliases {
+                aliases.update_seq_name(&mut row.0);
+            }
             match row.2.as_str() {
                 TRANSCRIPT_STR | EXON_STR | CDS_STR | START_CODON_STR | STOP_CODON_STR => {
-                    let rf = TrxPart::try_from_row(row, &gid_regex, &tid_regex)
+                    let rf = TrxPart::try_from_row(row, &gid_regex, &tid_regex, &biotype_regex)
                         .map_err(::Error::from)?;
                     parts.push(rf);
                 },
@@ -239,13 +279,16 @@ impl<R: io::Read> Reader<R> {
         Ok(GffTranscripts {
             groups: parts.into_iter().group_by(TrxPart::transcript_group_key),
             loose_codons: self.loose_codons,
+            validate_frames: self.validate_frames,
         })
     }
 
     /// Creates an iterator of GFF rows.
     pub(crate) fn raw_rows_stream(&mut self) -> GffRawRows<R> {
         GffRawRows {
-            inner: self.inner.raw_rows()
+            inner: self.inner.raw_rows(),
+            cancel_token: self.cancel_token.as_ref(),
+            cancelled: false,
         }
     }
 }
@@ -261,6 +304,8 @@ impl Reader<fs::File> {
 /// Iterator over GFF rows.
 pub(crate) struct GffRawRows<'a, R: 'a> where R: io::Read {
     inner: gff::RawRows<'a, R>,
+    cancel_token: Option<&'a CancelToken>,
+    cancelled: bool,
 }
 
 impl<'a, R> Iterator for GffRawRows<'a, R> where R: io::Read {
@@ -268,6 +313,13 @@ impl<'a, R> Iterator for GffRawRows<'a, R> where R: io::Read {
     type Item = ::Result<gff::RawRow>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if self.cancelled {
+            return None;
+        }
+        if self.cancel_token.map(|token| token.is_cancelled()).unwrap_or(false) {
+            self.cancelled = true;
+            return Some(Err(::Error::Cancelled));
+        }
         self.inner.next()
             .map(|row| row.map_err(|e| ::Error::from(GffError::from(e))))
     }
@@ -276,16 +328,18 @@ impl<'a, R> Iterator for GffRawRows<'a, R> where R: io::Read {
 /// Helper struct for creating transcripts.
 ///
 /// This struct is meant to be used when complete parsing of the GFF attribute column is not
-/// required. Instead, only the minimum required values (gene and transcript identifiers) are
-/// parsed.
+/// required. Instead, only the minimum required values (gene and transcript identifiers) and the
+/// transcript biotype are parsed.
 #[derive(Debug, PartialEq)]
 struct TrxPart {
     feature: String,
     chrom: String,
     coord: Coord<u64>,
     strand: Strand,
+    frame: Option<u8>,
     transcript_id: String,
     gene_id: String,
+    biotype: Option<String>,
 }
 
 /// The type used for sorting GFF records.
@@ -296,11 +350,13 @@ type TrxSortKey = (String, String, String, u64, u64, u8);
 
 impl TrxPart {
 
-    /// Creates a `TrxPart` from the given GFF row and the gene and transcript identifier regexes.
+    /// Creates a `TrxPart` from the given GFF row and the gene and transcript identifier and
+    /// transcript biotype regexes.
     fn try_from_row(
         row: gff::RawRow,
         gx_regex: &Regex,
         trx_regex: &Regex,
+        biotype_regex: &Regex,
     ) -> Result<Self, GffError> {
 
         let gx_id = gx_regex.captures(&row.8)
@@ -313,13 +369,19 @@ impl TrxPart {
             .map(|v| v.as_str().to_owned())
             .ok_or(GffError::MissingTranscriptId)?;
 
+        let biotype = biotype_regex.captures(&row.8)
+            .and_then(|cap| cap.name("value"))
+            .map(|v| v.as_str().to_owned());
+
         Ok(TrxPart {
             feature: row.2,
             chrom: row.0,
             coord: (row.3 - 1, row.4),
             strand: Strand::from_char(&row.6).unwrap(),
+            frame: str_to_frame(&row.7.to_string()),
             transcript_id: trx_id,
             gene_id: gx_id,
+            biotype: biotype,
         })
     }
 
@@ -348,12 +410,13 @@ impl TrxPart {
 ///
 /// This struct is meant to be updated as features of a transcript are parsed.
 #[derive(Debug, Default)]
-struct TrxCoords {
+pub(crate) struct TrxCoords {
     trx_coord: Option<Coord<u64>>,
     exon_coords: Vec<Coord<u64>>,
     cds_coord: Option<Coord<u64>>,
     codon_5: Option<u64>,
     codon_3: Option<u64>,
+    cds_frames: Vec<(u64, u8)>,
 }
 
 impl TrxCoords {
@@ -361,7 +424,7 @@ impl TrxCoords {
     /// Sets the transcript 5'-most and 3'-most coordinates.
     ///
     /// If this is set more than once, an error will be returned.
-    fn set_trx_coord(&mut self, coord: Coord<u64>) -> Result<(), GffError> {
+    pub(crate) fn set_trx_coord(&mut self, coord: Coord<u64>) -> Result<(), GffError> {
         if let None = self.trx_coord {
             self.trx_coord = Some(coord);
         } else {
@@ -371,37 +434,59 @@ impl TrxCoords {
     }
 
     /// Adds an exon coordinate of the transcript.
-    fn add_exon_coord(&mut self, coord: Coord<u64>) {
+    pub(crate) fn add_exon_coord(&mut self, coord: Coord<u64>) {
         self.exon_coords.push(coord);
     }
 
     /// Adds a CDS coordinate.
     ///
     /// This will update the 5' and 3'-most CDS coordinates.
-    fn include_cds_coord(&mut self, coord: Coord<u64>) {
+    pub(crate) fn include_cds_coord(&mut self, coord: Coord<u64>) {
         self.cds_coord = (self.cds_coord).or(Some(INIT_COORD))
             .map(|(a, b)| (min(a, coord.0), max(b, coord.1)));
     }
 
+    /// Adds the declared frame of a CDS, keyed by the 5' coordinate of the CDS.
+    pub(crate) fn add_cds_frame(&mut self, coord: Coord<u64>, strand: Strand, frame: u8) {
+        let coord_5 = if strand == Strand::Reverse { coord.1 } else { coord.0 };
+        self.cds_frames.push((coord_5, frame));
+    }
+
+    /// Removes and returns the declared CDS frames.
+    pub(crate) fn take_cds_frames(&mut self) -> Vec<(u64, u8)> {
+        mem::replace(&mut self.cds_frames, Vec::new())
+    }
+
     /// Adds a 5'-most codon coordinate.
-    fn include_codon_5(&mut self, coord_5: u64) {
+    pub(crate) fn include_codon_5(&mut self, coord_5: u64) {
         self.codon_5 = (self.codon_5).or(Some(INIT_START))
             .map(|c| min(c, coord_5));
     }
 
     /// Ads a 3'-most codon coordinate.
-    fn include_codon_3(&mut self, coord_3: u64) {
+    pub(crate) fn include_codon_3(&mut self, coord_3: u64) {
         self.codon_3 = (self.codon_3).or(Some(INIT_END))
             .map(|c| max(c, coord_3));
     }
 
+    /// Sets the transcript coordinates to the 5'-most and 3'-most exon coordinates.
+    ///
+    /// This is only done when no transcript coordinates have been set, as is the case for
+    /// formats without transcript features.
+    pub(crate) fn infer_trx_coord(&mut self) {
+        if self.trx_coord.is_none() && !self.exon_coords.is_empty() {
+            self.trx_coord = Some(self.exon_coords.iter()
+                .fold(INIT_COORD, |acc, c| (min(acc.0, c.0), max(acc.1, c.1))));
+        }
+    }
+
     /// Returns coordinates required to create a transcript.
-    fn resolve<'a>(
+    pub(crate) fn resolve<'a>(
         self,
         strand: Strand,
         loose_codons: bool,
         tid: Option<&'a str>
-    ) -> Result<RawTrxCoords, GffError> {
+    ) -> Result<TranscriptCoords, GffError> {
 
         let trx_coord = self.trx_coord
             .ok_or(GffError::MissingTranscript(tid.map(|v| v.to_owned())))?;
@@ -445,7 +530,7 @@ impl TrxCoords {
             }
         }
 
-        Ok((trx_coord, self.exon_coords, coding_coord))
+        Ok(TranscriptCoords::from((trx_coord, self.exon_coords, coding_coord)))
     }
 }
 
@@ -453,6 +538,7 @@ impl TrxCoords {
 pub struct GffTranscripts {
     groups: GroupBy<TrxGroupKey, vec::IntoIter<TrxPart>, TrxGroupFunc>,
     loose_codons: bool,
+    validate_frames: bool,
 }
 
 /// The type used for grouping records into transcripts.
@@ -474,8 +560,12 @@ impl Iterator for GffTranscripts {
         let group_to_transcript = |(key, tps): (TrxGroupKey, TrxGroup)| {
             let (gid, tid, chrom, strand) = key;
             let mut tc = TrxCoords::default();
+            let mut biotype = None;
 
             for tp in tps {
+                if biotype.is_none() {
+                    biotype = tp.biotype;
+                }
                 match (tp.feature.as_str(), strand) {
                     (TRANSCRIPT_STR, _) => {
                         tc.set_trx_coord(tp.coord)
@@ -486,6 +576,9 @@ impl Iterator for GffTranscripts {
                     },
                     (CDS_STR, _) => {
                         tc.include_cds_coord(tp.coord);
+                        if let Some(frame) = tp.frame {
+                            tc.add_cds_frame(tp.coord, strand, frame);
+                        }
                     },
                     (START_CODON_STR, Strand::Forward) | (STOP_CODON_STR, Strand::Reverse) => {
                         tc.include_codon_5(tp.coord.0);
@@ -497,17 +590,20 @@ impl Iterator for GffTranscripts {
                 }
             }
 
-            let ((trx_start, trx_end), exn_coords, coding_coord) =
-                tc.resolve(strand, self.loose_codons, Some(tid.as_str()))
-                    .map_err(::Error::from)?;
+            let frames = tc.take_cds_frames();
+            let coords = tc.resolve(strand, self.loose_codons, Some(tid.as_str()))
+                .map_err(::Error::from)?;
 
-            TBuilder::new(chrom, trx_start, trx_end)
+            let mut builder = TBuilder::from_coords(chrom, coords)
                 .id(tid)
                 .gene_id(gid)
                 .strand(strand)
-                .coords(exn_coords, coding_coord)
-                .coding_incl_stop(true)
-                .build()
+                .coding_incl_stop(true);
+            if let Some(biotype) = biotype {
+                builder = builder.biotype(biotype);
+            }
+            let transcript = builder.build()?;
+            apply_cds_frames(transcript, frames, self.validate_frames)
         };
 
         self.groups.into_iter().map(group_to_transcript).next()
@@ -545,7 +641,7 @@ impl Gene {
     /// Transforms the gene into GFF records.
     pub fn into_gff_records(mut self) -> ::Result<Vec<gff::Record>> {
 
-        let mut attribs = self.set_attributes(MultiMap::new());
+        let mut attribs = self.set_attributes(Attributes::new());
 
         self.id()
             .ok_or(GffError::MissingGeneId)
@@ -561,7 +657,7 @@ impl Gene {
             .score(score)
             .strand(strand_to_char(&self.strand()))
             .frame(UNK_CHAR)
-            .attributes(attribs)
+            .attributes(attribs.into_multimap())
             .build()
             .map_err(|e| ::Error::from(GffError::from(e)))?;
         recs.push(gx_record);
@@ -588,7 +684,7 @@ impl Transcript {
     /// Transforms the transcript into GFF records.
     pub fn into_gff_records(mut self) -> ::Result<Vec<gff::Record>> {
 
-        let mut attribs = self.set_attributes(MultiMap::new());
+        let mut attribs = self.set_attributes(Attributes::new());
 
         self.gene_id()
             .ok_or(GffError::MissingGeneId)
@@ -608,7 +704,7 @@ impl Transcript {
             .score(score)
             .strand(strand_to_char(&self.strand()))
             .frame(UNK_CHAR)
-            .attributes(attribs)
+            .attributes(attribs.into_multimap())
             .build()
             .map_err(|e| ::Error::from(GffError::from(e)))?;
         recs.push(trx_record);
@@ -644,7 +740,7 @@ impl Exon {
     /// Transforms the exon into GFF records.
     pub fn into_gff_records(mut self) -> ::Result<Vec<gff::Record>> {
 
-        let mut attribs = self.set_attributes(MultiMap::new());
+        let mut attribs = self.set_attributes(Attributes::new());
 
         self.gene_id()
             .ok_or(GffError::MissingGeneId)
@@ -666,7 +762,7 @@ impl Exon {
                 .score(score.as_str())
                 .strand(strand_to_char(&self.strand()))
                 .frame(frame)
-                .attributes(attribs.clone())
+                .attributes(attribs.clone().into_multimap())
                 .build()
                 .map_err(|e| ::Error::from(GffError::from(e)))?;
 
@@ -679,7 +775,7 @@ impl Exon {
             .score(score.as_str())
             .strand(strand_to_char(&self.strand()))
             .frame(UNK_CHAR)
-            .attributes(attribs)
+            .attributes(attribs.into_multimap())
             .build()
             .map_err(|e| ::Error::from(GffError::from(e)))?;
         recs[0] = exn_record;
@@ -690,7 +786,7 @@ impl Exon {
 
 /// Helper function to extract source and score attributes.
 #[inline(always)]
-fn extract_source_score(attributes: &mut MultiMap<String, String>) -> (String, String) {
+pub(crate) fn extract_source_score(attributes: &mut Attributes) -> (String, String) {
     let source = attributes.remove("source")
         .and_then(|mut vec| vec.pop())
         .unwrap_or(UNK_STR.to_owned());
@@ -702,17 +798,38 @@ fn extract_source_score(attributes: &mut MultiMap<String, String>) -> (String, S
 
 /// Helper function to create a char given a strand reference.
 #[inline(always)]
-fn strand_to_char(strand: &Strand) -> char {
-    match strand {
-        &Strand::Forward => '+',
-        &Strand::Reverse => '-',
-        &Strand::Unknown => UNK_CHAR,
+pub(crate) fn strand_to_char(strand: &Strand) -> char {
+    strand.to_char()
+}
+
+/// Helper function to parse a frame column value.
+#[inline(always)]
+pub(crate) fn str_to_frame(raw: &str) -> Option<u8> {
+    match raw {
+        "0" => Some(0),
+        "1" => Some(1),
+        "2" => Some(2),
+        _ => None,
+    }
+}
+
+/// Helper function to store the declared CDS frames of a transcript, checking them against the
+/// computed frames if requested.
+pub(crate) fn apply_cds_frames(mut transcript: Transcript, frames: Vec<(u64, u8)>,
+                               validate_frames: bool) -> ::Result<Transcript>
+{
+    transcript.set_cds_frames(frames);
+    if validate_frames {
+        if let Some(err) = transcript.frame_mismatches().into_iter().next() {
+            return Err(::Error::Model(err));
+        }
     }
+    Ok(transcript)
 }
 
 /// Helper function to create a char given an optional frame.
 #[inline(always)]
-fn frame_to_char(frame: &Option<u8>) -> char {
+pub(crate) fn frame_to_char(frame: &Option<u8>) -> char {
     match frame {
         &Some(0) => '0',
         &Some(1) => '1',
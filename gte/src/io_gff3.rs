@@ -0,0 +1,336 @@
+/*! Writer for the GFF3 format.
+
+The writer serializes gene models into [GFF3](https://github.com/The-Sequence-Ontology/Specifications/blob/master/gff3.md)
+records linked by their `ID` and `Parent` attributes. Each gene is written as a gene record,
+followed by its transcript records, which are in turn followed by their exon and exon feature
+records. A `###` directive is written after every gene, so that downstream tools may process the
+output one gene at a time. Protein domains of the transcripts may be written along with their
+gene, before the directive.
+
+Features without identifiers are given identifiers derived from their parents, so that writing
+the same models always results in the same output.
+*/
+use std::cmp::{max, Ordering};
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use {Attributes, ExonFeatureKind as EFK, Gene, ProteinDomain, Transcript};
+use io_gff::{extract_source_score, frame_to_char, strand_to_char, CDS_STR, EXON_STR, GENE_STR,
+             START_CODON_STR, STOP_CODON_STR, TRANSCRIPT_STR, UTR_STR, UNK_STR};
+use sort::{cmp_positions, ChromOrder};
+
+
+/// Name for 5'UTR features, as defined by the Sequence Ontology.
+const UTR5_STR: &'static str = "five_prime_UTR";
+
+/// Name for 3'UTR features, as defined by the Sequence Ontology.
+const UTR3_STR: &'static str = "three_prime_UTR";
+
+/// Name for transcripts with a coding region, as defined by the Sequence Ontology.
+const MRNA_STR: &'static str = "mRNA";
+
+/// Attribute key for feature identifiers.
+const ID_STR: &'static str = "ID";
+
+/// Attribute key for parent feature identifiers.
+const PARENT_STR: &'static str = "Parent";
+
+/// Attribute key for feature names.
+const NAME_STR: &'static str = "Name";
+
+/// Name for protein domains, as defined by the Sequence Ontology.
+const DOMAIN_STR: &'static str = "polypeptide_domain";
+
+quick_error! {
+    /// Errors that occur when writing GFF3 files.
+    #[derive(Debug)]
+    pub enum Gff3Error {
+        /// Errors propagated from writing the underlying output.
+        Io(err: io::Error) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// GFF3 writer.
+pub struct Writer<W: io::Write> {
+    inner: W,
+    version_written: bool,
+    num_genes: usize,
+}
+
+impl<W: io::Write> Writer<W> {
+
+    /// Creates a GFF3 writer from another writer.
+    pub fn from_writer(in_writer: W) -> Writer<W> {
+        Writer {
+            inner: in_writer,
+            version_written: false,
+            num_genes: 0,
+        }
+    }
+
+    /// Writes a `##sequence-region` directive for the given zero-based, half-open coordinates.
+    ///
+    /// Following the format specification, this must be done before any gene is written.
+    pub fn write_sequence_region(&mut self, seq_name: &str, start: u64, end: u64)
+        -> ::Result<()>
+    {
+        self.write_version()?;
+        writeln!(self.inner, "##sequence-region {} {} {}", seq_name, start + 1, end)
+            .map_err(|e| ::Error::from(Gff3Error::from(e)))
+    }
+
+    /// Writes the given gene along with all its transcripts and exons.
+    ///
+    /// Genes without identifiers are given identifiers based on the number of genes already
+    /// written.
+    pub fn write_gene(&mut self, gene: &Gene) -> ::Result<()> {
+        self.write_gene_with_domains(gene, &[])
+    }
+
+    /// Writes the given gene along with all its transcripts and exons, followed by the given
+    /// protein domains of its transcripts.
+    ///
+    /// Each domain is written as `polypeptide_domain` records, one for each of its genomic
+    /// blocks, sharing an identifier and having the domain transcript as their parent. Domains
+    /// of transcripts not in the gene are ignored.
+    pub fn write_gene_with_domains(&mut self, gene: &Gene, domains: &[ProteinDomain])
+        -> ::Result<()>
+    {
+        self.write_version()?;
+        self.num_genes += 1;
+        let gid = gene.id()
+            .map(|v| v.to_owned())
+            .unwrap_or_else(|| format!("gene{}", self.num_genes));
+
+        let mut attribs = gene.attributes().clone();
+        let (source, score) = extract_source_score(&mut attribs);
+        self.write_line(gene.seq_name(), &source, GENE_STR, gene.start(), gene.end(), &score,
+                        strand_to_char(gene.strand()), None,
+                        &[(ID_STR, gid.as_str())], &attribs)?;
+
+        for (idx, transcript) in gene.transcripts().values().enumerate() {
+            self.write_transcript_parts(transcript, &gid, idx + 1)?;
+        }
+        for domain in domains {
+            if gene.transcripts().contains_key(domain.transcript_id()) {
+                self.write_domain(domain)?;
+            }
+        }
+
+        writeln!(self.inner, "###").map_err(|e| ::Error::from(Gff3Error::from(e)))
+    }
+
+    /// Writes the given genes, sorted as in `sort::by_position`.
+    ///
+    /// A `##sequence-region` directive spanning all genes is written for each sequence before
+    /// any gene is written.
+    pub fn write_genes<'a, I>(&mut self, genes: I) -> ::Result<()>
+        where I: IntoIterator<Item=&'a Gene>
+    {
+        let mut sorted = genes.into_iter().collect::<Vec<&Gene>>();
+        sorted.sort_by(|a, b| cmp_genes(a, b));
+
+        let mut regions: Vec<(&str, u64, u64)> = Vec::new();
+        for gene in sorted.iter() {
+            if let Some(region) = regions.last_mut() {
+                if region.0 == gene.seq_name() {
+                    region.2 = max(region.2, gene.end());
+                    continue;
+                }
+            }
+            regions.push((gene.seq_name(), gene.start(), gene.end()));
+        }
+        for &(seq_name, start, end) in regions.iter() {
+            self.write_sequence_region(seq_name, start, end)?;
+        }
+
+        for gene in sorted {
+            self.write_gene(gene)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the records of a transcript and its exons.
+    fn write_transcript_parts(&mut self, transcript: &Transcript, gid: &str, num: usize)
+        -> ::Result<()>
+    {
+        let tid = transcript.id()
+            .map(|v| v.to_owned())
+            .unwrap_or_else(|| format!("{}.{}", gid, num));
+        let strand = strand_to_char(transcript.strand());
+        let mut attribs = transcript.attributes().clone();
+        let (source, score) = extract_source_score(&mut attribs);
+        let feature = match transcript.coding_coord(true) {
+            Some(_) => MRNA_STR,
+            None => TRANSCRIPT_STR,
+        };
+        self.write_line(transcript.seq_name(), &source, feature,
+                        transcript.start(), transcript.end(), &score, strand, None,
+                        &[(ID_STR, tid.as_str()), (PARENT_STR, gid)], &attribs)?;
+
+        let num_exons = transcript.exons().len();
+        let cds_id = format!("{}.cds", tid);
+        for (idx, exon) in transcript.exons().iter().enumerate() {
+            let exon_num = match strand {
+                '-' => num_exons - idx,
+                _ => idx + 1,
+            };
+            let exon_id = exon.id()
+                .map(|v| v.to_owned())
+                .unwrap_or_else(|| format!("{}.exon{}", tid, exon_num));
+            let mut attribs = exon.attributes().clone();
+            let (source, score) = extract_source_score(&mut attribs);
+            self.write_line(exon.seq_name(), &source, EXON_STR, exon.start(), exon.end(),
+                            &score, strand, None,
+                            &[(ID_STR, exon_id.as_str()), (PARENT_STR, tid.as_str())],
+                            &attribs)?;
+
+            let mut features = exon.features().iter().collect::<Vec<_>>();
+            features.sort_by_key(|fx| (fx.start(), fx.end()));
+            for fx in features {
+                let (feature, phase, fid) = match fx.kind() {
+                    &EFK::UTR => (UTR_STR, None, None),
+                    &EFK::UTR5 => (UTR5_STR, None, None),
+                    &EFK::UTR3 => (UTR3_STR, None, None),
+                    &EFK::CDS { frame: f } => (CDS_STR, f, Some(cds_id.as_str())),
+                    &EFK::StartCodon { frame: f } => (START_CODON_STR, f, None),
+                    &EFK::StopCodon { frame: f } => (STOP_CODON_STR, f, None),
+                    &EFK::Any(ref s) => (s.as_str(), None, None),
+                };
+                let mut ids = Vec::with_capacity(2);
+                if let Some(fid) = fid {
+                    ids.push((ID_STR, fid));
+                }
+                ids.push((PARENT_STR, tid.as_str()));
+                self.write_line(exon.seq_name(), &source, feature, fx.start(), fx.end(),
+                                UNK_STR, strand, phase, &ids, &Attributes::new())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the records of a protein domain.
+    fn write_domain(&mut self, domain: &ProteinDomain) -> ::Result<()> {
+        let record = domain.record();
+        let did = format!("{}.{}.{}", domain.transcript_id(), record.domain_id(),
+                          record.residues().start + 1);
+        let name = record.domain_name().unwrap_or(record.domain_id());
+        let strand = strand_to_char(domain.strand());
+        for &(start, end) in domain.blocks() {
+            self.write_line(domain.seq_name(), UNK_STR, DOMAIN_STR, start, end, UNK_STR,
+                            strand, None,
+                            &[(ID_STR, did.as_str()), (PARENT_STR, domain.transcript_id()),
+                              (NAME_STR, name)],
+                            &Attributes::new())?;
+        }
+        Ok(())
+    }
+
+    /// Writes the version directive, if it has not been written yet.
+    fn write_version(&mut self) -> ::Result<()> {
+        if !self.version_written {
+            self.version_written = true;
+            writeln!(self.inner, "##gff-version 3")
+                .map_err(|e| ::Error::from(Gff3Error::from(e)))?;
+        }
+        Ok(())
+    }
+
+    /// Writes a single GFF3 record line.
+    fn write_line(
+        &mut self,
+        seq_name: &str,
+        source: &str,
+        feature: &str,
+        start: u64,
+        end: u64,
+        score: &str,
+        strand: char,
+        phase: Option<u8>,
+        ids: &[(&str, &str)],
+        attribs: &Attributes,
+    ) -> ::Result<()> {
+        writeln!(self.inner, "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                 escape(seq_name), source, feature, start + 1, end, score, strand,
+                 frame_to_char(&phase),
+                 format_attributes(ids, attribs))
+            .map_err(|e| ::Error::from(Gff3Error::from(e)))
+    }
+}
+
+impl Writer<fs::File> {
+
+    /// Creates a GFF3 writer that writes to the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let f = fs::File::create(path)?;
+        Ok(Writer::from_writer(f))
+    }
+}
+
+impl Writer<Vec<u8>> {
+
+    /// Creates a GFF3 writer that writes to an in-memory buffer.
+    ///
+    /// The initial capacity of the buffer is 64 KiB.
+    pub fn from_memory() -> Writer<Vec<u8>> {
+        Writer::from_writer(Vec::with_capacity(1024 * 64))
+    }
+
+    /// Returns the values of the in-memory buffer as a string.
+    pub fn as_string(&mut self) -> &str {
+        // the buffer only ever contains values written from `String`s
+        ::std::str::from_utf8(self.inner.as_slice()).expect("valid UTF-8 output")
+    }
+}
+
+/// Helper function for ordering genes by their positions and then identifiers.
+fn cmp_genes(a: &Gene, b: &Gene) -> Ordering {
+    cmp_positions(a, b, ChromOrder::default()).then_with(|| a.id().cmp(&b.id()))
+}
+
+/// Helper function to create the GFF3 attribute column.
+///
+/// The identifier attributes are written first, followed by the other attributes in their
+/// stored order. Multiple values of the same key are separated by commas.
+fn format_attributes(ids: &[(&str, &str)], attribs: &Attributes) -> String {
+    let mut items = ids.iter()
+        .map(|&(key, value)| format!("{}={}", key, escape(value)))
+        .collect::<Vec<String>>();
+
+    for (key, values) in attribs.iter_all() {
+        if key == ID_STR || key == PARENT_STR {
+            continue;
+        }
+        let joined = values.iter().map(|v| escape(v)).collect::<Vec<String>>().join(",");
+        items.push(format!("{}={}", escape(key), joined));
+    }
+
+    if items.is_empty() {
+        return UNK_STR.to_owned();
+    }
+    items.join(";")
+}
+
+/// Helper function to percent-encode characters with special meanings in GFF3 columns.
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\t' | '\n' | '\r' | '%' | ';' | '=' | '&' | ',' | '\x7f' => {
+                escaped.push_str(&format!("%{:02X}", c as u32));
+            },
+            c if (c as u32) < 0x20 => {
+                escaped.push_str(&format!("%{:02X}", c as u32));
+            },
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,1393 @@
+/*! Reader for the GTF format.
+
+The GTF format is a variant of the GFF format in which features of a transcript are linked using
+the `gene_id` and `transcript_id` attributes. The specification of version 2.2 of the format can
+be found [here](http://mblab.wustl.edu/GTF22.html).
+
+Unlike the GFF reader, the reader provided by this module parses the complete attribute column of
+each record, so the created transcripts and genes contain all attributes of their features. The
+writer provided by this module emits the complete feature hierarchy of the models, from genes down
+to the exon features.
+*/
+use std::cmp::{max, min};
+use std::convert::AsRef;
+use std::error::Error;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use linked_hash_map::{self, LinkedHashMap};
+
+use {Attributes, AnnotationSet, CancelToken, Coord, ErrorPolicy, Exon, ExonFeatureKind as EFK,
+     Gene, GBuilder, LoadReport, SeqNameAliases, Strand, TBuilder, Transcript, INIT_COORD};
+use biotype::{biotype_from_attributes, GENE_BIOTYPE_KEYS, TRANSCRIPT_BIOTYPE_KEYS};
+use instrument::LoadTimer;
+use io_gff::{TrxCoords, GffError, GENE_STR, TRANSCRIPT_STR, EXON_STR, CDS_STR, START_CODON_STR,
+             STOP_CODON_STR, UTR_STR, GENE_ID_STR, TRANSCRIPT_ID_STR, UNK_STR,
+             apply_cds_frames, extract_source_score, frame_to_char, strand_to_char};
+use sort::{cmp_positions, ChromOrder};
+use utils::{OptionDeref, describe_attributes, update_seq_name};
+
+
+/// Name for 5'UTR features, as defined in GTF version 2.2.
+const UTR5_STR: &'static str = "5UTR";
+
+/// Name for 3'UTR features, as defined in GTF version 2.2.
+const UTR3_STR: &'static str = "3UTR";
+
+/// Name for attribute key of exon numbers.
+const EXON_NUMBER_STR: &'static str = "exon_number";
+
+quick_error! {
+    /// Errors that occur when reading or writing GTF files.
+    #[derive(Debug)]
+    pub enum GtfError {
+        /// Occurs when a GTF line does not contain the expected columns or values.
+        InvalidRecord(line_num: usize) {
+            description("invalid gtf record")
+            display(self_) -> ("{}, line: {}", self_.description(), line_num)
+        }
+        /// Occurs when the attribute column of a GTF line can not be parsed.
+        InvalidAttributes(line_num: usize) {
+            description("invalid gtf attributes")
+            display(self_) -> ("{}, line: {}", self_.description(), line_num)
+        }
+        /// Occurs when a GTF line does not have any gene identifier attribute.
+        MissingGeneId(line_num: usize) {
+            description("gene identifier attribute not found")
+            display(self_) -> ("{}, line: {}", self_.description(), line_num)
+        }
+        /// Occurs when a transcript feature line does not have any transcript identifier
+        /// attribute.
+        MissingTranscriptId(line_num: usize) {
+            description("transcript identifier attribute not found")
+            display(self_) -> ("{}, line: {}", self_.description(), line_num)
+        }
+        /// Errors propagated from reading or writing the underlying input or output.
+        Io(err: io::Error) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// GTF record type.
+///
+/// This type represents a single line of a GTF file. Coordinates are zero-based, half-open.
+#[derive(Debug, Clone)]
+pub struct GtfRecord {
+    seq_name: String,
+    source: String,
+    feature: String,
+    start: u64,
+    end: u64,
+    score: Option<f64>,
+    strand: Strand,
+    frame: Option<u8>,
+    attributes: Attributes,
+}
+
+impl GtfRecord {
+
+    /// Creates a record of the given feature type and zero-based, half-open coordinates.
+    ///
+    /// All other columns are set to undefined values.
+    pub fn new<T, F>(seq_name: T, feature: F, start: u64, end: u64) -> Self
+        where T: Into<String>, F: Into<String>
+    {
+        GtfRecord {
+            seq_name: seq_name.into(),
+            source: UNK_STR.to_owned(),
+            feature: feature.into(),
+            start: start,
+            end: end,
+            score: None,
+            strand: Strand::Unknown,
+            frame: None,
+            attributes: Attributes::new(),
+        }
+    }
+
+    /// Returns the sequence name.
+    pub fn seq_name(&self) -> &str {
+        self.seq_name.as_str()
+    }
+
+    /// Sets the sequence name.
+    pub fn set_seq_name<T>(&mut self, seq_name: T)
+        where T: Into<String>
+    {
+        self.seq_name = seq_name.into();
+    }
+
+    /// Returns the source.
+    pub fn source(&self) -> &str {
+        self.source.as_str()
+    }
+
+    /// Sets the source.
+    pub fn set_source<T>(&mut self, source: T)
+        where T: Into<String>
+    {
+        self.source = source.into();
+    }
+
+    /// Returns the feature type.
+    pub fn feature(&self) -> &str {
+        self.feature.as_str()
+    }
+
+    /// Returns the genome-wise 5'-most coordinate of the record.
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// Returns the genome-wise 3'-most coordinate of the record.
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// Sets the genome-wise 5'-most and 3'-most coordinates of the record.
+    pub fn set_coords(&mut self, start: u64, end: u64) {
+        self.start = start;
+        self.end = end;
+    }
+
+    /// Returns the score, if defined.
+    pub fn score(&self) -> Option<f64> {
+        self.score
+    }
+
+    /// Sets the score.
+    pub fn set_score(&mut self, score: Option<f64>) {
+        self.score = score;
+    }
+
+    /// Returns the strand.
+    pub fn strand(&self) -> &Strand {
+        &self.strand
+    }
+
+    /// Sets the strand.
+    pub fn set_strand(&mut self, strand: Strand) {
+        self.strand = strand;
+    }
+
+    /// Returns the frame, if defined.
+    pub fn frame(&self) -> Option<u8> {
+        self.frame
+    }
+
+    /// Sets the frame.
+    pub fn set_frame(&mut self, frame: Option<u8>) {
+        self.frame = frame;
+    }
+
+    /// Returns the attributes.
+    pub fn attributes(&self) -> &Attributes {
+        &self.attributes
+    }
+
+    /// Returns a mutable reference to the attributes.
+    pub fn attributes_mut(&mut self) -> &mut Attributes {
+        &mut self.attributes
+    }
+
+    /// Returns the first value of the given attribute key, if present.
+    pub fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes.get(key).map(|v| v.as_str())
+    }
+
+    /// Parses a record from the given GTF line.
+    ///
+    /// The line number is only used for creating error values.
+    pub fn try_from_line(line: &str, line_num: usize) -> Result<Self, GtfError> {
+        let cols = line.split('\t').collect::<Vec<&str>>();
+        if cols.len() < 9 {
+            return Err(GtfError::InvalidRecord(line_num));
+        }
+        let invalid = |_| GtfError::InvalidRecord(line_num);
+
+        let start = u64::from_str(cols[3]).map_err(invalid)?;
+        let end = u64::from_str(cols[4]).map_err(invalid)?;
+        if start == 0 || start > end {
+            return Err(GtfError::InvalidRecord(line_num));
+        }
+        let score = match cols[5] {
+            UNK_STR => None,
+            raw => Some(f64::from_str(raw).map_err(|_| GtfError::InvalidRecord(line_num))?),
+        };
+        let strand = cols[6].chars().next()
+            .and_then(|c| Strand::from_char(&c).ok())
+            .ok_or(GtfError::InvalidRecord(line_num))?;
+        let frame = match cols[7] {
+            UNK_STR => None,
+            "0" => Some(0),
+            "1" => Some(1),
+            "2" => Some(2),
+            _ => return Err(GtfError::InvalidRecord(line_num)),
+        };
+        let attributes = parse_attributes(cols[8])
+            .ok_or(GtfError::InvalidAttributes(line_num))?;
+
+        Ok(GtfRecord {
+            seq_name: cols[0].to_owned(),
+            source: cols[1].to_owned(),
+            feature: cols[2].to_owned(),
+            start: start - 1,
+            end: end,
+            score: score,
+            strand: strand,
+            frame: frame,
+            attributes: attributes,
+        })
+    }
+}
+
+/// GTF reader.
+pub struct Reader<R: io::Read> {
+    inner: io::BufReader<R>,
+    gene_id_attr: String,
+    transcript_id_attr: String,
+    seq_name_prefix: Option<String>,
+    seq_name_lstrip: Option<String>,
+    loose_codons: bool,
+    validate_frames: bool,
+    seq_name_aliases: Option<SeqNameAliases>,
+    map_record: Option<Box<FnMut(&mut GtfRecord)>>,
+    cancel_token: Option<CancelToken>,
+    on_error: ErrorPolicy,
+}
+
+impl<R: io::Read> Reader<R> {
+
+    /// Creates a GTF reader from another reader.
+    pub fn from_reader(in_reader: R) -> Reader<R> {
+        Reader {
+            inner: io::BufReader::new(in_reader),
+            gene_id_attr: GENE_ID_STR.to_owned(),
+            transcript_id_attr: TRANSCRIPT_ID_STR.to_owned(),
+            seq_name_prefix: None,
+            seq_name_lstrip: None,
+            loose_codons: false,
+            validate_frames: false,
+            seq_name_aliases: None,
+            map_record: None,
+            cancel_token: None,
+            on_error: ErrorPolicy::Abort,
+        }
+    }
+
+    /// Sets the reader to use the given attribute key for getting gene identifiers.
+    pub fn gene_id_attr<T>(&mut self, gene_id_attr: T) -> &mut Self
+        where T: Into<String>
+    {
+        self.gene_id_attr = gene_id_attr.into();
+        self
+    }
+
+    /// Sets the reader to use the given attribute key for getting transcript identifiers.
+    pub fn transcript_id_attr<T>(&mut self, transcript_id_attr: T) -> &mut Self
+        where T: Into<String>
+    {
+        self.transcript_id_attr = transcript_id_attr.into();
+        self
+    }
+
+    /// Sets the reader to add the given prefix to all sequence names.
+    pub fn seq_name_prefix<T>(&mut self, prefix: T) -> &mut Self
+        where T: Into<String>
+    {
+        self.seq_name_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Sets the reader to trim the given string from all sequence names if present at the
+    /// beginning.
+    pub fn seq_name_lstrip<T>(&mut self, lstrip: T) -> &mut Self
+        where T: Into<String>
+    {
+        self.seq_name_lstrip = Some(lstrip.into());
+        self
+    }
+
+    /// Sets the reader to rename sequences using the given alias table.
+    ///
+    /// Aliases are resolved after any prefixing or left-stripping of sequence names.
+    pub fn seq_name_aliases(&mut self, aliases: SeqNameAliases) -> &mut Self {
+        self.seq_name_aliases = Some(aliases);
+        self
+    }
+
+    /// Sets the reader to use CDS coordinates when start and/or stop codons for transcripts
+    /// can not be found.
+    pub fn loose_codons(&mut self, loose_codons: bool) -> &mut Self {
+        self.loose_codons = loose_codons;
+        self
+    }
+
+    /// Sets the reader to check the declared frames of CDS records.
+    ///
+    /// Declared frames are always stored in the CDS features of the transcripts. When this is
+    /// set, a transcript whose declared frames differ from the frames computed from its coding
+    /// region is returned as a `ModelError::FrameMismatch` error for its first differing CDS.
+    pub fn validate_frames(&mut self, validate_frames: bool) -> &mut Self {
+        self.validate_frames = validate_frames;
+        self
+    }
+
+    /// Sets a function that rewrites every record before it is used, for example to rename
+    /// sequences, adjust coordinates, or add attributes.
+    ///
+    /// The function is applied to the records of all streams of the reader, after any sequence
+    /// name prefixing or left-stripping, so genes and transcripts are created from the
+    /// rewritten records.
+    pub fn map_record<F>(&mut self, func: F) -> &mut Self
+        where F: FnMut(&mut GtfRecord) + 'static
+    {
+        self.map_record = Some(Box::new(func));
+        self
+    }
+
+    /// Sets a token for cancelling the reading of records.
+    ///
+    /// The token is checked before each record is read. Once it is cancelled, streams of the
+    /// reader yield `Error::Cancelled` and then stop, and `load_annotation` returns that
+    /// error.
+    pub fn cancel_token(&mut self, token: CancelToken) -> &mut Self {
+        self.cancel_token = Some(token);
+        self
+    }
+
+    /// Sets the policy for handling malformed records.
+    ///
+    /// With `ErrorPolicy::Skip`, lines that can not be parsed are skipped, as are transcript
+    /// feature lines without gene or transcript identifiers and duplicate transcript lines.
+    /// When creating genes, transcripts that can not be created or added to their genes are
+    /// left out, with the line number of their first record, and genes left without any
+    /// transcripts are skipped.
+    pub fn on_error(&mut self, policy: ErrorPolicy) -> &mut Self {
+        self.on_error = policy;
+        self
+    }
+
+    /// Creates an iterator of GTF records.
+    ///
+    /// Empty lines and comment lines are skipped.
+    pub fn records_stream(&mut self) -> GtfRecordsStream<R> {
+        GtfRecordsStream {
+            inner: (&mut self.inner).lines(),
+            line_num: 0,
+            seq_name_prefix: self.seq_name_prefix.as_deref(),
+            seq_name_lstrip: self.seq_name_lstrip.as_deref(),
+            seq_name_aliases: self.seq_name_aliases.as_ref(),
+            map_record: self.map_record.as_mut().map(|func| &mut **func),
+            cancel_token: self.cancel_token.as_ref(),
+            cancelled: false,
+            on_error: &mut self.on_error,
+        }
+    }
+
+    /// Creates an iterator of transcripts.
+    ///
+    /// This iterator reads all GTF records into memory first, before grouping them into
+    /// transcripts. This is because features of a transcript may be interspersed with features
+    /// from another transcript. Transcripts are returned in the order their first feature
+    /// appears in the file.
+    pub fn transcripts_stream(&mut self) -> GtfTranscriptsStream {
+        let (loose_codons, validate_frames) = (self.loose_codons, self.validate_frames);
+        let (genes, error) = match self.gene_parts() {
+            Ok(genes) => (genes, None),
+            Err(e) => (LinkedHashMap::new(), Some(e)),
+        };
+        GtfTranscriptsStream {
+            error: error,
+            genes: genes.into_iter(),
+            transcripts: None,
+            loose_codons: loose_codons,
+            validate_frames: validate_frames,
+        }
+    }
+
+    /// Creates an iterator of genes.
+    ///
+    /// As with `transcripts_stream`, all GTF records are read into memory first. Genes are
+    /// returned in the order their first feature appears in the file.
+    pub fn genes_stream(&mut self) -> GtfGenesStream {
+        let (loose_codons, validate_frames) = (self.loose_codons, self.validate_frames);
+        let (genes, error) = match self.gene_parts() {
+            Ok(genes) => (genes, None),
+            Err(e) => (LinkedHashMap::new(), Some(e)),
+        };
+        GtfGenesStream {
+            error: error,
+            genes: genes.into_iter(),
+            loose_codons: loose_codons,
+            validate_frames: validate_frames,
+            on_error: &mut self.on_error,
+        }
+    }
+
+    /// Reads all genes into an annotation set, timing the parsing and assembly steps.
+    ///
+    /// Parsing includes reading the records and grouping them per gene and transcript, while
+    /// assembly includes creating the genes from the grouped records.
+    pub fn load_annotation(&mut self) -> ::Result<(AnnotationSet, LoadReport)> {
+        let mut timer = LoadTimer::new();
+        let (loose_codons, validate_frames) = (self.loose_codons, self.validate_frames);
+        let parts = timer.parse(|| self.gene_parts())?;
+        let on_error = &mut self.on_error;
+        let genes = timer.assemble(|| -> ::Result<Vec<Gene>> {
+            let mut genes = Vec::with_capacity(parts.len());
+            for ((_, seq_name, strand), gene) in parts {
+                let gene = gene.into_gene(seq_name, strand, loose_codons, validate_frames,
+                                          on_error)?;
+                if let Some(gene) = gene {
+                    genes.push(gene);
+                }
+            }
+            Ok(genes)
+        })?;
+        let report = timer.finish(&genes);
+        Ok((AnnotationSet::from_genes(genes), report))
+    }
+
+    /// Reads all records and groups the relevant ones per gene and transcript.
+    fn gene_parts(&mut self) -> ::Result<LinkedHashMap<GeneKey, GeneParts>> {
+        let gid_attr = self.gene_id_attr.clone();
+        let tid_attr = self.transcript_id_attr.clone();
+        let mut genes: LinkedHashMap<GeneKey, GeneParts> = LinkedHashMap::new();
+
+        let mut records = self.records_stream();
+        while let Some(result) = records.next() {
+            let mut record = result?;
+            let line_num = records.line_num;
+            match record.feature.as_str() {
+                GENE_STR | TRANSCRIPT_STR | EXON_STR | CDS_STR | START_CODON_STR
+                    | STOP_CODON_STR => {},
+                _ => continue,
+            }
+
+            let gid = match record.attributes.remove(gid_attr.as_str())
+                .and_then(|mut vs| vs.pop())
+            {
+                Some(gid) => gid,
+                None => {
+                    records.on_error.handle(line_num, GtfError::MissingGeneId(line_num).into())?;
+                    continue;
+                },
+            };
+            let tid = if record.feature == GENE_STR {
+                None
+            } else {
+                match record.attributes.remove(tid_attr.as_str()).and_then(|mut vs| vs.pop()) {
+                    Some(tid) => Some(tid),
+                    None => {
+                        let err = GtfError::MissingTranscriptId(line_num).into();
+                        records.on_error.handle(line_num, err)?;
+                        continue;
+                    },
+                }
+            };
+            let gkey = (gid.clone(), record.seq_name.clone(), strand_to_char(&record.strand));
+            let gene = genes.entry(gkey)
+                .or_insert_with(|| GeneParts::new(gid.clone(), line_num));
+
+            let tid = match tid {
+                Some(tid) => tid,
+                None => {
+                    gene.record = Some(record);
+                    continue;
+                },
+            };
+            let trx = gene.transcripts.entry(tid.clone())
+                .or_insert_with(|| TrxParts::new(tid, gid, &record, line_num));
+            if let Err(err) = trx.add_record(record) {
+                records.on_error.handle(line_num, err)?;
+            }
+        }
+
+        Ok(genes)
+    }
+}
+
+impl Reader<fs::File> {
+
+    /// Creates a GTF reader that reads from the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        fs::File::open(path).map(Reader::from_reader)
+    }
+}
+
+/// Iterator over GTF records.
+pub struct GtfRecordsStream<'a, R: 'a> where R: io::Read {
+    inner: io::Lines<&'a mut io::BufReader<R>>,
+    line_num: usize,
+    seq_name_prefix: Option<&'a str>,
+    seq_name_lstrip: Option<&'a str>,
+    seq_name_aliases: Option<&'a SeqNameAliases>,
+    map_record: Option<&'a mut (FnMut(&mut GtfRecord) + 'static)>,
+    cancel_token: Option<&'a CancelToken>,
+    cancelled: bool,
+    on_error: &'a mut ErrorPolicy,
+}
+
+impl<'a, R> Iterator for GtfRecordsStream<'a, R> where R: io::Read {
+
+    type Item = ::Result<GtfRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let lstrip = self.seq_name_lstrip.map(|v| (v, v.len()));
+        let prefix = self.seq_name_prefix;
+        loop {
+            if self.cancelled {
+                return None;
+            }
+            if self.cancel_token.map(|token| token.is_cancelled()).unwrap_or(false) {
+                self.cancelled = true;
+                return Some(Err(::Error::Cancelled));
+            }
+            let line = match self.inner.next() {
+                None => return None,
+                Some(Err(e)) => return Some(Err(::Error::from(GtfError::from(e)))),
+                Some(Ok(line)) => line,
+            };
+            self.line_num += 1;
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut record = match GtfRecord::try_from_line(line.as_str(), self.line_num) {
+                Ok(rec) => rec,
+                Err(e) => match self.on_error.handle(self.line_num, ::Error::from(e)) {
+                    Ok(()) => continue,
+                    Err(e) => return Some(Err(e)),
+                },
+            };
+            update_seq_name(&mut record.seq_name, prefix, lstrip);
+            if let Some(aliases) = self.seq_name_aliases {
+                aliases.update_seq_name(&mut record.seq_name);
+            }
+            if let Some(ref mut map_record) = self.map_record {
+                map_record(&mut record);
+            }
+            return Some(Ok(record));
+        }
+    }
+}
+
+/// The type used for grouping records into genes.
+///
+/// The tuple elements represent gene identifier, sequence name, and strand.
+type GeneKey = (String, String, char);
+
+/// Helper container of the records of a gene.
+#[derive(Debug)]
+struct GeneParts {
+    gene_id: String,
+    line_num: usize,
+    record: Option<GtfRecord>,
+    transcripts: LinkedHashMap<String, TrxParts>,
+}
+
+impl GeneParts {
+
+    fn new(gene_id: String, line_num: usize) -> Self {
+        GeneParts {
+            gene_id: gene_id,
+            line_num: line_num,
+            record: None,
+            transcripts: LinkedHashMap::new(),
+        }
+    }
+
+    /// Transforms the records into a gene.
+    ///
+    /// If no gene feature is present, the gene coordinates are inferred from its transcripts
+    /// and its attributes are those shared by all of its transcripts. Errors of the transcripts
+    /// are passed to the given policy, and `None` is returned when all of them are skipped.
+    fn into_gene(self, seq_name: String, strand: char, loose_codons: bool,
+                 validate_frames: bool, on_error: &mut ErrorPolicy) -> ::Result<Option<Gene>>
+    {
+        let has_transcripts = !self.transcripts.is_empty();
+        let mut transcripts = Vec::with_capacity(self.transcripts.len());
+        let mut shared_attribs: Option<Attributes> = None;
+        let (mut gene_start, mut gene_end) = INIT_COORD;
+        for (_, parts) in self.transcripts {
+            let line_num = parts.line_num;
+            let transcript = match parts.into_transcript(loose_codons, validate_frames) {
+                Ok(transcript) => transcript,
+                Err(err) => {
+                    on_error.handle(line_num, err)?;
+                    continue;
+                },
+            };
+            gene_start = min(gene_start, transcript.start());
+            gene_end = max(gene_end, transcript.end());
+            shared_attribs = Some(match shared_attribs {
+                None => transcript.attributes().clone(),
+                Some(attribs) => intersect_attributes(attribs, transcript.attributes()),
+            });
+            transcripts.push(transcript);
+        }
+
+        let (coord, attribs) = match self.record {
+            Some(rec) => ((rec.start, rec.end), record_attributes(rec)),
+            None => ((gene_start, gene_end), shared_attribs.unwrap_or_else(Attributes::new)),
+        };
+
+        let mut builder = GBuilder::new(seq_name, coord.0, coord.1)
+            .id(self.gene_id)
+            .strand_char(strand)
+            .add_transcripts(transcripts);
+        if let Some(biotype) = biotype_from_attributes(&attribs, &GENE_BIOTYPE_KEYS) {
+            builder = builder.biotype(biotype);
+        }
+        let (gene, errors) = builder.attributes(attribs).build_lenient()?;
+        for err in errors {
+            on_error.handle(self.line_num, err)?;
+        }
+        if has_transcripts && gene.transcripts().is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(gene))
+    }
+}
+
+/// Helper container of the records of a transcript.
+#[derive(Debug)]
+struct TrxParts {
+    transcript_id: String,
+    gene_id: String,
+    line_num: usize,
+    seq_name: String,
+    strand: Strand,
+    coords: TrxCoords,
+    attributes: Option<Attributes>,
+    exon_attributes: Option<Attributes>,
+    exon_record_attributes: Vec<(Coord<u64>, Attributes)>,
+}
+
+impl TrxParts {
+
+    fn new(transcript_id: String, gene_id: String, record: &GtfRecord, line_num: usize) -> Self {
+        TrxParts {
+            transcript_id: transcript_id,
+            gene_id: gene_id,
+            line_num: line_num,
+            seq_name: record.seq_name.clone(),
+            strand: record.strand,
+            coords: TrxCoords::default(),
+            attributes: None,
+            exon_attributes: None,
+            exon_record_attributes: Vec::new(),
+        }
+    }
+
+    /// Adds the given record to the transcript.
+    fn add_record(&mut self, record: GtfRecord) -> ::Result<()> {
+        let coord = (record.start, record.end);
+        match (record.feature.as_str(), self.strand) {
+            (TRANSCRIPT_STR, _) => {
+                self.coords.set_trx_coord(coord).map_err(::Error::from)?;
+            },
+            (EXON_STR, _) => {
+                self.coords.add_exon_coord(coord);
+            },
+            (CDS_STR, strand) => {
+                self.coords.include_cds_coord(coord);
+                if let Some(frame) = record.frame {
+                    self.coords.add_cds_frame(coord, strand, frame);
+                }
+            },
+            (START_CODON_STR, Strand::Forward) | (STOP_CODON_STR, Strand::Reverse) => {
+                self.coords.include_codon_5(coord.0);
+            },
+            (STOP_CODON_STR, Strand::Forward) | (START_CODON_STR, Strand::Reverse) => {
+                self.coords.include_codon_3(coord.1);
+            },
+            _ => {},
+        }
+        match record.feature.as_str() {
+            TRANSCRIPT_STR => {
+                self.attributes = Some(record_attributes(record));
+            },
+            EXON_STR => {
+                let attribs = record_attributes(record);
+                self.exon_record_attributes.push((coord, attribs.clone()));
+                self.exon_attributes = Some(match self.exon_attributes.take() {
+                    None => attribs,
+                    Some(acc) => intersect_attributes(acc, &attribs),
+                });
+            },
+            _ => {},
+        }
+        Ok(())
+    }
+
+    /// Transforms the records into a transcript.
+    ///
+    /// If no transcript feature is present, the transcript coordinates are inferred from its
+    /// exons and its attributes are those shared by all of its exons. Exon attributes that are
+    /// not shared with the transcript, such as exon identifiers, are stored in the exons.
+    fn into_transcript(mut self, loose_codons: bool, validate_frames: bool)
+        -> ::Result<Transcript>
+    {
+        self.coords.infer_trx_coord();
+        let frames = self.coords.take_cds_frames();
+        let coords = self.coords
+            .resolve(self.strand, loose_codons, Some(self.transcript_id.as_str()))
+            .map_err(::Error::from)?;
+        let attribs = self.attributes
+            .or(self.exon_attributes)
+            .unwrap_or_else(Attributes::new);
+
+        let mut builder = TBuilder::from_coords(self.seq_name, coords)
+            .id(self.transcript_id)
+            .gene_id(self.gene_id)
+            .strand(self.strand)
+            .coding_incl_stop(true);
+        if let Some(biotype) = biotype_from_attributes(&attribs, &TRANSCRIPT_BIOTYPE_KEYS) {
+            builder = builder.biotype(biotype);
+        }
+        let mut transcript = builder.attributes(attribs).build()?;
+        set_exon_attributes(&mut transcript, self.exon_record_attributes);
+        apply_cds_frames(transcript, frames, validate_frames)
+    }
+}
+
+/// Iterator over transcripts created from GTF records.
+pub struct GtfTranscriptsStream {
+    error: Option<::Error>,
+    genes: linked_hash_map::IntoIter<GeneKey, GeneParts>,
+    transcripts: Option<linked_hash_map::IntoIter<String, TrxParts>>,
+    loose_codons: bool,
+    validate_frames: bool,
+}
+
+impl Iterator for GtfTranscriptsStream {
+
+    type Item = ::Result<Transcript>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(err) = self.error.take() {
+            return Some(Err(err));
+        }
+        loop {
+            if let Some((_, parts)) = self.transcripts.as_mut().and_then(|trxs| trxs.next()) {
+                return Some(parts.into_transcript(self.loose_codons, self.validate_frames));
+            }
+            match self.genes.next() {
+                Some((_, gene)) => self.transcripts = Some(gene.transcripts.into_iter()),
+                None => return None,
+            }
+        }
+    }
+}
+
+/// Iterator over genes created from GTF records.
+pub struct GtfGenesStream<'a> {
+    error: Option<::Error>,
+    genes: linked_hash_map::IntoIter<GeneKey, GeneParts>,
+    loose_codons: bool,
+    validate_frames: bool,
+    on_error: &'a mut ErrorPolicy,
+}
+
+impl<'a> Iterator for GtfGenesStream<'a> {
+
+    type Item = ::Result<Gene>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(err) = self.error.take() {
+            return Some(Err(err));
+        }
+        let (loose_codons, validate_frames) = (self.loose_codons, self.validate_frames);
+        while let Some(((_, seq_name, strand), gene)) = self.genes.next() {
+            match gene.into_gene(seq_name, strand, loose_codons, validate_frames, self.on_error) {
+                Ok(Some(gene)) => return Some(Ok(gene)),
+                Ok(None) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        None
+    }
+}
+
+/// Quoting of attribute values written by the GTF writer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GtfQuoting {
+    /// All values are enclosed in double quotes. This is the default.
+    Always,
+    /// Values consisting only of digits are written without quotes, as done by GENCODE for
+    /// attributes such as `level` and `exon_number`.
+    NonNumeric,
+    /// Values are written without quotes, unless they are empty, contain whitespace,
+    /// semicolons, or double quotes, or are changed by escaping.
+    Never,
+}
+
+impl Default for GtfQuoting {
+    fn default() -> Self {
+        GtfQuoting::Always
+    }
+}
+
+/// Escaping of special characters in attribute values written by the GTF writer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GtfEscaping {
+    /// Backslashes and double quotes are preceded by a backslash. This is the default, and the
+    /// escaped values are restored by the GTF reader.
+    Backslash,
+    /// Percent signs, double quotes, semicolons, and control characters are percent-encoded,
+    /// for tools that split the attribute column on semicolons regardless of quotes.
+    Percent,
+    /// Values are written unchanged.
+    Verbatim,
+}
+
+impl Default for GtfEscaping {
+    fn default() -> Self {
+        GtfEscaping::Backslash
+    }
+}
+
+/// GTF writer.
+pub struct Writer<W: io::Write> {
+    inner: W,
+    comment_attributes: Option<Vec<String>>,
+    gene_buffer: Option<LinkedHashMap<GeneKey, BufferedGene>>,
+    quoting: GtfQuoting,
+    escaping: GtfEscaping,
+}
+
+impl<W: io::Write> Writer<W> {
+
+    /// Creates a GTF writer from another writer.
+    pub fn from_writer(in_writer: W) -> Writer<W> {
+        Writer {
+            inner: in_writer,
+            comment_attributes: None,
+            gene_buffer: None,
+            quoting: GtfQuoting::default(),
+            escaping: GtfEscaping::default(),
+        }
+    }
+
+    /// Sets how attribute values are quoted.
+    pub fn attribute_quoting(&mut self, quoting: GtfQuoting) -> &mut Self {
+        self.quoting = quoting;
+        self
+    }
+
+    /// Sets how special characters in attribute values are escaped.
+    pub fn attribute_escaping(&mut self, escaping: GtfEscaping) -> &mut Self {
+        self.escaping = escaping;
+        self
+    }
+
+    /// Sets the writer to group its output per gene.
+    ///
+    /// In this mode, written genes and transcripts are buffered instead of being written
+    /// directly. When `finish` is called, the buffered transcripts are grouped by their gene
+    /// identifiers, sequence names, and strands, and each group is written as a gene record
+    /// followed by the records of its transcripts. Genes are sorted as in `sort::by_position`,
+    /// and transcripts by their coordinates, so that all lines of a gene are adjacent even when
+    /// the transcripts were written in a different order.
+    ///
+    /// Genes created only from written transcripts have no attributes other than `gene_id`.
+    /// A transcript written more than once, on its own or as part of a gene, is written only
+    /// as the first written one.
+    pub fn group_by_gene(&mut self) -> &mut Self {
+        if self.gene_buffer.is_none() {
+            self.gene_buffer = Some(LinkedHashMap::new());
+        }
+        self
+    }
+
+    /// Writes all genes and transcripts buffered when grouping the output per gene.
+    ///
+    /// This does nothing when the writer does not group its output. It is also done when the
+    /// writer is dropped, ignoring any error.
+    pub fn finish(&mut self) -> ::Result<()> {
+        // Genes are built before the buffer is cleared, so that nothing is lost on failure.
+        let mut genes = {
+            let buffer = match self.gene_buffer.as_ref() {
+                Some(buffer) => buffer,
+                None => return Ok(()),
+            };
+            let mut genes = Vec::with_capacity(buffer.len());
+            for (&(ref gid, ref seq_name, strand), buffered) in buffer.iter() {
+                let (mut gene_start, mut gene_end) = buffered.interval.unwrap_or(INIT_COORD);
+                let mut transcripts = buffered.transcripts.clone();
+                for transcript in transcripts.iter() {
+                    gene_start = min(gene_start, transcript.start());
+                    gene_end = max(gene_end, transcript.end());
+                }
+                transcripts.sort_by(|a, b| {
+                    (a.start(), a.end(), a.id()).cmp(&(b.start(), b.end(), b.id()))
+                });
+                genes.push(GBuilder::new(seq_name.as_str(), gene_start, gene_end)
+                    .id(gid.as_str())
+                    .strand_char(strand)
+                    .attributes(buffered.attributes.clone().unwrap_or_else(Attributes::new))
+                    .add_transcripts(transcripts)
+                    .build()?);
+            }
+            genes
+        };
+        if let Some(buffer) = self.gene_buffer.as_mut() {
+            buffer.clear();
+        }
+        genes.sort_by(|a, b| {
+            cmp_positions(a, b, ChromOrder::default()).then_with(|| a.id().cmp(&b.id()))
+        });
+        for gene in genes.iter() {
+            self.write_gene_records(gene)?;
+        }
+        Ok(())
+    }
+
+    /// Sets the writer to append a comment built from the given attribute keys to each
+    /// transcript record.
+    ///
+    /// The comment contains the `key=value` pairs of the given attributes present in the
+    /// transcript, in the given order. It is ignored by the GTF reader of this crate.
+    pub fn comment_attributes<I, T>(&mut self, keys: I) -> &mut Self
+        where I: IntoIterator<Item=T>, T: Into<String>
+    {
+        self.comment_attributes = Some(keys.into_iter().map(|k| k.into()).collect());
+        self
+    }
+
+    /// Writes the given record.
+    ///
+    /// The `gene_id` and `transcript_id` attributes are written first, followed by the other
+    /// attributes in their stored order.
+    pub fn write_record(&mut self, record: &GtfRecord) -> ::Result<()> {
+        self.write_commented_record(record, None)
+    }
+
+    /// Writes the given transcript as a transcript record followed by its exon and exon feature
+    /// records.
+    ///
+    /// When grouping the output per gene, the transcript is buffered instead.
+    pub fn write_transcript(&mut self, transcript: &Transcript) -> ::Result<()> {
+        if let Some(buffer) = self.gene_buffer.as_mut() {
+            let key = buffer_key(transcript.gene_id(), transcript.seq_name(),
+                                 transcript.strand())?;
+            buffer.entry(key).or_insert_with(BufferedGene::default)
+                .add_transcript(transcript);
+            return Ok(());
+        }
+        self.write_transcript_records(transcript)
+    }
+
+    /// Writes the records of the given transcript directly.
+    fn write_transcript_records(&mut self, transcript: &Transcript) -> ::Result<()> {
+        let comment = self.comment_attributes.as_ref()
+            .map(|keys| describe_attributes(transcript.attributes(), keys))
+            .and_then(|desc| if desc.is_empty() { None } else { Some(desc) });
+        for (idx, record) in transcript_records(transcript)?.iter().enumerate() {
+            if idx == 0 {
+                self.write_commented_record(record, comment.as_deref())?;
+            } else {
+                self.write_record(record)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the given record, optionally followed by a comment.
+    fn write_commented_record(&mut self, record: &GtfRecord, comment: Option<&str>)
+        -> ::Result<()>
+    {
+        let score = record.score
+            .map(|v| format!("{}", v))
+            .unwrap_or_else(|| UNK_STR.to_owned());
+        let mut line = format!("{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                               record.seq_name, record.source, record.feature,
+                               record.start + 1, record.end, score,
+                               strand_to_char(&record.strand), frame_to_char(&record.frame),
+                               format_attributes(&record.attributes, self.quoting,
+                                                 self.escaping));
+        if let Some(text) = comment {
+            line.push_str(" # ");
+            line.push_str(text);
+        }
+        writeln!(self.inner, "{}", line).map_err(|e| ::Error::from(GtfError::from(e)))
+    }
+
+    /// Writes the given gene as a gene record followed by the records of its transcripts.
+    ///
+    /// When grouping the output per gene, the gene is buffered instead.
+    pub fn write_gene(&mut self, gene: &Gene) -> ::Result<()> {
+        if let Some(buffer) = self.gene_buffer.as_mut() {
+            let key = buffer_key(gene.id(), gene.seq_name(), gene.strand())?;
+            let buffered = buffer.entry(key).or_insert_with(BufferedGene::default);
+            buffered.attributes = Some(gene.attributes().clone());
+            buffered.interval = Some(match buffered.interval {
+                Some((start, end)) => (min(start, gene.start()), max(end, gene.end())),
+                None => (gene.start(), gene.end()),
+            });
+            for transcript in gene.transcripts().values() {
+                buffered.add_transcript(transcript);
+            }
+            return Ok(());
+        }
+        self.write_gene_records(gene)
+    }
+
+    /// Writes the records of the given gene directly.
+    fn write_gene_records(&mut self, gene: &Gene) -> ::Result<()> {
+        let gid = gene.id().ok_or(GffError::MissingGeneId)?;
+        let mut attribs = gene.attributes().clone();
+        let (source, score) = extract_source_score(&mut attribs);
+        let _ = attribs.replace_vec(GENE_ID_STR.to_owned(), vec![gid.to_owned()]);
+
+        let mut record = GtfRecord::new(gene.seq_name(), GENE_STR, gene.start(), gene.end());
+        record.source = source;
+        record.score = f64::from_str(score.as_str()).ok();
+        record.strand = *gene.strand();
+        record.attributes = attribs;
+        self.write_record(&record)?;
+
+        for transcript in gene.transcripts().values() {
+            self.write_transcript_records(transcript)?;
+        }
+        Ok(())
+    }
+}
+
+/// Helper container of the genes and transcripts buffered by a writer.
+#[derive(Debug, Default)]
+struct BufferedGene {
+    attributes: Option<Attributes>,
+    interval: Option<Coord<u64>>,
+    transcripts: Vec<Transcript>,
+}
+
+impl BufferedGene {
+
+    /// Adds the given transcript, unless a transcript with the same identifier was already
+    /// added.
+    fn add_transcript(&mut self, transcript: &Transcript) {
+        let is_dup = transcript.id().map_or(false, |tid| {
+            self.transcripts.iter().any(|trx| trx.id() == Some(tid))
+        });
+        if !is_dup {
+            self.transcripts.push(transcript.clone());
+        }
+    }
+}
+
+/// Helper function to create the key of a buffered gene.
+fn buffer_key(gene_id: Option<&str>, seq_name: &str, strand: &Strand) -> ::Result<GeneKey> {
+    let gid = gene_id.ok_or(GffError::MissingGeneId)?;
+    Ok((gid.to_owned(), seq_name.to_owned(), strand_to_char(strand)))
+}
+
+impl<W: io::Write> Drop for Writer<W> {
+    fn drop(&mut self) {
+        let _ = self.finish();
+        let _ = self.inner.flush();
+    }
+}
+
+impl Writer<fs::File> {
+
+    /// Creates a GTF writer that writes to the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let f = fs::File::create(path)?;
+        Ok(Writer::from_writer(f))
+    }
+}
+
+impl Writer<Vec<u8>> {
+
+    /// Creates a GTF writer that writes to an in-memory buffer.
+    ///
+    /// The initial capacity of the buffer is 64 KiB.
+    pub fn from_memory() -> Writer<Vec<u8>> {
+        Writer::from_writer(Vec::with_capacity(1024 * 64))
+    }
+
+    /// Returns the values of the in-memory buffer as a string.
+    pub fn as_string(&mut self) -> &str {
+        // the buffer only ever contains values written from `String`s
+        ::std::str::from_utf8(self.inner.as_slice()).expect("valid UTF-8 output")
+    }
+}
+
+/// Helper function to create the GTF records of a transcript.
+fn transcript_records(transcript: &Transcript) -> ::Result<Vec<GtfRecord>> {
+    let gid = transcript.gene_id().ok_or(GffError::MissingGeneId)?;
+    let tid = transcript.id().ok_or(GffError::MissingTranscriptId)?;
+
+    let mut attribs = transcript.attributes().clone();
+    let (source, score) = extract_source_score(&mut attribs);
+    let score = f64::from_str(score.as_str()).ok();
+    let _ = attribs.replace_vec(GENE_ID_STR.to_owned(), vec![gid.to_owned()]);
+    let _ = attribs.replace_vec(TRANSCRIPT_ID_STR.to_owned(), vec![tid.to_owned()]);
+
+    let make_record = |feature: &str, start: u64, end: u64, attribs: Attributes| {
+        let mut record = GtfRecord::new(transcript.seq_name(), feature, start, end);
+        record.source = source.clone();
+        record.score = score;
+        record.strand = *transcript.strand();
+        record.attributes = attribs;
+        record
+    };
+
+    let mut recs = vec![make_record(TRANSCRIPT_STR, transcript.start(), transcript.end(),
+                                    attribs.clone())];
+    let num_exons = transcript.exons().len();
+    for (idx, exon) in transcript.exons().iter().enumerate() {
+        let exon_num = match transcript.strand() {
+            &Strand::Reverse => num_exons - idx,
+            _ => idx + 1,
+        };
+        let exon_attribs = exon_attributes(exon, &attribs, exon_num);
+        recs.push(make_record(EXON_STR, exon.start(), exon.end(), exon_attribs.clone()));
+        for fx in exon.features() {
+            let (feature, frame) = match fx.kind() {
+                &EFK::UTR => (UTR_STR, None),
+                &EFK::UTR5 => (UTR5_STR, None),
+                &EFK::UTR3 => (UTR3_STR, None),
+                &EFK::CDS { frame: f } => (CDS_STR, f),
+                &EFK::StartCodon { frame: f } => (START_CODON_STR, f),
+                &EFK::StopCodon { frame: f } => (STOP_CODON_STR, f),
+                &EFK::Any(ref s) => (s.as_str(), None),
+            };
+            let mut record = make_record(feature, fx.start(), fx.end(), exon_attribs.clone());
+            record.frame = frame;
+            recs.push(record);
+        }
+    }
+
+    Ok(recs)
+}
+
+/// Helper function to create the attributes of an exon record from its transcript's attributes.
+///
+/// Values of the exon's own attributes replace those of the transcript with the same key. The
+/// exon number comes right after the identifiers, unless the transcript attributes already
+/// contain it.
+fn exon_attributes(
+    exon: &Exon,
+    trx_attribs: &Attributes,
+    exon_num: usize,
+) -> Attributes {
+    let exon_num = vec![format!("{}", exon_num)];
+    let mut attribs = Attributes::new();
+    if !trx_attribs.contains_key(EXON_NUMBER_STR) {
+        let _ = attribs.replace_vec(EXON_NUMBER_STR.to_owned(), exon_num.clone());
+    }
+    for (key, values) in trx_attribs.iter_all() {
+        let _ = attribs.replace_vec(key.clone(), values.clone());
+    }
+    for (key, values) in exon.attributes().iter_all() {
+        if key == "source" || key == "score" {
+            continue;
+        }
+        let _ = attribs.replace_vec(key.clone(), values.clone());
+    }
+    let _ = attribs.replace_vec(EXON_NUMBER_STR.to_owned(), exon_num);
+    attribs
+}
+
+/// Helper function to create the GTF attribute column.
+///
+/// The gene and transcript identifiers are written first, followed by the other attributes in
+/// their stored order.
+fn format_attributes(attribs: &Attributes, quoting: GtfQuoting, escaping: GtfEscaping)
+    -> String
+{
+    let mut keys = attribs.keys()
+        .map(|key| key.as_str())
+        .filter(|key| *key != GENE_ID_STR && *key != TRANSCRIPT_ID_STR)
+        .collect::<Vec<&str>>();
+    keys.insert(0, TRANSCRIPT_ID_STR);
+    keys.insert(0, GENE_ID_STR);
+
+    let mut formatted = Vec::new();
+    for key in keys {
+        if let Some(values) = attribs.get_vec(key) {
+            for value in values {
+                formatted.push(format!("{} {};", key, format_value(value, quoting, escaping)));
+            }
+        }
+    }
+    if formatted.is_empty() {
+        return UNK_STR.to_owned();
+    }
+    formatted.join(" ")
+}
+
+/// Helper function to quote and escape an attribute value.
+fn format_value(value: &str, quoting: GtfQuoting, escaping: GtfEscaping) -> String {
+    let escaped = match escaping {
+        GtfEscaping::Backslash => value.replace('\\', "\\\\").replace('"', "\\\""),
+        GtfEscaping::Percent => {
+            let mut escaped = String::with_capacity(value.len());
+            for c in value.chars() {
+                match c {
+                    '%' | '"' | ';' => escaped.push_str(&format!("%{:02X}", c as u32)),
+                    c if c.is_control() => escaped.push_str(&format!("%{:02X}", c as u32)),
+                    c => escaped.push(c),
+                }
+            }
+            escaped
+        },
+        GtfEscaping::Verbatim => value.to_owned(),
+    };
+    // escaped values are only restored by the reader when they are quoted
+    let quoted = escaped != value || match quoting {
+        GtfQuoting::Always => true,
+        GtfQuoting::NonNumeric => {
+            escaped.is_empty() || !escaped.bytes().all(|b| b.is_ascii_digit())
+        },
+        GtfQuoting::Never => {
+            escaped.is_empty()
+                || escaped.chars().any(|c| c.is_whitespace() || c == ';' || c == '"')
+        },
+    };
+    if quoted {
+        format!("\"{}\"", escaped)
+    } else {
+        escaped
+    }
+}
+
+/// Helper function to parse the GTF attribute column.
+///
+/// Values may be quoted or unquoted, and keys may appear more than once. A trailing comment
+/// started by `#` is ignored. `None` is returned when a key has no value or when a quoted value
+/// is not terminated. An undefined column value results in no attributes.
+fn parse_attributes(raw: &str) -> Option<Attributes> {
+    let mut attribs = Attributes::new();
+    if raw.trim() == UNK_STR {
+        return Some(attribs);
+    }
+    let bytes = raw.as_bytes();
+    let mut idx = 0;
+
+    loop {
+        while idx < bytes.len() && (bytes[idx] == b';' || (bytes[idx] as char).is_whitespace()) {
+            idx += 1;
+        }
+        // anything after an unquoted '#' is a comment
+        if idx >= bytes.len() || bytes[idx] == b'#' {
+            break;
+        }
+
+        let key_start = idx;
+        while idx < bytes.len() && bytes[idx] != b';' && !(bytes[idx] as char).is_whitespace() {
+            idx += 1;
+        }
+        let key = &raw[key_start..idx];
+        while idx < bytes.len() && (bytes[idx] == b' ' || bytes[idx] == b'\t') {
+            idx += 1;
+        }
+
+        let value =
+            if idx < bytes.len() && bytes[idx] == b'"' {
+                idx += 1;
+                let mut value = Vec::new();
+                let mut closed = false;
+                while idx < bytes.len() {
+                    match bytes[idx] {
+                        b'\\' if idx + 1 < bytes.len() => {
+                            value.push(bytes[idx + 1]);
+                            idx += 2;
+                        },
+                        b'"' => {
+                            closed = true;
+                            idx += 1;
+                            break;
+                        },
+                        b => {
+                            value.push(b);
+                            idx += 1;
+                        },
+                    }
+                }
+                if !closed {
+                    return None;
+                }
+                match String::from_utf8(value) {
+                    Ok(v) => v,
+                    Err(_) => return None,
+                }
+            } else {
+                let value_start = idx;
+                while idx < bytes.len() && bytes[idx] != b';' {
+                    idx += 1;
+                }
+                let value = raw[value_start..idx].trim_end();
+                if value.is_empty() {
+                    return None;
+                }
+                value.to_owned()
+            };
+
+        attribs.insert(key.to_owned(), value);
+    }
+
+    Some(attribs)
+}
+
+/// Helper function to create model attributes from a GTF record.
+///
+/// The source and score columns are stored as attributes, as is done when writing GFF records.
+fn record_attributes(record: GtfRecord) -> Attributes {
+    let mut attribs = record.attributes;
+    if record.source != UNK_STR {
+        attribs.insert("source".to_owned(), record.source);
+    }
+    if let Some(score) = record.score {
+        attribs.insert("score".to_owned(), format!("{}", score));
+    }
+    attribs
+}
+
+/// Helper function to store the exon record attributes not shared with the transcript in the
+/// exons with the same coordinates.
+fn set_exon_attributes(transcript: &mut Transcript, exon_attribs: Vec<(Coord<u64>, Attributes)>) {
+    let trx_attribs = transcript.attributes().clone();
+    for (coord, attribs) in exon_attribs {
+        let own = attribs.iter_all()
+            .filter(|&(key, values)| trx_attribs.get_vec(key) != Some(values))
+            .map(|(key, values)| (key.clone(), values.clone()))
+            .collect::<Vec<(String, Vec<String>)>>();
+        if own.is_empty() {
+            continue;
+        }
+        let exon = transcript.exons_mut().iter_mut()
+            .find(|exon| (exon.start(), exon.end()) == coord);
+        if let Some(exon) = exon {
+            for (key, values) in own {
+                let _ = exon.attributes_mut().replace_vec(key, values);
+            }
+        }
+    }
+}
+
+/// Helper function to retain only the attributes whose values are present in both inputs.
+fn intersect_attributes(
+    attribs: Attributes,
+    other: &Attributes,
+) -> Attributes {
+    let mut shared = Attributes::new();
+    for (key, values) in attribs.iter_all() {
+        if other.get_vec(key) == Some(values) {
+            for value in values {
+                shared.insert(key.clone(), value.clone());
+            }
+        }
+    }
+    shared
+}
+
@@ -0,0 +1,165 @@
+/*! Writer for newline-delimited JSON.
+
+Each gene is written as a single JSON object on its own line
+([NDJSON](http://ndjson.org/)), so that annotations can be loaded into tools such as `jq` or
+Elasticsearch one gene at a time. The objects nest the transcripts of the gene, their exons,
+and the exon features:
+
+```text
+{"seq_name":"chr1","start":100,"end":1000,"strand":"+","id":"gene01","attributes":{},
+ "transcripts":[{"id":"trx01","start":100,"end":1000,"coding":[200,800],"attributes":{},
+   "exons":[{"id":null,"start":100,"end":300,"attributes":{},
+     "features":[{"kind":"UTR5","start":100,"end":200,"frame":null}, ...]}, ...]}]}
+```
+
+Coordinates are zero-based, half-open. Attributes are written as objects mapping their keys,
+in their stored order, to lists of values. The coding region of a transcript excludes the stop
+codon and is `null` for noncoding transcripts.
+*/
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use {Attributes, Exon, ExonFeature, ExonFeatureKind as EFK, Gene, Transcript};
+use io_gff::{strand_to_char, CDS_STR, START_CODON_STR, STOP_CODON_STR, UTR_STR, UTR3_STR,
+             UTR5_STR};
+use utils::json_str;
+
+
+quick_error! {
+    /// Errors that occur when writing JSON files.
+    #[derive(Debug)]
+    pub enum JsonError {
+        /// Errors propagated from writing the underlying output.
+        Io(err: io::Error) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// Newline-delimited JSON writer.
+pub struct Writer<W: io::Write> {
+    inner: W,
+}
+
+impl<W: io::Write> Writer<W> {
+
+    /// Creates a JSON writer from another writer.
+    pub fn from_writer(in_writer: W) -> Writer<W> {
+        Writer { inner: in_writer }
+    }
+
+    /// Writes the given gene as a single line.
+    pub fn write_gene(&mut self, gene: &Gene) -> ::Result<()> {
+        let transcripts = gene.transcripts().values()
+            .map(transcript_json)
+            .collect::<Vec<String>>()
+            .join(",");
+        writeln!(self.inner,
+                 "{{\"seq_name\":{},\"start\":{},\"end\":{},\"strand\":\"{}\",\"id\":{},\
+                  \"attributes\":{},\"transcripts\":[{}]}}",
+                 json_str(gene.seq_name()), gene.start(), gene.end(),
+                 strand_to_char(gene.strand()), opt_json_str(gene.id()),
+                 attributes_json(gene.attributes()), transcripts)
+            .map_err(|e| ::Error::from(JsonError::from(e)))
+    }
+
+    /// Writes the given genes, one per line.
+    pub fn write_genes<'a, I>(&mut self, genes: I) -> ::Result<()>
+        where I: IntoIterator<Item=&'a Gene>
+    {
+        for gene in genes {
+            self.write_gene(gene)?;
+        }
+        Ok(())
+    }
+}
+
+impl Writer<fs::File> {
+
+    /// Creates a JSON writer that writes to the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let f = fs::File::create(path)?;
+        Ok(Writer::from_writer(f))
+    }
+}
+
+impl Writer<Vec<u8>> {
+
+    /// Creates a JSON writer that writes to an in-memory buffer.
+    ///
+    /// The initial capacity of the buffer is 64 KiB.
+    pub fn from_memory() -> Writer<Vec<u8>> {
+        Writer::from_writer(Vec::with_capacity(1024 * 64))
+    }
+
+    /// Returns the values of the in-memory buffer as a string.
+    pub fn as_string(&mut self) -> &str {
+        // the buffer only ever contains values written from `String`s
+        ::std::str::from_utf8(self.inner.as_slice()).expect("valid UTF-8 output")
+    }
+}
+
+/// Helper function for writing transcripts as JSON objects.
+fn transcript_json(transcript: &Transcript) -> String {
+    let coding = match transcript.coding_coord(false) {
+        Some((start, end)) => format!("[{},{}]", start, end),
+        None => "null".to_owned(),
+    };
+    let exons = transcript.exons().iter()
+        .map(exon_json)
+        .collect::<Vec<String>>()
+        .join(",");
+    format!("{{\"id\":{},\"start\":{},\"end\":{},\"coding\":{},\"attributes\":{},\
+             \"exons\":[{}]}}",
+            opt_json_str(transcript.id()), transcript.start(), transcript.end(), coding,
+            attributes_json(transcript.attributes()), exons)
+}
+
+/// Helper function for writing exons as JSON objects.
+fn exon_json(exon: &Exon) -> String {
+    let features = exon.features().iter()
+        .map(feature_json)
+        .collect::<Vec<String>>()
+        .join(",");
+    format!("{{\"id\":{},\"start\":{},\"end\":{},\"attributes\":{},\"features\":[{}]}}",
+            opt_json_str(exon.id()), exon.start(), exon.end(),
+            attributes_json(exon.attributes()), features)
+}
+
+/// Helper function for writing exon features as JSON objects.
+fn feature_json(feature: &ExonFeature) -> String {
+    let (kind, frame) = match feature.kind() {
+        &EFK::UTR => (UTR_STR, None),
+        &EFK::UTR5 => (UTR5_STR, None),
+        &EFK::UTR3 => (UTR3_STR, None),
+        &EFK::CDS { frame } => (CDS_STR, frame),
+        &EFK::StartCodon { frame } => (START_CODON_STR, frame),
+        &EFK::StopCodon { frame } => (STOP_CODON_STR, frame),
+        &EFK::Any(ref s) => (s.as_str(), None),
+    };
+    format!("{{\"kind\":{},\"start\":{},\"end\":{},\"frame\":{}}}",
+            json_str(kind), feature.start(), feature.end(),
+            frame.map(|f| f.to_string()).unwrap_or("null".to_owned()))
+}
+
+/// Helper function for writing attributes as JSON objects.
+fn attributes_json(attributes: &Attributes) -> String {
+    let items = attributes.iter_all()
+        .map(|(key, values)| {
+            format!("{}:[{}]", json_str(key),
+                    values.iter().map(|v| json_str(v)).collect::<Vec<String>>().join(","))
+        })
+        .collect::<Vec<String>>()
+        .join(",");
+    format!("{{{}}}", items)
+}
+
+/// Helper function for writing optional strings as JSON strings or nulls.
+fn opt_json_str(value: Option<&str>) -> String {
+    value.map(json_str).unwrap_or("null".to_owned())
+}
@@ -0,0 +1,277 @@
+/*! Reader for the UCSC knownGene table.
+
+The knownGene table is a [genePred](https://genome.ucsc.edu/FAQ/FAQformat.html#format9) table,
+which is similar to the refFlat format without its first column: each line denotes a transcript,
+but no gene symbol is present. The symbols are instead stored in the separate kgXref table,
+which maps the transcript identifiers in the first column to gene symbols in its fifth column.
+
+When a kgXref table is loaded, the gene symbols are used as the gene identifiers of the records.
+Otherwise, or when a transcript is not present in the kgXref table, the transcript identifier is
+used as the gene identifier. Columns after the exon coordinates (e.g. `proteinID` and `alignID`)
+are ignored.
+*/
+use std::cmp::{max, min};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+use std::str::FromStr;
+
+use linked_hash_map::{self, LinkedHashMap};
+
+use {CancelToken, Gene, GBuilder, RefFlatRecord, RefFlatRow, Transcript, INIT_COORD};
+
+
+quick_error! {
+    /// Errors that occur when reading knownGene and kgXref tables.
+    #[derive(Debug)]
+    pub enum KnownGeneError {
+        /// Occurs when a knownGene line can not be parsed.
+        InvalidRecord(line_num: usize) {
+            description("invalid knownGene record")
+            display(self_) -> ("{}, line: {}", self_.description(), line_num)
+        }
+        /// Occurs when a kgXref line does not have a gene symbol column.
+        InvalidXref(line_num: usize) {
+            description("invalid kgXref record")
+            display(self_) -> ("{}, line: {}", self_.description(), line_num)
+        }
+        /// Errors propagated from reading the underlying file.
+        Io(err: io::Error) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// Minimum number of knownGene columns, up to the exon end coordinates.
+const NUM_COLS: usize = 10;
+
+/// Position of the gene symbol column in kgXref tables.
+const XREF_SYMBOL_COL: usize = 4;
+
+/// knownGene reader.
+pub struct Reader<R: io::Read> {
+    inner: BufReader<R>,
+    line_num: usize,
+    symbols: HashMap<String, String>,
+    cancel_token: Option<CancelToken>,
+}
+
+impl<R: io::Read> Reader<R> {
+
+    /// Creates a knownGene reader from another reader.
+    pub fn from_reader(in_reader: R) -> Reader<R> {
+        Reader {
+            inner: BufReader::new(in_reader),
+            line_num: 0,
+            symbols: HashMap::new(),
+            cancel_token: None,
+        }
+    }
+
+    /// Sets the gene symbols used for the transcripts, keyed by transcript identifier.
+    pub fn symbols(&mut self, symbols: HashMap<String, String>) -> &mut Self {
+        self.symbols = symbols;
+        self
+    }
+
+    /// Loads the gene symbols used for the transcripts from the given kgXref table reader.
+    ///
+    /// Lines with empty gene symbols are ignored.
+    pub fn xref_reader<X: io::Read>(&mut self, xref_reader: X) -> ::Result<&mut Self> {
+        let mut symbols = HashMap::new();
+        for (idx, line) in BufReader::new(xref_reader).lines().enumerate() {
+            let line = line.map_err(|e| ::Error::from(KnownGeneError::from(e)))?;
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields = line.split('\t').collect::<Vec<&str>>();
+            let symbol = fields.get(XREF_SYMBOL_COL)
+                .ok_or(::Error::from(KnownGeneError::InvalidXref(idx + 1)))?;
+            if !symbol.is_empty() {
+                let _ = symbols.insert(fields[0].to_owned(), (*symbol).to_owned());
+            }
+        }
+        Ok(self.symbols(symbols))
+    }
+
+    /// Loads the gene symbols used for the transcripts from the kgXref table in the given path.
+    pub fn xref_file<P: AsRef<Path>>(&mut self, path: P) -> ::Result<&mut Self> {
+        let file = fs::File::open(path)
+            .map_err(|e| ::Error::from(KnownGeneError::from(e)))?;
+        self.xref_reader(file)
+    }
+
+    /// Sets a token for cancelling the reading of records.
+    ///
+    /// The token is checked before each record is read. Once it is cancelled, streams of the
+    /// reader yield `Error::Cancelled` and then stop.
+    pub fn cancel_token(&mut self, token: CancelToken) -> &mut Self {
+        self.cancel_token = Some(token);
+        self
+    }
+
+    /// Creates an iterator of records.
+    ///
+    /// The records are refFlat records whose gene identifiers are the gene symbols of the
+    /// transcripts.
+    pub fn records_stream(&mut self) -> KnownGeneRecordsStream<R> {
+        KnownGeneRecordsStream { reader: self, cancelled: false }
+    }
+
+    /// Creates an iterator of transcripts.
+    pub fn transcripts_stream(&mut self) -> KnownGeneTranscriptsStream<R> {
+        KnownGeneTranscriptsStream { inner: self.records_stream() }
+    }
+
+    /// Creates an iterator of genes.
+    ///
+    /// Since knownGene tables are sorted by coordinates, transcripts of the same gene are not
+    /// always adjacent. All records are thus read first and grouped by their gene symbols,
+    /// sequence names, and strands. Genes are yielded in the order of their first transcripts.
+    pub fn genes_stream(&mut self) -> KnownGeneGenesStream {
+        let (groups, error) = match self.gene_groups() {
+            Ok(groups) => (groups, None),
+            Err(e) => (LinkedHashMap::new(), Some(e)),
+        };
+        KnownGeneGenesStream {
+            error: error,
+            groups: groups.into_iter(),
+        }
+    }
+
+    /// Reads all transcripts and groups them per gene.
+    fn gene_groups(&mut self) -> ::Result<LinkedHashMap<GroupKey, Vec<Transcript>>> {
+        let mut groups: LinkedHashMap<GroupKey, Vec<Transcript>> = LinkedHashMap::new();
+        for result in self.records_stream() {
+            let record = result?;
+            let key = (record.gene_id_or_default().to_owned(), record.seq_name().to_owned(),
+                       record.strand());
+            groups.entry(key).or_insert_with(Vec::new).push(record.into_transcript()?);
+        }
+        Ok(groups)
+    }
+
+    /// Parses a refFlat record from the given knownGene line.
+    fn parse_line(&self, line: &str, line_num: usize) -> ::Result<RefFlatRecord> {
+        let invalid = || ::Error::from(KnownGeneError::InvalidRecord(line_num));
+        let fields = line.split('\t').collect::<Vec<&str>>();
+        if fields.len() < NUM_COLS {
+            return Err(invalid());
+        }
+        let parse_coord = |raw: &str| u64::from_str(raw).map_err(|_| invalid());
+        let mut strand_chars = fields[2].chars();
+        let strand = match (strand_chars.next(), strand_chars.next()) {
+            (Some(c), None) => c,
+            _ => return Err(invalid()),
+        };
+        let tid = fields[0];
+        let gid = self.symbols.get(tid).map(|s| s.as_str()).unwrap_or(tid);
+
+        let row: RefFlatRow = (
+            gid.to_owned(), tid.to_owned(), fields[1].to_owned(), strand,
+            parse_coord(fields[3])?, parse_coord(fields[4])?,
+            parse_coord(fields[5])?, parse_coord(fields[6])?,
+            usize::from_str(fields[7]).map_err(|_| invalid())?,
+            fields[8].to_owned(), fields[9].to_owned());
+        RefFlatRecord::try_from_row(row)
+    }
+}
+
+impl Reader<fs::File> {
+
+    /// Creates a knownGene reader that reads from the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        fs::File::open(path).map(Reader::from_reader)
+    }
+}
+
+/// Iterator over records created from knownGene lines.
+pub struct KnownGeneRecordsStream<'a, R: 'a> where R: io::Read {
+    reader: &'a mut Reader<R>,
+    cancelled: bool,
+}
+
+impl<'a, R> Iterator for KnownGeneRecordsStream<'a, R> where R: io::Read {
+
+    type Item = ::Result<RefFlatRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut line = String::new();
+        loop {
+            if self.cancelled {
+                return None;
+            }
+            if self.reader.cancel_token.as_ref().map(|t| t.is_cancelled()).unwrap_or(false) {
+                self.cancelled = true;
+                return Some(Err(::Error::Cancelled));
+            }
+            line.clear();
+            match self.reader.inner.read_line(&mut line) {
+                Ok(0) => return None,
+                Ok(_) => {},
+                Err(e) => return Some(Err(::Error::from(KnownGeneError::from(e)))),
+            }
+            self.reader.line_num += 1;
+            let trimmed = line.trim_end_matches(&['\n', '\r'][..]);
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            return Some(self.reader.parse_line(trimmed, self.reader.line_num));
+        }
+    }
+}
+
+/// Iterator over transcripts created from knownGene lines.
+pub struct KnownGeneTranscriptsStream<'a, R: 'a> where R: io::Read {
+    inner: KnownGeneRecordsStream<'a, R>,
+}
+
+impl<'a, R> Iterator for KnownGeneTranscriptsStream<'a, R> where R: io::Read {
+
+    type Item = ::Result<Transcript>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+            .map(|record| record.and_then(|rec| rec.into_transcript()))
+    }
+}
+
+/// The type used for grouping transcripts into genes.
+///
+/// The tuple elements represent gene identifier, sequence name, and strand.
+type GroupKey = (String, String, char);
+
+/// Iterator over genes created from knownGene lines.
+pub struct KnownGeneGenesStream {
+    error: Option<::Error>,
+    groups: linked_hash_map::IntoIter<GroupKey, Vec<Transcript>>,
+}
+
+impl Iterator for KnownGeneGenesStream {
+
+    type Item = ::Result<Gene>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(err) = self.error.take() {
+            return Some(Err(err));
+        }
+        self.groups.next()
+            .map(|((gid, seq_name, strand), transcripts)| {
+                let (mut gene_start, mut gene_end) = INIT_COORD;
+                for transcript in transcripts.iter() {
+                    gene_start = min(gene_start, transcript.start());
+                    gene_end = max(gene_end, transcript.end());
+                }
+                GBuilder::new(seq_name, gene_start, gene_end)
+                    .id(gid)
+                    .strand_char(strand)
+                    .add_transcripts(transcripts)
+                    .build()
+            })
+    }
+}
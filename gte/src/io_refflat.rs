@@ -6,21 +6,33 @@ line. It is used most prominently by the
 
 A minimum specification of the columns can be found on
 [this page](https://genome.ucsc.edu/goldenPath/gbdDescriptionsOld.html#RefFlat).
+
+Coordinate-sorted refFlat files compressed with `bgzip` and indexed with
+`tabix -0 -s 3 -b 5 -e 6` may be queried by region using an indexed reader.
+
+Records and transcripts may also be converted into each other with `TryFrom`, without going
+through a reader or a writer.
 */
 use std::cmp::{max, min};
-use std::convert::AsRef;
+use std::collections::HashSet;
+use std::convert::{AsRef, TryFrom};
 use std::error::Error;
-use std::io;
+use std::io::{self, BufRead};
+use std::mem;
 use std::num::ParseIntError;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 use csv;
-use itertools::{GroupBy, Group, Itertools};
-use linked_hash_map::LinkedHashMap;
-
-use {Coord, Gene, GBuilder, Strand, Transcript, TBuilder, DEF_ID, INIT_COORD};
+use itertools::Itertools;
+use linked_hash_map::{self, LinkedHashMap};
+
+use {AnnotationSet, CancelToken, Coord, ErrorPolicy, Gene, GBuilder, LoadReport, SeqNameAliases,
+     Strand, Transcript, TBuilder, DEF_ID, INIT_COORD};
+use instrument::LoadTimer;
+use sort::{cmp_positions, ChromOrder, Positioned};
+use tabix::{self, TabixError, TabixIndex};
 use utils::{OptionDeref, update_seq_name};
 
 
@@ -35,6 +47,13 @@ quick_error! {
             display(self_) -> ("{}, transcript ID: {}",
                                self_.description(), tid.as_deref().unwrap_or(DEF_ID))
         }
+        /// Occurs when an exon start coordinate is not less than its paired exon end
+        /// coordinate.
+        InvalidExonInterval(start: u64, end: u64, tid: Option<String>) {
+            description("exon start coordinate is not less than its end coordinate")
+            display(self_) -> ("{}, exon: {}-{}, transcript ID: {}", self_.description(),
+                               start, end, tid.as_deref().unwrap_or(DEF_ID))
+        }
         /// Indicates a duplicate transcript identifier with the same gene identifier.
         DuplicateTranscriptId(gid: Option<String>) {
             description("gene has multiple transcripts with the same identifier")
@@ -56,15 +75,57 @@ quick_error! {
                                self_.description(), tid.as_deref().unwrap_or(DEF_ID))
             cause(err)
         }
-        /// Errors propagated from the underlying `csv` crate.
-        Csv(err: csv::Error) {
+        /// Occurs when genes expected to be sorted by position are not, or when the records of
+        /// a gene are not consecutive.
+        UnsortedGene(gid: Option<String>) {
+            description("gene records are not sorted")
+            display(self_) -> ("{}, gene ID: {}",
+                               self_.description(), gid.as_deref().unwrap_or(DEF_ID))
+        }
+        /// Occurs when a refFlat line does not have the expected columns, with the one-based
+        /// line number, the name of the offending column if any, and the text of the line if
+        /// available.
+        InvalidRecord(line_num: usize, field: Option<&'static str>, line: Option<String>) {
+            description("invalid refFlat record")
+            display(self_) -> ("{}, {}", self_.description(),
+                               describe_location(*line_num, *field, line.as_deref()))
+        }
+        /// Occurs when a writer sorting its output buffers more rows than its limit.
+        BufferLimitExceeded(limit: usize) {
+            description("number of buffered rows exceeds the limit")
+            display(self_) -> ("{}, limit: {}", self_.description(), limit)
+        }
+        /// Errors propagated from reading the underlying file.
+        Io(err: io::Error) {
             description(err.description())
+            display("{}", err)
             from()
             cause(err)
         }
+        /// Errors propagated from the underlying `csv` crate, with the one-based line number
+        /// when reading.
+        Csv(err: csv::Error, line_num: Option<usize>) {
+            description(err.description())
+            display(self_) -> ("{}{}", err,
+                               line_num.map(|n| format!(", line: {}", n)).unwrap_or_default())
+            from(err: csv::Error) -> (err, None)
+            cause(err)
+        }
     }
 }
 
+/// Helper function for describing the location of an invalid record in error messages.
+fn describe_location(line_num: usize, field: Option<&str>, line: Option<&str>) -> String {
+    let mut location = format!("line: {}", line_num);
+    if let Some(field) = field {
+        location.push_str(&format!(", field: {}", field));
+    }
+    if let Some(line) = line {
+        location.push_str(&format!(", text: {:?}", line));
+    }
+    location
+}
+
 /// Raw refFlat row type.
 ///
 /// This type represents the simplest value types that compose a refFlat row. The provided reader
@@ -113,8 +174,15 @@ pub struct RefFlatRecord {
 impl RefFlatRecord {
 
     /// Returns the gene identifier.
-    pub fn gene_id(&self) -> &str {
-        self.gene_id.as_str()
+    ///
+    /// This is `None` when the gene identifier column is empty.
+    pub fn gene_id(&self) -> Option<&str> {
+        non_empty(&self.gene_id)
+    }
+
+    /// Returns the gene identifier, or `"<unknown>"` if the gene identifier column is empty.
+    pub fn gene_id_or_default(&self) -> &str {
+        self.gene_id().unwrap_or(DEF_ID)
     }
 
     /// Sets the gene identifier.
@@ -125,8 +193,16 @@ impl RefFlatRecord {
     }
 
     /// Returns the transcript identifier.
-    pub fn transcript_id(&self) -> &str {
-        self.transcript_id.as_str()
+    ///
+    /// This is `None` when the transcript identifier column is empty.
+    pub fn transcript_id(&self) -> Option<&str> {
+        non_empty(&self.transcript_id)
+    }
+
+    /// Returns the transcript identifier, or `"<unknown>"` if the transcript identifier column
+    /// is empty.
+    pub fn transcript_id_or_default(&self) -> &str {
+        self.transcript_id().unwrap_or(DEF_ID)
     }
 
     /// Sets the transcript identifier.
@@ -223,13 +299,11 @@ impl RefFlatRecord {
 
     /// Sets the exon coordinates of the record.
     ///
-    /// An error type will be returned if the number of coordinates differ.
+    /// An error type will be returned if the number of coordinates differ, or if any start
+    /// coordinate is not less than its paired end coordinate.
     pub fn set_exon_coords(&mut self, coord_starts: Vec<u64>, coord_ends: Vec<u64>) -> ::Result<()> {
-        if coord_starts.len() != coord_ends.len() {
-            let tid = self.transcript_id.clone();
-            let err = ::Error::from(RefFlatError::ExonCountMismatch(Some(tid)));
-            return Err(err);
-        }
+        check_exon_coords(&coord_starts, &coord_ends, &self.transcript_id)
+            .map_err(::Error::from)?;
         self.exon_starts = coord_starts;
         self.exon_ends = coord_ends;
         Ok(())
@@ -238,8 +312,9 @@ impl RefFlatRecord {
     /// Creates a record from a row.
     ///
     /// This method will return an error if:
-    /// * any of the exon coordinates are not valid u64 values, or
-    /// * the number of exon coordinates and the number of exons column value are not equal
+    /// * any of the exon coordinates are not valid u64 values,
+    /// * the number of exon coordinates and the number of exons column value are not equal, or
+    /// * any exon start coordinate is not less than its paired exon end coordinate
     pub fn try_from_row(row: RefFlatRow) -> ::Result<Self> {
 
         let exon_starts = Self::parse_coords(row.9.as_str(), row.1.as_str())
@@ -250,10 +325,8 @@ impl RefFlatRecord {
             let err = RefFlatError::ExonCountMismatch(Some(row.1.clone()));
             return Err(::Error::RefFlat(err));
         }
-        if exon_starts.len() != exon_ends.len() {
-            let err = RefFlatError::ExonCountMismatch(Some(row.1.clone()));
-            return Err(::Error::RefFlat(err));
-        }
+        check_exon_coords(&exon_starts, &exon_ends, &row.1)
+            .map_err(::Error::from)?;
 
         Ok(RefFlatRecord {
             gene_id: row.0,
@@ -269,13 +342,33 @@ impl RefFlatRecord {
         })
     }
 
+    /// Parses a record from a single refFlat line.
+    ///
+    /// A trailing line terminator is ignored. This method returns the same errors as the reader,
+    /// with the line reported as line 1. The same parsing is available through `str::parse`.
+    pub fn parse_line(line: &str) -> ::Result<Self> {
+        let line = line.trim_end_matches(&['\n', '\r'][..]);
+        let cols = line.split('\t').map(|col| col.to_owned()).collect::<Vec<String>>();
+        columns_to_row(cols, 1, false)
+            .map_err(::Error::from)
+            .and_then(RefFlatRecord::try_from_row)
+    }
+
     /// Transforms the record into a transcript.
+    ///
+    /// This is the same as `Transcript::try_from`.
     pub fn into_transcript(self) -> ::Result<Transcript> {
+        self.build_transcript(true)
+    }
+
+    /// Transforms the record into a transcript, optionally allowing an empty gene identifier
+    /// column.
+    fn build_transcript(self, require_gene_id: bool) -> ::Result<Transcript> {
 
         if self.transcript_id.is_empty() {
             return Err(::Error::from(::RefFlatError::MissingTranscriptId));
         }
-        if self.gene_id.is_empty() {
+        if require_gene_id && self.gene_id.is_empty() {
             return Err(::Error::from(::RefFlatError::MissingGeneId));
         }
         let coding_interval =
@@ -288,14 +381,27 @@ impl RefFlatRecord {
         let exon_coords = self.exon_starts.into_iter().zip(self.exon_ends.into_iter())
             .collect::<Vec<Coord<u64>>>();
 
-        TBuilder::new(self.seq_name, self.transcript_start, self.transcript_end)
+        let builder = TBuilder::new(self.seq_name, self.transcript_start, self.transcript_end)
             .id(self.transcript_id)
-            .gene_id(self.gene_id)
             .strand_char(self.strand)
             .coords(exon_coords, coding_interval)
-            .coding_incl_stop(true)
-            .build()
-            .map_err(::Error::from)
+            .coding_incl_stop(true);
+        if self.gene_id.is_empty() {
+            builder.build()
+        } else {
+            builder.gene_id(self.gene_id).build()
+        }
+    }
+
+    /// Returns the identifier used for grouping the record into genes.
+    ///
+    /// This is the transcript identifier when reading genePred lines.
+    fn gene_key(&self, gene_pred: bool) -> &String {
+        if gene_pred {
+            &self.transcript_id
+        } else {
+            &self.gene_id
+        }
     }
 
     /// Parses the given raw coordinate string into a vector of u64s.
@@ -317,11 +423,302 @@ impl RefFlatRecord {
     }
 }
 
+impl FromStr for RefFlatRecord {
+    type Err = ::Error;
+
+    fn from_str(line: &str) -> ::Result<RefFlatRecord> {
+        RefFlatRecord::parse_line(line)
+    }
+}
+
+impl<'a> TryFrom<&'a Transcript> for RefFlatRecord {
+    type Error = ::Error;
+
+    /// Creates a record from a transcript.
+    ///
+    /// An error is returned if the transcript has no identifier. Transcripts without a gene
+    /// identifier give records with an empty gene identifier column, and noncoding transcripts
+    /// give records whose coding start and end are both the transcript end.
+    fn try_from(transcript: &'a Transcript) -> ::Result<RefFlatRecord> {
+        let transcript_id = transcript.id()
+            .ok_or(::Error::RefFlat(RefFlatError::MissingTranscriptId))?;
+        let strand_char = match transcript.strand() {
+            &Strand::Forward => '+',
+            &Strand::Reverse => '-',
+            &Strand::Unknown => '.',
+        };
+        let (coding_start, coding_end) = transcript.coding_coord(true)
+            .unwrap_or((transcript.end(), transcript.end()));
+
+        Ok(RefFlatRecord {
+            gene_id: transcript.gene_id().unwrap_or("").to_owned(),
+            transcript_id: transcript_id.to_owned(),
+            seq_name: transcript.seq_name().to_owned(),
+            strand: strand_char,
+            transcript_start: transcript.start(),
+            transcript_end: transcript.end(),
+            coding_start: coding_start,
+            coding_end: coding_end,
+            exon_starts: transcript.exons().iter().map(|e| e.start()).collect(),
+            exon_ends: transcript.exons().iter().map(|e| e.end()).collect(),
+        })
+    }
+}
+
+impl TryFrom<RefFlatRecord> for Transcript {
+    type Error = ::Error;
+
+    /// Creates a transcript from a record.
+    ///
+    /// An error is returned if the record has an empty gene or transcript identifier column, or
+    /// if its coordinates do not form a valid transcript.
+    fn try_from(record: RefFlatRecord) -> ::Result<Transcript> {
+        record.build_transcript(true)
+    }
+}
+
+/// Helper function for checking that exon start and end coordinates pair up into exons.
+///
+/// The transcript identifier is only used for creating error values.
+fn check_exon_coords(starts: &[u64], ends: &[u64], tid: &str) -> Result<(), RefFlatError> {
+    if starts.len() != ends.len() {
+        return Err(RefFlatError::ExonCountMismatch(Some(tid.to_owned())));
+    }
+    match starts.iter().zip(ends).find(|&(start, end)| start >= end) {
+        Some((&start, &end)) => {
+            Err(RefFlatError::InvalidExonInterval(start, end, Some(tid.to_owned())))
+        },
+        None => Ok(()),
+    }
+}
+
+/// Number of columns in refFlat lines.
+const NUM_COLS: usize = 11;
+
+/// Names of the refFlat columns, as used in error values.
+const COL_NAMES: [&'static str; NUM_COLS] = ["geneName", "name", "chrom", "strand", "txStart",
+                                             "txEnd", "cdsStart", "cdsEnd", "exonCount",
+                                             "exonStarts", "exonEnds"];
+
+/// Helper function for creating a row from the columns of a refFlat line.
+///
+/// When reading genePred lines, an empty gene identifier column is added before the other
+/// columns. The line number is only used for creating error values.
+fn columns_to_row(mut cols: Vec<String>, line_num: usize, gene_pred: bool)
+    -> Result<RefFlatRow, RefFlatError>
+{
+    let skip = if gene_pred { 1 } else { 0 };
+    if cols.len() + skip != NUM_COLS {
+        return Err(RefFlatError::InvalidRecord(line_num, None, Some(cols.join("\t"))));
+    }
+    if gene_pred {
+        cols.insert(0, String::new());
+    }
+    let (strand, coords, num_exons) = {
+        let invalid = |idx: usize| {
+            let line = cols[skip..].join("\t");
+            RefFlatError::InvalidRecord(line_num, Some(COL_NAMES[idx]), Some(line))
+        };
+        let parse_coord = |idx: usize| u64::from_str(cols[idx].as_str()).map_err(|_| invalid(idx));
+        let mut strand_chars = cols[3].chars();
+        let strand = match (strand_chars.next(), strand_chars.next()) {
+            (Some(c), None) => c,
+            _ => return Err(invalid(3)),
+        };
+        let coords = (parse_coord(4)?, parse_coord(5)?, parse_coord(6)?, parse_coord(7)?);
+        let num_exons = usize::from_str(cols[8].as_str()).map_err(|_| invalid(8))?;
+        (strand, coords, num_exons)
+    };
+    let mut take = |idx: usize| mem::replace(&mut cols[idx], String::new());
+    Ok((take(0), take(1), take(2), strand, coords.0, coords.1, coords.2, coords.3, num_exons,
+        take(9), take(10)))
+}
+
+/// RefFlat record type that borrows its values from a line.
+///
+/// Identifiers, sequence names, and exon coordinates are not copied, which avoids allocations
+/// when most records are only inspected and then discarded. Exon coordinates are validated when
+/// the record is created, but only parsed when they are requested. Use `to_owned` to create a
+/// `RefFlatRecord` from records that need to be kept.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RefFlatRecordRef<'a> {
+    gene_id: &'a str,
+    transcript_id: &'a str,
+    seq_name: &'a str,
+    strand: char,
+    transcript_start: u64,
+    transcript_end: u64,
+    coding_start: u64,
+    coding_end: u64,
+    num_exons: usize,
+    exon_starts: &'a str,
+    exon_ends: &'a str,
+}
+
+impl<'a> RefFlatRecordRef<'a> {
+
+    /// Parses a record from the given refFlat line, without its line terminator.
+    ///
+    /// The line number is only used for creating error values.
+    pub fn try_from_line(line: &'a str, line_num: usize) -> ::Result<Self> {
+        let invalid = |field: Option<&'static str>| {
+            ::Error::from(RefFlatError::InvalidRecord(line_num, field, Some(line.to_owned())))
+        };
+        let cols = line.split('\t').collect::<Vec<&str>>();
+        if cols.len() != NUM_COLS {
+            return Err(invalid(None));
+        }
+        let parse_coord = |idx: usize| {
+            u64::from_str(cols[idx]).map_err(|_| invalid(Some(COL_NAMES[idx])))
+        };
+        let mut strand_chars = cols[3].chars();
+        let strand = match (strand_chars.next(), strand_chars.next()) {
+            (Some(c), None) => c,
+            _ => return Err(invalid(Some(COL_NAMES[3]))),
+        };
+
+        let record = RefFlatRecordRef {
+            gene_id: cols[0],
+            transcript_id: cols[1],
+            seq_name: cols[2],
+            strand: strand,
+            transcript_start: parse_coord(4)?,
+            transcript_end: parse_coord(5)?,
+            coding_start: parse_coord(6)?,
+            coding_end: parse_coord(7)?,
+            num_exons: usize::from_str(cols[8]).map_err(|_| invalid(Some(COL_NAMES[8])))?,
+            exon_starts: cols[9],
+            exon_ends: cols[10],
+        };
+        for coord in record.exon_starts().chain(record.exon_ends()) {
+            let _ = coord.map_err(|e| RefFlatError::InvalidExonCoord(
+                e, Some(record.transcript_id.to_owned())))?;
+        }
+        if record.exon_starts().count() != record.num_exons
+            || record.exon_ends().count() != record.num_exons
+        {
+            let tid = Some(record.transcript_id.to_owned());
+            return Err(::Error::from(RefFlatError::ExonCountMismatch(tid)));
+        }
+        if let Some((start, end)) = record.exon_coords().into_iter().find(|&(s, e)| s >= e) {
+            let tid = Some(record.transcript_id.to_owned());
+            return Err(::Error::from(RefFlatError::InvalidExonInterval(start, end, tid)));
+        }
+        Ok(record)
+    }
+
+    /// Returns the gene identifier, or `None` when the gene identifier column is empty.
+    pub fn gene_id(&self) -> Option<&'a str> {
+        non_empty(self.gene_id)
+    }
+
+    /// Returns the transcript identifier, or `None` when the transcript identifier column is
+    /// empty.
+    pub fn transcript_id(&self) -> Option<&'a str> {
+        non_empty(self.transcript_id)
+    }
+
+    /// Returns the sequence name.
+    pub fn seq_name(&self) -> &'a str {
+        self.seq_name
+    }
+
+    /// Returns the strand.
+    pub fn strand(&self) -> char {
+        self.strand
+    }
+
+    /// Returns the genome-wise 5'-most transcript coordinate of the record.
+    pub fn transcript_start(&self) -> u64 {
+        self.transcript_start
+    }
+
+    /// Returns the genome-wise 3'-most transcript coordinate of the record.
+    pub fn transcript_end(&self) -> u64 {
+        self.transcript_end
+    }
+
+    /// Returns the genome-wise 5'-most coding region coordinate of the record.
+    pub fn coding_start(&self) -> u64 {
+        self.coding_start
+    }
+
+    /// Returns the genome-wise 3'-most coding region coordinate of the record.
+    pub fn coding_end(&self) -> u64 {
+        self.coding_end
+    }
+
+    /// Returns the number of exons contained within the record.
+    pub fn num_exons(&self) -> usize {
+        self.num_exons
+    }
+
+    /// Returns the exon coordinates of the record.
+    pub fn exon_coords(&self) -> Vec<Coord<u64>> {
+        self.exon_starts().zip(self.exon_ends())
+            .map(|(start, end)| (start.unwrap(), end.unwrap()))
+            .collect()
+    }
+
+    /// Creates an owned record with the same values.
+    pub fn to_owned(&self) -> RefFlatRecord {
+        RefFlatRecord {
+            gene_id: self.gene_id.to_owned(),
+            transcript_id: self.transcript_id.to_owned(),
+            seq_name: self.seq_name.to_owned(),
+            strand: self.strand,
+            transcript_start: self.transcript_start,
+            transcript_end: self.transcript_end,
+            coding_start: self.coding_start,
+            coding_end: self.coding_end,
+            exon_starts: self.exon_starts().map(|coord| coord.unwrap()).collect(),
+            exon_ends: self.exon_ends().map(|coord| coord.unwrap()).collect(),
+        }
+    }
+
+    /// Parses the exon start coordinates lazily.
+    fn exon_starts(&self) -> CoordsIter<'a> {
+        split_coords(self.exon_starts)
+    }
+
+    /// Parses the exon end coordinates lazily.
+    fn exon_ends(&self) -> CoordsIter<'a> {
+        split_coords(self.exon_ends)
+    }
+}
+
+/// The type of lazily-parsed exon coordinates.
+type CoordsIter<'a> =
+    ::std::iter::Map<::std::str::Split<'a, char>, fn(&str) -> Result<u64, ParseIntError>>;
+
+/// Helper function for lazily parsing comma-separated coordinates.
+fn split_coords(raw_coords: &str) -> CoordsIter {
+    let parse: fn(&str) -> Result<u64, ParseIntError> = u64::from_str;
+    raw_coords.trim_matches(',').split(',').map(parse)
+}
+
+/// Helper function for reading empty column values as `None`.
+#[inline]
+fn non_empty(value: &str) -> Option<&str> {
+    if value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
 /// RefFlat reader.
 pub struct Reader<R: io::Read> {
     inner: csv::Reader<R>,
     seq_name_prefix: Option<String>,
     seq_name_lstrip: Option<String>,
+    region: Option<(String, u64, u64)>,
+    seq_name_aliases: Option<SeqNameAliases>,
+    map_record: Option<Box<FnMut(&mut RefFlatRecord)>>,
+    cancel_token: Option<CancelToken>,
+    on_error: ErrorPolicy,
+    gene_pred: bool,
+    merge_loci: bool,
 }
 
 impl<R: io::Read> Reader<R> {
@@ -334,6 +731,13 @@ impl<R: io::Read> Reader<R> {
                 .has_headers(false),
             seq_name_prefix: None,
             seq_name_lstrip: None,
+            region: None,
+            seq_name_aliases: None,
+            map_record: None,
+            cancel_token: None,
+            on_error: ErrorPolicy::Abort,
+            gene_pred: false,
+            merge_loci: false,
         }
     }
 
@@ -354,12 +758,94 @@ impl<R: io::Read> Reader<R> {
         self
     }
 
+    /// Sets the reader to rename sequences using the given alias table.
+    ///
+    /// Aliases are resolved after any prefixing or left-stripping of sequence names.
+    pub fn seq_name_aliases(&mut self, aliases: SeqNameAliases) -> &mut Self {
+        self.seq_name_aliases = Some(aliases);
+        self
+    }
+
+    /// Sets the reader to only yield records whose transcripts overlap the given zero-based,
+    /// half-open region.
+    ///
+    /// The sequence name is compared after any prefixing or left-stripping. Since genes are
+    /// created from the yielded records, genes only contain their overlapping transcripts.
+    pub fn region<T>(&mut self, seq_name: T, start: u64, end: u64) -> &mut Self
+        where T: Into<String>
+    {
+        self.region = Some((seq_name.into(), start, end));
+        self
+    }
+
+    /// Sets a function that rewrites every record before it is used, for example to rename
+    /// sequences, adjust coordinates, or rename genes.
+    ///
+    /// The function is applied to the records of all streams of the reader, after any sequence
+    /// name prefixing or left-stripping and before region filtering.
+    pub fn map_record<F>(&mut self, func: F) -> &mut Self
+        where F: FnMut(&mut RefFlatRecord) + 'static
+    {
+        self.map_record = Some(Box::new(func));
+        self
+    }
+
+    /// Sets a token for cancelling the reading of records.
+    ///
+    /// The token is checked before each record is read. Once it is cancelled, streams of the
+    /// reader yield `Error::Cancelled` and then stop, and `load_annotation` returns that
+    /// error.
+    pub fn cancel_token(&mut self, token: CancelToken) -> &mut Self {
+        self.cancel_token = Some(token);
+        self
+    }
+
+    /// Sets the policy for handling malformed records.
+    ///
+    /// With `ErrorPolicy::Skip`, records that can not be decoded and records that can not be
+    /// turned into transcripts are skipped. Genes are then created from their remaining
+    /// transcripts, and genes left without any transcripts are skipped. Line numbers count
+    /// every record of the input as a single line.
+    pub fn on_error(&mut self, policy: ErrorPolicy) -> &mut Self {
+        self.on_error = policy;
+        self
+    }
+
+    /// Sets whether the reader expects genePred lines, which have no gene identifier column.
+    ///
+    /// The records of such lines have no gene identifier, and neither do their transcripts.
+    /// Since transcripts can not be grouped by gene, each gene created from them contains a
+    /// single transcript and uses its identifier.
+    pub fn gene_pred(&mut self, gene_pred: bool) -> &mut Self {
+        self.gene_pred = gene_pred;
+        self
+    }
+
+    /// Sets whether records sharing a gene identifier are grouped into the same gene
+    /// regardless of their sequence names and strands in `unsorted_genes_stream`.
+    ///
+    /// This is off by default. When set, a gene takes the sequence name and strand of its first
+    /// record, and spans the coordinates of all of its records, even those on other sequences
+    /// or strands.
+    pub fn merge_loci(&mut self, merge: bool) -> &mut Self {
+        self.merge_loci = merge;
+        self
+    }
+
     /// Creates an iterator of refFlat records.
     pub fn records_stream(&mut self) -> RefFlatRecordsStream<R> {
         RefFlatRecordsStream {
-            inner: self.inner.decode(),
+            inner: self.inner.records(),
             seq_name_prefix: self.seq_name_prefix.as_deref(),
             seq_name_lstrip: self.seq_name_lstrip.as_deref(),
+            region: self.region.as_ref().map(|&(ref seq, start, end)| (seq.as_str(), start, end)),
+            seq_name_aliases: self.seq_name_aliases.as_ref(),
+            map_record: self.map_record.as_mut().map(|func| &mut **func),
+            cancel_token: self.cancel_token.as_ref(),
+            cancelled: false,
+            line_num: 0,
+            on_error: &mut self.on_error,
+            gene_pred: self.gene_pred,
         }
     }
 
@@ -375,8 +861,116 @@ impl<R: io::Read> Reader<R> {
     /// This iterator groups consecutive records based on their gene identifiers into genes.
     pub fn genes_stream(&mut self) -> RefFlatGenesStream<R> {
         RefFlatGenesStream {
-            inner: self.records_stream()
-                .group_by(RefFlatGenesStream::<R>::group_func),
+            inner: self.records_stream(),
+            next_record: None,
+            next_error: None,
+        }
+    }
+
+    /// Reads all genes into an annotation set, timing the parsing and assembly steps.
+    ///
+    /// Genes are created from consecutive records as in `genes_stream`. Parsing includes
+    /// reading and decoding the records, while assembly includes creating the transcripts and
+    /// genes from the records.
+    pub fn load_annotation(&mut self) -> ::Result<(AnnotationSet, LoadReport)> {
+        let mut timer = LoadTimer::new();
+        let mut genes = Vec::new();
+        let gene_pred = self.gene_pred;
+        {
+            let mut records = self.records_stream();
+            let mut group: Vec<(usize, RefFlatRecord)> = Vec::new();
+            loop {
+                let record = match timer.parse(|| records.next()) {
+                    Some(result) => Some((records.line_num, result?)),
+                    None => None,
+                };
+                let group_done = match (record.as_ref(), group.first()) {
+                    (Some(&(_, ref rec)), Some(&(_, ref first))) =>
+                        !same_gene(first, rec, gene_pred),
+                    (None, Some(_)) => true,
+                    (_, None) => false,
+                };
+                if group_done {
+                    let recs = mem::replace(&mut group, Vec::new());
+                    let on_error = &mut *records.on_error;
+                    let gene = timer.assemble(|| records_to_gene(recs, gene_pred, on_error))?;
+                    if let Some(gene) = gene {
+                        genes.push(gene);
+                    }
+                }
+                match record {
+                    Some(rec) => group.push(rec),
+                    None => break,
+                }
+            }
+        }
+        let report = timer.finish(&genes);
+        Ok((AnnotationSet::from_genes(genes), report))
+    }
+
+    /// Creates an iterator of genes from records in any order.
+    ///
+    /// Unlike `genes_stream`, the records of a gene do not need to be consecutive: all records
+    /// are read into memory first, and grouped by their gene identifiers, sequence names, and
+    /// strands, so that a gene symbol used at several loci gives one gene per locus. See
+    /// `merge_loci` for grouping by gene identifiers only. Genes are returned in the order
+    /// their first record appears in the file. If a record can not be read, its error is
+    /// yielded first, and the genes of the records read before it afterwards.
+    ///
+    /// Memory use is not bounded: it grows with the size of the whole input, not of the
+    /// largest gene. For large inputs whose records are already grouped per gene, use
+    /// `genes_stream` or `sorted_genes_stream` instead, or sort the input first.
+    pub fn unsorted_genes_stream(&mut self) -> RefFlatUnsortedGenesStream {
+        let (gene_pred, merge_loci) = (self.gene_pred, self.merge_loci);
+        let mut groups: LinkedHashMap<UnsortedKey, Vec<(usize, RefFlatRecord)>> =
+            LinkedHashMap::new();
+        let mut error = None;
+        {
+            let mut records = self.records_stream();
+            while let Some(result) = records.next() {
+                match result {
+                    Ok(record) => {
+                        let locus = if merge_loci {
+                            None
+                        } else {
+                            Some((record.seq_name.clone(), record.strand))
+                        };
+                        let key = (record.gene_key(gene_pred).clone(), locus);
+                        groups.entry(key).or_insert_with(Vec::new)
+                            .push((records.line_num, record));
+                    },
+                    Err(err) => {
+                        error = Some(err);
+                        break;
+                    },
+                }
+            }
+        }
+        RefFlatUnsortedGenesStream {
+            error: error,
+            groups: groups.into_iter(),
+            gene_pred: gene_pred,
+            on_error: &mut self.on_error,
+        }
+    }
+
+    /// Creates an iterator of genes from records sorted by gene and position.
+    ///
+    /// The records of each gene must be consecutive, the records of each sequence must be
+    /// consecutive, and genes must be sorted by their start coordinates within a sequence. Each
+    /// gene is yielded as soon as its last record is read, so memory use does not grow with the
+    /// size of the input. An error is yielded, and iteration stops, when the input is found to
+    /// be unsorted.
+    pub fn sorted_genes_stream(&mut self) -> RefFlatSortedGenesStream<R> {
+        RefFlatSortedGenesStream {
+            inner: self.records_stream(),
+            next_record: None,
+            next_error: None,
+            seq_name: None,
+            seen_seq_names: HashSet::new(),
+            seen_gene_ids: HashSet::new(),
+            last_start: 0,
+            done: false,
         }
     }
 }
@@ -387,13 +981,142 @@ impl Reader<fs::File> {
     pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
         fs::File::open(path).map(Reader::from_reader)
     }
+
+    /// Creates a reader for region queries on the bgzip-compressed refFlat file in the given
+    /// path, using the tabix index in the given index path.
+    pub fn indexed<P, Q>(path: P, tbi_path: Q) -> ::Result<IndexedReader>
+        where P: AsRef<Path>, Q: AsRef<Path>
+    {
+        let index = TabixIndex::from_file(tbi_path).map_err(::Error::from)?;
+        Ok(IndexedReader {
+            path: path.as_ref().to_path_buf(),
+            index: index,
+            cancel_token: None,
+        })
+    }
+}
+
+/// RefFlat reader for region queries on bgzip-compressed, tabix-indexed files.
+pub struct IndexedReader {
+    path: PathBuf,
+    index: TabixIndex,
+    cancel_token: Option<CancelToken>,
+}
+
+impl IndexedReader {
+
+    /// Sets a token for cancelling queries.
+    ///
+    /// The token is checked before each record of a query is read. Once it is cancelled,
+    /// queries return `Error::Cancelled`.
+    pub fn cancel_token(&mut self, token: CancelToken) -> &mut Self {
+        self.cancel_token = Some(token);
+        self
+    }
+
+    /// Returns the records whose transcripts overlap the given zero-based, half-open region.
+    ///
+    /// Only the parts of the file that may contain overlapping records are read. Sequences not
+    /// present in the index have no overlapping records.
+    pub fn query(&self, seq_name: &str, start: u64, end: u64) -> ::Result<Vec<RefFlatRecord>> {
+        let offset = match self.index.start_offset(seq_name, start, end) {
+            Some(offset) => offset,
+            None => return Ok(Vec::new()),
+        };
+        let decoder = tabix::open_at(&self.path, offset)
+            .map_err(|e| ::Error::from(TabixError::from(e)))?;
+
+        let mut reader = Reader::from_reader(decoder);
+        if let Some(ref token) = self.cancel_token {
+            let _ = reader.cancel_token(token.clone());
+        }
+        let mut records = Vec::new();
+        for result in reader.records_stream() {
+            let record = result?;
+            // the file is sorted, so no records past this one may overlap the region
+            if record.seq_name() != seq_name || record.transcript_start() >= end {
+                break;
+            }
+            if record.transcript_end() > start {
+                records.push(record);
+            }
+        }
+        Ok(records)
+    }
+
+    /// Returns the transcripts that overlap the given zero-based, half-open region.
+    pub fn query_transcripts(&self, seq_name: &str, start: u64, end: u64)
+        -> ::Result<Vec<Transcript>>
+    {
+        self.query(seq_name, start, end)?
+            .into_iter()
+            .map(|record| record.into_transcript())
+            .collect()
+    }
+}
+
+/// RefFlat reader that yields records borrowing from its line buffer.
+///
+/// Since each record borrows from the reader, records are read one at a time using
+/// `next_record` instead of an iterator. Empty lines are skipped, and sequence names are not
+/// modified.
+pub struct BorrowingReader<R: io::Read> {
+    inner: io::BufReader<R>,
+    line: String,
+    line_num: usize,
+}
+
+impl<R: io::Read> BorrowingReader<R> {
+
+    /// Creates a borrowing refFlat reader from another reader.
+    pub fn from_reader(in_reader: R) -> BorrowingReader<R> {
+        BorrowingReader {
+            inner: io::BufReader::new(in_reader),
+            line: String::new(),
+            line_num: 0,
+        }
+    }
+
+    /// Reads the next record, returning `None` when there are no more records.
+    pub fn next_record(&mut self) -> Option<::Result<RefFlatRecordRef>> {
+        loop {
+            self.line.clear();
+            match self.inner.read_line(&mut self.line) {
+                Ok(0) => return None,
+                Ok(_) => {},
+                Err(e) => return Some(Err(::Error::from(RefFlatError::from(e)))),
+            }
+            self.line_num += 1;
+            if !self.line.trim_end_matches(&['\n', '\r'][..]).is_empty() {
+                break;
+            }
+        }
+        let line = self.line.trim_end_matches(&['\n', '\r'][..]);
+        Some(RefFlatRecordRef::try_from_line(line, self.line_num))
+    }
+}
+
+impl BorrowingReader<fs::File> {
+
+    /// Creates a borrowing refFlat reader that reads from the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        fs::File::open(path).map(BorrowingReader::from_reader)
+    }
 }
 
 /// Iterator over refFlat records.
 pub struct RefFlatRecordsStream<'a, R: 'a> where R: io::Read {
-    inner: csv::DecodedRecords<'a, R, RefFlatRow>,
+    inner: csv::StringRecords<'a, R>,
     seq_name_prefix: Option<&'a str>,
     seq_name_lstrip: Option<&'a str>,
+    region: Option<(&'a str, u64, u64)>,
+    seq_name_aliases: Option<&'a SeqNameAliases>,
+    map_record: Option<&'a mut (FnMut(&mut RefFlatRecord) + 'static)>,
+    cancel_token: Option<&'a CancelToken>,
+    cancelled: bool,
+    line_num: usize,
+    on_error: &'a mut ErrorPolicy,
+    gene_pred: bool,
 }
 
 impl<'a, R> Iterator for RefFlatRecordsStream<'a, R> where R: io::Read {
@@ -403,16 +1126,56 @@ impl<'a, R> Iterator for RefFlatRecordsStream<'a, R> where R: io::Read {
     fn next(&mut self) -> Option<Self::Item> {
         let lstrip = self.seq_name_lstrip.map(|v| (v, v.len()));
         let prefix = self.seq_name_prefix;
-        self.inner.next()
-            .map(|row| {
-                row
-                    .or_else(|err| Err(::Error::from(RefFlatError::from(err))))
-                    .map(|mut row| {
-                        update_seq_name(&mut row.2, prefix, lstrip);
-                        row
-                    })
-                    .and_then(RefFlatRecord::try_from_row)
-            })
+        let aliases = self.seq_name_aliases;
+        let gene_pred = self.gene_pred;
+        loop {
+            if self.cancelled {
+                return None;
+            }
+            if self.cancel_token.map(|token| token.is_cancelled()).unwrap_or(false) {
+                self.cancelled = true;
+                return Some(Err(::Error::Cancelled));
+            }
+            let cols = match self.inner.next() {
+                None => return None,
+                Some(Err(err @ csv::Error::Io(_))) =>
+                    return Some(Err(::Error::from(RefFlatError::from(err)))),
+                Some(cols) => cols,
+            };
+            self.line_num += 1;
+            let line_num = self.line_num;
+            let result = cols
+                .map_err(|err| RefFlatError::Csv(err, Some(line_num)))
+                .and_then(|cols| columns_to_row(cols, line_num, gene_pred))
+                .map_err(::Error::from)
+                .and_then(|mut row| {
+                    update_seq_name(&mut row.2, prefix, lstrip);
+                    if let Some(aliases) = aliases {
+                        aliases.update_seq_name(&mut row.2);
+                    }
+                    RefFlatRecord::try_from_row(row)
+                });
+            let mut rec = match result {
+                Ok(rec) => rec,
+                Err(err) => match self.on_error.handle(self.line_num, err) {
+                    Ok(()) => continue,
+                    Err(err) => return Some(Err(err)),
+                },
+            };
+            if let Some(ref mut map_record) = self.map_record {
+                map_record(&mut rec);
+            }
+            match self.region {
+                Some((seq_name, start, end)) => {
+                    if rec.seq_name == seq_name && rec.transcript_start < end
+                        && rec.transcript_end > start
+                    {
+                        return Some(Ok(rec));
+                    }
+                },
+                None => return Some(Ok(rec)),
+            }
+        }
     }
 }
 
@@ -426,80 +1189,301 @@ impl<'a, R> Iterator for RefFlatTranscriptsStream<'a, R> where R: io::Read {
     type Item = ::Result<Transcript>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.inner.next()
-            .map(|record| record.and_then(|rec| rec.into_transcript()))
+        loop {
+            let rec = match self.inner.next() {
+                None => return None,
+                Some(Ok(rec)) => rec,
+                Some(Err(err)) => return Some(Err(err)),
+            };
+            match rec.build_transcript(!self.inner.gene_pred) {
+                Ok(trx) => return Some(Ok(trx)),
+                Err(err) => match self.inner.on_error.handle(self.inner.line_num, err) {
+                    Ok(()) => continue,
+                    Err(err) => return Some(Err(err)),
+                },
+            }
+        }
+    }
+}
+
+/// Iterator over genes created from refFlat records.
+pub struct RefFlatGenesStream<'a, R: 'a> where R: io::Read {
+    inner: RefFlatRecordsStream<'a, R>,
+    next_record: Option<(usize, RefFlatRecord)>,
+    next_error: Option<::Error>,
+}
+
+impl<'a, R> RefFlatGenesStream<'a, R> where R: io::Read {
+
+    /// Takes the consecutive records of the next gene, along with their line numbers.
+    fn next_records(&mut self) -> Option<::Result<Vec<(usize, RefFlatRecord)>>> {
+        let first = match self.next_record.take() {
+            Some(first) => first,
+            None => match self.inner.next() {
+                Some(Ok(record)) => (self.inner.line_num, record),
+                Some(Err(e)) => return Some(Err(e)),
+                None => return None,
+            },
+        };
+        let gene_pred = self.inner.gene_pred;
+        let mut records = vec![first];
+        loop {
+            match self.inner.next() {
+                Some(Ok(record)) => {
+                    let line_num = self.inner.line_num;
+                    if same_gene(&records[0].1, &record, gene_pred) {
+                        records.push((line_num, record));
+                    } else {
+                        self.next_record = Some((line_num, record));
+                        break;
+                    }
+                },
+                // yield the current gene first, and the error afterwards
+                Some(Err(e)) => {
+                    self.next_error = Some(e);
+                    break;
+                },
+                None => break,
+            }
+        }
+        Some(Ok(records))
+    }
+}
+
+impl<'a, R> Iterator for RefFlatGenesStream<'a, R> where R: io::Read {
+
+    type Item = ::Result<Gene>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(err) = self.next_error.take() {
+                return Some(Err(err));
+            }
+            let records = match self.next_records() {
+                Some(Ok(records)) => records,
+                Some(Err(e)) => return Some(Err(e)),
+                None => return None,
+            };
+            let gene_pred = self.inner.gene_pred;
+            match records_to_gene(records, gene_pred, self.inner.on_error) {
+                Ok(Some(gene)) => return Some(Ok(gene)),
+                Ok(None) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// Returns whether the given records belong to the same gene.
+fn same_gene(first: &RefFlatRecord, other: &RefFlatRecord, gene_pred: bool) -> bool {
+    first.gene_key(gene_pred) == other.gene_key(gene_pred) && first.seq_name == other.seq_name
+        && first.strand == other.strand
+}
+
+/// Creates a gene from the given records of its transcripts, along with their line numbers.
+///
+/// Records of genePred lines may have an empty gene identifier column. Errors of the
+/// transcripts are passed to the given policy, so the gene is created from the remaining
+/// transcripts when they are skipped. `None` is returned when no transcript remains.
+fn records_to_gene(records: Vec<(usize, RefFlatRecord)>, gene_pred: bool,
+                   on_error: &mut ErrorPolicy) -> ::Result<Option<Gene>>
+{
+    let first_line = records[0].0;
+    let (gid, seq_name, strand_char) = (records[0].1.gene_key(gene_pred).clone(),
+                                        records[0].1.seq_name.clone(), records[0].1.strand);
+    let mut transcripts: LinkedHashMap<String, Transcript> = LinkedHashMap::new();
+    let (mut gene_start, mut gene_end) = INIT_COORD;
+    for (line_num, record) in records {
+        let transcript = match record.build_transcript(!gene_pred) {
+            Ok(transcript) => transcript,
+            Err(err) => {
+                on_error.handle(line_num, err)?;
+                continue;
+            },
+        };
+        let tid = match transcript.id() {
+            Some(tid) if !transcripts.contains_key(tid) => tid.to_owned(),
+            Some(_) => {
+                let err = RefFlatError::DuplicateTranscriptId(Some(gid.clone()));
+                on_error.handle(line_num, ::Error::from(err))?;
+                continue;
+            },
+            None => {
+                on_error.handle(line_num, ::Error::from(RefFlatError::MissingTranscriptId))?;
+                continue;
+            },
+        };
+        gene_start = min(gene_start, transcript.start());
+        gene_end = max(gene_end, transcript.end());
+        let _ = transcripts.insert(tid, transcript);
+    }
+    if transcripts.is_empty() {
+        return Ok(None);
     }
+    let (gene, errors) = GBuilder::new(seq_name, gene_start, gene_end)
+        .id(gid)
+        .strand_char(strand_char)
+        .transcripts(transcripts)
+        .transcript_coding_incl_stop(true)
+        .build_lenient()?;
+    for err in errors {
+        on_error.handle(first_line, err)?;
+    }
+    Ok(Some(gene))
 }
 
-/// The type used for grouping records into genes.
+/// The type used for grouping sorted records into genes.
 ///
 /// The tuple elements represent gene identifier, sequence name, and strand.
-type GroupKey = Option<(String, String, char)>;
+type SortedKey = (String, String, char);
 
-/// The type of the function used for creating record-grouping keys for genes.
-type GroupFunc = fn(&::Result<RefFlatRecord>) -> GroupKey;
+/// Iterator over genes created from refFlat records sorted by gene and position.
+///
+/// Only the records of the current gene are kept in memory, along with the identifiers of the
+/// genes already yielded on the current sequence for detecting unsorted input.
+pub struct RefFlatSortedGenesStream<'a, R: 'a> where R: io::Read {
+    inner: RefFlatRecordsStream<'a, R>,
+    next_record: Option<(usize, RefFlatRecord)>,
+    next_error: Option<::Error>,
+    seq_name: Option<String>,
+    seen_seq_names: HashSet<String>,
+    seen_gene_ids: HashSet<String>,
+    last_start: u64,
+    done: bool,
+}
 
-/// The type of the grouped records for creating genes.
-type GroupedRecords<'a, 'b, R> = Group<'b, GroupKey, RefFlatRecordsStream<'a, R>, GroupFunc>;
+impl<'a, R> RefFlatSortedGenesStream<'a, R> where R: io::Read {
 
-/// Iterator over genes created from refFlat records.
-pub struct RefFlatGenesStream<'a, R: 'a> where R: io::Read, {
-    inner: GroupBy<GroupKey, RefFlatRecordsStream<'a, R>, GroupFunc>,
+    /// Takes the records of the next gene, checking that the gene is sorted.
+    fn next_records(&mut self) -> Option<::Result<(SortedKey, Vec<(usize, RefFlatRecord)>)>> {
+        let first = match self.next_record.take() {
+            Some(first) => first,
+            None => match self.inner.next() {
+                Some(Ok(record)) => (self.inner.line_num, record),
+                Some(Err(e)) => return Some(Err(e)),
+                None => return None,
+            },
+        };
+        let gene_pred = self.inner.gene_pred;
+        let key = (first.1.gene_key(gene_pred).clone(), first.1.seq_name.clone(), first.1.strand);
+        let mut records = vec![first];
+        loop {
+            match self.inner.next() {
+                Some(Ok(record)) => {
+                    let line_num = self.inner.line_num;
+                    if same_gene(&records[0].1, &record, gene_pred) {
+                        records.push((line_num, record));
+                    } else {
+                        self.next_record = Some((line_num, record));
+                        break;
+                    }
+                },
+                // yield the current gene first, and the error afterwards
+                Some(Err(e)) => {
+                    self.next_error = Some(e);
+                    break;
+                },
+                None => break,
+            }
+        }
+
+        let gene_start = records.iter().map(|&(_, ref rec)| rec.transcript_start).min().unwrap();
+        if self.seq_name.as_ref() != Some(&key.1) {
+            if !self.seen_seq_names.insert(key.1.clone()) {
+                return Some(Err(::Error::from(RefFlatError::UnsortedGene(Some(key.0)))));
+            }
+            self.seq_name = Some(key.1.clone());
+            self.seen_gene_ids.clear();
+            self.last_start = 0;
+        }
+        if gene_start < self.last_start || !self.seen_gene_ids.insert(key.0.clone()) {
+            return Some(Err(::Error::from(RefFlatError::UnsortedGene(Some(key.0)))));
+        }
+        self.last_start = gene_start;
+
+        Some(Ok((key, records)))
+    }
 }
 
-impl<'a, R> RefFlatGenesStream<'a, R> where R: io::Read {
+impl<'a, R> Iterator for RefFlatSortedGenesStream<'a, R> where R: io::Read {
+
+    type Item = ::Result<Gene>;
 
-    /// Creates the group key from the given refFlat record result.
-    fn group_func(result: &::Result<RefFlatRecord>) -> GroupKey {
-        result.as_ref().ok()
-            .map(|ref res| (res.gene_id.clone(), res.seq_name.clone(), res.strand.clone()))
-    }
-
-    /// Creates genes from the given grouped records.
-    fn group_to_gene<'b>(group: (GroupKey, GroupedRecords<'a, 'b, R>)) -> ::Result<Gene> {
-        let (group_key, records) = group;
-        match group_key {
-
-            None => Err(records.filter_map(|x| x.err()).next().unwrap()),
-
-            Some((gid, seq_name, strand_char)) => {
-                let mut transcripts = LinkedHashMap::new();
-                let (mut gene_start, mut gene_end) = INIT_COORD;
-                for record in records {
-                    let transcript = record.and_then(|rec| rec.into_transcript())?;
-                    gene_start = min(gene_start, transcript.start());
-                    gene_end = max(gene_end, transcript.end());
-                    let tid = transcript.id().map(|id| id.to_owned())
-                        .ok_or(::Error::from(RefFlatError::MissingTranscriptId))?;
-                    let existing_trx = transcripts.insert(tid, transcript);
-                    if existing_trx.is_some() {
-                        let err = RefFlatError::DuplicateTranscriptId(Some(gid));
-                        return Err(::Error::from(err));
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.done {
+                return None;
+            }
+            if let Some(err) = self.next_error.take() {
+                return Some(Err(err));
+            }
+            match self.next_records() {
+                Some(Ok((_, records))) => {
+                    let gene_pred = self.inner.gene_pred;
+                    match records_to_gene(records, gene_pred, self.inner.on_error) {
+                        Ok(Some(gene)) => return Some(Ok(gene)),
+                        Ok(None) => continue,
+                        Err(e) => return Some(Err(e)),
                     }
-                }
-                GBuilder::new(seq_name, gene_start, gene_end)
-                    .id(gid)
-                    .strand_char(strand_char)
-                    .transcripts(transcripts)
-                    .transcript_coding_incl_stop(true)
-                    .build()
-            },
+                },
+                Some(Err(e)) => {
+                    if let ::Error::RefFlat(RefFlatError::UnsortedGene(_)) = e {
+                        self.done = true;
+                    }
+                    return Some(Err(e));
+                },
+                None => return None,
+            }
         }
     }
 }
 
-impl<'a, R> Iterator for RefFlatGenesStream<'a, R> where R: io::Read {
+/// The type used for grouping records in any order into genes.
+///
+/// The tuple elements represent gene identifier and, unless loci are merged, sequence name and
+/// strand.
+type UnsortedKey = (String, Option<(String, char)>);
+
+/// Iterator over genes created from refFlat records in any order.
+///
+/// All records are read when the iterator is created, and kept until their gene is yielded, so
+/// memory use grows with the size of the input.
+pub struct RefFlatUnsortedGenesStream<'a> {
+    error: Option<::Error>,
+    groups: linked_hash_map::IntoIter<UnsortedKey, Vec<(usize, RefFlatRecord)>>,
+    gene_pred: bool,
+    on_error: &'a mut ErrorPolicy,
+}
+
+impl<'a> Iterator for RefFlatUnsortedGenesStream<'a> {
 
     type Item = ::Result<Gene>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.inner.into_iter().map(Self::group_to_gene).next()
+        if let Some(err) = self.error.take() {
+            return Some(Err(err));
+        }
+        let gene_pred = self.gene_pred;
+        while let Some((_, records)) = self.groups.next() {
+            match records_to_gene(records, gene_pred, self.on_error) {
+                Ok(Some(gene)) => return Some(Ok(gene)),
+                Ok(None) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        None
     }
 }
 
 /// RefFlat writer.
 pub struct Writer<W: io::Write> {
     inner: csv::Writer<W>,
+    sorted: bool,
+    seq_order: ChromOrder,
+    max_buffered: Option<usize>,
+    row_buffer: Vec<BufferedRow>,
+    exon_list_sep: char,
+    exon_list_trailing_sep: bool,
 }
 
 impl<W: io::Write> Writer<W> {
@@ -509,52 +1493,105 @@ impl<W: io::Write> Writer<W> {
         Writer {
             inner: csv::Writer::from_writer(in_writer)
                 .delimiter(b'\t')
-                .quote_style(csv::QuoteStyle::Never)
+                .quote_style(csv::QuoteStyle::Never),
+            sorted: false,
+            seq_order: ChromOrder::default(),
+            max_buffered: None,
+            row_buffer: Vec::new(),
+            exon_list_sep: ',',
+            exon_list_trailing_sep: true,
         }
     }
 
+    /// Sets whether the writer sorts its output by position.
+    ///
+    /// When set, written rows are buffered instead of being written directly. When `finish` is
+    /// called, or when the writer is dropped, the buffered rows are sorted as in
+    /// `sort::cmp_positions` and written in that order, so that the output can be compressed
+    /// with `bgzip` and indexed with `tabix` directly. Rows at the same position keep their
+    /// input order.
+    ///
+    /// Errors that occur when the writer is dropped are ignored, so `finish` should be called
+    /// to handle them.
+    pub fn sorted(&mut self, sorted: bool) -> &mut Self {
+        self.sorted = sorted;
+        self
+    }
+
+    /// Sets the order of sequence names used when sorting the output.
+    ///
+    /// The default is `ChromOrder::Karyotypic`.
+    pub fn seq_order(&mut self, order: ChromOrder) -> &mut Self {
+        self.seq_order = order;
+        self
+    }
+
+    /// Sets the separator of the coordinates in the exon start and end columns.
+    ///
+    /// The default separator is a comma, as in UCSC files. The reader of this crate only
+    /// accepts commas.
+    pub fn exon_list_separator(&mut self, sep: char) -> &mut Self {
+        self.exon_list_sep = sep;
+        self
+    }
+
+    /// Sets whether the exon start and end columns end with a separator.
+    ///
+    /// UCSC files end these columns with a comma, which is the default. The reader of this
+    /// crate accepts columns with or without it.
+    pub fn exon_list_trailing_separator(&mut self, trailing: bool) -> &mut Self {
+        self.exon_list_trailing_sep = trailing;
+        self
+    }
+
+    /// Sets the maximum number of rows buffered when sorting the output.
+    ///
+    /// Writing more rows than the limit returns `RefFlatError::BufferLimitExceeded`. There is
+    /// no limit by default.
+    pub fn max_buffered(&mut self, limit: usize) -> &mut Self {
+        self.max_buffered = Some(limit);
+        self
+    }
+
+    /// Writes all rows buffered when sorting the output.
+    ///
+    /// This does nothing when the writer does not sort its output.
+    pub fn finish(&mut self) -> ::Result<()> {
+        let mut rows = mem::replace(&mut self.row_buffer, Vec::new());
+        let order = self.seq_order;
+        rows.sort_by(|a, b| cmp_positions(a, b, order));
+        for buffered in rows.iter() {
+            self.write_row(&buffered.row)?;
+        }
+        Ok(())
+    }
+
     /// Writes the given row.
+    ///
+    /// When sorting the output, the row is buffered instead.
     pub fn write(&mut self, row: &RefFlatRow) -> ::Result<()> {
-        self.inner
-            .encode((&row.0, &row.1, &row.2, row.3, row.4, row.5, row.6, row.7, row.8,
-                     &row.9, &row.10))
-            .map_err(|e| ::Error::from(RefFlatError::from(e)))
+        if self.sorted {
+            return self.buffer_row(row.clone());
+        }
+        self.write_row(row)
     }
 
     /// Writes the given record.
     pub fn write_record(&mut self, record: &RefFlatRecord) -> ::Result<()> {
-        let mut exon_starts = record.exon_starts.iter().join(",");
-        exon_starts.push(',');
-        let mut exon_ends = record.exon_ends.iter().join(",");
-        exon_ends.push(',');
-        self.inner
-            .encode((&record.gene_id, &record.transcript_id, &record.seq_name,
-                     record.strand, record.transcript_start, record.transcript_end,
-                     record.coding_start, record.coding_end, record.num_exons(),
-                     exon_starts, exon_ends))
-            .map_err(|e| ::Error::from(RefFlatError::from(e)))
+        let exon_starts = self.format_exon_list(record.exon_starts.iter().cloned());
+        let exon_ends = self.format_exon_list(record.exon_ends.iter().cloned());
+        let row = (record.gene_id.clone(), record.transcript_id.clone(),
+                   record.seq_name.clone(), record.strand,
+                   record.transcript_start, record.transcript_end,
+                   record.coding_start, record.coding_end, record.num_exons(),
+                   exon_starts, exon_ends);
+        self.write_owned_row(row)
     }
 
     /// Writes the given transcript as a single row.
     pub fn write_transcript(&mut self, transcript: &Transcript) -> ::Result<()> {
-        let transcript_name = transcript.id()
-            .ok_or(::Error::RefFlat(RefFlatError::MissingTranscriptId))?;
-        let strand_char = match transcript.strand() {
-            &Strand::Forward => '+',
-            &Strand::Reverse => '-',
-            &Strand::Unknown => '.',
-        };
-
-        let (coding_start, coding_end) = transcript.coding_coord(true)
-            .unwrap_or((transcript.end(), transcript.end()));
-        let (exon_starts, exon_ends) = transcript.coords_field();
-
-        self.inner
-            .encode((transcript.gene_id(), transcript_name, transcript.seq_name(), strand_char,
-                     transcript.start(), transcript.end(),
-                     coding_start, coding_end, transcript.exons().len(),
-                     exon_starts, exon_ends))
-            .map_err(|e| ::Error::from(RefFlatError::from(e)))
+        let record = RefFlatRecord::try_from(transcript)?;
+        self.write_record(&record)
     }
 
     /// Writes the given gene as multiple rows.
@@ -564,18 +1601,77 @@ impl<W: io::Write> Writer<W> {
         }
         Ok(())
     }
+
+    /// Returns the string value of an exon coordinate column.
+    fn format_exon_list<I>(&self, coords: I) -> String
+        where I: Iterator<Item=u64>
+    {
+        let mut buf = [0; 4];
+        let sep = &*self.exon_list_sep.encode_utf8(&mut buf);
+        let mut value = coords.join(sep);
+        if self.exon_list_trailing_sep {
+            value.push_str(sep);
+        }
+        value
+    }
+
+    /// Writes or buffers the given row, depending on whether the output is sorted.
+    fn write_owned_row(&mut self, row: RefFlatRow) -> ::Result<()> {
+        if self.sorted {
+            return self.buffer_row(row);
+        }
+        self.write_row(&row)
+    }
+
+    /// Buffers the given row, checking the buffer limit.
+    fn buffer_row(&mut self, row: RefFlatRow) -> ::Result<()> {
+        if let Some(limit) = self.max_buffered {
+            if self.row_buffer.len() >= limit {
+                return Err(::Error::from(RefFlatError::BufferLimitExceeded(limit)));
+            }
+        }
+        let strand = Strand::from_char(&row.3).unwrap_or(Strand::Unknown);
+        self.row_buffer.push(BufferedRow { row: row, strand: strand });
+        Ok(())
+    }
+
+    /// Writes the given row directly.
+    fn write_row(&mut self, row: &RefFlatRow) -> ::Result<()> {
+        self.inner
+            .encode((&row.0, &row.1, &row.2, row.3, row.4, row.5, row.6, row.7, row.8,
+                     &row.9, &row.10))
+            .map_err(|e| ::Error::from(RefFlatError::from(e)))
+    }
+}
+
+impl<W: io::Write> Drop for Writer<W> {
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
+
+/// Row buffered by a sorting refFlat writer, along with its parsed strand.
+struct BufferedRow {
+    row: RefFlatRow,
+    strand: Strand,
 }
 
-impl Transcript {
+impl Positioned for BufferedRow {
+
+    fn seq_name(&self) -> &str {
+        self.row.2.as_str()
+    }
+
+    fn start(&self) -> u64 {
+        self.row.4
+    }
+
+    fn end(&self) -> u64 {
+        self.row.5
+    }
 
-    /// Returns the string values of the exon coordinate columns.
-    #[inline(always)]
-    fn coords_field(&self) -> (String, String) {
-        let mut coord_starts = self.exons().iter().map(|exon| exon.start()).join(",");
-        coord_starts.push(',');
-        let mut coord_ends = self.exons().iter().map(|exon| exon.end()).join(",");
-        coord_ends.push(',');
-        (coord_starts, coord_ends)
+    fn strand(&self) -> &Strand {
+        &self.strand
     }
 }
 
@@ -0,0 +1,419 @@
+/*! Streaming annotation of VCF files.
+
+The annotator reads a coordinate-sorted [VCF](https://samtools.github.io/hts-specs/VCFv4.3.pdf)
+file and a coordinate-sorted stream of genes, joining them in a single pass. Only the genes that
+may still overlap upcoming variants are kept in memory, so files with millions of variants can be
+annotated with constant memory.
+
+Each variant overlapping a gene gets three `INFO` tags, with one value per overlapping
+transcript:
+
+* `GENE`: identifier of the gene.
+* `TRANSCRIPT`: identifier of the transcript.
+* `GTE_CSQ`: [Sequence Ontology](http://www.sequenceontology.org/) term of the most severe
+  location of the variant in the transcript, e.g. `coding_sequence_variant` or `intron_variant`.
+  Its name differs from the `CSQ` tag of VEP, so that both annotations may be kept.
+
+Values of these tags already present in the input, along with their header lines, are replaced.
+
+The order of the sequences is taken from the `##contig` lines of the VCF header, and the genes
+must be sorted by the same order and then by their start coordinates. Genes on sequences not
+listed in the header are skipped. When the header does not list any contigs, the records and the
+genes must instead be sorted by the sequence name order set with `seq_order`, which is the
+karyotypic order by default. Only the fixed columns of the VCF records are parsed; all other
+columns are written unchanged.
+*/
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::cmp::Ordering;
+use std::io::{self, BufRead, BufReader, Write};
+use std::mem;
+use std::str::FromStr;
+
+use {AnnotationError, Gene, RegionLabel, RegionWindows};
+use sort::ChromOrder;
+use annotation::classify_transcript_region;
+
+
+quick_error! {
+    /// Errors that occur when annotating VCF files.
+    #[derive(Debug)]
+    pub enum VcfError {
+        /// Occurs when a VCF record line can not be parsed.
+        InvalidRecord(line_num: usize) {
+            description("invalid VCF record")
+            display(self_) -> ("{}, line: {}", self_.description(), line_num)
+        }
+        /// Occurs when a record is on a sequence not listed in the VCF header.
+        UnknownSeqName(seq_name: String, line_num: usize) {
+            description("sequence name not found in VCF header contigs")
+            display(self_) -> ("{}: {}, line: {}", self_.description(), seq_name, line_num)
+        }
+        /// Occurs when a record is not sorted after the previous record.
+        UnsortedRecord(line_num: usize) {
+            description("VCF records are not sorted")
+            display(self_) -> ("{}, line: {}", self_.description(), line_num)
+        }
+        /// Errors propagated from reading or writing the underlying files.
+        Io(err: io::Error) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// Default tag for gene identifiers.
+const DEF_GENE_TAG: &'static str = "GENE";
+
+/// Default tag for transcript identifiers.
+const DEF_TRANSCRIPT_TAG: &'static str = "TRANSCRIPT";
+
+/// Default tag for consequences.
+const DEF_CONSEQUENCE_TAG: &'static str = "GTE_CSQ";
+
+/// Prefix of VCF header lines describing INFO tags.
+const INFO_PREFIX: &'static str = "##INFO=<";
+
+/// Prefix of VCF header lines describing contigs.
+const CONTIG_PREFIX: &'static str = "##contig=<";
+
+/// Minimum number of VCF columns, up to the INFO column.
+const NUM_COLS: usize = 8;
+
+/// Position of the INFO column in VCF records.
+const INFO_COL: usize = 7;
+
+/// Counts of a VCF annotation run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct VcfSummary {
+    num_variants: usize,
+    num_annotated: usize,
+}
+
+impl VcfSummary {
+
+    /// Returns the number of variants read.
+    pub fn num_variants(&self) -> usize {
+        self.num_variants
+    }
+
+    /// Returns the number of variants overlapping at least one transcript.
+    pub fn num_annotated(&self) -> usize {
+        self.num_annotated
+    }
+}
+
+/// Streaming annotator of VCF files.
+#[derive(Debug, Clone)]
+pub struct VcfAnnotator {
+    gene_tag: String,
+    transcript_tag: String,
+    consequence_tag: String,
+    seq_order: ChromOrder,
+}
+
+impl Default for VcfAnnotator {
+    fn default() -> Self {
+        VcfAnnotator::new()
+    }
+}
+
+impl VcfAnnotator {
+
+    /// Creates an annotator using the default `GENE`, `TRANSCRIPT`, and `GTE_CSQ` tags.
+    pub fn new() -> Self {
+        VcfAnnotator {
+            gene_tag: DEF_GENE_TAG.to_owned(),
+            transcript_tag: DEF_TRANSCRIPT_TAG.to_owned(),
+            consequence_tag: DEF_CONSEQUENCE_TAG.to_owned(),
+            seq_order: ChromOrder::Karyotypic,
+        }
+    }
+
+    /// Sets the INFO tag of the gene identifiers.
+    pub fn gene_tag<T>(&mut self, tag: T) -> &mut Self
+        where T: Into<String>
+    {
+        self.gene_tag = tag.into();
+        self
+    }
+
+    /// Sets the INFO tag of the transcript identifiers.
+    pub fn transcript_tag<T>(&mut self, tag: T) -> &mut Self
+        where T: Into<String>
+    {
+        self.transcript_tag = tag.into();
+        self
+    }
+
+    /// Sets the INFO tag of the consequences.
+    pub fn consequence_tag<T>(&mut self, tag: T) -> &mut Self
+        where T: Into<String>
+    {
+        self.consequence_tag = tag.into();
+        self
+    }
+
+    /// Sets the order of sequence names used when the VCF header does not list any contigs.
+    ///
+    /// The default is `ChromOrder::Karyotypic`. This value is ignored when the header has
+    /// `##contig` lines.
+    pub fn seq_order(&mut self, order: ChromOrder) -> &mut Self {
+        self.seq_order = order;
+        self
+    }
+
+    /// Annotates the variants read from the given VCF reader with the given genes, writing the
+    /// annotated VCF to the given writer.
+    ///
+    /// Descriptions of the tags are added to the header, right before the column header line.
+    pub fn annotate<R, G, W>(&self, vcf: R, genes: G, mut out: W) -> ::Result<VcfSummary>
+        where R: io::Read, G: IntoIterator<Item=::Result<Gene>>, W: io::Write
+    {
+        let mut genes = genes.into_iter();
+        let mut ranks: HashMap<String, usize> = HashMap::new();
+        let mut order: Option<SeqOrder> = None;
+        let mut active: VecDeque<Gene> = VecDeque::new();
+        let mut next_gene: Option<Gene> = None;
+        let mut last_gene: Option<(String, u64)> = None;
+        let mut last_variant: Option<(String, u64)> = None;
+        let mut summary = VcfSummary::default();
+
+        let mut line_num = 0;
+        for line in BufReader::new(vcf).lines() {
+            let line = line.map_err(vcf_io_error)?;
+            line_num += 1;
+
+            if line.starts_with(CONTIG_PREFIX) {
+                if let Some(id) = contig_id(&line) {
+                    let rank = ranks.len();
+                    let _ = ranks.entry(id.to_owned()).or_insert(rank);
+                }
+            }
+            if line.starts_with(INFO_PREFIX) && self.is_own_info_header(&line) {
+                continue;
+            }
+            if line.starts_with("#CHROM") {
+                self.write_info_headers(&mut out)?;
+            }
+            if line.starts_with('#') || line.is_empty() {
+                writeln!(out, "{}", line).map_err(vcf_io_error)?;
+                continue;
+            }
+
+            if order.is_none() {
+                order = Some(SeqOrder::new(mem::replace(&mut ranks, HashMap::new()),
+                                           self.seq_order));
+            }
+            let order = order.as_ref().unwrap();
+            let cols = line.splitn(NUM_COLS + 1, '\t').collect::<Vec<&str>>();
+            if cols.len() < NUM_COLS {
+                return Err(::Error::from(VcfError::InvalidRecord(line_num)));
+            }
+            let seq_name = cols[0];
+            if !order.contains(seq_name) {
+                let err = VcfError::UnknownSeqName(seq_name.to_owned(), line_num);
+                return Err(::Error::from(err));
+            }
+            let pos = u64::from_str(cols[1])
+                .map_err(|_| ::Error::from(VcfError::InvalidRecord(line_num)))?;
+            if pos == 0 {
+                return Err(::Error::from(VcfError::InvalidRecord(line_num)));
+            }
+            let (start, end) = (pos - 1, pos - 1 + cols[3].len() as u64);
+            let unsorted = last_variant.as_ref()
+                .map(|&(ref last_seq, last_start)| {
+                    order.cmp((last_seq.as_str(), last_start), (seq_name, start))
+                        == Ordering::Greater
+                })
+                .unwrap_or(false);
+            if unsorted {
+                return Err(::Error::from(VcfError::UnsortedRecord(line_num)));
+            }
+            last_variant = Some((seq_name.to_owned(), start));
+            summary.num_variants += 1;
+
+            // load all genes that start before the variant ends
+            loop {
+                if next_gene.is_none() {
+                    next_gene = next_known_gene(&mut genes, order, &mut last_gene)?;
+                }
+                match next_gene.take() {
+                    Some(gene) => {
+                        if order.cmp((gene.seq_name(), gene.start()), (seq_name, end))
+                            == Ordering::Less
+                        {
+                            active.push_back(gene);
+                        } else {
+                            next_gene = Some(gene);
+                            break;
+                        }
+                    },
+                    None => break,
+                }
+            }
+            active.retain(|gene| gene.seq_name() == seq_name && gene.end() > start);
+
+            let annotations = active.iter()
+                .flat_map(|gene| gene.transcripts().iter().map(move |t| (gene, t)))
+                .filter_map(|(gene, (tid, trx))| {
+                    let mut labels = Vec::new();
+                    classify_transcript_region(trx, start, end, &RegionWindows::new(0, 0, 0),
+                                               &mut labels);
+                    labels.into_iter()
+                        .filter_map(consequence)
+                        .min_by_key(|&(severity, _)| severity)
+                        .map(|(_, csq)| (gene.id_or_default().to_owned(), tid.clone(), csq))
+                })
+                .collect::<Vec<(String, String, &'static str)>>();
+
+            let mut info = cols[INFO_COL].split(';')
+                .filter(|entry| !entry.is_empty() && *entry != ".")
+                .filter(|entry| !self.is_own_tag(entry.split('=').next().unwrap_or("")))
+                .map(|entry| entry.to_owned())
+                .collect::<Vec<String>>();
+            if !annotations.is_empty() {
+                summary.num_annotated += 1;
+                let join = |values: Vec<&str>| values.join(",");
+                info.push(format!("{}={}", self.gene_tag,
+                                  join(annotations.iter().map(|a| a.0.as_str()).collect())));
+                info.push(format!("{}={}", self.transcript_tag,
+                                  join(annotations.iter().map(|a| a.1.as_str()).collect())));
+                info.push(format!("{}={}", self.consequence_tag,
+                                  join(annotations.iter().map(|a| a.2).collect())));
+            }
+            let info = if info.is_empty() { ".".to_owned() } else { info.join(";") };
+            let mut new_cols = cols[..INFO_COL].to_vec();
+            new_cols.push(info.as_str());
+            new_cols.extend(cols[INFO_COL + 1..].iter());
+            writeln!(out, "{}", new_cols.join("\t")).map_err(vcf_io_error)?;
+        }
+
+        Ok(summary)
+    }
+
+    /// Returns whether the given INFO key is one of the tags written by the annotator.
+    fn is_own_tag(&self, key: &str) -> bool {
+        key == self.gene_tag || key == self.transcript_tag || key == self.consequence_tag
+    }
+
+    /// Returns whether the given `##INFO` header line describes one of the tags written by the
+    /// annotator.
+    fn is_own_info_header(&self, line: &str) -> bool {
+        header_id(&line[INFO_PREFIX.len()..]).map(|id| self.is_own_tag(id)).unwrap_or(false)
+    }
+
+    /// Writes the header lines describing the INFO tags.
+    fn write_info_headers<W: io::Write>(&self, out: &mut W) -> ::Result<()> {
+        let tags = [
+            (&self.gene_tag, "Overlapping gene identifiers"),
+            (&self.transcript_tag, "Overlapping transcript identifiers"),
+            (&self.consequence_tag, "Variant consequences per overlapping transcript"),
+        ];
+        for &(tag, description) in tags.iter() {
+            writeln!(out, "##INFO=<ID={},Number=.,Type=String,Description=\"{}\">",
+                     tag, description).map_err(vcf_io_error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Order of sequence names shared by the VCF records and the genes.
+enum SeqOrder {
+    /// Ranks of the sequences listed in the `##contig` header lines.
+    Contigs(HashMap<String, usize>),
+    /// Order of sequence names used when the header does not list any contigs.
+    Names(ChromOrder),
+}
+
+impl SeqOrder {
+
+    /// Creates the order from the ranks of the header contigs, falling back to the given order
+    /// of sequence names when there are none.
+    fn new(ranks: HashMap<String, usize>, fallback: ChromOrder) -> SeqOrder {
+        if ranks.is_empty() {
+            SeqOrder::Names(fallback)
+        } else {
+            SeqOrder::Contigs(ranks)
+        }
+    }
+
+    /// Returns whether records and genes on the given sequence can be ordered.
+    fn contains(&self, seq_name: &str) -> bool {
+        match *self {
+            SeqOrder::Contigs(ref ranks) => ranks.contains_key(seq_name),
+            SeqOrder::Names(_) => true,
+        }
+    }
+
+    /// Compares two positions, given as sequence names and coordinates.
+    fn cmp(&self, a: (&str, u64), b: (&str, u64)) -> Ordering {
+        let by_seq = match *self {
+            SeqOrder::Contigs(ref ranks) => ranks.get(a.0).cmp(&ranks.get(b.0)),
+            SeqOrder::Names(order) => order.cmp_seq_names(a.0, b.0),
+        };
+        by_seq.then(a.1.cmp(&b.1))
+    }
+}
+
+/// Reads the next gene on a sequence known to the given order.
+fn next_known_gene<G>(genes: &mut G, order: &SeqOrder, last_gene: &mut Option<(String, u64)>)
+    -> ::Result<Option<Gene>>
+    where G: Iterator<Item=::Result<Gene>>
+{
+    for result in genes {
+        let gene = result?;
+        if !order.contains(gene.seq_name()) {
+            continue;
+        }
+        let unsorted = last_gene.as_ref()
+            .map(|&(ref last_seq, last_start)| {
+                order.cmp((last_seq.as_str(), last_start), (gene.seq_name(), gene.start()))
+                    == Ordering::Greater
+            })
+            .unwrap_or(false);
+        if unsorted {
+            return Err(::Error::from(
+                AnnotationError::UnsortedGenes(gene.seq_name().to_owned(), gene.start())));
+        }
+        *last_gene = Some((gene.seq_name().to_owned(), gene.start()));
+        return Ok(Some(gene));
+    }
+    Ok(None)
+}
+
+/// Returns the Sequence Ontology term of the given region label along with its severity, for
+/// labels within transcripts.
+///
+/// Lower values are more severe, following the order of the consequence table of Ensembl VEP.
+/// This differs from the order of `RegionLabel`, which puts UTRs before coding exons.
+fn consequence(label: RegionLabel) -> Option<(u8, &'static str)> {
+    match label {
+        RegionLabel::CodingExon => Some((0, "coding_sequence_variant")),
+        RegionLabel::UTR5 => Some((1, "5_prime_UTR_variant")),
+        RegionLabel::UTR3 => Some((2, "3_prime_UTR_variant")),
+        RegionLabel::OtherExon => Some((3, "non_coding_transcript_exon_variant")),
+        RegionLabel::Intron => Some((4, "intron_variant")),
+        RegionLabel::Promoter | RegionLabel::TTS | RegionLabel::DistalIntergenic => None,
+    }
+}
+
+/// Returns the ID of the given `##contig` header line.
+fn contig_id(line: &str) -> Option<&str> {
+    header_id(&line[CONTIG_PREFIX.len()..])
+}
+
+/// Returns the ID of a structured header line, given its fields after the opening bracket.
+fn header_id(fields: &str) -> Option<&str> {
+    fields.trim_end_matches('>')
+        .split(',')
+        .find(|field| field.starts_with("ID="))
+        .map(|field| &field[3..])
+}
+
+/// Helper function for converting I/O errors.
+fn vcf_io_error(err: io::Error) -> ::Error {
+    ::Error::from(VcfError::from(err))
+}
@@ -0,0 +1,137 @@
+/*! Exonic regions unique to single isoforms.
+
+Probes and primers that target a single isoform must lie in exonic bases that no other isoform
+of the same gene contains. This module computes these regions for every transcript of an
+annotation set and, given a genome, extracts their sequences.
+*/
+use std::cmp::max;
+
+use {AnnotationSet, Coord, Genome, Strand};
+use utils::OptionDeref;
+
+
+/// Exonic regions of a transcript that are not part of any other transcript of its gene.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IsoformRegions {
+    gene_id: Option<String>,
+    transcript_id: String,
+    seq_name: String,
+    strand: Strand,
+    regions: Vec<Coord<u64>>,
+}
+
+impl IsoformRegions {
+
+    /// Returns the identifier of the gene of the transcript.
+    pub fn gene_id(&self) -> Option<&str> {
+        self.gene_id.as_deref()
+    }
+
+    /// Returns the identifier of the transcript.
+    pub fn transcript_id(&self) -> &str {
+        self.transcript_id.as_str()
+    }
+
+    /// Returns the sequence name of the transcript.
+    pub fn seq_name(&self) -> &str {
+        self.seq_name.as_str()
+    }
+
+    /// Returns the strand of the transcript.
+    pub fn strand(&self) -> &Strand {
+        &self.strand
+    }
+
+    /// Returns the genome-wise coordinates of the unique regions, sorted by their start
+    /// coordinates.
+    pub fn regions(&self) -> &[Coord<u64>] {
+        self.regions.as_slice()
+    }
+
+    /// Returns the total number of bases in the unique regions.
+    pub fn num_bases(&self) -> u64 {
+        self.regions.iter().map(|&(start, end)| end - start).sum()
+    }
+
+    /// Returns the sequences of the unique regions as read from the transcript's strand.
+    ///
+    /// The sequences are in the order of the regions. `None` is returned if any region is
+    /// outside of the genome's sequences.
+    pub fn sequences(&self, genome: &Genome) -> Option<Vec<Vec<u8>>> {
+        self.regions.iter()
+            .map(|&(start, end)| genome.stranded_subseq(&self.seq_name, start, end, &self.strand))
+            .collect()
+    }
+}
+
+impl AnnotationSet {
+
+    /// Returns the exonic regions unique to each transcript among the transcripts of its gene.
+    ///
+    /// Regions shorter than `min_len` bases are left out, as they are too short for probes or
+    /// primers. Transcripts without any remaining region are left out as well. The results
+    /// are in the order of the genes and their transcripts. Transcripts of single-isoform genes
+    /// have all of their exons as unique regions.
+    pub fn unique_isoform_regions(&self, min_len: u64) -> Vec<IsoformRegions> {
+        let mut results = Vec::new();
+        for gene in self.genes() {
+            for (tid, transcript) in gene.transcripts() {
+                let shared = merge(gene.transcripts().iter()
+                    .filter(|&(other_tid, _)| other_tid != tid)
+                    .flat_map(|(_, other)| other.exons().iter())
+                    .map(|exon| (exon.start(), exon.end()))
+                    .collect());
+                let regions = merge(transcript.exons().iter()
+                        .map(|exon| (exon.start(), exon.end()))
+                        .collect())
+                    .into_iter()
+                    .flat_map(|exon| subtract(exon, &shared))
+                    .filter(|&(start, end)| end - start >= max(min_len, 1))
+                    .collect::<Vec<Coord<u64>>>();
+                if regions.is_empty() {
+                    continue;
+                }
+                results.push(IsoformRegions {
+                    gene_id: gene.id().map(|id| id.to_owned()),
+                    transcript_id: tid.clone(),
+                    seq_name: transcript.seq_name().to_owned(),
+                    strand: *transcript.strand(),
+                    regions: regions,
+                });
+            }
+        }
+        results
+    }
+}
+
+/// Helper function for sorting coordinates and merging the overlapping or abutting ones.
+fn merge(mut coords: Vec<Coord<u64>>) -> Vec<Coord<u64>> {
+    coords.sort();
+    let mut merged: Vec<Coord<u64>> = Vec::with_capacity(coords.len());
+    for (start, end) in coords {
+        if let Some(last) = merged.last_mut() {
+            if start <= last.1 {
+                last.1 = max(last.1, end);
+                continue;
+            }
+        }
+        merged.push((start, end));
+    }
+    merged
+}
+
+/// Helper function for removing the given sorted, merged coordinates from a region.
+fn subtract(region: Coord<u64>, removed: &[Coord<u64>]) -> Vec<Coord<u64>> {
+    let mut remaining = Vec::new();
+    let mut start = region.0;
+    for &(rstart, rend) in removed.iter().filter(|&&(s, e)| s < region.1 && e > region.0) {
+        if rstart > start {
+            remaining.push((start, rstart));
+        }
+        start = max(start, rend);
+    }
+    if start < region.1 {
+        remaining.push((start, region.1));
+    }
+    remaining
+}
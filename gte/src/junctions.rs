@@ -0,0 +1,501 @@
+/*! Splice junction lists and junction-based annotation metrics.
+
+The junction lists are read from the `SJ.out.tab` format produced by the
+[STAR aligner](https://github.com/alexdobin/STAR). Each line of the format denotes a single
+junction with these tab-separated columns:
+
+1. sequence name
+2. first base of the intron (one-based)
+3. last base of the intron (one-based)
+4. strand (`0`: undefined, `1`: `+`, `2`: `-`)
+5. intron motif
+6. annotation status
+7. number of uniquely mapping reads spanning the junction
+8. number of multi-mapping reads spanning the junction
+9. maximum spliced alignment overhang
+
+Only the first four columns are required. Coordinates are converted into zero-based, half-open
+intervals spanning the intron.
+*/
+use std::cmp::max;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fs;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+use std::str::FromStr;
+
+use {AnnotationSet, Coord, Gene, Strand};
+use utils::OptionDeref;
+
+
+quick_error! {
+    /// Errors that occur when reading junction files.
+    #[derive(Debug)]
+    pub enum JunctionError {
+        /// Occurs when a junction line can not be parsed.
+        InvalidRecord(line_num: usize) {
+            description("invalid junction record")
+            display(self_) -> ("{}, line: {}", self_.description(), line_num)
+        }
+        /// Errors propagated from reading the underlying file.
+        Io(err: io::Error) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// Splice junction observed in a dataset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpliceJunction {
+    seq_name: String,
+    start: u64,
+    end: u64,
+    strand: Strand,
+    unique_reads: u64,
+    multi_reads: u64,
+}
+
+impl SpliceJunction {
+
+    /// Creates a new junction spanning the given intron coordinates, without any read support.
+    pub fn new<T>(seq_name: T, start: u64, end: u64, strand: Strand) -> Self
+        where T: Into<String>
+    {
+        SpliceJunction {
+            seq_name: seq_name.into(),
+            start: start,
+            end: end,
+            strand: strand,
+            unique_reads: 0,
+            multi_reads: 0,
+        }
+    }
+
+    /// Returns the sequence name.
+    pub fn seq_name(&self) -> &str {
+        self.seq_name.as_str()
+    }
+
+    /// Returns the genome-wise 5'-most coordinate of the intron.
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// Returns the genome-wise 3'-most coordinate of the intron.
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// Returns a reference of the strand.
+    pub fn strand(&self) -> &Strand {
+        &self.strand
+    }
+
+    /// Returns the number of uniquely mapping reads spanning the junction.
+    pub fn unique_reads(&self) -> u64 {
+        self.unique_reads
+    }
+
+    /// Sets the number of uniquely mapping reads spanning the junction.
+    pub fn set_unique_reads(&mut self, count: u64) {
+        self.unique_reads = count;
+    }
+
+    /// Returns the number of multi-mapping reads spanning the junction.
+    pub fn multi_reads(&self) -> u64 {
+        self.multi_reads
+    }
+
+    /// Sets the number of multi-mapping reads spanning the junction.
+    pub fn set_multi_reads(&mut self, count: u64) {
+        self.multi_reads = count;
+    }
+
+    /// Parses a single junction line.
+    fn try_from_line(line: &str, line_num: usize) -> Result<Self, JunctionError> {
+        let fields = line.split('\t').collect::<Vec<&str>>();
+        if fields.len() < 4 {
+            return Err(JunctionError::InvalidRecord(line_num));
+        }
+        let parse_num = |idx: usize| -> Result<u64, JunctionError> {
+            fields.get(idx)
+                .map(|v| u64::from_str(v).map_err(|_| JunctionError::InvalidRecord(line_num)))
+                .unwrap_or(Ok(0))
+        };
+        let first = parse_num(1)?;
+        let last = parse_num(2)?;
+        if first == 0 || first > last {
+            return Err(JunctionError::InvalidRecord(line_num));
+        }
+        let strand = match fields[3] {
+            "1" | "+" => Strand::Forward,
+            "2" | "-" => Strand::Reverse,
+            "0" | "." => Strand::Unknown,
+            _ => return Err(JunctionError::InvalidRecord(line_num)),
+        };
+        let mut junction = SpliceJunction::new(fields[0], first - 1, last, strand);
+        junction.unique_reads = parse_num(6)?;
+        junction.multi_reads = parse_num(7)?;
+        Ok(junction)
+    }
+}
+
+/// Junction file reader.
+pub struct Reader<R: io::Read> {
+    inner: BufReader<R>,
+    line_num: usize,
+}
+
+impl<R: io::Read> Reader<R> {
+
+    /// Creates a junction reader from another reader.
+    pub fn from_reader(in_reader: R) -> Reader<R> {
+        Reader {
+            inner: BufReader::new(in_reader),
+            line_num: 0,
+        }
+    }
+
+    /// Creates an iterator of junctions.
+    pub fn junctions_stream(&mut self) -> JunctionsStream<R> {
+        JunctionsStream { reader: self }
+    }
+}
+
+impl Reader<fs::File> {
+
+    /// Creates a junction reader that reads from the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        fs::File::open(path).map(Reader::from_reader)
+    }
+}
+
+/// Iterator over junctions.
+pub struct JunctionsStream<'a, R: 'a> where R: io::Read {
+    reader: &'a mut Reader<R>,
+}
+
+impl<'a, R> Iterator for JunctionsStream<'a, R> where R: io::Read {
+
+    type Item = ::Result<SpliceJunction>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            match self.reader.inner.read_line(&mut line) {
+                Ok(0) => return None,
+                Ok(_) => {},
+                Err(e) => return Some(Err(::Error::from(JunctionError::from(e)))),
+            }
+            self.reader.line_num += 1;
+            let trimmed = line.trim_end_matches(&['\n', '\r'][..]);
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            return Some(SpliceJunction::try_from_line(trimmed, self.reader.line_num)
+                        .map_err(::Error::from));
+        }
+    }
+}
+
+/// Junction metrics of a single gene.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneJunctionStats {
+    gene_id: Option<String>,
+    num_annotated: usize,
+    num_known: usize,
+    num_novel: usize,
+}
+
+impl GeneJunctionStats {
+
+    /// Returns the gene identifier.
+    pub fn gene_id(&self) -> Option<&str> {
+        self.gene_id.as_deref()
+    }
+
+    /// Returns the number of distinct introns annotated in the gene.
+    pub fn num_annotated(&self) -> usize {
+        self.num_annotated
+    }
+
+    /// Returns the number of distinct observed junctions that match an annotated intron of the
+    /// gene.
+    pub fn num_known(&self) -> usize {
+        self.num_known
+    }
+
+    /// Returns the number of distinct observed junctions within the gene that match none of its
+    /// annotated introns.
+    pub fn num_novel(&self) -> usize {
+        self.num_novel
+    }
+
+    /// Returns the fraction of observed junctions within the gene that are known.
+    ///
+    /// The returned value is `None` if no junctions are observed within the gene.
+    pub fn known_fraction(&self) -> Option<f64> {
+        fraction(self.num_known, self.num_known + self.num_novel)
+    }
+
+    /// Returns the fraction of annotated introns of the gene that are observed.
+    ///
+    /// The returned value is `None` if the gene has no annotated introns.
+    pub fn completeness(&self) -> Option<f64> {
+        fraction(self.num_known, self.num_annotated)
+    }
+}
+
+/// Junction metrics of an annotation set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JunctionReport {
+    genes: Vec<GeneJunctionStats>,
+    num_known: usize,
+    num_novel: usize,
+    num_intergenic: usize,
+}
+
+impl JunctionReport {
+
+    /// Returns the per-gene metrics, in the order of the genes in the annotation set.
+    pub fn genes(&self) -> &[GeneJunctionStats] {
+        self.genes.as_slice()
+    }
+
+    /// Returns the number of observed junctions matching any annotated intron.
+    pub fn num_known(&self) -> usize {
+        self.num_known
+    }
+
+    /// Returns the number of observed junctions within genes that match no annotated intron.
+    pub fn num_novel(&self) -> usize {
+        self.num_novel
+    }
+
+    /// Returns the number of observed junctions that lie outside of all genes.
+    pub fn num_intergenic(&self) -> usize {
+        self.num_intergenic
+    }
+
+    /// Returns the fraction of all observed junctions that are known.
+    pub fn known_fraction(&self) -> Option<f64> {
+        fraction(self.num_known, self.num_known + self.num_novel + self.num_intergenic)
+    }
+}
+
+/// Expected number of known and novel junctions observed at a given sequencing depth.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaturationPoint {
+    fraction: f64,
+    known: f64,
+    novel: f64,
+}
+
+impl SaturationPoint {
+
+    /// Returns the fraction of reads the point is computed for.
+    pub fn fraction(&self) -> f64 {
+        self.fraction
+    }
+
+    /// Returns the expected number of known junctions.
+    pub fn known(&self) -> f64 {
+        self.known
+    }
+
+    /// Returns the expected number of novel junctions, including intergenic ones.
+    pub fn novel(&self) -> f64 {
+        self.novel
+    }
+}
+
+/// Annotated introns of a gene, used for matching observed junctions.
+struct GeneIntrons<'a> {
+    idx: usize,
+    gene: &'a Gene,
+    introns: HashSet<Coord<u64>>,
+}
+
+/// Genes of a single sequence, sorted by their start coordinates.
+struct SeqGenes<'a> {
+    genes: Vec<GeneIntrons<'a>>,
+    // running maximum of gene end coordinates, used to bound overlap queries
+    max_ends: Vec<u64>,
+}
+
+/// Index of annotated introns for classifying observed junctions.
+struct IntronIndex<'a> {
+    seqs: HashMap<&'a str, SeqGenes<'a>>,
+}
+
+impl<'a> IntronIndex<'a> {
+
+    /// Creates the index from the given annotation set.
+    fn new(annotation: &'a AnnotationSet) -> Self {
+        let mut seqs: HashMap<&'a str, SeqGenes<'a>> = HashMap::new();
+        for (idx, gene) in annotation.genes().iter().enumerate() {
+            let introns = gene.transcripts().values()
+                .flat_map(|trx| trx.intron_coords().into_iter())
+                .collect();
+            seqs.entry(gene.seq_name())
+                .or_insert_with(|| SeqGenes { genes: Vec::new(), max_ends: Vec::new() })
+                .genes.push(GeneIntrons { idx: idx, gene: gene, introns: introns });
+        }
+        for seq_genes in seqs.values_mut() {
+            seq_genes.genes.sort_by_key(|gi| (gi.gene.start(), gi.gene.end()));
+            let mut cur_max = 0;
+            seq_genes.max_ends = seq_genes.genes.iter()
+                .map(|gi| { cur_max = max(cur_max, gi.gene.end()); cur_max })
+                .collect();
+        }
+        IntronIndex { seqs: seqs }
+    }
+
+    /// Returns the genes enveloping the given junction on a compatible strand, along with
+    /// whether the junction is one of the gene's annotated introns.
+    fn classify(&self, junction: &SpliceJunction) -> Vec<(usize, bool)> {
+        let seq_genes = match self.seqs.get(junction.seq_name()) {
+            Some(sg) => sg,
+            None => return Vec::new(),
+        };
+        let (mut lo, mut hi) = (0, seq_genes.genes.len());
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if seq_genes.genes[mid].gene.start() <= junction.start {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        let mut res = Vec::new();
+        let mut idx = lo;
+        while idx > 0 {
+            idx -= 1;
+            if seq_genes.max_ends[idx] < junction.end {
+                break;
+            }
+            let gi = &seq_genes.genes[idx];
+            let strand_ok = match (&junction.strand, gi.gene.strand()) {
+                (&Strand::Unknown, _) | (_, &Strand::Unknown) => true,
+                (a, b) => a == b,
+            };
+            if strand_ok && gi.gene.end() >= junction.end {
+                res.push((gi.idx, gi.introns.contains(&(junction.start, junction.end))));
+            }
+        }
+        res
+    }
+}
+
+impl AnnotationSet {
+
+    /// Computes junction metrics of the annotation set given the observed junctions.
+    ///
+    /// Junctions supported by fewer than `min_unique_reads` uniquely mapping reads are ignored.
+    /// A junction is assigned to all genes that envelope it on a compatible strand. Junctions
+    /// on unknown strands are compatible with any gene. Junctions listed more than once are
+    /// counted once.
+    pub fn junction_report<I>(&self, junctions: I, min_unique_reads: u64) -> JunctionReport
+        where I: IntoIterator<Item=SpliceJunction>
+    {
+        let index = IntronIndex::new(self);
+        let mut genes = self.genes().iter()
+            .map(|gene| GeneJunctionStats {
+                gene_id: gene.id().map(|v| v.to_owned()),
+                num_annotated: gene.transcripts().values()
+                    .flat_map(|trx| trx.intron_coords().into_iter())
+                    .collect::<HashSet<Coord<u64>>>()
+                    .len(),
+                num_known: 0,
+                num_novel: 0,
+            })
+            .collect::<Vec<GeneJunctionStats>>();
+        // Junctions are counted once per distinct intron, so that duplicated input records do
+        // not inflate the counts.
+        let mut gene_known = vec![HashSet::new(); genes.len()];
+        let mut gene_novel = vec![HashSet::new(); genes.len()];
+        let mut seen = HashSet::new();
+        let (mut num_known, mut num_novel, mut num_intergenic) = (0, 0, 0);
+
+        for junction in junctions {
+            if junction.unique_reads < min_unique_reads {
+                continue;
+            }
+            let coord = (junction.start, junction.end);
+            let hits = index.classify(&junction);
+            for &(idx, known) in hits.iter() {
+                let _ = if known {
+                    gene_known[idx].insert(coord)
+                } else {
+                    gene_novel[idx].insert(coord)
+                };
+            }
+            if !seen.insert((junction.seq_name, coord)) {
+                continue;
+            }
+            if hits.is_empty() {
+                num_intergenic += 1;
+            } else if hits.iter().any(|&(_, known)| known) {
+                num_known += 1;
+            } else {
+                num_novel += 1;
+            }
+        }
+        for (idx, stats) in genes.iter_mut().enumerate() {
+            stats.num_known = gene_known[idx].len();
+            stats.num_novel = gene_novel[idx].len();
+        }
+
+        JunctionReport {
+            genes: genes,
+            num_known: num_known,
+            num_novel: num_novel,
+            num_intergenic: num_intergenic,
+        }
+    }
+
+    /// Computes the expected number of known and novel junctions observed when only the given
+    /// fractions of the reads are used.
+    ///
+    /// A junction supported by `n` uniquely mapping reads is expected to be observed with
+    /// probability `1 - (1 - fraction)^n` when the reads are subsampled.
+    pub fn junction_saturation(&self, junctions: &[SpliceJunction], fractions: &[f64])
+        -> Vec<SaturationPoint>
+    {
+        let index = IntronIndex::new(self);
+        let classified = junctions.iter()
+            .filter(|j| j.unique_reads > 0)
+            .map(|j| (j.unique_reads, index.classify(j).iter().any(|&(_, known)| known)))
+            .collect::<Vec<(u64, bool)>>();
+
+        fractions.iter()
+            .map(|&frac| {
+                let (mut known, mut novel) = (0.0, 0.0);
+                for &(reads, is_known) in classified.iter() {
+                    let p = 1.0 - (1.0 - frac).powf(reads as f64);
+                    if is_known {
+                        known += p;
+                    } else {
+                        novel += p;
+                    }
+                }
+                SaturationPoint { fraction: frac, known: known, novel: novel }
+            })
+            .collect()
+    }
+}
+
+/// Helper function for computing fractions that may have a zero denominator.
+#[inline]
+fn fraction(num: usize, denom: usize) -> Option<f64> {
+    if denom == 0 {
+        None
+    } else {
+        Some(num as f64 / denom as f64)
+    }
+}
@@ -16,30 +16,180 @@ or from formats such as GFF and refFlat which are commonly used for storing gene
 
 extern crate bio;
 extern crate csv;
+extern crate flate2;
 extern crate itertools;
 extern crate linked_hash_map;
 extern crate multimap;
 #[macro_use]
 extern crate quick_error;
 extern crate regex;
+#[cfg(feature = "serde")]
+extern crate serde;
 
 pub use bio::utils::Strand;
 pub use bio::io::gff::GffType;
 
+mod strand;
+pub use strand::StrandExt;
+#[cfg(feature = "serde")]
+pub use strand::serde_strand;
+
+mod attributes;
+pub use attributes::Attributes;
+
+mod biotype;
+pub use biotype::Biotype;
+
+mod tags;
+pub use tags::TranscriptTag;
+
 mod model;
 pub use model::{Feature, ModelError, FeatureKind,
                 EBuilder, Exon, ExonFeature, ExonFeatureKind,
                 TBuilder, Transcript, TranscriptFeature, TranscriptFeatureKind,
-                GBuilder, Gene, GeneFeature, GeneFeatureKind};
+                GBuilder, Gene, GeneFeature, GeneFeatureKind, TranscriptCoords,
+                CanonicalPolicy, UnknownStrandCoding, ValidationMode};
+
+mod fusion;
 
 mod io_refflat;
 pub use io_refflat::{Reader as RefFlatReader, Writer as RefFlatWriter,
-                     RefFlatError, RefFlatRow, RefFlatRecord,
-                     RefFlatRecordsStream, RefFlatTranscriptsStream, RefFlatGenesStream};
+                     IndexedReader as RefFlatIndexedReader,
+                     BorrowingReader as RefFlatBorrowingReader,
+                     RefFlatError, RefFlatRow, RefFlatRecord, RefFlatRecordRef,
+                     RefFlatRecordsStream, RefFlatTranscriptsStream, RefFlatGenesStream,
+                     RefFlatSortedGenesStream, RefFlatUnsortedGenesStream};
+
+mod io_knowngene;
+pub use io_knowngene::{Reader as KnownGeneReader, KnownGeneError,
+                       KnownGeneRecordsStream, KnownGeneTranscriptsStream, KnownGeneGenesStream};
+
+mod tabix;
+pub use tabix::TabixError;
+
+mod io_bed;
+pub use io_bed::{Writer as BedWriter, BedError, TrackOptions};
 
 mod io_gff;
 pub use io_gff::{Reader as GffReader, GffError, GffTranscripts};
 
+mod io_gff3;
+pub use io_gff3::{Writer as Gff3Writer, Gff3Error};
+
+mod io_gtf;
+pub use io_gtf::{Reader as GtfReader, Writer as GtfWriter, GtfError, GtfRecord,
+                 GtfRecordsStream, GtfTranscriptsStream, GtfGenesStream, GtfQuoting,
+                 GtfEscaping};
+
+mod liftover;
+pub use liftover::{ChainMap, LiftedGene, LiftedInterval, LiftoverError, Unmapped};
+
+mod assembly;
+pub use assembly::{clamp_to_contig, expand_window, Assembly, AssemblyError, BoundsViolation,
+                   BoundsViolationKind};
+
+mod aliases;
+pub use aliases::{AliasError, SeqNameAliases};
+
+mod annotation;
+pub use annotation::{AnnotationSet, AnnotationTranscripts, AnnotationError, GenomeAnnotation,
+                     Locus, Overlaps, RegionLabel, RegionWindows, overlaps, overlaps_with};
+
+mod compact;
+pub use compact::{CompactAnnotation, CompactTranscript};
+
+mod workspace;
+pub use workspace::{Workspace, WorkspaceError};
+
+mod instrument;
+pub use instrument::{AnnotationStats, LoadReport};
+
+mod cancel;
+pub use cancel::CancelToken;
+
+mod policy;
+pub use policy::ErrorPolicy;
+
+mod shuffle;
+pub use shuffle::{IntervalSampler, SampleError};
+
+mod diff;
+pub use diff::{ExonChange, GeneDiff, TranscriptDiff};
+
+mod synthetic;
+pub use synthetic::AnnotationGenerator;
+
+mod eqclass;
+pub use eqclass::{CompatibilityIndex, EquivalenceClass};
+
+mod junctions;
+pub use junctions::{Reader as JunctionReader, JunctionError, JunctionsStream, SpliceJunction,
+                    GeneJunctionStats, JunctionReport, SaturationPoint};
+
+mod quant;
+pub use quant::{Reader as QuantReader, QuantError, QuantRecord, QuantRecordsStream,
+                Quantification};
+
+mod domains;
+pub use domains::{Reader as DomainReader, DomainError, DomainRecord, DomainRecordsStream,
+                  ProteinDomain};
+
+mod cache;
+pub use cache::{AnnotationCache, CacheError};
+
+#[cfg(feature = "stream")]
+mod batch;
+#[cfg(feature = "stream")]
+pub use batch::{BatchReader, BatchRecord, BatchWriter};
+
+mod threads;
+pub use threads::ThreadError;
+#[cfg(feature = "threads")]
+pub use threads::{spawn_reader, spawn_reader_with_capacity};
+
+mod lookup;
+pub use lookup::{Writer as GeneLocationWriter, GeneLocation, GeneLocations, LookupError};
+
+mod io_json;
+pub use io_json::{Writer as JsonWriter, JsonError};
+
+mod io_vcf;
+pub use io_vcf::{VcfAnnotator, VcfError, VcfSummary};
+
+mod genome;
+pub use genome::{Genome, GenomeError};
+
+mod isoforms;
+pub use isoforms::IsoformRegions;
+
+mod primers;
+pub use primers::{Amplicon, PrimerError, PrimerPair};
+
+mod panel;
+pub use panel::{ExonCoverage, GeneCoverage, PanelError, PanelTargets, TranscriptCoverage};
+
+mod splice;
+pub use splice::{Writer as SpliceSiteWriter, SpliceError, SpliceSiteExtractor, SpliceSiteKind,
+                 SpliceSiteWindow};
+
+mod flatten;
+pub use flatten::ExonBin;
+
+mod grouping;
+pub use grouping::GeneAssembler;
+
+mod compare;
+pub use compare::{ClassCode, TranscriptMatch};
+
+mod orfs;
+pub use orfs::Orf;
+
+mod pretty;
+
+pub mod prelude;
+
+pub mod sort;
+
 
 quick_error! {
     /// The error type returned by the `gte` crate.
@@ -59,6 +209,27 @@ quick_error! {
             from()
             cause(err)
         }
+        /// Errors that occur when reading knownGene and kgXref tables.
+        KnownGene(err: KnownGeneError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when reading tabix indices.
+        Tabix(err: TabixError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when writing BED files.
+        Bed(err: BedError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
         /// Errors that occur when reading or writing GFF file variants.
         Gff(err: GffError) {
             description(err.description())
@@ -66,6 +237,150 @@ quick_error! {
             from()
             cause(err)
         }
+        /// Errors that occur when writing GFF3 files.
+        Gff3(err: Gff3Error) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when reading or writing GTF files.
+        Gtf(err: GtfError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when reading chain files.
+        Liftover(err: LiftoverError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when working with collections of gene models.
+        Annotation(err: AnnotationError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when reading assembly sequence lengths.
+        Assembly(err: AssemblyError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when sampling random intervals.
+        Sample(err: SampleError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when reading junction files.
+        Junction(err: JunctionError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when reading quantification files.
+        Quant(err: QuantError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when reading protein domain tables.
+        Domain(err: DomainError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when reading or writing annotation caches.
+        Cache(err: CacheError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when writing JSON files.
+        Json(err: JsonError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when annotating VCF files.
+        Vcf(err: VcfError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when reading genome sequences.
+        Genome(err: GenomeError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when extracting or writing splice site windows.
+        Splice(err: SpliceError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when reading sequence name aliases.
+        Alias(err: AliasError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when querying workspaces.
+        Workspace(err: WorkspaceError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when reading or writing gene lookup tables.
+        Lookup(err: LookupError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when creating primer pairs.
+        Primer(err: PrimerError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when reading panel targets.
+        Panel(err: PanelError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when reading annotations on background threads.
+        Thread(err: ThreadError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Occurs when an operation is stopped through a `CancelToken`.
+        Cancelled {
+            description("operation cancelled")
+        }
     }
 }
 
@@ -77,14 +392,19 @@ pub type Result<T> = ::std::result::Result<T, Error>;
 /// Helper type alias for raw coordinates.
 type Coord<T> = (T, T);
 
-/// Helper type alias for raw transcript coordinate inputs.
+/// Type alias for raw transcript coordinate inputs.
 ///
 /// This is an alias for a three-element tuple which contains:
 /// * The coordinate of the full transcript (as Coord<u64>).
 /// * The coordinates of all the transcript's exons (as (Vec<Coord<u64>>)).
 /// * The optional coordinate of the coding region, which may or may not include
 ///   the stop codon.
-type RawTrxCoords = (Coord<u64>, Vec<Coord<u64>>, Option<Coord<u64>>);
+///
+/// Values of this type can be converted into [`TranscriptCoords`].
+///
+/// [`TranscriptCoords`]: struct.TranscriptCoords.html
+#[deprecated(note = "use `TranscriptCoords` instead")]
+pub type RawTrxCoords = (Coord<u64>, Vec<Coord<u64>>, Option<Coord<u64>>);
 
 /// Fallback identifier for when a string value is required.
 const DEF_ID: &'static str = "<unknown>";
@@ -106,6 +426,8 @@ const INIT_COORD: (u64, u64) = (INIT_START, INIT_END);
 mod utils {
     use std::ops::Deref;
 
+    use Attributes;
+
     // taken from: https://stackoverflow.com/q/31233938/243058
     /// Helper trait for dereferencing wrapped option values.
     ///
@@ -144,4 +466,90 @@ mod utils {
             }
         }
     }
+
+    /// Helper function for writing strings as JSON strings.
+    pub(crate) fn json_str(value: &str) -> String {
+        let mut res = String::with_capacity(value.len() + 2);
+        res.push('"');
+        for c in value.chars() {
+            match c {
+                '"' => res.push_str("\\\""),
+                '\\' => res.push_str("\\\\"),
+                '\n' => res.push_str("\\n"),
+                '\r' => res.push_str("\\r"),
+                '\t' => res.push_str("\\t"),
+                c if (c as u32) < 0x20 => res.push_str(&format!("\\u{:04x}", c as u32)),
+                c => res.push(c),
+            }
+        }
+        res.push('"');
+        res
+    }
+
+    /// Helper function for creating a human-readable description from selected attributes.
+    ///
+    /// Each present attribute is written as `key=value`, with multiple values separated by
+    /// commas, and the attributes are separated by `"; "`. Tabs and newlines are replaced with
+    /// spaces so that the description can be used in a single column.
+    pub(crate) fn describe_attributes(
+        attributes: &Attributes,
+        keys: &[String],
+    ) -> String {
+        keys.iter()
+            .filter_map(|key| attributes.get_vec(key.as_str())
+                        .map(|values| format!("{}={}", key, values.join(","))))
+            .collect::<Vec<String>>()
+            .join("; ")
+            .replace(|c: char| c == '\t' || c == '\n' || c == '\r', " ")
+    }
+
+    /// Small, seedable pseudo-random number generator.
+    ///
+    /// This is the SplitMix64 generator, which is fast and has no issues with any seed value. It
+    /// is implemented here so that the same seed results in the same values across versions.
+    pub(crate) struct SplitMix64 {
+        state: u64,
+    }
+
+    impl SplitMix64 {
+
+        pub(crate) fn new(seed: u64) -> Self {
+            SplitMix64 { state: seed }
+        }
+
+        pub(crate) fn next_u64(&mut self) -> u64 {
+            self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = self.state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^ (z >> 31)
+        }
+
+        /// Returns a uniformly distributed value in `[0, n)`, for a nonzero `n`.
+        pub(crate) fn below(&mut self, n: u64) -> u64 {
+            // reject values from the incomplete last block to avoid modulo bias
+            let zone = ::std::u64::MAX - (::std::u64::MAX % n);
+            loop {
+                let value = self.next_u64();
+                if value < zone {
+                    return value % n;
+                }
+            }
+        }
+
+        /// Returns a uniformly distributed value in `[lo, hi]`, for `lo <= hi`.
+        pub(crate) fn between(&mut self, lo: u64, hi: u64) -> u64 {
+            match (hi - lo).checked_add(1) {
+                Some(n) => lo + self.below(n),
+                // the range covers all u64 values
+                None => self.next_u64(),
+            }
+        }
+
+        /// Returns `true` with the given probability.
+        pub(crate) fn chance(&mut self, probability: f64) -> bool {
+            // 53 bits is the precision of f64, so all values in [0, 1) can be represented
+            ((self.next_u64() >> 11) as f64) < probability * (1u64 << 53) as f64
+        }
+    }
 }
@@ -0,0 +1,574 @@
+/*! Coordinate remapping between assemblies using UCSC chain files.
+
+The chain format describes pairwise alignments between a source (reference) assembly and a
+target (query) assembly. A description of the format can be found on
+[this page](https://genome.ucsc.edu/goldenPath/help/chain.html).
+
+Intervals, exons, transcripts, and genes can be mapped. Features that do not map cleanly onto a
+single chain are rejected with the reason as an `Unmapped` value. Genes are mapped transcript by
+transcript, so that a gene whose transcripts end up in different places is split: the mapped
+gene keeps the transcripts in the most common place, while the others are reported.
+*/
+use std::cmp::{max, min};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+use std::str::FromStr;
+
+use bio::utils::Interval;
+
+use {Coord, EBuilder, Exon, ExonFeature, GBuilder, Gene, Strand, StrandExt, TBuilder,
+     Transcript};
+
+
+quick_error! {
+    /// Errors that occur when reading chain files.
+    #[derive(Debug)]
+    pub enum LiftoverError {
+        /// Occurs when a chain header line can not be parsed.
+        InvalidChainHeader(line_num: usize) {
+            description("invalid chain header line")
+            display(self_) -> ("{}, line: {}", self_.description(), line_num)
+        }
+        /// Occurs when an alignment data line can not be parsed.
+        InvalidChainBlock(line_num: usize) {
+            description("invalid chain alignment data line")
+            display(self_) -> ("{}, line: {}", self_.description(), line_num)
+        }
+        /// Errors propagated from reading the underlying file.
+        Io(err: io::Error) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+    }
+}
+
+quick_error! {
+    /// Reasons for a feature not being mapped to the target assembly.
+    #[derive(Debug)]
+    pub enum Unmapped {
+        /// The feature does not overlap any aligned block.
+        Deleted {
+            description("deleted in new assembly")
+        }
+        /// Some bases of the feature do not overlap any aligned block.
+        PartiallyDeleted {
+            description("partially deleted in new assembly")
+        }
+        /// The feature overlaps blocks from more than one chain, none of which covers all of
+        /// its bases, or the transcript maps away from the rest of its gene.
+        Split {
+            description("split in new assembly")
+        }
+        /// Parts of the feature map to different chains, sequences, or strands.
+        Inconsistent {
+            description("parts of feature map inconsistently in new assembly")
+        }
+        /// None of the transcripts of the gene can be mapped.
+        NoTranscripts {
+            description("no transcript mapped to new assembly")
+        }
+        /// The mapped coordinates do not form a valid model.
+        Invalid(err: ::Error) {
+            description("mapped coordinates form an invalid model")
+            display(self_) -> ("{}: {}", self_.description(), err)
+        }
+    }
+}
+
+/// Interval mapped to the target assembly.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiftedInterval {
+    seq_name: String,
+    start: u64,
+    end: u64,
+    reverse: bool,
+    chain_idx: usize,
+}
+
+impl LiftedInterval {
+
+    /// Returns the target sequence name.
+    pub fn seq_name(&self) -> &str {
+        self.seq_name.as_str()
+    }
+
+    /// Returns the genome-wise 5'-most target coordinate.
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// Returns the genome-wise 3'-most target coordinate.
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// Returns whether the interval was mapped onto the opposite strand.
+    pub fn is_reversed(&self) -> bool {
+        self.reverse
+    }
+}
+
+/// Ungapped alignment block of a chain.
+#[derive(Debug)]
+struct ChainBlock {
+    t_start: u64,
+    t_end: u64,
+    q_name: String,
+    q_start: u64,
+    q_size: u64,
+    reverse: bool,
+    chain_idx: usize,
+}
+
+impl ChainBlock {
+
+    /// Maps the given source coordinate, which must lie in the block, onto the target assembly.
+    #[inline]
+    fn map_pos(&self, pos: u64) -> u64 {
+        let offset = self.q_start + (pos - self.t_start);
+        if self.reverse {
+            self.q_size - offset - 1
+        } else {
+            offset
+        }
+    }
+}
+
+/// Aligned blocks of a single source sequence.
+#[derive(Debug, Default)]
+struct SeqBlocks {
+    blocks: Vec<ChainBlock>,
+    // running maximum of block end coordinates, used to bound overlap queries
+    max_ends: Vec<u64>,
+}
+
+impl SeqBlocks {
+
+    /// Returns all blocks overlapping the given interval, sorted by their start coordinates.
+    fn overlapping(&self, start: u64, end: u64) -> Vec<&ChainBlock> {
+        let (mut lo, mut hi) = (0, self.blocks.len());
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if self.blocks[mid].t_start < end {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        let mut res = Vec::new();
+        let mut idx = lo;
+        while idx > 0 {
+            idx -= 1;
+            if self.max_ends[idx] <= start {
+                break;
+            }
+            let block = &self.blocks[idx];
+            if block.t_end > start {
+                res.push(block);
+            }
+        }
+        res.reverse();
+        res
+    }
+}
+
+/// Coordinate map between two assemblies created from a chain file.
+#[derive(Debug, Default)]
+pub struct ChainMap {
+    seqs: HashMap<String, SeqBlocks>,
+}
+
+impl ChainMap {
+
+    /// Creates a chain map from the given chain file reader.
+    pub fn from_reader<R: io::Read>(in_reader: R) -> ::Result<ChainMap> {
+        let mut seqs: HashMap<String, SeqBlocks> = HashMap::new();
+        // current source sequence, source and target positions, target name, size, and strand
+        let mut state: Option<(String, u64, u64, String, u64, bool)> = None;
+        let mut chain_idx = 0;
+
+        for (idx, line) in BufReader::new(in_reader).lines().enumerate() {
+            let line_num = idx + 1;
+            let line = line.map_err(|e| ::Error::from(LiftoverError::from(e)))?;
+            let fields = line.split_whitespace().collect::<Vec<&str>>();
+            if fields.is_empty() || fields[0].starts_with('#') {
+                continue;
+            }
+            if fields[0] == "chain" {
+                state = Some(parse_chain_header(&fields)
+                    .ok_or(::Error::from(LiftoverError::InvalidChainHeader(line_num)))?);
+                chain_idx += 1;
+                continue;
+            }
+
+            let invalid_block = || ::Error::from(LiftoverError::InvalidChainBlock(line_num));
+            let nums = fields.iter()
+                .map(|f| u64::from_str(f))
+                .collect::<Result<Vec<u64>, _>>()
+                .map_err(|_| invalid_block())?;
+            let (t_name, t_pos, q_pos, q_name, q_size, reverse) = state.take()
+                .ok_or_else(|| invalid_block())?;
+            let (size, dt, dq) = match nums.len() {
+                3 => (nums[0], nums[1], nums[2]),
+                1 => (nums[0], 0, 0),
+                _ => return Err(invalid_block()),
+            };
+            seqs.entry(t_name.clone()).or_insert_with(SeqBlocks::default).blocks
+                .push(ChainBlock {
+                    t_start: t_pos,
+                    t_end: t_pos + size,
+                    q_name: q_name.clone(),
+                    q_start: q_pos,
+                    q_size: q_size,
+                    reverse: reverse,
+                    chain_idx: chain_idx,
+                });
+            if nums.len() == 3 {
+                state = Some((t_name, t_pos + size + dt, q_pos + size + dq,
+                              q_name, q_size, reverse));
+            }
+        }
+
+        for seq_blocks in seqs.values_mut() {
+            seq_blocks.blocks.sort_by_key(|b| (b.t_start, b.t_end));
+            let mut cur_max = 0;
+            seq_blocks.max_ends = seq_blocks.blocks.iter()
+                .map(|b| { cur_max = max(cur_max, b.t_end); cur_max })
+                .collect();
+        }
+
+        Ok(ChainMap { seqs: seqs })
+    }
+
+    /// Creates a chain map from the chain file in the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> ::Result<ChainMap> {
+        let file = fs::File::open(path)
+            .map_err(|e| ::Error::from(LiftoverError::from(e)))?;
+        ChainMap::from_reader(file)
+    }
+
+    /// Maps the given source interval onto the target assembly.
+    ///
+    /// An interval is mapped only when all of its bases are covered by aligned blocks of a
+    /// single chain. When more than one chain covers the interval, the chain listed first in
+    /// the chain file is used, which is the highest-scoring chain in UCSC chain files.
+    pub fn lift_interval(&self, seq_name: &str, start: u64, end: u64)
+        -> Result<LiftedInterval, Unmapped>
+    {
+        self.lift_interval_on(seq_name, start, end, None)
+    }
+
+    /// Maps the given source interval onto the target assembly, using the given chain if it
+    /// covers the interval.
+    fn lift_interval_on(&self, seq_name: &str, start: u64, end: u64,
+                        preferred_chain: Option<usize>) -> Result<LiftedInterval, Unmapped>
+    {
+        if start >= end {
+            return Err(Unmapped::Deleted);
+        }
+        let blocks = self.seqs.get(seq_name)
+            .map(|sb| sb.overlapping(start, end))
+            .unwrap_or_else(Vec::new);
+        if blocks.is_empty() {
+            return Err(Unmapped::Deleted);
+        }
+        let mut chains = blocks.iter().map(|b| b.chain_idx).collect::<Vec<usize>>();
+        chains.sort();
+        chains.dedup();
+        if let Some(pos) = preferred_chain.and_then(|pc| chains.iter().position(|&c| c == pc)) {
+            let preferred = chains.remove(pos);
+            chains.insert(0, preferred);
+        }
+        for &chain_idx in chains.iter() {
+            let chain_blocks = blocks.iter()
+                .cloned()
+                .filter(|b| b.chain_idx == chain_idx)
+                .collect::<Vec<&ChainBlock>>();
+            let covered = chain_blocks.iter()
+                .map(|b| min(b.t_end, end) - max(b.t_start, start))
+                .fold(0, |acc, x| acc + x);
+            if covered == end - start {
+                return Ok(lift_on_blocks(&chain_blocks, start, end));
+            }
+        }
+        if chains.len() > 1 {
+            Err(Unmapped::Split)
+        } else {
+            Err(Unmapped::PartiallyDeleted)
+        }
+    }
+
+    /// Maps the given transcript onto the target assembly.
+    ///
+    /// All exons and the coding region boundaries of the transcript must map onto the same
+    /// chain. The features of the mapped transcript are inferred again from the mapped exon and
+    /// coding coordinates.
+    pub fn lift_transcript(&self, transcript: &Transcript) -> Result<Transcript, Unmapped> {
+        let seq_name = transcript.seq_name();
+
+        // exons after the first prefer the chain of the first exon, so that transcripts covered
+        // by more than one chain map consistently
+        let mut lifted_exons: Vec<LiftedInterval> =
+            Vec::with_capacity(transcript.exons().len());
+        for exon in transcript.exons() {
+            let preferred = lifted_exons.first().map(|li| li.chain_idx);
+            lifted_exons.push(
+                self.lift_interval_on(seq_name, exon.start(), exon.end(), preferred)?);
+        }
+
+        let anchor = match lifted_exons.first() {
+            Some(lexn) => lexn.clone(),
+            None => self.lift_interval(seq_name, transcript.start(), transcript.end())?,
+        };
+        let same_chain = |li: &LiftedInterval| li.chain_idx == anchor.chain_idx;
+        if !lifted_exons.iter().all(&same_chain) {
+            return Err(Unmapped::Inconsistent);
+        }
+
+        let coding_coord = match transcript.coding_coord(true) {
+            None => None,
+            Some((start, end)) => {
+                let preferred = Some(anchor.chain_idx);
+                let lstart = self.lift_interval_on(seq_name, start, start + 1, preferred)?;
+                let lend = self.lift_interval_on(seq_name, end - 1, end, preferred)?;
+                if !same_chain(&lstart) || !same_chain(&lend) {
+                    return Err(Unmapped::Inconsistent);
+                }
+                Some((min(lstart.start, lend.start), max(lstart.end, lend.end)))
+            },
+        };
+
+        let exon_coords = lifted_exons.iter()
+            .map(|li| (li.start, li.end))
+            .collect::<Vec<Coord<u64>>>();
+        let (trx_start, trx_end) = exon_coords.iter()
+            .fold((anchor.start, anchor.end), |acc, c| (min(acc.0, c.0), max(acc.1, c.1)));
+
+        let mut builder = TBuilder::new(anchor.seq_name.as_str(), trx_start, trx_end)
+            .strand(lifted_strand(transcript.strand(), anchor.reverse))
+            .attributes(transcript.attributes().clone())
+            .coding_incl_stop(true);
+        if !exon_coords.is_empty() {
+            builder = builder.coords(exon_coords, coding_coord);
+        }
+        if let Some(tid) = transcript.id() {
+            builder = builder.id(tid);
+        }
+        if let Some(gid) = transcript.gene_id() {
+            builder = builder.gene_id(gid);
+        }
+        if let Some(biotype) = transcript.biotype() {
+            builder = builder.biotype(biotype.clone());
+        }
+        builder.build().map_err(Unmapped::Invalid)
+    }
+
+    /// Maps the given exon and its features onto the target assembly.
+    ///
+    /// The exon and all of its features must map onto the same chain. The features keep their
+    /// kinds and are sorted by their mapped coordinates.
+    pub fn lift_exon(&self, exon: &Exon) -> Result<Exon, Unmapped> {
+        let seq_name = exon.seq_name();
+        let lifted = self.lift_interval(seq_name, exon.start(), exon.end())?;
+        let mut features = Vec::with_capacity(exon.features().len());
+        for fx in exon.features() {
+            let lfx = self.lift_interval_on(seq_name, fx.start(), fx.end(),
+                                            Some(lifted.chain_idx))?;
+            if lfx.chain_idx != lifted.chain_idx {
+                return Err(Unmapped::Inconsistent);
+            }
+            // lifted intervals are never empty
+            let interval = Interval::new(lfx.start..lfx.end).unwrap();
+            features.push(ExonFeature::new(interval, fx.kind().clone()));
+        }
+        features.sort_by_key(|fx| (fx.start(), fx.end()));
+
+        let mut builder = EBuilder::new(lifted.seq_name.as_str(), lifted.start, lifted.end)
+            .strand(lifted_strand(exon.strand(), lifted.reverse))
+            .attributes(exon.attributes().clone())
+            .features(features);
+        if let Some(id) = exon.id() {
+            builder = builder.id(id);
+        }
+        if let Some(tid) = exon.transcript_id() {
+            builder = builder.transcript_id(tid);
+        }
+        if let Some(gid) = exon.gene_id() {
+            builder = builder.gene_id(gid);
+        }
+        builder.build().map_err(Unmapped::Invalid)
+    }
+
+    /// Maps the given gene onto the target assembly, transcript by transcript.
+    ///
+    /// Each transcript is mapped with `lift_transcript`. The mapped gene contains the mapped
+    /// transcripts on the target sequence and strand shared by most of them, preferring the
+    /// place of the earliest transcript on ties, and spans exactly these transcripts. Mapped
+    /// transcripts elsewhere are reported as `Unmapped::Split`. Genes without transcripts are
+    /// mapped as intervals.
+    pub fn lift_gene(&self, gene: &Gene) -> LiftedGene {
+        let mut lifted = Vec::new();
+        let mut unmapped = Vec::new();
+        for (tid, transcript) in gene.transcripts() {
+            match self.lift_transcript(transcript) {
+                Ok(ltrx) => lifted.push((tid.clone(), ltrx)),
+                Err(reason) => unmapped.push((tid.clone(), reason)),
+            }
+        }
+
+        if gene.transcripts().is_empty() {
+            let lifted_gene = self.lift_interval(gene.seq_name(), gene.start(), gene.end())
+                .and_then(|li| {
+                    let strand = lifted_strand(gene.strand(), li.reverse);
+                    build_gene(gene, li.seq_name.as_str(), (li.start, li.end), strand, vec![])
+                });
+            return LiftedGene { gene: lifted_gene, unmapped_transcripts: unmapped };
+        }
+
+        let mut places: Vec<((String, char), usize)> = Vec::new();
+        for &(_, ref ltrx) in lifted.iter() {
+            let place = (ltrx.seq_name().to_owned(), ltrx.strand().to_char());
+            match places.iter().position(|&(ref p, _)| *p == place) {
+                Some(idx) => places[idx].1 += 1,
+                None => places.push((place, 1)),
+            }
+        }
+        let mut best: Option<&((String, char), usize)> = None;
+        for entry in places.iter() {
+            if best.map(|b| entry.1 > b.1).unwrap_or(true) {
+                best = Some(entry);
+            }
+        }
+        let (seq_name, strand_char) = match best.map(|entry| entry.0.clone()) {
+            Some(place) => place,
+            None => return LiftedGene {
+                gene: Err(Unmapped::NoTranscripts),
+                unmapped_transcripts: unmapped,
+            },
+        };
+
+        let mut kept = Vec::with_capacity(lifted.len());
+        for (tid, ltrx) in lifted {
+            if ltrx.seq_name() == seq_name && ltrx.strand().to_char() == strand_char {
+                kept.push(ltrx);
+            } else {
+                unmapped.push((tid, Unmapped::Split));
+            }
+        }
+        let span = kept.iter()
+            .fold((u64::max_value(), 0),
+                  |acc, trx| (min(acc.0, trx.start()), max(acc.1, trx.end())));
+        let strand = *kept[0].strand();
+        LiftedGene {
+            gene: build_gene(gene, seq_name.as_str(), span, strand, kept),
+            unmapped_transcripts: unmapped,
+        }
+    }
+}
+
+/// Gene mapped to the target assembly, along with its transcripts that could not be mapped.
+#[derive(Debug)]
+pub struct LiftedGene {
+    gene: Result<Gene, Unmapped>,
+    unmapped_transcripts: Vec<(String, Unmapped)>,
+}
+
+impl LiftedGene {
+
+    /// Returns the mapped gene, or the reason why it could not be mapped.
+    pub fn gene(&self) -> Result<&Gene, &Unmapped> {
+        self.gene.as_ref()
+    }
+
+    /// Returns the identifiers of the transcripts that could not be mapped, along with the
+    /// reasons, in the order of the source gene.
+    ///
+    /// Transcripts that were mapped away from the rest of the gene are listed last.
+    pub fn unmapped_transcripts(&self) -> &[(String, Unmapped)] {
+        self.unmapped_transcripts.as_slice()
+    }
+
+    /// Returns whether the gene and all of its transcripts were mapped.
+    pub fn is_complete(&self) -> bool {
+        self.gene.is_ok() && self.unmapped_transcripts.is_empty()
+    }
+
+    /// Consumes the result and returns the mapped gene, or the reason why it could not be
+    /// mapped.
+    pub fn into_gene(self) -> Result<Gene, Unmapped> {
+        self.gene
+    }
+}
+
+/// Returns the strand of a feature after mapping it through a chain.
+fn lifted_strand(strand: &Strand, reverse: bool) -> Strand {
+    match (strand, reverse) {
+        (&Strand::Forward, true) => Strand::Reverse,
+        (&Strand::Reverse, true) => Strand::Forward,
+        (&strand, _) => strand,
+    }
+}
+
+/// Maps the given interval through the given blocks of a single chain, which must cover all of
+/// its bases and be sorted by their start coordinates.
+fn lift_on_blocks(blocks: &[&ChainBlock], start: u64, end: u64) -> LiftedInterval {
+    let (first, last) = (blocks[0], blocks[blocks.len() - 1]);
+    let (mapped_first, mapped_last) = (first.map_pos(start), last.map_pos(end - 1));
+    let (lifted_start, lifted_end) =
+        if first.reverse {
+            (mapped_last, mapped_first + 1)
+        } else {
+            (mapped_first, mapped_last + 1)
+        };
+
+    LiftedInterval {
+        seq_name: first.q_name.clone(),
+        start: lifted_start,
+        end: lifted_end,
+        reverse: first.reverse,
+        chain_idx: first.chain_idx,
+    }
+}
+
+/// Builds a mapped gene with the identifier and attributes of the given source gene.
+fn build_gene(source: &Gene, seq_name: &str, span: Coord<u64>, strand: Strand,
+              transcripts: Vec<Transcript>) -> Result<Gene, Unmapped>
+{
+    let mut builder = GBuilder::new(seq_name, span.0, span.1)
+        .strand(strand)
+        .attributes(source.attributes().clone())
+        .add_transcripts(transcripts);
+    if let Some(gid) = source.id() {
+        builder = builder.id(gid);
+    }
+    if let Some(biotype) = source.biotype() {
+        builder = builder.biotype(biotype.clone());
+    }
+    builder.build().map_err(Unmapped::Invalid)
+}
+
+/// Parses the fields of a chain header line.
+///
+/// The returned tuple contains the source sequence name, the source and target start
+/// coordinates, the target sequence name, the target sequence size, and whether the target is
+/// on the reverse strand.
+fn parse_chain_header(fields: &[&str]) -> Option<(String, u64, u64, String, u64, bool)> {
+    if fields.len() < 12 {
+        return None;
+    }
+    let reverse = match fields[9] {
+        "+" => false,
+        "-" => true,
+        _ => return None,
+    };
+    match (u64::from_str(fields[5]), u64::from_str(fields[8]), u64::from_str(fields[10])) {
+        (Ok(t_start), Ok(q_size), Ok(q_start)) =>
+            Some((fields[2].to_owned(), t_start, q_start, fields[7].to_owned(), q_size, reverse)),
+        _ => None,
+    }
+}
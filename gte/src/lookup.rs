@@ -0,0 +1,250 @@
+/*! Quick lookup tables of gene locations by symbol.
+
+Lookup tables are tab-separated files with one gene per line, for tools that only need to
+resolve gene symbols into locations and should not parse a whole annotation on every run. The
+columns are:
+
+1. gene symbol
+2. sequence name
+3. start coordinate (zero-based)
+4. end coordinate (half-open)
+5. strand (`+`, `-`, or `.`)
+
+The first line is a column header starting with `#`. A symbol may appear on more than one line,
+for example for genes in the pseudoautosomal regions of the sex chromosomes.
+*/
+use std::error::Error;
+use std::fs;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+use std::str::FromStr;
+
+use linked_hash_map::LinkedHashMap;
+
+use {Gene, Strand};
+use io_gff::strand_to_char;
+
+
+quick_error! {
+    /// Errors that occur when reading or writing gene lookup tables.
+    #[derive(Debug)]
+    pub enum LookupError {
+        /// Occurs when a line does not contain the expected columns or values.
+        InvalidRecord(line_num: usize) {
+            description("invalid gene location record")
+            display(self_) -> ("{}, line: {}", self_.description(), line_num)
+        }
+        /// Errors propagated from reading or writing the underlying file.
+        Io(err: io::Error) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// Location of a gene in a lookup table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneLocation {
+    seq_name: String,
+    start: u64,
+    end: u64,
+    strand: Strand,
+}
+
+impl GeneLocation {
+
+    /// Returns the sequence name.
+    pub fn seq_name(&self) -> &str {
+        self.seq_name.as_str()
+    }
+
+    /// Returns the zero-based start coordinate.
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// Returns the end coordinate.
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// Returns the strand.
+    pub fn strand(&self) -> &Strand {
+        &self.strand
+    }
+}
+
+/// Gene locations keyed by symbol, as read from a lookup table.
+///
+/// Symbols are stored in the order they first appear.
+#[derive(Debug, Clone, Default)]
+pub struct GeneLocations {
+    locations: LinkedHashMap<String, Vec<GeneLocation>>,
+}
+
+impl GeneLocations {
+
+    /// Reads gene locations from the given lookup table reader.
+    ///
+    /// Empty lines and lines starting with `#` are ignored.
+    pub fn from_reader<R: io::Read>(in_reader: R) -> ::Result<GeneLocations> {
+        let mut locations = GeneLocations::default();
+        for (idx, line) in BufReader::new(in_reader).lines().enumerate() {
+            let line = line.map_err(|e| ::Error::from(LookupError::from(e)))?;
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (symbol, location) = parse_line(&line)
+                .ok_or(::Error::from(LookupError::InvalidRecord(idx + 1)))?;
+            locations.locations.entry(symbol.to_owned()).or_insert_with(Vec::new).push(location);
+        }
+        Ok(locations)
+    }
+
+    /// Reads gene locations from the lookup table in the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> ::Result<GeneLocations> {
+        let file = fs::File::open(path)
+            .map_err(|e| ::Error::from(LookupError::from(e)))?;
+        GeneLocations::from_reader(file)
+    }
+
+    /// Returns the locations of the given symbol, in the order they appear in the table.
+    ///
+    /// An empty slice is returned for unknown symbols.
+    pub fn get(&self, symbol: &str) -> &[GeneLocation] {
+        self.locations.get(symbol).map(|locs| locs.as_slice()).unwrap_or(&[])
+    }
+
+    /// Returns the symbols, in the order they first appear in the table.
+    pub fn symbols(&self) -> Vec<&str> {
+        self.locations.keys().map(|symbol| symbol.as_str()).collect()
+    }
+
+    /// Returns the number of symbols.
+    pub fn len(&self) -> usize {
+        self.locations.len()
+    }
+
+    /// Returns whether the table contains no symbols.
+    pub fn is_empty(&self) -> bool {
+        self.locations.is_empty()
+    }
+}
+
+/// Helper function for parsing a single lookup table line.
+fn parse_line(line: &str) -> Option<(&str, GeneLocation)> {
+    let cols = line.split('\t').collect::<Vec<&str>>();
+    if cols.len() != 5 || cols[0].is_empty() || cols[1].is_empty() {
+        return None;
+    }
+    let strand = cols[4].chars().next()
+        .and_then(|c| Strand::from_char(&c).ok())?;
+    match (u64::from_str(cols[2]), u64::from_str(cols[3])) {
+        (Ok(start), Ok(end)) if start <= end => Some((cols[0], GeneLocation {
+            seq_name: cols[1].to_owned(),
+            start: start,
+            end: end,
+            strand: strand,
+        })),
+        _ => None,
+    }
+}
+
+/// Gene lookup table writer.
+pub struct Writer<W: io::Write> {
+    inner: W,
+    symbol_attr: Option<String>,
+    header_written: bool,
+}
+
+impl<W: io::Write> Writer<W> {
+
+    /// Creates a gene lookup table writer from another writer.
+    pub fn from_writer(in_writer: W) -> Writer<W> {
+        Writer {
+            inner: in_writer,
+            symbol_attr: None,
+            header_written: false,
+        }
+    }
+
+    /// Sets the writer to take gene symbols from the given gene attribute, such as
+    /// `gene_name` for GENCODE annotations.
+    ///
+    /// Genes without the attribute fall back to their identifiers. By default, gene
+    /// identifiers are used as symbols, as in refFlat annotations.
+    pub fn symbol_attr<T>(&mut self, key: T) -> &mut Self
+        where T: Into<String>
+    {
+        self.symbol_attr = Some(key.into());
+        self
+    }
+
+    /// Writes the location of the given gene as a single line.
+    ///
+    /// The column header line is written before the first gene. Genes without a symbol are
+    /// skipped.
+    pub fn write_gene(&mut self, gene: &Gene) -> ::Result<()> {
+        self.write_header()?;
+        let symbol = self.symbol_attr.as_ref()
+            .and_then(|key| gene.attributes().get(key))
+            .map(|symbol| symbol.as_str())
+            .or_else(|| gene.id());
+        match symbol {
+            Some(symbol) =>
+                writeln!(self.inner, "{}\t{}\t{}\t{}\t{}",
+                         symbol, gene.seq_name(), gene.start(), gene.end(),
+                         strand_to_char(gene.strand()))
+                    .map_err(|e| ::Error::from(LookupError::from(e))),
+            None => Ok(()),
+        }
+    }
+
+    /// Writes the locations of the given genes, one per line.
+    pub fn write_genes<'a, I>(&mut self, genes: I) -> ::Result<()>
+        where I: IntoIterator<Item=&'a Gene>
+    {
+        self.write_header()?;
+        for gene in genes {
+            self.write_gene(gene)?;
+        }
+        Ok(())
+    }
+
+    /// Helper method for writing the column header line once.
+    fn write_header(&mut self) -> ::Result<()> {
+        if self.header_written {
+            return Ok(());
+        }
+        self.header_written = true;
+        writeln!(self.inner, "#symbol\tchrom\tstart\tend\tstrand")
+            .map_err(|e| ::Error::from(LookupError::from(e)))
+    }
+}
+
+impl Writer<fs::File> {
+
+    /// Creates a gene lookup table writer that writes to the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let f = fs::File::create(path)?;
+        Ok(Writer::from_writer(f))
+    }
+}
+
+impl Writer<Vec<u8>> {
+
+    /// Creates a gene lookup table writer that writes to an in-memory buffer.
+    ///
+    /// The initial capacity of the buffer is 64 KiB.
+    pub fn from_memory() -> Writer<Vec<u8>> {
+        Writer::from_writer(Vec::with_capacity(1024 * 64))
+    }
+
+    /// Returns the values of the in-memory buffer as a string.
+    pub fn as_string(&mut self) -> &str {
+        // the buffer only ever contains values written from `str`s
+        ::std::str::from_utf8(self.inner.as_slice()).expect("valid UTF-8 output")
+    }
+}
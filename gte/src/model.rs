@@ -1,21 +1,39 @@
 /*! Core gene, transcript, and exon models and builders.
 
+Sequence names are shared between models: all models on the same sequence that are created in
+the same thread point to a single copy of the sequence name.
+
+With the `serde` feature enabled, the models and feature kinds implement `Serialize` and
+`Deserialize`. Intervals are represented as `[start, end]` pairs, strands as their characters,
+attributes as maps of keys to lists of values in their stored order, and the transcripts of a
+gene as a list of `[id, transcript]` pairs in their original order. Deserialized models are not
+validated beyond their intervals, so they should only be read from trusted sources such as
+caches.
 */
 use std::cmp::{max, min};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::mem;
 use std::error::Error;
+use std::ops::Range;
+use std::sync::Arc;
 
 use bio::utils::{self as bio_utils, Interval, IntervalError};
 use bio::utils::Strand;
 use linked_hash_map::LinkedHashMap;
-use multimap::MultiMap;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-use {Coord, RawTrxCoords, DEF_ID, INIT_COORD};
+use {expand_window, Assembly, Attributes, Biotype, Coord, TranscriptTag, DEF_ID, INIT_COORD};
+use tags::{parse_support_level, SUPPORT_LEVEL_KEY, TAG_KEY};
 use utils::OptionDeref;
 
 use self::ExonFeatureKind::*;
 
 
+/// Minimum distance between a stop codon and the last exon-exon junction for NMD candidates.
+const NMD_JUNCTION_DISTANCE: u64 = 50;
+
 /// Macro for implementing methods shared by the gene, transcript, and exon models.
 macro_rules! impl_common {
     ($struct_ty:ty) => (
@@ -24,14 +42,14 @@ macro_rules! impl_common {
 
             /// Returns the sequence name.
             pub fn seq_name(&self) -> &str {
-                self.seq_name.as_str()
+                &self.seq_name
             }
 
             /// Sets the sequence name.
             pub fn set_seq_name<T>(&mut self, name: T)
                 where T: Into<String>
             {
-                self.seq_name = name.into()
+                self.seq_name = intern_seq_name(name.into())
             }
 
             /// Returns the identifier.
@@ -39,6 +57,11 @@ macro_rules! impl_common {
                 self.id.as_deref()
             }
 
+            /// Returns the identifier, or `"<unknown>"` if it is not set.
+            pub fn id_or_default(&self) -> &str {
+                self.id().unwrap_or(DEF_ID)
+            }
+
             /// Returns a reference of the strand.
             pub fn strand(&self) -> &Strand {
                 &self.strand
@@ -50,18 +73,18 @@ macro_rules! impl_common {
             }
 
             /// Returns a reference of the attributes.
-            pub fn attributes(&self) -> &MultiMap<String, String> {
+            pub fn attributes(&self) -> &Attributes {
                 &self.attributes
             }
 
             /// Returns a mutable reference of the attributes.
-            pub fn attributes_mut(&mut self) -> &mut MultiMap<String, String> {
+            pub fn attributes_mut(&mut self) -> &mut Attributes {
                 &mut self.attributes
             }
 
             /// Sets the attribute and returns the old value.
-            pub fn set_attributes(&mut self, attributes: MultiMap<String, String>)
-                -> MultiMap<String, String>
+            pub fn set_attributes(&mut self, attributes: Attributes)
+                -> Attributes
             {
                 mem::replace(&mut self.attributes, attributes)
             }
@@ -93,7 +116,9 @@ macro_rules! impl_common {
 
 /// Genomic feature spanning an interval.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Feature<K: FeatureKind> {
+    #[cfg_attr(feature = "serde", serde(with = "serde_impls::interval"))]
     interval: Interval<u64>,
     kind: K,
 }
@@ -140,6 +165,7 @@ pub trait FeatureKind {}
 
 /// Possible feature kinds for exons.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ExonFeatureKind {
     /// UTR on unknown strands.
     UTR,
@@ -182,6 +208,7 @@ pub type ExonFeature = Feature<ExonFeatureKind>;
 
 /// Possible feature kinds for transcripts.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum TranscriptFeatureKind {
     /// An intron.
     Intron,
@@ -196,6 +223,7 @@ pub type TranscriptFeature = Feature<TranscriptFeatureKind>;
 
 /// Feature kind for genes.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct GeneFeatureKind(String);
 
 impl FeatureKind for GeneFeatureKind {}
@@ -207,14 +235,19 @@ pub type GeneFeature = Feature<GeneFeatureKind>;
 ///
 /// To create an exon, an `EBuilder` needs to be used.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Exon {
-    seq_name: String,
+    #[cfg_attr(feature = "serde", serde(with = "serde_impls::seq_name"))]
+    seq_name: Arc<str>,
+    #[cfg_attr(feature = "serde", serde(with = "serde_impls::interval"))]
     interval: Interval<u64>,
+    #[cfg_attr(feature = "serde", serde(with = "::strand::serde_strand"))]
     strand: Strand,
     id: Option<String>,
     gene_id: Option<String>,
     transcript_id: Option<String>,
-    attributes: MultiMap<String, String>,
+    #[cfg_attr(feature = "serde", serde(with = "serde_impls::attributes"))]
+    attributes: Attributes,
     features: Vec<ExonFeature>,
 }
 
@@ -234,6 +267,11 @@ impl Exon {
         self.transcript_id.as_deref()
     }
 
+    /// Returns the identifier of the transcript of the exon, or `"<unknown>"` if it is not set.
+    pub fn transcript_id_or_default(&self) -> &str {
+        self.transcript_id().unwrap_or(DEF_ID)
+    }
+
     /// Sets the transcript identifier of the exon.
     pub fn set_transcript_id<T>(&mut self, transcript_id: Option<T>)
         where T: Into<String>
@@ -246,6 +284,11 @@ impl Exon {
         self.gene_id.as_deref()
     }
 
+    /// Returns the identifier of the gene of the exon, or `"<unknown>"` if it is not set.
+    pub fn gene_id_or_default(&self) -> &str {
+        self.gene_id().unwrap_or(DEF_ID)
+    }
+
     /// Sets the gene identifier of the exon.
     pub fn set_gene_id<T>(&mut self, gene_id: Option<T>)
         where T: Into<String>
@@ -290,15 +333,16 @@ impl Exon {
 /// via its `build` function.
 #[derive(Debug)]
 pub struct EBuilder {
-    seq_name: String,
+    seq_name: Arc<str>,
     start: u64,
     end: u64,
+    interval: Option<Interval<u64>>,
     strand: Option<Strand>,
     strand_char: Option<char>,
     id: Option<String>,
     transcript_id: Option<String>,
     gene_id: Option<String>,
-    attributes: MultiMap<String, String>,
+    attributes: Attributes,
     features: Vec<ExonFeature>,
 }
 
@@ -309,19 +353,31 @@ impl EBuilder {
         where T: Into<String>
     {
         EBuilder {
-            seq_name: seq_name.into(),
+            seq_name: intern_seq_name(seq_name.into()),
             start: start,
             end: end,
+            interval: None,
             strand: None,
             strand_char: None,
             id: None,
             transcript_id: None,
             gene_id: None,
-            attributes: MultiMap::new(),
+            attributes: Attributes::new(),
             features: Vec::new(),
         }
     }
 
+    /// Creates a new builder for an exon with the given interval.
+    ///
+    /// The interval is used as it is, without being validated again by the `build` method.
+    pub fn from_interval<T>(seq_name: T, interval: Interval<u64>) -> Self
+        where T: Into<String>
+    {
+        let mut builder = EBuilder::new(seq_name, interval.start, interval.end);
+        builder.interval = Some(interval);
+        builder
+    }
+
     /// Sets the strand of the exon.
     pub fn strand(mut self, strand: Strand) -> Self {
         self.strand = Some(strand);
@@ -378,8 +434,8 @@ impl EBuilder {
     }
 
     /// Sets the entire attribute of the exon.
-    pub fn attributes(mut self, attributes: MultiMap<String, String>) -> Self {
-        self.attributes = attributes;
+    pub fn attributes<T: Into<Attributes>>(mut self, attributes: T) -> Self {
+        self.attributes = attributes.into();
         self
     }
 
@@ -397,8 +453,10 @@ impl EBuilder {
 
     /// Validates the input data and builds the exon.
     pub fn build(self) -> ::Result<Exon> {
-        let interval = coord_to_interval(self.start, self.end)
-            .map_err(::Error::Model)?;
+        let interval = match self.interval {
+            Some(interval) => interval,
+            None => coord_to_interval(self.start, self.end).map_err(::Error::Model)?,
+        };
         let strand = resolve_strand_input(self.strand, self.strand_char)
             .map_err(::Error::Model)?;
         let feature = Exon {
@@ -419,14 +477,25 @@ impl EBuilder {
 ///
 /// To create a transcript, a `TBuilder` needs to be used.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Transcript {
-    seq_name: String,
+    #[cfg_attr(feature = "serde", serde(with = "serde_impls::seq_name"))]
+    seq_name: Arc<str>,
+    #[cfg_attr(feature = "serde", serde(with = "serde_impls::interval"))]
     interval: Interval<u64>,
+    #[cfg_attr(feature = "serde", serde(with = "::strand::serde_strand"))]
     strand: Strand,
     id: Option<String>,
     gene_id: Option<String>,
-    attributes: MultiMap<String, String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    biotype: Option<Biotype>,
+    #[cfg_attr(feature = "serde", serde(with = "serde_impls::attributes"))]
+    attributes: Attributes,
     exons: Vec<Exon>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    cds_5_incomplete: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    cds_3_incomplete: bool,
 }
 
 impl_common!(Transcript);
@@ -445,11 +514,30 @@ impl Transcript {
         self.id = id.map(|id| id.into())
     }
 
+    /// Sets the sequence name of the transcript and all of its exons.
+    pub fn rename_seq<T>(&mut self, name: T)
+        where T: Into<String>
+    {
+        self.rename_seq_shared(intern_seq_name(name.into()))
+    }
+
+    fn rename_seq_shared(&mut self, seq_name: Arc<str>) {
+        for exon in self.exons.iter_mut() {
+            exon.seq_name = seq_name.clone();
+        }
+        self.seq_name = seq_name;
+    }
+
     /// Returns the gene identifier of the transcript.
     pub fn gene_id(&self) -> Option<&str> {
         self.gene_id.as_deref()
     }
 
+    /// Returns the gene identifier of the transcript, or `"<unknown>"` if it is not set.
+    pub fn gene_id_or_default(&self) -> &str {
+        self.gene_id().unwrap_or(DEF_ID)
+    }
+
     /// Sets the gene identifier of the transcript.
     pub fn set_gene_id<T>(&mut self, gene_id: Option<T>)
         where T: Into<String> + Clone
@@ -460,16 +548,486 @@ impl Transcript {
         self.gene_id = gene_id.map(|v| v.into())
     }
 
+    /// Returns the biotype of the transcript.
+    pub fn biotype(&self) -> Option<&Biotype> {
+        self.biotype.as_ref()
+    }
+
+    /// Sets the biotype of the transcript.
+    pub fn set_biotype(&mut self, biotype: Option<Biotype>) {
+        self.biotype = biotype
+    }
+
+    /// Returns the tags of the transcript, from its `tag` attributes.
+    pub fn tags(&self) -> Vec<TranscriptTag> {
+        self.attributes.get_vec(TAG_KEY)
+            .map(|tags| tags.iter().map(|tag| TranscriptTag::from(tag.as_str())).collect())
+            .unwrap_or_else(Vec::new)
+    }
+
+    /// Returns whether the transcript has the given tag.
+    pub fn has_tag(&self, tag: &TranscriptTag) -> bool {
+        self.attributes.get_vec(TAG_KEY)
+            .map(|tags| tags.iter().any(|t| t == tag.as_str()))
+            .unwrap_or(false)
+    }
+
+    /// Returns the support level of the transcript, from its `transcript_support_level`
+    /// attribute.
+    ///
+    /// The level ranges from 1, for the best supported transcripts, to 5. `None` is returned
+    /// when the attribute is missing or is `NA`.
+    pub fn support_level(&self) -> Option<u8> {
+        self.attributes.get(SUPPORT_LEVEL_KEY)
+            .and_then(|raw| parse_support_level(raw))
+    }
+
     /// Returns a slice of the transcript's exons.
     pub fn exons(&self) -> &[Exon] {
         self.exons.as_slice()
     }
 
+    /// Returns a mutable slice of the transcript's exons.
+    pub(crate) fn exons_mut(&mut self) -> &mut [Exon] {
+        self.exons.as_mut_slice()
+    }
+
     /// Consumes the transcript and returns its exons.
     pub fn take_exons(self) -> Vec<Exon> {
         self.exons
     }
 
+    /// Creates a transcript containing only the exons in the given index range.
+    ///
+    /// The exon indices follow the genome-wise order of `exons()`. The interval of the new
+    /// transcript spans the selected exons, and its coding region is the part of the original
+    /// coding region contained in them, so start and stop codons are only kept when their exons
+    /// are selected. The CDS frames are recomputed in the transcript-wise order, starting from
+    /// the frame of the first selected CDS.
+    ///
+    /// An error is returned if the range is empty or exceeds the number of exons.
+    pub fn subrange_exons(&self, range: Range<usize>) -> ::Result<Transcript> {
+        if range.start >= range.end || range.end > self.exons.len() {
+            let tid = self.id.clone();
+            return Err(::Error::from(ModelError::InvalidExonRange(tid)));
+        }
+        let mut exons = self.exons[range].to_vec();
+        let interval = Interval::new(exons[0].start()..exons[exons.len() - 1].end())
+            .map_err(ModelError::from)?;
+
+        let mut idxs = (0..exons.len()).collect::<Vec<usize>>();
+        if self.strand == Strand::Reverse {
+            idxs.reverse();
+        }
+        let mut frame: Option<u8> = None;
+        for idx in idxs {
+            for fx in exons[idx].features.iter_mut() {
+                let span = fx.span();
+                if let CDS { frame: ref mut cds_frame } = fx.kind {
+                    match frame {
+                        None => frame = *cds_frame,
+                        Some(_) => *cds_frame = frame,
+                    }
+                    // frame of the next CDS, from the number of bases left after this one
+                    frame = frame.map(|f| ((3 - (span + 3 - u64::from(f)) % 3) % 3) as u8);
+                }
+            }
+        }
+
+        Ok(Transcript {
+            seq_name: self.seq_name.clone(),
+            interval: interval,
+            strand: self.strand,
+            id: self.id.clone(),
+            gene_id: self.gene_id.clone(),
+            biotype: self.biotype.clone(),
+            attributes: self.attributes.clone(),
+            exons: exons,
+            cds_5_incomplete: self.cds_5_incomplete,
+            cds_3_incomplete: self.cds_3_incomplete,
+        })
+    }
+
+    /// Adds the given exon to the transcript, keeping the exons sorted by their coordinates.
+    ///
+    /// The transcript interval is extended to cover the exon, which takes the transcript and
+    /// gene identifiers of the transcript. When `update_features` is `true`, the features of all
+    /// exons are derived again from the current coding region, including the stop codon, the
+    /// same way `TBuilder` derives them from coordinates. Otherwise, the features of the exon
+    /// are kept as they are.
+    ///
+    /// An error is returned if the sequence name or strand of the exon differs from the
+    /// transcript, if the exon overlaps an existing exon, or if the features can not be
+    /// derived. The transcript is left unchanged on errors.
+    pub fn add_exon(&mut self, mut exon: Exon, update_features: bool) -> ::Result<()> {
+        if exon.seq_name() != self.seq_name() || exon.strand() != &self.strand {
+            return Err(::Error::from(ModelError::UnmatchedExon(self.id.clone())));
+        }
+        if self.exons.iter().any(|ex| ex.start() < exon.end() && exon.start() < ex.end()) {
+            return Err(::Error::from(ModelError::OverlappingExon(self.id.clone())));
+        }
+        let idx = self.exons.iter()
+            .position(|ex| ex.start() > exon.start())
+            .unwrap_or(self.exons.len());
+        let mut coords = self.exons.iter()
+            .map(|ex| (ex.start(), ex.end()))
+            .collect::<Vec<Coord<u64>>>();
+        coords.insert(idx, (exon.start(), exon.end()));
+        let features =
+            if update_features { Some(self.derive_features(&coords)?) } else { None };
+
+        exon.seq_name = self.seq_name.clone();
+        exon.set_transcript_id(self.id.clone());
+        exon.set_gene_id(self.gene_id.clone());
+        self.exons.insert(idx, exon);
+        self.interval = Interval::new(coords[0].0..coords[coords.len() - 1].1).unwrap();
+        if let Some(features) = features {
+            for (exon, fxs) in self.exons.iter_mut().zip(features.into_iter()) {
+                exon.features = fxs;
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the exon at the given index from the transcript and returns it.
+    ///
+    /// The index follows the genome-wise order of `exons()`. The transcript interval is shrunk
+    /// to its remaining exons. When `update_features` is `true`, the features of the remaining
+    /// exons are derived again as in `add_exon`, from the part of the coding region that
+    /// remains; the transcript becomes noncoding if the removed exon contained its start or
+    /// stop codon.
+    ///
+    /// An error is returned if the index exceeds the number of exons, if the exon is the only
+    /// exon of the transcript, or if the features can not be derived. The transcript is left
+    /// unchanged on errors.
+    pub fn remove_exon(&mut self, idx: usize, update_features: bool) -> ::Result<Exon> {
+        if idx >= self.exons.len() || self.exons.len() == 1 {
+            return Err(::Error::from(ModelError::InvalidExonRange(self.id.clone())));
+        }
+        let mut coords = self.exons.iter()
+            .map(|ex| (ex.start(), ex.end()))
+            .collect::<Vec<Coord<u64>>>();
+        let _ = coords.remove(idx);
+        let features = if update_features {
+            let mut remaining = self.clone();
+            let _ = remaining.exons.remove(idx);
+            Some(remaining.derive_features(&coords)?)
+        } else {
+            None
+        };
+
+        let removed = self.exons.remove(idx);
+        self.interval = Interval::new(coords[0].0..coords[coords.len() - 1].1).unwrap();
+        if let Some(features) = features {
+            for (exon, fxs) in self.exons.iter_mut().zip(features.into_iter()) {
+                exon.features = fxs;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Helper method for deriving the features of the given sorted exon coordinates from the
+    /// current coding region of the transcript.
+    fn derive_features(&self, coords: &Vec<Coord<u64>>) -> ::Result<Vec<Vec<ExonFeature>>> {
+        let interval = coord_to_interval(coords[0].0, coords[coords.len() - 1].1)?;
+        let exons = infer_exons(&self.seq_name, &interval, &self.strand, self.id.as_deref(),
+                                self.gene_id.as_deref(), coords,
+                                self.coding_coord(true), true,
+                                (self.cds_5_incomplete, self.cds_3_incomplete),
+                                CodonTruncation::Never,
+                                ValidationMode::Strict)?;
+        Ok(exons.into_iter().map(|exon| exon.features).collect())
+    }
+
+    /// Returns the genome-wise coordinates of the introns of the transcript.
+    ///
+    /// Introns are the gaps between consecutive exons, so abutting exons do not create an
+    /// intron.
+    pub fn intron_coords(&self) -> Vec<Coord<u64>> {
+        self.exons.iter().zip(self.exons.iter().skip(1))
+            .filter(|&(prev, next)| prev.end() < next.start())
+            .map(|(prev, next)| (prev.end(), next.start()))
+            .collect()
+    }
+
+    /// Returns whether the given aligned blocks, such as those of a spliced read, are consistent
+    /// with the exon structure of the transcript.
+    ///
+    /// This is equivalent to `is_compatible_with_tolerance(blocks, 0)`.
+    pub fn is_compatible_with(&self, blocks: &[Coord<u64>]) -> bool {
+        self.is_compatible_with_tolerance(blocks, 0)
+    }
+
+    /// Returns whether the given aligned blocks are consistent with the exon structure of the
+    /// transcript, allowing the outer ends of the blocks to extend past their exons.
+    ///
+    /// The blocks must be sorted, non-overlapping, and are compatible when:
+    ///
+    /// * Every gap between consecutive blocks is exactly one of the transcript's introns, and
+    ///   these introns are consecutive in the transcript.
+    /// * The first block starts at most `end_tolerance` bases before the start of its exon, and
+    ///   the last block ends at most `end_tolerance` bases after the end of its exon.
+    ///
+    /// Abutting blocks and abutting exons are treated as single blocks and exons.
+    pub fn is_compatible_with_tolerance(&self, blocks: &[Coord<u64>], end_tolerance: u64)
+        -> bool
+    {
+        let segments = match merge_abutting(blocks.iter().cloned()) {
+            Some(segments) => segments,
+            None => return false,
+        };
+        let exons = merge_abutting(self.exons.iter().map(|exon| (exon.start(), exon.end())))
+            .unwrap_or_else(Vec::new);
+        if segments.is_empty() {
+            return false;
+        }
+        let (first, last) = (segments[0], segments[segments.len() - 1]);
+
+        if segments.len() == 1 {
+            return exons.iter().any(|&(start, end)| {
+                first.0 < end && first.1 > start
+                    && first.0 + end_tolerance >= start && first.1 <= end + end_tolerance
+            });
+        }
+
+        let num_introns = segments.len() - 1;
+        let offset = match exons.iter().position(|&(_, end)| end == first.1) {
+            Some(idx) if idx + num_introns < exons.len() => idx,
+            _ => return false,
+        };
+        let introns_match = segments.iter().zip(segments.iter().skip(1)).enumerate()
+            .all(|(idx, (prev, next))| {
+                exons[offset + idx].1 == prev.1 && exons[offset + idx + 1].0 == next.0
+            });
+
+        introns_match
+            && first.0 + end_tolerance >= exons[offset].0
+            && first.0 < exons[offset].1
+            && last.1 <= exons[offset + num_introns].1 + end_tolerance
+            && last.1 > exons[offset + num_introns].0
+    }
+
+    /// Returns the genome-wise coordinates of the coding parts of the transcript's exons.
+    ///
+    /// The coordinates include the stop codon and are empty when no coding region is defined.
+    pub fn coding_exon_coords(&self) -> Vec<Coord<u64>> {
+        match self.coding_coord(true) {
+            Some((cstart, cend)) => self.exons.iter()
+                .filter(|exon| exon.start() < cend && exon.end() > cstart)
+                .map(|exon| (max(exon.start(), cstart), min(exon.end(), cend)))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns the length of the spliced transcript, that is, the total length of its exons.
+    pub fn spliced_len(&self) -> u64 {
+        self.exons.iter().fold(0, |acc, exon| acc + exon.span())
+    }
+
+    /// Returns the total length of the transcript's `CDS` exon features, which include the start
+    /// codon.
+    ///
+    /// The stop codon features are added when `incl_stop` is `true`. The returned value is zero
+    /// for noncoding transcripts.
+    pub fn cds_len(&self, incl_stop: bool) -> u64 {
+        self.exons.iter()
+            .flat_map(|exon| exon.features().iter())
+            .filter(|fx| match fx.kind() {
+                &CDS { .. } => true,
+                &StopCodon { .. } => incl_stop,
+                _ => false,
+            })
+            .fold(0, |acc, fx| acc + fx.span())
+    }
+
+    /// Returns the coordinates of the 5' untranslated regions of the transcript's exons, in
+    /// strand order.
+    ///
+    /// These are the `UTR5` exon features. On unknown strands, the `UTR` features before the
+    /// coding region are returned, treating the transcript as if it were on the forward strand.
+    /// The coordinates start from the transcript-wise 5' end, so they are in descending order
+    /// on the reverse strand.
+    pub fn five_prime_utrs(&self) -> Vec<Coord<u64>> {
+        self.utr_coords(true)
+    }
+
+    /// Returns the coordinates of the 3' untranslated regions of the transcript's exons, in
+    /// strand order.
+    ///
+    /// These are the `UTR3` exon features. On unknown strands, the `UTR` features after the
+    /// coding region are returned, treating the transcript as if it were on the forward strand.
+    /// The coordinates start from the transcript-wise 5' end, so they are in descending order
+    /// on the reverse strand.
+    pub fn three_prime_utrs(&self) -> Vec<Coord<u64>> {
+        self.utr_coords(false)
+    }
+
+    /// Returns the total lengths of the 5' and of the 3' untranslated regions of the
+    /// transcript's exons.
+    ///
+    /// The lengths are computed from the coordinates returned by `five_prime_utrs` and
+    /// `three_prime_utrs`, so `UTR` features of noncoding transcripts are not counted.
+    pub fn utr_lens(&self) -> (u64, u64) {
+        let sum = |coords: Vec<Coord<u64>>| {
+            coords.iter().fold(0, |acc, &(start, end)| acc + end - start)
+        };
+        (sum(self.utr_coords(true)), sum(self.utr_coords(false)))
+    }
+
+    /// Helper method for collecting either the 5' or the 3' UTR coordinates.
+    fn utr_coords(&self, five_prime: bool) -> Vec<Coord<u64>> {
+        let coding = self.coding_coord(true);
+        let mut coords = self.exons.iter()
+            .flat_map(|exon| exon.features().iter())
+            .filter(|fx| match (fx.kind(), coding) {
+                (&UTR5, _) => five_prime,
+                (&UTR3, _) => !five_prime,
+                (&UTR, Some((cstart, cend))) =>
+                    if five_prime { fx.end() <= cstart } else { fx.start() >= cend },
+                _ => false,
+            })
+            .map(|fx| (fx.start(), fx.end()))
+            .collect::<Vec<Coord<u64>>>();
+        coords.sort();
+        if self.strand == Strand::Reverse {
+            coords.reverse();
+        }
+        coords
+    }
+
+    /// Extends the transcript by the given number of bases at its 5' end.
+    ///
+    /// The terminal exon is extended along with the transcript. If the exon has features, the
+    /// extension becomes part of its UTR, either by extending the terminal UTR feature or by
+    /// adding a new one. Transcripts on unknown strands are extended at their genome-wise start.
+    ///
+    /// The extension is clamped at the start of the sequence and, if an assembly containing the
+    /// sequence is given, at its end.
+    pub fn extend_5p(&mut self, n: u64, assembly: Option<&Assembly>) {
+        match self.strand {
+            Strand::Forward => self.extend_start(n, UTR5),
+            Strand::Reverse => self.extend_end(n, UTR5, assembly),
+            Strand::Unknown => self.extend_start(n, UTR),
+        }
+    }
+
+    /// Extends the transcript by the given number of bases at its 3' end.
+    ///
+    /// This mirrors `extend_5p`. Transcripts on unknown strands are extended at their
+    /// genome-wise end.
+    pub fn extend_3p(&mut self, n: u64, assembly: Option<&Assembly>) {
+        match self.strand {
+            Strand::Forward => self.extend_end(n, UTR3, assembly),
+            Strand::Reverse => self.extend_start(n, UTR3),
+            Strand::Unknown => self.extend_end(n, UTR, assembly),
+        }
+    }
+
+    /// Trims the transcript to its coding region, including the stop codon.
+    ///
+    /// Exons outside of the coding region are removed, the terminal coding exons are trimmed,
+    /// and all UTR features are removed. An error is returned if the transcript has no coding
+    /// region.
+    pub fn trim_to_cds(&mut self) -> ::Result<()> {
+        let (cstart, cend) = match self.coding_coord(true) {
+            Some(coord) => coord,
+            None => return Err(::Error::from(ModelError::UnspecifiedCoding(self.id.clone()))),
+        };
+        let exons = mem::replace(&mut self.exons, Vec::new());
+        for mut exon in exons.into_iter() {
+            if exon.end() <= cstart || exon.start() >= cend {
+                continue;
+            }
+            exon.interval = coord_to_interval(max(exon.start(), cstart), min(exon.end(), cend))?;
+            exon.features.retain(|fx| match fx.kind {
+                UTR | UTR5 | UTR3 => false,
+                _ => true,
+            });
+            self.exons.push(exon);
+        }
+        self.interval = coord_to_interval(cstart, cend)?;
+        Ok(())
+    }
+
+    /// Extends the transcript and its first exon to a smaller start coordinate.
+    fn extend_start(&mut self, n: u64, utr_kind: ExonFeatureKind) {
+        let (new_start, _) =
+            expand_window(&self.seq_name, (self.start(), self.end()), n, 0, &Strand::Forward,
+                          None);
+        if let Some(exon) = self.exons.first_mut() {
+            let old_start = exon.start();
+            exon.interval = Interval::new(new_start..exon.end()).unwrap();
+            if new_start < old_start && !exon.features.is_empty() {
+                let utr_idx = exon.features.iter()
+                    .position(|fx| fx.start() == old_start && fx.kind == utr_kind);
+                match utr_idx {
+                    Some(idx) => {
+                        let fx_end = exon.features[idx].end();
+                        exon.features[idx].interval = Interval::new(new_start..fx_end).unwrap();
+                    },
+                    None => exon.features.insert(
+                        0, Feature::new(Interval::new(new_start..old_start).unwrap(), utr_kind)),
+                }
+            }
+        }
+        self.interval = Interval::new(new_start..self.end()).unwrap();
+    }
+
+    /// Extends the transcript and its last exon to a larger end coordinate.
+    fn extend_end(&mut self, n: u64, utr_kind: ExonFeatureKind, assembly: Option<&Assembly>) {
+        let (_, new_end) =
+            expand_window(&self.seq_name, (self.start(), self.end()), 0, n, &Strand::Forward,
+                          assembly);
+        // transcripts already past the end of their sequence are left as they are
+        let new_end = max(self.end(), new_end);
+        if let Some(exon) = self.exons.last_mut() {
+            let old_end = exon.end();
+            exon.interval = Interval::new(exon.start()..new_end).unwrap();
+            if new_end > old_end && !exon.features.is_empty() {
+                let utr_idx = exon.features.iter()
+                    .rposition(|fx| fx.end() == old_end && fx.kind == utr_kind);
+                match utr_idx {
+                    Some(idx) => {
+                        let fx_start = exon.features[idx].start();
+                        exon.features[idx].interval = Interval::new(fx_start..new_end).unwrap();
+                    },
+                    None => exon.features.push(
+                        Feature::new(Interval::new(old_end..new_end).unwrap(), utr_kind)),
+                }
+            }
+        }
+        self.interval = Interval::new(self.start()..new_end).unwrap();
+    }
+
+    /// Returns whether the transcript is a candidate for nonsense-mediated decay (NMD).
+    ///
+    /// This follows the 50-nucleotide rule: transcripts whose stop codon ends more than 50 bases
+    /// upstream of their last exon-exon junction, measured along the spliced transcript, are
+    /// likely to be degraded. Transcripts without a coding region, without introns, or on an
+    /// unknown strand are never candidates.
+    pub fn is_nmd_candidate(&self) -> bool {
+        let introns = self.intron_coords();
+        let (stop_end, junction) = match (self.coding_coord(true), &self.strand) {
+            (Some((_, cend)), &Strand::Forward) if !introns.is_empty() =>
+                (cend, introns[introns.len() - 1].0),
+            (Some((cstart, _)), &Strand::Reverse) if !introns.is_empty() =>
+                (cstart, introns[0].1),
+            _ => return false,
+        };
+        let (start, end) = match self.strand {
+            Strand::Reverse => (junction, stop_end),
+            _ => (stop_end, junction),
+        };
+        if start >= end {
+            return false;
+        }
+        let distance = self.exons.iter()
+            .filter(|exon| exon.start() < end && exon.end() > start)
+            .fold(0, |acc, exon| acc + min(exon.end(), end) - max(exon.start(), start));
+        distance > NMD_JUNCTION_DISTANCE
+    }
+
     /// Returns the genome-wise 5' and 3'-most coordinate of the coding region.
     ///
     /// The returned coding region coordinates may include the stop codon, depending on the value
@@ -489,6 +1047,239 @@ impl Transcript {
         }
     }
 
+    /// Returns whether the coding region is incomplete at its 5' end.
+    ///
+    /// Such transcripts have no start codon, and their coding region starts at the 5'-most
+    /// base of their CDS features.
+    pub fn cds_5_incomplete(&self) -> bool {
+        self.cds_5_incomplete
+    }
+
+    /// Returns whether the coding region is incomplete at its 3' end.
+    ///
+    /// Such transcripts have no stop codon, and their coding region ends at the 3'-most base
+    /// of their CDS features regardless of whether the stop codon is included.
+    pub fn cds_3_incomplete(&self) -> bool {
+        self.cds_3_incomplete
+    }
+
+    /// Sets the frames of the CDS features to the given declared values.
+    ///
+    /// Each frame is given along with the genome-wise coordinate of the 5' end of its CDS,
+    /// which is the start coordinate on the forward strand and the end coordinate on the reverse
+    /// strand, so that CDS records with or without their stop codon match the same feature.
+    /// Frames of CDS features not matching any of the coordinates are kept as they are.
+    pub fn set_cds_frames<I>(&mut self, frames: I)
+        where I: IntoIterator<Item=(u64, u8)>
+    {
+        let frames = frames.into_iter().collect::<HashMap<u64, u8>>();
+        if frames.is_empty() {
+            return;
+        }
+        let reverse = self.strand == Strand::Reverse;
+        for exon in self.exons.iter_mut() {
+            for fx in exon.features.iter_mut() {
+                let coord_5 = if reverse { fx.end() } else { fx.start() };
+                if let CDS { ref mut frame } = fx.kind {
+                    if let Some(&declared) = frames.get(&coord_5) {
+                        *frame = Some(declared);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the CDS features whose frames differ from the frames computed from the coding
+    /// region, as `ModelError::FrameMismatch` values in the transcript-wise order.
+    ///
+    /// Frames are computed from the 5'-most CDS feature, which has frame 0 unless the coding
+    /// region is incomplete at its 5' end, in which case its own frame is used. CDS features
+    /// without a frame are skipped.
+    pub fn frame_mismatches(&self) -> Vec<ModelError> {
+        let mut cds_fxs = self.exons.iter()
+            .flat_map(|exon| exon.features.iter())
+            .filter_map(|fx| match fx.kind {
+                CDS { frame } => Some((fx, frame)),
+                _ => None,
+            })
+            .collect::<Vec<(&ExonFeature, Option<u8>)>>();
+        if self.strand == Strand::Reverse {
+            cds_fxs.reverse();
+        }
+
+        let mut mismatches = Vec::new();
+        let mut computed = match (self.cds_5_incomplete, cds_fxs.first()) {
+            (true, Some(&(_, Some(frame)))) => frame,
+            _ => 0,
+        };
+        for (fx, frame) in cds_fxs {
+            if let Some(declared) = frame {
+                if declared != computed {
+                    mismatches.push(ModelError::FrameMismatch(
+                        self.id.clone(), fx.start(), fx.end(), declared, computed));
+                }
+            }
+            computed = calc_next_frame(fx.span(), computed);
+        }
+        mismatches
+    }
+
+    /// Returns the genome-wise intervals encoding the given range of protein residues.
+    ///
+    /// The residue range is zero-based and half-open, with residue 0 encoded by the start
+    /// codon. As in `genomic_to_protein`, the frame of the start codon is respected, so that
+    /// residue 0 covers only the bases of an incomplete first codon. Residues encoded by codons
+    /// split across exons yield one interval per exon, so the returned intervals are sorted
+    /// genome-wise and never span introns.
+    ///
+    /// An error is returned if the transcript has no coding region, if its strand is unknown,
+    /// or if the range is empty or extends past the last residue before the stop codon.
+    pub fn protein_to_genomic(&self, aa_range: Range<u64>) -> ::Result<Vec<Coord<u64>>> {
+        let (cstart, cend) = match self.coding_coord(false) {
+            Some(coord) => coord,
+            None => return Err(::Error::from(ModelError::UnspecifiedCoding(self.id.clone()))),
+        };
+        let mut blocks = self.exons.iter()
+            .filter(|exon| exon.start() < cend && exon.end() > cstart)
+            .map(|exon| (max(exon.start(), cstart), min(exon.end(), cend)))
+            .collect::<Vec<Coord<u64>>>();
+        if self.strand == Strand::Reverse {
+            blocks.reverse();
+        }
+        // bases of an incomplete first codon, counted as part of residue 0
+        let shift = (3 - u64::from(self.start_codon_frame())) % 3;
+        let (nt_start, nt_end) =
+            ((aa_range.start * 3).saturating_sub(shift), (aa_range.end * 3).saturating_sub(shift));
+        let coding_len = blocks.iter().fold(0, |acc, &(start, end)| acc + end - start);
+        if aa_range.start >= aa_range.end || nt_end > coding_len {
+            return Err(::Error::from(ModelError::InvalidResidueRange(self.id.clone())));
+        }
+
+        let mut coords = Vec::new();
+        let mut offset = 0;
+        for (start, end) in blocks {
+            let (block_start, block_end) = (offset, offset + end - start);
+            offset = block_end;
+            if block_end <= nt_start || block_start >= nt_end {
+                continue;
+            }
+            // offsets of the overlapping part, relative to the 5' end of the block
+            let (rel_start, rel_end) =
+                (max(block_start, nt_start) - block_start, min(block_end, nt_end) - block_start);
+            coords.push(match self.strand {
+                Strand::Reverse => (end - rel_end, end - rel_start),
+                _ => (start + rel_start, start + rel_end),
+            });
+        }
+        if self.strand == Strand::Reverse {
+            coords.reverse();
+        }
+        Ok(coords)
+    }
+
+    /// Returns the offset of the given genome-wise coordinate along the spliced transcript.
+    ///
+    /// Offsets are zero-based and counted from the 5' end of the transcript, so on the reverse
+    /// strand offset 0 is the base at `end() - 1`. Transcripts with an unknown strand are read
+    /// as if they were on the forward strand.
+    ///
+    /// The returned value is `None` if the coordinate is intronic or outside the transcript.
+    pub fn genomic_to_transcript(&self, pos: u64) -> Option<u64> {
+        let mut offset = 0;
+        let mut found = None;
+        for exon in self.exons.iter() {
+            if pos >= exon.start() && pos < exon.end() {
+                found = Some(offset + pos - exon.start());
+            }
+            offset += exon.span();
+        }
+        found.map(|fwd_offset| match self.strand {
+            Strand::Reverse => offset - 1 - fwd_offset,
+            _ => fwd_offset,
+        })
+    }
+
+    /// Returns the offset of the given genome-wise coordinate along the coding region, including
+    /// the stop codon.
+    ///
+    /// Offsets are zero-based and counted from the first base of the start codon, so the HGVS
+    /// `c.` position of a base is its offset plus one.
+    ///
+    /// The returned value is `None` if the transcript has no coding region, if its strand is
+    /// unknown, or if the coordinate is outside the coding region or intronic.
+    pub fn genomic_to_cds(&self, pos: u64) -> Option<u64> {
+        let (cstart, cend) = match (self.coding_coord(true), &self.strand) {
+            (Some(coord), &Strand::Forward) | (Some(coord), &Strand::Reverse) => coord,
+            _ => return None,
+        };
+        if pos < cstart || pos >= cend {
+            return None;
+        }
+        let first = match self.strand {
+            Strand::Reverse => cend - 1,
+            _ => cstart,
+        };
+        match (self.genomic_to_transcript(pos), self.genomic_to_transcript(first)) {
+            (Some(pos_offset), Some(first_offset)) => Some(pos_offset - first_offset),
+            _ => None,
+        }
+    }
+
+    /// Returns the residue encoded by the codon containing the given genome-wise coordinate,
+    /// along with the position of the coordinate in the codon.
+    ///
+    /// Both values are zero-based, with residue 0 encoded by the start codon and the stop codon
+    /// counted as the last residue. The frame of the start codon is respected, so that bases
+    /// of an incomplete first codon belong to residue 0.
+    ///
+    /// The returned value is `None` in the same cases as `genomic_to_cds`.
+    pub fn genomic_to_protein(&self, pos: u64) -> Option<(u64, u8)> {
+        self.genomic_to_cds(pos)
+            .map(|cds_offset| {
+                let adjusted = cds_offset + (3 - u64::from(self.start_codon_frame())) % 3;
+                (adjusted / 3, (adjusted % 3) as u8)
+            })
+    }
+
+    /// Returns the genome-wise coordinate of the given offset along the spliced transcript.
+    ///
+    /// This is the inverse of `genomic_to_transcript`. The returned value is `None` if the
+    /// offset is not smaller than the total exon length.
+    pub fn transcript_to_genomic(&self, offset: u64) -> Option<u64> {
+        self.project_interval(offset, offset + 1).first().map(|&(start, _)| start)
+    }
+
+    /// Returns the genome-wise intervals of the given zero-based, half-open range of offsets
+    /// along the spliced transcript.
+    ///
+    /// Ranges spanning exon junctions yield one interval per exon, so the returned intervals
+    /// are sorted genome-wise and never span introns. Parts of the range past the total exon
+    /// length are ignored, so the returned vector is empty for ranges outside the transcript.
+    pub fn project_interval(&self, tx_start: u64, tx_end: u64) -> Vec<Coord<u64>> {
+        let total = self.spliced_len();
+        let (tx_start, tx_end) = (min(tx_start, total), min(tx_end, total));
+        if tx_start >= tx_end {
+            return Vec::new();
+        }
+        // offsets counted from the genome-wise start of the transcript
+        let (fwd_start, fwd_end) = match self.strand {
+            Strand::Reverse => (total - tx_end, total - tx_start),
+            _ => (tx_start, tx_end),
+        };
+        let mut coords = Vec::new();
+        let mut offset = 0;
+        for exon in self.exons.iter() {
+            let (block_start, block_end) = (offset, offset + exon.span());
+            offset = block_end;
+            if block_end <= fwd_start || block_start >= fwd_end {
+                continue;
+            }
+            coords.push((exon.start() + max(block_start, fwd_start) - block_start,
+                         exon.start() + min(block_end, fwd_end) - block_start));
+        }
+        coords
+    }
+
     /// Returns the genome-wise 5'-most coordinate of the coding region.
     fn coding_start_coord(&self, incl_stop: bool) -> Option<u64> {
         match &self.strand {
@@ -500,7 +1291,7 @@ impl Transcript {
                         }
                     }
                 }
-                None
+                self.incomplete_cds_bound(self.cds_5_incomplete, true)
             },
             &Strand::Reverse => {
                 let mut codon_rem = if incl_stop { 0 } else { 3 };
@@ -517,7 +1308,7 @@ impl Transcript {
                         }
                     }
                 }
-                None
+                self.incomplete_cds_bound(self.cds_3_incomplete, true)
             },
             &Strand::Unknown if incl_stop => {
                 for exon in self.exons.iter() {
@@ -533,6 +1324,27 @@ impl Transcript {
         }
     }
 
+    /// Returns the frame of the 5'-most start codon feature, or 0 if it has none.
+    ///
+    /// Transcripts without a start codon, such as those whose coding region is incomplete at
+    /// its 5' end, use the frame of their 5'-most CDS feature instead.
+    fn start_codon_frame(&self) -> u8 {
+        let mut kinds = self.exons.iter()
+            .flat_map(|exon| exon.features.iter())
+            .map(|fx| &fx.kind)
+            .collect::<Vec<&ExonFeatureKind>>();
+        if self.strand == Strand::Reverse {
+            kinds.reverse();
+        }
+        let start_codon_frame = kinds.iter()
+            .filter_map(|kind| match **kind { StartCodon { frame } => Some(frame), _ => None })
+            .next();
+        let cds_frame = kinds.iter()
+            .filter_map(|kind| match **kind { CDS { frame } => Some(frame), _ => None })
+            .next();
+        start_codon_frame.or(cds_frame).and_then(|frame| frame).unwrap_or(0)
+    }
+
     /// Returns the genome-wise 3'-most coordinate of the coding region.
     fn coding_end_coord(&self, incl_stop: bool) -> Option<u64> {
         match &self.strand {
@@ -551,7 +1363,7 @@ impl Transcript {
                         }
                     }
                 }
-                None
+                self.incomplete_cds_bound(self.cds_3_incomplete, false)
             },
             &Strand::Reverse => {
                 for exon in self.exons.iter().rev() {
@@ -561,7 +1373,7 @@ impl Transcript {
                         }
                     }
                 }
-                None
+                self.incomplete_cds_bound(self.cds_5_incomplete, false)
             },
             &Strand::Unknown if incl_stop => {
                 for exon in self.exons.iter().rev() {
@@ -577,6 +1389,59 @@ impl Transcript {
         }
     }
 
+    /// Returns the genome-wise start or end coordinate of the CDS features if the coding
+    /// region is incomplete, in place of the missing codon.
+    fn incomplete_cds_bound(&self, incomplete: bool, genome_start: bool) -> Option<u64> {
+        if !incomplete {
+            return None;
+        }
+        let cds = self.exons.iter()
+            .flat_map(|exon| exon.features.iter())
+            .filter(|fx| if let CDS { .. } = fx.kind { true } else { false });
+        if genome_start {
+            cds.map(|fx| fx.start()).min()
+        } else {
+            cds.map(|fx| fx.end()).max()
+        }
+    }
+
+}
+
+/// Strictness of the consistency checks between nested intervals when building models.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationMode {
+    /// Inconsistent intervals are errors. This is the default.
+    Strict,
+    /// Inconsistent intervals are repaired by resizing the enclosing interval.
+    Fix,
+    /// Inconsistent intervals are kept as they are.
+    Ignore,
+}
+
+impl Default for ValidationMode {
+    fn default() -> Self {
+        ValidationMode::Strict
+    }
+}
+
+/// Handling of coding regions given for transcripts with an unknown strand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnknownStrandCoding {
+    /// The coding region only gets CDS features, without start and stop codons, so that
+    /// `Transcript::coding_coord(false)` returns `None`. This is the default.
+    CdsOnly,
+    /// The transcript is built on the forward strand, with start and stop codons.
+    Forward,
+    /// The coding region is dropped, and the transcript is built as a noncoding transcript.
+    Skip,
+    /// The coding region is an error.
+    Error,
+}
+
+impl Default for UnknownStrandCoding {
+    fn default() -> Self {
+        UnknownStrandCoding::CdsOnly
+    }
 }
 
 /// Builder for transcripts.
@@ -585,21 +1450,31 @@ impl Transcript {
 /// via its `build` function.
 #[derive(Debug)]
 pub struct TBuilder {
-    seq_name: String,
+    seq_name: Arc<str>,
     start: u64,
     end: u64,
+    interval: Option<Interval<u64>>,
     strand: Option<Strand>,
     strand_char: Option<char>,
     id: Option<String>,
     gene_id: Option<String>,
-    attributes: MultiMap<String, String>,
+    biotype: Option<Biotype>,
+    attributes: Attributes,
     // Input can be a vector of pre-made features ...
     exons: Option<Vec<Exon>>,
     // Or exon coordinates, possibly coupled with cds coord
     // NOTE: Can we instead of using Vec<_> here keep it as an unconsumed iterator?
     exon_coords: Option<Vec<Coord<u64>>>,
+    exon_ids: Option<Vec<String>>,
+    exon_attributes: Option<Vec<Attributes>>,
     coding_coord: Option<Coord<u64>>,
     coding_incl_stop: bool,
+    cds_5_incomplete: bool,
+    cds_3_incomplete: bool,
+    truncate_codons: bool,
+    seq_len: Option<u64>,
+    unknown_strand_coding: UnknownStrandCoding,
+    validation: ValidationMode,
 }
 
 impl TBuilder {
@@ -608,22 +1483,63 @@ impl TBuilder {
     pub fn new<T>(seq_name: T, start: u64, end: u64) -> Self
         where T: Into<String>
     {
+        TBuilder::with_shared_seq_name(intern_seq_name(seq_name.into()), start, end)
+    }
+
+    /// Creates a new builder for a transcript whose sequence name is already shared.
+    fn with_shared_seq_name(seq_name: Arc<str>, start: u64, end: u64) -> Self {
         TBuilder {
-            seq_name: seq_name.into(),
+            seq_name: seq_name,
             start: start,
             end: end,
+            interval: None,
             strand: None,
             strand_char: None,
             id: None,
             gene_id: None,
-            attributes: MultiMap::new(),
+            biotype: None,
+            attributes: Attributes::new(),
             exons: None,
             exon_coords: None,
+            exon_ids: None,
+            exon_attributes: None,
             coding_coord: None,
             coding_incl_stop: false,
+            cds_5_incomplete: false,
+            cds_3_incomplete: false,
+            truncate_codons: false,
+            seq_len: None,
+            unknown_strand_coding: UnknownStrandCoding::CdsOnly,
+            validation: ValidationMode::Strict,
         }
     }
 
+    /// Creates a new builder for a transcript with the given coordinates.
+    ///
+    /// This is equivalent to creating the builder using `new` with the transcript coordinate and
+    /// then invoking `coords` with the exon and coding coordinates and `exon_attributes` with
+    /// the exon attributes.
+    pub fn from_coords<T>(seq_name: T, coords: TranscriptCoords) -> Self
+        where T: Into<String>
+    {
+        let TranscriptCoords { interval, exons, exon_attributes, coding } = coords;
+        TBuilder::new(seq_name, interval.0, interval.1)
+            .coords(exons, coding)
+            .exon_attributes(exon_attributes)
+    }
+
+    /// Creates a new builder for a transcript with the given interval.
+    ///
+    /// The interval is used as it is, without being validated again by the `build` method,
+    /// unless it is resized to span the exons in `ValidationMode::Fix`.
+    pub fn from_interval<T>(seq_name: T, interval: Interval<u64>) -> Self
+        where T: Into<String>
+    {
+        let mut builder = TBuilder::new(seq_name, interval.start, interval.end);
+        builder.interval = Some(interval);
+        builder
+    }
+
     /// Sets the strand of the transcript.
     pub fn strand(mut self, strand: Strand) -> Self {
         self.strand = Some(strand);
@@ -663,6 +1579,14 @@ impl TBuilder {
         self
     }
 
+    /// Sets the biotype of the transcript.
+    pub fn biotype<T>(mut self, biotype: T) -> Self
+        where T: Into<Biotype>
+    {
+        self.biotype = Some(biotype.into());
+        self
+    }
+
     /// Sets a single attribute of the transcript.
     pub fn attribute<K, V>(mut self, key: K, value: V) -> Self
         where K: Into<String>, V: Into<String>
@@ -672,12 +1596,17 @@ impl TBuilder {
     }
 
     /// Sets the entire attribute of the transcript.
-    pub fn attributes(mut self, attributes: MultiMap<String, String>) -> Self {
-        self.attributes = attributes;
+    pub fn attributes<T: Into<Attributes>>(mut self, attributes: T) -> Self {
+        self.attributes = attributes.into();
         self
     }
 
     /// Sets the exons for the transcript.
+    ///
+    /// The exons are sorted by their coordinates when the transcript is built. The `build`
+    /// method returns an error if any exon has a different sequence name or strand than the
+    /// transcript, if any exons overlap each other, or if the exons do not span the transcript
+    /// coordinate, subject to the mode set by the `validation` method.
     pub fn exons(mut self, exons: Vec<Exon>) -> Self {
         self.exons =
             if exons.is_empty() {
@@ -709,29 +1638,195 @@ impl TBuilder {
         where E: IntoIterator<Item=Coord<u64>>
     {
         self.exon_coords = Some(exon_coords.into_iter().collect());
+        self.exon_ids = None;
         self.coding_coord = coding_coord;
         self
     }
 
-    /// Sets the inclusion of stop codon in coding coordinates.
+    /// Sets the exons of the transcript by their coordinates and identifiers.
     ///
-    /// This value is ignored if the `coding_coord` argument of the `coords` method is `None`.
-    pub fn coding_incl_stop(mut self, incl_stop: bool) -> Self {
-        self.coding_incl_stop = incl_stop;
+    /// This is the same as `coords`, except that each exon coordinate is paired with the
+    /// identifier of the exon to create, such as `TRX:exon1`. The identifiers stay with their
+    /// exons when the exons are sorted by their coordinates.
+    pub fn coords_with_ids<E, T>(self, exons: E, coding_coord: Option<Coord<u64>>) -> Self
+        where E: IntoIterator<Item=(Coord<u64>, T)>, T: Into<String>
+    {
+        let (exon_coords, exon_ids): (Vec<Coord<u64>>, Vec<String>) = exons.into_iter()
+            .map(|(coord, id)| (coord, id.into()))
+            .unzip();
+        let mut builder = self.coords(exon_coords, coding_coord);
+        builder.exon_ids = Some(exon_ids);
+        builder
+    }
+
+    /// Sets the attributes of the exons created from exon coordinates.
+    ///
+    /// Each item is paired with the exon coordinate at the same position in the input of
+    /// `coords` or `coords_with_ids`, and stays with its exon when the exons are sorted by their
+    /// coordinates. Exons without a matching item get no attributes. This value is ignored when
+    /// exons are set with the `exons` method.
+    pub fn exon_attributes<E, T>(mut self, exon_attributes: E) -> Self
+        where E: IntoIterator<Item=T>, T: Into<Attributes>
+    {
+        let exon_attributes = exon_attributes.into_iter()
+            .map(|attributes| attributes.into())
+            .collect::<Vec<Attributes>>();
+        self.exon_attributes =
+            if exon_attributes.is_empty() {
+                None
+            } else {
+                Some(exon_attributes)
+            };
+        self
+    }
+
+    /// Sets the inclusion of stop codon in coding coordinates.
+    ///
+    /// This value is ignored if the `coding_coord` argument of the `coords` method is `None`.
+    pub fn coding_incl_stop(mut self, incl_stop: bool) -> Self {
+        self.coding_incl_stop = incl_stop;
+        self
+    }
+
+    /// Sets whether the coding region is incomplete at its 5' end.
+    ///
+    /// This mirrors the `cdsStartStat` column of genePred files. Partial models whose coding
+    /// region starts before the transcript do not get a start codon, and their coding region
+    /// starts at the 5'-most CDS base instead. This value is ignored if the `coding_coord`
+    /// argument of the `coords` method is `None`.
+    pub fn cds_5_incomplete(mut self, incomplete: bool) -> Self {
+        self.cds_5_incomplete = incomplete;
+        self
+    }
+
+    /// Sets whether the coding region is incomplete at its 3' end.
+    ///
+    /// This mirrors the `cdsEndStat` column of genePred files. Partial models whose coding
+    /// region extends past the transcript do not get a stop codon, so there need not be room for
+    /// one after the coding region, and the coding coordinate is never adjusted for it by
+    /// `coding_incl_stop`. This value is ignored if the `coding_coord` argument of the `coords`
+    /// method is `None`.
+    pub fn cds_3_incomplete(mut self, incomplete: bool) -> Self {
+        self.cds_3_incomplete = incomplete;
+        self
+    }
+
+    /// Sets whether stop codons that do not fit in the exons are truncated.
+    ///
+    /// By default, the `build` method returns `ModelError::CodingTooLarge` when there is no room
+    /// for the stop codon after the coding region, for example when the coding region ends at
+    /// the edge of its sequence. When this is set, the stop codon is dropped instead: its bases
+    /// in the exons become part of the 3' UTR, and the coding region of the transcript is marked
+    /// as incomplete at its 3' end. If the sequence length is known through the `assembly`
+    /// method, this is only done for stop codons that would extend past the sequence ends.
+    /// This value is ignored if the `coding_coord` argument of the `coords` method is `None`.
+    pub fn truncate_codons(mut self, truncate: bool) -> Self {
+        self.truncate_codons = truncate;
+        self
+    }
+
+    /// Sets the assembly containing the transcript's sequence.
+    ///
+    /// The length of the sequence limits the truncation of stop codons set with
+    /// `truncate_codons`. Sequences not in the assembly are treated as having unknown lengths.
+    pub fn assembly(mut self, assembly: &Assembly) -> Self {
+        self.seq_len = assembly.seq_len(&self.seq_name);
+        self
+    }
+
+    /// Sets how a coding region is handled when the strand of the transcript is unknown.
+    ///
+    /// See `UnknownStrandCoding` for the available policies. With
+    /// `UnknownStrandCoding::Error`, the `build` method returns
+    /// `ModelError::CodingOnUnknownStrand`. This value is only used for coding regions given
+    /// with the `coords` method, and is ignored when exons are set with the `exons` method.
+    pub fn unknown_strand_coding(mut self, policy: UnknownStrandCoding) -> Self {
+        self.unknown_strand_coding = policy;
+        self
+    }
+
+    /// Sets how exon coordinates that do not span the transcript exactly are handled.
+    ///
+    /// In `ValidationMode::Strict`, the `build` method returns `ModelError::UnmatchedExons`. In
+    /// `ValidationMode::Fix`, the transcript interval is resized to span its exons, and in
+    /// `ValidationMode::Ignore`, the transcript interval is kept as it is.
+    pub fn validation(mut self, mode: ValidationMode) -> Self {
+        self.validation = mode;
         self
     }
 
     /// Validates the input data and builds a transcript.
     pub fn build(self) -> ::Result<Transcript> {
-        let interval = coord_to_interval(self.start, self.end)
-            .map_err(::Error::Model)?;
-        let strand = resolve_strand_input(self.strand, self.strand_char)
+        let fix_interval = self.validation == ValidationMode::Fix;
+        let (start, end) = match (self.exons.as_ref(), self.exon_coords.as_ref()) {
+            (Some(exons), _) if fix_interval => {
+                coords_span(exons.iter().map(|exon| (exon.start(), exon.end())))
+            },
+            (None, Some(coords)) if fix_interval && !coords.is_empty() => {
+                coords_span(coords.iter().cloned())
+            },
+            _ => (self.start, self.end),
+        };
+        let interval = match self.interval.filter(|iv| (iv.start, iv.end) == (start, end)) {
+            Some(interval) => interval,
+            None => coord_to_interval(start, end).map_err(::Error::Model)?,
+        };
+        let mut strand = resolve_strand_input(self.strand, self.strand_char)
             .map_err(::Error::Model)?;
-        let exons = resolve_exons_input(
+        let inferred = self.exons.is_none();
+        let mut coding_coord = self.coding_coord;
+        if inferred && coding_coord.is_some() && strand == Strand::Unknown {
+            match self.unknown_strand_coding {
+                UnknownStrandCoding::CdsOnly => {},
+                UnknownStrandCoding::Forward => strand = Strand::Forward,
+                UnknownStrandCoding::Skip => coding_coord = None,
+                UnknownStrandCoding::Error => {
+                    let err = ModelError::CodingOnUnknownStrand(self.id);
+                    return Err(::Error::Model(err));
+                },
+            }
+        }
+        let paired = self.exon_ids.is_some() || self.exon_attributes.is_some();
+        let (exon_coords, exon_ids, exon_attributes) = match self.exon_coords {
+            Some(coords) if paired => {
+                let mut order = (0..coords.len()).collect::<Vec<usize>>();
+                order.sort_by_key(|&idx| coords[idx]);
+                let ids = self.exon_ids.map(|ids| reorder(ids, &order));
+                let attributes = self.exon_attributes.map(|attribs| reorder(attribs, &order));
+                let coords = order.iter().map(|&idx| coords[idx]).collect::<Vec<Coord<u64>>>();
+                (Some(coords), ids, attributes)
+            },
+            coords => (coords, None, None),
+        };
+        let truncation = match (self.truncate_codons, self.seq_len) {
+            (false, _) => CodonTruncation::Never,
+            (true, None) => CodonTruncation::AtExonEnds,
+            (true, Some(seq_len)) => CodonTruncation::AtSeqEnds(seq_len),
+        };
+        let mut exons = resolve_exons_input(
             &self.seq_name, &interval, &strand, self.id.as_deref(),
-            self.gene_id.as_deref(), None, // TODO: allow for exon IDs here
-            self.exons, self.exon_coords.as_ref(), self.coding_coord,
-            self.coding_incl_stop).map_err(::Error::Model)?;
+            self.gene_id.as_deref(), self.exons, exon_coords.as_ref(), coding_coord,
+            self.coding_incl_stop, (self.cds_5_incomplete, self.cds_3_incomplete),
+            truncation, self.validation)
+            .map_err(::Error::Model)?;
+        if let (true, Some(ids)) = (inferred, exon_ids) {
+            for (exon, id) in exons.iter_mut().zip(ids) {
+                exon.id = id;
+            }
+        }
+        if let (true, Some(attributes)) = (inferred, exon_attributes) {
+            for (exon, attribs) in exons.iter_mut().zip(attributes) {
+                if let Some(attribs) = attribs {
+                    exon.attributes = attribs;
+                }
+            }
+        }
+        let truncated = inferred && self.truncate_codons && !self.cds_3_incomplete
+            && stop_codon_truncated(&exons, &strand);
+        if truncated {
+            remove_codon_features(&mut exons, false, true);
+        }
+        let cds_3_incomplete = self.cds_3_incomplete || truncated;
 
         let transcript = Transcript {
             seq_name: self.seq_name,
@@ -739,23 +1834,130 @@ impl TBuilder {
             strand: strand,
             id: self.id,
             gene_id: self.gene_id,
+            biotype: self.biotype,
             attributes: self.attributes,
             exons: exons,
+            cds_5_incomplete: self.cds_5_incomplete,
+            cds_3_incomplete: cds_3_incomplete,
         };
         Ok(transcript)
     }
 }
 
+/// Coordinates of a transcript, its exons, and its coding region.
+///
+/// This is the input type for creating transcripts by their coordinates. All coordinates are
+/// two-element tuples `(a: u64, b: u64)` where `a` <= `b`, and are only checked when the
+/// transcript is built.
+///
+/// Exons may also be given attributes, such as those of GTF exon records, which are set on the
+/// exons of the built transcript.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranscriptCoords {
+    interval: Coord<u64>,
+    exons: Vec<Coord<u64>>,
+    exon_attributes: Vec<Attributes>,
+    coding: Option<Coord<u64>>,
+}
+
+impl TranscriptCoords {
+
+    /// Creates transcript coordinates with the given start and end, without any exons.
+    pub fn new(start: u64, end: u64) -> Self {
+        TranscriptCoords {
+            interval: (start, end),
+            exons: Vec::new(),
+            exon_attributes: Vec::new(),
+            coding: None,
+        }
+    }
+
+    /// Adds a single exon coordinate.
+    pub fn exon(mut self, start: u64, end: u64) -> Self {
+        self.exons.push((start, end));
+        self
+    }
+
+    /// Adds a single exon coordinate along with the attributes of the exon.
+    ///
+    /// Exons added without attributes, with `exon` or `exons`, get empty attributes.
+    pub fn exon_with_attributes<T>(mut self, start: u64, end: u64, attributes: T) -> Self
+        where T: Into<Attributes>
+    {
+        self.exon_attributes.resize(self.exons.len(), Attributes::new());
+        self.exon_attributes.push(attributes.into());
+        self.exons.push((start, end));
+        self
+    }
+
+    /// Adds multiple exon coordinates.
+    pub fn exons<E>(mut self, exon_coords: E) -> Self
+        where E: IntoIterator<Item=Coord<u64>>
+    {
+        self.exons.extend(exon_coords);
+        self
+    }
+
+    /// Sets the coding region coordinate.
+    ///
+    /// Whether this includes the stop codon or not is set by the builder of the transcript.
+    pub fn coding(mut self, start: u64, end: u64) -> Self {
+        self.coding = Some((start, end));
+        self
+    }
+
+    /// Returns the transcript coordinate.
+    pub fn interval(&self) -> Coord<u64> {
+        self.interval
+    }
+
+    /// Returns the exon coordinates.
+    pub fn exon_coords(&self) -> &[Coord<u64>] {
+        self.exons.as_slice()
+    }
+
+    /// Returns the exon attributes, in the same order as the exon coordinates.
+    ///
+    /// Exons added after the last one with attributes are not listed.
+    pub fn exon_attributes(&self) -> &[Attributes] {
+        self.exon_attributes.as_slice()
+    }
+
+    /// Returns the coding region coordinate, if defined.
+    pub fn coding_coord(&self) -> Option<Coord<u64>> {
+        self.coding
+    }
+}
+
+impl From<(Coord<u64>, Vec<Coord<u64>>, Option<Coord<u64>>)> for TranscriptCoords {
+    fn from(raw: (Coord<u64>, Vec<Coord<u64>>, Option<Coord<u64>>)) -> Self {
+        TranscriptCoords {
+            interval: raw.0,
+            exons: raw.1,
+            exon_attributes: Vec::new(),
+            coding: raw.2,
+        }
+    }
+}
+
 /// The gene model.
 ///
 /// To create a gene, a `GBuilder` needs to be used.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Gene {
-    seq_name: String,
+    #[cfg_attr(feature = "serde", serde(with = "serde_impls::seq_name"))]
+    seq_name: Arc<str>,
+    #[cfg_attr(feature = "serde", serde(with = "serde_impls::interval"))]
     interval: Interval<u64>,
+    #[cfg_attr(feature = "serde", serde(with = "::strand::serde_strand"))]
     strand: Strand,
     id: Option<String>,
-    attributes: MultiMap<String, String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    biotype: Option<Biotype>,
+    #[cfg_attr(feature = "serde", serde(with = "serde_impls::attributes"))]
+    attributes: Attributes,
+    #[cfg_attr(feature = "serde", serde(with = "serde_impls::transcripts"))]
     transcripts: LinkedHashMap<String, Transcript>,
 }
 
@@ -771,20 +1973,238 @@ impl Gene {
         where T: Into<String> + Clone
     {
         for (_, transcript) in self.transcripts.iter_mut() {
-            transcript.set_id(id.clone())
+            transcript.set_gene_id(id.clone())
         }
         self.id = id.map(|v| v.into())
     }
 
-    /// Sets the transcripts for the gene.
+    /// Sets the sequence name of the gene, all of its transcripts, and their exons.
+    pub fn rename_seq<T>(&mut self, name: T)
+        where T: Into<String>
+    {
+        let seq_name = intern_seq_name(name.into());
+        for (_, transcript) in self.transcripts.iter_mut() {
+            transcript.rename_seq_shared(seq_name.clone());
+        }
+        self.seq_name = seq_name;
+    }
+
+    /// Returns the biotype of the gene.
+    pub fn biotype(&self) -> Option<&Biotype> {
+        self.biotype.as_ref()
+    }
+
+    /// Sets the biotype of the gene.
+    pub fn set_biotype(&mut self, biotype: Option<Biotype>) {
+        self.biotype = biotype
+    }
+
+    /// Returns a reference of the transcripts, keyed by their identifiers.
     pub fn transcripts(&self) -> &LinkedHashMap<String, Transcript> {
         &self.transcripts
     }
 
+    /// Returns a mutable reference of the transcripts, keyed by their identifiers.
+    ///
+    /// Changes made through this reference are not checked. Transcripts should be added with
+    /// `insert_transcript` instead, and `fit_interval` should be called after changing the
+    /// coordinates of any transcript, so that the gene interval still covers its transcripts.
+    pub fn transcripts_mut(&mut self) -> &mut LinkedHashMap<String, Transcript> {
+        &mut self.transcripts
+    }
+
     /// Consumes the gene and returns its transcripts.
     pub fn take_transcripts(self) -> LinkedHashMap<String, Transcript> {
         self.transcripts
     }
+
+    /// Adds the given transcript to the gene, keyed by its identifier, and extends the gene
+    /// interval to cover it.
+    ///
+    /// A transcript already stored under the same identifier is replaced and returned, after
+    /// which the gene interval is fitted to its transcripts as in `fit_interval`. The strand of
+    /// the transcript must match the gene strand, unless the gene strand is unknown. If the gene
+    /// has an identifier, it is set as the gene identifier of the transcript and its exons.
+    ///
+    /// An error is returned if the transcript has no identifier or if its sequence name or
+    /// strand does not match the gene.
+    pub fn insert_transcript(&mut self, mut transcript: Transcript)
+        -> ::Result<Option<Transcript>>
+    {
+        let trx_id = transcript.id()
+            .map(|tid| tid.to_owned())
+            .ok_or(ModelError::UnidentifiedTranscript)?;
+        if transcript.seq_name() != self.seq_name() {
+            return Err(::Error::from(ModelError::UnmatchedSeqName(Some(trx_id))));
+        }
+        check_gene_strand(&self.strand, &transcript, &trx_id)?;
+        if let Some(ref gid) = self.id {
+            transcript.set_gene_id(Some(gid.as_str()));
+        }
+        let (start, end) = (min(self.start(), transcript.start()),
+                            max(self.end(), transcript.end()));
+        let replaced = self.transcripts.insert(trx_id, transcript);
+        if replaced.is_some() {
+            self.fit_interval(false);
+        } else {
+            self.interval = Interval::new(start..end).unwrap();
+        }
+        Ok(replaced)
+    }
+
+    /// Removes the transcript with the given identifier from the gene and returns it.
+    ///
+    /// The gene interval is fitted to the remaining transcripts as in `fit_interval`.
+    pub fn remove_transcript(&mut self, transcript_id: &str) -> Option<Transcript> {
+        let removed = self.transcripts.remove(transcript_id);
+        if removed.is_some() {
+            self.fit_interval(false);
+        }
+        removed
+    }
+
+    /// Sets the gene interval to the span of its transcripts.
+    ///
+    /// When `update_strand` is `true`, the gene strand is also set to the strand shared by all
+    /// of its transcripts, or to the unknown strand if the transcripts are on different strands.
+    /// Genes without transcripts are left unchanged.
+    pub fn fit_interval(&mut self, update_strand: bool) {
+        let mut span: Option<(u64, u64, Strand)> = None;
+        for trx in self.transcripts.values() {
+            span = match span {
+                None => Some((trx.start(), trx.end(), *trx.strand())),
+                Some((start, end, strand)) => {
+                    let strand = if strand == *trx.strand() { strand } else { Strand::Unknown };
+                    Some((min(start, trx.start()), max(end, trx.end()), strand))
+                },
+            };
+        }
+        if let Some((start, end, strand)) = span {
+            self.interval = Interval::new(start..end).unwrap();
+            if update_strand {
+                self.strand = strand;
+            }
+        }
+    }
+
+    /// Returns the genome-wise 5' and 3'-most coordinate of the coding regions of all the
+    /// gene's transcripts.
+    ///
+    /// The coordinates include the stop codon. The returned value is `None` when none of the
+    /// transcripts has a coding region.
+    pub fn coding_span(&self) -> Option<Coord<u64>> {
+        self.transcripts.values()
+            .filter_map(|trx| trx.coding_coord(true))
+            .fold(None, |acc, (start, end)| match acc {
+                None => Some((start, end)),
+                Some((acc_start, acc_end)) => Some((min(acc_start, start), max(acc_end, end))),
+            })
+    }
+
+    /// Returns the union of the coding parts of the exons of all the gene's transcripts.
+    ///
+    /// The returned coordinates are sorted and do not overlap or abut each other. A position is
+    /// contained in any of these coordinates if and only if it lies in the coding region of any
+    /// of the gene's transcripts.
+    pub fn coding_exon_coords(&self) -> Vec<Coord<u64>> {
+        let mut coords = self.transcripts.values()
+            .flat_map(|trx| trx.coding_exon_coords())
+            .collect::<Vec<Coord<u64>>>();
+        coords.sort();
+
+        let mut merged: Vec<Coord<u64>> = Vec::with_capacity(coords.len());
+        for (start, end) in coords {
+            if let Some(last) = merged.last_mut() {
+                if start <= last.1 {
+                    last.1 = max(last.1, end);
+                    continue;
+                }
+            }
+            merged.push((start, end));
+        }
+        merged
+    }
+
+    /// Returns the transcript with the longest coding region.
+    ///
+    /// The coding region length is the total length of the coding parts of the transcript's
+    /// exons, including the stop codon. When more than one transcript has the longest coding
+    /// region, the first one is returned. The returned value is `None` when none of the
+    /// transcripts has a coding region.
+    pub fn max_cds_transcript(&self) -> Option<&Transcript> {
+        let mut found: Option<(&Transcript, u64)> = None;
+        for trx in self.transcripts.values() {
+            let len = trx.coding_exon_coords().iter()
+                .fold(0, |acc, &(start, end)| acc + end - start);
+            if len == 0 {
+                continue;
+            }
+            found = match found {
+                Some((_, max_len)) if max_len >= len => found,
+                _ => Some((trx, len)),
+            };
+        }
+        found.map(|(trx, _)| trx)
+    }
+
+    /// Returns the transcript representing the gene according to the given policy.
+    ///
+    /// Ties are broken in favor of the transcript that comes first in the gene. The returned
+    /// value is `None` when the gene has no transcripts or, with `CanonicalPolicy::Tagged`, when
+    /// none of the transcripts has the tag.
+    pub fn canonical_transcript(&self, policy: &CanonicalPolicy) -> Option<&Transcript> {
+        match policy {
+            &CanonicalPolicy::LongestCds => self.max_cds_transcript()
+                .or_else(|| self.canonical_transcript(&CanonicalPolicy::LongestSpliced)),
+            &CanonicalPolicy::LongestSpliced => {
+                let mut found: Option<&Transcript> = None;
+                for trx in self.transcripts.values() {
+                    found = match found {
+                        Some(max_trx) if max_trx.spliced_len() >= trx.spliced_len() => found,
+                        _ => Some(trx),
+                    };
+                }
+                found
+            },
+            &CanonicalPolicy::Tagged(ref tag) => {
+                let tag = TranscriptTag::from(tag.as_str());
+                self.transcripts.values().find(|trx| trx.has_tag(&tag))
+            },
+            &CanonicalPolicy::Annotated => {
+                let tagged = |tag| self.transcripts.values().find(|trx| trx.has_tag(&tag));
+                tagged(TranscriptTag::ManeSelect)
+                    .or_else(|| tagged(TranscriptTag::EnsemblCanonical))
+                    .or_else(|| {
+                        self.transcripts.values()
+                            .min_by_key(|trx| {
+                                (!trx.has_tag(&TranscriptTag::Basic),
+                                 trx.support_level().unwrap_or(u8::max_value()))
+                            })
+                    })
+            },
+        }
+    }
+}
+
+/// Policies for choosing the transcript that represents a gene.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CanonicalPolicy {
+    /// The transcript with the longest coding region, including the stop codon.
+    ///
+    /// Genes without coding transcripts fall back to `LongestSpliced`.
+    LongestCds,
+    /// The transcript with the longest total exon length.
+    LongestSpliced,
+    /// The first transcript whose `tag` attribute has the given value, such as
+    /// `Ensembl_canonical` or `MANE_Select` in GENCODE annotations.
+    Tagged(String),
+    /// The transcript chosen from the tags and support levels of Ensembl and GENCODE
+    /// annotations.
+    ///
+    /// The first transcript tagged `MANE_Select` is chosen, or otherwise the first one tagged
+    /// `Ensembl_canonical`. Without either tag, transcripts tagged `basic` are preferred, then
+    /// those with the best `transcript_support_level`, and then those coming first in the gene.
+    Annotated,
 }
 
 /// Builder for genes.
@@ -793,16 +2213,19 @@ impl Gene {
 /// via its `build` function.
 #[derive(Debug)]
 pub struct GBuilder {
-    seq_name: String,
+    seq_name: Arc<str>,
     start: u64,
     end: u64,
     strand: Option<Strand>,
     strand_char: Option<char>,
     id: Option<String>,
-    attributes: MultiMap<String, String>,
+    biotype: Option<Biotype>,
+    attributes: Attributes,
     transcripts: Option<LinkedHashMap<String, Transcript>>,
-    transcript_coords: Option<LinkedHashMap<String, RawTrxCoords>>,
+    transcript_coords: Option<LinkedHashMap<String, TranscriptCoords>>,
     transcript_coding_incl_stop: bool,
+    added_transcripts: Vec<Transcript>,
+    validation: ValidationMode,
 }
 
 impl GBuilder {
@@ -812,16 +2235,19 @@ impl GBuilder {
         where T: Into<String>
     {
         GBuilder {
-            seq_name: seq_name.into(),
+            seq_name: intern_seq_name(seq_name.into()),
             start: start,
             end: end,
             strand: None,
             strand_char: None,
             id: None,
-            attributes: MultiMap::new(),
+            biotype: None,
+            attributes: Attributes::new(),
             transcripts: None,
             transcript_coords: None,
             transcript_coding_incl_stop: false,
+            added_transcripts: Vec::new(),
+            validation: ValidationMode::Strict,
         }
     }
 
@@ -856,6 +2282,14 @@ impl GBuilder {
         self
     }
 
+    /// Sets the biotype of the gene.
+    pub fn biotype<T>(mut self, biotype: T) -> Self
+        where T: Into<Biotype>
+    {
+        self.biotype = Some(biotype.into());
+        self
+    }
+
     /// Sets a single attribute of the gene.
     pub fn attribute<K, V>(mut self, key: K, value: V) -> Self
         where K: Into<String>, V: Into<String>
@@ -865,8 +2299,8 @@ impl GBuilder {
     }
 
     /// Sets the entire attribute of the gene.
-    pub fn attributes(mut self, attributes: MultiMap<String, String>) -> Self {
-        self.attributes = attributes;
+    pub fn attributes<T: Into<Attributes>>(mut self, attributes: T) -> Self {
+        self.attributes = attributes.into();
         self
     }
 
@@ -881,15 +2315,33 @@ impl GBuilder {
     /// The coordinates are supplied as a `LinkedHashMap`, keyed by the transcript identifiers.
     /// This map is used so that ordering of the transcripts in the gene to build is defined.
     ///
-    /// The values of this map is a three-element tuple consisting of:
+    /// The values of this map are anything convertible into `TranscriptCoords`, such as
+    /// `TranscriptCoords` itself or a three-element tuple consisting of:
     ///
     /// * A tuple of the transcript coordinate.
     /// * A vector of the exon coordinates for the particular transcript.
     /// * A coding region that may exist in the transcript.
     ///
     /// All coordinates are supplied as two-element tuples `(a: u64, b: u64)` where `a` <= `b`.
-    pub fn transcript_coords(mut self, coords: LinkedHashMap<String, RawTrxCoords>)-> Self {
-        self.transcript_coords = Some(coords);
+    pub fn transcript_coords<V>(mut self, coords: LinkedHashMap<String, V>) -> Self
+        where V: Into<TranscriptCoords>
+    {
+        self.transcript_coords = Some(coords.into_iter()
+            .map(|(tid, raw)| (tid, raw.into()))
+            .collect());
+        self
+    }
+
+    /// Adds a single transcript to the gene by its coordinates.
+    ///
+    /// The transcript is placed after any transcripts previously set using the
+    /// `transcript_coords` or `add_transcript_coords` methods.
+    pub fn add_transcript_coords<T>(mut self, transcript_id: T, coords: TranscriptCoords) -> Self
+        where T: Into<String>
+    {
+        let mut trxs_coords = self.transcript_coords.take().unwrap_or_else(LinkedHashMap::new);
+        let _ = trxs_coords.insert(transcript_id.into(), coords);
+        self.transcript_coords = Some(trxs_coords);
         self
     }
 
@@ -901,26 +2353,109 @@ impl GBuilder {
         self
     }
 
+    /// Adds a single built transcript to the gene.
+    ///
+    /// The transcript is keyed by its identifier and is placed after any transcripts set using
+    /// the `transcripts` or `transcript_coords` methods. When the gene is built, the transcript
+    /// is checked to have an identifier that is not yet used in the gene, to have the same
+    /// sequence name and strand as the gene, and to be fully enveloped by the gene. As in
+    /// `Gene::insert_transcript`, any transcript strand is accepted if the gene strand is
+    /// unknown. If the gene has an identifier, it is also set as the gene identifier of the
+    /// transcript.
+    pub fn add_transcript(mut self, transcript: Transcript) -> Self {
+        self.added_transcripts.push(transcript);
+        self
+    }
+
+    /// Adds multiple built transcripts to the gene.
+    ///
+    /// See the `add_transcript` method for the checks performed on each transcript.
+    pub fn add_transcripts<I>(mut self, transcripts: I) -> Self
+        where I: IntoIterator<Item=Transcript>
+    {
+        self.added_transcripts.extend(transcripts);
+        self
+    }
+
+    /// Sets how transcripts that are not fully enveloped by the gene are handled.
+    ///
+    /// In `ValidationMode::Strict`, these transcripts cause
+    /// `ModelError::TranscriptNotFullyEnveloped`. In `ValidationMode::Fix`, the gene interval is
+    /// widened to enclose all of its transcripts, and in `ValidationMode::Ignore`, the gene
+    /// interval is kept as it is. The mode is also used for transcripts created from
+    /// coordinates, as set by `TBuilder::validation`.
+    pub fn validation(mut self, mode: ValidationMode) -> Self {
+        self.validation = mode;
+        self
+    }
+
     /// Validates the input data and builds a gene.
     pub fn build(self) -> ::Result<Gene> {
-        let interval = coord_to_interval(self.start, self.end)
+        self.build_gene(None)
+    }
+
+    /// Validates the input data and builds a gene, skipping invalid transcripts.
+    ///
+    /// Unlike `build`, an invalid transcript does not cause the whole gene to fail. Instead,
+    /// the transcript is left out of the gene and its error is returned along with the gene, in
+    /// the order the transcripts were supplied. Errors of the gene itself, such as an invalid
+    /// interval or strand, are still returned as errors.
+    pub fn build_lenient(self) -> ::Result<(Gene, Vec<::Error>)> {
+        let mut errors = Vec::new();
+        let gene = self.build_gene(Some(&mut errors))?;
+        Ok((gene, errors))
+    }
+
+    /// Builds a gene, collecting transcript errors if a container for them is given.
+    fn build_gene(self, mut errors: Option<&mut Vec<::Error>>) -> ::Result<Gene> {
+        let (start, end) =
+            if self.validation == ValidationMode::Fix {
+                self.transcripts_span()
+            } else {
+                (self.start, self.end)
+            };
+        let interval = coord_to_interval(start, end)
             .map_err(::Error::Model)?;
         let strand = resolve_strand_input(self.strand, self.strand_char)
             .map_err(::Error::Model)?;
-        let transcripts = resolve_transcripts_input(
+        let mut transcripts = resolve_transcripts_input(
             &self.seq_name, &interval, &strand, self.id.as_deref(),
-            self.transcripts, self.transcript_coords, self.transcript_coding_incl_stop)?;
+            self.transcripts, self.transcript_coords, self.transcript_coding_incl_stop,
+            self.validation, errors.as_mut().map(|errs| &mut **errs))?;
+        for transcript in self.added_transcripts {
+            let res = add_gene_transcript(&mut transcripts, &self.seq_name, &interval, &strand,
+                                          self.id.as_deref(), self.validation, transcript)
+                .map_err(::Error::Model);
+            collect_error(res, errors.as_mut().map(|errs| &mut **errs))?;
+        }
 
         let gene = Gene {
             seq_name: self.seq_name,
             interval: interval,
             strand: strand,
             id: self.id,
+            biotype: self.biotype,
             attributes: self.attributes,
             transcripts: transcripts,
         };
         Ok(gene)
     }
+
+    /// Returns the gene coordinates widened to enclose all transcript inputs, including the
+    /// exons of transcripts given by their coordinates.
+    fn transcripts_span(&self) -> Coord<u64> {
+        let built = self.transcripts.iter()
+            .flat_map(|trxs| trxs.values())
+            .chain(self.added_transcripts.iter())
+            .map(|trx| (trx.start(), trx.end()));
+        let from_coords = self.transcript_coords.iter()
+            .flat_map(|trxs_coords| trxs_coords.values())
+            .flat_map(|coords| {
+                Some(coords.interval).into_iter().chain(coords.exons.iter().cloned())
+            });
+        built.chain(from_coords)
+            .fold((self.start, self.end), |(start, end), (a, b)| (min(start, a), max(end, b)))
+    }
 }
 
 quick_error! {
@@ -969,6 +2504,31 @@ quick_error! {
             display(self_) -> ("{}, transcript ID: {}",
                                self_.description(), tid.as_deref().unwrap_or(DEF_ID))
         }
+        /// Occurs when a range of exon indices is empty or exceeds the number of exons.
+        InvalidExonRange(tid: Option<String>) {
+            description("invalid exon index range")
+            display(self_) -> ("{}, transcript ID: {}",
+                               self_.description(), tid.as_deref().unwrap_or(DEF_ID))
+        }
+        /// Occurs when a coding region is required but the transcript has none, or its strand
+        /// is unknown.
+        UnspecifiedCoding(tid: Option<String>) {
+            description("transcript has no coding region with a known strand")
+            display(self_) -> ("{}, transcript ID: {}",
+                               self_.description(), tid.as_deref().unwrap_or(DEF_ID))
+        }
+        /// Occurs when a range of protein residues is empty or exceeds the coding region.
+        InvalidResidueRange(tid: Option<String>) {
+            description("invalid protein residue range")
+            display(self_) -> ("{}, transcript ID: {}",
+                               self_.description(), tid.as_deref().unwrap_or(DEF_ID))
+        }
+        /// Occurs when a fusion breakpoint does not lie in an exon of its transcript.
+        InvalidBreakpoint(tid: Option<String>) {
+            description("fusion breakpoint not in an exon")
+            display(self_) -> ("{}, transcript ID: {}",
+                               self_.description(), tid.as_deref().unwrap_or(DEF_ID))
+        }
         /// Occurs when the first and/or last exon coordinates do not match the transcript
         /// coordinates.
         UnmatchedExons(tid: Option<String>) {
@@ -1008,6 +2568,56 @@ quick_error! {
             display(self_) -> ("{}, transcript ID: {}",
                                self_.description(), tid.as_deref().unwrap_or(DEF_ID))
         }
+        /// Occurs when a transcript without any identifier is added to a gene.
+        UnidentifiedTranscript {
+            description("transcript added to gene has no identifier")
+        }
+        /// Occurs when a transcript added to a gene has a different sequence name than the gene.
+        UnmatchedSeqName(tid: Option<String>) {
+            description("transcript sequence name differs from gene sequence name")
+            display(self_) -> ("{}, transcript ID: {}",
+                               self_.description(), tid.as_deref().unwrap_or(DEF_ID))
+        }
+        /// Occurs when a transcript added to a gene has a different strand than the gene.
+        UnmatchedStrand(tid: Option<String>) {
+            description("transcript strand differs from gene strand")
+            display(self_) -> ("{}, transcript ID: {}",
+                               self_.description(), tid.as_deref().unwrap_or(DEF_ID))
+        }
+        /// Occurs when a transcript added to a gene has an identifier already used in the gene.
+        DuplicateTranscriptId(tid: Option<String>) {
+            description("transcript identifier already present in gene")
+            display(self_) -> ("{}, transcript ID: {}",
+                               self_.description(), tid.as_deref().unwrap_or(DEF_ID))
+        }
+        /// Occurs when an exon added to or built with a transcript has a different sequence
+        /// name or strand than the transcript.
+        UnmatchedExon(tid: Option<String>) {
+            description("exon sequence name or strand differs from transcript")
+            display(self_) -> ("{}, transcript ID: {}",
+                               self_.description(), tid.as_deref().unwrap_or(DEF_ID))
+        }
+        /// Occurs when a coding region is given for a transcript with an unknown strand and
+        /// `UnknownStrandCoding::Error` is set.
+        CodingOnUnknownStrand(tid: Option<String>) {
+            description("coding region given for transcript with unknown strand")
+            display(self_) -> ("{}, transcript ID: {}",
+                               self_.description(), tid.as_deref().unwrap_or(DEF_ID))
+        }
+        /// Occurs when an exon added to or built with a transcript overlaps one of its exons.
+        OverlappingExon(tid: Option<String>) {
+            description("exon overlaps an existing exon of transcript")
+            display(self_) -> ("{}, transcript ID: {}",
+                               self_.description(), tid.as_deref().unwrap_or(DEF_ID))
+        }
+        /// Occurs when the declared frame of a CDS differs from the frame computed from the
+        /// coding region.
+        FrameMismatch(tid: Option<String>, start: u64, end: u64, declared: u8, computed: u8) {
+            description("declared CDS frame differs from computed frame")
+            display(self_) -> ("{}, transcript ID: {}, CDS: {}-{}, declared: {}, computed: {}",
+                               self_.description(), tid.as_deref().unwrap_or(DEF_ID),
+                               start, end, declared, computed)
+        }
     }
 }
 
@@ -1034,16 +2644,18 @@ fn resolve_strand_input(
 
 /// Resolves the exon inputs given to a `TBuilder`.
 fn resolve_exons_input(
-    transcript_seqname: &String,
+    transcript_seqname: &Arc<str>,
     transcript_interval: &Interval<u64>,
     transcript_strand: &Strand,
     transcript_id: Option<&str>,
     gene_id: Option<&str>,
-    exon_id: Option<&str>,
     exons: Option<Vec<Exon>>,
     exon_coords: Option<&Vec<Coord<u64>>>,
     coding_coord: Option<Coord<u64>>,
-    coding_incl_stop: bool
+    coding_incl_stop: bool,
+    cds_incomplete: (bool, bool),
+    truncation: CodonTruncation,
+    validation: ValidationMode,
 ) -> Result<Vec<Exon>, ModelError>
 {
     match (exons, exon_coords, coding_coord) {
@@ -1056,25 +2668,81 @@ fn resolve_exons_input(
 
         // features defined ~ takes precedence over coords (GTF input, since we need
         // to construct the tx features first to store its annotations)
-        // TODO: Maybe do some checks to ensure the given features are correct?
-        (Some(exns), _, _) => Ok(exns.into_iter().collect()),
+        (Some(exns), _, _) =>
+            check_exons(transcript_seqname, transcript_interval, transcript_strand, transcript_id,
+                        exns, validation),
 
         // exon defined & coords possibly defined (refFlat input)
         (None, Some(raw_exon_coords), raw_coding_coord) =>
             infer_exons(transcript_seqname, transcript_interval, transcript_strand, transcript_id,
-                        gene_id, exon_id, raw_exon_coords, raw_coding_coord, coding_incl_stop),
+                        gene_id, raw_exon_coords, raw_coding_coord, coding_incl_stop,
+                        cds_incomplete, truncation, validation),
     }
 }
 
+/// Checks the pre-built exons given to a `TBuilder`, sorting them by their coordinates.
+///
+/// The exons must have the same sequence name and strand as the transcript, must not overlap
+/// each other, and must span the transcript interval unless the validation mode is `Ignore`.
+fn check_exons(
+    transcript_seqname: &Arc<str>,
+    transcript_interval: &Interval<u64>,
+    transcript_strand: &Strand,
+    transcript_id: Option<&str>,
+    mut exons: Vec<Exon>,
+    validation: ValidationMode,
+) -> Result<Vec<Exon>, ModelError>
+{
+    let tid = transcript_id.map(|id| id.to_owned());
+
+    for exon in exons.iter_mut() {
+        if exon.seq_name() != &**transcript_seqname || exon.strand() != transcript_strand {
+            return Err(ModelError::UnmatchedExon(tid));
+        }
+        exon.seq_name = transcript_seqname.clone();
+    }
+    exons.sort_by_key(|exon| (exon.start(), exon.end()));
+    if exons.windows(2).any(|pair| pair[0].end() > pair[1].start()) {
+        return Err(ModelError::OverlappingExon(tid));
+    }
+
+    let exon_r = coords_span(exons.iter().map(|exon| (exon.start(), exon.end())));
+    if validation != ValidationMode::Ignore
+        && (exon_r.0 != transcript_interval.start || exon_r.1 != transcript_interval.end)
+    {
+        return Err(ModelError::UnmatchedExons(tid));
+    }
+    Ok(exons)
+}
+
+/// Helper function for computing the region spanned by nonempty coordinates.
+fn coords_span<I>(coords: I) -> Coord<u64>
+    where I: Iterator<Item=Coord<u64>>
+{
+    coords.fold(INIT_COORD, |(start, end), (a, b)| (min(start, a), max(end, b)))
+}
+
+/// Helper function for arranging per-exon values in the given order of exon indices.
+///
+/// Indices without a value, for inputs shorter than the number of exons, give `None`.
+fn reorder<T>(values: Vec<T>, order: &[usize]) -> Vec<Option<T>> {
+    let mut values = values.into_iter().map(Some).collect::<Vec<Option<T>>>();
+    order.iter()
+        .map(|&idx| values.get_mut(idx).and_then(|value| value.take()))
+        .collect()
+}
+
 /// Resolves the transcript inputs given to a `GBuilder`.
 fn resolve_transcripts_input(
-    gene_seqname: &String,
+    gene_seqname: &Arc<str>,
     gene_interval: &Interval<u64>,
     gene_strand: &Strand,
     gene_id: Option<&str>,
     transcripts: Option<LinkedHashMap<String, Transcript>>,
-    transcript_coords: Option<LinkedHashMap<String, RawTrxCoords>>,
-    transcript_coding_incl_stop: bool
+    transcript_coords: Option<LinkedHashMap<String, TranscriptCoords>>,
+    transcript_coding_incl_stop: bool,
+    validation: ValidationMode,
+    mut errors: Option<&mut Vec<::Error>>,
 ) -> ::Result<LinkedHashMap<String, Transcript>>
 {
     match (transcripts, transcript_coords) {
@@ -1087,48 +2755,125 @@ fn resolve_transcripts_input(
         // transcripts coords defined, create transcripts
         (None, Some(trxs_coords)) => {
             let mut trxs = LinkedHashMap::new();
-            for (trx_id, (trx_coord, exon_coords, coding_coord)) in trxs_coords.into_iter() {
-
-                if trx_coord.0 < gene_interval.start || trx_coord.1 > gene_interval.end {
-                    let tid = Some(trx_id);
-                    return Err(::Error::Model(ModelError::TranscriptNotFullyEnveloped(tid)));
-                }
-
-                let btrx = TBuilder::new(gene_seqname.clone(), trx_coord.0, trx_coord.1)
-                    .strand(*gene_strand)
-                    .id(trx_id.clone())
-                    .coords(exon_coords, coding_coord)
-                    .coding_incl_stop(transcript_coding_incl_stop);
-                let trx = match gene_id {
-                    Some(ref gid) => btrx
-                        .gene_id(gid.to_owned())
-                        .build()?,
-                    None => btrx.build()?
+            for (trx_id, coords) in trxs_coords.into_iter() {
+                let TranscriptCoords { interval: trx_coord, exons, exon_attributes, coding } =
+                    coords;
+
+                let trx = if validation != ValidationMode::Ignore
+                    && (trx_coord.0 < gene_interval.start || trx_coord.1 > gene_interval.end)
+                {
+                    let tid = Some(trx_id.clone());
+                    Err(::Error::Model(ModelError::TranscriptNotFullyEnveloped(tid)))
+                } else {
+                    let btrx = TBuilder::with_shared_seq_name(gene_seqname.clone(),
+                                                             trx_coord.0, trx_coord.1)
+                        .strand(*gene_strand)
+                        .id(trx_id.clone())
+                        .coords(exons, coding)
+                        .exon_attributes(exon_attributes)
+                        .coding_incl_stop(transcript_coding_incl_stop)
+                        .validation(validation);
+                    match gene_id {
+                        Some(ref gid) => btrx.gene_id(gid.to_owned()).build(),
+                        None => btrx.build(),
+                    }
                 };
                 // We do not expect any duplicate trx_id here, so the insert() operation
                 // result can be discarded.
-                let _ = trxs.insert(trx_id, trx);
+                if let Some(trx) = collect_error(trx, errors.as_mut().map(|errs| &mut **errs))? {
+                    let _ = trxs.insert(trx_id, trx);
+                }
             }
             Ok(trxs)
         },
     }
 }
 
+/// Helper function for either collecting or returning errors.
+///
+/// If a container is given, any error is added to it and `Ok(None)` is returned. Otherwise, the
+/// error is returned as is.
+fn collect_error<T>(result: ::Result<T>, errors: Option<&mut Vec<::Error>>)
+    -> ::Result<Option<T>>
+{
+    match (result, errors) {
+        (Ok(value), _) => Ok(Some(value)),
+        (Err(e), Some(errs)) => {
+            errs.push(e);
+            Ok(None)
+        },
+        (Err(e), None) => Err(e),
+    }
+}
+
+/// Checks that a transcript strand matches the strand of its gene.
+///
+/// Any transcript strand is accepted if the gene strand is unknown.
+fn check_gene_strand(
+    gene_strand: &Strand,
+    transcript: &Transcript,
+    trx_id: &str,
+) -> Result<(), ModelError>
+{
+    if *gene_strand != Strand::Unknown && transcript.strand() != gene_strand {
+        return Err(ModelError::UnmatchedStrand(Some(trx_id.to_owned())));
+    }
+    Ok(())
+}
+
+/// Validates a built transcript and adds it to the transcripts of a gene.
+fn add_gene_transcript(
+    transcripts: &mut LinkedHashMap<String, Transcript>,
+    gene_seqname: &Arc<str>,
+    gene_interval: &Interval<u64>,
+    gene_strand: &Strand,
+    gene_id: Option<&str>,
+    validation: ValidationMode,
+    mut transcript: Transcript,
+) -> Result<(), ModelError>
+{
+    let trx_id = transcript.id()
+        .map(|tid| tid.to_owned())
+        .ok_or(ModelError::UnidentifiedTranscript)?;
+
+    if transcript.seq_name() != &**gene_seqname {
+        return Err(ModelError::UnmatchedSeqName(Some(trx_id)));
+    }
+    check_gene_strand(gene_strand, &transcript, &trx_id)?;
+    if validation != ValidationMode::Ignore
+        && (transcript.start() < gene_interval.start || transcript.end() > gene_interval.end)
+    {
+        return Err(ModelError::TranscriptNotFullyEnveloped(Some(trx_id)));
+    }
+    if transcripts.contains_key(&trx_id) {
+        return Err(ModelError::DuplicateTranscriptId(Some(trx_id)));
+    }
+
+    if let Some(gid) = gene_id {
+        transcript.set_gene_id(Some(gid));
+    }
+    let _ = transcripts.insert(trx_id, transcript);
+    Ok(())
+}
+
 /// Infers exons and their features given coordinate values and identifiers.
 fn infer_exons(
-    transcript_seqname: &String,
+    transcript_seqname: &Arc<str>,
     transcript_interval: &Interval<u64>,
     transcript_strand: &Strand,
     transcript_id: Option<&str>,
     gene_id: Option<&str>,
-    exon_id: Option<&str>,
     exon_coords: &Vec<Coord<u64>>,
     coding_coord: Option<Coord<u64>>,
-    coding_incl_stop: bool
+    coding_incl_stop: bool,
+    cds_incomplete: (bool, bool),
+    truncation: CodonTruncation,
+    validation: ValidationMode,
 ) -> Result<Vec<Exon>, ModelError>
 {
 
     let tid = transcript_id.map(|id| id.to_owned());
+    let (cds_5_incomplete, cds_3_incomplete) = cds_incomplete;
 
     if exon_coords.len() == 0 {
         return Err(ModelError::UnspecifiedExons(tid));
@@ -1144,7 +2889,7 @@ fn infer_exons(
     m_exon_coords.sort();
 
     let adj_coding_coord =
-        if coding_incl_stop {
+        if coding_incl_stop && !cds_3_incomplete {
             coding_coord.and_then(|(a, b)| {
                 adjust_coding_coord(a, b, &transcript_strand, &m_exon_coords)
             })
@@ -1154,7 +2899,9 @@ fn infer_exons(
 
     let exon_r = (m_exon_coords.first().unwrap().0, m_exon_coords.last().unwrap().1);
 
-    if exon_r.0 != transcript_interval.start || exon_r.1 != transcript_interval.end {
+    if validation != ValidationMode::Ignore
+        && (exon_r.0 != transcript_interval.start || exon_r.1 != transcript_interval.end)
+    {
         return Err(ModelError::UnmatchedExons(tid));
     }
 
@@ -1178,18 +2925,25 @@ fn infer_exons(
             if !cine.0 || !cine.1 {
                 return Err(ModelError::CodingInIntron(tid));
             }
-            // There must be room for stop codons (which is not inclusive in coding_coord)
-            let stop_codon_ok = match transcript_strand {
+            // There must be room for stop codons (which is not inclusive in coding_coord),
+            // unless the coding region is incomplete at its 3' end or codons may be truncated
+            let stop_codon_fits = match transcript_strand {
                 &Strand::Forward => coding_r.1 + 3 <= exon_r.1,
-                &Strand::Reverse => coding_r.0 - 3 >= exon_r.0,
+                &Strand::Reverse => exon_r.0 + 3 <= coding_r.0,
                 &Strand::Unknown =>
-                    coding_r.0 - 3 >= exon_r.0 && coding_r.1 + 3 <= exon_r.1,
+                    exon_r.0 + 3 <= coding_r.0 && coding_r.1 + 3 <= exon_r.1,
             };
+            let stop_codon_ok = cds_3_incomplete || stop_codon_fits
+                || truncation.allows(coding_r, transcript_strand);
             if !stop_codon_ok {
                 return Err(ModelError::CodingTooLarge(tid));
             }
-            infer_exon_features(&m_exon_coords, coding_r, &transcript_seqname, transcript_strand,
-                                transcript_id, gene_id, exon_id)
+            let mut exons = infer_exon_features(&m_exon_coords, coding_r, &transcript_seqname,
+                                                transcript_strand, transcript_id, gene_id)?;
+            if cds_5_incomplete || cds_3_incomplete {
+                remove_codon_features(&mut exons, cds_5_incomplete, cds_3_incomplete);
+            }
+            Ok(exons)
         }
 
         // No CDS intervals mean we just sort the coordinates and create the exons
@@ -1201,10 +2955,10 @@ fn infer_exons(
                         seq_name: transcript_seqname.clone(),
                         interval: Interval::new(start..end).unwrap(),
                         strand: *transcript_strand,
-                        id: exon_id.map(|id| id.to_owned()),
+                        id: None,
                         transcript_id: tid.clone(),
                         gene_id: gene_id.map(|id| id.to_owned()),
-                        attributes: MultiMap::new(),
+                        attributes: Attributes::new(),
                         features: Vec::new(),
                     });
             }
@@ -1213,6 +2967,100 @@ fn infer_exons(
     }
 }
 
+/// Policy for stop codons that do not fit in the exons of a transcript.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CodonTruncation {
+    /// Stop codons must fit in the exons.
+    Never,
+    /// Stop codons may be truncated at the ends of the exons.
+    AtExonEnds,
+    /// Stop codons may only be truncated where they extend past the ends of a sequence of the
+    /// given length.
+    AtSeqEnds(u64),
+}
+
+impl CodonTruncation {
+
+    /// Returns whether the stop codon of the given coding region may be truncated.
+    fn allows(&self, coding_r: Coord<u64>, strand: &Strand) -> bool {
+        match (*self, strand) {
+            (CodonTruncation::Never, _) => false,
+            (CodonTruncation::AtExonEnds, _) => true,
+            (CodonTruncation::AtSeqEnds(seq_len), &Strand::Forward) => coding_r.1 + 3 > seq_len,
+            (CodonTruncation::AtSeqEnds(_), &Strand::Reverse) => coding_r.0 < 3,
+            (CodonTruncation::AtSeqEnds(seq_len), &Strand::Unknown) =>
+                coding_r.0 < 3 || coding_r.1 + 3 > seq_len,
+        }
+    }
+}
+
+/// Returns whether the given exons have a coding region whose stop codon features span less
+/// than a full codon.
+fn stop_codon_truncated(exons: &[Exon], strand: &Strand) -> bool {
+    if strand == &Strand::Unknown {
+        return false;
+    }
+    let (mut has_cds, mut stop_span) = (false, 0);
+    for fx in exons.iter().flat_map(|exon| exon.features.iter()) {
+        match fx.kind {
+            CDS { .. } => has_cds = true,
+            StopCodon { .. } => stop_span += fx.span(),
+            _ => {},
+        }
+    }
+    has_cds && stop_span < 3
+}
+
+/// Removes the start and/or stop codon features of incomplete coding regions.
+///
+/// The bases of removed stop codons become part of the 3' UTR, while the bases of removed start
+/// codons stay covered by their CDS features.
+fn remove_codon_features(exons: &mut Vec<Exon>, cds_5_incomplete: bool, cds_3_incomplete: bool) {
+    for exon in exons.iter_mut() {
+        let mut features: Vec<ExonFeature> = Vec::with_capacity(exon.features.len());
+        for mut fx in exon.features.drain(..) {
+            match fx.kind {
+                StartCodon { .. } if cds_5_incomplete => continue,
+                StopCodon { .. } if cds_3_incomplete => fx.kind = UTR3,
+                _ => {},
+            }
+            if let Some(last) = features.last_mut() {
+                if last.kind == fx.kind && last.end() == fx.start() {
+                    last.interval = Interval::new(last.start()..fx.end()).unwrap();
+                    continue;
+                }
+            }
+            features.push(fx);
+        }
+        exon.features = features;
+    }
+}
+
+/// Merges the given sorted coordinates that abut each other.
+///
+/// Returns `None` if any coordinate is empty, or if the coordinates are unsorted or overlapping.
+fn merge_abutting<I>(coords: I) -> Option<Vec<Coord<u64>>>
+    where I: IntoIterator<Item=Coord<u64>>
+{
+    let mut merged: Vec<Coord<u64>> = Vec::new();
+    for (start, end) in coords {
+        if start >= end {
+            return None;
+        }
+        if let Some(last) = merged.last_mut() {
+            if start < last.1 {
+                return None;
+            }
+            if start == last.1 {
+                last.1 = end;
+                continue;
+            }
+        }
+        merged.push((start, end));
+    }
+    Some(merged)
+}
+
 /// Adjusts the given coding coordinates so that the stop codon is excluded.
 fn adjust_coding_coord(mut start: u64, mut end: u64,
                        strand: &Strand, exon_coords: &Vec<Coord<u64>>
@@ -1223,7 +3071,7 @@ fn adjust_coding_coord(mut start: u64, mut end: u64,
         &Strand::Forward => {
             for &(exon_start, exon_end) in exon_coords.iter().rev() {
                 if exon_start <= end && end <= exon_end {
-                    let adj_end = max(end - codon_rem, exon_start);
+                    let adj_end = max(end.saturating_sub(codon_rem), exon_start);
                     codon_rem -= end - adj_end;
                     end = adj_end;
                     if codon_rem == 0 {
@@ -1249,6 +3097,173 @@ fn adjust_coding_coord(mut start: u64, mut end: u64,
     Some((start, end))
 }
 
+/// Minimum number of interned sequence names before unused names are removed.
+const MIN_INTERN_PRUNE_LEN: usize = 1024;
+
+thread_local! {
+    /// Sequence names shared by all models created in the current thread, along with the number
+    /// of names at which unused names are next removed.
+    static SEQ_NAMES: RefCell<(HashSet<Arc<str>>, usize)> =
+        RefCell::new((HashSet::new(), MIN_INTERN_PRUNE_LEN));
+}
+
+/// Returns the shared instance of the given sequence name, adding it if not yet present.
+///
+/// Annotations usually contain many features on only a few sequences, so sharing the names
+/// avoids storing the same sequence name for every gene, transcript, and exon. Names that are no
+/// longer used by any model are removed whenever the number of names doubles, so the names kept
+/// by a thread are bounded by the names of its live models rather than by all names it has
+/// ever seen.
+fn intern_seq_name(seq_name: String) -> Arc<str> {
+    SEQ_NAMES.with(|cell| {
+        let mut cell = cell.borrow_mut();
+        let &mut (ref mut seq_names, ref mut prune_len) = &mut *cell;
+        if let Some(shared) = seq_names.get(seq_name.as_str()) {
+            return shared.clone();
+        }
+        if seq_names.len() >= *prune_len {
+            // names only referenced by the set are not used by any model
+            seq_names.retain(|name| Arc::strong_count(name) > 1);
+            *prune_len = max(MIN_INTERN_PRUNE_LEN, seq_names.len() * 2);
+        }
+        let shared: Arc<str> = Arc::from(seq_name);
+        let _ = seq_names.insert(shared.clone());
+        shared
+    })
+}
+
+/// Serialization helpers for model fields, for use with `#[serde(with = "...")]`.
+#[cfg(feature = "serde")]
+mod serde_impls {
+
+    /// Serialization of sequence names, sharing the deserialized names.
+    pub mod seq_name {
+        use std::sync::Arc;
+
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        /// Serializes the sequence name as a string.
+        pub fn serialize<S>(seq_name: &Arc<str>, serializer: S) -> Result<S::Ok, S::Error>
+            where S: Serializer
+        {
+            serializer.serialize_str(seq_name)
+        }
+
+        /// Deserializes the sequence name, sharing it with other models.
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Arc<str>, D::Error>
+            where D: Deserializer<'de>
+        {
+            String::deserialize(deserializer).map(super::super::intern_seq_name)
+        }
+    }
+
+    /// Serialization of intervals as `[start, end]` pairs.
+    pub mod interval {
+        use bio::utils::Interval;
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+        use serde::de::Error;
+
+        /// Serializes the interval as a `[start, end]` pair.
+        pub fn serialize<S>(interval: &Interval<u64>, serializer: S) -> Result<S::Ok, S::Error>
+            where S: Serializer
+        {
+            (interval.start, interval.end).serialize(serializer)
+        }
+
+        /// Deserializes the interval from a `[start, end]` pair.
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Interval<u64>, D::Error>
+            where D: Deserializer<'de>
+        {
+            let (start, end) = <(u64, u64)>::deserialize(deserializer)?;
+            Interval::new(start..end)
+                .map_err(|_| D::Error::custom(format!("invalid interval: [{}, {}]", start, end)))
+        }
+    }
+
+    /// Serialization of attributes as maps of keys to lists of values, in their stored order.
+    pub mod attributes {
+        use std::fmt;
+
+        use serde::{Deserializer, Serializer};
+        use serde::de::{MapAccess, Visitor};
+        use serde::ser::SerializeMap;
+
+        use Attributes;
+
+        /// Serializes the attributes as a map of keys to lists of values.
+        pub fn serialize<S>(attributes: &Attributes, serializer: S)
+            -> Result<S::Ok, S::Error>
+            where S: Serializer
+        {
+            let mut map = serializer.serialize_map(Some(attributes.len()))?;
+            for (key, values) in attributes.iter_all() {
+                map.serialize_entry(key, values)?;
+            }
+            map.end()
+        }
+
+        /// Deserializes the attributes from a map of keys to lists of values.
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Attributes, D::Error>
+            where D: Deserializer<'de>
+        {
+            deserializer.deserialize_map(AttributesVisitor)
+        }
+
+        /// Visitor that keeps the order of the deserialized keys.
+        struct AttributesVisitor;
+
+        impl<'de> Visitor<'de> for AttributesVisitor {
+            type Value = Attributes;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a map of keys to lists of values")
+            }
+
+            fn visit_map<A>(self, mut access: A) -> Result<Attributes, A::Error>
+                where A: MapAccess<'de>
+            {
+                let mut attributes = Attributes::new();
+                while let Some((key, values)) = access.next_entry::<String, Vec<String>>()? {
+                    for value in values {
+                        attributes.insert(key.clone(), value);
+                    }
+                }
+                Ok(attributes)
+            }
+        }
+    }
+
+    /// Serialization of gene transcripts as lists of `[id, transcript]` pairs.
+    pub mod transcripts {
+        use linked_hash_map::LinkedHashMap;
+        use serde::{Deserialize, Deserializer, Serializer};
+        use serde::ser::SerializeSeq;
+
+        use Transcript;
+
+        /// Serializes the transcripts as a list of `[id, transcript]` pairs.
+        pub fn serialize<S>(transcripts: &LinkedHashMap<String, Transcript>, serializer: S)
+            -> Result<S::Ok, S::Error>
+            where S: Serializer
+        {
+            let mut seq = serializer.serialize_seq(Some(transcripts.len()))?;
+            for entry in transcripts.iter() {
+                seq.serialize_element(&entry)?;
+            }
+            seq.end()
+        }
+
+        /// Deserializes the transcripts from a list of `[id, transcript]` pairs.
+        pub fn deserialize<'de, D>(deserializer: D)
+            -> Result<LinkedHashMap<String, Transcript>, D::Error>
+            where D: Deserializer<'de>
+        {
+            Vec::<(String, Transcript)>::deserialize(deserializer)
+                .map(|entries| entries.into_iter().collect())
+        }
+    }
+}
+
 /// Helper function to create an interval from start and end coordinates.
 #[inline(always)]
 fn coord_to_interval(start: u64, end: u64) -> Result<Interval<u64>, ModelError> {
@@ -1261,11 +3276,10 @@ fn coord_to_interval(start: u64, end: u64) -> Result<Interval<u64>, ModelError>
 fn infer_exon_features(
     exon_coords: &Vec<Coord<u64>>,
     coding_r: Coord<u64>,
-    transcript_seqname: &String,
+    transcript_seqname: &Arc<str>,
     transcript_strand: &Strand,
     transcript_id: Option<&str>,
     gene_id: Option<&str>,
-    exon_id: Option<&str>,
 ) -> Result<Vec<Exon>, ModelError> {
 
     let mut exons: Vec<Exon> = Vec::with_capacity(exon_coords.len() * 2 + 4);
@@ -1279,10 +3293,10 @@ fn infer_exon_features(
             seq_name: transcript_seqname.clone(),
             interval: Interval::new(start..end).unwrap(),
             strand: *transcript_strand,
-            id: exon_id.map(|v| v.to_owned()),
+            id: None,
             transcript_id: transcript_id.map(|v| v.to_owned()),
             gene_id: gene_id.map(|v| v.to_owned()),
-            attributes: MultiMap::new(),
+            attributes: Attributes::new(),
             features: features,
         }
     };
@@ -1304,7 +3318,7 @@ fn infer_exon_features(
             let mut exon = exn(start, end, vec![]);
             let utr_end =
                 if let &Strand::Reverse = transcript_strand {
-                    min(end, coding_r.0 - codon1_rem)
+                    min(end, coding_r.0.saturating_sub(codon1_rem))
                 } else {
                     min(end, coding_r.0)
                 };
@@ -1317,7 +3331,7 @@ fn infer_exon_features(
 
             } else if end == coding_r.0 {
                 if let &Strand::Reverse = transcript_strand {
-                    let fx = feat(max(start, coding_r.0 - codon1_rem), coding_r.0,
+                    let fx = feat(max(start, coding_r.0.saturating_sub(codon1_rem)), coding_r.0,
                                   StopCodon { frame: None });
                     codon1_rem -= fx.span();
                     exon.features.push(fx);
@@ -1335,8 +3349,8 @@ fn infer_exon_features(
                         exon.features.push(fx);
                     },
                     &Strand::Reverse => {
-                        let fx = feat(max(start, coding_r.0 - codon1_rem), coding_r.0,
-                                      StopCodon { frame: None });
+                        let fx = feat(max(start, coding_r.0.saturating_sub(codon1_rem)),
+                                      coding_r.0, StopCodon { frame: None });
                         codon1_rem -= fx.span();
                         exon.features.push(fx);
                         codon1_rem = backtrack_and_push(&mut exons, StopCodon { frame: None },
@@ -1360,8 +3374,8 @@ fn infer_exon_features(
                         exon.features.push(feat(coding_r.0, coding_r.1, CDS { frame: None }));
                     },
                     &Strand::Reverse => {
-                        let fx = feat(max(coding_r.0 - codon1_rem, start), coding_r.0,
-                                      StopCodon { frame: None });
+                        let fx = feat(max(start, coding_r.0.saturating_sub(codon1_rem)),
+                                      coding_r.0, StopCodon { frame: None });
                         codon1_rem -= fx.span();
                         exon.features.push(fx);
                         codon1_rem = backtrack_and_push(&mut exons, StopCodon { frame: None },
@@ -1401,8 +3415,8 @@ fn infer_exon_features(
                         }
                     },
                     &Strand::Reverse => {
-                        let fx = feat(max(start, coding_r.0 - codon2_rem), coding_r.0,
-                                      StopCodon { frame: None });
+                        let fx = feat(max(start, coding_r.0.saturating_sub(codon2_rem)),
+                                      coding_r.0, StopCodon { frame: None });
                         codon1_rem -= fx.span();
                         exon.features.push(fx);
                         codon1_rem = backtrack_and_push(&mut exons, StopCodon { frame: None },
@@ -1651,7 +3665,7 @@ where F: Fn(u64, u64, ExonFeatureKind) -> ExonFeature
         if codon_rem == 0 {
             break;
         };
-        let fx = feature_maker(max(exon.start(), exon.end() - codon_rem),
+        let fx = feature_maker(max(exon.start(), exon.end().saturating_sub(codon_rem)),
                                exon.end(), efk.clone());
         codon_rem -= fx.span();
         let ofxp_start = exon.features.last()
@@ -0,0 +1,116 @@
+/*! Open reading frames of transcripts.
+
+Noncoding transcripts, such as lncRNAs or transcripts from de novo assemblies, may still contain
+open reading frames. This module finds them in the spliced transcript sequence: an open reading
+frame starts with an `ATG` codon and ends with the first in-frame `TAA`, `TAG`, or `TGA` codon.
+Only the most upstream start codon is used for each stop codon, and frames without a stop codon
+before the 3' end of the transcript are left out.
+
+The genome-wise coordinates of an open reading frame include its stop codon, so they can be used
+as the coding coordinate of a transcript built with `TBuilder::coding_incl_stop(true)`.
+*/
+use std::cmp::Reverse;
+
+use {Coord, Genome, Strand, Transcript};
+
+
+/// Codon that starts open reading frames.
+const START_CODON: &'static [u8] = b"ATG";
+
+/// Codons that end open reading frames.
+const STOP_CODONS: [&'static [u8]; 3] = [b"TAA", b"TAG", b"TGA"];
+
+/// Open reading frame of a spliced transcript.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Orf {
+    tx_start: u64,
+    tx_end: u64,
+    coding_coord: Coord<u64>,
+}
+
+impl Orf {
+
+    /// Returns the offset of the first base of the start codon along the spliced transcript.
+    ///
+    /// Offsets are zero-based and counted from the 5' end of the transcript.
+    pub fn tx_start(&self) -> u64 {
+        self.tx_start
+    }
+
+    /// Returns the offset past the last base of the stop codon along the spliced transcript.
+    pub fn tx_end(&self) -> u64 {
+        self.tx_end
+    }
+
+    /// Returns the genome-wise coding coordinate of the open reading frame, including the stop
+    /// codon.
+    pub fn coding_coord(&self) -> Coord<u64> {
+        self.coding_coord
+    }
+
+    /// Returns the length of the open reading frame along the spliced transcript, excluding the
+    /// stop codon.
+    pub fn coding_len(&self) -> u64 {
+        self.tx_end - self.tx_start - 3
+    }
+}
+
+impl Transcript {
+
+    /// Returns the sequence of the spliced transcript, read from its 5' end.
+    ///
+    /// Transcripts with an unknown strand are read as if they were on the forward strand. The
+    /// returned value is `None` if any exon is outside of the genome's sequences.
+    pub fn spliced_seq(&self, genome: &Genome) -> Option<Vec<u8>> {
+        let mut exon_seqs = Vec::with_capacity(self.exons().len());
+        for exon in self.exons().iter() {
+            exon_seqs.push(genome.stranded_subseq(self.seq_name(), exon.start(), exon.end(),
+                                                  self.strand())?);
+        }
+        if self.strand() == &Strand::Reverse {
+            exon_seqs.reverse();
+        }
+        Some(exon_seqs.concat())
+    }
+
+    /// Returns the open reading frames of the transcript whose length, excluding the stop
+    /// codon, is at least `min_len` bases.
+    ///
+    /// The open reading frames are sorted from the longest to the shortest, with ties sorted by
+    /// their offsets along the spliced transcript. The coding region of the transcript, if any,
+    /// is ignored. The returned value is `None` if the transcript sequence can not be read from
+    /// the genome.
+    pub fn find_orfs(&self, genome: &Genome, min_len: u64) -> Option<Vec<Orf>> {
+        let seq = self.spliced_seq(genome)?.to_ascii_uppercase();
+        let mut orfs = Vec::new();
+        for frame in 0..3 {
+            let mut start = None;
+            let mut offset = frame;
+            while offset + 3 <= seq.len() {
+                let codon = &seq[offset..offset + 3];
+                if start.is_none() && codon == START_CODON {
+                    start = Some(offset);
+                } else if STOP_CODONS.contains(&codon) {
+                    if let Some(orf_start) = start.take() {
+                        if (offset - orf_start) as u64 >= min_len {
+                            orfs.push(self.orf((orf_start as u64, offset as u64 + 3)));
+                        }
+                    }
+                }
+                offset += 3;
+            }
+        }
+        orfs.sort_by_key(|orf| (Reverse(orf.coding_len()), orf.tx_start));
+        Some(orfs)
+    }
+
+    /// Creates an open reading frame from its range of offsets along the spliced transcript.
+    fn orf(&self, (tx_start, tx_end): Coord<u64>) -> Orf {
+        let blocks = self.project_interval(tx_start, tx_end);
+        Orf {
+            tx_start: tx_start,
+            tx_end: tx_end,
+            coding_coord: (blocks[0].0, blocks[blocks.len() - 1].1),
+        }
+    }
+}
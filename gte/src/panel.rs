@@ -0,0 +1,390 @@
+/*! Coverage of gene models by sequencing panel targets.
+
+Targets are read from BED files, of which only the first three columns are used. Overlapping and
+abutting targets are merged, so bases covered by several targets are counted once.
+
+Coverage is reported per gene, per transcript, and per exon. Gene-level values count the bases of
+the merged exons of all transcripts, while transcript-level values count the bases of the exons
+of the transcript. CDS bases include the stop codon. Exons are numbered from the 5' end of their
+transcripts, as in clinical reports, and list the bases not covered by any target as gaps.
+*/
+use std::cmp::{max, min};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+use std::str::FromStr;
+
+use {AnnotationSet, Coord, Gene, Strand, Transcript};
+
+
+quick_error! {
+    /// Errors that occur when reading panel targets.
+    #[derive(Debug)]
+    pub enum PanelError {
+        /// Occurs when a line does not contain the expected columns or values.
+        InvalidTarget(line_num: usize) {
+            description("invalid target record")
+            display(self_) -> ("{}, line: {}", self_.description(), line_num)
+        }
+        /// Errors propagated from reading the underlying file.
+        Io(err: io::Error) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// Merged target regions of a sequencing panel.
+#[derive(Debug, Clone, Default)]
+pub struct PanelTargets {
+    regions: HashMap<String, Vec<Coord<u64>>>,
+}
+
+impl PanelTargets {
+
+    /// Creates an empty set of targets.
+    pub fn new() -> Self {
+        PanelTargets::default()
+    }
+
+    /// Reads targets from the given BED reader.
+    ///
+    /// Empty lines, comment lines, and `track` and `browser` lines are ignored.
+    pub fn from_reader<R: io::Read>(in_reader: R) -> ::Result<PanelTargets> {
+        let mut targets = PanelTargets::new();
+        for (idx, line) in BufReader::new(in_reader).lines().enumerate() {
+            let line = line.map_err(|e| ::Error::from(PanelError::from(e)))?;
+            if line.trim().is_empty() || line.starts_with('#') || line.starts_with("track")
+                || line.starts_with("browser")
+            {
+                continue;
+            }
+            let (seq_name, start, end) = parse_line(&line)
+                .ok_or(::Error::from(PanelError::InvalidTarget(idx + 1)))?;
+            targets.add(seq_name, start, end);
+        }
+        Ok(targets)
+    }
+
+    /// Reads targets from the BED file in the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> ::Result<PanelTargets> {
+        let file = fs::File::open(path)
+            .map_err(|e| ::Error::from(PanelError::from(e)))?;
+        PanelTargets::from_reader(file)
+    }
+
+    /// Adds the given zero-based, half-open target region.
+    ///
+    /// Empty regions are ignored.
+    pub fn add<T>(&mut self, seq_name: T, start: u64, end: u64)
+        where T: Into<String>
+    {
+        if start >= end {
+            return;
+        }
+        let regions = self.regions.entry(seq_name.into()).or_insert_with(Vec::new);
+        let idx = match regions.binary_search(&(start, end)) {
+            Ok(_) => return,
+            Err(idx) => idx,
+        };
+        regions.insert(idx, (start, end));
+        let merged = merge_sorted(regions.as_slice());
+        *regions = merged;
+    }
+
+    /// Returns the merged target regions of the given sequence, sorted by their start
+    /// coordinates.
+    pub fn regions(&self, seq_name: &str) -> &[Coord<u64>] {
+        self.regions.get(seq_name).map(|regions| regions.as_slice()).unwrap_or(&[])
+    }
+
+    /// Returns the number of merged target regions.
+    pub fn len(&self) -> usize {
+        self.regions.values().map(|regions| regions.len()).sum()
+    }
+
+    /// Returns whether there are no target regions.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the total number of targeted bases.
+    pub fn num_bases(&self) -> u64 {
+        self.regions.values()
+            .flat_map(|regions| regions.iter())
+            .map(|&(start, end)| end - start)
+            .sum()
+    }
+
+    /// Returns the number of targeted bases in the given region, along with the parts of the
+    /// region outside of the targets.
+    fn cover(&self, seq_name: &str, start: u64, end: u64) -> (u64, Vec<Coord<u64>>) {
+        let regions = self.regions(seq_name);
+        // targets are merged, so their end coordinates are sorted as well
+        let first = match regions.binary_search_by(|&(_, tend)| tend.cmp(&start)) {
+            Ok(idx) => idx + 1,
+            Err(idx) => idx,
+        };
+        let mut covered = 0;
+        let mut gaps = Vec::new();
+        let mut pos = start;
+        for &(tstart, tend) in regions[first..].iter().take_while(|&&(tstart, _)| tstart < end) {
+            if tstart > pos {
+                gaps.push((pos, tstart));
+            }
+            covered += min(tend, end) - max(tstart, start);
+            pos = max(pos, tend);
+        }
+        if pos < end {
+            gaps.push((pos, end));
+        }
+        (covered, gaps)
+    }
+}
+
+/// Coverage of an exon by panel targets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExonCoverage {
+    number: usize,
+    start: u64,
+    end: u64,
+    covered: u64,
+    gaps: Vec<Coord<u64>>,
+}
+
+impl ExonCoverage {
+
+    /// Returns the one-based number of the exon, counted from the 5' end of its transcript.
+    pub fn number(&self) -> usize {
+        self.number
+    }
+
+    /// Returns the zero-based start coordinate of the exon.
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// Returns the end coordinate of the exon.
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// Returns the number of exon bases covered by targets.
+    pub fn covered(&self) -> u64 {
+        self.covered
+    }
+
+    /// Returns the fraction of exon bases covered by targets.
+    pub fn fraction(&self) -> f64 {
+        fraction(self.covered, self.end - self.start)
+    }
+
+    /// Returns the genome-wise coordinates of the exon parts not covered by any target, sorted
+    /// by their start coordinates.
+    pub fn gaps(&self) -> &[Coord<u64>] {
+        self.gaps.as_slice()
+    }
+}
+
+/// Coverage of a transcript by panel targets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranscriptCoverage {
+    transcript_id: Option<String>,
+    exonic: Coord<u64>,
+    cds: Option<Coord<u64>>,
+    exons: Vec<ExonCoverage>,
+}
+
+impl TranscriptCoverage {
+
+    /// Returns the identifier of the transcript.
+    pub fn transcript_id(&self) -> Option<&str> {
+        self.transcript_id.as_ref().map(|id| id.as_str())
+    }
+
+    /// Returns the number of exonic bases covered by targets and the total number of exonic
+    /// bases.
+    pub fn exonic_bases(&self) -> Coord<u64> {
+        self.exonic
+    }
+
+    /// Returns the fraction of exonic bases covered by targets.
+    pub fn exonic_fraction(&self) -> f64 {
+        fraction(self.exonic.0, self.exonic.1)
+    }
+
+    /// Returns the number of CDS bases covered by targets and the total number of CDS bases.
+    ///
+    /// The returned value is `None` for noncoding transcripts.
+    pub fn cds_bases(&self) -> Option<Coord<u64>> {
+        self.cds
+    }
+
+    /// Returns the fraction of CDS bases covered by targets.
+    ///
+    /// The returned value is `None` for noncoding transcripts.
+    pub fn cds_fraction(&self) -> Option<f64> {
+        self.cds.map(|(covered, total)| fraction(covered, total))
+    }
+
+    /// Returns the coverage of each exon, sorted by their start coordinates.
+    pub fn exons(&self) -> &[ExonCoverage] {
+        self.exons.as_slice()
+    }
+}
+
+/// Coverage of a gene by panel targets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneCoverage {
+    gene_id: Option<String>,
+    exonic: Coord<u64>,
+    cds: Option<Coord<u64>>,
+    transcripts: Vec<TranscriptCoverage>,
+}
+
+impl GeneCoverage {
+
+    /// Returns the identifier of the gene.
+    pub fn gene_id(&self) -> Option<&str> {
+        self.gene_id.as_ref().map(|id| id.as_str())
+    }
+
+    /// Returns the number of merged exonic bases covered by targets and the total number of
+    /// merged exonic bases.
+    pub fn exonic_bases(&self) -> Coord<u64> {
+        self.exonic
+    }
+
+    /// Returns the fraction of merged exonic bases covered by targets.
+    pub fn exonic_fraction(&self) -> f64 {
+        fraction(self.exonic.0, self.exonic.1)
+    }
+
+    /// Returns the number of merged CDS bases covered by targets and the total number of
+    /// merged CDS bases.
+    ///
+    /// The returned value is `None` for genes without coding transcripts.
+    pub fn cds_bases(&self) -> Option<Coord<u64>> {
+        self.cds
+    }
+
+    /// Returns the fraction of merged CDS bases covered by targets.
+    ///
+    /// The returned value is `None` for genes without coding transcripts.
+    pub fn cds_fraction(&self) -> Option<f64> {
+        self.cds.map(|(covered, total)| fraction(covered, total))
+    }
+
+    /// Returns the coverage of each transcript, in the order of the gene's transcripts.
+    pub fn transcripts(&self) -> &[TranscriptCoverage] {
+        self.transcripts.as_slice()
+    }
+}
+
+impl AnnotationSet {
+
+    /// Returns the coverage of each gene by the given panel targets, in the order of the
+    /// genes.
+    pub fn panel_coverage(&self, targets: &PanelTargets) -> Vec<GeneCoverage> {
+        self.genes().iter().map(|gene| gene_coverage(gene, targets)).collect()
+    }
+}
+
+/// Helper function for computing the coverage of a gene.
+fn gene_coverage(gene: &Gene, targets: &PanelTargets) -> GeneCoverage {
+    let mut exons = gene.transcripts().values()
+        .flat_map(|trx| trx.exons().iter().map(|exon| (exon.start(), exon.end())))
+        .collect::<Vec<Coord<u64>>>();
+    exons.sort();
+    let cds = gene.coding_exon_coords();
+    let seq_name = gene.seq_name();
+    GeneCoverage {
+        gene_id: gene.id().map(|id| id.to_owned()),
+        exonic: count_covered(seq_name, &merge_sorted(&exons), targets),
+        cds: if cds.is_empty() { None } else { Some(count_covered(seq_name, &cds, targets)) },
+        transcripts: gene.transcripts().values()
+            .map(|trx| transcript_coverage(trx, targets))
+            .collect(),
+    }
+}
+
+/// Helper function for computing the coverage of a transcript.
+fn transcript_coverage(transcript: &Transcript, targets: &PanelTargets) -> TranscriptCoverage {
+    let seq_name = transcript.seq_name();
+    let num_exons = transcript.exons().len();
+    let exons = transcript.exons().iter().enumerate()
+        .map(|(idx, exon)| {
+            let (covered, gaps) = targets.cover(seq_name, exon.start(), exon.end());
+            ExonCoverage {
+                number: match transcript.strand() {
+                    &Strand::Reverse => num_exons - idx,
+                    _ => idx + 1,
+                },
+                start: exon.start(),
+                end: exon.end(),
+                covered: covered,
+                gaps: gaps,
+            }
+        })
+        .collect::<Vec<ExonCoverage>>();
+    let exonic = exons.iter()
+        .fold((0, 0), |acc, exon| (acc.0 + exon.covered, acc.1 + exon.end - exon.start));
+    let cds = transcript.coding_exon_coords();
+    TranscriptCoverage {
+        transcript_id: transcript.id().map(|id| id.to_owned()),
+        exonic: exonic,
+        cds: if cds.is_empty() { None } else { Some(count_covered(seq_name, &cds, targets)) },
+        exons: exons,
+    }
+}
+
+/// Helper function for counting the covered bases of sorted, nonoverlapping regions, along with
+/// their total number of bases.
+fn count_covered(seq_name: &str, regions: &[Coord<u64>], targets: &PanelTargets) -> Coord<u64> {
+    let mut counts = (0, 0);
+    for &(start, end) in regions.iter() {
+        counts.0 += targets.cover(seq_name, start, end).0;
+        counts.1 += end - start;
+    }
+    counts
+}
+
+/// Helper function for merging sorted regions that overlap or abut.
+fn merge_sorted(regions: &[Coord<u64>]) -> Vec<Coord<u64>> {
+    let mut merged: Vec<Coord<u64>> = Vec::with_capacity(regions.len());
+    for &(start, end) in regions.iter() {
+        if let Some(last) = merged.last_mut() {
+            if start <= last.1 {
+                last.1 = max(last.1, end);
+                continue;
+            }
+        }
+        merged.push((start, end));
+    }
+    merged
+}
+
+/// Helper function for computing a fraction that is zero when the total is zero.
+fn fraction(covered: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        covered as f64 / total as f64
+    }
+}
+
+/// Helper function for parsing a single BED line.
+fn parse_line(line: &str) -> Option<(&str, u64, u64)> {
+    let cols = line.split('\t').collect::<Vec<&str>>();
+    if cols.len() < 3 || cols[0].is_empty() {
+        return None;
+    }
+    match (u64::from_str(cols[1]), u64::from_str(cols[2])) {
+        (Ok(start), Ok(end)) if start <= end => Some((cols[0], start, end)),
+        _ => None,
+    }
+}
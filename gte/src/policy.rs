@@ -0,0 +1,71 @@
+/*! Handling of malformed records.
+
+Annotation dumps from production databases routinely contain a few broken rows. By default,
+readers stop at the first malformed record and yield its error. An `ErrorPolicy::Skip` policy
+set through a reader's `on_error` method instead passes the line number and the error of each
+malformed record to a callback and carries on with the next record, so a handful of bad rows do
+not abort the whole stream:
+
+```
+use std::cell::RefCell;
+use std::rc::Rc;
+use gte::{ErrorPolicy, RefFlatReader};
+
+let data = "g1\tt1\tchr1\t+\t10\t50\t50\t50\t1\t10,\t50,\n\
+            g2\tt2\tchr1\t+\tten\t50\t50\t50\t1\t10,\t50,\n\
+            g3\tt3\tchr1\t+\t60\t90\t90\t90\t1\t60,\t90,\n";
+let skipped = Rc::new(RefCell::new(Vec::new()));
+let mut reader = RefFlatReader::from_reader(data.as_bytes());
+let log = skipped.clone();
+reader.on_error(ErrorPolicy::Skip(Box::new(move |line_num, _| {
+    log.borrow_mut().push(line_num)
+})));
+let transcripts = reader.transcripts_stream().collect::<Result<Vec<_>, _>>().unwrap();
+assert_eq!(transcripts.len(), 2);
+assert_eq!(*skipped.borrow(), vec![2]);
+```
+
+Errors from reading the underlying input and cancellations are never skipped.
+*/
+use std::fmt;
+
+
+/// Policy for handling malformed records in readers.
+pub enum ErrorPolicy {
+    /// Yields the error of a malformed record. This is the default.
+    Abort,
+    /// Skips malformed records, calling the function with the one-based line number and the
+    /// error of each skipped record.
+    Skip(Box<FnMut(usize, &::Error)>),
+}
+
+impl Default for ErrorPolicy {
+    fn default() -> Self {
+        ErrorPolicy::Abort
+    }
+}
+
+impl fmt::Debug for ErrorPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ErrorPolicy::Abort => write!(f, "Abort"),
+            ErrorPolicy::Skip(_) => write!(f, "Skip(..)"),
+        }
+    }
+}
+
+impl ErrorPolicy {
+
+    /// Handles the error of the malformed record at the given line.
+    ///
+    /// The error is returned back when records are not skipped.
+    pub(crate) fn handle(&mut self, line_num: usize, err: ::Error) -> ::Result<()> {
+        match *self {
+            ErrorPolicy::Abort => Err(err),
+            ErrorPolicy::Skip(ref mut func) => {
+                func(line_num, &err);
+                Ok(())
+            },
+        }
+    }
+}
@@ -0,0 +1,33 @@
+/*! Commonly used types, for glob imports.
+
+The prelude brings the gene models and their builders, the readers and writers of the supported
+formats along with their streams, annotation collections, strands, and the crate's error and
+result types into scope:
+
+```
+use gte::prelude::*;
+
+fn build_gene() -> GteResult<Gene> {
+    GBuilder::new("chr1", 100, 500)
+        .strand(Strand::Forward)
+        .add_transcript_coords("trx01", TranscriptCoords::new(100, 500).exon(100, 500))
+        .build()
+}
+
+assert_eq!(build_gene().unwrap().strand().to_char(), '+');
+```
+
+The error and result types are renamed to `GteError` and `GteResult`, so that they do not shadow
+the standard `Result` type.
+*/
+pub use {Strand, StrandExt};
+pub use {EBuilder, Exon, ExonFeature, ExonFeatureKind, GBuilder, Gene, TBuilder, Transcript,
+         TranscriptCoords};
+pub use {AnnotationSet, Assembly, GenomeAnnotation};
+pub use {GtfGenesStream, GtfReader, GtfRecord, GtfRecordsStream, GtfTranscriptsStream, GtfWriter};
+pub use {RefFlatGenesStream, RefFlatReader, RefFlatRecord, RefFlatRecordsStream,
+         RefFlatTranscriptsStream, RefFlatWriter};
+pub use {GffReader, GffType, Gff3Writer};
+pub use {KnownGeneGenesStream, KnownGeneReader, KnownGeneTranscriptsStream};
+pub use {BedWriter, JsonWriter};
+pub use {Error as GteError, Result as GteResult};
@@ -0,0 +1,165 @@
+/*! Human-readable rendering of genes, transcripts, and exons.
+
+Formatting a model with `{}` gives a one-line summary of its identifier, location, and strand,
+suitable for log messages. The alternate form `{:#}`, also returned by `to_pretty_string`,
+renders the model as an indented tree of its transcripts, exons, and exon features:
+
+```
+use gte::{GBuilder, Strand, TranscriptCoords};
+
+let gene = GBuilder::new("chr1", 100, 1000)
+    .id("gene01")
+    .strand(Strand::Forward)
+    .add_transcript_coords("trx01", TranscriptCoords::new(100, 1000)
+        .exons(vec![(100, 300), (700, 1000)])
+        .coding(200, 800))
+    .build()
+    .unwrap();
+assert_eq!(gene.to_string(), "gene gene01 chr1:100-1000(+), 1 transcript");
+assert_eq!(gene.to_pretty_string(), "\
+gene gene01 chr1:100-1000(+), 1 transcript
+  transcript trx01 chr1:100-1000(+), 2 exons, CDS 200-803
+    exon <unknown> chr1:100-300(+)
+      UTR5 100-200
+      start_codon 200-203 frame 0
+      CDS 200-300 frame 0
+    exon <unknown> chr1:700-1000(+)
+      CDS 700-800 frame 2
+      stop_codon 800-803 frame 0
+      UTR3 803-1000");
+```
+
+Coordinates are zero-based, half-open, and the coding region includes the stop codon. Biotypes
+are shown after the strand when present.
+*/
+use std::fmt;
+
+use {Exon, ExonFeature, ExonFeatureKind as EFK, Gene, Transcript, DEF_ID};
+use io_gff::{strand_to_char, CDS_STR, START_CODON_STR, STOP_CODON_STR, UTR_STR, UTR3_STR,
+             UTR5_STR};
+
+
+/// Number of spaces added for each level of the tree.
+const INDENT: usize = 2;
+
+impl Gene {
+
+    /// Returns the gene and all of its transcripts, exons, and exon features as an indented
+    /// tree.
+    pub fn to_pretty_string(&self) -> String {
+        format!("{:#}", self)
+    }
+}
+
+impl Transcript {
+
+    /// Returns the transcript and all of its exons and exon features as an indented tree.
+    pub fn to_pretty_string(&self) -> String {
+        format!("{:#}", self)
+    }
+}
+
+impl Exon {
+
+    /// Returns the exon and all of its features as an indented tree.
+    pub fn to_pretty_string(&self) -> String {
+        format!("{:#}", self)
+    }
+}
+
+impl fmt::Display for Gene {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let tree = f.alternate();
+        fmt_gene(f, self, tree)
+    }
+}
+
+impl fmt::Display for Transcript {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let tree = f.alternate();
+        fmt_transcript(f, self, 0, tree)
+    }
+}
+
+impl fmt::Display for Exon {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let tree = f.alternate();
+        fmt_exon(f, self, 0, tree)
+    }
+}
+
+/// Helper function to write a gene, optionally followed by its transcripts.
+fn fmt_gene(f: &mut fmt::Formatter, gene: &Gene, tree: bool) -> fmt::Result {
+    let num_transcripts = gene.transcripts().len();
+    write!(f, "gene {} {}:{}-{}({})", gene.id().unwrap_or(DEF_ID), gene.seq_name(),
+           gene.start(), gene.end(), strand_to_char(gene.strand()))?;
+    if let Some(biotype) = gene.biotype() {
+        write!(f, " {}", biotype)?;
+    }
+    write!(f, ", {} {}", num_transcripts,
+           if num_transcripts == 1 { "transcript" } else { "transcripts" })?;
+    if tree {
+        for transcript in gene.transcripts().values() {
+            f.write_str("\n")?;
+            fmt_transcript(f, transcript, 1, true)?;
+        }
+    }
+    Ok(())
+}
+
+/// Helper function to write a transcript at the given depth, optionally followed by its exons.
+fn fmt_transcript(f: &mut fmt::Formatter, transcript: &Transcript, depth: usize, tree: bool)
+    -> fmt::Result
+{
+    let num_exons = transcript.exons().len();
+    write!(f, "{:indent$}transcript {} {}:{}-{}({})", "", transcript.id().unwrap_or(DEF_ID),
+           transcript.seq_name(), transcript.start(), transcript.end(),
+           strand_to_char(transcript.strand()), indent = depth * INDENT)?;
+    if let Some(biotype) = transcript.biotype() {
+        write!(f, " {}", biotype)?;
+    }
+    write!(f, ", {} {}", num_exons, if num_exons == 1 { "exon" } else { "exons" })?;
+    if let Some((start, end)) = transcript.coding_coord(true) {
+        write!(f, ", CDS {}-{}", start, end)?;
+    }
+    if tree {
+        for exon in transcript.exons() {
+            f.write_str("\n")?;
+            fmt_exon(f, exon, depth + 1, true)?;
+        }
+    }
+    Ok(())
+}
+
+/// Helper function to write an exon at the given depth, optionally followed by its features.
+fn fmt_exon(f: &mut fmt::Formatter, exon: &Exon, depth: usize, tree: bool) -> fmt::Result {
+    write!(f, "{:indent$}exon {} {}:{}-{}({})", "", exon.id().unwrap_or(DEF_ID),
+           exon.seq_name(), exon.start(), exon.end(), strand_to_char(exon.strand()),
+           indent = depth * INDENT)?;
+    if tree {
+        for feature in exon.features() {
+            f.write_str("\n")?;
+            fmt_feature(f, feature, depth + 1)?;
+        }
+    }
+    Ok(())
+}
+
+/// Helper function to write an exon feature at the given depth.
+fn fmt_feature(f: &mut fmt::Formatter, feature: &ExonFeature, depth: usize) -> fmt::Result {
+    let (kind, frame) = match feature.kind() {
+        &EFK::UTR => (UTR_STR, None),
+        &EFK::UTR5 => (UTR5_STR, None),
+        &EFK::UTR3 => (UTR3_STR, None),
+        &EFK::CDS { frame } => (CDS_STR, frame),
+        &EFK::StartCodon { frame } => (START_CODON_STR, frame),
+        &EFK::StopCodon { frame } => (STOP_CODON_STR, frame),
+        &EFK::Any(ref s) => (s.as_str(), None),
+    };
+    write!(f, "{:indent$}{} {}-{}", "", kind, feature.start(), feature.end(),
+           indent = depth * INDENT)?;
+    if let Some(frame) = frame {
+        write!(f, " frame {}", frame)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,219 @@
+/*! Placement of PCR primer pairs on transcripts.
+
+A primer pair is given by the genome-wise coordinates of its two primers: the left primer lies
+before the right primer on the genome, regardless of which one is called forward in the assay.
+Primers designed on cDNA may span exon-exon junctions, in which case they are given as one block
+per exon, as reported by spliced aligners.
+
+A transcript is amplified by a primer pair when both primers lie in its exons and their blocks
+are contiguous along the spliced transcript. The amplicon then covers the spliced transcript from
+the first base of the left primer to the last base of the right primer.
+*/
+use std::cmp::{max, min};
+use std::error::Error;
+
+use {Coord, GenomeAnnotation, Transcript};
+
+
+quick_error! {
+    /// Errors that occur when creating primer pairs.
+    #[derive(Debug)]
+    pub enum PrimerError {
+        /// Occurs when a primer has no blocks.
+        EmptyPrimer {
+            description("primer has no blocks")
+        }
+        /// Occurs when a primer block is empty or overlaps or precedes its previous block.
+        InvalidBlock(start: u64, end: u64) {
+            description("primer blocks must be nonempty, sorted, and nonoverlapping")
+            display(self_) -> ("{}, block: {}-{}", self_.description(), start, end)
+        }
+        /// Occurs when the left primer does not end before the right primer starts.
+        OverlappingPrimers(left_end: u64, right_start: u64) {
+            description("left primer must end before right primer starts")
+            display(self_) -> ("{}, left end: {}, right start: {}", self_.description(),
+                               left_end, right_start)
+        }
+    }
+}
+
+/// Pair of primers placed on a sequence.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrimerPair {
+    seq_name: String,
+    left: Vec<Coord<u64>>,
+    right: Vec<Coord<u64>>,
+}
+
+impl PrimerPair {
+
+    /// Creates a primer pair from the zero-based, half-open coordinates of primers that do not
+    /// span exon-exon junctions.
+    pub fn new<T>(seq_name: T, left: Coord<u64>, right: Coord<u64>) -> ::Result<Self>
+        where T: Into<String>
+    {
+        PrimerPair::from_blocks(seq_name, vec![left], vec![right])
+    }
+
+    /// Creates a primer pair from the blocks of each primer.
+    ///
+    /// Blocks are zero-based and half-open, and the blocks of each primer must be sorted and
+    /// nonoverlapping.
+    pub fn from_blocks<T>(seq_name: T, left: Vec<Coord<u64>>, right: Vec<Coord<u64>>)
+        -> ::Result<Self>
+        where T: Into<String>
+    {
+        check_blocks(&left)?;
+        check_blocks(&right)?;
+        let (left_end, right_start) = (left[left.len() - 1].1, right[0].0);
+        if left_end > right_start {
+            return Err(::Error::from(PrimerError::OverlappingPrimers(left_end, right_start)));
+        }
+        Ok(PrimerPair {
+            seq_name: seq_name.into(),
+            left: left,
+            right: right,
+        })
+    }
+
+    /// Returns the sequence name of the primer pair.
+    pub fn seq_name(&self) -> &str {
+        self.seq_name.as_str()
+    }
+
+    /// Returns the blocks of the left primer.
+    pub fn left(&self) -> &[Coord<u64>] {
+        self.left.as_slice()
+    }
+
+    /// Returns the blocks of the right primer.
+    pub fn right(&self) -> &[Coord<u64>] {
+        self.right.as_slice()
+    }
+
+    /// Returns the genome-wise region from the start of the left primer to the end of the right
+    /// primer.
+    pub fn span(&self) -> Coord<u64> {
+        (self.left[0].0, self.right[self.right.len() - 1].1)
+    }
+}
+
+/// Product of a primer pair on a transcript.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Amplicon {
+    gene_id: Option<String>,
+    transcript_id: Option<String>,
+    length: u64,
+    left_spans_junction: bool,
+    right_spans_junction: bool,
+}
+
+impl Amplicon {
+
+    /// Returns the gene identifier of the amplified transcript.
+    pub fn gene_id(&self) -> Option<&str> {
+        self.gene_id.as_ref().map(|id| id.as_str())
+    }
+
+    /// Returns the identifier of the amplified transcript.
+    pub fn transcript_id(&self) -> Option<&str> {
+        self.transcript_id.as_ref().map(|id| id.as_str())
+    }
+
+    /// Returns the length of the amplicon along the spliced transcript, including the primers.
+    pub fn length(&self) -> u64 {
+        self.length
+    }
+
+    /// Returns whether the left primer spans an exon-exon junction of the transcript.
+    pub fn left_spans_junction(&self) -> bool {
+        self.left_spans_junction
+    }
+
+    /// Returns whether the right primer spans an exon-exon junction of the transcript.
+    pub fn right_spans_junction(&self) -> bool {
+        self.right_spans_junction
+    }
+
+    /// Returns whether any of the primers spans an exon-exon junction of the transcript.
+    ///
+    /// Such amplicons are specific to cDNA, since the primer can not anneal to genomic DNA.
+    pub fn spans_junction(&self) -> bool {
+        self.left_spans_junction || self.right_spans_junction
+    }
+}
+
+impl Transcript {
+
+    /// Returns the product of the given primer pair on the transcript.
+    ///
+    /// The returned value is `None` if the primer pair is on another sequence or if any of its
+    /// primers does not lie entirely in the exons of the transcript.
+    pub fn amplicon(&self, primers: &PrimerPair) -> Option<Amplicon> {
+        if self.seq_name() != primers.seq_name() {
+            return None;
+        }
+        let (left, right) = match (self.primer_offsets(&primers.left),
+                                   self.primer_offsets(&primers.right)) {
+            (Some(left), Some(right)) => (left, right),
+            _ => return None,
+        };
+        let (start, end) = (min(left.0, right.0), max(left.1, right.1));
+        Some(Amplicon {
+            gene_id: self.gene_id().map(|id| id.to_owned()),
+            transcript_id: self.id().map(|id| id.to_owned()),
+            length: end - start,
+            left_spans_junction: primers.left.len() > 1,
+            right_spans_junction: primers.right.len() > 1,
+        })
+    }
+
+    /// Returns the range of offsets of the given primer blocks along the spliced transcript.
+    ///
+    /// The returned value is `None` unless the offsets project back exactly onto the blocks,
+    /// that is, unless the blocks are exonic and only separated by introns.
+    fn primer_offsets(&self, blocks: &[Coord<u64>]) -> Option<Coord<u64>> {
+        let first = blocks[0].0;
+        let last = blocks[blocks.len() - 1].1 - 1;
+        let (start, end) = match (self.genomic_to_transcript(first),
+                                  self.genomic_to_transcript(last)) {
+            (Some(a), Some(b)) => (min(a, b), max(a, b) + 1),
+            _ => return None,
+        };
+        if self.project_interval(start, end).as_slice() == blocks {
+            Some((start, end))
+        } else {
+            None
+        }
+    }
+}
+
+impl GenomeAnnotation {
+
+    /// Returns the products of the given primer pair on all transcripts, sorted by the start
+    /// coordinates of the transcripts.
+    ///
+    /// Transcripts that are not amplified are left out.
+    pub fn place_primers(&self, primers: &PrimerPair) -> Vec<Amplicon> {
+        let (start, end) = primers.span();
+        self.find_overlapping_transcripts(primers.seq_name(), start, end).into_iter()
+            .filter_map(|trx| trx.amplicon(primers))
+            .collect()
+    }
+}
+
+/// Helper function for checking the blocks of a primer.
+fn check_blocks(blocks: &[Coord<u64>]) -> Result<(), PrimerError> {
+    if blocks.is_empty() {
+        return Err(PrimerError::EmptyPrimer);
+    }
+    let mut prev_end = None;
+    for &(start, end) in blocks.iter() {
+        let after_prev = prev_end.map(|prev| start >= prev).unwrap_or(true);
+        if start >= end || !after_prev {
+            return Err(PrimerError::InvalidBlock(start, end));
+        }
+        prev_end = Some(end);
+    }
+    Ok(())
+}
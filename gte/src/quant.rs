@@ -0,0 +1,298 @@
+/*! Transcript quantification files and expression-based annotation filtering.
+
+The quantification files are the per-transcript tables produced by
+[salmon](https://github.com/COMBINE-lab/salmon) (`quant.sf`) and
+[kallisto](https://github.com/pachterlab/kallisto) (`abundance.tsv`). Both formats are
+tab-separated with a header line. The columns used are located by their header names:
+
+* transcript identifier: `Name` (salmon) or `target_id` (kallisto)
+* TPM: `TPM` (salmon) or `tpm` (kallisto)
+* read count: `NumReads` (salmon) or `est_counts` (kallisto)
+
+Transcript names written by salmon from GENCODE transcriptomes may contain additional
+`|`-separated fields after the identifier. These fields are removed.
+*/
+use std::cmp::{max, min};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+use std::str::FromStr;
+
+use linked_hash_map::LinkedHashMap;
+
+use {AnnotationSet, GBuilder, INIT_COORD};
+
+
+quick_error! {
+    /// Errors that occur when reading quantification files.
+    #[derive(Debug)]
+    pub enum QuantError {
+        /// Occurs when the input does not have any header line.
+        MissingHeader {
+            description("quantification header not found")
+        }
+        /// Occurs when the header does not contain a required column.
+        MissingColumn(name: &'static str) {
+            description("required quantification column not found")
+            display(self_) -> ("{}, column: {}", self_.description(), name)
+        }
+        /// Occurs when a quantification line can not be parsed.
+        InvalidRecord(line_num: usize) {
+            description("invalid quantification record")
+            display(self_) -> ("{}, line: {}", self_.description(), line_num)
+        }
+        /// Errors propagated from reading the underlying file.
+        Io(err: io::Error) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// Header names of the transcript identifier column.
+const ID_COLS: [&'static str; 2] = ["Name", "target_id"];
+
+/// Header names of the TPM column.
+const TPM_COLS: [&'static str; 2] = ["TPM", "tpm"];
+
+/// Header names of the read count column.
+const READS_COLS: [&'static str; 2] = ["NumReads", "est_counts"];
+
+/// Expression estimate of a single transcript.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuantRecord {
+    transcript_id: String,
+    tpm: f64,
+    num_reads: f64,
+}
+
+impl QuantRecord {
+
+    /// Returns the transcript identifier.
+    pub fn transcript_id(&self) -> &str {
+        self.transcript_id.as_str()
+    }
+
+    /// Returns the abundance, in transcripts per million.
+    pub fn tpm(&self) -> f64 {
+        self.tpm
+    }
+
+    /// Returns the estimated number of reads.
+    pub fn num_reads(&self) -> f64 {
+        self.num_reads
+    }
+}
+
+/// Positions of the used columns.
+#[derive(Debug, Clone, Copy)]
+struct Columns {
+    id: usize,
+    tpm: usize,
+    reads: usize,
+}
+
+impl Columns {
+
+    /// Locates the used columns in the given header line.
+    fn from_header(header: &str) -> Result<Self, QuantError> {
+        let names = header.split('\t').collect::<Vec<&str>>();
+        let find = |cands: &[&'static str]| {
+            names.iter().position(|name| cands.contains(name))
+                .ok_or(QuantError::MissingColumn(cands[0]))
+        };
+        Ok(Columns {
+            id: find(&ID_COLS)?,
+            tpm: find(&TPM_COLS)?,
+            reads: find(&READS_COLS)?,
+        })
+    }
+
+    /// Parses a record from the given line.
+    fn parse(&self, line: &str, line_num: usize) -> Result<QuantRecord, QuantError> {
+        let fields = line.split('\t').collect::<Vec<&str>>();
+        let field = |idx: usize| {
+            fields.get(idx).cloned().ok_or(QuantError::InvalidRecord(line_num))
+        };
+        let parse_num = |idx: usize| {
+            field(idx).and_then(|raw| f64::from_str(raw)
+                                .map_err(|_| QuantError::InvalidRecord(line_num)))
+        };
+        let raw_id = field(self.id)?;
+        let tid = raw_id.split('|').next().unwrap_or(raw_id);
+        if tid.is_empty() {
+            return Err(QuantError::InvalidRecord(line_num));
+        }
+        Ok(QuantRecord {
+            transcript_id: tid.to_owned(),
+            tpm: parse_num(self.tpm)?,
+            num_reads: parse_num(self.reads)?,
+        })
+    }
+}
+
+/// Quantification file reader.
+pub struct Reader<R: io::Read> {
+    inner: BufReader<R>,
+    line_num: usize,
+    columns: Option<Columns>,
+}
+
+impl<R: io::Read> Reader<R> {
+
+    /// Creates a quantification reader from another reader.
+    pub fn from_reader(in_reader: R) -> Reader<R> {
+        Reader {
+            inner: BufReader::new(in_reader),
+            line_num: 0,
+            columns: None,
+        }
+    }
+
+    /// Creates an iterator of quantification records.
+    pub fn records_stream(&mut self) -> QuantRecordsStream<R> {
+        QuantRecordsStream { reader: self }
+    }
+
+    /// Reads all records into a quantification table.
+    pub fn quantification(&mut self) -> ::Result<Quantification> {
+        let mut tpms = HashMap::new();
+        for result in self.records_stream() {
+            let record = result?;
+            let _ = tpms.insert(record.transcript_id, record.tpm);
+        }
+        Ok(Quantification { tpms: tpms })
+    }
+}
+
+impl Reader<fs::File> {
+
+    /// Creates a quantification reader that reads from the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        fs::File::open(path).map(Reader::from_reader)
+    }
+}
+
+/// Iterator over quantification records.
+pub struct QuantRecordsStream<'a, R: 'a> where R: io::Read {
+    reader: &'a mut Reader<R>,
+}
+
+impl<'a, R> Iterator for QuantRecordsStream<'a, R> where R: io::Read {
+
+    type Item = ::Result<QuantRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            match self.reader.inner.read_line(&mut line) {
+                Ok(0) => {
+                    return match self.reader.columns {
+                        None if self.reader.line_num == 0 => {
+                            self.reader.line_num += 1;
+                            Some(Err(::Error::from(QuantError::MissingHeader)))
+                        },
+                        _ => None,
+                    };
+                },
+                Ok(_) => {},
+                Err(e) => return Some(Err(::Error::from(QuantError::from(e)))),
+            }
+            self.reader.line_num += 1;
+            let trimmed = line.trim_end_matches(&['\n', '\r'][..]);
+            if trimmed.is_empty() {
+                continue;
+            }
+            match self.reader.columns {
+                None => match Columns::from_header(trimmed) {
+                    Ok(cols) => self.reader.columns = Some(cols),
+                    Err(e) => return Some(Err(::Error::from(e))),
+                },
+                Some(cols) => return Some(cols.parse(trimmed, self.reader.line_num)
+                                          .map_err(::Error::from)),
+            }
+        }
+    }
+}
+
+/// Transcript abundances from a quantification file.
+#[derive(Debug, Clone, Default)]
+pub struct Quantification {
+    tpms: HashMap<String, f64>,
+}
+
+impl Quantification {
+
+    /// Creates a quantification table from the given records.
+    pub fn from_records<I>(records: I) -> Self
+        where I: IntoIterator<Item=QuantRecord>
+    {
+        Quantification {
+            tpms: records.into_iter().map(|rec| (rec.transcript_id, rec.tpm)).collect(),
+        }
+    }
+
+    /// Returns the abundance of the given transcript, in transcripts per million.
+    pub fn tpm(&self, transcript_id: &str) -> Option<f64> {
+        self.tpms.get(transcript_id).cloned()
+    }
+
+    /// Returns the number of quantified transcripts.
+    pub fn len(&self) -> usize {
+        self.tpms.len()
+    }
+
+    /// Returns whether no transcripts are quantified.
+    pub fn is_empty(&self) -> bool {
+        self.tpms.is_empty()
+    }
+}
+
+impl AnnotationSet {
+
+    /// Creates an annotation set containing only the transcripts whose abundance is at least the
+    /// given TPM value.
+    ///
+    /// Transcripts that are absent from the quantification are treated as not expressed. Genes
+    /// with no remaining transcripts are removed, and the coordinates of the remaining genes are
+    /// shrunk to span their remaining transcripts.
+    pub fn filter_by_expression(&self, quant: &Quantification, min_tpm: f64)
+        -> ::Result<AnnotationSet>
+    {
+        let mut genes = Vec::new();
+        for gene in self.genes() {
+            let mut transcripts = LinkedHashMap::new();
+            let (mut gene_start, mut gene_end) = INIT_COORD;
+            for (tid, trx) in gene.transcripts() {
+                let expressed = quant.tpm(tid.as_str())
+                    .map(|tpm| tpm >= min_tpm)
+                    .unwrap_or(false);
+                if expressed {
+                    gene_start = min(gene_start, trx.start());
+                    gene_end = max(gene_end, trx.end());
+                    let _ = transcripts.insert(tid.clone(), trx.clone());
+                }
+            }
+            if transcripts.is_empty() {
+                continue;
+            }
+            let mut builder = GBuilder::new(gene.seq_name(), gene_start, gene_end)
+                .strand(*gene.strand())
+                .attributes(gene.attributes().clone())
+                .transcripts(transcripts);
+            if let Some(gid) = gene.id() {
+                builder = builder.id(gid);
+            }
+            if let Some(biotype) = gene.biotype() {
+                builder = builder.biotype(biotype.clone());
+            }
+            genes.push(builder.build()?);
+        }
+        Ok(AnnotationSet::from_genes(genes))
+    }
+}
@@ -0,0 +1,181 @@
+/*! Random intervals for building null sets.
+
+The sampler draws random intervals that have the same lengths and are on the same sequences as
+a set of query intervals, similar to `bedtools shuffle -chrom`. The random intervals may be set
+to avoid exonic regions, or to only be placed within them, using the collapsed exons of an
+annotation set.
+
+Intervals are drawn uniformly from all positions where they fit, and the sampler is seeded, so
+that the same seed always results in the same intervals.
+*/
+use std::cmp::min;
+use std::collections::HashMap;
+use std::error::Error;
+
+use {AnnotationSet, Assembly, Coord};
+use utils::SplitMix64;
+
+
+/// Seed used when none is set.
+const DEF_SEED: u64 = 0x5EED;
+
+quick_error! {
+    /// Errors that occur when sampling random intervals.
+    #[derive(Debug)]
+    pub enum SampleError {
+        /// Occurs when a query interval is on a sequence not present in the assembly.
+        UnknownSeqName(seq_name: String) {
+            description("sequence name not found in assembly")
+            display(self_) -> ("{}: {}", self_.description(), seq_name)
+        }
+        /// Occurs when a query interval has a start coordinate larger than its end coordinate.
+        InvalidInterval(seq_name: String, start: u64, end: u64) {
+            description("interval start is larger than its end")
+            display(self_) -> ("{}: {}:{}-{}", self_.description(), seq_name, start, end)
+        }
+        /// Occurs when there is no region large enough to place an interval.
+        NoRoom(seq_name: String, len: u64) {
+            description("no region large enough for interval")
+            display(self_) -> ("{}: {}, length: {}", self_.description(), seq_name, len)
+        }
+    }
+}
+
+/// Placement of sampled intervals relative to exonic regions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ExonPolicy {
+    Ignore,
+    Avoid,
+    Restrict,
+}
+
+/// Sampler of random intervals within the sequences of an assembly.
+pub struct IntervalSampler<'a> {
+    assembly: &'a Assembly,
+    exons: HashMap<String, Vec<Coord<u64>>>,
+    policy: ExonPolicy,
+    regions: HashMap<String, Vec<Coord<u64>>>,
+    rng: SplitMix64,
+}
+
+impl<'a> IntervalSampler<'a> {
+
+    /// Creates a sampler that places intervals anywhere in the given assembly.
+    pub fn new(assembly: &'a Assembly) -> Self {
+        IntervalSampler {
+            assembly: assembly,
+            exons: HashMap::new(),
+            policy: ExonPolicy::Ignore,
+            regions: HashMap::new(),
+            rng: SplitMix64::new(DEF_SEED),
+        }
+    }
+
+    /// Sets the seed of the random number generator.
+    pub fn seed(&mut self, seed: u64) -> &mut Self {
+        self.rng = SplitMix64::new(seed);
+        self
+    }
+
+    /// Sets the sampler to only place intervals that do not overlap any exon of the given
+    /// annotation set.
+    pub fn avoid_exons(&mut self, annotation: &AnnotationSet) -> &mut Self {
+        self.set_exons(annotation, ExonPolicy::Avoid)
+    }
+
+    /// Sets the sampler to only place intervals that are fully contained within the collapsed
+    /// exons of the given annotation set.
+    pub fn restrict_to_exons(&mut self, annotation: &AnnotationSet) -> &mut Self {
+        self.set_exons(annotation, ExonPolicy::Restrict)
+    }
+
+    /// Samples a random interval of the given length on the given sequence.
+    ///
+    /// The returned coordinate is zero-based and half-open.
+    pub fn sample_interval(&mut self, seq_name: &str, len: u64) -> ::Result<Coord<u64>> {
+        if !self.regions.contains_key(seq_name) {
+            let seq_len = self.assembly.seq_len(seq_name)
+                .ok_or_else(|| SampleError::UnknownSeqName(seq_name.to_owned()))
+                .map_err(::Error::from)?;
+            let regions = self.allowed_regions(seq_name, seq_len);
+            let _ = self.regions.insert(seq_name.to_owned(), regions);
+        }
+        let regions = &self.regions[seq_name];
+
+        let num_positions = regions.iter()
+            .filter(|&&(start, end)| end - start >= len)
+            .map(|&(start, end)| end - start - len + 1)
+            .fold(0, |acc, n| acc + n);
+        if num_positions == 0 {
+            return Err(::Error::from(SampleError::NoRoom(seq_name.to_owned(), len)));
+        }
+
+        let mut offset = self.rng.below(num_positions);
+        for &(start, end) in regions.iter().filter(|&&(start, end)| end - start >= len) {
+            let n = end - start - len + 1;
+            if offset < n {
+                return Ok((start + offset, start + offset + len));
+            }
+            offset -= n;
+        }
+        unreachable!("offset is always less than the number of positions")
+    }
+
+    /// Samples one random interval for each of the given query intervals.
+    ///
+    /// Each sampled interval has the same sequence name and length as its query interval, and
+    /// the sampled intervals are returned in the order of the queries.
+    pub fn sample<I, S>(&mut self, queries: I) -> ::Result<Vec<(String, u64, u64)>>
+        where I: IntoIterator<Item=(S, u64, u64)>, S: AsRef<str>
+    {
+        let mut sampled = Vec::new();
+        for (seq_name, start, end) in queries {
+            let seq_name = seq_name.as_ref();
+            if start > end {
+                return Err(::Error::from(
+                    SampleError::InvalidInterval(seq_name.to_owned(), start, end)));
+            }
+            let (sstart, send) = self.sample_interval(seq_name, end - start)?;
+            sampled.push((seq_name.to_owned(), sstart, send));
+        }
+        Ok(sampled)
+    }
+
+    /// Sets the exons and their policy, clearing any previously computed regions.
+    fn set_exons(&mut self, annotation: &AnnotationSet, policy: ExonPolicy) -> &mut Self {
+        self.exons = annotation.collapsed_exons();
+        self.policy = policy;
+        self.regions.clear();
+        self
+    }
+
+    /// Computes the regions of the given sequence in which intervals may be placed.
+    fn allowed_regions(&self, seq_name: &str, seq_len: u64) -> Vec<Coord<u64>> {
+        let empty = Vec::new();
+        let exons = self.exons.get(seq_name).unwrap_or(&empty);
+        match self.policy {
+            ExonPolicy::Ignore => vec![(0, seq_len)],
+            ExonPolicy::Restrict => exons.iter()
+                .filter(|&&(start, _)| start < seq_len)
+                .map(|&(start, end)| (start, min(end, seq_len)))
+                .collect(),
+            ExonPolicy::Avoid => {
+                let mut regions = Vec::with_capacity(exons.len() + 1);
+                let mut cur = 0;
+                for &(start, end) in exons.iter() {
+                    if start >= seq_len {
+                        break;
+                    }
+                    if start > cur {
+                        regions.push((cur, start));
+                    }
+                    cur = end;
+                }
+                if cur < seq_len {
+                    regions.push((cur, seq_len));
+                }
+                regions
+            },
+        }
+    }
+}
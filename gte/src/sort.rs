@@ -0,0 +1,180 @@
+/*! Sorting of genes and transcripts by their genomic positions.
+
+Tools such as tabix and IGV expect their input to be sorted by sequence name and start
+coordinate. The functions in this module sort models by their sequence names, then by their
+start and end coordinates, and then by their strands, so that the same input always gives the
+same output order.
+
+Sequence names are compared according to a `ChromOrder`. The default karyotypic order puts
+numbered chromosomes first, in numeric order, followed by `X`, `Y`, and the mitochondrial
+sequence, ignoring any `chr` prefix. Other sequence names come last, in lexicographic order:
+
+```
+use gte::{GBuilder, Gene, Strand};
+use gte::sort;
+
+let gene = |seq_name: &str, start: u64| -> Gene {
+    GBuilder::new(seq_name, start, start + 100)
+        .strand(Strand::Forward)
+        .build()
+        .unwrap()
+};
+let mut genes = vec![gene("chr10", 100), gene("chrX", 50), gene("chr2", 300),
+                     gene("chr2", 100), gene("chrUn_gl000220", 10)];
+sort::by_position(&mut genes);
+let positions = genes.iter().map(|g| (g.seq_name(), g.start())).collect::<Vec<_>>();
+assert_eq!(positions, vec![("chr2", 100), ("chr2", 300), ("chr10", 100), ("chrX", 50),
+                           ("chrUn_gl000220", 10)]);
+```
+*/
+use std::cmp::Ordering;
+use std::vec;
+
+use {Exon, Gene, Strand, Transcript};
+use annotation::strand_ord;
+
+
+/// Order of sequence names when sorting by position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChromOrder {
+    /// Numbered chromosomes in numeric order, then `X`, `Y`, `M` or `MT`, then the other
+    /// sequences in lexicographic order. This is the default.
+    Karyotypic,
+    /// All sequences in lexicographic order, as done by `sort -k1,1`.
+    Lexicographic,
+}
+
+impl Default for ChromOrder {
+    fn default() -> Self {
+        ChromOrder::Karyotypic
+    }
+}
+
+impl ChromOrder {
+
+    /// Compares two sequence names.
+    pub fn cmp_seq_names(&self, a: &str, b: &str) -> Ordering {
+        match *self {
+            ChromOrder::Karyotypic => karyotypic_key(a).cmp(&karyotypic_key(b)),
+            ChromOrder::Lexicographic => a.cmp(b),
+        }
+    }
+}
+
+/// Models that can be sorted by their genomic positions.
+pub trait Positioned {
+
+    /// Returns the sequence name.
+    fn seq_name(&self) -> &str;
+
+    /// Returns the genome-wise 5'-most coordinate.
+    fn start(&self) -> u64;
+
+    /// Returns the genome-wise 3'-most coordinate.
+    fn end(&self) -> u64;
+
+    /// Returns a reference of the strand.
+    fn strand(&self) -> &Strand;
+}
+
+macro_rules! impl_positioned {
+    ($struct_ty:ty) => (
+
+        impl Positioned for $struct_ty {
+
+            fn seq_name(&self) -> &str {
+                <$struct_ty>::seq_name(self)
+            }
+
+            fn start(&self) -> u64 {
+                <$struct_ty>::start(self)
+            }
+
+            fn end(&self) -> u64 {
+                <$struct_ty>::end(self)
+            }
+
+            fn strand(&self) -> &Strand {
+                <$struct_ty>::strand(self)
+            }
+        }
+
+    );
+}
+
+impl_positioned!(Gene);
+impl_positioned!(Transcript);
+impl_positioned!(Exon);
+
+/// Sorts the given models by position, using the karyotypic order of sequence names.
+pub fn by_position<T: Positioned>(items: &mut Vec<T>) {
+    by_position_with(items, ChromOrder::Karyotypic)
+}
+
+/// Sorts the given models by position, using the given order of sequence names.
+///
+/// The sort is stable, so models at the same position keep their relative order.
+pub fn by_position_with<T: Positioned>(items: &mut Vec<T>, order: ChromOrder) {
+    items.sort_by(|a, b| cmp_positions(a, b, order))
+}
+
+/// Compares the positions of two models.
+pub fn cmp_positions<T: Positioned>(a: &T, b: &T, order: ChromOrder) -> Ordering {
+    order.cmp_seq_names(a.seq_name(), b.seq_name())
+        .then_with(|| {
+            (a.start(), a.end(), strand_ord(a.strand()))
+                .cmp(&(b.start(), b.end(), strand_ord(b.strand())))
+        })
+}
+
+/// Iterator over models sorted by position.
+///
+/// This struct is created by the `sorted` function. The input is read in full, so memory use
+/// grows with the number of models.
+#[derive(Debug)]
+pub struct Sorted<T> {
+    inner: vec::IntoIter<T>,
+}
+
+impl<T> Iterator for Sorted<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// Returns an iterator over the given models sorted by position, using the given order of
+/// sequence names.
+///
+/// Streams of results, such as those of the readers, can be collected into a `Result` first,
+/// so that errors are returned before any model is sorted.
+pub fn sorted<I>(items: I, order: ChromOrder) -> Sorted<I::Item>
+    where I: IntoIterator,
+          I::Item: Positioned
+{
+    let mut items = items.into_iter().collect::<Vec<I::Item>>();
+    by_position_with(&mut items, order);
+    Sorted { inner: items.into_iter() }
+}
+
+/// Helper function for creating the sort key of a sequence name in karyotypic order.
+fn karyotypic_key(seq_name: &str) -> (u8, u64, &str) {
+    let base = match seq_name.get(..3) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("chr") => &seq_name[3..],
+        _ => seq_name,
+    };
+    if let Ok(num) = base.parse::<u64>() {
+        return (0, num, seq_name);
+    }
+    match base {
+        "X" | "x" => (1, 0, seq_name),
+        "Y" | "y" => (1, 1, seq_name),
+        "M" | "m" | "MT" | "Mt" | "mt" => (1, 2, seq_name),
+        _ => (2, 0, seq_name),
+    }
+}
@@ -0,0 +1,286 @@
+/*! Sequence windows around annotated splice sites.
+
+The windows are meant for building training sets of splice site models. Each donor and acceptor
+site of every transcript intron is extracted once, as a fixed-width window centered on the
+exon-intron boundary and read from the transcript strand, so that:
+
+* donor windows contain `flank` exonic bases followed by `flank` intronic bases, the latter
+  starting with the donor dinucleotide (usually `GT`), and
+* acceptor windows contain `flank` intronic bases, the last two being the acceptor dinucleotide
+  (usually `AG`), followed by `flank` exonic bases.
+
+Sites of transcripts with an unknown strand are skipped, as are windows that would extend past
+either end of their sequence.
+
+Windows may be written as tab-separated lines using `SpliceSiteWriter`, with these columns:
+
+1. sequence name
+2. boundary position (zero-based, genome-wise coordinate of the first base to the right of
+   the boundary)
+3. strand
+4. site label (`donor` or `acceptor`)
+5. gene identifiers (comma-separated)
+6. transcript identifiers (comma-separated)
+7. window sequence
+*/
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use {Gene, Genome, Strand, DEF_ID};
+use io_gff::strand_to_char;
+
+
+quick_error! {
+    /// Errors that occur when extracting or writing splice site windows.
+    #[derive(Debug)]
+    pub enum SpliceError {
+        /// Occurs when the genome does not contain the sequence of an annotated intron.
+        MissingSequence(seq_name: String) {
+            description("sequence not found in genome")
+            display(self_) -> ("{}: '{}'", self_.description(), seq_name)
+        }
+        /// Errors propagated from writing the underlying output.
+        Io(err: io::Error) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// Splice site labels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SpliceSiteKind {
+    /// 5' splice site, at the start of an intron.
+    Donor,
+    /// 3' splice site, at the end of an intron.
+    Acceptor,
+}
+
+impl SpliceSiteKind {
+
+    /// Returns the label of the site kind as written in splice site tables.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            SpliceSiteKind::Donor => "donor",
+            SpliceSiteKind::Acceptor => "acceptor",
+        }
+    }
+}
+
+/// Sequence window around a single splice site.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpliceSiteWindow {
+    seq_name: String,
+    position: u64,
+    strand: Strand,
+    kind: SpliceSiteKind,
+    gene_ids: Vec<String>,
+    transcript_ids: Vec<String>,
+    sequence: Vec<u8>,
+}
+
+impl SpliceSiteWindow {
+
+    /// Returns the sequence name.
+    pub fn seq_name(&self) -> &str {
+        self.seq_name.as_str()
+    }
+
+    /// Returns the genome-wise coordinate of the first base to the right of the boundary.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Returns a reference of the strand.
+    pub fn strand(&self) -> &Strand {
+        &self.strand
+    }
+
+    /// Returns the site label.
+    pub fn kind(&self) -> SpliceSiteKind {
+        self.kind
+    }
+
+    /// Returns the identifiers of the genes using the site, in annotation order.
+    pub fn gene_ids(&self) -> &[String] {
+        self.gene_ids.as_slice()
+    }
+
+    /// Returns the identifiers of the transcripts using the site, in annotation order.
+    pub fn transcript_ids(&self) -> &[String] {
+        self.transcript_ids.as_slice()
+    }
+
+    /// Returns the window sequence, read from the transcript strand.
+    pub fn sequence(&self) -> &[u8] {
+        self.sequence.as_slice()
+    }
+}
+
+/// Extractor of sequence windows around splice sites.
+#[derive(Debug, Clone)]
+pub struct SpliceSiteExtractor {
+    flank: u64,
+}
+
+impl SpliceSiteExtractor {
+
+    /// Creates an extractor whose windows contain `flank` bases on each side of the boundary.
+    pub fn new(flank: u64) -> Self {
+        SpliceSiteExtractor { flank: flank }
+    }
+
+    /// Returns the number of bases on each side of the boundary.
+    pub fn flank(&self) -> u64 {
+        self.flank
+    }
+
+    /// Extracts the windows of all splice sites of the given genes.
+    ///
+    /// Sites shared by several transcripts are returned once, listing all of them. The windows
+    /// are sorted by sequence name, position, strand, and label.
+    pub fn extract<'a, I>(&self, genes: I, genome: &Genome) -> ::Result<Vec<SpliceSiteWindow>>
+        where I: IntoIterator<Item=&'a Gene>
+    {
+        let mut sites: BTreeMap<(String, u64, char, SpliceSiteKind), SpliceSiteWindow> =
+            BTreeMap::new();
+
+        for gene in genes {
+            let gene_id = gene.id().unwrap_or(DEF_ID);
+            for (tid, transcript) in gene.transcripts() {
+                let strand = *transcript.strand();
+                let (start_kind, end_kind) = match strand {
+                    Strand::Forward => (SpliceSiteKind::Donor, SpliceSiteKind::Acceptor),
+                    Strand::Reverse => (SpliceSiteKind::Acceptor, SpliceSiteKind::Donor),
+                    Strand::Unknown => continue,
+                };
+                let introns = transcript.intron_coords();
+                if !introns.is_empty() && genome.seq(transcript.seq_name()).is_none() {
+                    let seq_name = transcript.seq_name().to_owned();
+                    return Err(::Error::from(SpliceError::MissingSequence(seq_name)));
+                }
+                let boundaries = introns.iter()
+                    .flat_map(|&(start, end)| vec![(start, start_kind), (end, end_kind)]);
+                for (position, kind) in boundaries {
+                    let key = (transcript.seq_name().to_owned(), position,
+                               strand_to_char(&strand), kind);
+                    if let Some(window) = sites.get_mut(&key) {
+                        if !window.gene_ids.iter().any(|gid| gid == gene_id) {
+                            window.gene_ids.push(gene_id.to_owned());
+                        }
+                        window.transcript_ids.push(tid.clone());
+                        continue;
+                    }
+                    let sequence = match self.window_seq(genome, &key.0, position, &strand) {
+                        Some(seq) => seq,
+                        None => continue,
+                    };
+                    let window = SpliceSiteWindow {
+                        seq_name: key.0.clone(),
+                        position: position,
+                        strand: strand,
+                        kind: kind,
+                        gene_ids: vec![gene_id.to_owned()],
+                        transcript_ids: vec![tid.clone()],
+                        sequence: sequence,
+                    };
+                    let _ = sites.insert(key, window);
+                }
+            }
+        }
+
+        Ok(sites.into_iter().map(|(_, window)| window).collect())
+    }
+
+    /// Helper method for reading the window around the given boundary.
+    fn window_seq(&self, genome: &Genome, seq_name: &str, position: u64, strand: &Strand)
+        -> Option<Vec<u8>>
+    {
+        if position < self.flank {
+            return None;
+        }
+        genome.stranded_subseq(seq_name, position - self.flank, position + self.flank, strand)
+    }
+}
+
+/// Splice site table writer.
+pub struct Writer<W: io::Write> {
+    inner: W,
+    header_written: bool,
+}
+
+impl<W: io::Write> Writer<W> {
+
+    /// Creates a splice site table writer from another writer.
+    pub fn from_writer(in_writer: W) -> Writer<W> {
+        Writer {
+            inner: in_writer,
+            header_written: false,
+        }
+    }
+
+    /// Writes the given window as a single line.
+    ///
+    /// The column header line is written before the first window.
+    pub fn write_window(&mut self, window: &SpliceSiteWindow) -> ::Result<()> {
+        self.write_header()?;
+        writeln!(self.inner, "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                 window.seq_name(), window.position(), strand_to_char(window.strand()),
+                 window.kind().as_str(), window.gene_ids().join(","),
+                 window.transcript_ids().join(","),
+                 String::from_utf8_lossy(window.sequence()))
+            .map_err(|e| ::Error::from(SpliceError::from(e)))
+    }
+
+    /// Writes the given windows, one per line.
+    pub fn write_windows<'a, I>(&mut self, windows: I) -> ::Result<()>
+        where I: IntoIterator<Item=&'a SpliceSiteWindow>
+    {
+        self.write_header()?;
+        for window in windows {
+            self.write_window(window)?;
+        }
+        Ok(())
+    }
+
+    /// Helper method for writing the column header line once.
+    fn write_header(&mut self) -> ::Result<()> {
+        if self.header_written {
+            return Ok(());
+        }
+        self.header_written = true;
+        writeln!(self.inner, "#seq_name\tposition\tstrand\tlabel\tgene_ids\ttranscript_ids\t\
+                              sequence")
+            .map_err(|e| ::Error::from(SpliceError::from(e)))
+    }
+}
+
+impl Writer<fs::File> {
+
+    /// Creates a splice site table writer that writes to the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let f = fs::File::create(path)?;
+        Ok(Writer::from_writer(f))
+    }
+}
+
+impl Writer<Vec<u8>> {
+
+    /// Creates a splice site table writer that writes to an in-memory buffer.
+    ///
+    /// The initial capacity of the buffer is 64 KiB.
+    pub fn from_memory() -> Writer<Vec<u8>> {
+        Writer::from_writer(Vec::with_capacity(1024 * 64))
+    }
+
+    /// Returns the values of the in-memory buffer as a string.
+    pub fn as_string(&mut self) -> &str {
+        // sequences are written lossily, so the buffer is always valid UTF-8
+        ::std::str::from_utf8(self.inner.as_slice()).expect("valid UTF-8 output")
+    }
+}
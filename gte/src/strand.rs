@@ -0,0 +1,115 @@
+/*! Helpers for working with strands.
+
+The `Strand` type is re-exported from the `bio` crate, so conversions such as `From<i8>` can not
+be implemented for it here. The helpers are instead provided by the `StrandExt` trait, which
+must be in scope to be used:
+
+```
+use gte::{Strand, StrandExt};
+
+assert_eq!(Strand::Forward.opposite(), Strand::Reverse);
+assert_eq!(Strand::from_i8(-1), Strand::Reverse);
+assert_eq!(Strand::Reverse.to_char(), '-');
+```
+
+With the `serde` feature enabled, the `serde_strand` module can be used to serialize strands as
+their characters, using `#[serde(with = "gte::serde_strand")]`.
+*/
+use bio::utils::Strand;
+
+
+/// Extension methods for `Strand`.
+pub trait StrandExt {
+
+    /// Creates a strand from its numeric representation.
+    ///
+    /// Positive values are the forward strand, negative values are the reverse strand, and zero
+    /// is the unknown strand.
+    fn from_i8(value: i8) -> Self;
+
+    /// Returns the numeric representation of the strand: `1`, `-1`, or `0`.
+    fn to_i8(&self) -> i8;
+
+    /// Returns the character representation of the strand: `'+'`, `'-'`, or `'.'`.
+    fn to_char(&self) -> char;
+
+    /// Returns the opposite strand.
+    ///
+    /// The opposite of an unknown strand is the unknown strand.
+    fn opposite(&self) -> Self;
+
+    /// Returns whether both strands are known and equal.
+    ///
+    /// Unlike `==`, two unknown strands are not considered the same.
+    fn is_same(&self, other: &Self) -> bool;
+}
+
+impl StrandExt for Strand {
+
+    fn from_i8(value: i8) -> Strand {
+        if value > 0 {
+            Strand::Forward
+        } else if value < 0 {
+            Strand::Reverse
+        } else {
+            Strand::Unknown
+        }
+    }
+
+    fn to_i8(&self) -> i8 {
+        match self {
+            &Strand::Forward => 1,
+            &Strand::Reverse => -1,
+            &Strand::Unknown => 0,
+        }
+    }
+
+    fn to_char(&self) -> char {
+        match self {
+            &Strand::Forward => '+',
+            &Strand::Reverse => '-',
+            &Strand::Unknown => '.',
+        }
+    }
+
+    fn opposite(&self) -> Strand {
+        match self {
+            &Strand::Forward => Strand::Reverse,
+            &Strand::Reverse => Strand::Forward,
+            &Strand::Unknown => Strand::Unknown,
+        }
+    }
+
+    fn is_same(&self, other: &Strand) -> bool {
+        match (self, other) {
+            (&Strand::Forward, &Strand::Forward) | (&Strand::Reverse, &Strand::Reverse) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Serialization of strands as their characters, for use with `#[serde(with = "...")]`.
+#[cfg(feature = "serde")]
+pub mod serde_strand {
+    use bio::utils::Strand;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use serde::de::Error;
+
+    use super::StrandExt;
+
+    /// Serializes the strand as `'+'`, `'-'`, or `'.'`.
+    pub fn serialize<S>(strand: &Strand, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer
+    {
+        serializer.serialize_char(strand.to_char())
+    }
+
+    /// Deserializes the strand from its character.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Strand, D::Error>
+        where D: Deserializer<'de>
+    {
+        let value = char::deserialize(deserializer)?;
+        Strand::from_char(&value)
+            .map_err(|_| D::Error::custom(format!("invalid strand character: {}", value)))
+    }
+}
@@ -0,0 +1,278 @@
+/*! Synthetic gene annotations for testing and benchmarking.
+
+The generator creates small but well-formed annotations: genes are placed one after another on
+each sequence, separated by intergenic gaps, and each gene has one or more transcripts sharing a
+set of exons. Alternative isoforms are created by skipping internal exons, and coding
+transcripts have coding regions whose lengths are multiples of three, with room for their stop
+codons.
+
+Generation is seeded, so that the same generator settings always result in the same genes:
+
+```
+use gte::AnnotationGenerator;
+
+let mut generator = AnnotationGenerator::new();
+let _ = generator.seed(7).num_seqs(2).genes_per_seq(5).isoforms(1, 3);
+let genes = generator.generate();
+assert_eq!(genes.len(), 10);
+assert_eq!(genes[0].id(), Some("chr1.g1"));
+
+let starts = |genes: Vec<gte::Gene>| genes.iter().map(|g| g.start()).collect::<Vec<u64>>();
+assert_eq!(starts(genes), starts(generator.generate()));
+```
+*/
+use std::cmp::{max, min};
+
+use {Assembly, Coord, Gene, GBuilder, Strand, TranscriptCoords};
+use utils::SplitMix64;
+
+
+/// Seed used when none is set.
+const DEF_SEED: u64 = 0x5EED;
+
+/// Minimum length of generated exons.
+///
+/// This ensures that coding regions always fit in the first and last exons.
+const MIN_EXON_LEN: u64 = 20;
+
+/// Generator of deterministic, synthetic gene annotations.
+#[derive(Debug, Clone)]
+pub struct AnnotationGenerator {
+    seed: u64,
+    seq_prefix: String,
+    num_seqs: usize,
+    genes_per_seq: usize,
+    isoforms: Coord<usize>,
+    exons: Coord<usize>,
+    exon_len: Coord<u64>,
+    intron_len: Coord<u64>,
+    intergenic_len: Coord<u64>,
+    coding_ratio: f64,
+    reverse_ratio: f64,
+}
+
+impl Default for AnnotationGenerator {
+    fn default() -> Self {
+        AnnotationGenerator::new()
+    }
+}
+
+impl AnnotationGenerator {
+
+    /// Creates a generator with the default settings.
+    ///
+    /// By default, 10 genes are generated on a single sequence named `chr1`. Each gene has 1 to
+    /// 3 transcripts with 1 to 6 exons, three quarters of the genes are coding, and half of the
+    /// genes are on the reverse strand.
+    pub fn new() -> Self {
+        AnnotationGenerator {
+            seed: DEF_SEED,
+            seq_prefix: "chr".to_owned(),
+            num_seqs: 1,
+            genes_per_seq: 10,
+            isoforms: (1, 3),
+            exons: (1, 6),
+            exon_len: (60, 400),
+            intron_len: (80, 2000),
+            intergenic_len: (500, 5000),
+            coding_ratio: 0.75,
+            reverse_ratio: 0.5,
+        }
+    }
+
+    /// Sets the seed of the random number generator.
+    pub fn seed(&mut self, seed: u64) -> &mut Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Sets the prefix of the sequence names, which are numbered from 1.
+    pub fn seq_prefix<T>(&mut self, prefix: T) -> &mut Self
+        where T: Into<String>
+    {
+        self.seq_prefix = prefix.into();
+        self
+    }
+
+    /// Sets the number of sequences.
+    pub fn num_seqs(&mut self, num_seqs: usize) -> &mut Self {
+        self.num_seqs = num_seqs;
+        self
+    }
+
+    /// Sets the number of genes on each sequence.
+    pub fn genes_per_seq(&mut self, genes_per_seq: usize) -> &mut Self {
+        self.genes_per_seq = genes_per_seq;
+        self
+    }
+
+    /// Sets the inclusive range of the number of transcripts per gene.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min` is zero or larger than `max`.
+    pub fn isoforms(&mut self, min: usize, max: usize) -> &mut Self {
+        assert!(min > 0 && min <= max, "invalid isoform count range");
+        self.isoforms = (min, max);
+        self
+    }
+
+    /// Sets the inclusive range of the number of exons per gene.
+    ///
+    /// Transcripts always contain the first and last exons of their gene, and may skip any of
+    /// the others.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min` is zero or larger than `max`.
+    pub fn exons(&mut self, min: usize, max: usize) -> &mut Self {
+        assert!(min > 0 && min <= max, "invalid exon count range");
+        self.exons = (min, max);
+        self
+    }
+
+    /// Sets the inclusive range of exon lengths.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min` is smaller than 20 or larger than `max`.
+    pub fn exon_len(&mut self, min: u64, max: u64) -> &mut Self {
+        assert!(min >= MIN_EXON_LEN && min <= max, "invalid exon length range");
+        self.exon_len = (min, max);
+        self
+    }
+
+    /// Sets the inclusive range of intron lengths.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min` is zero or larger than `max`.
+    pub fn intron_len(&mut self, min: u64, max: u64) -> &mut Self {
+        assert!(min > 0 && min <= max, "invalid intron length range");
+        self.intron_len = (min, max);
+        self
+    }
+
+    /// Sets the inclusive range of the lengths of gaps before, between, and after genes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min` is larger than `max`.
+    pub fn intergenic_len(&mut self, min: u64, max: u64) -> &mut Self {
+        assert!(min <= max, "invalid intergenic length range");
+        self.intergenic_len = (min, max);
+        self
+    }
+
+    /// Sets the fraction of genes that are coding.
+    pub fn coding_ratio(&mut self, ratio: f64) -> &mut Self {
+        self.coding_ratio = ratio;
+        self
+    }
+
+    /// Sets the fraction of genes that are on the reverse strand.
+    pub fn reverse_ratio(&mut self, ratio: f64) -> &mut Self {
+        self.reverse_ratio = ratio;
+        self
+    }
+
+    /// Generates the genes.
+    ///
+    /// Genes are named `{seq_name}.g{n}` and their transcripts `{gene_id}.t{n}`, both numbered
+    /// from 1. The genes are sorted by sequence and position.
+    pub fn generate(&self) -> Vec<Gene> {
+        self.generate_with_assembly().0
+    }
+
+    /// Generates the genes, along with an assembly containing the lengths of their sequences.
+    pub fn generate_with_assembly(&self) -> (Vec<Gene>, Assembly) {
+        let mut rng = SplitMix64::new(self.seed);
+        let mut genes = Vec::with_capacity(self.num_seqs * self.genes_per_seq);
+        let mut assembly = Assembly::new();
+
+        for seq_idx in 0..self.num_seqs {
+            let seq_name = format!("{}{}", self.seq_prefix, seq_idx + 1);
+            let mut cursor = 0;
+            for gene_idx in 0..self.genes_per_seq {
+                cursor += rng.between(self.intergenic_len.0, self.intergenic_len.1);
+                let gene_id = format!("{}.g{}", seq_name, gene_idx + 1);
+                let gene = self.generate_gene(&mut rng, &seq_name, gene_id, cursor);
+                cursor = gene.end();
+                genes.push(gene);
+            }
+            cursor += rng.between(self.intergenic_len.0, self.intergenic_len.1);
+            assembly.add_seq(seq_name, cursor);
+        }
+
+        (genes, assembly)
+    }
+
+    /// Generates a single gene starting at the given position.
+    fn generate_gene(&self, rng: &mut SplitMix64, seq_name: &str, gene_id: String, start: u64)
+        -> Gene
+    {
+        let strand =
+            if rng.chance(self.reverse_ratio) { Strand::Reverse } else { Strand::Forward };
+        let num_exons = rng.between(self.exons.0 as u64, self.exons.1 as u64) as usize;
+        let mut exons = Vec::with_capacity(num_exons);
+        let mut cursor = start;
+        for idx in 0..num_exons {
+            if idx > 0 {
+                cursor += rng.between(self.intron_len.0, self.intron_len.1);
+            }
+            let exon_end = cursor + rng.between(self.exon_len.0, self.exon_len.1);
+            exons.push((cursor, exon_end));
+            cursor = exon_end;
+        }
+        let (first, last) = (exons[0], exons[num_exons - 1]);
+
+        // The coding region starts in the first third of the first exon and ends in the last
+        // third of the last exon, leaving at least three bases for the stop codon on either
+        // side. Each transcript then trims its coding region so its length is a multiple of
+        // three, which moves the coding 3' end by at most two bases.
+        let coding = if rng.chance(self.coding_ratio) {
+            let first_len = first.1 - first.0;
+            let last_len = last.1 - last.0;
+            let coding_start = first.0 + rng.between(3, first_len / 3);
+            let coding_end = last.1 - rng.between(3, last_len / 3);
+            Some((coding_start, coding_end))
+        } else {
+            None
+        };
+
+        let num_isoforms = rng.between(self.isoforms.0 as u64, self.isoforms.1 as u64);
+        let mut gbuilder = GBuilder::new(seq_name, first.0, last.1)
+            .strand(strand)
+            .id(gene_id.as_str());
+        for idx in 0..num_isoforms {
+            // the first transcript always contains all exons
+            let trx_exons = exons.iter().enumerate()
+                .filter(|&(eidx, _)| {
+                    idx == 0 || eidx == 0 || eidx == num_exons - 1 || rng.chance(0.5)
+                })
+                .map(|(_, &coord)| coord)
+                .collect::<Vec<Coord<u64>>>();
+            let trx_coding = coding.map(|coord| trim_coding(coord, &trx_exons, &strand));
+            let trx_coords = TranscriptCoords::from(((first.0, last.1), trx_exons, trx_coding));
+            gbuilder = gbuilder.add_transcript_coords(format!("{}.t{}", gene_id, idx + 1),
+                                                      trx_coords);
+        }
+
+        gbuilder.build()
+            .expect("synthetic genes are valid")
+    }
+}
+
+/// Trims the 3' end of the given coding region so that its length within the given exons is a
+/// multiple of three.
+fn trim_coding(coding: Coord<u64>, exons: &[Coord<u64>], strand: &Strand) -> Coord<u64> {
+    let len = exons.iter()
+        .filter(|&&(start, end)| start < coding.1 && end > coding.0)
+        .map(|&(start, end)| min(end, coding.1) - max(start, coding.0))
+        .fold(0, |acc, n| acc + n);
+    let excess = len % 3;
+    match strand {
+        &Strand::Reverse => (coding.0 + excess, coding.1),
+        _ => (coding.0, coding.1 - excess),
+    }
+}
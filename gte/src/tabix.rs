@@ -0,0 +1,215 @@
+/*! Region queries on bgzip-compressed files using tabix indices.
+
+The [tabix](https://samtools.github.io/hts-specs/tabix.pdf) index maps genomic regions to
+positions in a [BGZF](https://samtools.github.io/hts-specs/SAMv1.pdf)-compressed, coordinate-sorted
+file. These positions are virtual offsets, which combine the offset of a compressed block in the
+file with the offset of a line within the uncompressed block.
+
+Only the parts needed to find where reading should start are implemented here. Since indexed
+files are sorted, readers are expected to read from that position onwards until they encounter a
+line past the queried region.
+*/
+use std::cmp::min;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use flate2::read::MultiGzDecoder;
+
+
+quick_error! {
+    /// Errors that occur when reading tabix indices.
+    #[derive(Debug)]
+    pub enum TabixError {
+        /// Occurs when the index does not start with the tabix magic string.
+        InvalidMagic {
+            description("invalid tabix index magic string")
+        }
+        /// Occurs when the index ends before all its contents are read.
+        TruncatedIndex {
+            description("tabix index is truncated")
+        }
+        /// Errors propagated from reading the underlying files.
+        Io(err: io::Error) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// Magic string at the beginning of tabix indices.
+const TBI_MAGIC: &'static [u8] = b"TBI\x01";
+
+/// Size of the linear index windows, in bases.
+const LINEAR_SHIFT: u64 = 14;
+
+/// Largest coordinate covered by the binning scheme.
+const MAX_COORD: u64 = 1 << 29;
+
+/// Bin level shifts and first bin numbers of the binning scheme, from the largest level.
+const BIN_LEVELS: [(u64, u64); 5] = [(26, 1), (23, 9), (20, 73), (17, 585), (14, 4681)];
+
+/// Index of a single reference sequence.
+#[derive(Debug, Clone, Default)]
+struct RefIndex {
+    bins: HashMap<u32, Vec<(u64, u64)>>,
+    intervals: Vec<u64>,
+}
+
+/// Tabix index of a bgzip-compressed file.
+#[derive(Debug, Clone)]
+pub(crate) struct TabixIndex {
+    ref_ids: HashMap<String, usize>,
+    refs: Vec<RefIndex>,
+}
+
+impl TabixIndex {
+
+    /// Reads the tabix index in the given path.
+    pub(crate) fn from_file<P: AsRef<Path>>(path: P) -> Result<TabixIndex, TabixError> {
+        let file = fs::File::open(path)?;
+        let mut raw = Vec::new();
+        let _ = MultiGzDecoder::new(file)?.read_to_end(&mut raw)?;
+        TabixIndex::from_bytes(&raw)
+    }
+
+    /// Parses the given uncompressed index.
+    fn from_bytes(raw: &[u8]) -> Result<TabixIndex, TabixError> {
+        if !raw.starts_with(TBI_MAGIC) {
+            return Err(TabixError::InvalidMagic);
+        }
+        let mut cur = ByteCursor { raw: raw, pos: TBI_MAGIC.len() };
+        let n_ref = cur.read_u32()? as usize;
+        // format, sequence, start, and end columns, meta character, and skipped lines
+        cur.skip(4 * 6)?;
+        let l_nm = cur.read_u32()? as usize;
+        let names = cur.take(l_nm)?;
+        let ref_ids = names.split(|&b| b == 0)
+            .filter(|name| !name.is_empty())
+            .enumerate()
+            .map(|(idx, name)| (String::from_utf8_lossy(name).into_owned(), idx))
+            .collect::<HashMap<String, usize>>();
+
+        // counts are not trusted for pre-allocation beyond what the remaining bytes can hold:
+        // each reference takes at least 8 bytes, and each chunk 16 bytes
+        let mut refs = Vec::with_capacity(min(n_ref, cur.remaining() / 8));
+        for _ in 0..n_ref {
+            let mut ref_index = RefIndex::default();
+            let n_bin = cur.read_u32()?;
+            for _ in 0..n_bin {
+                let bin = cur.read_u32()?;
+                let n_chunk = cur.read_u32()?;
+                let mut chunks = Vec::with_capacity(min(n_chunk as usize, cur.remaining() / 16));
+                for _ in 0..n_chunk {
+                    chunks.push((cur.read_u64()?, cur.read_u64()?));
+                }
+                let _ = ref_index.bins.insert(bin, chunks);
+            }
+            let n_intv = cur.read_u32()?;
+            for _ in 0..n_intv {
+                ref_index.intervals.push(cur.read_u64()?);
+            }
+            refs.push(ref_index);
+        }
+
+        Ok(TabixIndex { ref_ids: ref_ids, refs: refs })
+    }
+
+    /// Returns the virtual offset of the first line that may overlap the given zero-based,
+    /// half-open region.
+    ///
+    /// `None` is returned when no line may overlap the region. As in htslib, the region end is
+    /// clamped to the largest coordinate covered by the binning scheme.
+    pub(crate) fn start_offset(&self, seq_name: &str, start: u64, end: u64) -> Option<u64> {
+        let ref_index = match self.ref_ids.get(seq_name).and_then(|&idx| self.refs.get(idx)) {
+            Some(ref_index) => ref_index,
+            None => return None,
+        };
+        let end = min(end, MAX_COORD);
+        if start >= end {
+            return None;
+        }
+        let min_offset = if ref_index.intervals.is_empty() {
+            0
+        } else {
+            let window = min((start >> LINEAR_SHIFT) as usize, ref_index.intervals.len() - 1);
+            ref_index.intervals[window]
+        };
+
+        reg2bins(start, end).into_iter()
+            .filter_map(|bin| ref_index.bins.get(&bin))
+            .flat_map(|chunks| chunks.iter())
+            .filter(|&&(_, chunk_end)| chunk_end > min_offset)
+            .map(|&(chunk_start, _)| chunk_start)
+            .min()
+    }
+}
+
+/// Opens the given bgzip-compressed file for reading from the given virtual offset.
+pub(crate) fn open_at<P: AsRef<Path>>(path: P, virtual_offset: u64)
+    -> io::Result<MultiGzDecoder<fs::File>>
+{
+    let mut file = fs::File::open(path)?;
+    let _ = file.seek(SeekFrom::Start(virtual_offset >> 16))?;
+    let mut decoder = MultiGzDecoder::new(file)?;
+    let within_block = virtual_offset & 0xFFFF;
+    let _ = io::copy(&mut (&mut decoder).take(within_block), &mut io::sink())?;
+    Ok(decoder)
+}
+
+/// Returns the bins that may contain features overlapping the given zero-based, half-open
+/// region.
+///
+/// The region must not be empty, and its end is clamped to the largest coordinate covered by the
+/// binning scheme.
+fn reg2bins(start: u64, end: u64) -> Vec<u32> {
+    let last = min(end, MAX_COORD) - 1;
+    let start = min(start, last);
+    let mut bins = vec![0];
+    for &(shift, first_bin) in BIN_LEVELS.iter() {
+        for bin in (first_bin + (start >> shift))..(first_bin + (last >> shift) + 1) {
+            bins.push(bin as u32);
+        }
+    }
+    bins
+}
+
+/// Helper struct for reading little-endian integers from a byte slice.
+struct ByteCursor<'a> {
+    raw: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteCursor<'a> {
+
+    fn remaining(&self) -> usize {
+        self.raw.len() - self.pos
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], TabixError> {
+        if self.pos + n > self.raw.len() {
+            return Err(TabixError::TruncatedIndex);
+        }
+        let bytes = &self.raw[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, n: usize) -> Result<(), TabixError> {
+        self.take(n).map(|_| ())
+    }
+
+    fn read_u32(&mut self) -> Result<u32, TabixError> {
+        let bytes = self.take(4)?;
+        Ok(bytes.iter().rev().fold(0, |acc, &b| (acc << 8) | u32::from(b)))
+    }
+
+    fn read_u64(&mut self) -> Result<u64, TabixError> {
+        let bytes = self.take(8)?;
+        Ok(bytes.iter().rev().fold(0, |acc, &b| (acc << 8) | u64::from(b)))
+    }
+}
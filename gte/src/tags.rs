@@ -0,0 +1,91 @@
+/*! Transcript tags and support levels from Ensembl and GENCODE annotations.
+
+Ensembl and GENCODE GTF files describe transcripts with `tag` attributes, such as `basic`,
+`CCDS`, or `MANE_Select`, and with a `transcript_support_level` attribute ranging from `1`
+(all splice junctions supported by a single mRNA) to `5` (no supporting evidence), or `NA`.
+Transcripts keep these as plain attributes, and `Transcript` provides typed accessors for them:
+
+```
+use gte::{TBuilder, TranscriptTag};
+
+let trx = TBuilder::new("chr1", 100, 1000)
+    .attribute("tag", "basic")
+    .attribute("tag", "MANE_Select")
+    .attribute("transcript_support_level", "1 (assigned to previous version 3)")
+    .coords(vec![(100, 1000)], None)
+    .build()
+    .unwrap();
+assert!(trx.has_tag(&TranscriptTag::ManeSelect));
+assert_eq!(trx.tags(), vec![TranscriptTag::Basic, TranscriptTag::ManeSelect]);
+assert_eq!(trx.support_level(), Some(1));
+```
+*/
+use std::fmt;
+
+
+/// Attribute key of transcript tags.
+pub(crate) const TAG_KEY: &'static str = "tag";
+
+/// Attribute key of transcript support levels.
+pub(crate) const SUPPORT_LEVEL_KEY: &'static str = "transcript_support_level";
+
+/// Tag of a transcript.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TranscriptTag {
+    /// `basic`, for transcripts in the GENCODE basic set.
+    Basic,
+    /// `CCDS`, for transcripts that are part of the consensus CDS set.
+    Ccds,
+    /// `MANE_Select`, for the MANE Select transcript of a gene.
+    ManeSelect,
+    /// `MANE_Plus_Clinical`, for additional MANE transcripts of clinical interest.
+    ManePlusClinical,
+    /// `Ensembl_canonical`, for the canonical transcript of a gene in Ensembl.
+    EnsemblCanonical,
+    /// Any other tag.
+    Other(String),
+}
+
+impl TranscriptTag {
+
+    /// Returns the name of the tag, as written in annotation files.
+    pub fn as_str(&self) -> &str {
+        match *self {
+            TranscriptTag::Basic => "basic",
+            TranscriptTag::Ccds => "CCDS",
+            TranscriptTag::ManeSelect => "MANE_Select",
+            TranscriptTag::ManePlusClinical => "MANE_Plus_Clinical",
+            TranscriptTag::EnsemblCanonical => "Ensembl_canonical",
+            TranscriptTag::Other(ref name) => name.as_str(),
+        }
+    }
+}
+
+impl<'a> From<&'a str> for TranscriptTag {
+    fn from(name: &'a str) -> TranscriptTag {
+        match name {
+            "basic" => TranscriptTag::Basic,
+            "CCDS" => TranscriptTag::Ccds,
+            "MANE_Select" => TranscriptTag::ManeSelect,
+            "MANE_Plus_Clinical" => TranscriptTag::ManePlusClinical,
+            "Ensembl_canonical" => TranscriptTag::EnsemblCanonical,
+            other => TranscriptTag::Other(other.to_owned()),
+        }
+    }
+}
+
+impl fmt::Display for TranscriptTag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Helper function to parse a transcript support level value.
+///
+/// Ensembl may append a note to the level, as in `1 (assigned to previous version 3)`, so only
+/// the leading number is used. Values outside of 1 to 5, such as `NA`, give no level.
+pub(crate) fn parse_support_level(raw: &str) -> Option<u8> {
+    raw.split_whitespace().next()
+        .and_then(|level| level.parse::<u8>().ok())
+        .and_then(|level| if level >= 1 && level <= 5 { Some(level) } else { None })
+}
@@ -0,0 +1,135 @@
+/*! Reading annotations on background threads.
+
+The functions in this module are only available with the `threads` feature enabled. They parse
+an annotation file on a background thread and send the assembled genes over a bounded channel,
+so that interactive applications such as genome viewers can stay responsive while loading.
+
+The channel holds a limited number of genes. Parsing blocks once the channel is full and resumes
+as the receiver takes genes from it, so a slow consumer never causes genes to pile up in memory.
+Parsing stops as soon as the receiver is dropped.
+
+The format of the file is inferred from its extension: `.gtf` files are read as GTF, while
+`.refflat` and `.txt` files are read as refFlat.
+*/
+use std::error::Error;
+use std::io;
+#[cfg(feature = "threads")]
+use std::path::Path;
+#[cfg(feature = "threads")]
+use std::sync::mpsc::{self, Receiver, SyncSender};
+#[cfg(feature = "threads")]
+use std::thread::{self, JoinHandle};
+
+#[cfg(feature = "threads")]
+use {Gene, GtfReader, RefFlatReader};
+
+
+/// Default number of genes held by the channel.
+#[cfg(feature = "threads")]
+const DEF_CHANNEL_CAPACITY: usize = 256;
+
+quick_error! {
+    /// Errors that occur when reading annotations on background threads.
+    #[derive(Debug)]
+    pub enum ThreadError {
+        /// Occurs when the format of a file can not be inferred from its extension.
+        UnknownFormat(path: String) {
+            description("unknown annotation file format")
+            display(self_) -> ("{}, path: {}", self_.description(), path)
+        }
+        /// Errors propagated from opening the underlying file.
+        Io(err: io::Error) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// Annotation file formats that can be read on background threads.
+#[cfg(feature = "threads")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Gtf,
+    RefFlat,
+}
+
+#[cfg(feature = "threads")]
+impl Format {
+
+    /// Infers the format of the file in the given path from its extension.
+    fn from_path(path: &Path) -> Option<Format> {
+        let ext = path.extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_lowercase());
+        match ext.as_ref().map(|ext| ext.as_str()) {
+            Some("gtf") => Some(Format::Gtf),
+            Some("refflat") | Some("txt") => Some(Format::RefFlat),
+            _ => None,
+        }
+    }
+}
+
+/// Parses the annotation file in the given path on a background thread.
+///
+/// Genes are sent over a channel holding at most 256 genes, in the order the file's reader
+/// yields them. Errors are sent over the same channel; an error in opening the file or in
+/// inferring its format is sent as the only item. The channel is closed once the file is
+/// read.
+#[cfg(feature = "threads")]
+pub fn spawn_reader<P>(path: P) -> (JoinHandle<()>, Receiver<::Result<Gene>>)
+    where P: AsRef<Path>
+{
+    spawn_reader_with_capacity(path, DEF_CHANNEL_CAPACITY)
+}
+
+/// Parses the annotation file in the given path on a background thread, sending genes over a
+/// channel holding at most the given number of genes.
+///
+/// A capacity of 0 makes every send wait until the receiver takes the gene. See
+/// `spawn_reader` for details.
+#[cfg(feature = "threads")]
+pub fn spawn_reader_with_capacity<P>(path: P, capacity: usize)
+    -> (JoinHandle<()>, Receiver<::Result<Gene>>)
+    where P: AsRef<Path>
+{
+    let path = path.as_ref().to_path_buf();
+    let (sender, receiver) = mpsc::sync_channel(capacity);
+    let handle = thread::spawn(move || {
+        if let Err(e) = send_genes(&path, &sender) {
+            let _ = sender.send(Err(e));
+        }
+    });
+    (handle, receiver)
+}
+
+/// Helper function for reading the genes of a file and sending them to the given sender.
+#[cfg(feature = "threads")]
+fn send_genes(path: &Path, sender: &SyncSender<::Result<Gene>>) -> ::Result<()> {
+    let format = Format::from_path(path)
+        .ok_or_else(|| ThreadError::UnknownFormat(path.display().to_string()))?;
+    match format {
+        Format::Gtf => {
+            let mut reader = GtfReader::from_file(path).map_err(ThreadError::from)?;
+            send_all(reader.genes_stream(), sender);
+        },
+        Format::RefFlat => {
+            let mut reader = RefFlatReader::from_file(path).map_err(ThreadError::from)?;
+            send_all(reader.genes_stream(), sender);
+        },
+    }
+    Ok(())
+}
+
+/// Helper function for sending genes until the receiver is dropped.
+#[cfg(feature = "threads")]
+fn send_all<I>(genes: I, sender: &SyncSender<::Result<Gene>>)
+    where I: Iterator<Item=::Result<Gene>>
+{
+    for gene in genes {
+        if sender.send(gene).is_err() {
+            break;
+        }
+    }
+}
@@ -0,0 +1,125 @@
+/*! Collections of annotations keyed by genome.
+
+A `Workspace` holds several indexed annotations under names chosen by the caller, such as
+`hg38` and `mm10` or two versions of the same annotation, and routes overlap queries to the
+annotation with the given name. Queries can also be run against all annotations at once, for
+example to compare the genes of two annotation versions in the same region.
+*/
+use std::error::Error;
+
+use linked_hash_map::{self, LinkedHashMap};
+
+use {Gene, GenomeAnnotation, Transcript};
+
+
+quick_error! {
+    /// Errors that occur when querying workspaces.
+    #[derive(Debug)]
+    pub enum WorkspaceError {
+        /// Occurs when the workspace has no annotation with the given name.
+        UnknownGenome(key: String) {
+            description("genome not found in workspace")
+            display(self_) -> ("{}: {}", self_.description(), key)
+        }
+    }
+}
+
+/// Named annotations, indexed for overlap queries.
+///
+/// The annotations are stored in the order they are added.
+#[derive(Debug, Clone, Default)]
+pub struct Workspace {
+    annotations: LinkedHashMap<String, GenomeAnnotation>,
+}
+
+impl Workspace {
+
+    /// Creates an empty workspace.
+    pub fn new() -> Self {
+        Workspace { annotations: LinkedHashMap::new() }
+    }
+
+    /// Adds an annotation under the given name, indexing it if necessary.
+    ///
+    /// Any annotation previously stored under the same name is replaced and returned. Since
+    /// `AnnotationSet` converts into `GenomeAnnotation`, annotation sets can be added directly.
+    pub fn insert<K, A>(&mut self, key: K, annotation: A) -> Option<GenomeAnnotation>
+        where K: Into<String>, A: Into<GenomeAnnotation>
+    {
+        self.annotations.insert(key.into(), annotation.into())
+    }
+
+    /// Removes and returns the annotation with the given name.
+    pub fn remove(&mut self, key: &str) -> Option<GenomeAnnotation> {
+        self.annotations.remove(key)
+    }
+
+    /// Returns the annotation with the given name.
+    pub fn get(&self, key: &str) -> Option<&GenomeAnnotation> {
+        self.annotations.get(key)
+    }
+
+    /// Returns whether the workspace contains an annotation with the given name.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.annotations.contains_key(key)
+    }
+
+    /// Returns the names of the annotations, in the order they were added.
+    pub fn keys(&self) -> Vec<&str> {
+        self.annotations.keys().map(|key| key.as_str()).collect()
+    }
+
+    /// Returns an iterator over the names and annotations, in the order they were added.
+    pub fn iter(&self) -> linked_hash_map::Iter<String, GenomeAnnotation> {
+        self.annotations.iter()
+    }
+
+    /// Returns the number of annotations in the workspace.
+    pub fn len(&self) -> usize {
+        self.annotations.len()
+    }
+
+    /// Returns whether the workspace contains no annotations.
+    pub fn is_empty(&self) -> bool {
+        self.annotations.is_empty()
+    }
+
+    /// Returns the genes of the named annotation overlapping the given zero-based, half-open
+    /// region, sorted by their start coordinates.
+    pub fn find_overlapping(&self, key: &str, seq_name: &str, start: u64, end: u64)
+        -> ::Result<Vec<&Gene>>
+    {
+        self.annotation(key)
+            .map(|annotation| annotation.find_overlapping(seq_name, start, end))
+    }
+
+    /// Returns the transcripts of the named annotation overlapping the given zero-based,
+    /// half-open region, sorted by their start coordinates.
+    pub fn find_overlapping_transcripts(&self, key: &str, seq_name: &str, start: u64, end: u64)
+        -> ::Result<Vec<&Transcript>>
+    {
+        self.annotation(key)
+            .map(|annotation| annotation.find_overlapping_transcripts(seq_name, start, end))
+    }
+
+    /// Returns the genes of all annotations overlapping the given zero-based, half-open region,
+    /// along with the names of their annotations.
+    ///
+    /// The genes are grouped by annotation in the order the annotations were added, and are
+    /// sorted by their start coordinates within each annotation.
+    pub fn find_overlapping_all(&self, seq_name: &str, start: u64, end: u64)
+        -> Vec<(&str, &Gene)>
+    {
+        self.annotations.iter()
+            .flat_map(|(key, annotation)| {
+                annotation.find_overlapping(seq_name, start, end).into_iter()
+                    .map(move |gene| (key.as_str(), gene))
+            })
+            .collect()
+    }
+
+    fn annotation(&self, key: &str) -> ::Result<&GenomeAnnotation> {
+        self.annotations.get(key)
+            .ok_or_else(|| ::Error::from(WorkspaceError::UnknownGenome(key.to_owned())))
+    }
+}
@@ -0,0 +1,81 @@
+#[macro_use]
+extern crate matches;
+extern crate gte;
+
+use gte::{AliasError, AnnotationSet, Error, GffReader, GffType, GtfReader, RefFlatReader,
+          SeqNameAliases};
+
+
+static MULT_ROWS_NO_CDS: &'static str = include_str!("data/mult_rows_no_cds.refFlat");
+
+static ALIASES: &'static str = "\
+# alias\tchrom\tsource
+1\tchr1\tensembl
+CM000663.2\tchr1\tgenbank
+3\tchr3\tensembl
+";
+
+#[test]
+fn aliases_from_reader() {
+    let aliases = SeqNameAliases::from_reader(ALIASES.as_bytes()).expect("aliases");
+    assert_eq!(aliases.len(), 3);
+    assert_eq!(aliases.get("CM000663.2"), Some("chr1"));
+    assert_eq!(aliases.resolve("3"), "chr3");
+    assert_eq!(aliases.resolve("chrM"), "chrM");
+
+    let inverse = aliases.inverse();
+    assert_eq!(inverse.len(), 2);
+    assert_eq!(inverse.get("chr1"), Some("1"));
+
+    let err = SeqNameAliases::from_reader("chr1\n".as_bytes()).unwrap_err();
+    assert!(matches!(err, Error::Alias(AliasError::InvalidRecord(1))));
+}
+
+#[test]
+fn aliases_ensembl_to_ucsc() {
+    let aliases = SeqNameAliases::ensembl_to_ucsc();
+    assert_eq!(aliases.len(), 25);
+    assert_eq!(aliases.get("22"), Some("chr22"));
+    assert_eq!(aliases.get("MT"), Some("chrM"));
+    assert_eq!(aliases.inverse().get("chrX"), Some("X"));
+}
+
+#[test]
+fn aliases_refflat_reader_and_rename() {
+    let mut reader = RefFlatReader::from_reader(MULT_ROWS_NO_CDS.as_bytes());
+    let _ = reader.seq_name_aliases(SeqNameAliases::ensembl_to_ucsc().inverse());
+    let mut annotation = AnnotationSet::try_from_results(reader.genes_stream())
+        .expect("an annotation");
+    let seq_names = |annotation: &AnnotationSet| {
+        annotation.transcripts()
+            .flat_map(|trx| trx.exons().iter().map(|exon| exon.seq_name().to_owned()))
+            .collect::<Vec<String>>()
+    };
+    assert!(seq_names(&annotation).iter().all(|name| name == "1" || name == "3"));
+
+    annotation.rename_seqs(&SeqNameAliases::ensembl_to_ucsc());
+    assert!(annotation.genes().iter().all(|gene| gene.seq_name().starts_with("chr")));
+    assert!(seq_names(&annotation).iter().all(|name| name == "chr1" || name == "chr3"));
+}
+
+#[test]
+fn aliases_gtf_reader() {
+    let gtf = "MT\tsrc\texon\t1\t10\t.\t+\t.\tgene_id \"g1\"; transcript_id \"t1\";\n";
+    let mut reader = GtfReader::from_reader(gtf.as_bytes());
+    let _ = reader.seq_name_aliases(SeqNameAliases::ensembl_to_ucsc());
+    let trx = reader.transcripts_stream().next().expect("a result").expect("a transcript");
+    assert_eq!(trx.seq_name(), "chrM");
+    assert_eq!(trx.exons()[0].seq_name(), "chrM");
+}
+
+#[test]
+fn aliases_gff_reader() {
+    let gtf = "MT\tsrc\ttranscript\t1\t10\t.\t+\t.\tgene_id \"g1\"; transcript_id \"t1\";
+MT\tsrc\texon\t1\t10\t.\t+\t.\tgene_id \"g1\"; transcript_id \"t1\";
+";
+    let mut reader = GffReader::from_reader(gtf.as_bytes(), GffType::GTF2);
+    let _ = reader.seq_name_aliases(SeqNameAliases::ensembl_to_ucsc());
+    let trx = reader.transcripts().expect("transcripts")
+        .next().expect("a result").expect("a transcript");
+    assert_eq!(trx.seq_name(), "chrM");
+}
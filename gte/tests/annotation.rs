@@ -0,0 +1,177 @@
+extern crate gte;
+
+mod common;
+
+use gte::{AnnotationSet, Gene, GenomeAnnotation, RegionLabel, RegionWindows, Strand,
+          TranscriptCoords, overlaps, overlaps_with};
+use gte::sort::ChromOrder;
+use Strand::*;
+
+use common::{make_gene, single_exon_gene};
+
+
+fn make_annotation() -> AnnotationSet {
+    AnnotationSet::from_genes(vec![
+        single_exon_gene("chr2", "gene05", Forward, 100, 200),
+        single_exon_gene("chr1", "gene03", Reverse, 950, 1200),
+        single_exon_gene("chr1", "gene01", Forward, 100, 500),
+        single_exon_gene("chr1", "gene02", Forward, 400, 900),
+        single_exon_gene("chr1", "gene04", Forward, 1500, 1600),
+    ])
+}
+
+#[test]
+fn cluster_loci_unstranded() {
+    let annotation = make_annotation();
+    let loci = annotation.cluster_loci(0, false);
+    assert_eq!(loci.len(), 4);
+    assert_eq!(loci[0].id(), "XLOC_000001");
+    assert_eq!((loci[0].seq_name(), loci[0].start(), loci[0].end()), ("chr1", 100, 900));
+    assert_eq!(loci[0].strand(), &Forward);
+    assert_eq!(loci[0].gene_indices(), &[2, 3]);
+    assert_eq!(loci[1].id(), "XLOC_000002");
+    assert_eq!(loci[1].genes(&annotation)[0].id(), Some("gene03"));
+    assert_eq!(loci[3].seq_name(), "chr2");
+}
+
+#[test]
+fn cluster_loci_max_gap() {
+    let annotation = make_annotation();
+    let loci = annotation.cluster_loci(50, false);
+    assert_eq!(loci.len(), 3);
+    assert_eq!((loci[0].start(), loci[0].end()), (100, 1200));
+    assert_eq!(loci[0].strand(), &Unknown);
+    assert_eq!(loci[0].gene_indices(), &[1, 2, 3]);
+}
+
+#[test]
+fn cluster_loci_stranded() {
+    let annotation = make_annotation();
+    let loci = annotation.cluster_loci(1000, true);
+    assert_eq!(loci.len(), 3);
+    assert_eq!((loci[0].start(), loci[0].end(), loci[0].strand()), (100, 1600, &Forward));
+    assert_eq!((loci[1].start(), loci[1].end(), loci[1].strand()), (950, 1200, &Reverse));
+}
+
+fn gene_ids<'a>(genes: Vec<&'a Gene>) -> Vec<&'a str> {
+    genes.into_iter().map(|g| g.id().unwrap()).collect()
+}
+
+#[test]
+fn genome_annotation_find_overlapping() {
+    let index = GenomeAnnotation::from(make_annotation());
+    assert_eq!(index.len(), 5);
+    assert_eq!(gene_ids(index.find_overlapping("chr1", 450, 460)), vec!["gene01", "gene02"]);
+    assert_eq!(gene_ids(index.find_overlapping("chr1", 899, 1000)), vec!["gene02", "gene03"]);
+    assert_eq!(gene_ids(index.find_overlapping("chr1", 1200, 1500)), Vec::<&str>::new());
+    assert_eq!(gene_ids(index.find_overlapping("chr1", 0, 2000)),
+               vec!["gene01", "gene02", "gene03", "gene04"]);
+    assert_eq!(gene_ids(index.find_overlapping("chr2", 150, 151)), vec!["gene05"]);
+    assert!(index.find_overlapping("chr3", 0, 2000).is_empty());
+}
+
+#[test]
+fn genome_annotation_find_overlapping_transcripts() {
+    let index = make_annotation().take_genes().into_iter().collect::<GenomeAnnotation>();
+    let trxs = index.find_overlapping_transcripts("chr1", 1000, 1550);
+    assert_eq!(trxs.iter().map(|t| t.id().unwrap()).collect::<Vec<_>>(),
+               vec!["gene03.t1", "gene04.t1"]);
+    assert!(index.find_overlapping_transcripts("chr2", 200, 300).is_empty());
+}
+
+#[test]
+fn overlaps_sorted_sweep() {
+    let a = vec![
+        single_exon_gene("chr1", "a1", Forward, 100, 500),
+        single_exon_gene("chr1", "a2", Reverse, 400, 900),
+        single_exon_gene("chr1", "a3", Forward, 1500, 1600),
+        single_exon_gene("chr2", "a4", Forward, 100, 200),
+    ];
+    let b = vec![
+        single_exon_gene("chr1", "b1", Forward, 450, 460),
+        single_exon_gene("chr1", "b2", Forward, 900, 1000),
+        single_exon_gene("chr1", "b3", Forward, 1000, 2000),
+        single_exon_gene("chr2", "b4", Forward, 150, 300),
+        single_exon_gene("chr3", "b5", Forward, 100, 200),
+    ];
+    let pairs = overlaps(a, b)
+        .map(|res| {
+            let (ga, gb) = res.expect("an overlapping pair");
+            (ga.id().unwrap().to_owned(), gb.id().unwrap().to_owned())
+        })
+        .collect::<Vec<(String, String)>>();
+    let expected = vec![("a1", "b1"), ("a2", "b1"), ("a3", "b3"), ("a4", "b4")];
+    assert_eq!(pairs, expected.into_iter()
+                   .map(|(x, y)| (x.to_owned(), y.to_owned()))
+                   .collect::<Vec<(String, String)>>());
+}
+
+#[test]
+fn overlaps_unsorted() {
+    let a = vec![
+        single_exon_gene("chr1", "a1", Forward, 500, 600),
+        single_exon_gene("chr1", "a2", Forward, 100, 200),
+    ];
+    let b = vec![single_exon_gene("chr1", "b1", Forward, 100, 700)];
+    let results = overlaps(a, b).collect::<Vec<_>>();
+    assert_eq!(results.len(), 2);
+    assert!(results[0].is_ok());
+    assert!(results[1].is_err());
+}
+
+#[test]
+fn overlaps_seq_order() {
+    let a = || vec![
+        single_exon_gene("chr2", "a1", Forward, 100, 200),
+        single_exon_gene("chr10", "a2", Forward, 100, 200),
+    ];
+    let b = || vec![
+        single_exon_gene("chr2", "b1", Forward, 150, 250),
+        single_exon_gene("chr10", "b2", Forward, 150, 250),
+    ];
+    assert_eq!(overlaps(a(), b()).filter(|res| res.is_ok()).count(), 2);
+    let results = overlaps_with(a(), b(), ChromOrder::Lexicographic).collect::<Vec<_>>();
+    assert!(results.iter().any(|res| res.is_err()));
+}
+
+fn make_classify_annotation() -> AnnotationSet {
+    let coding = make_gene("chr1", "gene01", Forward, vec![
+        ("trx01", TranscriptCoords::new(1000, 2500)
+            .exons(vec![(1000, 1200), (1500, 1800), (2000, 2500)])
+            .coding(1100, 2200)),
+    ]);
+    let noncoding = single_exon_gene("chr2", "gene02", Reverse, 1000, 2000);
+    AnnotationSet::from_genes(vec![coding, noncoding])
+}
+
+#[test]
+fn classify_region_labels() {
+    use RegionLabel::*;
+    let annotation = make_classify_annotation();
+    let windows = RegionWindows::new(200, 100, 300);
+    let classify = |seq_name: &str, start: u64, end: u64| {
+        annotation.classify_region_with(seq_name, start, end, &windows)
+    };
+    assert_eq!(classify("chr1", 850, 900), vec![Promoter]);
+    assert_eq!(classify("chr1", 1050, 1060), vec![Promoter, UTR5]);
+    assert_eq!(classify("chr1", 1300, 1400), vec![Intron]);
+    assert_eq!(classify("chr1", 1150, 1600), vec![CodingExon, Intron]);
+    assert_eq!(classify("chr1", 1600, 1610), vec![CodingExon]);
+    assert_eq!(classify("chr1", 2400, 2450), vec![UTR3]);
+    assert_eq!(classify("chr1", 2600, 2700), vec![TTS]);
+    assert_eq!(classify("chr1", 5000, 6000), vec![DistalIntergenic]);
+    assert_eq!(classify("chr3", 1000, 2000), vec![DistalIntergenic]);
+
+    // noncoding, reverse strand transcript
+    assert_eq!(classify("chr2", 1950, 2050), vec![Promoter, OtherExon]);
+    assert_eq!(classify("chr2", 1500, 1600), vec![OtherExon]);
+    assert_eq!(classify("chr2", 800, 900), vec![TTS]);
+}
+
+#[test]
+fn classify_region_default_windows() {
+    let annotation = make_classify_annotation();
+    assert_eq!(annotation.classify_region("chr1", 0, 10), vec![RegionLabel::Promoter]);
+    assert_eq!(annotation.classify_region("chr1", 6000, 6010),
+               vec![RegionLabel::DistalIntergenic]);
+}
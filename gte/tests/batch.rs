@@ -0,0 +1,57 @@
+#![cfg(feature = "stream")]
+#[macro_use]
+extern crate matches;
+extern crate gte;
+
+use gte::{AnnotationGenerator, BatchReader, BatchRecord, BatchWriter, CacheError, Error,
+          GeneDiff};
+
+
+#[test]
+fn batch_roundtrip() {
+    let mut generator = AnnotationGenerator::new();
+    let _ = generator.num_seqs(1).genes_per_seq(5).isoforms(1, 2);
+    let genes = generator.generate();
+    let transcript = genes[0].transcripts().values().next().expect("a transcript").clone();
+
+    let mut writer = BatchWriter::new(Vec::new()).max_records(2);
+    for gene in genes.iter() {
+        writer.write_gene(gene).expect("a gene record");
+    }
+    writer.write_transcript(&transcript).expect("a transcript record");
+    let buf = writer.finish().expect("a stream");
+
+    let batches = BatchReader::new(buf.as_slice())
+        .collect::<gte::Result<Vec<Vec<BatchRecord>>>>()
+        .expect("batches");
+    assert_eq!(batches.iter().map(|batch| batch.len()).collect::<Vec<usize>>(),
+               vec![2, 2, 2]);
+
+    let records = batches.into_iter().flat_map(|batch| batch.into_iter()).collect::<Vec<_>>();
+    for (gene, record) in genes.iter().zip(records.iter()) {
+        match record {
+            &BatchRecord::Gene(ref read_gene) =>
+                assert!(GeneDiff::between(gene, read_gene).is_empty()),
+            other => panic!("unexpected record: {:?}", other),
+        }
+    }
+    match records.last() {
+        Some(&BatchRecord::Transcript(ref read_trx)) => {
+            assert_eq!(read_trx.seq_name(), transcript.seq_name());
+            assert_eq!(read_trx.id(), transcript.id());
+            assert_eq!(read_trx.strand(), transcript.strand());
+            assert_eq!(read_trx.exons().len(), transcript.exons().len());
+        },
+        other => panic!("unexpected record: {:?}", other),
+    }
+}
+
+#[test]
+fn batch_empty_and_invalid() {
+    let buf = BatchWriter::new(Vec::new()).finish().expect("a stream");
+    assert_eq!(BatchReader::new(buf.as_slice()).count(), 0);
+
+    let mut reader = BatchReader::new(&b"GTEC\x01"[..]);
+    let err = reader.next_batch().unwrap_err();
+    assert!(matches!(err, Error::Cache(CacheError::InvalidHeader)));
+}
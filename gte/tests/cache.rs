@@ -0,0 +1,97 @@
+#[macro_use]
+extern crate matches;
+extern crate gte;
+
+mod common;
+
+use gte::{AnnotationCache, AnnotationGenerator, CacheError, Error, GBuilder, Gene, GeneDiff,
+          Strand, TBuilder, TranscriptCoords};
+
+
+fn make_genes() -> Vec<Gene> {
+    let mut generator = AnnotationGenerator::new();
+    let _ = generator.num_seqs(2).genes_per_seq(10).isoforms(1, 3).coding_ratio(0.5);
+    let mut genes = generator.generate();
+    genes.push(common::gene_builder("chrX", "geneX", Strand::Unknown, vec![
+        ("trxX", TranscriptCoords::new(100, 1000).exons(vec![(100, 300), (500, 1000)])),
+    ])
+        .attribute("name", "ABC1")
+        .attribute("tag", "basic")
+        .attribute("tag", "CCDS")
+        .build()
+        .expect("a gene"));
+    let mut unstranded = GBuilder::new("chrY", 100, 1000)
+        .strand(Strand::Unknown)
+        .id("geneY")
+        .build()
+        .expect("a gene");
+    let _ = unstranded.insert_transcript(TBuilder::new("chrY", 100, 1000)
+        .strand(Strand::Forward)
+        .id("trxY")
+        .coords(vec![(100, 1000)], None)
+        .build()
+        .expect("a transcript"))
+        .expect("an inserted transcript");
+    let _ = unstranded.insert_transcript(TBuilder::new("chrY", 100, 1000)
+        .strand(Strand::Reverse)
+        .id("trxY2")
+        .coords(vec![(100, 1000)], Some((100, 800)))
+        .cds_5_incomplete(true)
+        .cds_3_incomplete(true)
+        .build()
+        .expect("a transcript"))
+        .expect("an inserted transcript");
+    genes.push(unstranded);
+    genes
+}
+
+#[test]
+fn cache_roundtrip() {
+    let genes = make_genes();
+    let mut buf = Vec::new();
+    AnnotationCache::write_to(&mut buf, &genes).expect("a cache");
+    let cached = AnnotationCache::read_from(buf.as_slice()).expect("cached genes");
+    assert_eq!(cached.len(), genes.len());
+
+    for (gene, cached_gene) in genes.iter().zip(cached.iter()) {
+        assert!(GeneDiff::between(gene, cached_gene).is_empty());
+        assert_eq!(gene.strand(), cached_gene.strand());
+        for (tid, trx) in gene.transcripts().iter() {
+            let cached_trx = &cached_gene.transcripts()[tid];
+            assert_eq!(trx.seq_name(), cached_trx.seq_name());
+            assert_eq!(trx.strand(), cached_trx.strand());
+            assert_eq!(trx.cds_5_incomplete(), cached_trx.cds_5_incomplete());
+            assert_eq!(trx.cds_3_incomplete(), cached_trx.cds_3_incomplete());
+            for (exon, cached_exon) in trx.exons().iter().zip(cached_trx.exons().iter()) {
+                assert_eq!(exon.features(), cached_exon.features());
+            }
+        }
+    }
+    assert_eq!(cached[cached.len() - 1].transcripts()["trxY"].strand(), &Strand::Forward);
+    assert!(cached[cached.len() - 1].transcripts()["trxY2"].cds_5_incomplete());
+    let attributes = cached[cached.len() - 2].attributes();
+    assert_eq!(attributes.get_vec("tag"), Some(&vec!["basic".to_owned(), "CCDS".to_owned()]));
+
+    let mut rewritten = Vec::new();
+    AnnotationCache::write_to(&mut rewritten, &cached).expect("a cache");
+    assert_eq!(rewritten, buf);
+}
+
+#[test]
+fn cache_invalid() {
+    let genes = make_genes();
+    let mut buf = Vec::new();
+    AnnotationCache::write_to(&mut buf, &genes).expect("a cache");
+
+    assert!(matches!(AnnotationCache::read_from(&b"GTF\n"[..]).unwrap_err(),
+                     Error::Cache(CacheError::InvalidHeader)));
+
+    let mut other_version = buf.clone();
+    other_version[4] = 99;
+    assert!(matches!(AnnotationCache::read_from(other_version.as_slice()).unwrap_err(),
+                     Error::Cache(CacheError::UnsupportedVersion(99))));
+
+    let truncated = &buf[..buf.len() / 2];
+    assert!(matches!(AnnotationCache::read_from(truncated).unwrap_err(),
+                     Error::Cache(CacheError::Io(_))));
+}
@@ -0,0 +1,47 @@
+//! Gene model fixtures shared by the integration tests.
+//!
+//! Each test file includes this module with `mod common;` and uses only some of its fixtures.
+#![allow(dead_code)]
+
+use gte::{GBuilder, Gene, Strand, TranscriptCoords};
+
+
+/// Returns a builder of a gene with the given transcripts, spanning all of them.
+pub fn gene_builder<S, T>(seq_name: &str, id: &str, strand: Strand, transcripts: T) -> GBuilder
+    where S: Into<String>, T: IntoIterator<Item=(S, TranscriptCoords)>
+{
+    let transcripts = transcripts.into_iter().collect::<Vec<_>>();
+    let start = transcripts.iter().map(|&(_, ref coords)| coords.interval().0).min();
+    let end = transcripts.iter().map(|&(_, ref coords)| coords.interval().1).max();
+    let mut builder = GBuilder::new(seq_name, start.expect("a transcript"),
+                                    end.expect("a transcript"))
+        .strand(strand)
+        .id(id);
+    for (tid, coords) in transcripts {
+        builder = builder.add_transcript_coords(tid, coords);
+    }
+    builder
+}
+
+/// Returns a gene with the given transcripts, spanning all of them.
+pub fn make_gene<S, T>(seq_name: &str, id: &str, strand: Strand, transcripts: T) -> Gene
+    where S: Into<String>, T: IntoIterator<Item=(S, TranscriptCoords)>
+{
+    gene_builder(seq_name, id, strand, transcripts).build().expect("a gene")
+}
+
+/// Returns a gene with a single noncoding transcript made of the given exons.
+///
+/// The transcript identifier is the gene identifier suffixed with `.t1`.
+pub fn spliced_gene(seq_name: &str, id: &str, strand: Strand, exons: Vec<(u64, u64)>) -> Gene {
+    let (start, end) = (exons[0].0, exons[exons.len() - 1].1);
+    make_gene(seq_name, id, strand,
+              vec![(format!("{}.t1", id), TranscriptCoords::new(start, end).exons(exons))])
+}
+
+/// Returns a gene with a single noncoding, single-exon transcript.
+///
+/// The transcript identifier is the gene identifier suffixed with `.t1`.
+pub fn single_exon_gene(seq_name: &str, id: &str, strand: Strand, start: u64, end: u64) -> Gene {
+    spliced_gene(seq_name, id, strand, vec![(start, end)])
+}
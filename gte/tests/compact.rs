@@ -0,0 +1,88 @@
+extern crate gte;
+
+use gte::{AnnotationGenerator, AnnotationSet, CompactAnnotation, GBuilder, GenomeAnnotation,
+          Strand, TBuilder, ValidationMode};
+
+
+fn make_annotation() -> AnnotationSet {
+    let mut generator = AnnotationGenerator::new();
+    let _ = generator.seed(7).num_seqs(3).genes_per_seq(150).isoforms(1, 4).coding_ratio(0.7);
+    AnnotationSet::from_genes(generator.generate())
+}
+
+#[test]
+fn compact_queries() {
+    let annotation = make_annotation();
+    let compact = annotation.to_compact();
+    let num_transcripts = annotation.transcripts().count();
+    assert_eq!(compact.len(), num_transcripts);
+    assert!(compact.approx_bytes() < annotation.stats().approx_bytes());
+
+    let seq_names = compact.seq_names().into_iter().map(|s| s.to_owned()).collect::<Vec<_>>();
+    assert_eq!(seq_names.len(), 3);
+    let index = GenomeAnnotation::from(annotation.clone());
+    for seq_name in seq_names.iter() {
+        let all = compact.transcripts(seq_name);
+        let max_end = all.iter().map(|trx| trx.end()).max().unwrap_or(0);
+        let mut start = 0;
+        while start < max_end {
+            let end = start + 5000;
+            let mut expected = index.find_overlapping_transcripts(seq_name, start, end)
+                .into_iter()
+                .map(|trx| (trx.start(), trx.end(), trx.id().unwrap_or("").to_owned()))
+                .collect::<Vec<_>>();
+            let mut found = compact.find_overlapping_transcripts(seq_name, start, end)
+                .into_iter()
+                .map(|trx| (trx.start(), trx.end(), trx.id().to_owned()))
+                .collect::<Vec<_>>();
+            expected.sort();
+            found.sort();
+            assert_eq!(found, expected);
+            start = end;
+        }
+    }
+    assert!(compact.find_overlapping_transcripts("chrNone", 0, 1000).is_empty());
+}
+
+#[test]
+fn compact_transcript_roundtrip() {
+    let annotation = make_annotation();
+    let compact = CompactAnnotation::from_genes(annotation.genes().to_vec());
+    for gene in annotation.genes() {
+        for (tid, trx) in gene.transcripts() {
+            let found = compact.find_overlapping_transcripts(trx.seq_name(), trx.start(),
+                                                             trx.start() + 1);
+            let stored = found.iter().find(|ctrx| ctrx.id() == tid).expect("a transcript");
+            assert_eq!(stored.gene_id(), gene.id());
+            assert_eq!(stored.strand(), trx.strand());
+            assert_eq!(stored.coding_coord(), trx.coding_coord(true));
+            let rebuilt = stored.to_transcript().expect("a transcript");
+            assert_eq!(rebuilt.exons().iter().map(|e| (e.start(), e.end())).collect::<Vec<_>>(),
+                       trx.exons().iter().map(|e| (e.start(), e.end())).collect::<Vec<_>>());
+            assert_eq!(rebuilt.coding_coord(true), trx.coding_coord(true));
+        }
+    }
+}
+
+#[test]
+fn compact_skips_unencodable_transcripts() {
+    let build = |id: &str, start: u64, end: u64| {
+        TBuilder::new("chrT", start, end)
+            .id(id)
+            .strand(Strand::Forward)
+            .coords(vec![(100, 300), (700, 1000)], None)
+            .validation(ValidationMode::Ignore)
+            .build()
+            .expect("a transcript")
+    };
+    let gene = GBuilder::new("chrT", 100, 1000)
+        .strand(Strand::Forward)
+        .validation(ValidationMode::Ignore)
+        .add_transcript(build("trx01", 100, 1000))
+        .add_transcript(build("trx02", 150, 900))
+        .build()
+        .expect("a gene");
+    let compact = CompactAnnotation::from_genes(vec![gene]);
+    assert_eq!(compact.len(), 1);
+    assert_eq!(compact.transcripts("chrT")[0].id(), "trx01");
+}
@@ -0,0 +1,91 @@
+extern crate gte;
+
+use gte::{ClassCode, GBuilder, GenomeAnnotation, Strand, TBuilder, Transcript,
+          TranscriptCoords};
+use Strand::*;
+
+
+fn make_reference() -> GenomeAnnotation {
+    let multi = GBuilder::new("chr1", 100, 600)
+        .strand(Forward)
+        .id("gene01")
+        .add_transcript_coords("trx01", TranscriptCoords::new(100, 600)
+            .exons(vec![(100, 200), (300, 400), (500, 600)]))
+        .add_transcript_coords("trx02", TranscriptCoords::new(100, 600)
+            .exons(vec![(100, 200), (500, 600)]))
+        .build()
+        .expect("a gene");
+    let single = GBuilder::new("chr1", 2000, 2500)
+        .strand(Forward)
+        .id("gene02")
+        .add_transcript_coords("trx03", TranscriptCoords::new(2000, 2500).exon(2000, 2500))
+        .build()
+        .expect("a gene");
+    GenomeAnnotation::from_genes(vec![multi, single])
+}
+
+fn make_query(seq_name: &str, strand: Strand, exons: Vec<(u64, u64)>) -> Transcript {
+    let (start, end) = (exons[0].0, exons[exons.len() - 1].1);
+    TBuilder::new(seq_name, start, end)
+        .strand(strand)
+        .id("query")
+        .coords(exons, None)
+        .build()
+        .expect("a transcript")
+}
+
+fn classify(reference: &GenomeAnnotation, seq_name: &str, strand: Strand,
+            exons: Vec<(u64, u64)>) -> (char, Option<String>)
+{
+    let found = reference.classify_transcript(&make_query(seq_name, strand, exons));
+    assert_eq!(found.query_id(), Some("query"));
+    (found.class_code().code(), found.ref_transcript_id().map(|id| id.to_owned()))
+}
+
+#[test]
+fn classify_multi_exon_queries() {
+    let reference = make_reference();
+    let exon_sets = vec![
+        (vec![(90, 200), (300, 400), (500, 650)], ('=', "trx01")),
+        (vec![(150, 200), (300, 350)], ('c', "trx01")),
+        (vec![(100, 200), (300, 400), (500, 600), (700, 800)], ('k', "trx01")),
+        (vec![(100, 200), (500, 550), (580, 600)], ('j', "trx02")),
+        (vec![(350, 450)], ('o', "trx01")),
+    ];
+    for (exons, (code, tid)) in exon_sets {
+        assert_eq!(classify(&reference, "chr1", Forward, exons),
+                   (code, Some(tid.to_owned())));
+    }
+    let (code, tid) = classify(&reference, "chr1", Forward, vec![(420, 480)]);
+    assert_eq!(code, 'i');
+    assert!(tid.is_some());
+}
+
+#[test]
+fn classify_single_exon_queries() {
+    let reference = make_reference();
+    assert_eq!(classify(&reference, "chr1", Forward, vec![(2050, 2500)]),
+               ('=', Some("trx03".to_owned())));
+    assert_eq!(classify(&reference, "chr1", Forward, vec![(2100, 2200)]),
+               ('c', Some("trx03".to_owned())));
+    assert_eq!(classify(&reference, "chr1", Reverse, vec![(2100, 2200)]),
+               ('x', Some("trx03".to_owned())));
+    assert_eq!(classify(&reference, "chr1", Forward, vec![(5000, 5100)]), ('u', None));
+    assert_eq!(classify(&reference, "chr2", Forward, vec![(100, 200)]), ('u', None));
+}
+
+#[test]
+fn classify_transcripts_report() {
+    let reference = make_reference();
+    let queries = vec![make_query("chr1", Forward, vec![(100, 200), (300, 400), (500, 600)]),
+                       make_query("chr1", Unknown, vec![(2100, 2200)])];
+    let matches = reference.classify_transcripts(queries.iter());
+    assert_eq!(matches.len(), 2);
+    assert_eq!(matches[0].class_code(), ClassCode::Exact);
+    assert_eq!(matches[0].ref_gene_id(), Some("gene01"));
+    assert_eq!(matches[0].shared_introns(), 2);
+    assert_eq!(matches[0].overlap(), 300);
+    assert_eq!(matches[1].class_code(), ClassCode::Contained);
+    assert_eq!(matches[1].ref_gene_id(), Some("gene02"));
+    assert!(ClassCode::Exact < ClassCode::Unknown);
+}
@@ -0,0 +1,91 @@
+extern crate gte;
+
+mod common;
+
+use gte::{ExonChange, Gene, GeneDiff, Strand, TranscriptCoords};
+
+use common::make_gene;
+
+
+fn make_genes() -> (Gene, Gene) {
+    let old = make_gene("chr1", "gene01", Strand::Forward, vec![
+        ("trx01", TranscriptCoords::new(100, 1000)
+            .exons(vec![(100, 200), (300, 400), (500, 600), (900, 1000)])
+            .coding(150, 950)),
+        ("trx02", TranscriptCoords::new(100, 600).exons(vec![(100, 200), (500, 600)])),
+        ("trx03", TranscriptCoords::new(300, 400).exon(300, 400)),
+    ]);
+    let new = make_gene("chr1", "gene01", Strand::Forward, vec![
+        ("trx01", TranscriptCoords::new(100, 1200)
+            .exons(vec![(100, 200), (300, 450), (700, 800), (900, 1200)])
+            .coding(150, 1100)),
+        ("trx02", TranscriptCoords::new(100, 600).exons(vec![(100, 200), (500, 600)])),
+        ("trx04", TranscriptCoords::new(700, 800).exon(700, 800)),
+    ]);
+    (old, new)
+}
+
+#[test]
+fn gene_diff_changes() {
+    let (old, new) = make_genes();
+    let diff = GeneDiff::between(&old, &new);
+    assert!(!diff.is_empty());
+    assert_eq!(diff.gene_id(), Some("gene01"));
+    assert_eq!(diff.old_region(), ("chr1", 100, 1000));
+    assert_eq!(diff.new_region(), ("chr1", 100, 1200));
+    assert_eq!(diff.gained_transcripts(), &["trx04".to_owned()]);
+    assert_eq!(diff.lost_transcripts(), &["trx03".to_owned()]);
+
+    assert_eq!(diff.transcript_diffs().len(), 1);
+    let trx_diff = &diff.transcript_diffs()[0];
+    assert_eq!(trx_diff.transcript_id(), "trx01");
+    assert_eq!(trx_diff.exon_changes(), &[
+        ExonChange::Shifted { old: (300, 400), new: (300, 450) },
+        ExonChange::Lost((500, 600)),
+        ExonChange::Gained((700, 800)),
+        ExonChange::Shifted { old: (900, 1000), new: (900, 1200) },
+    ]);
+    assert_eq!(trx_diff.coding_change(), Some((Some((150, 950)), Some((150, 1100)))));
+}
+
+#[test]
+fn gene_diff_same() {
+    let (old, _) = make_genes();
+    let diff = GeneDiff::between(&old, &old);
+    assert!(diff.is_empty());
+    assert_eq!(diff.to_string(), "gene gene01 chr1:100-1000");
+}
+
+#[test]
+fn gene_diff_text() {
+    let (old, new) = make_genes();
+    let expected = [
+        "gene gene01 chr1:100-1000 -> chr1:100-1200",
+        "+ transcript trx04",
+        "- transcript trx03",
+        "~ transcript trx01",
+        "  ~ exon 300-400 -> 300-450",
+        "  - exon 500-600",
+        "  + exon 700-800",
+        "  ~ exon 900-1000 -> 900-1200",
+        "  ~ CDS 150-950 -> 150-1100",
+    ].join("\n");
+    assert_eq!(GeneDiff::between(&old, &new).to_string(), expected);
+}
+
+#[test]
+fn gene_diff_json() {
+    let (old, new) = make_genes();
+    let expected = concat!(
+        r#"{"gene_id":"gene01","#,
+        r#""old":{"seq_name":"chr1","start":100,"end":1000},"#,
+        r#""new":{"seq_name":"chr1","start":100,"end":1200},"#,
+        r#""gained_transcripts":["trx04"],"lost_transcripts":["trx03"],"#,
+        r#""transcripts":[{"transcript_id":"trx01","exons":["#,
+        r#"{"change":"shifted","old":[300,400],"new":[300,450]},"#,
+        r#"{"change":"lost","old":[500,600]},"#,
+        r#"{"change":"gained","new":[700,800]},"#,
+        r#"{"change":"shifted","old":[900,1000],"new":[900,1200]}],"#,
+        r#""coding":{"old":[150,950],"new":[150,1100]}}]}"#);
+    assert_eq!(GeneDiff::between(&old, &new).to_json(), expected);
+}
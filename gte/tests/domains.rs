@@ -0,0 +1,82 @@
+#[macro_use]
+extern crate matches;
+extern crate gte;
+
+mod common;
+
+use gte::{AnnotationSet, BedWriter, DomainError, DomainReader, Error, Gene, Gff3Writer, Strand,
+          TranscriptCoords};
+
+
+static DOMAINS: &'static str = "# protein\tdomain\tstart\tend\tname
+trx01\tPF00069\t11\t60\tPkinase
+trx01\tPF00001\t60\t70\t
+trx99\tPF00002\t1\t5
+
+trx02\tPF00003\t1\t1
+";
+
+fn make_gene() -> Gene {
+    common::make_gene("chr1", "gene01", Strand::Forward, vec![
+        ("trx01", TranscriptCoords::new(100, 1000)
+            .exons(vec![(100, 300), (400, 500), (700, 1000)])
+            .coding(250, 751)),
+        ("trx02", TranscriptCoords::new(100, 1000).exon(100, 1000)),
+    ])
+}
+
+#[test]
+fn domain_reader() {
+    let mut reader = DomainReader::from_reader(DOMAINS.as_bytes());
+    let records = reader.records_stream()
+        .collect::<gte::Result<Vec<_>>>()
+        .expect("domain records");
+    assert_eq!(records.len(), 4);
+    assert_eq!(records[0].protein_id(), "trx01");
+    assert_eq!(records[0].domain_id(), "PF00069");
+    assert_eq!(records[0].domain_name(), Some("Pkinase"));
+    assert_eq!(records[0].residues(), 10..60);
+    assert_eq!(records[1].domain_name(), None);
+    assert_eq!(records[3].residues(), 0..1);
+
+    let mut reader = DomainReader::from_reader(&b"trx01\tPF00069\t0\t60\n"[..]);
+    let result = reader.records_stream().next().expect("a result");
+    assert!(matches!(result.unwrap_err(), Error::Domain(DomainError::InvalidRecord(1))));
+}
+
+#[test]
+fn domain_mapping() {
+    let annotation = AnnotationSet::from_genes(vec![make_gene()]);
+    let mut reader = DomainReader::from_reader(DOMAINS.as_bytes());
+    let (mapped, unmapped) = annotation.map_domains(reader.records_stream())
+        .expect("mapped domains");
+
+    assert_eq!(mapped.len(), 1);
+    assert_eq!(mapped[0].transcript_id(), "trx01");
+    assert_eq!(mapped[0].gene_id(), Some("gene01"));
+    assert_eq!(mapped[0].blocks(), &[(280, 300), (400, 500), (700, 730)]);
+    assert_eq!((mapped[0].start(), mapped[0].end()), (280, 730));
+    assert_eq!(unmapped.iter().map(|rec| rec.domain_id()).collect::<Vec<&str>>(),
+               vec!["PF00001", "PF00002", "PF00003"]);
+
+    let mut bed = BedWriter::from_memory();
+    bed.write_domain(&mapped[0]).expect("a BED line");
+    assert_eq!(bed.as_string(), "chr1\t280\t730\ttrx01:PF00069\t0\t+\t280\t730\t0\t3\t\
+                                 20,100,30,\t0,120,420,\n");
+
+    let mut gff3 = Gff3Writer::from_memory();
+    gff3.write_gene_with_domains(&make_gene(), &mapped).expect("GFF3 records");
+    let lines = gff3.as_string().lines().map(|l| l.to_owned()).collect::<Vec<String>>();
+    let domain_lines = lines.iter()
+        .filter(|line| line.contains("\tpolypeptide_domain\t"))
+        .collect::<Vec<&String>>();
+    assert_eq!(domain_lines, vec![
+        "chr1\t.\tpolypeptide_domain\t281\t300\t.\t+\t.\t\
+         ID=trx01.PF00069.11;Parent=trx01;Name=Pkinase",
+        "chr1\t.\tpolypeptide_domain\t401\t500\t.\t+\t.\t\
+         ID=trx01.PF00069.11;Parent=trx01;Name=Pkinase",
+        "chr1\t.\tpolypeptide_domain\t701\t730\t.\t+\t.\t\
+         ID=trx01.PF00069.11;Parent=trx01;Name=Pkinase",
+    ]);
+    assert_eq!(lines[lines.len() - 1], "###");
+}
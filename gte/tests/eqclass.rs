@@ -0,0 +1,64 @@
+extern crate gte;
+
+mod common;
+
+use gte::{CompatibilityIndex, EquivalenceClass, Gene, Strand, TranscriptCoords};
+
+
+fn make_gene() -> Gene {
+    common::make_gene("chrT", "gene01", Strand::Forward, vec![
+        ("trx01", TranscriptCoords::new(100, 1000)
+            .exons(vec![(100, 300), (400, 500), (700, 1000)])),
+        ("trx02", TranscriptCoords::new(100, 1000).exons(vec![(100, 300), (700, 1000)])),
+        ("trx03", TranscriptCoords::new(350, 1000).exons(vec![(350, 500), (700, 1000)])),
+    ])
+}
+
+#[test]
+fn equivalence_class_new() {
+    let ec = EquivalenceClass::new(vec!["trx02", "trx01", "trx02"]);
+    assert_eq!(ec.transcript_ids(), &["trx01".to_owned(), "trx02".to_owned()]);
+    assert_eq!(ec.len(), 2);
+    assert!(ec.contains("trx01"));
+    assert!(!ec.contains("trx03"));
+    assert_eq!(ec, EquivalenceClass::new(vec!["trx01", "trx02"]));
+}
+
+#[test]
+fn compatibility_index_unspliced() {
+    let gene = make_gene();
+    let index = CompatibilityIndex::new(&gene);
+    assert_eq!(index.gene().id(), Some("gene01"));
+    assert_eq!(index.equivalence_class(&[(150, 250)], 0),
+               EquivalenceClass::new(vec!["trx01", "trx02"]));
+    assert_eq!(index.equivalence_class(&[(410, 490)], 0),
+               EquivalenceClass::new(vec!["trx01", "trx03"]));
+    assert_eq!(index.equivalence_class(&[(800, 900)], 0),
+               EquivalenceClass::new(vec!["trx01", "trx02", "trx03"]));
+    assert_eq!(index.equivalence_class(&[(360, 450)], 0),
+               EquivalenceClass::new(vec!["trx03"]));
+    assert!(index.equivalence_class(&[(280, 320)], 0).is_empty());
+}
+
+#[test]
+fn compatibility_index_spliced() {
+    let gene = make_gene();
+    let index = CompatibilityIndex::new(&gene);
+    assert_eq!(index.equivalence_class(&[(250, 300), (700, 750)], 0),
+               EquivalenceClass::new(vec!["trx02"]));
+    assert_eq!(index.equivalence_class(&[(450, 500), (700, 750)], 0),
+               EquivalenceClass::new(vec!["trx01", "trx03"]));
+    assert_eq!(index.equivalence_class(&[(250, 300), (400, 500), (700, 750)], 0),
+               EquivalenceClass::new(vec!["trx01"]));
+    assert!(index.equivalence_class(&[(250, 300), (410, 450)], 0).is_empty());
+}
+
+#[test]
+fn compatibility_index_end_tolerance() {
+    let gene = make_gene();
+    let index = CompatibilityIndex::new(&gene);
+    assert_eq!(index.equivalence_class(&[(395, 450)], 0),
+               EquivalenceClass::new(vec!["trx03"]));
+    assert_eq!(index.equivalence_class(&[(395, 450)], 5),
+               EquivalenceClass::new(vec!["trx01", "trx03"]));
+}
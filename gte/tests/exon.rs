@@ -54,6 +54,16 @@ fn ebuilder_alt1() {
     assert_eq!(exon.features().len(), 0);
 }
 
+#[test]
+fn ebuilder_from_interval() {
+    let exon = EBuilder::from_interval("chrT", Interval::new(10..20).unwrap())
+        .strand(Strand::Forward)
+        .build()
+        .expect("an exon");
+    assert_eq!(exon.seq_name(), "chrT");
+    assert_eq!((exon.start(), exon.end()), (10, 20));
+}
+
 #[test]
 fn ebuilder_interval_invalid() {
     let exonb = EBuilder::new("chrE", 20, 10).build();
@@ -6,7 +6,8 @@ extern crate multimap;
 use linked_hash_map::LinkedHashMap;
 use multimap::MultiMap;
 
-use gte::{Strand, GBuilder};
+use gte::{CanonicalPolicy, ExonBin, Strand, GBuilder, TBuilder, Transcript, TranscriptCoords,
+          ValidationMode};
 use Strand::*;
 
 #[test]
@@ -38,3 +39,415 @@ fn gbuilder_basic() {
     assert_eq!(gx.attributes().len(), 2);
     assert_eq!(gx.transcripts().len(), 2);
 }
+
+#[test]
+fn gbuilder_transcript_coords_exon_attributes() {
+    let mut attribs = MultiMap::new();
+    attribs.insert("exon_id".to_owned(), "EXN02".to_owned());
+    let gx = GBuilder::new("chrT", 100, 1000)
+        .strand(Forward)
+        .add_transcript_coords("trx01", TranscriptCoords::new(100, 1000)
+            .exon(100, 300)
+            .exon_with_attributes(700, 1000, attribs))
+        .build()
+        .expect("a gene");
+    let exons = gx.transcripts()["trx01"].exons();
+    assert!(exons[0].attributes().is_empty());
+    assert_eq!(exons[1].attributes().get("exon_id"), Some(&"EXN02".to_owned()));
+}
+
+fn make_transcript(seq_name: &str, id: Option<&str>, strand: Strand,
+                   start: u64, end: u64) -> Transcript {
+    let btrx = TBuilder::new(seq_name, start, end)
+        .strand(strand)
+        .coords(vec![(start, end)], None);
+    match id {
+        Some(tid) => btrx.id(tid).build(),
+        None => btrx.build(),
+    }.expect("a transcript")
+}
+
+#[test]
+fn gbuilder_add_transcripts() {
+    let bgx = GBuilder::new("chrT", 100, 1000)
+        .strand(Forward)
+        .id("gene-1")
+        .add_transcript(make_transcript("chrT", Some("trx01"), Forward, 100, 500))
+        .add_transcripts(vec![make_transcript("chrT", Some("trx02"), Forward, 300, 1000)])
+        .build();
+    assert!(bgx.is_ok(), "{:?}", bgx);
+    let gx = bgx.unwrap();
+    assert_eq!(gx.transcripts().keys().collect::<Vec<&String>>(), vec!["trx01", "trx02"]);
+    assert_eq!(gx.transcripts()["trx01"].gene_id(), Some("gene-1"));
+}
+
+#[test]
+fn gbuilder_add_transcript_invalid() {
+    let build = |trx: Transcript| {
+        GBuilder::new("chrT", 100, 1000)
+            .strand(Forward)
+            .add_transcript(make_transcript("chrT", Some("trx01"), Forward, 100, 500))
+            .add_transcript(trx)
+            .build()
+    };
+    assert!(build(make_transcript("chrT", None, Forward, 100, 500)).is_err());
+    assert!(build(make_transcript("chrU", Some("trx02"), Forward, 100, 500)).is_err());
+    assert!(build(make_transcript("chrT", Some("trx02"), Reverse, 100, 500)).is_err());
+    assert!(build(make_transcript("chrT", Some("trx02"), Forward, 50, 500)).is_err());
+    assert!(build(make_transcript("chrT", Some("trx01"), Forward, 100, 500)).is_err());
+    assert!(build(make_transcript("chrT", Some("trx02"), Forward, 100, 500)).is_ok());
+}
+
+#[test]
+fn gbuilder_add_transcript_unknown_strand() {
+    let gx = GBuilder::new("chrT", 100, 1000)
+        .strand(Unknown)
+        .add_transcript(make_transcript("chrT", Some("trx01"), Forward, 100, 500))
+        .add_transcript(make_transcript("chrT", Some("trx02"), Reverse, 300, 1000))
+        .build()
+        .expect("a gene");
+    assert_eq!(gx.transcripts().len(), 2);
+
+    let mut gx = GBuilder::new("chrT", 100, 1000).strand(Unknown).build().expect("a gene");
+    assert!(gx.insert_transcript(make_transcript("chrT", Some("trx01"), Reverse, 100, 500))
+        .is_ok());
+}
+
+#[test]
+fn gbuilder_build_lenient() {
+    let (gx, errors) = GBuilder::new("chrT", 100, 1000)
+        .strand(Forward)
+        .id("gene-1")
+        .add_transcript_coords("trx01", TranscriptCoords::new(100, 500).exon(100, 500))
+        .add_transcript_coords("trx02", TranscriptCoords::new(50, 500).exon(50, 500))
+        .add_transcript_coords("trx03", TranscriptCoords::new(100, 500).exon(300, 200))
+        .add_transcript(make_transcript("chrT", Some("trx04"), Reverse, 100, 500))
+        .add_transcript(make_transcript("chrT", Some("trx05"), Forward, 300, 1000))
+        .build_lenient()
+        .expect("a gene");
+    assert_eq!(gx.transcripts().keys().collect::<Vec<&String>>(), vec!["trx01", "trx05"]);
+    assert_eq!(errors.len(), 3);
+
+    let bgx = GBuilder::new("chrT", 1000, 100)
+        .strand(Forward)
+        .add_transcript(make_transcript("chrT", Some("trx01"), Forward, 100, 500))
+        .build_lenient();
+    assert!(bgx.is_err());
+}
+
+#[test]
+fn gbuilder_add_transcript_coords() {
+    let coords = TranscriptCoords::new(100, 1000)
+        .exon(100, 300)
+        .exons(vec![(400, 500), (700, 1000)])
+        .coding(200, 800);
+    assert_eq!(coords.interval(), (100, 1000));
+    assert_eq!(coords.exon_coords().len(), 3);
+    assert_eq!(coords.coding_coord(), Some((200, 800)));
+
+    let bgx = GBuilder::new("chrT", 100, 1000)
+        .strand(Forward)
+        .id("gene-1")
+        .add_transcript_coords("trx01", coords)
+        .add_transcript_coords("trx02", TranscriptCoords::new(100, 500).exon(100, 500))
+        .build();
+    assert!(bgx.is_ok(), "{:?}", bgx);
+    let gx = bgx.unwrap();
+    assert_eq!(gx.transcripts().keys().collect::<Vec<&String>>(), vec!["trx01", "trx02"]);
+    assert_eq!(gx.transcripts()["trx01"].exons().len(), 3);
+    assert_eq!(gx.transcripts()["trx01"].coding_coord(false), Some((200, 800)));
+}
+
+#[test]
+fn gbuilder_transcript_coords_structs() {
+    let mut coords = LinkedHashMap::new();
+    coords.insert("trx01".to_owned(),
+                  TranscriptCoords::new(100, 1000).exons(vec![(100, 300), (700, 1000)]));
+    coords.insert("trx02".to_owned(),
+                  TranscriptCoords::new(100, 500).exon(100, 500).coding(150, 450));
+    let gx = GBuilder::new("chrT", 100, 1000)
+        .strand(Forward)
+        .transcript_coords(coords)
+        .build()
+        .expect("a gene");
+    assert_eq!(gx.transcripts().keys().collect::<Vec<&String>>(), vec!["trx01", "trx02"]);
+    assert_eq!(gx.transcripts()["trx01"].exons().len(), 2);
+    assert_eq!(gx.transcripts()["trx02"].coding_coord(false), Some((150, 450)));
+}
+
+#[test]
+fn transcript_coords_from_tuple() {
+    let coords = TranscriptCoords::from(((100, 500), vec![(100, 200), (300, 500)], None));
+    assert_eq!(coords, TranscriptCoords::new(100, 500).exon(100, 200).exon(300, 500));
+    let trx = TBuilder::from_coords("chrT", coords)
+        .strand(Forward)
+        .build()
+        .expect("a transcript");
+    assert_eq!(trx.exons().len(), 2);
+}
+
+#[test]
+fn gene_coding_summary() {
+    let mut coords = LinkedHashMap::new();
+    coords.insert("trx01".to_owned(),
+                  ((100, 1000), vec![(100, 300), (400, 500), (700, 1000)], Some((200, 800))));
+    coords.insert("trx02".to_owned(),
+                  ((100, 1000), vec![(100, 300), (400, 550), (700, 1000)], Some((150, 900))));
+    coords.insert("trx03".to_owned(),
+                  ((100, 1000), vec![(100, 1000)], None));
+    let gx = GBuilder::new("chrT", 100, 1000)
+        .strand(Forward)
+        .transcript_coords(coords)
+        .transcript_coding_incl_stop(true)
+        .build()
+        .expect("a gene");
+    assert_eq!(gx.coding_span(), Some((150, 900)));
+    assert_eq!(gx.coding_exon_coords(), vec![(150, 300), (400, 550), (700, 900)]);
+    assert_eq!(gx.max_cds_transcript().and_then(|trx| trx.id()), Some("trx02"));
+}
+
+#[test]
+fn gene_coding_summary_noncoding() {
+    let mut coords = LinkedHashMap::new();
+    coords.insert("trx01".to_owned(), ((100, 1000), vec![(100, 1000)], None));
+    let gx = GBuilder::new("chrT", 100, 1000)
+        .strand(Forward)
+        .transcript_coords(coords)
+        .build()
+        .expect("a gene");
+    assert_eq!(gx.coding_span(), None);
+    assert!(gx.coding_exon_coords().is_empty());
+    assert!(gx.max_cds_transcript().is_none());
+}
+
+#[test]
+fn gene_shared_seq_names() {
+    let gx1 = GBuilder::new("chrT", 100, 1000)
+        .strand(Forward)
+        .add_transcript_coords("trx01", TranscriptCoords::new(100, 500).exon(100, 500))
+        .build()
+        .expect("a gene");
+    let mut gx2 = GBuilder::new("chrT".to_owned(), 2000, 3000)
+        .strand(Forward)
+        .build()
+        .expect("a gene");
+    let trx = &gx1.transcripts()["trx01"];
+    assert_eq!(gx1.seq_name().as_ptr(), gx2.seq_name().as_ptr());
+    assert_eq!(gx1.seq_name().as_ptr(), trx.seq_name().as_ptr());
+    assert_eq!(trx.seq_name().as_ptr(), trx.exons()[0].seq_name().as_ptr());
+
+    gx2.set_seq_name("chrU");
+    assert_eq!(gx2.seq_name(), "chrU");
+    assert_eq!(gx1.seq_name(), "chrT");
+}
+
+#[test]
+fn gene_insert_remove_transcripts() {
+    let mut gx = GBuilder::new("chrT", 100, 1000)
+        .strand(Forward)
+        .id("gene-1")
+        .add_transcript(make_transcript("chrT", Some("trx01"), Forward, 100, 500))
+        .add_transcript(make_transcript("chrT", Some("trx02"), Forward, 300, 1000))
+        .build()
+        .expect("a gene");
+
+    let replaced = gx.insert_transcript(make_transcript("chrT", Some("trx03"), Forward, 50, 1200))
+        .expect("an inserted transcript");
+    assert!(replaced.is_none());
+    assert_eq!((gx.start(), gx.end()), (50, 1200));
+
+    let replaced = gx.insert_transcript(make_transcript("chrT", Some("trx03"), Forward, 200, 400))
+        .expect("an inserted transcript");
+    assert_eq!(replaced.map(|trx| trx.start()), Some(50));
+    assert_eq!((gx.start(), gx.end()), (100, 1000));
+
+    assert!(gx.remove_transcript("trx02").is_some());
+    assert!(gx.remove_transcript("trx02").is_none());
+    assert_eq!((gx.start(), gx.end()), (100, 500));
+    assert_eq!(gx.transcripts().keys().collect::<Vec<&String>>(), vec!["trx01", "trx03"]);
+
+    assert!(gx.insert_transcript(make_transcript("chrT", Some("trx04"), Reverse, 100, 500))
+        .is_err());
+    assert!(gx.insert_transcript(make_transcript("chrU", Some("trx04"), Forward, 100, 500))
+        .is_err());
+    assert!(gx.insert_transcript(make_transcript("chrT", None, Forward, 100, 500)).is_err());
+    assert_eq!(gx.transcripts().len(), 2);
+}
+
+#[test]
+fn gene_fit_interval() {
+    let mut gx = GBuilder::new("chrT", 0, 2000)
+        .strand(Unknown)
+        .build()
+        .expect("a gene");
+    gx.fit_interval(true);
+    assert_eq!((gx.start(), gx.end(), gx.strand()), (0, 2000, &Unknown));
+
+    let _ = gx.insert_transcript(make_transcript("chrT", Some("trx01"), Forward, 100, 500))
+        .expect("an inserted transcript");
+    let _ = gx.insert_transcript(make_transcript("chrT", Some("trx02"), Forward, 300, 700))
+        .expect("an inserted transcript");
+    assert_eq!((gx.start(), gx.end()), (0, 2000));
+    gx.fit_interval(false);
+    assert_eq!((gx.start(), gx.end(), gx.strand()), (100, 700, &Unknown));
+    gx.fit_interval(true);
+    assert_eq!(gx.strand(), &Forward);
+}
+
+#[test]
+fn gene_transcripts_mut() {
+    let mut gx = GBuilder::new("chrT", 100, 1000)
+        .strand(Forward)
+        .id("gene-1")
+        .add_transcript(make_transcript("chrT", Some("trx01"), Forward, 100, 500))
+        .build()
+        .expect("a gene");
+
+    let _ = gx.insert_transcript(make_transcript("chrT", Some("trx02"), Forward, 300, 1000))
+        .expect("an inserted transcript");
+    let trx = &gx.transcripts()["trx02"];
+    assert_eq!(trx.gene_id(), Some("gene-1"));
+    assert_eq!(trx.exons()[0].gene_id(), Some("gene-1"));
+
+    gx.set_id(Some("gene-2"));
+    assert!(gx.transcripts().values().all(|trx| trx.gene_id() == Some("gene-2")));
+    assert_eq!(gx.transcripts()["trx01"].id(), Some("trx01"));
+
+    gx.transcripts_mut()["trx01"].attributes_mut().insert("tag".to_owned(), "basic".to_owned());
+    assert_eq!(gx.transcripts()["trx01"].attributes().get("tag"), Some(&"basic".to_owned()));
+    let _ = gx.transcripts_mut().remove("trx02");
+    gx.fit_interval(false);
+    assert_eq!((gx.start(), gx.end()), (100, 500));
+}
+
+#[test]
+fn gene_canonical_transcript() {
+    let tagged = TBuilder::new("chrT", 100, 1000)
+        .strand(Forward)
+        .id("trx03")
+        .coords(vec![(100, 300), (700, 1000)], Some((200, 800)))
+        .attribute("tag", "basic")
+        .attribute("tag", "Ensembl_canonical")
+        .build()
+        .expect("a transcript");
+    let gx = GBuilder::new("chrT", 100, 1000)
+        .strand(Forward)
+        .add_transcript_coords("trx01", TranscriptCoords::new(100, 1000).exon(100, 1000))
+        .add_transcript_coords("trx02", TranscriptCoords::new(100, 1000)
+            .exons(vec![(100, 300), (400, 500), (700, 1000)])
+            .coding(150, 900))
+        .add_transcript(tagged)
+        .build()
+        .expect("a gene");
+    let canonical_id = |policy| gx.canonical_transcript(&policy).and_then(|trx| trx.id());
+    assert_eq!(canonical_id(CanonicalPolicy::LongestCds), Some("trx02"));
+    assert_eq!(canonical_id(CanonicalPolicy::LongestSpliced), Some("trx01"));
+    assert_eq!(canonical_id(CanonicalPolicy::Tagged("Ensembl_canonical".to_owned())),
+               Some("trx03"));
+    assert_eq!(canonical_id(CanonicalPolicy::Tagged("MANE_Select".to_owned())), None);
+
+    let noncoding = GBuilder::new("chrT", 100, 1000)
+        .strand(Forward)
+        .add_transcript_coords("trx01", TranscriptCoords::new(100, 500).exon(100, 500))
+        .add_transcript_coords("trx02", TranscriptCoords::new(100, 1000)
+            .exons(vec![(100, 200), (600, 1000)]))
+        .build()
+        .expect("a gene");
+    assert_eq!(noncoding.canonical_transcript(&CanonicalPolicy::LongestCds)
+                   .and_then(|trx| trx.id()),
+               Some("trx02"));
+}
+
+#[test]
+fn gene_canonical_transcript_annotated() {
+    let make_trx = |tid: &str, tags: &[&str], tsl: &str| {
+        let mut builder = TBuilder::new("chrT", 100, 1000)
+            .strand(Forward)
+            .id(tid)
+            .coords(vec![(100, 300), (700, 1000)], Some((200, 800)))
+            .attribute("transcript_support_level", tsl);
+        for tag in tags {
+            builder = builder.attribute("tag", *tag);
+        }
+        builder.build().expect("a transcript")
+    };
+    let make_gene = |transcripts: Vec<Transcript>| {
+        GBuilder::new("chrT", 100, 1000)
+            .strand(Forward)
+            .add_transcripts(transcripts)
+            .build()
+            .expect("a gene")
+    };
+    let canonical_id = |transcripts| {
+        make_gene(transcripts).canonical_transcript(&CanonicalPolicy::Annotated)
+            .and_then(|trx| trx.id().map(|tid| tid.to_owned()))
+    };
+
+    assert_eq!(canonical_id(vec![make_trx("trx01", &["basic", "Ensembl_canonical"], "1"),
+                                 make_trx("trx02", &["basic", "MANE_Select"], "2")]),
+               Some("trx02".to_owned()));
+    assert_eq!(canonical_id(vec![make_trx("trx01", &["basic"], "1"),
+                                 make_trx("trx02", &["Ensembl_canonical"], "NA")]),
+               Some("trx02".to_owned()));
+    assert_eq!(canonical_id(vec![make_trx("trx01", &[], "1"),
+                                 make_trx("trx02", &["basic"], "NA"),
+                                 make_trx("trx03", &["basic", "CCDS"], "2"),
+                                 make_trx("trx04", &["basic"], "2")]),
+               Some("trx03".to_owned()));
+    assert_eq!(canonical_id(vec![]), None);
+}
+
+#[test]
+fn gene_flattened_exons() {
+    let gx = GBuilder::new("chrT", 100, 1000)
+        .strand(Forward)
+        .add_transcript_coords("trx01", TranscriptCoords::new(100, 1000)
+            .exons(vec![(100, 300), (400, 500), (700, 1000)]))
+        .add_transcript_coords("trx02", TranscriptCoords::new(100, 1000)
+            .exons(vec![(100, 300), (400, 550), (800, 1000)]))
+        .build()
+        .expect("a gene");
+    let bins = gx.flattened_exons();
+    let summary = |bin: &ExonBin| (bin.start(), bin.end(), bin.transcript_ids().join(","));
+    assert_eq!(bins.iter().map(summary).collect::<Vec<_>>(),
+               vec![(100, 300, "trx01,trx02".to_owned()),
+                    (400, 500, "trx01,trx02".to_owned()),
+                    (500, 550, "trx02".to_owned()),
+                    (700, 800, "trx01".to_owned()),
+                    (800, 1000, "trx01,trx02".to_owned())]);
+    assert_eq!(bins.iter().map(|bin| bin.span()).sum::<u64>(), 650);
+
+    let empty = GBuilder::new("chrT", 100, 1000).strand(Forward).build().expect("a gene");
+    assert!(empty.flattened_exons().is_empty());
+}
+
+#[test]
+fn gbuilder_validation_mode() {
+    let trx = TBuilder::new("chrT", 100, 1000)
+        .strand(Forward)
+        .id("trx01")
+        .coords(vec![(100, 300), (700, 1000)], None)
+        .build()
+        .expect("a transcript");
+    let build = |mode| {
+        GBuilder::new("chrT", 200, 800)
+            .strand(Forward)
+            .validation(mode)
+            .add_transcript_coords("trx02", TranscriptCoords::new(60, 700)
+                                   .exons(vec![(50, 120), (600, 700)]))
+            .add_transcript(trx.clone())
+            .build()
+    };
+    assert!(build(ValidationMode::Strict).is_err());
+
+    let gx = build(ValidationMode::Fix).expect("a gene");
+    assert_eq!((gx.start(), gx.end()), (50, 1000));
+    assert_eq!(gx.transcripts().keys().cloned().collect::<Vec<String>>(),
+               vec!["trx02".to_owned(), "trx01".to_owned()]);
+    let trx02 = gx.transcripts().get("trx02").expect("a transcript");
+    assert_eq!((trx02.start(), trx02.end()), (50, 700));
+
+    let gx = build(ValidationMode::Ignore).expect("a gene");
+    assert_eq!((gx.start(), gx.end()), (200, 800));
+    assert_eq!(gx.transcripts().len(), 2);
+}
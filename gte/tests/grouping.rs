@@ -0,0 +1,62 @@
+extern crate gte;
+
+use gte::{GeneAssembler, Strand, TBuilder, Transcript};
+use Strand::*;
+
+
+fn make_transcript(id: Option<&str>, strand: Strand, exons: Vec<(u64, u64)>) -> Transcript {
+    let (start, end) = (exons[0].0, exons[exons.len() - 1].1);
+    let builder = TBuilder::new("chrT", start, end)
+        .strand(strand)
+        .gene_id("unreliable")
+        .coords(exons, None);
+    match id {
+        Some(id) => builder.id(id),
+        None => builder,
+    }.build().expect("a transcript")
+}
+
+#[test]
+fn assemble_genes_by_overlap() {
+    let transcripts = vec![
+        make_transcript(Some("t1"), Forward, vec![(100, 200), (300, 400)]),
+        make_transcript(Some("t2"), Forward, vec![(1000, 1100)]),
+        make_transcript(Some("t3"), Forward, vec![(200, 300)]),
+        make_transcript(Some("t4"), Forward, vec![(350, 500), (900, 1050)]),
+        make_transcript(Some("t5"), Reverse, vec![(150, 250)]),
+    ];
+    let mut assembler = GeneAssembler::new();
+    let _ = assembler.id_prefix("STRG.");
+    let genes = assembler.assemble(transcripts).expect("genes");
+
+    let summary = genes.iter()
+        .map(|gene| {
+            let tids = gene.transcripts().keys().cloned().collect::<Vec<String>>();
+            (gene.id().unwrap().to_owned(), gene.start(), gene.end(), *gene.strand(), tids)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(summary, vec![
+        ("STRG.000001".to_owned(), 100, 1100, Forward,
+         vec!["t1".to_owned(), "t2".to_owned(), "t4".to_owned()]),
+        ("STRG.000002".to_owned(), 150, 250, Reverse, vec!["t5".to_owned()]),
+        ("STRG.000003".to_owned(), 200, 300, Forward, vec!["t3".to_owned()]),
+    ]);
+    for gene in genes.iter() {
+        for trx in gene.transcripts().values() {
+            assert_eq!(trx.gene_id(), gene.id());
+        }
+    }
+}
+
+#[test]
+fn assemble_genes_errors() {
+    let assembler = GeneAssembler::default();
+    assert!(assembler.assemble(Vec::new()).expect("no genes").is_empty());
+
+    let unnamed = vec![make_transcript(None, Forward, vec![(100, 200)])];
+    assert!(assembler.assemble(unnamed).is_err());
+
+    let duplicates = vec![make_transcript(Some("t1"), Forward, vec![(100, 200)]),
+                          make_transcript(Some("t1"), Forward, vec![(150, 250)])];
+    assert!(assembler.assemble(duplicates).is_err());
+}
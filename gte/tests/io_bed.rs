@@ -0,0 +1,126 @@
+extern crate gte;
+
+use std::env;
+use std::fs;
+
+use gte::{AnnotationSet, Assembly, BedWriter, GBuilder, Strand, TBuilder, TrackOptions,
+          Transcript, ValidationMode};
+
+
+fn make_transcript() -> Transcript {
+    TBuilder::new("chr1", 100, 1000)
+        .id("trx01")
+        .gene_id("gene01")
+        .strand(Strand::Reverse)
+        .attribute("gene_name", "ABC1")
+        .attribute("source", "havana")
+        .coords(vec![(100, 300), (400, 500), (700, 1000)], Some((200, 800)))
+        .coding_incl_stop(true)
+        .build()
+        .expect("a transcript")
+}
+
+#[test]
+fn bed_writer_transcript() {
+    let mut writer = BedWriter::from_memory();
+    writer.write_transcript(&make_transcript()).expect("a written transcript");
+    assert_eq!(writer.as_string(),
+               "chr1\t100\t1000\ttrx01\t0\t-\t200\t800\t0\t3\t200,100,300,\t0,300,600,\n");
+}
+
+#[test]
+fn transcript_to_bed12_blocks() {
+    assert_eq!(make_transcript().to_bed12_blocks().expect("BED12 blocks"),
+               (200, 800, vec![200, 100, 300], vec![0, 300, 600]));
+    let noncoding = TBuilder::new("chr1", 100, 500)
+        .strand(Strand::Forward)
+        .coords(vec![(100, 500)], None)
+        .build()
+        .expect("a transcript");
+    assert_eq!(noncoding.to_bed12_blocks().expect("BED12 blocks"),
+               (500, 500, vec![400], vec![0]));
+    let unchecked = TBuilder::new("chr1", 150, 500)
+        .id("trx03")
+        .strand(Strand::Forward)
+        .coords(vec![(100, 500)], None)
+        .validation(ValidationMode::Ignore)
+        .build()
+        .expect("a transcript");
+    let err = unchecked.to_bed12_blocks().expect_err("an error");
+    assert_eq!(err.to_string(),
+               "exon starts before its transcript, exon start: 100, transcript ID: trx03");
+}
+
+#[test]
+fn bed_writer_detail() {
+    let mut writer = BedWriter::from_memory();
+    writer.detail_attributes(vec!["gene_name", "missing", "source"]);
+    writer.write_transcript(&make_transcript()).expect("a written transcript");
+    assert_eq!(writer.as_string(),
+               "chr1\t100\t1000\ttrx01\t0\t-\t200\t800\t0\t3\t200,100,300,\t0,300,600,\
+                \tgene01\tgene_name=ABC1; source=havana\n");
+}
+
+#[test]
+fn bed_writer_exon_features() {
+    let transcript = TBuilder::new("chr2", 100, 300)
+        .id("trx02")
+        .strand(Strand::Forward)
+        .coords(vec![(100, 300)], Some((150, 210)))
+        .build()
+        .expect("a transcript");
+    let mut writer = BedWriter::from_memory();
+    writer.write_exon_features(&transcript).expect("written features");
+    assert_eq!(writer.as_string(),
+               "chr2\t100\t150\ttrx02:UTR5\t0\t+\n\
+                chr2\t150\t153\ttrx02:start_codon\t0\t+\n\
+                chr2\t150\t210\ttrx02:CDS\t0\t+\n\
+                chr2\t210\t213\ttrx02:stop_codon\t0\t+\n\
+                chr2\t213\t300\ttrx02:UTR3\t0\t+\n");
+}
+
+#[test]
+fn annotation_export_tracks() {
+    let noncoding = TBuilder::new("chr1", 100, 1000)
+        .id("trx02")
+        .strand(Strand::Reverse)
+        .coords(vec![(100, 300), (700, 1000)], None)
+        .build()
+        .expect("a transcript");
+    let gene = GBuilder::new("chr1", 100, 1000)
+        .id("gene01")
+        .strand(Strand::Reverse)
+        .add_transcript(make_transcript())
+        .add_transcript(noncoding)
+        .build()
+        .expect("a gene");
+    let annotation = AnnotationSet::from_genes(vec![gene]);
+    let dir = env::temp_dir().join(format!("gte-export-tracks-{}", std::process::id()));
+    annotation.export_tracks(&dir, &TrackOptions::new().tss_flank(2).unique(true))
+        .expect("exported tracks");
+
+    let read = |name: &str| fs::read_to_string(dir.join(name)).expect("a track file");
+    assert_eq!(read("genes.bed"), "chr1\t100\t1000\tgene01\t0\t-\n");
+    assert_eq!(read("exons.bed"),
+               "chr1\t100\t300\ttrx01\t0\t-\n\
+                chr1\t400\t500\ttrx01\t0\t-\n\
+                chr1\t700\t1000\ttrx01\t0\t-\n");
+    assert_eq!(read("cds.bed"),
+               "chr1\t203\t300\ttrx01\t0\t-\n\
+                chr1\t400\t500\ttrx01\t0\t-\n\
+                chr1\t700\t800\ttrx01\t0\t-\n");
+    assert_eq!(read("utr5.bed"), "chr1\t800\t1000\ttrx01\t0\t-\n");
+    assert_eq!(read("utr3.bed"), "chr1\t100\t200\ttrx01\t0\t-\n");
+    assert_eq!(read("introns.bed"),
+               "chr1\t300\t400\ttrx01\t0\t-\n\
+                chr1\t500\t700\ttrx01\t0\t-\n\
+                chr1\t300\t700\ttrx02\t0\t-\n");
+    assert_eq!(read("tss.bed"), "chr1\t997\t1002\ttrx01\t0\t-\n");
+
+    let mut assembly = Assembly::new();
+    assembly.add_seq("chr1", 1000);
+    annotation.export_tracks(&dir, &TrackOptions::new().tss_flank(2).assembly(assembly))
+        .expect("exported tracks");
+    assert_eq!(read("tss.bed"), "chr1\t997\t1000\ttrx01\t0\t-\n");
+    fs::remove_dir_all(&dir).expect("a removed directory");
+}
@@ -1,7 +1,7 @@
 extern crate bio;
 extern crate gte;
 
-use gte::{GffType, GffReader,
+use gte::{Biotype, CancelToken, Error, GffType, GffReader,
           ExonFeatureKind as EFK, Strand};
 use Strand::*;
 
@@ -49,3 +49,20 @@ fn gtf_reader_multiple_transcripts() {
 
     assert!(transcripts.next().is_none());
 }
+
+#[test]
+fn gtf_reader_biotypes() {
+    let mut reader = GffReader::from_reader(SINGLE_GENE_GTF.as_bytes(), GffType::GTF2);
+    let biotypes = reader.transcripts().expect("transcripts")
+        .map(|res| res.expect("a transcript").biotype().cloned())
+        .collect::<Vec<Option<Biotype>>>();
+    assert_eq!(biotypes, vec![Some(Biotype::ProteinCoding), Some(Biotype::MiRNA)]);
+}
+
+#[test]
+fn gtf_reader_cancel_token() {
+    let token = CancelToken::new();
+    token.cancel();
+    let mut reader = GffReader::from_reader(SINGLE_GENE_GTF.as_bytes(), GffType::GTF2);
+    assert!(matches!(reader.cancel_token(token).transcripts(), Err(Error::Cancelled)));
+}
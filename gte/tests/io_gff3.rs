@@ -0,0 +1,58 @@
+extern crate gte;
+
+use gte::{GBuilder, Gene, Gff3Writer, Strand, TranscriptCoords};
+use Strand::*;
+
+
+fn make_gene(seq_name: &str, id: Option<&str>, start: u64) -> Gene {
+    let bgx = GBuilder::new(seq_name, start, start + 900)
+        .strand(Forward)
+        .attribute("Name", "a;b")
+        .add_transcript_coords("trx01", TranscriptCoords::new(start, start + 900)
+            .exons(vec![(start, start + 200), (start + 600, start + 900)])
+            .coding(start + 100, start + 700));
+    match id {
+        Some(gid) => bgx.id(gid).build(),
+        None => bgx.build(),
+    }.expect("a gene")
+}
+
+#[test]
+fn gff3_writer_gene() {
+    let mut writer = Gff3Writer::from_memory();
+    writer.write_gene(&make_gene("chr1", Some("gene01"), 100)).expect("a written gene");
+    let lines = writer.as_string().lines().map(|l| l.to_owned()).collect::<Vec<String>>();
+    assert_eq!(lines[0], "##gff-version 3");
+    assert_eq!(lines[1], "chr1\t.\tgene\t101\t1000\t.\t+\t.\tID=gene01;Name=a%3Bb");
+    assert_eq!(lines[2], "chr1\t.\tmRNA\t101\t1000\t.\t+\t.\tID=trx01;Parent=gene01");
+    assert_eq!(lines[3], "chr1\t.\texon\t101\t300\t.\t+\t.\tID=trx01.exon1;Parent=trx01");
+    assert_eq!(lines.last().map(|l| l.as_str()), Some("###"));
+
+    let cds_lines = lines.iter()
+        .filter(|l| l.split('\t').nth(2) == Some("CDS"))
+        .collect::<Vec<&String>>();
+    assert_eq!(cds_lines.len(), 2);
+    for line in cds_lines {
+        assert!(line.ends_with("ID=trx01.cds;Parent=trx01"));
+    }
+}
+
+#[test]
+fn gff3_writer_genes_sorted() {
+    let genes = vec![make_gene("chr2", Some("gene03"), 100),
+                     make_gene("chr1", None, 5000),
+                     make_gene("chr1", Some("gene01"), 100)];
+    let mut writer = Gff3Writer::from_memory();
+    writer.write_genes(&genes).expect("written genes");
+    let lines = writer.as_string().lines().map(|l| l.to_owned()).collect::<Vec<String>>();
+    assert_eq!(lines[0], "##gff-version 3");
+    assert_eq!(lines[1], "##sequence-region chr1 101 5900");
+    assert_eq!(lines[2], "##sequence-region chr2 101 1000");
+
+    let gene_ids = lines.iter()
+        .filter(|l| l.split('\t').nth(2) == Some("gene"))
+        .map(|l| l.split('\t').nth(8).unwrap().split(';').next().unwrap().to_owned())
+        .collect::<Vec<String>>();
+    assert_eq!(gene_ids, vec!["ID=gene01", "ID=gene2", "ID=gene03"]);
+    assert_eq!(lines.iter().filter(|l| l.as_str() == "###").count(), 3);
+}
@@ -0,0 +1,577 @@
+#[macro_use]
+extern crate matches;
+extern crate gte;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gte::{Biotype, CancelToken, Error, ErrorPolicy, GtfEscaping, GtfQuoting, GtfReader,
+          GtfRecord, GtfWriter, GBuilder, Gene, ModelError, TBuilder, ExonFeatureKind as EFK,
+          Strand};
+use Strand::*;
+
+
+static SINGLE_GENE_GTF: &'static str = include_str!("data/single_gene.gtf");
+
+static NO_PARENTS_GTF: &'static str = "\
+# comment line
+chr1\tsrc\texon\t101\t200\t.\t-\t.\tgene_id \"g1\"; transcript_id \"t1\"; exon_number 2; name \"a\";
+chr1\tsrc\texon\t301\t400\t.\t-\t.\tgene_id \"g1\"; transcript_id \"t1\"; exon_number 1; name \"a\";
+
+chr1\tsrc\tCDS\t151\t200\t.\t-\t2\tgene_id \"g1\"; transcript_id \"t1\";
+chr1\tsrc\tCDS\t301\t350\t.\t-\t0\tgene_id \"g1\"; transcript_id \"t1\";
+chr1\tsrc\tstart_codon\t348\t350\t.\t-\t0\tgene_id \"g1\"; transcript_id \"t1\";
+chr1\tsrc\tstop_codon\t148\t150\t.\t-\t0\tgene_id \"g1\"; transcript_id \"t1\";
+";
+
+
+#[test]
+fn gtf_record_attributes() {
+    let line = "chr1\tsrc\texon\t1\t10\t0.5\t+\t.\t\
+                gene_id \"g1\"; tag \"a\"; tag \"b\"; level 2; note \"x; \\\"y\\\"\";";
+    let rec = GtfRecord::try_from_line(line, 1).expect("a record");
+    assert_eq!(rec.seq_name(), "chr1");
+    assert_eq!((rec.start(), rec.end()), (0, 10));
+    assert_eq!(rec.score(), Some(0.5));
+    assert_eq!(rec.strand(), &Forward);
+    assert_eq!(rec.frame(), None);
+    assert_eq!(rec.attribute("gene_id"), Some("g1"));
+    assert_eq!(rec.attributes().get_vec("tag"), Some(&vec!["a".to_owned(), "b".to_owned()]));
+    assert_eq!(rec.attribute("level"), Some("2"));
+    assert_eq!(rec.attribute("note"), Some("x; \"y\""));
+}
+
+#[test]
+fn gtf_record_invalid() {
+    assert!(GtfRecord::try_from_line("chr1\tsrc\texon\t1\t10", 1).is_err());
+    assert!(GtfRecord::try_from_line("chr1\tsrc\texon\t0\t10\t.\t+\t.\tgene_id \"g1\";", 1)
+        .is_err());
+    assert!(GtfRecord::try_from_line("chr1\tsrc\texon\t1\t10\t.\t+\t.\tgene_id \"g1;", 1)
+        .is_err());
+}
+
+#[test]
+fn gtf_reader_records_stream() {
+    let mut reader = GtfReader::from_reader(SINGLE_GENE_GTF.as_bytes());
+    let records = reader.records_stream()
+        .collect::<Result<Vec<GtfRecord>, _>>()
+        .expect("records");
+    assert_eq!(records.len(), 12);
+    assert_eq!(records[0].feature(), "gene");
+    assert_eq!(records[0].source(), "HAVANA");
+    assert_eq!(records[0].attribute("gene_name"), Some("HOXD1"));
+}
+
+#[test]
+fn gtf_reader_transcripts_stream() {
+    let mut reader = GtfReader::from_reader(SINGLE_GENE_GTF.as_bytes());
+    let mut transcripts = reader.transcripts_stream();
+
+    let trx1 = transcripts.next().expect("a transcript result").expect("a transcript");
+    assert_eq!(trx1.id(), Some("ENST00000331462.5"));
+    assert_eq!(trx1.gene_id(), Some("ENSG00000128645.13"));
+    assert_eq!(trx1.seq_name(), "chr2");
+    assert_eq!((trx1.start(), trx1.end()), (176188578, 176190907));
+    assert_eq!(trx1.strand(), &Forward);
+    assert_eq!(trx1.exons().len(), 2);
+    assert_eq!(trx1.coding_coord(true), Some((176188801, 176190142)));
+    assert_eq!(trx1.attributes().get("transcript_name"), Some(&"HOXD1-001".to_owned()));
+    assert_eq!(trx1.attributes().get_vec("tag").map(|v| v.len()), Some(3));
+    assert_eq!(trx1.attributes().get("source"), Some(&"HAVANA".to_owned()));
+    assert!(trx1.attributes().get("transcript_id").is_none());
+    assert!(trx1.attributes().get("gene_id").is_none());
+
+    let trx2 = transcripts.next().expect("a transcript result").expect("a transcript");
+    assert_eq!(trx2.id(), Some("ENST00000610524.1"));
+    assert_eq!((trx2.start(), trx2.end()), (176188842, 176188901));
+    assert_eq!(trx2.exons().len(), 1);
+    assert_eq!(trx2.coding_coord(true), None);
+
+    assert!(transcripts.next().is_none());
+}
+
+#[test]
+fn gtf_reader_map_record() {
+    let mut reader = GtfReader::from_reader(NO_PARENTS_GTF.as_bytes());
+    let _ = reader.map_record(|rec| {
+        let (start, end) = (rec.start() + 1000, rec.end() + 1000);
+        rec.set_coords(start, end);
+        rec.set_seq_name("1");
+        rec.attributes_mut().insert("source_file".to_owned(), "a.gtf".to_owned());
+    });
+    let mut transcripts = reader.transcripts_stream();
+
+    let trx = transcripts.next().expect("a transcript result").expect("a transcript");
+    assert_eq!(trx.seq_name(), "1");
+    assert_eq!((trx.start(), trx.end()), (1100, 1400));
+    assert_eq!(trx.coding_coord(true), Some((1147, 1350)));
+    assert_eq!(trx.attributes().get("source_file"), Some(&"a.gtf".to_owned()));
+    assert!(transcripts.next().is_none());
+}
+
+#[test]
+fn gtf_reader_transcripts_stream_no_parents() {
+    let mut reader = GtfReader::from_reader(NO_PARENTS_GTF.as_bytes());
+    let mut transcripts = reader.transcripts_stream();
+
+    let trx = transcripts.next().expect("a transcript result").expect("a transcript");
+    assert_eq!((trx.start(), trx.end()), (100, 400));
+    assert_eq!(trx.strand(), &Reverse);
+    assert_eq!(trx.coding_coord(true), Some((147, 350)));
+    assert_eq!(trx.attributes().get("name"), Some(&"a".to_owned()));
+    assert!(trx.attributes().get("exon_number").is_none());
+    let exn1_kinds = trx.exons()[0].features().iter()
+        .map(|fx| fx.kind().clone())
+        .collect::<Vec<EFK>>();
+    assert_eq!(exn1_kinds[0], EFK::UTR3);
+
+    assert!(transcripts.next().is_none());
+}
+
+#[test]
+fn gtf_reader_genes_stream() {
+    let mut reader = GtfReader::from_reader(SINGLE_GENE_GTF.as_bytes());
+    let mut genes = reader.genes_stream();
+
+    let gene = genes.next().expect("a gene result").expect("a gene");
+    assert_eq!(gene.id(), Some("ENSG00000128645.13"));
+    assert_eq!((gene.start(), gene.end()), (176188578, 176190907));
+    assert_eq!(gene.strand(), &Forward);
+    assert_eq!(gene.transcripts().len(), 2);
+    assert_eq!(gene.attributes().get("gene_name"), Some(&"HOXD1".to_owned()));
+
+    assert!(genes.next().is_none());
+}
+
+#[test]
+fn gtf_reader_genes_stream_no_parents() {
+    let mut reader = GtfReader::from_reader(NO_PARENTS_GTF.as_bytes());
+    let mut genes = reader.genes_stream();
+
+    let gene = genes.next().expect("a gene result").expect("a gene");
+    assert_eq!(gene.id(), Some("g1"));
+    assert_eq!((gene.start(), gene.end()), (100, 400));
+    assert_eq!(gene.attributes().get("name"), Some(&"a".to_owned()));
+
+    assert!(genes.next().is_none());
+}
+
+#[test]
+fn gtf_reader_biotypes() {
+    let mut reader = GtfReader::from_reader(SINGLE_GENE_GTF.as_bytes());
+    let gene = reader.genes_stream().next().expect("a gene result").expect("a gene");
+    assert_eq!(gene.biotype(), Some(&Biotype::ProteinCoding));
+    let biotypes = gene.transcripts().values()
+        .map(|trx| trx.biotype().cloned())
+        .collect::<Vec<Option<Biotype>>>();
+    assert_eq!(biotypes, vec![Some(Biotype::ProteinCoding), Some(Biotype::MiRNA)]);
+    assert_eq!(gene.attributes().get("gene_type"), Some(&"protein_coding".to_owned()));
+
+    let data = "chr1\tsrc\texon\t1\t10\t.\t+\t.\tgene_id \"g1\"; transcript_id \"t1\"; \
+                gene_biotype \"TEC\"; transcript_biotype \"TEC\";\n\
+                chr1\tsrc\texon\t21\t30\t.\t+\t.\tgene_id \"g2\"; transcript_id \"t2\";\n";
+    let mut reader = GtfReader::from_reader(data.as_bytes());
+    let genes = reader.genes_stream()
+        .collect::<Result<Vec<Gene>, _>>()
+        .expect("genes");
+    assert_eq!(genes[0].biotype(), Some(&Biotype::Other("TEC".to_owned())));
+    assert_eq!(genes[0].transcripts()["t1"].biotype().map(|b| b.as_str()), Some("TEC"));
+    assert_eq!(genes[1].biotype(), None);
+    assert_eq!(genes[1].transcripts()["t2"].biotype(), None);
+}
+
+#[test]
+fn gtf_reader_missing_transcript_id() {
+    let data = "chr1\tsrc\texon\t1\t10\t.\t+\t.\tgene_id \"g1\";\n";
+    let mut reader = GtfReader::from_reader(data.as_bytes());
+    let mut transcripts = reader.transcripts_stream();
+    assert!(transcripts.next().expect("a transcript result").is_err());
+    assert!(transcripts.next().is_none());
+}
+
+#[test]
+fn gtf_writer_transcript() {
+    let trx = TBuilder::new("chr1", 100, 1000)
+        .id("t1")
+        .gene_id("g1")
+        .strand(Reverse)
+        .attribute("source", "src")
+        .attribute("name", "a \"b\"")
+        .coords(vec![(100, 300), (700, 1000)], Some((200, 800)))
+        .coding_incl_stop(true)
+        .build()
+        .expect("a transcript");
+    let mut writer = GtfWriter::from_memory();
+    writer.write_transcript(&trx).expect("a written transcript");
+    let lines = writer.as_string().lines().map(|l| l.to_owned()).collect::<Vec<String>>();
+    assert_eq!(lines[0],
+               "chr1\tsrc\ttranscript\t101\t1000\t.\t-\t.\t\
+                gene_id \"g1\"; transcript_id \"t1\"; name \"a \\\"b\\\"\";");
+    assert_eq!(lines[1],
+               "chr1\tsrc\texon\t101\t300\t.\t-\t.\t\
+                gene_id \"g1\"; transcript_id \"t1\"; exon_number \"2\"; name \"a \\\"b\\\"\";");
+    let mut features = lines.iter()
+        .map(|l| l.split('\t').nth(2).unwrap().to_owned())
+        .collect::<Vec<String>>();
+    features.sort();
+    assert_eq!(features, vec!["3UTR", "5UTR", "CDS", "CDS", "exon", "exon",
+                              "start_codon", "stop_codon", "transcript"]);
+}
+
+#[test]
+fn gtf_writer_roundtrip() {
+    let mut reader = GtfReader::from_reader(SINGLE_GENE_GTF.as_bytes());
+    let genes = reader.genes_stream()
+        .collect::<Result<Vec<Gene>, _>>()
+        .expect("genes");
+    let mut writer = GtfWriter::from_memory();
+    for gene in genes.iter() {
+        writer.write_gene(gene).expect("a written gene");
+    }
+
+    let mut reader2 = GtfReader::from_reader(writer.as_string().as_bytes());
+    let genes2 = reader2.genes_stream()
+        .collect::<Result<Vec<Gene>, _>>()
+        .expect("genes");
+    assert_eq!(genes2.len(), 1);
+    assert_eq!(genes2[0].id(), genes[0].id());
+    assert_eq!(genes2[0].interval(), genes[0].interval());
+    assert_eq!(genes2[0].attributes().get("gene_name"), Some(&"HOXD1".to_owned()));
+    for (trx2, trx) in genes2[0].transcripts().values().zip(genes[0].transcripts().values()) {
+        assert_eq!(trx2.id(), trx.id());
+        assert_eq!(trx2.coding_coord(true), trx.coding_coord(true));
+        assert_eq!(trx2.exons().len(), trx.exons().len());
+        assert_eq!(trx2.attributes().get_vec("tag"), trx.attributes().get_vec("tag"));
+    }
+}
+
+#[test]
+fn gtf_writer_roundtrip_attribute_order() {
+    let data = "chr1\tsrc\ttranscript\t1\t100\t.\t+\t.\tgene_id \"g1\"; transcript_id \"t1\"; \
+                gene_type \"lncRNA\"; level 2; tag \"basic\"; gene_name \"ABC\"; tag \"TAGENE\";\n\
+                chr1\tsrc\texon\t1\t100\t.\t+\t.\tgene_id \"g1\"; transcript_id \"t1\"; \
+                exon_number 1;\n";
+    let mut reader = GtfReader::from_reader(data.as_bytes());
+    let trx = reader.transcripts_stream().next()
+        .expect("a transcript result")
+        .expect("a transcript");
+    let keys = trx.attributes().keys().map(|k| k.as_str()).collect::<Vec<&str>>();
+    assert_eq!(keys, vec!["gene_type", "level", "tag", "gene_name", "source"]);
+    assert_eq!(trx.exons()[0].attributes().get("exon_number"), Some(&"1".to_owned()));
+    assert_eq!(trx.exons()[0].attributes().len(), 1);
+
+    let mut writer = GtfWriter::from_memory();
+    writer.write_transcript(&trx).expect("a written transcript");
+    let lines = writer.as_string().lines().map(|l| l.to_owned()).collect::<Vec<String>>();
+    assert_eq!(lines[0],
+               "chr1\tsrc\ttranscript\t1\t100\t.\t+\t.\tgene_id \"g1\"; transcript_id \"t1\"; \
+                gene_type \"lncRNA\"; level \"2\"; tag \"basic\"; tag \"TAGENE\"; \
+                gene_name \"ABC\";");
+    assert_eq!(lines[1],
+               "chr1\tsrc\texon\t1\t100\t.\t+\t.\tgene_id \"g1\"; transcript_id \"t1\"; \
+                exon_number \"1\"; gene_type \"lncRNA\"; level \"2\"; tag \"basic\"; \
+                tag \"TAGENE\"; gene_name \"ABC\";");
+}
+
+#[test]
+fn gtf_writer_attribute_quoting() {
+    let trx = TBuilder::new("chr1", 100, 1000)
+        .id("t1")
+        .gene_id("g1")
+        .strand(Forward)
+        .attribute("level", "2")
+        .attribute("note", "a \"b\"; c")
+        .coords(vec![(100, 1000)], None)
+        .build()
+        .expect("a transcript");
+    let attribute_column = |quoting: GtfQuoting, escaping: GtfEscaping| {
+        let mut writer = GtfWriter::from_memory();
+        let _ = writer.attribute_quoting(quoting).attribute_escaping(escaping);
+        writer.write_transcript(&trx).expect("a written transcript");
+        writer.as_string().lines().nth(1).unwrap().split('\t').nth(8).unwrap().to_owned()
+    };
+
+    assert_eq!(attribute_column(GtfQuoting::Always, GtfEscaping::Backslash),
+               "gene_id \"g1\"; transcript_id \"t1\"; exon_number \"1\"; level \"2\"; \
+                note \"a \\\"b\\\"; c\";");
+    assert_eq!(attribute_column(GtfQuoting::NonNumeric, GtfEscaping::Backslash),
+               "gene_id \"g1\"; transcript_id \"t1\"; exon_number 1; level 2; \
+                note \"a \\\"b\\\"; c\";");
+    assert_eq!(attribute_column(GtfQuoting::Never, GtfEscaping::Percent),
+               "gene_id g1; transcript_id t1; exon_number 1; level 2; note \"a %22b%22%3B c\";");
+    assert_eq!(attribute_column(GtfQuoting::Always, GtfEscaping::Verbatim),
+               "gene_id \"g1\"; transcript_id \"t1\"; exon_number \"1\"; level \"2\"; \
+                note \"a \"b\"; c\";");
+
+    let mut writer = GtfWriter::from_memory();
+    let _ = writer.attribute_quoting(GtfQuoting::Never);
+    writer.write_transcript(&trx).expect("a written transcript");
+    let mut reader = GtfReader::from_reader(writer.as_string().as_bytes());
+    let trx2 = reader.transcripts_stream().next()
+        .expect("a transcript result")
+        .expect("a transcript");
+    assert_eq!(trx2.attributes().get("note"), Some(&"a \"b\"; c".to_owned()));
+    assert_eq!(trx2.attributes().get("level"), Some(&"2".to_owned()));
+}
+
+#[test]
+fn gtf_writer_attribute_escaping_round_trip() {
+    let trx = TBuilder::new("chr1", 100, 1000)
+        .id("t1")
+        .gene_id("g1")
+        .strand(Forward)
+        .attribute("path", "a\\b")
+        .attribute("quote", "x\"y")
+        .coords(vec![(100, 1000)], None)
+        .build()
+        .expect("a transcript");
+    for &quoting in [GtfQuoting::Always, GtfQuoting::NonNumeric, GtfQuoting::Never].iter() {
+        let mut writer = GtfWriter::from_memory();
+        let _ = writer.attribute_quoting(quoting).attribute_escaping(GtfEscaping::Backslash);
+        writer.write_transcript(&trx).expect("a written transcript");
+        let mut reader = GtfReader::from_reader(writer.as_string().as_bytes());
+        let trx2 = reader.transcripts_stream().next()
+            .expect("a transcript result")
+            .expect("a transcript");
+        assert_eq!(trx2.attributes().get("path"), Some(&"a\\b".to_owned()), "{:?}", quoting);
+        assert_eq!(trx2.attributes().get("quote"), Some(&"x\"y".to_owned()), "{:?}", quoting);
+    }
+}
+
+#[test]
+fn gtf_writer_missing_ids() {
+    let trx = TBuilder::new("chr1", 100, 1000)
+        .coords(vec![(100, 1000)], None)
+        .build()
+        .expect("a transcript");
+    let mut writer = GtfWriter::from_memory();
+    assert!(writer.write_transcript(&trx).is_err());
+}
+
+#[test]
+fn gtf_writer_comment_attributes() {
+    let trx = TBuilder::new("chr1", 100, 1000)
+        .id("t1")
+        .gene_id("g1")
+        .strand(Forward)
+        .attribute("transcript_name", "ABC1-001")
+        .coords(vec![(100, 1000)], None)
+        .build()
+        .expect("a transcript");
+    let mut writer = GtfWriter::from_memory();
+    writer.comment_attributes(vec!["transcript_name"]);
+    writer.write_transcript(&trx).expect("a written transcript");
+    let lines = writer.as_string().lines().map(|l| l.to_owned()).collect::<Vec<String>>();
+    assert!(lines[0].ends_with("; # transcript_name=ABC1-001"));
+    assert!(!lines[1].contains('#'));
+
+    let mut reader = GtfReader::from_reader(writer.as_string().as_bytes());
+    let trx2 = reader.transcripts_stream().next()
+        .expect("a transcript result")
+        .expect("a transcript");
+    assert_eq!(trx2.attributes().get("transcript_name"), Some(&"ABC1-001".to_owned()));
+}
+
+#[test]
+fn gtf_writer_group_by_gene() {
+    let make_trx = |tid: &str, gid: &str, seq_name: &str, start: u64, end: u64| {
+        TBuilder::new(seq_name, start, end)
+            .id(tid)
+            .gene_id(gid)
+            .strand(Forward)
+            .coords(vec![(start, end)], None)
+            .build()
+            .expect("a transcript")
+    };
+    let mut writer = GtfWriter::from_memory();
+    writer.group_by_gene();
+    writer.write_transcript(&make_trx("t3", "g2", "chr1", 500, 900)).expect("a buffered trx");
+    writer.write_transcript(&make_trx("t1", "g1", "chr1", 100, 300)).expect("a buffered trx");
+    writer.write_transcript(&make_trx("t4", "g3", "chr0", 100, 200)).expect("a buffered trx");
+    writer.write_transcript(&make_trx("t2", "g1", "chr1", 50, 250)).expect("a buffered trx");
+    assert_eq!(writer.as_string(), "");
+
+    writer.finish().expect("written genes");
+    let features = writer.as_string().lines()
+        .map(|line| {
+            let cols = line.split('\t').collect::<Vec<&str>>();
+            format!("{}:{}:{}", cols[2], cols[3], cols[8].split(';').nth(1).unwrap_or("").trim())
+        })
+        .collect::<Vec<String>>();
+    assert_eq!(features, vec![
+        "gene:101:",
+        "transcript:101:transcript_id \"t4\"",
+        "exon:101:transcript_id \"t4\"",
+        "gene:51:",
+        "transcript:51:transcript_id \"t2\"",
+        "exon:51:transcript_id \"t2\"",
+        "transcript:101:transcript_id \"t1\"",
+        "exon:101:transcript_id \"t1\"",
+        "gene:501:",
+        "transcript:501:transcript_id \"t3\"",
+        "exon:501:transcript_id \"t3\"",
+    ]);
+}
+
+#[test]
+fn gtf_writer_group_by_gene_edge_cases() {
+    let trx = TBuilder::new("chr1", 100, 300)
+        .id("t1")
+        .gene_id("g1")
+        .strand(Forward)
+        .coords(vec![(100, 300)], None)
+        .build()
+        .expect("a transcript");
+    let gene = GBuilder::new("chr1", 100, 300)
+        .id("g1")
+        .strand(Forward)
+        .add_transcript(trx.clone())
+        .build()
+        .expect("a gene");
+    let empty_gene = GBuilder::new("chr2", 1000, 2000)
+        .id("g2")
+        .strand(Reverse)
+        .build()
+        .expect("a gene without transcripts");
+
+    let mut buf = Vec::new();
+    {
+        let mut writer = GtfWriter::from_writer(&mut buf);
+        writer.group_by_gene();
+        writer.write_gene(&gene).expect("a buffered gene");
+        writer.write_gene(&gene).expect("a buffered gene");
+        writer.write_transcript(&trx).expect("a buffered trx");
+        writer.write_gene(&empty_gene).expect("a buffered gene");
+    }
+    let text = String::from_utf8(buf).expect("a string");
+    let features = text.lines()
+        .map(|line| {
+            let cols = line.split('\t').collect::<Vec<&str>>();
+            format!("{}:{}:{}:{}", cols[0], cols[2], cols[3], cols[4])
+        })
+        .collect::<Vec<String>>();
+    assert_eq!(features, vec![
+        "chr1:gene:101:300",
+        "chr1:transcript:101:300",
+        "chr1:exon:101:300",
+        "chr2:gene:1001:2000",
+    ]);
+}
+
+#[test]
+fn gtf_reader_load_annotation() {
+    let mut reader = GtfReader::from_reader(SINGLE_GENE_GTF.as_bytes());
+    let (annotation, report) = reader.load_annotation().expect("an annotation");
+    assert_eq!(annotation.len(), 1);
+    assert_eq!(report.stats(), &annotation.stats());
+    assert_eq!(report.stats().num_genes(), 1);
+    assert_eq!(report.stats().num_transcripts(), 2);
+    assert_eq!(report.stats().num_exons(), 3);
+    assert!(report.stats().num_features() > 0);
+    assert!(report.stats().approx_bytes() > 0);
+    assert_eq!(report.total_time(), report.parse_time() + report.assembly_time());
+}
+
+#[test]
+fn gtf_reader_cancel_token() {
+    let token = CancelToken::new();
+    let hook_token = token.clone();
+    let mut reader = GtfReader::from_reader(SINGLE_GENE_GTF.as_bytes());
+    let _ = reader
+        .cancel_token(token)
+        .map_record(move |_| hook_token.cancel());
+    {
+        let mut records = reader.records_stream();
+        assert!(records.next().expect("a record result").is_ok());
+        let err = records.next().expect("a record result").unwrap_err();
+        assert!(matches!(err, Error::Cancelled));
+        assert!(records.next().is_none());
+    }
+    assert!(matches!(reader.load_annotation(), Err(Error::Cancelled)));
+}
+
+#[test]
+fn gtf_reader_declared_frames() {
+    let mut reader = GtfReader::from_reader(NO_PARENTS_GTF.as_bytes());
+    let trx = reader.transcripts_stream().next()
+        .expect("a transcript result")
+        .expect("a transcript");
+    let cds_frames = trx.exons().iter()
+        .flat_map(|exon| exon.features().iter())
+        .filter_map(|fx| match fx.kind() {
+            &EFK::CDS { frame } => Some((fx.start(), frame)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(cds_frames, vec![(150, Some(2)), (300, Some(0))]);
+    let mismatches = trx.frame_mismatches();
+    assert_eq!(mismatches.len(), 1);
+    assert_eq!(mismatches[0].to_string(),
+               "declared CDS frame differs from computed frame, transcript ID: t1, \
+                CDS: 150-200, declared: 2, computed: 1");
+
+    let mut reader = GtfReader::from_reader(NO_PARENTS_GTF.as_bytes());
+    let _ = reader.validate_frames(true);
+    let result = reader.transcripts_stream().next().expect("a transcript result");
+    assert!(matches!(result, Err(Error::Model(ModelError::FrameMismatch(..)))));
+
+    let mut reader = GtfReader::from_reader(SINGLE_GENE_GTF.as_bytes());
+    let _ = reader.validate_frames(true);
+    assert!(reader.transcripts_stream().all(|result| result.is_ok()));
+}
+
+#[test]
+fn gtf_reader_skip_errors() {
+    let input = "\
+chr1\tsrc\texon\t101\t200\t.\t+\t.\tgene_id \"g1\"; transcript_id \"t1\";
+chr1\tsrc\texon\tx\t200\t.\t+\t.\tgene_id \"g1\"; transcript_id \"t1\";
+chr1\tsrc\texon\t301\t400\t.\t+\t.\ttranscript_id \"t2\";
+chr1\tsrc\texon\t301\t400\t.\t+\t.\tgene_id \"g1\"; transcript_id \"t1\";
+";
+    let skipped = Rc::new(RefCell::new(Vec::new()));
+    let mut reader = GtfReader::from_reader(input.as_bytes());
+    let log = skipped.clone();
+    let _ = reader.on_error(ErrorPolicy::Skip(Box::new(move |line_num, err| {
+        log.borrow_mut().push((line_num, err.to_string()))
+    })));
+    let trxs = reader.transcripts_stream()
+        .collect::<Result<Vec<_>, _>>()
+        .expect("transcripts");
+    assert_eq!(trxs.len(), 1);
+    assert_eq!(trxs[0].exons().len(), 2);
+    assert_eq!(*skipped.borrow(),
+               vec![(2, "invalid gtf record, line: 2".to_owned()),
+                    (3, "gene identifier attribute not found, line: 3".to_owned())]);
+
+    let mut reader = GtfReader::from_reader(input.as_bytes());
+    assert!(matches!(reader.transcripts_stream().next(), Some(Err(Error::Gtf(..)))));
+}
+
+#[test]
+fn gtf_reader_skip_transcript_errors_in_genes() {
+    let input = "\
+chr1\tsrc\tgene\t101\t500\t.\t+\t.\tgene_id \"g1\";
+chr1\tsrc\texon\t101\t200\t.\t+\t.\tgene_id \"g1\"; transcript_id \"t1\";
+chr1\tsrc\texon\t401\t600\t.\t+\t.\tgene_id \"g1\"; transcript_id \"t2\";
+chr1\tsrc\tgene\t1001\t1100\t.\t+\t.\tgene_id \"g2\";
+chr1\tsrc\texon\t1201\t1300\t.\t+\t.\tgene_id \"g2\"; transcript_id \"t3\";
+chr1\tsrc\texon\t2001\t2100\t.\t+\t.\tgene_id \"g3\"; transcript_id \"t4\";
+";
+    let skipped = Rc::new(RefCell::new(Vec::new()));
+    let mut reader = GtfReader::from_reader(input.as_bytes());
+    let log = skipped.clone();
+    let _ = reader.on_error(ErrorPolicy::Skip(Box::new(move |line_num, _| {
+        log.borrow_mut().push(line_num)
+    })));
+    let genes = reader.genes_stream()
+        .collect::<Result<Vec<Gene>, _>>()
+        .expect("genes");
+    assert_eq!(genes.iter().map(|gx| gx.id().unwrap()).collect::<Vec<&str>>(), vec!["g1", "g3"]);
+    assert_eq!(genes[0].transcripts().keys().collect::<Vec<&String>>(), vec!["t1"]);
+    assert_eq!(*skipped.borrow(), vec![1, 4]);
+
+    let mut reader = GtfReader::from_reader(input.as_bytes());
+    let results = reader.genes_stream().collect::<Vec<_>>();
+    assert_eq!(results.len(), 3);
+    assert!(matches!(results[0], Err(Error::Model(ModelError::TranscriptNotFullyEnveloped(_)))));
+}
@@ -0,0 +1,52 @@
+extern crate gte;
+extern crate serde_json;
+
+mod common;
+
+use serde_json::Value;
+
+use gte::{Gene, JsonWriter, Strand, TranscriptCoords};
+
+
+fn make_gene(id: &str, seq_name: &str) -> Gene {
+    common::gene_builder(seq_name, id, Strand::Reverse, vec![
+        ("trx01", TranscriptCoords::new(100, 1000)
+            .exons(vec![(100, 300), (400, 1000)])
+            .coding(200, 800)),
+        ("trx02", TranscriptCoords::new(400, 1000).exon(400, 1000)),
+    ])
+        .attribute("tag", "basic")
+        .attribute("name", "A\"B\tC")
+        .build()
+        .expect("a gene")
+}
+
+#[test]
+fn json_writer_genes() {
+    let genes = vec![make_gene("gene01", "chr1"), make_gene("gene02", "chr2")];
+    let mut writer = JsonWriter::from_memory();
+    writer.write_genes(&genes).expect("written genes");
+    let output = writer.as_string().to_owned();
+    let lines = output.lines().collect::<Vec<&str>>();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with(
+        "{\"seq_name\":\"chr1\",\"start\":100,\"end\":1000,\"strand\":\"-\",\"id\":\"gene01\",\
+         \"attributes\":{\"tag\":[\"basic\"],\"name\":[\"A\\\"B\\tC\"]},"));
+
+    let gene: Value = serde_json::from_str(lines[1]).expect("a JSON object");
+    assert_eq!(gene["id"], Value::from("gene02"));
+    assert_eq!(gene["transcripts"].as_array().map(|trxs| trxs.len()), Some(2));
+
+    let trx = &gene["transcripts"][0];
+    assert_eq!(trx["id"], Value::from("trx01"));
+    assert_eq!(trx["coding"], serde_json::json!([200, 800]));
+    assert_eq!(trx["exons"][1]["start"], Value::from(400));
+    let kinds = trx["exons"][0]["features"].as_array().expect("features").iter()
+        .map(|fx| fx["kind"].as_str().expect("a feature kind").to_owned())
+        .collect::<Vec<String>>();
+    assert!(kinds.contains(&"CDS".to_owned()) && kinds.contains(&"UTR3".to_owned()));
+
+    let noncoding = &gene["transcripts"][1];
+    assert_eq!(noncoding["coding"], Value::Null);
+    assert_eq!(noncoding["exons"][0]["features"], serde_json::json!([]));
+}
@@ -0,0 +1,78 @@
+extern crate gte;
+
+use std::collections::HashMap;
+
+use gte::{CancelToken, Error, KnownGeneReader, Strand};
+
+
+static KNOWN_GENE: &'static str = "\
+uc001aaa.3\tchr1\t+\t11873\t14409\t11873\t11873\t3\t11873,12612,13220,\t12227,12721,14409,\t\tuc001aaa.3
+uc031tlb.1\tchr1\t-\t14361\t16765\t14361\t14361\t2\t14361,16606,\t14829,16765,\t\tuc031tlb.1
+uc010nxq.1\tchr1\t+\t11873\t14409\t12189\t13639\t3\t11873,12594,13402,\t12227,12721,14409,\tB7ZGX9\tuc010nxq.1
+";
+
+static KG_XREF: &'static str = "\
+uc001aaa.3\tNR_046018\t\t\tDDX11L1\tNR_046018\t\tDEAD/H box polypeptide 11 like 1
+uc010nxq.1\tNM_001\tB7ZGX9\tB7ZGX9_HUMAN\tDDX11L1\t\t\tDEAD/H box polypeptide 11 like 1
+uc031tlb.1\tAK000001\t\t\t\t\t\tno symbol
+";
+
+#[test]
+fn knowngene_reader_records() {
+    let mut reader = KnownGeneReader::from_reader(KNOWN_GENE.as_bytes());
+    let records = reader.records_stream()
+        .collect::<Result<Vec<_>, _>>()
+        .expect("knownGene records");
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[0].transcript_id(), Some("uc001aaa.3"));
+    assert_eq!(records[0].gene_id(), Some("uc001aaa.3"));
+    assert_eq!(records[1].seq_name(), "chr1");
+    assert_eq!(records[1].strand(), '-');
+    assert_eq!(records[2].exon_starts(), &[11873, 12594, 13402]);
+}
+
+#[test]
+fn knowngene_reader_xref_genes() {
+    let mut reader = KnownGeneReader::from_reader(KNOWN_GENE.as_bytes());
+    reader.xref_reader(KG_XREF.as_bytes()).expect("a loaded kgXref table");
+    let genes = reader.genes_stream()
+        .collect::<Result<Vec<_>, _>>()
+        .expect("knownGene genes");
+    assert_eq!(genes.len(), 2);
+    assert_eq!(genes[0].id(), Some("DDX11L1"));
+    assert_eq!(genes[0].strand(), &Strand::Forward);
+    assert_eq!(genes[0].transcripts().keys().collect::<Vec<_>>(),
+               vec!["uc001aaa.3", "uc010nxq.1"]);
+    assert_eq!(genes[1].id(), Some("uc031tlb.1"));
+    assert_eq!((genes[1].start(), genes[1].end()), (14361, 16765));
+}
+
+#[test]
+fn knowngene_reader_symbols() {
+    let mut symbols = HashMap::new();
+    symbols.insert("uc031tlb.1".to_owned(), "WASH7P".to_owned());
+    let mut reader = KnownGeneReader::from_reader(KNOWN_GENE.as_bytes());
+    let transcripts = reader.symbols(symbols).transcripts_stream()
+        .collect::<Result<Vec<_>, _>>()
+        .expect("knownGene transcripts");
+    assert_eq!(transcripts[1].gene_id(), Some("WASH7P"));
+    assert_eq!(transcripts[0].gene_id(), Some("uc001aaa.3"));
+}
+
+#[test]
+fn knowngene_reader_invalid() {
+    let mut reader = KnownGeneReader::from_reader("uc001aaa.3\tchr1\t+\t11873\n".as_bytes());
+    assert!(reader.records_stream().next().expect("a result").is_err());
+}
+
+#[test]
+fn knowngene_reader_cancel_token() {
+    let token = CancelToken::new();
+    let mut reader = KnownGeneReader::from_reader(KNOWN_GENE.as_bytes());
+    let _ = reader.cancel_token(token.clone());
+    let mut records = reader.records_stream();
+    assert!(records.next().expect("a record result").is_ok());
+    token.cancel();
+    assert!(matches!(records.next(), Some(Err(Error::Cancelled))));
+    assert!(records.next().is_none());
+}
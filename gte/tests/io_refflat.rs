@@ -1,14 +1,28 @@
 extern crate bio;
+extern crate flate2;
 extern crate linked_hash_map;
+#[macro_use]
+extern crate matches;
 extern crate gte;
 
-use std::io;
-
+use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::process;
+use std::rc::Rc;
+use std::time::Duration;
+
+use flate2::Compression;
+use flate2::write::GzEncoder;
 use linked_hash_map::LinkedHashMap;
 
-use gte::{RefFlatReader, RefFlatWriter, RefFlatRecord,
+use gte::sort::ChromOrder;
+use gte::{RefFlatReader, RefFlatWriter, RefFlatRecord, RefFlatRecordRef, RefFlatBorrowingReader,
           RefFlatRecordsStream, RefFlatTranscriptsStream, RefFlatGenesStream,
-          Transcript, TBuilder, Gene, GBuilder, Strand};
+          Transcript, TBuilder, Gene, GBuilder, Strand, CancelToken, Error, ErrorPolicy,
+          TabixError};
 
 
 static SINGLE_ROW_NO_CDS: &'static str = include_str!("data/single_row_no_cds.refFlat");
@@ -35,11 +49,24 @@ fn refflat_reader_records_single_row_no_cds() {
     let mut records = reader.records_stream();
 
     let rec1 = next_rec(&mut records);
-    assert_eq!(rec1.gene_id(), "DDX11L1");
+    assert_eq!(rec1.gene_id(), Some("DDX11L1"));
 
     assert!(records.next().is_none());
 }
 
+#[test]
+fn refflat_reader_records_empty_ids() {
+    let mut reader = RefFlatReader::from_reader(
+        "\t\tchr1\t+\t100\t200\t200\t200\t1\t100,\t200,\n".as_bytes());
+    let mut records = reader.records_stream();
+
+    let rec1 = next_rec(&mut records);
+    assert_eq!(rec1.gene_id(), None);
+    assert_eq!(rec1.gene_id_or_default(), "<unknown>");
+    assert_eq!(rec1.transcript_id(), None);
+    assert_eq!(rec1.transcript_id_or_default(), "<unknown>");
+}
+
 #[test]
 fn refflat_reader_transcripts_single_row_no_cds() {
     let mut reader = RefFlatReader::from_reader(SINGLE_ROW_NO_CDS.as_bytes());
@@ -68,14 +95,31 @@ fn refflat_reader_records_mult_rows_no_cds() {
     let mut records = reader.records_stream();
 
     let rec1 = next_rec(&mut records);
-    assert_eq!(rec1.gene_id(), "DDX11L1");
+    assert_eq!(rec1.gene_id(), Some("DDX11L1"));
 
     let rec2 = next_rec(&mut records);
-    assert_eq!(rec2.gene_id(), "MIR570");
+    assert_eq!(rec2.gene_id(), Some("MIR570"));
 
     assert!(records.next().is_none());
 }
 
+#[test]
+fn refflat_reader_map_record() {
+    let mut reader = RefFlatReader::from_reader(MULT_ROWS_NO_CDS.as_bytes());
+    let _ = reader
+        .map_record(|rec| {
+            let seq_name = rec.seq_name().trim_start_matches("chr").to_owned();
+            rec.set_seq_name(seq_name);
+        })
+        .region("3", 0, 200000000);
+    let mut records = reader.records_stream();
+
+    let rec = next_rec(&mut records);
+    assert_eq!(rec.gene_id(), Some("MIR570"));
+    assert_eq!(rec.seq_name(), "3");
+    assert!(records.next().is_none());
+}
+
 #[test]
 fn refflat_reader_transcripts_mult_rows_no_cds() {
     let mut reader = RefFlatReader::from_reader(MULT_ROWS_NO_CDS.as_bytes());
@@ -110,14 +154,14 @@ fn refflat_reader_records_mult_rows_mult_genes_with_cds() {
     let mut records = reader.records_stream();
 
     let rec1 = next_rec(&mut records);
-    assert_eq!(rec1.transcript_id(), "NM_001297605");
+    assert_eq!(rec1.transcript_id(), Some("NM_001297605"));
 
     let _rec2 = next_rec(&mut records);
     let _rec3 = next_rec(&mut records);
     let _rec4 = next_rec(&mut records);
 
     let rec5 = next_rec(&mut records);
-    assert_eq!(rec5.transcript_id(), "NM_138428");
+    assert_eq!(rec5.transcript_id(), Some("NM_138428"));
 
     assert!(records.next().is_none());
 }
@@ -407,3 +451,492 @@ fn refflat_writer_genes_mult_rows_mult_genes_with_cds() {
     }
     assert_eq!(writer.as_string(), MULT_ROWS_MULT_GENES_WITH_CDS);
 }
+
+#[test]
+fn refflat_writer_sorted() {
+    let mut reader = RefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    let records = reader.records_stream()
+        .collect::<Result<Vec<RefFlatRecord>, _>>()
+        .expect("records");
+
+    let mut writer = RefFlatWriter::from_writer(vec![]);
+    let _ = writer.sorted(true);
+    for rec in records[2..].iter().chain(records[..2].iter()) {
+        writer.write_record(rec).expect("a successful write");
+    }
+    assert_eq!(writer.as_string(), "");
+    writer.finish().expect("a successful finish");
+    assert_eq!(writer.as_string(), MULT_ROWS_MULT_GENES_WITH_CDS);
+
+    let mut writer = RefFlatWriter::from_writer(vec![]);
+    let _ = writer.sorted(true).max_buffered(4);
+    for rec in records[..4].iter() {
+        writer.write_record(rec).expect("a successful write");
+    }
+    let err = writer.write_record(&records[4]).expect_err("an error");
+    assert_eq!(err.to_string(), "number of buffered rows exceeds the limit, limit: 4");
+}
+
+#[test]
+fn refflat_writer_sorted_drop() {
+    let mut reader = RefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    let records = reader.records_stream()
+        .collect::<Result<Vec<RefFlatRecord>, _>>()
+        .expect("records");
+
+    let mut output = vec![];
+    {
+        let mut writer = RefFlatWriter::from_writer(&mut output);
+        let _ = writer.sorted(true);
+        for rec in records[2..].iter().chain(records[..2].iter()) {
+            writer.write_record(rec).expect("a successful write");
+        }
+    }
+    assert_eq!(String::from_utf8(output).expect("UTF-8 output"), MULT_ROWS_MULT_GENES_WITH_CDS);
+}
+
+#[test]
+fn refflat_writer_sorted_seq_order() {
+    let rows = "g1\tt1\tchr10\t+\t100\t200\t100\t100\t1\t100,\t200,\n\
+                g2\tt2\tchr2\t+\t300\t400\t300\t300\t1\t300,\t400,\n";
+    let records = RefFlatReader::from_reader(rows.as_bytes()).records_stream()
+        .collect::<Result<Vec<RefFlatRecord>, _>>()
+        .expect("records");
+    let sorted_ids = |order: ChromOrder| -> Vec<String> {
+        let mut writer = RefFlatWriter::from_writer(vec![]);
+        let _ = writer.sorted(true).seq_order(order);
+        for rec in records.iter() {
+            writer.write_record(rec).expect("a successful write");
+        }
+        writer.finish().expect("a successful finish");
+        writer.as_string().lines()
+            .map(|line| line.split('\t').nth(1).unwrap().to_owned())
+            .collect()
+    };
+    assert_eq!(sorted_ids(ChromOrder::Karyotypic), vec!["t2", "t1"]);
+    assert_eq!(sorted_ids(ChromOrder::Lexicographic), vec!["t1", "t2"]);
+}
+
+#[test]
+fn refflat_writer_exon_list_format() {
+    let row = "DDX11L1\tNR_046018\tchr1\t+\t11873\t14409\t14409\t14409\t3\t\
+               11873,12612,13220,\t12227,12721,14409,\n";
+    let mut reader = RefFlatReader::from_reader(row.as_bytes());
+    let trx = reader.transcripts_stream().next().expect("a transcript result")
+        .expect("a transcript");
+
+    let mut writer = RefFlatWriter::from_memory();
+    let _ = writer.exon_list_trailing_separator(false);
+    writer.write_transcript(&trx).expect("a successful write");
+    assert_eq!(writer.as_string(),
+               "DDX11L1\tNR_046018\tchr1\t+\t11873\t14409\t14409\t14409\t3\t\
+                11873,12612,13220\t12227,12721,14409\n");
+
+    let mut writer = RefFlatWriter::from_memory();
+    let _ = writer.exon_list_separator(';');
+    writer.write_transcript(&trx).expect("a successful write");
+    assert_eq!(writer.as_string(),
+               "DDX11L1\tNR_046018\tchr1\t+\t11873\t14409\t14409\t14409\t3\t\
+                11873;12612;13220;\t12227;12721;14409;\n");
+}
+
+#[test]
+fn refflat_reader_gene_pred() {
+    let data = "NM_1\tchr1\t+\t100\t200\t200\t200\t1\t100,\t200,\n\
+                NM_2\tchr1\t+\t150\t300\t150\t300\t2\t150,250,\t180,300,\n";
+
+    let mut reader = RefFlatReader::from_reader(data.as_bytes());
+    assert!(reader.records_stream().next().expect("a record result").is_err());
+
+    let mut reader = RefFlatReader::from_reader(data.as_bytes());
+    let _ = reader.gene_pred(true);
+    let mut records = reader.records_stream();
+    let rec1 = next_rec(&mut records);
+    assert_eq!(rec1.gene_id(), None);
+    assert_eq!(rec1.transcript_id(), Some("NM_1"));
+
+    let mut reader = RefFlatReader::from_reader(data.as_bytes());
+    let _ = reader.gene_pred(true);
+    let transcripts = reader.transcripts_stream()
+        .collect::<Result<Vec<Transcript>, _>>()
+        .expect("transcripts");
+    assert_eq!(transcripts.iter().map(|trx| (trx.id(), trx.gene_id())).collect::<Vec<_>>(),
+               vec![(Some("NM_1"), None), (Some("NM_2"), None)]);
+
+    let mut reader = RefFlatReader::from_reader(data.as_bytes());
+    let _ = reader.gene_pred(true);
+    let genes = reader.genes_stream()
+        .collect::<Result<Vec<Gene>, _>>()
+        .expect("genes");
+    assert_eq!(genes.iter().map(|gx| gx.id()).collect::<Vec<_>>(),
+               vec![Some("NM_1"), Some("NM_2")]);
+    assert!(genes.iter().all(|gx| gx.transcripts().len() == 1));
+}
+
+#[test]
+fn refflat_reader_unsorted_genes() {
+    let lines = MULT_ROWS_MULT_GENES_WITH_CDS.lines().collect::<Vec<&str>>();
+    let data = [lines[2], lines[0], lines[3], lines[1], lines[4]].join("\n");
+
+    let mut reader = RefFlatReader::from_reader(data.as_bytes());
+    assert_eq!(reader.genes_stream().count(), 5);
+
+    let mut reader = RefFlatReader::from_reader(data.as_bytes());
+    let genes = reader.unsorted_genes_stream()
+        .collect::<Result<Vec<Gene>, _>>()
+        .expect("genes");
+    assert_eq!(genes.len(), 2);
+    assert_eq!(genes[0].id(), Some("SMIM12"));
+    assert_eq!(genes[0].transcripts().keys().collect::<Vec<_>>(),
+               vec!["NM_001164824", "NM_001164825", "NM_138428"]);
+    assert_eq!(genes[1].id(), Some("TNFRSF14"));
+    assert_eq!(genes[1].transcripts().len(), 2);
+}
+
+#[test]
+fn refflat_reader_unsorted_genes_loci() {
+    let data = "PLCXD1\tNM_1\tchrX\t+\t100\t200\t200\t200\t1\t100,\t200,\n\
+                PLCXD1\tNM_1\tchrY\t+\t150\t300\t300\t300\t1\t150,\t300,\n\
+                PLCXD1\tNM_2\tchrX\t+\t120\t250\t250\t250\t1\t120,\t250,\n";
+
+    let mut reader = RefFlatReader::from_reader(data.as_bytes());
+    let genes = reader.unsorted_genes_stream()
+        .collect::<Result<Vec<Gene>, _>>()
+        .expect("genes");
+    assert_eq!(genes.iter().map(|gx| (gx.seq_name(), gx.start(), gx.end(),
+                                      gx.transcripts().len()))
+                   .collect::<Vec<_>>(),
+               vec![("chrX", 100, 250, 2), ("chrY", 150, 300, 1)]);
+
+    let mut reader = RefFlatReader::from_reader(data.as_bytes());
+    let _ = reader.merge_loci(true);
+    let mut genes = reader.unsorted_genes_stream();
+    let err = genes.next().expect("a gene result").expect_err("an error");
+    assert_eq!(err.to_string(), "gene has multiple transcripts with the same identifier, \
+                                 gene ID: PLCXD1");
+}
+
+#[test]
+fn refflat_reader_region_records() {
+    let mut reader = RefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    let tids = reader.region("chr1", 34859100, 34860000)
+        .records_stream()
+        .map(|rec| rec.expect("a refflat record").transcript_id_or_default().to_owned())
+        .collect::<Vec<String>>();
+    assert_eq!(tids, vec!["NM_001164825", "NM_138428"]);
+}
+
+#[test]
+fn refflat_reader_region_genes() {
+    let mut reader = RefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    let genes = reader.region("chr1", 2556000, 2557000)
+        .genes_stream()
+        .collect::<Result<Vec<Gene>, _>>()
+        .expect("genes");
+    assert_eq!(genes.len(), 1);
+    assert_eq!(genes[0].id(), Some("TNFRSF14"));
+    assert_eq!(genes[0].transcripts().len(), 2);
+
+    let mut reader = RefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    assert!(reader.region("chr2", 2556000, 2557000).transcripts_stream().next().is_none());
+}
+
+static INDEXED: &'static str = concat!(env!("CARGO_MANIFEST_DIR"),
+                                       "/tests/data/indexed.refFlat.gz");
+static INDEXED_TBI: &'static str = concat!(env!("CARGO_MANIFEST_DIR"),
+                                           "/tests/data/indexed.refFlat.gz.tbi");
+
+fn query_ids(seq_name: &str, start: u64, end: u64) -> Vec<String> {
+    let reader = RefFlatReader::indexed(INDEXED, INDEXED_TBI).expect("an indexed reader");
+    reader.query(seq_name, start, end).expect("queried records")
+        .into_iter()
+        .map(|rec| rec.transcript_id_or_default().to_owned())
+        .collect()
+}
+
+#[test]
+fn indexed_reader_truncated_index() {
+    // index claiming 2^32 - 1 sequences, without any of their contents
+    let mut raw = b"TBI\x01".to_vec();
+    raw.extend_from_slice(&[0xff, 0xff, 0xff, 0xff]);
+    raw.extend_from_slice(&[0; 4 * 7]);
+    let path = env::temp_dir().join(format!("gte-truncated-{}.tbi", process::id()));
+    let mut encoder = GzEncoder::new(fs::File::create(&path).expect("an index file"),
+                                     Compression::Default);
+    encoder.write_all(&raw).expect("written index");
+    let _ = encoder.finish().expect("a compressed index");
+
+    let result = RefFlatReader::indexed(INDEXED, &path);
+    assert!(matches!(result, Err(Error::Tabix(TabixError::TruncatedIndex))));
+}
+
+#[test]
+fn indexed_reader_query() {
+    assert_eq!(query_ids("chr1", 450, 460), vec!["TRX1", "TRX2"]);
+    assert_eq!(query_ids("chr1", 600, 700), vec!["TRX2"]);
+    assert_eq!(query_ids("chr1", 25000, 4999000), vec!["TRX3"]);
+    assert_eq!(query_ids("chr1", 4999000, 5000500), vec!["TRX4"]);
+    assert_eq!(query_ids("chr2", 1500, 1600), vec!["TRX5"]);
+    assert_eq!(query_ids("chr2", 0, u64::max_value()), vec!["TRX5"]);
+}
+
+#[test]
+fn indexed_reader_query_none() {
+    assert!(query_ids("chr1", 900, 20000).is_empty());
+    assert!(query_ids("chr2", 0, 1000).is_empty());
+    assert!(query_ids("chr3", 0, 1000).is_empty());
+}
+
+#[test]
+fn indexed_reader_query_transcripts() {
+    let reader = RefFlatReader::indexed(INDEXED, INDEXED_TBI).expect("an indexed reader");
+    let trxs = reader.query_transcripts("chr1", 150, 160).expect("queried transcripts");
+    assert_eq!(trxs.len(), 1);
+    assert_eq!(trxs[0].id(), Some("TRX1"));
+    assert_eq!(trxs[0].exons().len(), 2);
+}
+
+#[test]
+fn indexed_reader_query_cancel_token() {
+    let token = CancelToken::new();
+    let mut reader = RefFlatReader::indexed(INDEXED, INDEXED_TBI).expect("an indexed reader");
+    let _ = reader.cancel_token(token.clone());
+    assert_eq!(reader.query("chr2", 1500, 1600).expect("queried records").len(), 1);
+    token.cancel();
+    assert!(matches!(reader.query("chr2", 1500, 1600), Err(Error::Cancelled)));
+}
+
+#[test]
+fn refflat_reader_sorted_genes() {
+    let mut reader = RefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    let genes = reader.sorted_genes_stream()
+        .collect::<Result<Vec<Gene>, _>>()
+        .expect("genes");
+    assert_eq!(genes.len(), 2);
+    assert_eq!(genes[0].id(), Some("TNFRSF14"));
+    assert_eq!(genes[0].transcripts().len(), 2);
+    assert_eq!(genes[1].id(), Some("SMIM12"));
+    assert_eq!(genes[1].transcripts().len(), 3);
+    assert_eq!((genes[1].start(), genes[1].end()), (34850361, 34859816));
+}
+
+fn sorted_gene_results(lines: &[usize]) -> Vec<Result<String, String>> {
+    let rows = MULT_ROWS_MULT_GENES_WITH_CDS.lines().collect::<Vec<&str>>();
+    let input = lines.iter().map(|&idx| format!("{}\n", rows[idx])).collect::<String>();
+    let mut reader = RefFlatReader::from_reader(input.as_bytes());
+    reader.sorted_genes_stream()
+        .map(|res| res.map(|gx| gx.id().unwrap().to_owned()).map_err(|e| e.to_string()))
+        .collect()
+}
+
+#[test]
+fn refflat_reader_sorted_genes_unsorted() {
+    let results = sorted_gene_results(&[2, 3, 0, 1]);
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0], Ok("SMIM12".to_owned()));
+    assert!(results[1].is_err());
+
+    // records of the same gene that are not consecutive
+    let results = sorted_gene_results(&[0, 2, 3, 1]);
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[1], Ok("SMIM12".to_owned()));
+    assert!(results[2].is_err());
+}
+
+#[test]
+fn refflat_reader_load_annotation() {
+    let mut reader = RefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    let (annotation, report) = reader.load_annotation().expect("an annotation");
+    assert_eq!(annotation.genes().iter().map(|gx| gx.id().unwrap()).collect::<Vec<&str>>(),
+               vec!["TNFRSF14", "SMIM12"]);
+    assert_eq!(report.stats(), &annotation.stats());
+    assert_eq!(report.stats().num_genes(), 2);
+    assert_eq!(report.stats().num_transcripts(), 5);
+    assert!(report.stats().num_exons() >= 5);
+    assert!(report.stats().approx_bytes() > 0);
+}
+
+#[test]
+fn refflat_reader_cancel_token_timeout() {
+    let mut reader = RefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    let _ = reader.cancel_token(CancelToken::with_timeout(Duration::new(0, 0)));
+    assert!(matches!(reader.load_annotation(), Err(Error::Cancelled)));
+    let mut genes = reader.sorted_genes_stream();
+    assert!(matches!(genes.next(), Some(Err(Error::Cancelled))));
+}
+
+#[test]
+fn refflat_reader_skip_errors() {
+    let rows = MULT_ROWS_MULT_GENES_WITH_CDS.lines().collect::<Vec<&str>>();
+    let input = format!("{}\n{}\n{}\n{}\n",
+                        rows[0],
+                        "GENE\tTRX\tchr1\t+\tten\t200\t200\t200\t1\t100,\t200,",
+                        "GENE\tTRX\tchr1\t+\t100\t200\t200\t200\t2\t100,\t200,",
+                        rows[1]);
+
+    let skipped = Rc::new(RefCell::new(Vec::new()));
+    let mut reader = RefFlatReader::from_reader(input.as_bytes());
+    let log = skipped.clone();
+    let _ = reader.on_error(ErrorPolicy::Skip(Box::new(move |line_num, err| {
+        log.borrow_mut().push((line_num, err.to_string()))
+    })));
+    let trxs = reader.transcripts_stream()
+        .collect::<Result<Vec<Transcript>, _>>()
+        .expect("transcripts");
+    assert_eq!(trxs.len(), 2);
+    let skipped = skipped.borrow();
+    assert_eq!(skipped.iter().map(|&(line_num, _)| line_num).collect::<Vec<usize>>(), vec![2, 3]);
+    assert!(skipped[1].1.starts_with("number of exons and number of exon coordinates"));
+
+    let mut reader = RefFlatReader::from_reader(input.as_bytes());
+    assert_eq!(reader.transcripts_stream().filter(|res| res.is_err()).count(), 2);
+}
+
+#[test]
+fn refflat_reader_skip_transcript_errors_in_genes() {
+    let input = "\
+G1\tT1\tchr1\t+\t100\t200\t200\t200\t1\t100,\t200,
+G1\tT1\tchr1\t+\t100\t300\t300\t300\t1\t100,\t300,
+G1\tT2\tchr1\t+\t150\t300\t300\t300\t1\t150,\t300,
+G2\tT3\tchr1\t+\t700\t800\t650\t750\t1\t700,\t800,
+G3\tT4\tchr1\t+\t900\t1000\t1000\t1000\t1\t900,\t1000,
+";
+    let skipped = Rc::new(RefCell::new(Vec::new()));
+    let mut reader = RefFlatReader::from_reader(input.as_bytes());
+    let log = skipped.clone();
+    let _ = reader.on_error(ErrorPolicy::Skip(Box::new(move |line_num, _| {
+        log.borrow_mut().push(line_num)
+    })));
+    let genes = reader.genes_stream()
+        .collect::<Result<Vec<Gene>, _>>()
+        .expect("genes");
+    assert_eq!(genes.iter().map(|gx| gx.id().unwrap()).collect::<Vec<&str>>(), vec!["G1", "G3"]);
+    assert_eq!(genes[0].transcripts().keys().collect::<Vec<&String>>(), vec!["T1", "T2"]);
+    assert_eq!(genes[0].end(), 300);
+    assert_eq!(*skipped.borrow(), vec![2, 4]);
+
+    let mut reader = RefFlatReader::from_reader(input.as_bytes());
+    let results = reader.genes_stream().collect::<Vec<_>>();
+    assert_eq!(results.iter().filter(|res| res.is_err()).count(), 2);
+}
+
+#[test]
+fn refflat_reader_invalid_record_location() {
+    let input = "GENE\tTRX1\tchr1\t+\t100\t200\t200\t200\t1\t100,\t200,\n\
+                 GENE\tTRX2\tchr1\t+\t100\tx\t200\t200\t1\t100,\t200,\n";
+    let mut reader = RefFlatReader::from_reader(input.as_bytes());
+    let results = reader.records_stream()
+        .map(|res| res.map_err(|e| e.to_string()))
+        .collect::<Vec<_>>();
+    assert_eq!(results.len(), 2);
+    assert!(results[0].is_ok());
+    let expected = "invalid refFlat record, line: 2, field: txEnd, text: \
+                    \"GENE\\tTRX2\\tchr1\\t+\\t100\\tx\\t200\\t200\\t1\\t100,\\t200,\"";
+    assert_eq!(results[1], Err(expected.to_owned()));
+
+    let err = RefFlatRecordRef::try_from_line("GENE\tTRX\tchr1", 7).expect_err("an error");
+    assert_eq!(err.to_string(), "invalid refFlat record, line: 7, text: \"GENE\\tTRX\\tchr1\"");
+}
+
+#[test]
+fn refflat_borrowing_reader() {
+    let mut reader = RefFlatBorrowingReader::from_reader(
+        MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    let mut owned = RefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    let mut records = owned.records_stream();
+
+    let mut num_records = 0;
+    while let Some(result) = reader.next_record() {
+        let rec = result.expect("a borrowed refflat record");
+        let expected = next_rec(&mut records);
+        assert_eq!(rec.gene_id(), expected.gene_id());
+        assert_eq!(rec.seq_name(), expected.seq_name());
+        assert_eq!(rec.num_exons(), expected.num_exons());
+        assert_eq!(rec.exon_coords(), expected.exon_starts().iter().cloned()
+                   .zip(expected.exon_ends().iter().cloned())
+                   .collect::<Vec<(u64, u64)>>());
+        assert_eq!(rec.to_owned(), expected);
+        num_records += 1;
+    }
+    assert_eq!(num_records, 5);
+    assert!(records.next().is_none());
+}
+
+#[test]
+fn refflat_record_ref_invalid() {
+    let line = "GENE\tTRX\tchr1\t+\t100\t200\t200\t200\t1\t100,\t200,";
+    let rec = RefFlatRecordRef::try_from_line(line, 1).expect("a borrowed refflat record");
+    assert_eq!(rec.transcript_id(), Some("TRX"));
+    assert_eq!((rec.transcript_start(), rec.transcript_end()), (100, 200));
+
+    assert!(RefFlatRecordRef::try_from_line("GENE\tTRX\tchr1", 1).is_err());
+    let line = "GENE\tTRX\tchr1\t+\t100\t200\t200\t200\t2\t100,\t200,";
+    assert!(RefFlatRecordRef::try_from_line(line, 1).is_err());
+    let line = "GENE\tTRX\tchr1\t+\t100\t200\t200\t200\t1\tx,\t200,";
+    assert!(RefFlatRecordRef::try_from_line(line, 1).is_err());
+}
+
+#[test]
+fn refflat_record_transcript_conversion() {
+    let trx = TBuilder::new("chr1", 100, 1000)
+        .id("TRX")
+        .gene_id("GENE")
+        .strand(Strand::Reverse)
+        .coords(vec![(100, 300), (700, 1000)], Some((200, 800)))
+        .coding_incl_stop(true)
+        .build()
+        .expect("a transcript");
+    let rec = RefFlatRecord::try_from(&trx).expect("a refflat record");
+    assert_eq!((rec.gene_id(), rec.transcript_id()), (Some("GENE"), Some("TRX")));
+    assert_eq!((rec.seq_name(), rec.strand()), ("chr1", '-'));
+    assert_eq!((rec.coding_start(), rec.coding_end()), (200, 800));
+    assert_eq!(rec.exon_starts(), &[100, 700]);
+    assert_eq!(rec.exon_ends(), &[300, 1000]);
+
+    let back = Transcript::try_from(rec).expect("a transcript");
+    assert_eq!(back.coding_coord(true), trx.coding_coord(true));
+    assert_eq!(back.exons().iter().map(|e| (e.start(), e.end())).collect::<Vec<_>>(),
+               vec![(100, 300), (700, 1000)]);
+
+    let no_id = TBuilder::new("chr1", 100, 1000)
+        .strand(Strand::Forward)
+        .coords(vec![(100, 1000)], None)
+        .build()
+        .expect("a transcript");
+    assert!(RefFlatRecord::try_from(&no_id).is_err());
+}
+
+#[test]
+fn refflat_record_parse_line() {
+    let line = "GENE\tTRX\tchr1\t+\t100\t1000\t200\t800\t2\t100,700,\t300,1000,\n";
+    let rec = RefFlatRecord::parse_line(line).expect("a refflat record");
+    assert_eq!((rec.gene_id(), rec.transcript_id()), (Some("GENE"), Some("TRX")));
+    assert_eq!((rec.transcript_start(), rec.transcript_end()), (100, 1000));
+    assert_eq!(rec.exon_starts(), &[100, 700]);
+    assert_eq!(rec.exon_ends(), &[300, 1000]);
+    assert_eq!(line.parse::<RefFlatRecord>().expect("a refflat record"), rec);
+
+    assert!("GENE\tTRX\tchr1".parse::<RefFlatRecord>().is_err());
+    let line = "GENE\tTRX\tchr1\t+\t100\t1000\t200\t800\t3\t100,700,\t300,1000,";
+    assert!(RefFlatRecord::parse_line(line).is_err());
+}
+
+#[test]
+fn refflat_record_invalid_exon_coords() {
+    let line = "GENE\tTRX\tchr1\t+\t100\t1000\t200\t800\t2\t100,700,\t300,700,";
+    let err = RefFlatRecord::parse_line(line).expect_err("an error");
+    assert_eq!(err.to_string(),
+               "exon start coordinate is not less than its end coordinate, exon: 700-700, \
+                transcript ID: TRX");
+    assert!(RefFlatRecordRef::try_from_line(line, 1).is_err());
+
+    let line = "GENE\tTRX\tchr1\t+\t100\t1000\t200\t800\t2\t100,700,\t300,";
+    let err = RefFlatRecord::parse_line(line).expect_err("an error");
+    assert!(err.to_string().ends_with("transcript ID: TRX"));
+
+    let mut rec = RefFlatRecord::parse_line(
+        "GENE\tTRX\tchr1\t+\t100\t1000\t200\t800\t1\t100,\t1000,")
+        .expect("a refflat record");
+    assert!(rec.set_exon_coords(vec![100, 700], vec![300, 600]).is_err());
+    assert!(rec.set_exon_coords(vec![100, 700], vec![300, 1000]).is_ok());
+}
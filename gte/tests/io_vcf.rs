@@ -0,0 +1,133 @@
+#[macro_use]
+extern crate matches;
+extern crate gte;
+
+mod common;
+
+use gte::{Error, Gene, Strand, TranscriptCoords, VcfAnnotator, VcfError};
+
+use common::make_gene;
+
+
+static HEADER: &'static str = "##fileformat=VCFv4.2
+##contig=<ID=chr1,length=5000>
+##contig=<ID=chr2,length=5000>
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO
+";
+
+fn make_genes() -> Vec<Gene> {
+    let gene01 = make_gene("chr1", "gene01", Strand::Forward, vec![
+        ("trx01", TranscriptCoords::new(100, 1000)
+            .exons(vec![(100, 300), (500, 1000)])
+            .coding(200, 800)),
+        ("trx02", TranscriptCoords::new(500, 1000).exon(500, 1000)),
+    ]);
+    let gene02 = make_gene("chr2", "gene02", Strand::Reverse, vec![
+        ("trx03", TranscriptCoords::new(100, 200).exon(100, 200)),
+    ]);
+    vec![gene01, gene02]
+}
+
+fn annotate(records: &str) -> gte::Result<(String, usize, usize)> {
+    let vcf = format!("{}{}", HEADER, records);
+    let mut out = Vec::new();
+    let genes = make_genes().into_iter().map(Ok);
+    let summary = VcfAnnotator::new().annotate(vcf.as_bytes(), genes, &mut out)?;
+    Ok((String::from_utf8(out).unwrap(), summary.num_variants(), summary.num_annotated()))
+}
+
+#[test]
+fn vcf_annotate() {
+    let records = [
+        "chr1\t50\trs1\tA\tG\t.\tPASS\t.",
+        "chr1\t151\trs2\tC\tT\t.\tPASS\tDP=10",
+        "chr1\t401\trs3\tG\tA\t.\tPASS\t.",
+        "chr1\t601\trs4\tT\tC\t.\tPASS\t.\tGT\t0/1",
+        "chr2\t150\trs5\tA\tC\t.\tPASS\t.",
+    ].join("\n");
+    let (out, num_variants, num_annotated) = annotate(&records).expect("an annotated VCF");
+    assert_eq!((num_variants, num_annotated), (5, 4));
+
+    let lines = out.lines().collect::<Vec<&str>>();
+    assert_eq!(lines.len(), 12);
+    assert!(lines[3].starts_with("##INFO=<ID=GENE,"));
+    assert!(lines[4].starts_with("##INFO=<ID=TRANSCRIPT,"));
+    assert!(lines[5].starts_with("##INFO=<ID=GTE_CSQ,"));
+    assert!(lines[6].starts_with("#CHROM"));
+    assert_eq!(lines[7], "chr1\t50\trs1\tA\tG\t.\tPASS\t.");
+    assert_eq!(lines[8], "chr1\t151\trs2\tC\tT\t.\tPASS\t\
+                          DP=10;GENE=gene01;TRANSCRIPT=trx01;GTE_CSQ=5_prime_UTR_variant");
+    assert_eq!(lines[9], "chr1\t401\trs3\tG\tA\t.\tPASS\t\
+                          GENE=gene01;TRANSCRIPT=trx01;GTE_CSQ=intron_variant");
+    assert_eq!(lines[10], "chr1\t601\trs4\tT\tC\t.\tPASS\t\
+                           GENE=gene01,gene01;TRANSCRIPT=trx01,trx02;\
+                           GTE_CSQ=coding_sequence_variant,non_coding_transcript_exon_variant\t\
+                           GT\t0/1");
+    assert_eq!(lines[11], "chr2\t150\trs5\tA\tC\t.\tPASS\t\
+                           GENE=gene02;TRANSCRIPT=trx03;\
+                           GTE_CSQ=non_coding_transcript_exon_variant");
+}
+
+#[test]
+fn vcf_annotate_unsorted() {
+    let records = "chr2\t150\trs1\tA\tC\t.\tPASS\t.\nchr1\t50\trs2\tA\tG\t.\tPASS\t.";
+    assert!(matches!(annotate(records).unwrap_err(), Error::Vcf(VcfError::UnsortedRecord(6))));
+}
+
+#[test]
+fn vcf_annotate_unknown_seq() {
+    let records = "chrX\t150\trs1\tA\tC\t.\tPASS\t.";
+    assert!(matches!(annotate(records).unwrap_err(),
+                     Error::Vcf(VcfError::UnknownSeqName(_, 5))));
+}
+
+#[test]
+fn vcf_annotate_most_severe() {
+    let records = "chr1\t199\trs1\tACG\tA\t.\tPASS\t.";
+    let (out, _, num_annotated) = annotate(records).expect("an annotated VCF");
+    assert_eq!(num_annotated, 1);
+    assert_eq!(out.lines().last(), Some("chr1\t199\trs1\tACG\tA\t.\tPASS\t\
+                                         GENE=gene01;TRANSCRIPT=trx01;\
+                                         GTE_CSQ=coding_sequence_variant"));
+}
+
+#[test]
+fn vcf_annotate_existing_tags() {
+    let vcf = "##fileformat=VCFv4.2
+##contig=<ID=chr1,length=5000>
+##INFO=<ID=GENE,Number=1,Type=String,Description=\"Old genes\">
+##INFO=<ID=CSQ,Number=.,Type=String,Description=\"VEP consequences\">
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO
+chr1\t50\trs1\tA\tG\t.\tPASS\tGENE=old
+chr1\t401\trs2\tG\tA\t.\tPASS\tCSQ=A|intron_variant;GENE=old;DP=3
+";
+    let mut out = Vec::new();
+    let _ = VcfAnnotator::new().annotate(vcf.as_bytes(), make_genes().into_iter().map(Ok),
+                                         &mut out)
+        .expect("an annotated VCF");
+    let out = String::from_utf8(out).unwrap();
+    let lines = out.lines().collect::<Vec<&str>>();
+    assert_eq!(lines.iter().filter(|line| line.starts_with("##INFO=<ID=GENE,")).count(), 1);
+    assert!(lines.iter().any(|line| line.starts_with("##INFO=<ID=CSQ,")));
+    assert_eq!(lines[lines.len() - 2], "chr1\t50\trs1\tA\tG\t.\tPASS\t.");
+    assert_eq!(lines[lines.len() - 1], "chr1\t401\trs2\tG\tA\t.\tPASS\t\
+                                        CSQ=A|intron_variant;DP=3;GENE=gene01;\
+                                        TRANSCRIPT=trx01;GTE_CSQ=intron_variant");
+}
+
+#[test]
+fn vcf_annotate_missing_contigs() {
+    let vcf = "##fileformat=VCFv4.2\n#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO
+chr1\t601\trs1\tT\tC\t.\tPASS\t.\nchr2\t150\trs2\tA\tC\t.\tPASS\t.\n";
+    let mut out = Vec::new();
+    let summary = VcfAnnotator::new()
+        .annotate(vcf.as_bytes(), make_genes().into_iter().map(Ok), &mut out)
+        .expect("an annotated VCF");
+    assert_eq!((summary.num_variants(), summary.num_annotated()), (2, 2));
+
+    let vcf = "#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO
+chr2\t150\trs1\tA\tC\t.\tPASS\t.\nchr1\t601\trs2\tT\tC\t.\tPASS\t.\n";
+    let res = VcfAnnotator::new().annotate(vcf.as_bytes(), make_genes().into_iter().map(Ok),
+                                           Vec::new());
+    assert!(matches!(res.unwrap_err(), Error::Vcf(VcfError::UnsortedRecord(3))));
+}
@@ -0,0 +1,53 @@
+extern crate gte;
+
+mod common;
+
+use gte::{AnnotationSet, Genome, Strand, TranscriptCoords};
+
+use common::make_gene;
+
+
+static FASTA: &'static str = ">chr1
+ACGTACGTAAGGCCTTAAGGCCTTACGTACGTACGTACGTTTTTGGGGGCCC
+";
+
+fn make_annotation() -> AnnotationSet {
+    let fwd = make_gene("chr1", "gene01", Strand::Forward, vec![
+        ("trx01", TranscriptCoords::new(0, 40).exons(vec![(0, 10), (20, 40)])),
+        ("trx02", TranscriptCoords::new(5, 50).exons(vec![(5, 10), (30, 50)])),
+    ]);
+    let rev = make_gene("chr1", "gene02", Strand::Reverse, vec![
+        ("trx03", TranscriptCoords::new(44, 50).exon(44, 50)),
+    ]);
+    AnnotationSet::from_genes(vec![fwd, rev])
+}
+
+#[test]
+fn unique_isoform_regions() {
+    let annotation = make_annotation();
+    let results = annotation.unique_isoform_regions(1);
+    let summary = results.iter()
+        .map(|r| (r.transcript_id().to_owned(), r.regions().to_vec(), r.num_bases()))
+        .collect::<Vec<_>>();
+    assert_eq!(summary, vec![
+        ("trx01".to_owned(), vec![(0, 5), (20, 30)], 15),
+        ("trx02".to_owned(), vec![(40, 50)], 10),
+        ("trx03".to_owned(), vec![(44, 50)], 6),
+    ]);
+    assert_eq!(results[0].gene_id(), Some("gene01"));
+
+    let results = annotation.unique_isoform_regions(8);
+    assert_eq!(results.iter().map(|r| r.transcript_id()).collect::<Vec<_>>(),
+               vec!["trx01", "trx02"]);
+    assert_eq!(results[0].regions(), &[(20, 30)]);
+}
+
+#[test]
+fn unique_isoform_sequences() {
+    let genome = Genome::from_reader(FASTA.as_bytes()).expect("a genome");
+    let results = make_annotation().unique_isoform_regions(1);
+    assert_eq!(results[0].sequences(&genome),
+               Some(vec![b"ACGTA".to_vec(), b"CCTTACGTAC".to_vec()]));
+    assert_eq!(results[2].sequences(&genome), Some(vec![b"GCCCCC".to_vec()]));
+    assert_eq!(results[2].sequences(&Genome::new()), None);
+}
@@ -0,0 +1,108 @@
+extern crate gte;
+
+mod common;
+
+use gte::{AnnotationSet, JunctionReader, SpliceJunction, Strand};
+
+use common::spliced_gene;
+
+
+static JUNCTIONS: &'static str = "\
+chr1\t301\t400\t1\t1\t1\t10\t0\t20
+chr1\t301\t700\t1\t1\t0\t5\t0\t20
+chr1\t2001\t2100\t0\t0\t0\t3\t0\t10
+chr1\t501\t700\t2\t2\t0\t1\t0\t10
+";
+
+
+fn make_annotation() -> AnnotationSet {
+    AnnotationSet::from_genes(vec![
+        spliced_gene("chr1", "gene01", Strand::Forward, vec![(100, 300), (400, 500), (700, 1000)]),
+    ])
+}
+
+fn read_junctions() -> Vec<SpliceJunction> {
+    let mut reader = JunctionReader::from_reader(JUNCTIONS.as_bytes());
+    let junctions = reader.junctions_stream()
+        .collect::<Result<Vec<SpliceJunction>, _>>()
+        .expect("junctions");
+    junctions
+}
+
+#[test]
+fn junction_reader_basic() {
+    let junctions = read_junctions();
+    assert_eq!(junctions.len(), 4);
+    assert_eq!(junctions[0].seq_name(), "chr1");
+    assert_eq!((junctions[0].start(), junctions[0].end()), (300, 400));
+    assert_eq!(junctions[0].strand(), &Strand::Forward);
+    assert_eq!(junctions[0].unique_reads(), 10);
+    assert_eq!(junctions[2].strand(), &Strand::Unknown);
+    assert_eq!(junctions[3].strand(), &Strand::Reverse);
+}
+
+#[test]
+fn junction_reader_invalid() {
+    let mut reader = JunctionReader::from_reader("chr1\tfoo\t400\t1\n".as_bytes());
+    let mut junctions = reader.junctions_stream();
+    assert!(junctions.next().expect("a junction result").is_err());
+}
+
+#[test]
+fn junction_report_basic() {
+    let annotation = make_annotation();
+    let report = annotation.junction_report(read_junctions(), 0);
+    assert_eq!(report.num_known(), 1);
+    assert_eq!(report.num_novel(), 1);
+    assert_eq!(report.num_intergenic(), 2);
+    assert_eq!(report.genes().len(), 1);
+    let stats = &report.genes()[0];
+    assert_eq!(stats.gene_id(), Some("gene01"));
+    assert_eq!(stats.num_annotated(), 2);
+    assert_eq!(stats.num_known(), 1);
+    assert_eq!(stats.num_novel(), 1);
+    assert_eq!(stats.known_fraction(), Some(0.5));
+    assert_eq!(stats.completeness(), Some(0.5));
+}
+
+#[test]
+fn junction_report_min_reads() {
+    let annotation = make_annotation();
+    let report = annotation.junction_report(read_junctions(), 6);
+    assert_eq!(report.num_known(), 1);
+    assert_eq!(report.num_novel(), 0);
+    assert_eq!(report.num_intergenic(), 0);
+}
+
+#[test]
+fn junction_report_duplicated_junctions() {
+    let annotation = make_annotation();
+    let text = "\
+chr1\t301\t400\t1\t1\t1\t10\t0\t20
+chr1\t301\t400\t1\t1\t1\t4\t0\t20
+chr1\t301\t400\t1\t1\t1\t8\t0\t20
+chr1\t301\t450\t1\t1\t0\t2\t0\t20
+chr1\t301\t450\t1\t1\t0\t2\t0\t20
+";
+    let junctions = JunctionReader::from_reader(text.as_bytes())
+        .junctions_stream()
+        .collect::<Result<Vec<SpliceJunction>, _>>()
+        .expect("junctions");
+    let report = annotation.junction_report(junctions, 0);
+    assert_eq!(report.num_known(), 1);
+    assert_eq!(report.num_novel(), 1);
+    let stats = &report.genes()[0];
+    assert_eq!(stats.num_known(), 1);
+    assert_eq!(stats.num_novel(), 1);
+    assert_eq!(stats.completeness(), Some(0.5));
+    assert!(stats.completeness().unwrap() <= 1.0);
+}
+
+#[test]
+fn junction_saturation_basic() {
+    let annotation = make_annotation();
+    let points = annotation.junction_saturation(&read_junctions(), &[0.0, 1.0]);
+    assert_eq!(points.len(), 2);
+    assert_eq!((points[0].known(), points[0].novel()), (0.0, 0.0));
+    assert_eq!((points[1].known(), points[1].novel()), (1.0, 3.0));
+}
@@ -0,0 +1,187 @@
+extern crate bio;
+extern crate gte;
+#[macro_use]
+extern crate matches;
+
+use bio::utils::Interval;
+
+use gte::{ChainMap, EBuilder, ExonFeature, ExonFeatureKind, GBuilder, Strand, TBuilder,
+          TranscriptCoords, Unmapped};
+
+
+static CHAINS: &'static str = "\
+chain 1000 chr1 1000 + 0 1000 chrA 2000 + 100 1090 1
+500 10 0
+490
+
+chain 1000 chr2 500 + 0 100 chrB 300 - 0 100 2
+100
+";
+
+
+#[test]
+fn chain_lift_interval_forward() {
+    let chains = ChainMap::from_reader(CHAINS.as_bytes()).expect("a chain map");
+
+    let li1 = chains.lift_interval("chr1", 100, 200).expect("a lifted interval");
+    assert_eq!(li1.seq_name(), "chrA");
+    assert_eq!((li1.start(), li1.end()), (200, 300));
+    assert!(!li1.is_reversed());
+
+    let li2 = chains.lift_interval("chr1", 520, 530).expect("a lifted interval");
+    assert_eq!((li2.start(), li2.end()), (610, 620));
+}
+
+#[test]
+fn chain_lift_interval_reverse() {
+    let chains = ChainMap::from_reader(CHAINS.as_bytes()).expect("a chain map");
+
+    let li = chains.lift_interval("chr2", 10, 20).expect("a lifted interval");
+    assert_eq!(li.seq_name(), "chrB");
+    assert_eq!((li.start(), li.end()), (280, 290));
+    assert!(li.is_reversed());
+}
+
+#[test]
+fn chain_lift_interval_unmapped() {
+    let chains = ChainMap::from_reader(CHAINS.as_bytes()).expect("a chain map");
+
+    assert!(matches!(chains.lift_interval("chr1", 495, 515),
+                     Err(Unmapped::PartiallyDeleted)));
+    assert!(matches!(chains.lift_interval("chr3", 10, 20), Err(Unmapped::Deleted)));
+}
+
+#[test]
+fn chain_lift_interval_multiple_chains() {
+    let chains = ChainMap::from_reader("\
+chain 1000 chr5 1000 + 0 1000 chrA 1000 + 0 1000 1
+1000
+
+chain 500 chr5 1000 + 100 200 chrB 1000 + 0 100 2
+100
+
+chain 500 chr6 100 + 0 50 chrA 100 + 0 50 3
+50
+
+chain 500 chr6 100 + 50 100 chrB 100 + 0 50 4
+50
+".as_bytes()).expect("a chain map");
+
+    let li = chains.lift_interval("chr5", 120, 150).expect("a lifted interval");
+    assert_eq!(li.seq_name(), "chrA");
+    assert_eq!((li.start(), li.end()), (120, 150));
+    assert!(matches!(chains.lift_interval("chr6", 40, 60), Err(Unmapped::Split)));
+}
+
+#[test]
+fn chain_invalid_block() {
+    let chains = ChainMap::from_reader("chain 1 chr1 10 + 0 10 chrA 10 + 0 10 1\nfoo\n".as_bytes());
+    assert!(chains.is_err());
+}
+
+#[test]
+fn chain_lift_transcript_forward() {
+    let chains = ChainMap::from_reader(CHAINS.as_bytes()).expect("a chain map");
+    let trx = TBuilder::new("chr1", 100, 700)
+        .strand(Strand::Forward)
+        .id("trx01")
+        .gene_id("gene01")
+        .coords(vec![(100, 200), (600, 700)], Some((150, 650)))
+        .build()
+        .expect("a transcript");
+
+    let lifted = chains.lift_transcript(&trx).expect("a lifted transcript");
+    assert_eq!(lifted.seq_name(), "chrA");
+    assert_eq!((lifted.start(), lifted.end()), (200, 790));
+    assert_eq!(lifted.strand(), &Strand::Forward);
+    assert_eq!(lifted.id(), Some("trx01"));
+    assert_eq!(lifted.gene_id(), Some("gene01"));
+    let exon_coords = lifted.exons().iter()
+        .map(|exn| (exn.start(), exn.end()))
+        .collect::<Vec<(u64, u64)>>();
+    assert_eq!(exon_coords, vec![(200, 300), (690, 790)]);
+    assert_eq!(lifted.coding_coord(true), Some((250, 743)));
+}
+
+#[test]
+fn chain_lift_transcript_reverse() {
+    let chains = ChainMap::from_reader(CHAINS.as_bytes()).expect("a chain map");
+    let trx = TBuilder::new("chr2", 10, 60)
+        .strand(Strand::Forward)
+        .coords(vec![(10, 20), (40, 60)], None)
+        .build()
+        .expect("a transcript");
+
+    let lifted = chains.lift_transcript(&trx).expect("a lifted transcript");
+    assert_eq!(lifted.seq_name(), "chrB");
+    assert_eq!((lifted.start(), lifted.end()), (240, 290));
+    assert_eq!(lifted.strand(), &Strand::Reverse);
+}
+
+fn make_feat(start: u64, end: u64, kind: ExonFeatureKind) -> ExonFeature {
+    ExonFeature::new(Interval::new(start..end).unwrap(), kind)
+}
+
+#[test]
+fn chain_lift_exon() {
+    let chains = ChainMap::from_reader(CHAINS.as_bytes()).expect("a chain map");
+    let exon = EBuilder::new("chr2", 10, 40)
+        .strand(Strand::Forward)
+        .id("exn01")
+        .transcript_id("trx01")
+        .feature(make_feat(10, 20, ExonFeatureKind::UTR5))
+        .feature(make_feat(20, 40, ExonFeatureKind::CDS { frame: Some(0) }))
+        .build()
+        .expect("an exon");
+
+    let lifted = chains.lift_exon(&exon).expect("a lifted exon");
+    assert_eq!(lifted.seq_name(), "chrB");
+    assert_eq!((lifted.start(), lifted.end()), (260, 290));
+    assert_eq!(lifted.strand(), &Strand::Reverse);
+    assert_eq!(lifted.transcript_id(), Some("trx01"));
+    let fxs = lifted.features().iter()
+        .map(|fx| (fx.start(), fx.end(), fx.kind().clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(fxs, vec![(260, 280, ExonFeatureKind::CDS { frame: Some(0) }),
+                         (280, 290, ExonFeatureKind::UTR5)]);
+
+    let exon = EBuilder::new("chr1", 490, 520).build().expect("an exon");
+    assert!(matches!(chains.lift_exon(&exon), Err(Unmapped::PartiallyDeleted)));
+}
+
+#[test]
+fn chain_lift_gene() {
+    let chains = ChainMap::from_reader(CHAINS.as_bytes()).expect("a chain map");
+    let gene = GBuilder::new("chr1", 100, 600)
+        .strand(Strand::Forward)
+        .id("gene01")
+        .add_transcript_coords("trx01", TranscriptCoords::new(100, 300)
+            .exons(vec![(100, 150), (200, 300)]))
+        .add_transcript_coords("trx02", TranscriptCoords::new(120, 400)
+            .exons(vec![(120, 400)]))
+        .add_transcript_coords("trx03", TranscriptCoords::new(450, 600)
+            .exons(vec![(450, 600)]))
+        .build()
+        .expect("a gene");
+
+    let lifted = chains.lift_gene(&gene);
+    assert!(!lifted.is_complete());
+    {
+        let lgene = lifted.gene().expect("a lifted gene");
+        assert_eq!(lgene.seq_name(), "chrA");
+        assert_eq!(lgene.id(), Some("gene01"));
+        assert_eq!((lgene.start(), lgene.end()), (200, 500));
+        assert_eq!(lgene.transcripts().keys().collect::<Vec<_>>(), vec!["trx01", "trx02"]);
+    }
+    let unmapped = lifted.unmapped_transcripts();
+    assert_eq!(unmapped.len(), 1);
+    assert_eq!(unmapped[0].0, "trx03");
+    assert!(matches!(unmapped[0].1, Unmapped::PartiallyDeleted));
+
+    let gene = GBuilder::new("chr3", 100, 200)
+        .add_transcript_coords("trx04", TranscriptCoords::new(100, 200)
+            .exons(vec![(100, 200)]))
+        .build()
+        .expect("a gene");
+    assert!(matches!(chains.lift_gene(&gene).into_gene(), Err(Unmapped::NoTranscripts)));
+}
@@ -0,0 +1,51 @@
+#[macro_use]
+extern crate matches;
+extern crate gte;
+
+mod common;
+
+use gte::{Error, Gene, GeneLocationWriter, GeneLocations, LookupError, Strand, TranscriptCoords};
+
+
+fn make_gene(seq_name: &str, id: &str, name: Option<&str>, start: u64, end: u64) -> Gene {
+    let transcript = (format!("{}.t1", id), TranscriptCoords::new(start, end).exon(start, end));
+    let mut builder = common::gene_builder(seq_name, id, Strand::Reverse, vec![transcript]);
+    if let Some(name) = name {
+        builder = builder.attribute("gene_name", name);
+    }
+    builder.build().expect("a gene")
+}
+
+#[test]
+fn lookup_roundtrip() {
+    let genes = vec![
+        make_gene("chrX", "ENSG01", Some("SHOX"), 100, 200),
+        make_gene("chrY", "ENSG01_PAR_Y", Some("SHOX"), 100, 200),
+        make_gene("chr1", "ENSG02", None, 1000, 2500),
+    ];
+    let mut writer = GeneLocationWriter::from_memory();
+    writer.symbol_attr("gene_name");
+    writer.write_genes(&genes).expect("written genes");
+    assert_eq!(writer.as_string(),
+               "#symbol\tchrom\tstart\tend\tstrand\n\
+                SHOX\tchrX\t100\t200\t-\n\
+                SHOX\tchrY\t100\t200\t-\n\
+                ENSG02\tchr1\t1000\t2500\t-\n");
+
+    let locations = GeneLocations::from_reader(writer.as_string().as_bytes())
+        .expect("gene locations");
+    assert_eq!(locations.symbols(), vec!["SHOX", "ENSG02"]);
+    let shox = locations.get("SHOX");
+    assert_eq!(shox.iter().map(|loc| loc.seq_name()).collect::<Vec<_>>(), vec!["chrX", "chrY"]);
+    assert_eq!((shox[0].start(), shox[0].end()), (100, 200));
+    assert_eq!(shox[0].strand(), &Strand::Reverse);
+    assert!(locations.get("TP53").is_empty());
+}
+
+#[test]
+fn lookup_invalid() {
+    let err = GeneLocations::from_reader("#header\nTP53\tchr17\t10\t5\t-\n".as_bytes())
+        .unwrap_err();
+    assert!(matches!(err, Error::Lookup(LookupError::InvalidRecord(2))));
+    assert!(GeneLocations::from_reader("TP53\tchr17\t10\t20\n".as_bytes()).is_err());
+}
@@ -0,0 +1,58 @@
+extern crate gte;
+
+use gte::{Genome, Strand, TBuilder, Transcript};
+
+
+static FASTA: &'static str = ">chr1
+GGATGAAACCGTTTTTTTAGCTAAGGGGGGGGGGGGGGGG
+>chr2
+aaccttagggTTTCATCC
+";
+
+fn make_transcript(seq_name: &str, strand: Strand, exons: Vec<(u64, u64)>) -> Transcript {
+    let (start, end) = (exons[0].0, exons[exons.len() - 1].1);
+    TBuilder::new(seq_name, start, end)
+        .strand(strand)
+        .id("trx01")
+        .coords(exons, None)
+        .build()
+        .expect("a transcript")
+}
+
+#[test]
+fn find_orfs_spliced() {
+    let genome = Genome::from_reader(FASTA.as_bytes()).expect("a genome");
+    let exons = vec![(0, 10), (20, 40)];
+    let trx = make_transcript("chr1", Strand::Forward, exons.clone());
+    assert_eq!(trx.spliced_seq(&genome),
+               Some(b"GGATGAAACCCTAAGGGGGGGGGGGGGGGG".to_vec()));
+
+    let orfs = trx.find_orfs(&genome, 9).expect("ORFs");
+    assert_eq!(orfs.len(), 1);
+    assert_eq!((orfs[0].tx_start(), orfs[0].tx_end()), (2, 14));
+    assert_eq!(orfs[0].coding_len(), 9);
+    assert_eq!(orfs[0].coding_coord(), (2, 24));
+    assert!(trx.find_orfs(&genome, 10).expect("ORFs").is_empty());
+
+    let coding = TBuilder::new("chr1", 0, 40)
+        .strand(Strand::Forward)
+        .coords(exons, Some(orfs[0].coding_coord()))
+        .coding_incl_stop(true)
+        .build()
+        .expect("a transcript");
+    assert_eq!(coding.coding_coord(true), Some((2, 24)));
+    assert_eq!(coding.coding_coord(false), Some((2, 21)));
+}
+
+#[test]
+fn find_orfs_reverse() {
+    let genome = Genome::from_reader(FASTA.as_bytes()).expect("a genome");
+    let trx = make_transcript("chr2", Strand::Reverse, vec![(0, 18)]);
+    let orfs = trx.find_orfs(&genome, 0).expect("ORFs");
+    assert_eq!(orfs.len(), 1);
+    assert_eq!((orfs[0].tx_start(), orfs[0].tx_end()), (2, 14));
+    assert_eq!(orfs[0].coding_coord(), (4, 16));
+
+    let outside = make_transcript("chr2", Strand::Reverse, vec![(0, 10), (15, 30)]);
+    assert_eq!(outside.find_orfs(&genome, 0), None);
+}
@@ -0,0 +1,79 @@
+#[macro_use]
+extern crate matches;
+extern crate gte;
+
+mod common;
+
+use gte::{AnnotationSet, Error, PanelError, PanelTargets, Strand, TranscriptCoords};
+
+use common::{gene_builder, make_gene};
+
+
+static TARGETS: &'static str = "track name=panel
+chr1\t90\t150
+chr1\t140\t180
+chr1\t320\t400\tamplicon01
+chr1\t1000\t1300
+";
+
+fn make_annotation() -> AnnotationSet {
+    let fwd = gene_builder("chr1", "gene01", Strand::Forward, vec![
+        ("trx01", TranscriptCoords::new(100, 600)
+            .exons(vec![(100, 200), (300, 400), (500, 600)])
+            .coding(150, 550)),
+        ("trx02", TranscriptCoords::new(100, 600).exons(vec![(100, 200), (500, 600)])),
+    ])
+        .transcript_coding_incl_stop(true)
+        .build()
+        .expect("a gene");
+    let rev = make_gene("chr1", "gene02", Strand::Reverse, vec![
+        ("trx03", TranscriptCoords::new(1000, 1300).exons(vec![(1000, 1100), (1200, 1300)])),
+    ]);
+    AnnotationSet::from_genes(vec![fwd, rev])
+}
+
+#[test]
+fn panel_targets_from_reader() {
+    let targets = PanelTargets::from_reader(TARGETS.as_bytes()).expect("targets");
+    assert_eq!(targets.len(), 3);
+    assert_eq!(targets.regions("chr1"), &[(90, 180), (320, 400), (1000, 1300)]);
+    assert_eq!(targets.num_bases(), 470);
+    assert!(targets.regions("chr2").is_empty());
+
+    let err = PanelTargets::from_reader("chr1\t100\t200\nchr1\tx\t10\n".as_bytes()).unwrap_err();
+    assert!(matches!(err, Error::Panel(PanelError::InvalidTarget(2))));
+}
+
+#[test]
+fn panel_coverage() {
+    let targets = PanelTargets::from_reader(TARGETS.as_bytes()).expect("targets");
+    let coverage = make_annotation().panel_coverage(&targets);
+    assert_eq!(coverage.len(), 2);
+
+    let gene = &coverage[0];
+    assert_eq!(gene.gene_id(), Some("gene01"));
+    assert_eq!(gene.exonic_bases(), (160, 300));
+    assert_eq!(gene.cds_bases(), Some((110, 200)));
+    assert_eq!(gene.cds_fraction(), Some(0.55));
+
+    let trx = &gene.transcripts()[0];
+    assert_eq!(trx.transcript_id(), Some("trx01"));
+    assert_eq!(trx.exonic_bases(), (160, 300));
+    assert_eq!(trx.cds_bases(), Some((110, 200)));
+    let exons = trx.exons().iter()
+        .map(|exon| (exon.number(), exon.covered(), exon.gaps().to_vec()))
+        .collect::<Vec<_>>();
+    assert_eq!(exons, vec![(1, 80, vec![(180, 200)]),
+                           (2, 80, vec![(300, 320)]),
+                           (3, 0, vec![(500, 600)])]);
+    let trx = &gene.transcripts()[1];
+    assert_eq!(trx.exonic_bases(), (80, 200));
+    assert_eq!(trx.cds_fraction(), None);
+
+    let gene = &coverage[1];
+    assert_eq!(gene.exonic_fraction(), 1.0);
+    assert_eq!(gene.cds_bases(), None);
+    let exons = gene.transcripts()[0].exons();
+    assert_eq!(exons.iter().map(|exon| exon.number()).collect::<Vec<_>>(), vec![2, 1]);
+    assert!(exons.iter().all(|exon| exon.gaps().is_empty()));
+}
@@ -0,0 +1,71 @@
+#[macro_use]
+extern crate matches;
+extern crate gte;
+
+mod common;
+
+use gte::{Error, GenomeAnnotation, PrimerError, PrimerPair, Strand, TranscriptCoords};
+
+use common::make_gene;
+
+
+fn make_annotation() -> GenomeAnnotation {
+    let fwd = make_gene("chr1", "gene01", Strand::Forward, vec![
+        ("trx01", TranscriptCoords::new(100, 600).exons(vec![(100, 200), (300, 400), (500, 600)])),
+        ("trx02", TranscriptCoords::new(100, 600).exons(vec![(100, 200), (500, 600)])),
+    ]);
+    let rev = make_gene("chr1", "gene02", Strand::Reverse, vec![
+        ("trx03", TranscriptCoords::new(1000, 1300).exons(vec![(1000, 1100), (1200, 1300)])),
+    ]);
+    GenomeAnnotation::from_genes(vec![fwd, rev])
+}
+
+fn summarize(annotation: &GenomeAnnotation, primers: &PrimerPair)
+    -> Vec<(String, u64, bool, bool)>
+{
+    let mut products = annotation.place_primers(primers).into_iter()
+        .map(|amp| (amp.transcript_id().unwrap_or("").to_owned(), amp.length(),
+                    amp.left_spans_junction(), amp.right_spans_junction()))
+        .collect::<Vec<_>>();
+    products.sort();
+    products
+}
+
+#[test]
+fn place_primers() {
+    let annotation = make_annotation();
+
+    let primers = PrimerPair::new("chr1", (150, 170), (520, 540)).expect("primers");
+    assert_eq!(primers.span(), (150, 540));
+    assert_eq!(summarize(&annotation, &primers),
+               vec![("trx01".to_owned(), 190, false, false),
+                    ("trx02".to_owned(), 90, false, false)]);
+
+    let primers = PrimerPair::from_blocks("chr1", vec![(190, 200), (300, 310)], vec![(520, 540)])
+        .expect("primers");
+    assert_eq!(summarize(&annotation, &primers), vec![("trx01".to_owned(), 150, true, false)]);
+
+    let primers = PrimerPair::new("chr1", (1050, 1070), (1250, 1270)).expect("primers");
+    let products = annotation.place_primers(&primers);
+    assert_eq!(products.len(), 1);
+    assert_eq!(products[0].gene_id(), Some("gene02"));
+    assert_eq!(products[0].length(), 120);
+    assert!(!products[0].spans_junction());
+
+    // intronic and other-sequence primers do not amplify anything
+    let primers = PrimerPair::new("chr1", (150, 170), (250, 270)).expect("primers");
+    assert!(annotation.place_primers(&primers).is_empty());
+    let primers = PrimerPair::new("chr2", (150, 170), (520, 540)).expect("primers");
+    assert!(annotation.place_primers(&primers).is_empty());
+}
+
+#[test]
+fn primer_pair_invalid() {
+    let err = PrimerPair::new("chr1", (200, 220), (150, 170)).unwrap_err();
+    assert!(matches!(err, Error::Primer(PrimerError::OverlappingPrimers(220, 150))));
+    let err = PrimerPair::from_blocks("chr1", vec![], vec![(150, 170)]).unwrap_err();
+    assert!(matches!(err, Error::Primer(PrimerError::EmptyPrimer)));
+    let err = PrimerPair::from_blocks("chr1", vec![(10, 20), (15, 30)], vec![(150, 170)])
+        .unwrap_err();
+    assert!(matches!(err, Error::Primer(PrimerError::InvalidBlock(15, 30))));
+}
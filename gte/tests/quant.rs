@@ -0,0 +1,84 @@
+extern crate gte;
+
+mod common;
+
+use gte::{AnnotationSet, QuantReader, QuantRecord, Strand, TranscriptCoords};
+
+use common::make_gene;
+
+
+static SALMON_QUANT: &'static str = "\
+Name\tLength\tEffectiveLength\tTPM\tNumReads
+trx01|gene01|\t1000\t800.0\t12.5\t100
+trx02\t500\t300.0\t0.5\t2
+trx03\t500\t300.0\t3.0\t10
+";
+
+static KALLISTO_QUANT: &'static str = "\
+target_id\tlength\teff_length\test_counts\ttpm
+trx01\t1000\t800.0\t100\t12.5
+";
+
+
+fn make_annotation() -> AnnotationSet {
+    let gene1 = make_gene("chr1", "gene01", Strand::Forward, vec![
+        ("trx01", TranscriptCoords::new(100, 1000).exons(vec![(100, 300), (700, 1000)])),
+        ("trx02", TranscriptCoords::new(50, 1200).exons(vec![(50, 300), (700, 1200)])),
+    ]);
+    let gene2 = make_gene("chr1", "gene02", Strand::Reverse, vec![
+        ("trx04", TranscriptCoords::new(5000, 6000).exon(5000, 6000)),
+    ]);
+    AnnotationSet::from_genes(vec![gene1, gene2])
+}
+
+#[test]
+fn quant_reader_salmon() {
+    let mut reader = QuantReader::from_reader(SALMON_QUANT.as_bytes());
+    let records = reader.records_stream()
+        .collect::<Result<Vec<QuantRecord>, _>>()
+        .expect("records");
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[0].transcript_id(), "trx01");
+    assert_eq!(records[0].tpm(), 12.5);
+    assert_eq!(records[0].num_reads(), 100.0);
+}
+
+#[test]
+fn quant_reader_kallisto() {
+    let mut reader = QuantReader::from_reader(KALLISTO_QUANT.as_bytes());
+    let quant = reader.quantification().expect("a quantification");
+    assert_eq!(quant.len(), 1);
+    assert_eq!(quant.tpm("trx01"), Some(12.5));
+    assert_eq!(quant.tpm("trx02"), None);
+}
+
+#[test]
+fn quant_reader_invalid() {
+    let mut reader = QuantReader::from_reader("Name\tLength\n".as_bytes());
+    assert!(reader.quantification().is_err());
+    let mut reader = QuantReader::from_reader(
+        "Name\tLength\tEffectiveLength\tTPM\tNumReads\ntrx01\t10\t5\tfoo\t1\n".as_bytes());
+    assert!(reader.quantification().is_err());
+    let mut reader = QuantReader::from_reader("".as_bytes());
+    assert!(reader.quantification().is_err());
+}
+
+#[test]
+fn filter_by_expression_basic() {
+    let mut reader = QuantReader::from_reader(SALMON_QUANT.as_bytes());
+    let quant = reader.quantification().expect("a quantification");
+    let filtered = make_annotation().filter_by_expression(&quant, 1.0).expect("an annotation");
+    assert_eq!(filtered.len(), 1);
+    let gene = &filtered.genes()[0];
+    assert_eq!(gene.id(), Some("gene01"));
+    assert_eq!((gene.start(), gene.end()), (100, 1000));
+    assert_eq!(gene.transcripts().keys().collect::<Vec<&String>>(), vec!["trx01"]);
+}
+
+#[test]
+fn filter_by_expression_none() {
+    let mut reader = QuantReader::from_reader(SALMON_QUANT.as_bytes());
+    let quant = reader.quantification().expect("a quantification");
+    let filtered = make_annotation().filter_by_expression(&quant, 100.0).expect("an annotation");
+    assert!(filtered.is_empty());
+}
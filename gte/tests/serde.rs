@@ -0,0 +1,74 @@
+#![cfg(feature = "serde")]
+extern crate gte;
+extern crate serde_json;
+
+mod common;
+
+use gte::{ExonFeatureKind, GBuilder, Gene, Strand, TranscriptCoords};
+
+
+fn make_gene() -> Gene {
+    common::gene_builder("chr1", "gene01", Strand::Reverse, vec![
+        ("trx02", TranscriptCoords::new(100, 1000)
+            .exons(vec![(100, 300), (500, 1000)])
+            .coding(200, 800)),
+        ("trx01", TranscriptCoords::new(500, 1000).exon(500, 1000)),
+    ])
+        .attribute("name", "ABC1")
+        .build()
+        .expect("a gene")
+}
+
+#[test]
+fn serde_gene_roundtrip() {
+    let gene = make_gene();
+    let json = serde_json::to_string(&gene).expect("a JSON string");
+    let parsed: Gene = serde_json::from_str(&json).expect("a gene");
+    assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
+
+    assert_eq!((parsed.seq_name(), parsed.start(), parsed.end()), ("chr1", 100, 1000));
+    assert_eq!(parsed.strand(), &Strand::Reverse);
+    assert_eq!(parsed.attributes().get("name").map(|v| v.as_str()), Some("ABC1"));
+    assert_eq!(parsed.transcripts().keys().collect::<Vec<&String>>(), vec!["trx02", "trx01"]);
+    let trx = &parsed.transcripts()["trx02"];
+    assert_eq!(trx.coding_coord(false), gene.transcripts()["trx02"].coding_coord(false));
+    assert_eq!(trx.exons()[0].features(), gene.transcripts()["trx02"].exons()[0].features());
+    assert_eq!(parsed.seq_name().as_ptr(), gene.seq_name().as_ptr());
+}
+
+#[test]
+fn serde_representation() {
+    let gene = make_gene();
+    let value = serde_json::to_value(&gene).expect("a JSON value");
+    assert_eq!(value["interval"], serde_json::json!([100, 1000]));
+    assert_eq!(value["strand"], serde_json::json!("-"));
+    assert_eq!(value["attributes"], serde_json::json!({"name": ["ABC1"]}));
+    assert_eq!(value["transcripts"][0][0], serde_json::json!("trx02"));
+    assert_eq!(serde_json::to_value(&ExonFeatureKind::CDS { frame: Some(1) }).unwrap(),
+               serde_json::json!({"CDS": {"frame": 1}}));
+}
+
+#[test]
+fn serde_invalid_interval() {
+    let json = serde_json::to_string(&make_gene()).unwrap()
+        .replacen("[100,1000]", "[1000,100]", 1);
+    assert!(serde_json::from_str::<Gene>(&json).is_err());
+}
+
+#[test]
+fn serde_attribute_order() {
+    let gene = GBuilder::new("chr1", 100, 1000)
+        .attribute("tag", "basic")
+        .attribute("level", "2")
+        .attribute("gene_name", "ABC1")
+        .attribute("tag", "CCDS")
+        .build()
+        .expect("a gene");
+    let json = serde_json::to_string(&gene).expect("a JSON string");
+    assert!(json.contains("\"attributes\":{\"tag\":[\"basic\",\"CCDS\"],\"level\":[\"2\"],\
+                           \"gene_name\":[\"ABC1\"]}"));
+
+    let parsed: Gene = serde_json::from_str(&json).expect("a gene");
+    assert_eq!(parsed.attributes().keys().collect::<Vec<&String>>(),
+               vec!["tag", "level", "gene_name"]);
+}
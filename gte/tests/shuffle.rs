@@ -0,0 +1,138 @@
+extern crate gte;
+
+mod common;
+
+use gte::{clamp_to_contig, expand_window, AnnotationSet, Assembly, BoundsViolationKind,
+          IntervalSampler, Strand};
+
+use common::spliced_gene;
+
+
+fn make_assembly() -> Assembly {
+    Assembly::from_reader("chr1\t1000\n\nchr2\t500\textra\n".as_bytes()).expect("an assembly")
+}
+
+fn make_annotation() -> AnnotationSet {
+    AnnotationSet::from_genes(vec![
+        spliced_gene("chr1", "gene01", Strand::Forward, vec![(100, 200), (300, 400)]),
+        spliced_gene("chr1", "gene02", Strand::Forward, vec![(150, 250), (800, 1000)]),
+    ])
+}
+
+#[test]
+fn assembly_from_reader() {
+    let assembly = make_assembly();
+    assert_eq!(assembly.len(), 2);
+    assert_eq!(assembly.seq_len("chr1"), Some(1000));
+    assert_eq!(assembly.seq_len("chr2"), Some(500));
+    assert_eq!(assembly.seq_len("chr3"), None);
+    assert_eq!(assembly.iter().map(|(k, _)| k.as_str()).collect::<Vec<_>>(),
+               vec!["chr1", "chr2"]);
+}
+
+#[test]
+fn assembly_from_reader_invalid() {
+    assert!(Assembly::from_reader("chr1\tabc\n".as_bytes()).is_err());
+    assert!(Assembly::from_reader("chr1\t10\nchr1\t20\n".as_bytes()).is_err());
+}
+
+#[test]
+fn assembly_check_bounds() {
+    let assembly = make_assembly();
+    let genes = vec![
+        spliced_gene("chr1", "gene01", Strand::Forward, vec![(100, 200), (300, 1000)]),
+        spliced_gene("chr2", "gene02", Strand::Forward, vec![(100, 200), (300, 501)]),
+        spliced_gene("chrUn", "gene03", Strand::Forward, vec![(100, 200)]),
+    ];
+    let violations = assembly.check_bounds(&genes);
+    assert_eq!(violations.iter().map(|v| v.to_string()).collect::<Vec<_>>(), vec![
+        "gene gene02 chr2:100-501: exceeds sequence length 500",
+        "gene gene02 transcript gene02.t1 chr2:100-501: exceeds sequence length 500",
+        "gene gene02 transcript gene02.t1 exon 2 chr2:300-501: exceeds sequence length 500",
+        "gene gene03 chrUn:100-200: unknown sequence",
+    ]);
+    assert_eq!(violations[2].exon_num(), Some(2));
+    assert_eq!(violations[3].kind(), &BoundsViolationKind::UnknownSeqName);
+    assert!(assembly.check_bounds(make_annotation().genes()).is_empty());
+}
+
+#[test]
+fn assembly_clamp_and_expand() {
+    let assembly = make_assembly();
+    assert_eq!(clamp_to_contig("chr2", (400, 600), &assembly), (400, 500));
+    assert_eq!(clamp_to_contig("chr2", (550, 600), &assembly), (500, 500));
+    assert_eq!(clamp_to_contig("chrUn", (550, 600), &assembly), (550, 600));
+
+    let fwd = Strand::Forward;
+    let rev = Strand::Reverse;
+    assert_eq!(expand_window("chr2", (200, 300), 100, 20, &fwd, None), (100, 320));
+    assert_eq!(expand_window("chr2", (200, 300), 100, 20, &rev, None), (180, 400));
+    assert_eq!(expand_window("chr2", (50, 450), 100, 20, &fwd, Some(&assembly)), (0, 470));
+    assert_eq!(expand_window("chr2", (50, 450), 100, 20, &rev, Some(&assembly)), (30, 500));
+}
+
+#[test]
+fn annotation_collapsed_exons() {
+    let exons = make_annotation().collapsed_exons();
+    assert_eq!(exons.len(), 1);
+    assert_eq!(exons["chr1"], vec![(100, 250), (300, 400), (800, 1000)]);
+}
+
+#[test]
+fn sample_matches_queries() {
+    let assembly = make_assembly();
+    let queries = vec![("chr1", 10, 60), ("chr2", 0, 500), ("chr1", 5, 5)];
+    let mut sampler = IntervalSampler::new(&assembly);
+    let sampled = sampler.seed(7).sample(queries.clone()).expect("sampled intervals");
+    assert_eq!(sampled.len(), 3);
+    for (&(qseq, qstart, qend), &(ref sseq, sstart, send)) in queries.iter().zip(sampled.iter()) {
+        assert_eq!(qseq, sseq.as_str());
+        assert_eq!(qend - qstart, send - sstart);
+        assert!(send <= assembly.seq_len(qseq).unwrap());
+    }
+    assert_eq!((sampled[1].1, sampled[1].2), (0, 500));
+}
+
+#[test]
+fn sample_same_seed() {
+    let assembly = make_assembly();
+    let queries = vec![("chr1", 10, 60), ("chr2", 100, 120)];
+    let sampled1 = IntervalSampler::new(&assembly).seed(42).sample(queries.clone()).unwrap();
+    let sampled2 = IntervalSampler::new(&assembly).seed(42).sample(queries).unwrap();
+    assert_eq!(sampled1, sampled2);
+}
+
+#[test]
+fn sample_avoid_exons() {
+    let assembly = make_assembly();
+    let annotation = make_annotation();
+    let mut sampler = IntervalSampler::new(&assembly);
+    let _ = sampler.avoid_exons(&annotation);
+    for _ in 0..200 {
+        let (start, end) = sampler.sample_interval("chr1", 40).expect("an interval");
+        assert!(end <= 100 || (start >= 250 && end <= 300) || (start >= 400 && end <= 800));
+    }
+    assert!(sampler.sample_interval("chr1", 401).is_err());
+}
+
+#[test]
+fn sample_restrict_to_exons() {
+    let assembly = make_assembly();
+    let annotation = make_annotation();
+    let mut sampler = IntervalSampler::new(&assembly);
+    let _ = sampler.restrict_to_exons(&annotation);
+    for _ in 0..200 {
+        let (start, end) = sampler.sample_interval("chr1", 120).expect("an interval");
+        assert!((start >= 100 && end <= 250) || (start >= 800 && end <= 1000));
+    }
+    assert!(sampler.sample_interval("chr2", 10).is_err());
+}
+
+#[test]
+fn sample_invalid_queries() {
+    let assembly = make_assembly();
+    let mut sampler = IntervalSampler::new(&assembly);
+    assert!(sampler.sample(vec![("chr3", 0, 10)]).is_err());
+    assert!(sampler.sample(vec![("chr1", 10, 0)]).is_err());
+    assert!(sampler.sample(vec![("chr1", 0, 1001)]).is_err());
+}
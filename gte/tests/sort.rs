@@ -0,0 +1,65 @@
+extern crate gte;
+
+use std::cmp::Ordering;
+
+use gte::{Strand, TBuilder, Transcript};
+use gte::sort::{self, ChromOrder};
+use Strand::*;
+
+
+fn make_transcript(seq_name: &str, start: u64, end: u64, strand: Strand) -> Transcript {
+    TBuilder::new(seq_name, start, end)
+        .strand(strand)
+        .coords(vec![(start, end)], None)
+        .build()
+        .expect("a transcript")
+}
+
+fn positions(transcripts: &[Transcript]) -> Vec<(&str, u64, u64, Strand)> {
+    transcripts.iter()
+        .map(|trx| (trx.seq_name(), trx.start(), trx.end(), *trx.strand()))
+        .collect()
+}
+
+fn unsorted() -> Vec<Transcript> {
+    vec![make_transcript("chrM", 10, 20, Forward),
+         make_transcript("chr10", 100, 200, Forward),
+         make_transcript("chr2", 100, 300, Reverse),
+         make_transcript("chrY", 50, 60, Forward),
+         make_transcript("chr2", 100, 300, Forward),
+         make_transcript("chrX", 50, 60, Forward),
+         make_transcript("chr2", 100, 200, Forward),
+         make_transcript("GL000220.1", 5, 10, Forward),
+         make_transcript("chr1", 500, 600, Forward)]
+}
+
+#[test]
+fn by_position_karyotypic() {
+    let mut transcripts = unsorted();
+    sort::by_position(&mut transcripts);
+    assert_eq!(positions(&transcripts),
+               vec![("chr1", 500, 600, Forward), ("chr2", 100, 200, Forward),
+                    ("chr2", 100, 300, Forward), ("chr2", 100, 300, Reverse),
+                    ("chr10", 100, 200, Forward), ("chrX", 50, 60, Forward),
+                    ("chrY", 50, 60, Forward), ("chrM", 10, 20, Forward),
+                    ("GL000220.1", 5, 10, Forward)]);
+}
+
+#[test]
+fn sorted_lexicographic() {
+    let transcripts = sort::sorted(unsorted(), ChromOrder::Lexicographic).collect::<Vec<_>>();
+    assert_eq!(positions(&transcripts),
+               vec![("GL000220.1", 5, 10, Forward), ("chr1", 500, 600, Forward),
+                    ("chr10", 100, 200, Forward), ("chr2", 100, 200, Forward),
+                    ("chr2", 100, 300, Forward), ("chr2", 100, 300, Reverse),
+                    ("chrM", 10, 20, Forward), ("chrX", 50, 60, Forward),
+                    ("chrY", 50, 60, Forward)]);
+}
+
+#[test]
+fn karyotypic_seq_names() {
+    let order = ChromOrder::Karyotypic;
+    assert_eq!(order.cmp_seq_names("9", "chr10"), Ordering::Less);
+    assert_eq!(order.cmp_seq_names("chrX", "Y"), Ordering::Less);
+    assert_eq!(order.cmp_seq_names("MT", "chrUn"), Ordering::Less);
+}
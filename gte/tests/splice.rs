@@ -0,0 +1,126 @@
+#[macro_use]
+extern crate matches;
+extern crate gte;
+
+mod common;
+
+use gte::{Error, Gene, Genome, GenomeError, SpliceError, SpliceSiteExtractor, SpliceSiteKind,
+          SpliceSiteWriter, Strand, TranscriptCoords};
+
+use common::make_gene;
+
+
+static FASTA: &'static str = ">chr1 test sequence
+ACGTTGCAAGGCTTACGATCGGTAAGCTAGCAGGCATC
+CTTGAACCTGCTTACCGTAGCAT
+
+>chrM
+ACGT
+";
+
+fn make_genes() -> Vec<Gene> {
+    let fwd = make_gene("chr1", "gene01", Strand::Forward, vec![
+        ("trx01", TranscriptCoords::new(10, 40).exons(vec![(10, 20), (30, 40)])),
+        ("trx02", TranscriptCoords::new(12, 40).exons(vec![(12, 20), (30, 40)])),
+    ]);
+    let rev = make_gene("chr1", "gene02", Strand::Reverse, vec![
+        ("trx03", TranscriptCoords::new(40, 58).exons(vec![(40, 45), (50, 58)])),
+    ]);
+    vec![fwd, rev]
+}
+
+#[test]
+fn genome_from_reader() {
+    let genome = Genome::from_reader(FASTA.as_bytes()).expect("a genome");
+    assert_eq!(genome.len(), 2);
+    assert_eq!(genome.seq("chr1").map(|seq| seq.len()), Some(61));
+    assert_eq!(genome.subseq("chrM", 1, 3), Some(&b"CG"[..]));
+    assert_eq!(genome.subseq("chrM", 1, 5), None);
+    assert_eq!(genome.stranded_subseq("chrM", 0, 3, &Strand::Reverse), Some(b"CGT".to_vec()));
+
+    let err = Genome::from_reader(&b"ACGT\n>chr1\nACGT\n"[..]).unwrap_err();
+    assert!(matches!(err, Error::Genome(GenomeError::MissingHeader(1))));
+    let err = Genome::from_reader(&b">chr1\nACGT\n>chr1\nACGT\n"[..]).unwrap_err();
+    assert!(matches!(err, Error::Genome(GenomeError::DuplicateSeqName(3))));
+}
+
+fn push_u32(buf: &mut Vec<u8>, value: u32, big_endian: bool) {
+    let bytes = [value as u8, (value >> 8) as u8, (value >> 16) as u8, (value >> 24) as u8];
+    if big_endian {
+        buf.extend(bytes.iter().rev());
+    } else {
+        buf.extend(bytes.iter());
+    }
+}
+
+fn make_2bit(big_endian: bool) -> Vec<u8> {
+    let mut buf = Vec::new();
+    for &value in [0x1A41_2743, 0, 1, 0].iter() {
+        push_u32(&mut buf, value, big_endian);
+    }
+    buf.push(4);
+    buf.extend(b"chr1");
+    push_u32(&mut buf, 25, big_endian);
+    // ACGTNNacgT: one N block at 4-6 and one mask block at 6-9.
+    for &value in [10, 1, 4, 2, 1, 6, 3, 0].iter() {
+        push_u32(&mut buf, value, big_endian);
+    }
+    buf.extend(&[0x9C, 0x09, 0xC0]);
+    buf
+}
+
+#[test]
+fn genome_from_2bit_reader() {
+    for &big_endian in [false, true].iter() {
+        let buf = make_2bit(big_endian);
+        let genome = Genome::from_2bit_reader(buf.as_slice()).expect("a genome");
+        assert_eq!(genome.len(), 1);
+        assert_eq!(genome.seq("chr1"), Some(&b"ACGTNNacgT"[..]));
+        assert_eq!(genome.stranded_subseq("chr1", 5, 8, &Strand::Reverse),
+                   Some(b"gtN".to_vec()));
+    }
+
+    let buf = make_2bit(false);
+    let err = Genome::from_2bit_reader(&buf[..buf.len() - 1]).unwrap_err();
+    assert!(matches!(err, Error::Genome(GenomeError::InvalidTwoBit(_))));
+    let err = Genome::from_2bit_reader(FASTA.as_bytes()).unwrap_err();
+    assert!(matches!(err, Error::Genome(GenomeError::InvalidTwoBit(_))));
+}
+
+#[test]
+fn splice_site_windows() {
+    let genome = Genome::from_reader(FASTA.as_bytes()).expect("a genome");
+    let genes = make_genes();
+    let windows = SpliceSiteExtractor::new(3).extract(&genes, &genome).expect("windows");
+
+    let summary = windows.iter()
+        .map(|w| (w.position(), w.kind(), String::from_utf8_lossy(w.sequence()).into_owned()))
+        .collect::<Vec<_>>();
+    assert_eq!(summary, vec![
+        (20, SpliceSiteKind::Donor, "ATCGGT".to_owned()),
+        (30, SpliceSiteKind::Acceptor, "TAGCAG".to_owned()),
+        (45, SpliceSiteKind::Acceptor, "CAGGTT".to_owned()),
+        (50, SpliceSiteKind::Donor, "GTAAGC".to_owned()),
+    ]);
+    assert_eq!(windows[0].transcript_ids(), &["trx01".to_owned(), "trx02".to_owned()]);
+    assert_eq!(windows[0].gene_ids(), &["gene01".to_owned()]);
+    assert_eq!(windows[2].strand(), &Strand::Reverse);
+
+    // windows past the sequence ends are skipped
+    let windows = SpliceSiteExtractor::new(25).extract(&genes, &genome).expect("windows");
+    assert_eq!(windows.iter().map(|w| w.position()).collect::<Vec<u64>>(), vec![30]);
+
+    let mut writer = SpliceSiteWriter::from_memory();
+    writer.write_windows(&windows).expect("splice site lines");
+    let lines = writer.as_string().lines().collect::<Vec<&str>>();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0], "#seq_name\tposition\tstrand\tlabel\tgene_ids\ttranscript_ids\tsequence");
+    assert!(lines[1].starts_with("chr1\t30\t+\tacceptor\tgene01\ttrx01,trx02\t"));
+}
+
+#[test]
+fn splice_site_missing_sequence() {
+    let genome = Genome::from_reader(&b">chrM\nACGT\n"[..]).expect("a genome");
+    let err = SpliceSiteExtractor::new(3).extract(&make_genes(), &genome).unwrap_err();
+    assert!(matches!(err, Error::Splice(SpliceError::MissingSequence(_))));
+}
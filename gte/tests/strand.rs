@@ -0,0 +1,39 @@
+extern crate gte;
+
+use gte::{Strand, StrandExt};
+use Strand::*;
+
+
+#[test]
+fn strand_from_to_i8() {
+    assert_eq!(Strand::from_i8(1), Forward);
+    assert_eq!(Strand::from_i8(42), Forward);
+    assert_eq!(Strand::from_i8(-1), Reverse);
+    assert_eq!(Strand::from_i8(0), Unknown);
+    assert_eq!(Forward.to_i8(), 1);
+    assert_eq!(Reverse.to_i8(), -1);
+    assert_eq!(Unknown.to_i8(), 0);
+}
+
+#[test]
+fn strand_to_char() {
+    assert_eq!(Forward.to_char(), '+');
+    assert_eq!(Reverse.to_char(), '-');
+    assert_eq!(Unknown.to_char(), '.');
+}
+
+#[test]
+fn strand_opposite() {
+    assert_eq!(Forward.opposite(), Reverse);
+    assert_eq!(Reverse.opposite(), Forward);
+    assert_eq!(Unknown.opposite(), Unknown);
+}
+
+#[test]
+fn strand_is_same() {
+    assert!(Forward.is_same(&Forward));
+    assert!(Reverse.is_same(&Reverse));
+    assert!(!Forward.is_same(&Reverse));
+    assert!(!Unknown.is_same(&Unknown));
+    assert!(!Unknown.is_same(&Forward));
+}
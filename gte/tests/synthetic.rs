@@ -0,0 +1,70 @@
+extern crate gte;
+
+use std::cmp::{max, min};
+
+use gte::{AnnotationGenerator, Gene, Strand};
+
+
+fn gene_coords(genes: &[Gene]) -> Vec<(String, u64, u64, usize)> {
+    genes.iter()
+        .map(|g| (g.id().unwrap().to_owned(), g.start(), g.end(), g.transcripts().len()))
+        .collect()
+}
+
+#[test]
+fn annotation_generator_deterministic() {
+    let mut generator = AnnotationGenerator::new();
+    let _ = generator.seed(42).num_seqs(3).genes_per_seq(4);
+    let genes = generator.generate();
+    assert_eq!(genes.len(), 12);
+    assert_eq!(gene_coords(&genes), gene_coords(&generator.generate()));
+
+    let _ = generator.seed(43);
+    assert!(gene_coords(&genes) != gene_coords(&generator.generate()));
+}
+
+#[test]
+fn annotation_generator_well_formed() {
+    let mut generator = AnnotationGenerator::new();
+    let _ = generator.num_seqs(2).genes_per_seq(20).isoforms(2, 4).exons(3, 5)
+        .coding_ratio(1.0);
+    let (genes, assembly) = generator.generate_with_assembly();
+    assert_eq!(assembly.iter().map(|(name, _)| name.as_str()).collect::<Vec<&str>>(),
+               vec!["chr1", "chr2"]);
+
+    let mut prev_end = 0;
+    for gene in genes.iter() {
+        let seq_len = assembly.seq_len(gene.seq_name()).expect("a sequence length");
+        assert!(gene.end() < seq_len);
+        if gene.id() != Some(&format!("{}.g1", gene.seq_name())[..]) {
+            assert!(gene.start() > prev_end);
+        }
+        prev_end = gene.end();
+
+        let num_trxs = gene.transcripts().len();
+        assert!(num_trxs >= 2 && num_trxs <= 4);
+        for (tid, trx) in gene.transcripts().iter() {
+            assert!(tid.starts_with(gene.id().unwrap()));
+            assert_eq!((trx.start(), trx.end()), (gene.start(), gene.end()));
+            let (cstart, cend) = trx.coding_coord(false).expect("a coding region");
+            let coding_len = trx.exons().iter()
+                .filter(|exon| exon.start() < cend && exon.end() > cstart)
+                .map(|exon| min(exon.end(), cend) - max(exon.start(), cstart))
+                .fold(0, |acc, n| acc + n);
+            assert_eq!(coding_len % 3, 0, "{}", tid);
+        }
+    }
+}
+
+#[test]
+fn annotation_generator_strand_mix() {
+    let mut generator = AnnotationGenerator::new();
+    let _ = generator.genes_per_seq(50).reverse_ratio(0.0).coding_ratio(0.0);
+    let genes = generator.generate();
+    assert!(genes.iter().all(|g| g.strand() == &Strand::Forward));
+    assert!(genes.iter().all(|g| g.transcripts().values()
+                             .all(|trx| trx.coding_coord(false).is_none())));
+
+    let _ = generator.reverse_ratio(1.0);
+    assert!(generator.generate().iter().all(|g| g.strand() == &Strand::Reverse));
+}
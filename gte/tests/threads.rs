@@ -0,0 +1,49 @@
+#![cfg(feature = "threads")]
+#[macro_use]
+extern crate matches;
+extern crate gte;
+
+use gte::{spawn_reader, spawn_reader_with_capacity, Error, ThreadError};
+
+
+static REFFLAT: &'static str = concat!(env!("CARGO_MANIFEST_DIR"),
+                                       "/tests/data/mult_rows_mult_genes_with_cds.refFlat");
+static GTF: &'static str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/single_gene.gtf");
+static GZ: &'static str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/indexed.refFlat.gz");
+
+#[test]
+fn spawn_reader_genes() {
+    let (handle, receiver) = spawn_reader_with_capacity(REFFLAT, 0);
+    let genes = receiver.iter().collect::<gte::Result<Vec<_>>>().expect("genes");
+    handle.join().expect("a finished thread");
+    assert_eq!(genes.iter().map(|gene| gene.id().unwrap_or("")).collect::<Vec<_>>(),
+               vec!["TNFRSF14", "SMIM12"]);
+    assert_eq!(genes[1].transcripts().len(), 3);
+
+    let (handle, receiver) = spawn_reader(GTF);
+    let genes = receiver.iter().collect::<gte::Result<Vec<_>>>().expect("genes");
+    handle.join().expect("a finished thread");
+    assert_eq!(genes.len(), 1);
+    assert_eq!(genes[0].id(), Some("ENSG00000128645.13"));
+}
+
+#[test]
+fn spawn_reader_dropped_receiver() {
+    let (handle, receiver) = spawn_reader_with_capacity(REFFLAT, 0);
+    assert!(receiver.recv().expect("a gene").is_ok());
+    drop(receiver);
+    handle.join().expect("a finished thread");
+}
+
+#[test]
+fn spawn_reader_errors() {
+    let (handle, receiver) = spawn_reader(GZ);
+    let results = receiver.iter().collect::<Vec<_>>();
+    handle.join().expect("a finished thread");
+    assert_eq!(results.len(), 1);
+    assert!(matches!(results[0], Err(Error::Thread(ThreadError::UnknownFormat(_)))));
+
+    let (_, receiver) = spawn_reader("nonexistent.gtf");
+    let result = receiver.recv().expect("an error");
+    assert!(matches!(result, Err(Error::Thread(ThreadError::Io(_)))));
+}
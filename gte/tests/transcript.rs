@@ -2,9 +2,11 @@ extern crate bio;
 extern crate gte;
 extern crate multimap;
 
+use bio::utils::Interval;
 use multimap::MultiMap;
 
-use gte::{ExonFeatureKind, Strand, TBuilder, Transcript};
+use gte::{Assembly, Attributes, EBuilder, ExonFeature, ExonFeatureKind, Strand, TBuilder,
+          Transcript, TranscriptTag, UnknownStrandCoding, ValidationMode};
 use ExonFeatureKind::*;
 use Strand::*;
 
@@ -1403,3 +1405,692 @@ fn tbuilder_coords_unk_coding_incl_stop() {
         .build();
     assert!(btrx.is_err());
 }
+
+#[test]
+fn transcript_is_compatible_with() {
+    let trx = TBuilder::new("chrT", 100, 1000)
+        .coords(vec![(100, 300), (400, 500), (700, 1000)], None)
+        .build().unwrap();
+    assert!(trx.is_compatible_with(&[(250, 300), (400, 450)]));
+    assert!(trx.is_compatible_with(&[(250, 300), (400, 500), (700, 720)]));
+    assert!(trx.is_compatible_with(&[(410, 450)]));
+    assert!(trx.is_compatible_with(&[(250, 280), (280, 300), (400, 450)]));
+    // skipped intron
+    assert!(!trx.is_compatible_with(&[(250, 300), (700, 750)]));
+    // mismatched splice site
+    assert!(!trx.is_compatible_with(&[(250, 300), (405, 450)]));
+    // retained intron
+    assert!(!trx.is_compatible_with(&[(280, 420)]));
+    // overhanging ends
+    assert!(!trx.is_compatible_with(&[(395, 450)]));
+    assert!(!trx.is_compatible_with(&[(250, 300), (400, 505)]));
+    assert!(!trx.is_compatible_with(&[]));
+    assert!(!trx.is_compatible_with(&[(400, 450), (250, 300)]));
+}
+
+#[test]
+fn transcript_is_compatible_with_tolerance() {
+    let trx = TBuilder::new("chrT", 100, 1000)
+        .coords(vec![(100, 300), (400, 500), (700, 1000)], None)
+        .build().unwrap();
+    assert!(trx.is_compatible_with_tolerance(&[(395, 450)], 5));
+    assert!(trx.is_compatible_with_tolerance(&[(250, 300), (400, 505)], 5));
+    assert!(trx.is_compatible_with_tolerance(&[(95, 300), (400, 500), (700, 1005)], 5));
+    assert!(!trx.is_compatible_with_tolerance(&[(250, 300), (400, 506)], 5));
+    assert!(!trx.is_compatible_with_tolerance(&[(280, 420)], 5));
+}
+
+#[test]
+fn transcript_id_or_default() {
+    let trx = TBuilder::new("chrT", 100, 300)
+        .coords(vec![(100, 300)], None)
+        .build().unwrap();
+    assert_eq!(trx.id_or_default(), "<unknown>");
+    assert_eq!(trx.gene_id_or_default(), "<unknown>");
+    assert_eq!(trx.exons()[0].transcript_id_or_default(), "<unknown>");
+    let trx = TBuilder::new("chrT", 100, 300)
+        .id("trx01")
+        .gene_id("gene01")
+        .coords(vec![(100, 300)], None)
+        .build().unwrap();
+    assert_eq!(trx.id_or_default(), "trx01");
+    assert_eq!(trx.gene_id_or_default(), "gene01");
+    assert_eq!(trx.exons()[0].gene_id_or_default(), "gene01");
+}
+
+#[test]
+fn transcript_subrange_exons() {
+    let (trx, fxs) = trx_fxs(100, 1000, Forward, vec![(100, 300), (400, 500), (700, 1000)],
+                             Some((250, 750)));
+    assert_eq!(fxs[1], vec![(400, 500, CDS { frame: Some(1) })]);
+
+    let sub = trx.subrange_exons(1..3).expect("a sub-transcript");
+    assert_eq!((sub.start(), sub.end()), (400, 1000));
+    assert_eq!(exon_coords(&sub), vec![(400, 500), (700, 1000)]);
+    let sub_fxs = exon_fxs_coords(&sub);
+    assert_eq!(sub_fxs[0], vec![(400, 500, CDS { frame: Some(1) })]);
+    assert_eq!(sub_fxs[1], vec![(700, 750, CDS { frame: Some(0) }),
+                                (750, 753, StopCodon { frame: Some(0) }),
+                                (753, 1000, UTR3)]);
+
+    let sub = trx.subrange_exons(0..1).expect("a sub-transcript");
+    assert_eq!((sub.start(), sub.end()), (100, 300));
+    assert_eq!(exon_fxs_coords(&sub), vec![fxs[0].clone()]);
+}
+
+#[test]
+fn transcript_subrange_exons_invalid() {
+    let (trx, _) = trx_fxs(100, 1000, Forward, vec![(100, 300), (400, 500), (700, 1000)], None);
+    assert!(trx.subrange_exons(1..1).is_err());
+    assert!(trx.subrange_exons(2..4).is_err());
+}
+
+#[test]
+fn transcript_protein_to_genomic() {
+    let (trx, _) = trx_fxs(100, 1000, Forward, vec![(100, 300), (400, 500), (700, 1000)],
+                           Some((250, 751)));
+    assert_eq!(trx.protein_to_genomic(0..1).unwrap(), vec![(250, 253)]);
+    assert_eq!(trx.protein_to_genomic(16..17).unwrap(), vec![(298, 300), (400, 401)]);
+    assert_eq!(trx.protein_to_genomic(10..60).unwrap(),
+               vec![(280, 300), (400, 500), (700, 730)]);
+    assert_eq!(trx.protein_to_genomic(66..67).unwrap(), vec![(748, 751)]);
+    assert!(trx.protein_to_genomic(66..68).is_err());
+    assert!(trx.protein_to_genomic(5..5).is_err());
+
+    let (trx, _) = trx_fxs(100, 1000, Reverse, vec![(100, 300), (400, 500), (700, 1000)],
+                           Some((249, 750)));
+    assert_eq!(trx.protein_to_genomic(0..1).unwrap(), vec![(747, 750)]);
+    assert_eq!(trx.protein_to_genomic(16..17).unwrap(), vec![(499, 500), (700, 702)]);
+    assert_eq!(trx.protein_to_genomic(66..67).unwrap(), vec![(249, 252)]);
+
+    let (trx, _) = trx_fxs(100, 1000, Forward, vec![(100, 1000)], None);
+    assert!(trx.protein_to_genomic(0..1).is_err());
+}
+
+#[test]
+fn transcript_protein_to_genomic_frame() {
+    let feat = |start, end, kind| ExonFeature::new(Interval::new(start..end).unwrap(), kind);
+    let exons = vec![
+        EBuilder::new("chrT", 100, 300).strand(Forward)
+            .features(vec![feat(100, 250, UTR5),
+                           feat(250, 253, StartCodon { frame: Some(1) }),
+                           feat(250, 300, CDS { frame: Some(1) })])
+            .build().unwrap(),
+        EBuilder::new("chrT", 400, 500).strand(Forward)
+            .features(vec![feat(400, 460, CDS { frame: Some(2) }),
+                           feat(460, 463, StopCodon { frame: Some(0) }),
+                           feat(463, 500, UTR3)])
+            .build().unwrap(),
+    ];
+    let trx = TBuilder::new("chrT", 100, 500).strand(Forward).exons(exons).build().unwrap();
+    assert_eq!(trx.protein_to_genomic(0..1).unwrap(), vec![(250, 251)]);
+    assert_eq!(trx.protein_to_genomic(1..2).unwrap(), vec![(251, 254)]);
+    assert_eq!(trx.protein_to_genomic(17..18).unwrap(), vec![(299, 300), (400, 402)]);
+
+    for pos in (250..300).chain(400..460) {
+        let (residue, _) = trx.genomic_to_protein(pos).expect("a residue");
+        let coords = trx.protein_to_genomic(residue..residue + 1).expect("residue coordinates");
+        assert!(coords.iter().any(|&(start, end)| pos >= start && pos < end),
+                "position {} not in {:?}", pos, coords);
+    }
+}
+
+#[test]
+fn transcript_protein_to_genomic_cds_5_incomplete() {
+    let feat = |start, end, kind| ExonFeature::new(Interval::new(start..end).unwrap(), kind);
+    let exons = vec![
+        EBuilder::new("chrT", 100, 300).strand(Forward)
+            .features(vec![feat(100, 300, CDS { frame: Some(2) })])
+            .build().unwrap(),
+        EBuilder::new("chrT", 400, 500).strand(Forward)
+            .features(vec![feat(400, 460, CDS { frame: Some(0) }),
+                           feat(460, 463, StopCodon { frame: Some(0) }),
+                           feat(463, 500, UTR3)])
+            .build().unwrap(),
+    ];
+    let trx = TBuilder::new("chrT", 100, 500)
+        .strand(Forward)
+        .exons(exons)
+        .cds_5_incomplete(true)
+        .build()
+        .unwrap();
+    assert!(trx.cds_5_incomplete());
+    assert_eq!(trx.genomic_to_protein(100), Some((0, 1)));
+    assert_eq!(trx.genomic_to_protein(102), Some((1, 0)));
+    assert_eq!(trx.protein_to_genomic(0..1).unwrap(), vec![(100, 102)]);
+    assert_eq!(trx.protein_to_genomic(1..2).unwrap(), vec![(102, 105)]);
+
+    for pos in (100..300).chain(400..460) {
+        let (residue, _) = trx.genomic_to_protein(pos).expect("a residue");
+        let coords = trx.protein_to_genomic(residue..residue + 1).expect("residue coordinates");
+        assert!(coords.iter().any(|&(start, end)| pos >= start && pos < end),
+                "position {} not in {:?}", pos, coords);
+    }
+}
+
+#[test]
+fn transcript_genomic_to_transcript() {
+    let exons = vec![(100, 300), (400, 500), (700, 1000)];
+    let (trx, _) = trx_fxs(100, 1000, Forward, exons.clone(), None);
+    assert_eq!(trx.genomic_to_transcript(100), Some(0));
+    assert_eq!(trx.genomic_to_transcript(299), Some(199));
+    assert_eq!(trx.genomic_to_transcript(400), Some(200));
+    assert_eq!(trx.genomic_to_transcript(999), Some(599));
+    assert_eq!(trx.genomic_to_transcript(300), None);
+    assert_eq!(trx.genomic_to_transcript(99), None);
+    assert_eq!(trx.genomic_to_transcript(1000), None);
+
+    let (trx, _) = trx_fxs(100, 1000, Reverse, exons, None);
+    assert_eq!(trx.genomic_to_transcript(999), Some(0));
+    assert_eq!(trx.genomic_to_transcript(400), Some(399));
+    assert_eq!(trx.genomic_to_transcript(100), Some(599));
+    assert_eq!(trx.genomic_to_transcript(650), None);
+}
+
+#[test]
+fn transcript_to_genomic_projection() {
+    let exons = vec![(100, 300), (400, 500), (700, 1000)];
+    let (trx, _) = trx_fxs(100, 1000, Forward, exons.clone(), None);
+    assert_eq!(trx.transcript_to_genomic(0), Some(100));
+    assert_eq!(trx.transcript_to_genomic(200), Some(400));
+    assert_eq!(trx.transcript_to_genomic(599), Some(999));
+    assert_eq!(trx.transcript_to_genomic(600), None);
+    assert_eq!(trx.project_interval(150, 350), vec![(250, 300), (400, 500), (700, 750)]);
+    assert_eq!(trx.project_interval(550, 700), vec![(950, 1000)]);
+    assert_eq!(trx.project_interval(600, 700), vec![]);
+    assert_eq!(trx.project_interval(10, 10), vec![]);
+
+    let (trx, _) = trx_fxs(100, 1000, Reverse, exons, None);
+    assert_eq!(trx.transcript_to_genomic(0), Some(999));
+    assert_eq!(trx.transcript_to_genomic(399), Some(400));
+    assert_eq!(trx.transcript_to_genomic(599), Some(100));
+    assert_eq!(trx.project_interval(250, 450), vec![(250, 300), (400, 500), (700, 750)]);
+    for offset in vec![0, 150, 300, 599] {
+        let pos = trx.transcript_to_genomic(offset).unwrap();
+        assert_eq!(trx.genomic_to_transcript(pos), Some(offset));
+    }
+}
+
+#[test]
+fn transcript_genomic_to_cds_and_protein() {
+    let exons = vec![(100, 300), (400, 500), (700, 1000)];
+    let (trx, _) = trx_fxs(100, 1000, Forward, exons.clone(), Some((250, 751)));
+    assert_eq!(trx.genomic_to_cds(250), Some(0));
+    assert_eq!(trx.genomic_to_cds(400), Some(50));
+    assert_eq!(trx.genomic_to_cds(753), Some(203));
+    assert_eq!(trx.genomic_to_cds(249), None);
+    assert_eq!(trx.genomic_to_cds(754), None);
+    assert_eq!(trx.genomic_to_cds(350), None);
+    assert_eq!(trx.genomic_to_protein(250), Some((0, 0)));
+    assert_eq!(trx.genomic_to_protein(400), Some((16, 2)));
+    assert_eq!(trx.genomic_to_protein(753), Some((67, 2)));
+    assert_eq!(trx.genomic_to_protein(350), None);
+
+    let (trx, _) = trx_fxs(100, 1000, Reverse, exons.clone(), Some((249, 750)));
+    assert_eq!(trx.genomic_to_cds(749), Some(0));
+    assert_eq!(trx.genomic_to_cds(499), Some(50));
+    assert_eq!(trx.genomic_to_cds(246), Some(203));
+    assert_eq!(trx.genomic_to_cds(750), None);
+    assert_eq!(trx.genomic_to_protein(747), Some((0, 2)));
+    assert_eq!(trx.genomic_to_protein(246), Some((67, 2)));
+
+    let (trx, _) = trx_fxs(100, 1000, Forward, exons, None);
+    assert_eq!(trx.genomic_to_cds(250), None);
+    assert_eq!(trx.genomic_to_protein(250), None);
+}
+
+#[test]
+fn transcript_is_nmd_candidate() {
+    let exons = vec![(100, 300), (400, 500), (700, 1000)];
+    let (trx, _) = trx_fxs(100, 1000, Forward, exons.clone(), Some((150, 210)));
+    assert!(trx.is_nmd_candidate());
+    let (trx, _) = trx_fxs(100, 1000, Forward, exons.clone(), Some((150, 460)));
+    assert!(!trx.is_nmd_candidate());
+    let (trx, _) = trx_fxs(100, 1000, Forward, exons.clone(), Some((250, 751)));
+    assert!(!trx.is_nmd_candidate());
+
+    let (trx, _) = trx_fxs(100, 1000, Reverse, exons.clone(), Some((790, 900)));
+    assert!(trx.is_nmd_candidate());
+    let (trx, _) = trx_fxs(100, 1000, Reverse, exons.clone(), Some((440, 900)));
+    assert!(!trx.is_nmd_candidate());
+
+    let (trx, _) = trx_fxs(100, 1000, Forward, exons, None);
+    assert!(!trx.is_nmd_candidate());
+    let (trx, _) = trx_fxs(100, 1000, Forward, vec![(100, 1000)], Some((150, 210)));
+    assert!(!trx.is_nmd_candidate());
+}
+
+#[test]
+fn transcript_extend() {
+    let mut assembly = Assembly::new();
+    assembly.add_seq("chrT", 1050);
+
+    let (mut trx, _) = trx_fxs(100, 1000, Forward, vec![(100, 300), (400, 500), (700, 1000)],
+                               Some((250, 751)));
+    trx.extend_5p(50, None);
+    trx.extend_3p(100, Some(&assembly));
+    assert_eq!((trx.start(), trx.end()), (50, 1050));
+    assert_eq!(exon_coords(&trx), vec![(50, 300), (400, 500), (700, 1050)]);
+    let fxs = exon_fxs_coords(&trx);
+    assert!(fxs[0].contains(&(50, 250, UTR5)));
+    assert!(fxs[2].contains(&(754, 1050, UTR3)));
+    assert_eq!(trx.coding_coord(false), Some((250, 751)));
+    trx.extend_5p(500, None);
+    assert_eq!(trx.start(), 0);
+
+    let (mut trx, _) = trx_fxs(100, 1000, Reverse, vec![(100, 1000)], Some((200, 800)));
+    trx.extend_5p(20, None);
+    trx.extend_3p(20, None);
+    assert_eq!((trx.start(), trx.end()), (80, 1020));
+    let fxs = exon_fxs_coords(&trx);
+    assert!(fxs[0].contains(&(80, 197, UTR3)));
+    assert!(fxs[0].contains(&(800, 1020, UTR5)));
+
+    let mut trx = TBuilder::new("chrT", 100, 500)
+        .strand(Forward)
+        .coords(vec![(100, 300), (400, 500)], Some((150, 500)))
+        .coding_incl_stop(true)
+        .build().unwrap();
+    trx.extend_3p(10, None);
+    let fxs = exon_fxs_coords(&trx);
+    assert!(fxs[1].contains(&(497, 500, StopCodon { frame: Some(0) })));
+    assert!(fxs[1].contains(&(500, 510, UTR3)));
+
+    let (mut trx, _) = trx_fxs(100, 1000, Forward, vec![(100, 300), (700, 1000)], None);
+    trx.extend_5p(10, None);
+    assert_eq!(exon_coords(&trx), vec![(90, 300), (700, 1000)]);
+    assert!(exon_fxs_coords(&trx)[0].is_empty());
+}
+
+#[test]
+fn transcript_trim_to_cds() {
+    let (mut trx, _) = trx_fxs(100, 1000, Forward, vec![(100, 300), (400, 500), (700, 1000)],
+                               Some((250, 751)));
+    trx.trim_to_cds().expect("a trimmed transcript");
+    assert_eq!((trx.start(), trx.end()), (250, 754));
+    assert_eq!(exon_coords(&trx), vec![(250, 300), (400, 500), (700, 754)]);
+    assert!(exon_fxs_coords(&trx).iter().flat_map(|fxs| fxs.iter())
+            .all(|fx| fx.2 != UTR5 && fx.2 != UTR3));
+    assert_eq!(trx.coding_coord(false), Some((250, 751)));
+
+    let (mut trx, _) = trx_fxs(100, 1000, Forward, vec![(100, 1000)], None);
+    assert!(trx.trim_to_cds().is_err());
+}
+
+#[test]
+fn transcript_fuse() {
+    let five = TBuilder::new("chrA", 100, 400)
+        .strand(Forward)
+        .id("trxA")
+        .gene_id("geneA")
+        .coords(vec![(100, 200), (300, 400)], Some((150, 349)))
+        .build().unwrap();
+    let three = TBuilder::new("chrB", 1000, 1300)
+        .strand(Reverse)
+        .id("trxB")
+        .coords(vec![(1000, 1100), (1200, 1300)], Some((1051, 1250)))
+        .build().unwrap();
+
+    let fusion = five.fuse(179, &three, 1219).unwrap();
+    assert_eq!(fusion.seq_name(), "trxA--trxB");
+    assert_eq!(fusion.id(), Some("trxA--trxB"));
+    assert_eq!(fusion.gene_id(), Some("geneA--<unknown>"));
+    assert_eq!(fusion.strand(), &Forward);
+    assert_eq!((fusion.start(), fusion.end()), (0, 300));
+    assert_eq!(exon_coords(&fusion), vec![(0, 100), (200, 300)]);
+    assert_eq!(fusion.coding_coord(true), Some((50, 252)));
+
+    // out of frame
+    let fusion = five.fuse(179, &three, 1220).unwrap();
+    assert_eq!((fusion.start(), fusion.end()), (0, 301));
+    assert_eq!(fusion.coding_coord(true), None);
+    // 5' breakpoint in the 5' UTR
+    let fusion = five.fuse(120, &three, 1219).unwrap();
+    assert_eq!(fusion.coding_coord(true), None);
+    // 5' breakpoint past the stop codon
+    let fusion = five.fuse(360, &three, 1219).unwrap();
+    assert_eq!(fusion.coding_coord(true), Some((50, 252)));
+    assert_eq!(exon_coords(&fusion), vec![(0, 100), (200, 281), (381, 481)]);
+
+    assert!(five.fuse(250, &three, 1219).is_err());
+    assert!(five.fuse(179, &three, 1150).is_err());
+}
+
+#[test]
+fn transcript_utrs() {
+    let (trx, _) = trx_fxs(100, 1000, Forward, vec![(100, 300), (400, 500), (700, 1000)],
+                           Some((450, 800)));
+    assert_eq!(trx.five_prime_utrs(), vec![(100, 300), (400, 450)]);
+    assert_eq!(trx.three_prime_utrs(), vec![(803, 1000)]);
+
+    let (trx, _) = trx_fxs(100, 1000, Reverse, vec![(100, 300), (400, 500), (700, 1000)],
+                           Some((200, 450)));
+    assert_eq!(trx.five_prime_utrs(), vec![(700, 1000), (450, 500)]);
+    assert_eq!(trx.three_prime_utrs(), vec![(100, 197)]);
+
+    let (trx, _) = trx_fxs(100, 1000, Unknown, vec![(100, 300), (400, 500), (700, 1000)],
+                           Some((150, 210)));
+    assert_eq!(trx.five_prime_utrs(), vec![(100, 150)]);
+    assert_eq!(trx.three_prime_utrs(), vec![(210, 300), (400, 500), (700, 1000)]);
+
+    let (trx, _) = trx_fxs(100, 1000, Forward, vec![(100, 300), (400, 1000)], None);
+    assert!(trx.five_prime_utrs().is_empty());
+    assert!(trx.three_prime_utrs().is_empty());
+}
+
+#[test]
+fn transcript_lens() {
+    let (trx, _) = trx_fxs(100, 1000, Forward, vec![(100, 300), (400, 500), (700, 1000)],
+                           Some((450, 800)));
+    assert_eq!(trx.spliced_len(), 600);
+    assert_eq!(trx.cds_len(false), 150);
+    assert_eq!(trx.cds_len(true), 153);
+    assert_eq!(trx.utr_lens(), (250, 197));
+
+    let (trx, _) = trx_fxs(100, 1000, Reverse, vec![(100, 300), (400, 500), (700, 1000)],
+                           Some((200, 450)));
+    assert_eq!(trx.spliced_len(), 600);
+    assert_eq!(trx.cds_len(true), 153);
+    assert_eq!(trx.utr_lens(), (350, 97));
+
+    let (trx, _) = trx_fxs(100, 1000, Forward, vec![(100, 300), (400, 1000)], None);
+    assert_eq!(trx.spliced_len(), 800);
+    assert_eq!(trx.cds_len(true), 0);
+    assert_eq!(trx.utr_lens(), (0, 0));
+}
+
+#[test]
+fn transcript_add_remove_exon() {
+    let mut trx = TBuilder::new("chrT", 100, 1000)
+        .strand(Forward)
+        .id("trx01")
+        .coords(vec![(100, 300), (700, 1000)], Some((200, 800)))
+        .build()
+        .expect("a transcript");
+    let (expected, expected_fxs) =
+        trx_fxs(100, 1000, Forward, vec![(100, 300), (400, 500), (700, 1000)], Some((200, 800)));
+
+    let exon = EBuilder::new("chrT", 400, 500).strand(Forward).build().expect("an exon");
+    trx.add_exon(exon, true).expect("an added exon");
+    assert_eq!(exon_coords(&trx), exon_coords(&expected));
+    assert_eq!(exon_fxs_coords(&trx), expected_fxs);
+    assert_eq!(trx.exons()[1].transcript_id(), Some("trx01"));
+
+    let exon = EBuilder::new("chrT", 50, 80).strand(Forward).build().expect("an exon");
+    trx.add_exon(exon, false).expect("an added exon");
+    assert_eq!((trx.start(), trx.end()), (50, 1000));
+    assert!(trx.exons()[0].features().is_empty());
+
+    let removed = trx.remove_exon(0, true).expect("a removed exon");
+    assert_eq!((removed.start(), removed.end()), (50, 80));
+    assert_eq!((trx.start(), trx.end()), (100, 1000));
+    assert_eq!(exon_fxs_coords(&trx), expected_fxs);
+    assert_eq!(trx.coding_coord(false), Some((200, 800)));
+
+    let overlapping = EBuilder::new("chrT", 250, 350).strand(Forward).build().expect("an exon");
+    assert!(trx.add_exon(overlapping, true).is_err());
+    let reverse = EBuilder::new("chrT", 500, 600).strand(Reverse).build().expect("an exon");
+    assert!(trx.add_exon(reverse, true).is_err());
+    assert!(trx.remove_exon(3, true).is_err());
+    assert_eq!(exon_coords(&trx), vec![(100, 300), (400, 500), (700, 1000)]);
+
+    let _ = trx.remove_exon(1, false).expect("a removed exon");
+    assert_eq!(exon_coords(&trx), vec![(100, 300), (700, 1000)]);
+    assert_eq!(exon_fxs_coords(&trx)[0], expected_fxs[0]);
+}
+
+#[test]
+fn tbuilder_incomplete_cds() {
+    let exons = vec![(100, 300), (400, 500), (700, 1000)];
+    let build = |coding, strand, cds_5_incomplete, cds_3_incomplete| {
+        TBuilder::new("chrT", 100, 1000)
+            .strand(strand)
+            .coords(exons.clone(), Some(coding))
+            .cds_5_incomplete(cds_5_incomplete)
+            .cds_3_incomplete(cds_3_incomplete)
+            .build()
+    };
+
+    assert!(build((150, 1000), Forward, false, false).is_err());
+    let trx = build((150, 1000), Forward, false, true).expect("a transcript");
+    assert!(trx.cds_3_incomplete() && !trx.cds_5_incomplete());
+    assert_eq!(trx.coding_coord(true), Some((150, 1000)));
+    assert_eq!(trx.coding_coord(false), Some((150, 1000)));
+    assert_eq!(exon_fxs_coords(&trx)[2], vec![(700, 1000, CDS { frame: Some(2) })]);
+
+    let trx = build((150, 210), Forward, false, true).expect("a transcript");
+    assert_eq!(exon_fxs_coords(&trx)[0], vec![(100, 150, UTR5),
+                                              (150, 153, StartCodon { frame: Some(0) }),
+                                              (150, 210, CDS { frame: Some(0) }),
+                                              (210, 300, UTR3)]);
+    assert_eq!(trx.coding_coord(true), Some((150, 210)));
+
+    let trx = build((150, 900), Reverse, true, false).expect("a transcript");
+    let fxs = exon_fxs_coords(&trx);
+    assert_eq!(fxs[0][1], (147, 150, StopCodon { frame: Some(0) }));
+    assert_eq!(fxs[2], vec![(700, 900, CDS { frame: Some(0) }), (900, 1000, UTR5)]);
+    assert_eq!(trx.coding_coord(true), Some((147, 900)));
+    assert_eq!(trx.coding_coord(false), Some((150, 900)));
+
+    let trx = build((100, 1000), Reverse, true, true).expect("a transcript");
+    assert!(exon_fxs_coords(&trx).iter()
+            .all(|fxs| fxs.iter().all(|fx| if let CDS { .. } = fx.2 { true } else { false })));
+    assert_eq!(trx.coding_coord(true), Some((100, 1000)));
+}
+
+#[test]
+fn tbuilder_validation_mode() {
+    let build = |mode| {
+        TBuilder::new("chrT", 150, 900)
+            .strand(Forward)
+            .coords(vec![(100, 300), (700, 1000)], None)
+            .validation(mode)
+            .build()
+    };
+    assert!(build(ValidationMode::Strict).is_err());
+    let trx = build(ValidationMode::Fix).expect("a transcript");
+    assert_eq!((trx.start(), trx.end()), (100, 1000));
+    let trx = build(ValidationMode::Ignore).expect("a transcript");
+    assert_eq!((trx.start(), trx.end()), (150, 900));
+    assert_eq!(trx.exons().len(), 2);
+}
+
+#[test]
+fn tbuilder_prebuilt_exons() {
+    let exon = |start, end, strand| {
+        EBuilder::new("chrT", start, end).strand(strand).build().expect("an exon")
+    };
+    let build = |exons| TBuilder::new("chrT", 100, 500).strand(Forward).exons(exons).build();
+
+    let trx = build(vec![exon(400, 500, Forward), exon(100, 200, Forward)])
+        .expect("a transcript");
+    assert_eq!(trx.exons().iter().map(|ex| (ex.start(), ex.end())).collect::<Vec<_>>(),
+               vec![(100, 200), (400, 500)]);
+
+    let err = |exons| build(exons).expect_err("an error").to_string();
+    assert!(err(vec![exon(100, 200, Reverse), exon(400, 500, Forward)])
+            .starts_with("exon sequence name or strand differs from transcript"));
+    assert!(err(vec![exon(100, 450, Forward), exon(400, 500, Forward)])
+            .starts_with("exon overlaps an existing exon of transcript"));
+    assert!(err(vec![exon(100, 200, Forward), exon(400, 450, Forward)])
+            .starts_with("first and/or last exon coordinates do not match"));
+}
+
+#[test]
+fn tbuilder_coords_with_ids() {
+    let trx = TBuilder::new("chrT", 100, 1000)
+        .strand(Reverse)
+        .id("TRX")
+        .coords_with_ids(vec![((700, 1000), "TRX:exon1"), ((100, 300), "TRX:exon3"),
+                              ((400, 500), "TRX:exon2")],
+                         Some((200, 800)))
+        .build()
+        .expect("a transcript");
+    assert_eq!(trx.exons().iter().map(|ex| (ex.start(), ex.id())).collect::<Vec<_>>(),
+               vec![(100, Some("TRX:exon3")), (400, Some("TRX:exon2")),
+                    (700, Some("TRX:exon1"))]);
+    assert_eq!(trx.coding_coord(false), Some((200, 800)));
+
+    let trx = TBuilder::new("chrT", 100, 500)
+        .strand(Forward)
+        .coords_with_ids(vec![((100, 500), "TRX:exon1")], None)
+        .coords(vec![(100, 500)], None)
+        .build()
+        .expect("a transcript");
+    assert_eq!(trx.exons()[0].id(), None);
+}
+
+#[test]
+fn tbuilder_from_interval() {
+    let build = |mode| {
+        TBuilder::from_interval("chrT", Interval::new(100..1000).unwrap())
+            .strand(Forward)
+            .coords(vec![(100, 300), (700, 900)], None)
+            .validation(mode)
+            .build()
+            .expect("a transcript")
+    };
+    let trx = build(ValidationMode::Ignore);
+    assert_eq!((trx.start(), trx.end()), (100, 1000));
+    let trx = build(ValidationMode::Fix);
+    assert_eq!((trx.start(), trx.end()), (100, 900));
+}
+
+#[test]
+fn tbuilder_exon_attributes() {
+    let attribs = |number: &str| {
+        let mut attribs = Attributes::new();
+        attribs.insert("exon_number".to_owned(), number.to_owned());
+        attribs
+    };
+    let trx = TBuilder::new("chrT", 100, 1000)
+        .strand(Reverse)
+        .exon_attributes(vec![attribs("1"), attribs("3")])
+        .coords_with_ids(vec![((700, 1000), "TRX:exon1"), ((100, 300), "TRX:exon3"),
+                              ((400, 500), "TRX:exon2")],
+                         Some((200, 800)))
+        .build()
+        .expect("a transcript");
+    assert_eq!(trx.exons().iter()
+                   .map(|ex| (ex.id(), ex.attributes().get("exon_number").map(|v| v.as_str())))
+                   .collect::<Vec<_>>(),
+               vec![(Some("TRX:exon3"), Some("3")), (Some("TRX:exon2"), None),
+                    (Some("TRX:exon1"), Some("1"))]);
+}
+
+#[test]
+fn tbuilder_truncate_codons() {
+    let build = |truncate| {
+        TBuilder::new("chrT", 0, 100)
+            .strand(Reverse)
+            .coords(vec![(0, 100)], Some((1, 60)))
+            .truncate_codons(truncate)
+            .build()
+    };
+    assert!(build(false).expect_err("an error").to_string()
+            .starts_with("coding region leaves no room for stop codon"));
+    let trx = build(true).expect("a transcript");
+    assert!(trx.cds_3_incomplete());
+    assert_eq!(trx.coding_coord(false), Some((1, 60)));
+    assert_eq!(trx.coding_coord(true), Some((1, 60)));
+    assert_eq!(trx.exons()[0].features().iter()
+                   .map(|fx| (fx.start(), fx.end(), fx.kind().clone()))
+                   .collect::<Vec<_>>(),
+               vec![(0, 1, UTR3), (1, 60, CDS { frame: Some(0) }),
+                    (57, 60, StartCodon { frame: Some(0) }), (60, 100, UTR5)]);
+}
+
+#[test]
+fn tbuilder_truncate_codons_assembly() {
+    let build = |seq_len| {
+        let mut assembly = Assembly::new();
+        assembly.add_seq("chrT", seq_len);
+        TBuilder::new("chrT", 0, 100)
+            .strand(Forward)
+            .coords(vec![(0, 100)], Some((10, 98)))
+            .truncate_codons(true)
+            .assembly(&assembly)
+            .build()
+    };
+    assert!(build(200).expect_err("an error").to_string()
+            .starts_with("coding region leaves no room for stop codon"));
+    let trx = build(100).expect("a transcript");
+    assert!(trx.cds_3_incomplete());
+    assert_eq!(trx.coding_coord(true), Some((10, 98)));
+}
+
+#[test]
+fn tbuilder_unknown_strand_coding() {
+    let build = |policy| {
+        TBuilder::new("chrT", 100, 500)
+            .strand(Unknown)
+            .coords(vec![(100, 200), (300, 500)], Some((150, 400)))
+            .unknown_strand_coding(policy)
+            .build()
+    };
+    let trx = build(UnknownStrandCoding::CdsOnly).expect("a transcript");
+    assert_eq!(trx.strand(), &Unknown);
+    assert_eq!(trx.coding_coord(false), None);
+    assert_eq!(trx.coding_coord(true), Some((150, 400)));
+
+    let trx = build(UnknownStrandCoding::Forward).expect("a transcript");
+    assert_eq!(trx.strand(), &Forward);
+    assert_eq!(trx.coding_coord(false), Some((150, 400)));
+
+    let trx = build(UnknownStrandCoding::Skip).expect("a transcript");
+    assert_eq!(trx.strand(), &Unknown);
+    assert_eq!(trx.coding_coord(true), None);
+    assert_eq!(trx.exons().len(), 2);
+
+    assert!(build(UnknownStrandCoding::Error).expect_err("an error").to_string()
+            .starts_with("coding region given for transcript with unknown strand"));
+}
+
+#[test]
+fn transcript_tags_and_support_level() {
+    let build = |tags: &[&str], tsl: Option<&str>| {
+        let mut builder = TBuilder::new("chrT", 100, 1000)
+            .coords(vec![(100, 1000)], None);
+        for tag in tags {
+            builder = builder.attribute("tag", *tag);
+        }
+        if let Some(tsl) = tsl {
+            builder = builder.attribute("transcript_support_level", tsl);
+        }
+        builder.build().expect("a transcript")
+    };
+
+    let trx = build(&["basic", "CCDS", "appris_principal_1"],
+                    Some("2 (assigned to previous version 5)"));
+    assert_eq!(trx.tags(), vec![TranscriptTag::Basic, TranscriptTag::Ccds,
+                                TranscriptTag::Other("appris_principal_1".to_owned())]);
+    assert!(trx.has_tag(&TranscriptTag::Ccds));
+    assert!(trx.has_tag(&TranscriptTag::from("appris_principal_1")));
+    assert!(!trx.has_tag(&TranscriptTag::ManeSelect));
+    assert_eq!(trx.support_level(), Some(2));
+
+    let untagged = build(&[], Some("NA"));
+    assert!(untagged.tags().is_empty());
+    assert_eq!(untagged.support_level(), None);
+    assert_eq!(build(&[], None).support_level(), None);
+    assert_eq!(build(&[], Some("6")).support_level(), None);
+}
+
+#[test]
+fn transcript_display() {
+    let trx = TBuilder::new("chrT", 100, 1000)
+        .id("trx01")
+        .strand(Reverse)
+        .biotype("lncRNA")
+        .coords(vec![(100, 300), (700, 1000)], None)
+        .build()
+        .expect("a transcript");
+    assert_eq!(format!("{}", trx), "transcript trx01 chrT:100-1000(-) lncRNA, 2 exons");
+    assert_eq!(trx.to_pretty_string(),
+               "transcript trx01 chrT:100-1000(-) lncRNA, 2 exons\n  \
+                exon <unknown> chrT:100-300(-)\n  \
+                exon <unknown> chrT:700-1000(-)");
+
+    let exon = &trx.exons()[0];
+    assert_eq!(exon.to_string(), "exon <unknown> chrT:100-300(-)");
+}
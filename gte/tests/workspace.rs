@@ -0,0 +1,51 @@
+#[macro_use]
+extern crate matches;
+extern crate gte;
+
+mod common;
+
+use gte::{AnnotationSet, Error, GenomeAnnotation, Strand, Workspace, WorkspaceError};
+
+use common::single_exon_gene;
+
+
+fn make_workspace() -> Workspace {
+    let mut workspace = Workspace::new();
+    let gene = |id, start, end| single_exon_gene("chr1", id, Strand::Forward, start, end);
+    let v1 = AnnotationSet::from_genes(vec![gene("g1", 100, 200), gene("g2", 500, 600)]);
+    let v2 = GenomeAnnotation::from_genes(vec![gene("g1", 150, 250)]);
+    assert!(workspace.insert("v1", v1).is_none());
+    assert!(workspace.insert("v2", v2).is_none());
+    workspace
+}
+
+#[test]
+fn workspace_routing() {
+    let workspace = make_workspace();
+    assert_eq!(workspace.len(), 2);
+    assert_eq!(workspace.keys(), vec!["v1", "v2"]);
+    assert!(workspace.contains_key("v2"));
+
+    let genes = workspace.find_overlapping("v1", "chr1", 0, 1000).expect("genes");
+    assert_eq!(genes.iter().map(|gene| gene.id().unwrap()).collect::<Vec<_>>(), vec!["g1", "g2"]);
+    let trxs = workspace.find_overlapping_transcripts("v2", "chr1", 240, 300).expect("trxs");
+    assert_eq!(trxs.iter().map(|trx| trx.id().unwrap()).collect::<Vec<_>>(), vec!["g1.t1"]);
+
+    let err = workspace.find_overlapping("mm10", "chr1", 0, 1000).unwrap_err();
+    assert!(matches!(err, Error::Workspace(WorkspaceError::UnknownGenome(_))));
+}
+
+#[test]
+fn workspace_find_overlapping_all() {
+    let mut workspace = make_workspace();
+    let found = workspace.find_overlapping_all("chr1", 180, 190).into_iter()
+        .map(|(key, gene)| (key.to_owned(), (gene.start(), gene.end())))
+        .collect::<Vec<_>>();
+    assert_eq!(found, vec![("v1".to_owned(), (100, 200)), ("v2".to_owned(), (150, 250))]);
+
+    let replaced = workspace.insert("v2", AnnotationSet::new()).expect("a replaced annotation");
+    assert_eq!(replaced.len(), 1);
+    assert_eq!(workspace.find_overlapping_all("chr1", 180, 190).len(), 1);
+    assert!(workspace.remove("v1").is_some());
+    assert!(workspace.find_overlapping_all("chr1", 180, 190).is_empty());
+}
@@ -70,6 +70,8 @@ fn build_cli<'a, 'b>() -> App<'a, 'b> {
                     AppSettings::VersionlessSubcommands])
         .subcommand(tools::gff_to_refflat::build_cli::<'a, 'b>())
         .subcommand(tools::stats::build_cli::<'a, 'b>())
+        .subcommand(tools::flank::build_cli::<'a, 'b>())
+        .subcommand(tools::liftover::build_cli::<'a, 'b>())
 }
 
 /// Runs the appropriate tool given the subcommand argument matches.
@@ -77,6 +79,8 @@ fn run(matches: ArgMatches) -> ::Result<()> {
     match matches.subcommand() {
         (tools::stats::NAME, Some(m)) => tools::stats::run(m),
         (tools::gff_to_refflat::NAME, Some(m)) => tools::gff_to_refflat::run(m),
+        (tools::flank::NAME, Some(m)) => tools::flank::run(m),
+        (tools::liftover::NAME, Some(m)) => tools::liftover::run(m),
         // We should not reach this point since we already require
         // that subcommands must be present in the app settings.
         _ => Err(Error::Other("unexpected command line parsing error")),
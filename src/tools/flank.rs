@@ -0,0 +1,226 @@
+use std::io::Write;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use gte::{self, expand_window, Assembly, GffReader, RefFlatReader, Strand, Transcript};
+
+use tools::TEMPLATE_SUBCMD;
+use utils;
+
+pub const NAME: &'static str = "flank";
+
+
+pub fn build_cli<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name(NAME)
+        .alias("promoters")
+        .about("Writes promoter, TSS, TES, or flanking regions of transcripts as BED")
+        .template(TEMPLATE_SUBCMD)
+        .arg(Arg::with_name("input")
+                .required(true)
+                .takes_value(true)
+                .help("Path to input annotation file or '-' for stdin"))
+        .arg(Arg::with_name("output")
+                .required(true)
+                .takes_value(true)
+                .help("Path to output BED file or '-' for stdout"))
+        .arg(Arg::with_name("input_format")
+                .short("-f")
+                .long("--input-format")
+                .required(true)
+                .takes_value(true)
+                .value_name("FORMAT")
+                .possible_values(&["refflat", "gff3", "gtf"])
+                .display_order(1)
+                .help("Input annotation format"))
+        .arg(Arg::with_name("region")
+                .short("-r")
+                .long("--region")
+                .takes_value(true)
+                .value_name("KIND")
+                .default_value("promoter")
+                .possible_values(&["promoter", "tss", "tes", "flank"])
+                .display_order(2)
+                .long_help(
+                    "Kind of region to write. 'promoter' writes the window around the \
+                     transcription start site, 'tss' and 'tes' write the single transcription \
+                     start or end base, and 'flank' writes the windows upstream and downstream \
+                     of the transcript body."))
+        .arg(Arg::with_name("upstream")
+                .short("-u")
+                .long("--upstream")
+                .takes_value(true)
+                .value_name("N")
+                .default_value("1000")
+                .display_order(3)
+                .help("Number of bases upstream of the anchor to include"))
+        .arg(Arg::with_name("downstream")
+                .short("-d")
+                .long("--downstream")
+                .takes_value(true)
+                .value_name("N")
+                .default_value("100")
+                .display_order(4)
+                .help("Number of bases downstream of the anchor to include"))
+        .arg(Arg::with_name("ignore_strand")
+                .long("--ignore-strand")
+                .takes_value(false)
+                .display_order(5)
+                .help("Treat all transcripts as if they are on the forward strand"))
+        .arg(Arg::with_name("biotype")
+                .long("--biotype")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .value_name("VALUE")
+                .display_order(6)
+                .help("Only write transcripts with the given biotype; may be repeated"))
+        .arg(Arg::with_name("biotype_attr")
+                .long("--biotype-attr")
+                .takes_value(true)
+                .value_name("KEY")
+                .display_order(7)
+                .help("Key of transcript attribute to use as the biotype, instead of the \
+                       biotype read from the input"))
+        .arg(Arg::with_name("chrom_sizes")
+                .long("--chrom-sizes")
+                .takes_value(true)
+                .value_name("FILE")
+                .display_order(8)
+                .help("Path to a chrom.sizes file used to clip regions to the sequence ends"))
+}
+
+/// Kinds of regions that can be written.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RegionKind {
+    Promoter,
+    Tss,
+    Tes,
+    Flank,
+}
+
+/// Options for computing the regions of each transcript.
+struct RegionOpts<'a> {
+    kind: RegionKind,
+    upstream: u64,
+    downstream: u64,
+    ignore_strand: bool,
+    biotypes: Option<Vec<&'a str>>,
+    biotype_attr: Option<&'a str>,
+    assembly: Option<Assembly>,
+}
+
+impl<'a> RegionOpts<'a> {
+
+    /// Returns whether the given transcript passes the biotype filter.
+    fn accepts(&self, transcript: &Transcript) -> bool {
+        match self.biotypes {
+            None => true,
+            Some(ref biotypes) => {
+                let biotype = match self.biotype_attr {
+                    Some(key) => transcript.attributes().get(key).map(|value| value.as_str()),
+                    None => transcript.biotype().map(|biotype| biotype.as_str()),
+                };
+                biotype.map(|value| biotypes.contains(&value)).unwrap_or(false)
+            },
+        }
+    }
+
+    /// Returns the regions of the given transcript.
+    ///
+    /// Upstream and downstream are relative to the transcript strand. Transcripts on unknown
+    /// strands are treated as if they are on the forward strand. Regions are clipped to the
+    /// sequence ends when an assembly is given, and empty regions are dropped.
+    fn regions(&self, transcript: &Transcript) -> Vec<(u64, u64)> {
+        let (start, end) = (transcript.start(), transcript.end());
+        let strand =
+            if self.ignore_strand || transcript.strand() != &Strand::Reverse {
+                Strand::Forward
+            } else {
+                Strand::Reverse
+            };
+        let reverse = strand == Strand::Reverse;
+        let (up, down) = (self.upstream, self.downstream);
+        // anchors of each region, along with their upstream and downstream window sizes
+        let anchors = match (self.kind, reverse) {
+            (RegionKind::Promoter, false) => vec![((start, start), up, down)],
+            (RegionKind::Promoter, true) => vec![((end, end), up, down)],
+            (RegionKind::Tss, false) | (RegionKind::Tes, true) => vec![((start, start + 1), 0, 0)],
+            (RegionKind::Tss, true) | (RegionKind::Tes, false) => vec![((end - 1, end), 0, 0)],
+            (RegionKind::Flank, false) => vec![((start, start), up, 0), ((end, end), 0, down)],
+            (RegionKind::Flank, true) => vec![((start, start), 0, down), ((end, end), up, 0)],
+        };
+        anchors.into_iter()
+            .map(|(anchor, up, down)| {
+                expand_window(transcript.seq_name(), anchor, up, down, &strand,
+                              self.assembly.as_ref())
+            })
+            .filter(|&(a, b)| a < b)
+            .collect()
+    }
+}
+
+pub fn run(args: &ArgMatches) -> ::Result<()> {
+
+    let opts = RegionOpts {
+        kind: match args.value_of("region").unwrap() {
+            "tss" => RegionKind::Tss,
+            "tes" => RegionKind::Tes,
+            "flank" => RegionKind::Flank,
+            _ => RegionKind::Promoter,
+        },
+        upstream: value_t!(args, "upstream", u64)
+            .map_err(|_| ::Error::Other("invalid upstream window size"))?,
+        downstream: value_t!(args, "downstream", u64)
+            .map_err(|_| ::Error::Other("invalid downstream window size"))?,
+        ignore_strand: args.is_present("ignore_strand"),
+        biotypes: args.values_of("biotype").map(|vs| vs.collect()),
+        biotype_attr: args.value_of("biotype_attr"),
+        assembly: match args.value_of("chrom_sizes") {
+            Some(path) => Some(Assembly::from_file(path)?),
+            None => None,
+        },
+    };
+
+    let reader = utils::resolve_reader(args.value_of("input").unwrap())?;
+    let mut writer = utils::resolve_writer(args.value_of("output").unwrap())?;
+
+    match args.value_of("input_format").unwrap() {
+        "refflat" => {
+            let mut rreader = RefFlatReader::from_reader(reader);
+            write_regions(rreader.transcripts_stream(), &mut writer, &opts)
+        },
+        fmt => {
+            let gff_type = utils::resolve_gff_type(fmt)?;
+            let mut greader = GffReader::from_reader(reader, gff_type);
+            write_regions(greader.transcripts()?, &mut writer, &opts)
+        },
+    }
+}
+
+/// Writes the regions of all transcripts as BED6 rows.
+fn write_regions<I, W>(transcripts: I, writer: &mut W, opts: &RegionOpts) -> ::Result<()>
+    where I: Iterator<Item=gte::Result<Transcript>>, W: Write
+{
+    for result in transcripts {
+        let trx = match result {
+            Ok(trx) => trx,
+            Err(gte::Error::Gff(gffe)) => {
+                eprintln!("skipping: {}", gffe);
+                continue;
+            },
+            Err(e) => return Err(::Error::from(e)),
+        };
+        if !opts.accepts(&trx) {
+            continue;
+        }
+        let strand_char = match trx.strand() {
+            &Strand::Forward => '+',
+            &Strand::Reverse => '-',
+            &Strand::Unknown => '.',
+        };
+        for (start, end) in opts.regions(&trx) {
+            writeln!(writer, "{}\t{}\t{}\t{}\t0\t{}",
+                     trx.seq_name(), start, end, trx.id().unwrap_or("."), strand_char)?;
+        }
+    }
+    Ok(())
+}
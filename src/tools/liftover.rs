@@ -0,0 +1,233 @@
+use std::cmp::{max, min};
+use std::collections::HashMap;
+use std::io::Write;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use gte::{self, ChainMap, GBuilder, Gene, Gff3Writer, GffReader, GtfReader, GtfWriter,
+          RefFlatReader, RefFlatWriter, StrandExt, Transcript, Unmapped};
+
+use tools::TEMPLATE_SUBCMD;
+use utils;
+
+pub const NAME: &'static str = "liftover";
+
+
+pub fn build_cli<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name(NAME)
+        .about("Remaps genes to another assembly using a chain file")
+        .template(TEMPLATE_SUBCMD)
+        .arg(Arg::with_name("input")
+                .required(true)
+                .takes_value(true)
+                .help("Path to input annotation file or '-' for stdin"))
+        .arg(Arg::with_name("output")
+                .required(true)
+                .takes_value(true)
+                .help("Path to output annotation file or '-' for stdout"))
+        .arg(Arg::with_name("chain")
+                .short("-c")
+                .long("--chain")
+                .required(true)
+                .takes_value(true)
+                .value_name("PATH")
+                .display_order(1)
+                .help("Path to chain file from the input to the output assembly"))
+        .arg(Arg::with_name("input_format")
+                .short("-f")
+                .long("--input-format")
+                .required(true)
+                .takes_value(true)
+                .value_name("FORMAT")
+                .possible_values(&["refflat", "gff3", "gtf"])
+                .display_order(2)
+                .help("Input annotation format"))
+        .arg(Arg::with_name("output_format")
+                .short("-F")
+                .long("--output-format")
+                .takes_value(true)
+                .value_name("FORMAT")
+                .possible_values(&["refflat", "gff3", "gtf"])
+                .display_order(3)
+                .help("Output annotation format, the input format if not specified"))
+        .arg(Arg::with_name("unmapped")
+                .short("-u")
+                .long("--unmapped")
+                .takes_value(true)
+                .value_name("PATH")
+                .display_order(4)
+                .long_help(
+                    "Path to output file of genes and transcripts that can not be remapped. \
+                     Each line contains the feature type ('gene' or 'transcript'), its \
+                     identifier, sequence name, start and end coordinates, and the reason, \
+                     separated by tabs. Genes whose transcripts map to different places are \
+                     reported as split, with the transcripts at the place shared by most of \
+                     them written to the output. If not specified, unmapped features are \
+                     reported to stderr."))
+}
+
+pub fn run(args: &ArgMatches) -> ::Result<()> {
+
+    let chains = ChainMap::from_file(args.value_of("chain").unwrap())?;
+
+    let input_format = args.value_of("input_format").unwrap();
+    let reader = utils::resolve_reader(args.value_of("input").unwrap())?;
+    let writer = utils::resolve_writer(args.value_of("output").unwrap())?;
+    let mut writer = match args.value_of("output_format").unwrap_or(input_format) {
+        "refflat" => GeneWriter::RefFlat(RefFlatWriter::from_writer(writer)),
+        "gtf" => GeneWriter::Gtf(GtfWriter::from_writer(writer)),
+        _ => GeneWriter::Gff3(Gff3Writer::from_writer(writer)),
+    };
+    let mut unmapped_writer = match args.value_of("unmapped") {
+        Some(path) => Some(utils::resolve_writer(path)?),
+        None => None,
+    };
+
+    match input_format {
+        "refflat" => {
+            let mut rreader = RefFlatReader::from_reader(reader);
+            lift_genes(rreader.unsorted_genes_stream(), &chains,
+                       &mut writer, &mut unmapped_writer)
+        },
+        "gtf" => {
+            let mut greader = GtfReader::from_reader(reader);
+            lift_genes(greader.genes_stream(), &chains,
+                       &mut writer, &mut unmapped_writer)
+        },
+        fmt => {
+            let gff_type = utils::resolve_gff_type(fmt)?;
+            let mut greader = GffReader::from_reader(reader, gff_type);
+            let genes = group_transcripts(greader.transcripts()?);
+            lift_genes(genes.into_iter(), &chains,
+                       &mut writer, &mut unmapped_writer)
+        },
+    }
+}
+
+/// Writer of genes in any of the supported output formats.
+enum GeneWriter<W: Write> {
+    RefFlat(RefFlatWriter<W>),
+    Gtf(GtfWriter<W>),
+    Gff3(Gff3Writer<W>),
+}
+
+impl<W: Write> GeneWriter<W> {
+
+    fn write_gene(&mut self, gene: &Gene) -> gte::Result<()> {
+        match *self {
+            GeneWriter::RefFlat(ref mut w) => w.write_gene(gene),
+            GeneWriter::Gtf(ref mut w) => w.write_gene(gene),
+            GeneWriter::Gff3(ref mut w) => w.write_gene(gene),
+        }
+    }
+}
+
+/// Groups transcripts into genes by their gene identifiers, sequence names, and strands.
+///
+/// Genes are returned in the order their first transcript appears. Errors of transcripts
+/// that can not be read are returned before all genes.
+fn group_transcripts<I>(transcripts: I) -> Vec<gte::Result<Gene>>
+    where I: Iterator<Item=gte::Result<Transcript>>
+{
+    let mut results = Vec::new();
+    let mut groups: Vec<Vec<Transcript>> = Vec::new();
+    let mut indices: HashMap<(String, String, char), usize> = HashMap::new();
+    for result in transcripts {
+        let trx = match result {
+            Ok(trx) => trx,
+            Err(e) => {
+                results.push(Err(e));
+                continue;
+            },
+        };
+        let key = (trx.gene_id_or_default().to_owned(), trx.seq_name().to_owned(),
+                   trx.strand().to_char());
+        let idx = *indices.entry(key).or_insert(groups.len());
+        if idx == groups.len() {
+            groups.push(Vec::new());
+        }
+        groups[idx].push(trx);
+    }
+    for group in groups {
+        let (start, end) = group.iter()
+            .fold((u64::max_value(), 0),
+                  |acc, trx| (min(acc.0, trx.start()), max(acc.1, trx.end())));
+        let (seq_name, gid, strand) = (group[0].seq_name().to_owned(),
+                                       group[0].gene_id_or_default().to_owned(),
+                                       *group[0].strand());
+        let gene = GBuilder::new(seq_name, start, end)
+            .id(gid)
+            .strand(strand)
+            .add_transcripts(group)
+            .build();
+        results.push(gene);
+    }
+    results
+}
+
+/// Remaps all genes and writes them along with any unmapped genes and transcripts.
+fn lift_genes<I, W, U>(
+    genes: I,
+    chains: &ChainMap,
+    writer: &mut GeneWriter<W>,
+    unmapped_writer: &mut Option<U>,
+) -> ::Result<()>
+    where I: Iterator<Item=gte::Result<Gene>>, W: Write, U: Write
+{
+    for result in genes {
+        let gene = match result {
+            Ok(gene) => gene,
+            Err(gte::Error::Gff(gffe)) => {
+                eprintln!("skipping: {}", gffe);
+                continue;
+            },
+            Err(gte::Error::Gtf(gtfe)) => {
+                eprintln!("skipping: {}", gtfe);
+                continue;
+            },
+            Err(e) => return Err(::Error::from(e)),
+        };
+        let lifted = chains.lift_gene(&gene);
+        for &(ref tid, ref reason) in lifted.unmapped_transcripts() {
+            if let Some(trx) = gene.transcripts().get(tid) {
+                report_unmapped(unmapped_writer, "transcript", tid,
+                                trx.seq_name(), trx.start(), trx.end(), reason)?;
+            }
+        }
+        let gid = gene.id().unwrap_or(".");
+        match lifted.gene() {
+            Ok(lgene) => {
+                let is_split = lifted.unmapped_transcripts().iter()
+                    .any(|&(_, ref reason)| match *reason { Unmapped::Split => true, _ => false });
+                if is_split {
+                    report_unmapped(unmapped_writer, "gene", gid, gene.seq_name(),
+                                    gene.start(), gene.end(), &Unmapped::Split)?;
+                }
+                writer.write_gene(lgene)?;
+            },
+            Err(reason) =>
+                report_unmapped(unmapped_writer, "gene", gid, gene.seq_name(),
+                                gene.start(), gene.end(), reason)?,
+        }
+    }
+    Ok(())
+}
+
+/// Reports a feature that can not be remapped to the given writer, or to stderr if there is
+/// none.
+fn report_unmapped<U: Write>(
+    unmapped_writer: &mut Option<U>,
+    kind: &str,
+    id: &str,
+    seq_name: &str,
+    start: u64,
+    end: u64,
+    reason: &Unmapped,
+) -> ::Result<()>
+{
+    match unmapped_writer.as_mut() {
+        Some(uw) => writeln!(uw, "{}\t{}\t{}\t{}\t{}\t{}",
+                             kind, id, seq_name, start, end, reason)?,
+        None => eprintln!("unmapped: {}, {} ID: {}", reason, kind, id),
+    }
+    Ok(())
+}
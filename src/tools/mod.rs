@@ -2,6 +2,8 @@
 
 pub mod stats;
 pub mod gff_to_refflat;
+pub mod flank;
+pub mod liftover;
 
 const TEMPLATE_SUBCMD: &'static str = "
 Usage: {usage}